@@ -0,0 +1,170 @@
+use std::f64::consts::PI;
+
+use crate::math::fft::rfft;
+use crate::math::matrix::Matrix;
+use crate::math::scalar::Float;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Window {
+  Hann,
+  Hamming,
+  Rectangular
+}
+
+impl Window {
+  pub fn coefficients(self, len: usize) -> Vec<f64> {
+    let phase = |i: usize| 2.0 * PI * i as f64 / len as f64;
+    (0..len)
+      .map(|i| match self {
+        Window::Hann => 0.5 - 0.5 * phase(i).cos(),
+        Window::Hamming => 0.54 - 0.46 * phase(i).cos(),
+        Window::Rectangular => 1.0
+      })
+      .collect()
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpectrogramOptions {
+  pub n_fft: usize,
+  pub hop_length: usize,
+  pub win_length: Option<usize>,
+  pub window: Window,
+  pub center: bool,
+  pub power: f64
+}
+
+impl SpectrogramOptions {
+  pub fn new() -> Self {
+    SpectrogramOptions { n_fft: 400, hop_length: 160, win_length: None, window: Window::Hann, center: true, power: 2.0 }
+  }
+
+  pub fn n_bins(&self) -> usize {
+    self.n_fft / 2 + 1
+  }
+}
+
+impl Default for SpectrogramOptions {
+  fn default() -> Self {
+    SpectrogramOptions::new()
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MelOptions {
+  pub spectrogram: SpectrogramOptions,
+  pub n_mels: usize,
+  pub f_min: f64,
+  pub f_max: Option<f64>,
+  pub log_offset: Option<f64>
+}
+
+impl MelOptions {
+  pub fn new() -> Self {
+    MelOptions { spectrogram: SpectrogramOptions::new(), n_mels: 40, f_min: 0.0, f_max: None, log_offset: Some(1e-6) }
+  }
+}
+
+impl Default for MelOptions {
+  fn default() -> Self {
+    MelOptions::new()
+  }
+}
+
+fn reflect(samples: &[f64], index: isize) -> f64 {
+  let n = samples.len() as isize;
+  if n == 1 {
+    return samples[0];
+  }
+  let period = 2 * (n - 1);
+  let mut i = index.rem_euclid(period);
+  if i >= n {
+    i = period - i;
+  }
+  samples[i as usize]
+}
+
+pub fn spectrogram<T: Float>(samples: &[f64], options: &SpectrogramOptions) -> Result<Matrix<T>, String> {
+  let win_length = options.win_length.unwrap_or(options.n_fft);
+  if options.n_fft == 0 || options.hop_length == 0 || win_length == 0 || win_length > options.n_fft {
+    return Err(format!("spectrogram: invalid n_fft {}, hop_length {} or win_length {}", options.n_fft, options.hop_length, win_length));
+  }
+  if samples.is_empty() {
+    return Err("spectrogram: no samples".to_string());
+  }
+
+  let pad = if options.center { (options.n_fft / 2) as isize } else { 0 };
+  let total = samples.len() + 2 * pad as usize;
+  if total < options.n_fft {
+    return Err(format!("spectrogram: {} samples are fewer than n_fft {}", samples.len(), options.n_fft));
+  }
+  let frames = 1 + (total - options.n_fft) / options.hop_length;
+
+  let mut window = vec![0.0; options.n_fft];
+  let left = (options.n_fft - win_length) / 2;
+  window[left..left + win_length].copy_from_slice(&options.window.coefficients(win_length));
+
+  let bins = options.n_bins();
+  let mut data = Vec::with_capacity(frames * bins);
+  let mut frame = vec![0.0; options.n_fft];
+  for f in 0..frames {
+    let start = (f * options.hop_length) as isize - pad;
+    for (i, value) in frame.iter_mut().enumerate() {
+      *value = reflect(samples, start + i as isize) * window[i];
+    }
+    data.extend(rfft(&frame).into_iter().map(|bin| T::from_f64(bin.abs().powf(options.power))));
+  }
+  Matrix::from_vec(frames, bins, data)
+}
+
+pub fn hz_to_mel(hz: f64) -> f64 {
+  2595.0 * (1.0 + hz / 700.0).log10()
+}
+
+pub fn mel_to_hz(mel: f64) -> f64 {
+  700.0 * (10f64.powf(mel / 2595.0) - 1.0)
+}
+
+pub fn mel_filterbank<T: Float>(n_mels: usize, n_fft: usize, sample_rate: u32, f_min: f64, f_max: f64) -> Result<Matrix<T>, String> {
+  if n_mels == 0 || n_fft == 0 || f_min < 0.0 || f_max <= f_min || f_max > sample_rate as f64 / 2.0 {
+    return Err(format!("mel_filterbank: invalid n_mels {} or frequency range [{}, {}] for sample rate {}", n_mels, f_min, f_max, sample_rate));
+  }
+  let bins = n_fft / 2 + 1;
+  let (low, high) = (hz_to_mel(f_min), hz_to_mel(f_max));
+  let edges: Vec<f64> = (0..n_mels + 2).map(|i| mel_to_hz(low + (high - low) * i as f64 / (n_mels + 1) as f64)).collect();
+  let frequency = |bin: usize| bin as f64 * sample_rate as f64 / n_fft as f64;
+  Ok(Matrix::from_fn(n_mels, bins, |mel, bin| {
+    let (left, center, right) = (edges[mel], edges[mel + 1], edges[mel + 2]);
+    let hz = frequency(bin);
+    let weight = ((hz - left) / (center - left)).min((right - hz) / (right - center)).max(0.0);
+    T::from_f64(weight)
+  }))
+}
+
+pub fn mel_spectrogram<T: Float>(samples: &[f64], sample_rate: u32, options: &MelOptions) -> Result<Matrix<T>, String> {
+  let power: Matrix<f64> = spectrogram(samples, &options.spectrogram)?;
+  let f_max = options.f_max.unwrap_or(sample_rate as f64 / 2.0);
+  let filters: Matrix<f64> = mel_filterbank(options.n_mels, options.spectrogram.n_fft, sample_rate, options.f_min, f_max)?;
+  Ok(Matrix::from_fn(power.rows, options.n_mels, |frame, mel| {
+    let energy: f64 = (0..power.cols).map(|bin| power.data[frame * power.cols + bin] * filters.data[mel * filters.cols + bin]).sum();
+    T::from_f64(match options.log_offset {
+      Some(offset) => (energy + offset).ln(),
+      None => energy
+    })
+  }))
+}
+
+pub fn mfcc<T: Float>(samples: &[f64], sample_rate: u32, n_mfcc: usize, options: &MelOptions) -> Result<Matrix<T>, String> {
+  if n_mfcc == 0 || n_mfcc > options.n_mels {
+    return Err(format!("mfcc: n_mfcc must be in 1..={}, got {}", options.n_mels, n_mfcc));
+  }
+  let mut options = *options;
+  options.log_offset = Some(options.log_offset.unwrap_or(1e-6));
+  let log_mel: Matrix<f64> = mel_spectrogram(samples, sample_rate, &options)?;
+  let n = options.n_mels as f64;
+  Ok(Matrix::from_fn(log_mel.rows, n_mfcc, |frame, k| {
+    let scale = if k == 0 { (1.0 / n).sqrt() } else { (2.0 / n).sqrt() };
+    let sum: f64 = (0..options.n_mels).map(|m| log_mel.data[frame * log_mel.cols + m] * (PI * k as f64 * (m as f64 + 0.5) / n).cos()).sum();
+    T::from_f64(scale * sum)
+  }))
+}
@@ -0,0 +1,5 @@
+pub mod features;
+pub mod wav;
+
+pub use features::{mel_filterbank, mel_spectrogram, mfcc, spectrogram, MelOptions, SpectrogramOptions, Window};
+pub use wav::{load_wav, read_wav, save_wav, write_wav, AudioBuffer};
@@ -0,0 +1,141 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+const FORMAT_PCM: u16 = 1;
+const FORMAT_FLOAT: u16 = 3;
+const FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AudioBuffer {
+  pub sample_rate: u32,
+  pub channels: Vec<Vec<f64>>
+}
+
+impl AudioBuffer {
+  pub fn new(sample_rate: u32, channels: Vec<Vec<f64>>) -> Result<Self, String> {
+    if sample_rate == 0 {
+      return Err("AudioBuffer: sample_rate must be positive".to_string());
+    }
+    if channels.is_empty() || channels.iter().any(|channel| channel.len() != channels[0].len()) {
+      return Err("AudioBuffer: expected at least one channel, all of equal length".to_string());
+    }
+    Ok(AudioBuffer { sample_rate, channels })
+  }
+
+  pub fn num_channels(&self) -> usize {
+    self.channels.len()
+  }
+
+  pub fn num_frames(&self) -> usize {
+    self.channels[0].len()
+  }
+
+  pub fn duration(&self) -> f64 {
+    self.num_frames() as f64 / self.sample_rate as f64
+  }
+
+  pub fn to_mono(&self) -> Vec<f64> {
+    let scale = 1.0 / self.num_channels() as f64;
+    (0..self.num_frames()).map(|i| self.channels.iter().map(|channel| channel[i]).sum::<f64>() * scale).collect()
+  }
+}
+
+fn u16_at(bytes: &[u8], offset: usize) -> u16 {
+  u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+  u32::from_le_bytes([bytes[offset], bytes[offset + 1], bytes[offset + 2], bytes[offset + 3]])
+}
+
+fn decode_sample(bytes: &[u8], format: u16, bits: u16) -> Result<f64, String> {
+  Ok(match (format, bits) {
+    (FORMAT_PCM, 8) => (bytes[0] as f64 - 128.0) / 128.0,
+    (FORMAT_PCM, 16) => i16::from_le_bytes([bytes[0], bytes[1]]) as f64 / 32768.0,
+    (FORMAT_PCM, 24) => (i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]]) >> 8) as f64 / 8_388_608.0,
+    (FORMAT_PCM, 32) => i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64 / 2_147_483_648.0,
+    (FORMAT_FLOAT, 32) => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+    (FORMAT_FLOAT, 64) => f64::from_le_bytes(bytes[..8].try_into().map_err(|_| "WAV: truncated sample".to_string())?),
+    _ => return Err(format!("WAV: unsupported format {} with {} bits per sample", format, bits))
+  })
+}
+
+pub fn read_wav<R: Read>(mut reader: R) -> Result<AudioBuffer, String> {
+  let mut bytes = Vec::new();
+  reader.read_to_end(&mut bytes).map_err(|e| e.to_string())?;
+  if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+    return Err("WAV: missing RIFF/WAVE header".to_string());
+  }
+
+  let mut format = None;
+  let mut data = None;
+  let mut offset = 12;
+  while offset + 8 <= bytes.len() {
+    let id = &bytes[offset..offset + 4];
+    let size = u32_at(&bytes, offset + 4) as usize;
+    let body = offset + 8;
+    let end = (body + size).min(bytes.len());
+    match id {
+      b"fmt " if size >= 16 && end - body >= 16 => {
+        let mut tag = u16_at(&bytes, body);
+        if tag == FORMAT_EXTENSIBLE && size >= 40 && end - body >= 26 {
+          tag = u16_at(&bytes, body + 24);
+        }
+        format = Some((tag, u16_at(&bytes, body + 2), u32_at(&bytes, body + 4), u16_at(&bytes, body + 14)));
+      }
+      b"data" => data = Some(&bytes[body..end]),
+      _ => {}
+    }
+    offset = body + size + size % 2;
+  }
+
+  let (tag, channels, sample_rate, bits) = format.ok_or_else(|| "WAV: missing fmt chunk".to_string())?;
+  let data = data.ok_or_else(|| "WAV: missing data chunk".to_string())?;
+  if channels == 0 || bits == 0 || bits % 8 != 0 {
+    return Err(format!("WAV: invalid format with {} channels and {} bits per sample", channels, bits));
+  }
+  let width = bits as usize / 8;
+  let frame = width * channels as usize;
+  let mut decoded = vec![Vec::with_capacity(data.len() / frame); channels as usize];
+  for chunk in data.chunks_exact(frame) {
+    for (channel, sample) in decoded.iter_mut().zip(chunk.chunks_exact(width)) {
+      channel.push(decode_sample(sample, tag, bits)?);
+    }
+  }
+  AudioBuffer::new(sample_rate, decoded)
+}
+
+pub fn load_wav<P: AsRef<Path>>(path: P) -> Result<AudioBuffer, String> {
+  let file = File::open(path.as_ref()).map_err(|e| format!("Cannot read {}: {}", path.as_ref().display(), e))?;
+  read_wav(BufReader::new(file))
+}
+
+pub fn write_wav<W: Write>(mut writer: W, audio: &AudioBuffer) -> Result<(), String> {
+  let channels = audio.num_channels() as u16;
+  let data_len = (audio.num_frames() * audio.num_channels() * 2) as u32;
+  let mut bytes = Vec::with_capacity(44 + data_len as usize);
+  bytes.extend(b"RIFF");
+  bytes.extend((36 + data_len).to_le_bytes());
+  bytes.extend(b"WAVEfmt ");
+  bytes.extend(16u32.to_le_bytes());
+  bytes.extend(FORMAT_PCM.to_le_bytes());
+  bytes.extend(channels.to_le_bytes());
+  bytes.extend(audio.sample_rate.to_le_bytes());
+  bytes.extend((audio.sample_rate * channels as u32 * 2).to_le_bytes());
+  bytes.extend((channels * 2).to_le_bytes());
+  bytes.extend(16u16.to_le_bytes());
+  bytes.extend(b"data");
+  bytes.extend(data_len.to_le_bytes());
+  for i in 0..audio.num_frames() {
+    for channel in &audio.channels {
+      bytes.extend(((channel[i].clamp(-1.0, 1.0) * 32767.0).round() as i16).to_le_bytes());
+    }
+  }
+  writer.write_all(&bytes).map_err(|e| e.to_string())
+}
+
+pub fn save_wav<P: AsRef<Path>>(path: P, audio: &AudioBuffer) -> Result<(), String> {
+  let file = File::create(path.as_ref()).map_err(|e| format!("Cannot write {}: {}", path.as_ref().display(), e))?;
+  write_wav(BufWriter::new(file), audio)
+}
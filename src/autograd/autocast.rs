@@ -0,0 +1,57 @@
+use std::cell::Cell;
+
+use super::Var;
+use crate::math::half::{bf16, f16, HalfFloat};
+use crate::math::scalar::Float;
+use crate::tensor::Tensor;
+
+thread_local! {
+  static AUTOCAST: Cell<Option<HalfPrecision>> = const { Cell::new(None) };
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HalfPrecision {
+  F16,
+  BF16
+}
+
+fn round_through<T: Float, H: HalfFloat>(tensor: &Tensor<T>) -> Tensor<T> {
+  tensor.map(|&x| T::from_f64(H::from_f32(x.to_f64() as f32).to_f32() as f64))
+}
+
+impl HalfPrecision {
+  pub fn round<T: Float>(self, tensor: &Tensor<T>) -> Tensor<T> {
+    match self {
+      HalfPrecision::F16 => round_through::<T, f16>(tensor),
+      HalfPrecision::BF16 => round_through::<T, bf16>(tensor)
+    }
+  }
+}
+
+struct AutocastGuard {
+  previous: Option<HalfPrecision>
+}
+
+impl Drop for AutocastGuard {
+  fn drop(&mut self) {
+    AUTOCAST.with(|precision| precision.set(self.previous));
+  }
+}
+
+pub fn autocast_precision() -> Option<HalfPrecision> {
+  AUTOCAST.with(|precision| precision.get())
+}
+
+pub fn autocast<R, F>(precision: Option<HalfPrecision>, f: F) -> R
+where
+  F: FnOnce() -> R
+{
+  let _guard = AutocastGuard { previous: AUTOCAST.with(|p| p.replace(precision)) };
+  f()
+}
+
+impl<T: Float> Var<T> {
+  pub fn round_to(&self, precision: HalfPrecision) -> Var<T> {
+    Var::from_op(precision.round(&self.value()), "round_to", vec![self.clone()], move |g| Ok(vec![g.round_to(precision)]))
+  }
+}
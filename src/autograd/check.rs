@@ -0,0 +1,80 @@
+use std::fmt;
+
+use super::{with_grad_mode, Var};
+use crate::math::scalar::Float;
+use crate::tensor::{IndexIter, Tensor};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GradCheckReport<T = f64> {
+  pub input: usize,
+  pub index: Vec<usize>,
+  pub analytic: T,
+  pub numeric: T,
+  pub error: T,
+  pub passed: bool
+}
+
+impl<T: Float + fmt::Display> fmt::Display for GradCheckReport<T> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "grad_check {}: worst error {} at input {} index {:?} (analytic {}, numeric {})",
+      if self.passed { "passed" } else { "failed" },
+      self.error,
+      self.input,
+      self.index,
+      self.analytic,
+      self.numeric
+    )
+  }
+}
+
+fn evaluate<T, F>(f: &F, inputs: &[Tensor<T>]) -> Result<T, String>
+where
+  T: Float,
+  F: Fn(&[Var<T>]) -> Result<Var<T>, String>
+{
+  with_grad_mode(false, || {
+    let vars: Vec<Var<T>> = inputs.iter().cloned().map(Var::constant).collect();
+    Ok(f(&vars)?.value().sum_all())
+  })
+}
+
+pub fn grad_check<T, F>(f: F, inputs: &[Tensor<T>], eps: T, tol: T) -> Result<GradCheckReport<T>, String>
+where
+  T: Float,
+  F: Fn(&[Var<T>]) -> Result<Var<T>, String>
+{
+  let vars: Vec<Var<T>> = inputs.iter().cloned().map(Var::param).collect();
+  let output = f(&vars)?;
+  if output.requires_grad() {
+    output.sum(&[], false)?.backward()?;
+  }
+
+  let mut worst: Option<GradCheckReport<T>> = None;
+  for (input, var) in vars.iter().enumerate() {
+    let analytic = var.grad().unwrap_or_else(|| Tensor::zeros(&var.shape()));
+
+    for index in IndexIter::new(inputs[input].shape()) {
+      let mut perturbed = inputs.to_vec();
+      let original = *inputs[input].get(&index).expect("index within input shape");
+
+      *perturbed[input].get_mut(&index).expect("index within input shape") = original + eps;
+      let plus = evaluate(&f, &perturbed)?;
+      *perturbed[input].get_mut(&index).expect("index within input shape") = original - eps;
+      let minus = evaluate(&f, &perturbed)?;
+
+      let numeric = (plus - minus) / (eps + eps);
+      let analytic = *analytic.get(&index).expect("gradient matches input shape");
+      let error = (analytic - numeric).abs() / T::one().max(analytic.abs()).max(numeric.abs());
+
+      if worst.as_ref().is_none_or(|w| error.is_nan() || error > w.error) {
+        worst = Some(GradCheckReport { input, index, analytic, numeric, error, passed: false });
+      }
+    }
+  }
+
+  let mut report = worst.ok_or_else(|| "grad_check: inputs contain no elements".to_string())?;
+  report.passed = report.error <= tol;
+  Ok(report)
+}
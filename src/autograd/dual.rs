@@ -0,0 +1,270 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use crate::math::matrix::Matrix;
+use crate::math::scalar::{Float, RealScalar, Scalar};
+use crate::math::vector::Vector;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Dual<T = f64> {
+  pub value: T,
+  pub deriv: T
+}
+
+impl<T: Float> Dual<T> {
+  pub fn new(value: T, deriv: T) -> Self {
+    Dual {
+      value,
+      deriv
+    }
+  }
+
+  pub fn constant(value: T) -> Self {
+    Dual::new(value, T::zero())
+  }
+
+  pub fn variable(value: T) -> Self {
+    Dual::new(value, T::one())
+  }
+
+  fn chain(self, value: T, slope: T) -> Self {
+    Dual::new(value, self.deriv * slope)
+  }
+}
+
+impl<T: Float> PartialEq for Dual<T> {
+  fn eq(&self, other: &Self) -> bool {
+    self.value == other.value
+  }
+}
+
+impl<T: Float> PartialOrd for Dual<T> {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    self.value.partial_cmp(&other.value)
+  }
+}
+
+impl<T: Float> Add for Dual<T> {
+  type Output = Self;
+
+  fn add(self, rhs: Self) -> Self::Output {
+    Dual::new(self.value + rhs.value, self.deriv + rhs.deriv)
+  }
+}
+
+impl<T: Float> Sub for Dual<T> {
+  type Output = Self;
+
+  fn sub(self, rhs: Self) -> Self::Output {
+    Dual::new(self.value - rhs.value, self.deriv - rhs.deriv)
+  }
+}
+
+impl<T: Float> Mul for Dual<T> {
+  type Output = Self;
+
+  fn mul(self, rhs: Self) -> Self::Output {
+    Dual::new(self.value * rhs.value, self.deriv * rhs.value + self.value * rhs.deriv)
+  }
+}
+
+impl<T: Float> Div for Dual<T> {
+  type Output = Self;
+
+  fn div(self, rhs: Self) -> Self::Output {
+    Dual::new(
+      self.value / rhs.value,
+      (self.deriv * rhs.value - self.value * rhs.deriv) / (rhs.value * rhs.value)
+    )
+  }
+}
+
+impl<T: Float> Neg for Dual<T> {
+  type Output = Self;
+
+  fn neg(self) -> Self::Output {
+    Dual::new(-self.value, -self.deriv)
+  }
+}
+
+macro_rules! impl_dual_assign {
+  ($($trait:ident, $method:ident, $op:tt);*) => {
+    $(impl<T: Float> $trait for Dual<T> {
+      fn $method(&mut self, rhs: Self) {
+        *self = *self $op rhs;
+      }
+    })*
+  };
+}
+
+impl_dual_assign!(AddAssign, add_assign, +; SubAssign, sub_assign, -; MulAssign, mul_assign, *; DivAssign, div_assign, /);
+
+impl<T: Float> Scalar for Dual<T> {
+  fn zero() -> Self {
+    Dual::constant(T::zero())
+  }
+
+  fn one() -> Self {
+    Dual::constant(T::one())
+  }
+
+  fn checked_neg(self) -> Option<Self> {
+    Some(-self)
+  }
+}
+
+impl<T: Float> RealScalar for Dual<T> {
+  fn abs(self) -> Self {
+    if self.value < T::zero() {
+      -self
+    } else {
+      self
+    }
+  }
+}
+
+impl<T: Float> Float for Dual<T> {
+  fn epsilon() -> Self {
+    Dual::constant(T::epsilon())
+  }
+
+  fn infinity() -> Self {
+    Dual::constant(T::infinity())
+  }
+
+  fn neg_infinity() -> Self {
+    Dual::constant(T::neg_infinity())
+  }
+
+  fn from_f64(value: f64) -> Self {
+    Dual::constant(T::from_f64(value))
+  }
+
+  fn to_f64(self) -> f64 {
+    self.value.to_f64()
+  }
+
+  fn sqrt(self) -> Self {
+    let root = self.value.sqrt();
+    self.chain(root, T::one() / (root + root))
+  }
+
+  fn exp(self) -> Self {
+    let e = self.value.exp();
+    self.chain(e, e)
+  }
+
+  fn ln(self) -> Self {
+    self.chain(self.value.ln(), T::one() / self.value)
+  }
+
+  fn powi(self, n: i32) -> Self {
+    self.chain(self.value.powi(n), T::from_f64(n as f64) * self.value.powi(n - 1))
+  }
+
+  fn powf(self, n: Self) -> Self {
+    let value = self.value.powf(n.value);
+    let mut deriv = n.value * self.value.powf(n.value - T::one()) * self.deriv;
+    if n.deriv != T::zero() {
+      deriv += value * self.value.ln() * n.deriv;
+    }
+    Dual::new(value, deriv)
+  }
+
+  fn tanh(self) -> Self {
+    let t = self.value.tanh();
+    self.chain(t, T::one() - t * t)
+  }
+
+  fn is_nan(self) -> bool {
+    self.value.is_nan() || self.deriv.is_nan()
+  }
+
+  fn max(self, other: Self) -> Self {
+    if other.value > self.value {
+      other
+    } else {
+      self
+    }
+  }
+
+  fn min(self, other: Self) -> Self {
+    if other.value < self.value {
+      other
+    } else {
+      self
+    }
+  }
+}
+
+#[cfg(feature = "num-traits")]
+impl<T: Float> num_traits::Zero for Dual<T> {
+  fn zero() -> Self {
+    <Self as Scalar>::zero()
+  }
+
+  fn is_zero(&self) -> bool {
+    self.value == T::zero() && self.deriv == T::zero()
+  }
+}
+
+#[cfg(feature = "num-traits")]
+impl<T: Float> num_traits::One for Dual<T> {
+  fn one() -> Self {
+    <Self as Scalar>::one()
+  }
+}
+
+impl<T: fmt::Display> fmt::Display for Dual<T> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match f.precision() {
+      Some(p) => write!(f, "{:.*} + {:.*}ε", p, self.value, p, self.deriv),
+      None => write!(f, "{} + {}ε", self.value, self.deriv)
+    }
+  }
+}
+
+pub fn derivative<T, F>(f: F, x: T) -> (T, T)
+where
+  T: Float,
+  F: Fn(Dual<T>) -> Dual<T>
+{
+  let y = f(Dual::variable(x));
+  (y.value, y.deriv)
+}
+
+pub fn jvp<T, F>(f: F, x: &Vector<T>, v: &Vector<T>) -> Result<(Vector<T>, Vector<T>), String>
+where
+  T: Float,
+  F: Fn(&Vector<Dual<T>>) -> Vector<Dual<T>>
+{
+  if x.len() != v.len() {
+    return Err(format!("jvp: tangent of length {} does not match input of length {}", v.len(), x.len()));
+  }
+
+  let input: Vector<Dual<T>> = x.iter().zip(v.iter()).map(|(&value, &deriv)| Dual::new(value, deriv)).collect();
+  let output = f(&input);
+  Ok((output.map(|d| d.value), output.map(|d| d.deriv)))
+}
+
+pub fn jacobian<T, F>(f: F, x: &Vector<T>) -> Matrix<T>
+where
+  T: Float,
+  F: Fn(&Vector<Dual<T>>) -> Vector<Dual<T>>
+{
+  let n = x.len();
+  let columns: Vec<Vector<T>> = (0..n)
+    .map(|j| {
+      let input: Vector<Dual<T>> = x
+        .iter()
+        .enumerate()
+        .map(|(i, &value)| Dual::new(value, if i == j { T::one() } else { T::zero() }))
+        .collect();
+      f(&input).map(|d| d.deriv)
+    })
+    .collect();
+
+  let m = columns.first().map_or(0, |c| c.len());
+  Matrix::from_fn(m, n, |i, j| columns[j][i])
+}
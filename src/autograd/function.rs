@@ -0,0 +1,46 @@
+use super::Var;
+use crate::math::scalar::Float;
+use crate::tensor::Tensor;
+
+pub trait Function<T: Float> {
+  fn name(&self) -> &'static str;
+
+  fn forward(&self, inputs: &[Tensor<T>]) -> Result<Tensor<T>, String>;
+
+  fn backward(&self, inputs: &[Var<T>], grad_output: &Var<T>) -> Result<Vec<Var<T>>, String>;
+}
+
+pub fn apply<T, F>(function: F, inputs: &[Var<T>]) -> Result<Var<T>, String>
+where
+  T: Float,
+  F: Function<T> + 'static
+{
+  let values: Vec<Tensor<T>> = inputs.iter().map(|input| input.value()).collect();
+  let output = function.forward(&values)?;
+
+  let name = function.name();
+  let saved = inputs.to_vec();
+  Ok(Var::from_op(output, name, inputs.to_vec(), move |g| {
+    let grads = function.backward(&saved, g)?;
+    if grads.len() != saved.len() {
+      return Err(format!(
+        "{}: backward returned {} gradients for {} inputs",
+        name,
+        grads.len(),
+        saved.len()
+      ));
+    }
+    for (i, (grad, input)) in grads.iter().zip(saved.iter()).enumerate() {
+      if grad.shape() != input.shape() {
+        return Err(format!(
+          "{}: gradient {} has shape {:?} but input has shape {:?}",
+          name,
+          i,
+          grad.shape(),
+          input.shape()
+        ));
+      }
+    }
+    Ok(grads)
+  }))
+}
@@ -0,0 +1,94 @@
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use super::Var;
+use crate::math::scalar::Float;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GraphNode {
+  pub id: usize,
+  pub op: &'static str,
+  pub shape: Vec<usize>,
+  pub requires_grad: bool,
+  pub is_param: bool
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Graph {
+  pub nodes: Vec<GraphNode>,
+  pub edges: Vec<(usize, usize)>
+}
+
+impl Graph {
+  pub fn from_root<T: Float>(root: &Var<T>) -> Self {
+    let mut graph = Graph::default();
+    let mut visited = HashSet::new();
+    let mut stack = vec![root.clone()];
+
+    while let Some(var) = stack.pop() {
+      if !visited.insert(var.id()) {
+        continue;
+      }
+
+      graph.nodes.push(GraphNode {
+        id: var.id(),
+        op: var.op(),
+        shape: var.shape(),
+        requires_grad: var.requires_grad(),
+        is_param: var.is_leaf() && var.requires_grad()
+      });
+
+      if let Some(grad_fn) = var.node.grad_fn.borrow().as_ref() {
+        for parent in &grad_fn.parents {
+          graph.edges.push((parent.id(), var.id()));
+          stack.push(parent.clone());
+        }
+      }
+    }
+
+    graph.nodes.sort_by_key(|node| node.id);
+    graph.edges.sort();
+    graph
+  }
+
+  pub fn to_dot(&self) -> String {
+    let mut out = String::from("digraph autograd {\n  rankdir=BT;\n  node [fontname=\"monospace\"];\n");
+    for node in &self.nodes {
+      let style = if node.is_param {
+        "shape=box, style=filled, fillcolor=lightblue"
+      } else if node.requires_grad {
+        "shape=ellipse"
+      } else {
+        "shape=ellipse, style=dashed"
+      };
+      writeln!(out, "  n{} [label=\"{}\\n{:?}\", {}];", node.id, node.op, node.shape, style).unwrap();
+    }
+    for (from, to) in &self.edges {
+      writeln!(out, "  n{} -> n{};", from, to).unwrap();
+    }
+    out.push_str("}\n");
+    out
+  }
+
+  pub fn to_json(&self) -> String {
+    let nodes: Vec<String> = self
+      .nodes
+      .iter()
+      .map(|node| {
+        format!(
+          "{{\"id\":{},\"op\":\"{}\",\"shape\":{:?},\"requires_grad\":{},\"is_param\":{}}}",
+          node.id, node.op, node.shape, node.requires_grad, node.is_param
+        )
+      })
+      .collect();
+    let edges: Vec<String> = self.edges.iter().map(|(from, to)| format!("[{},{}]", from, to)).collect();
+
+    format!("{{\"nodes\":[{}],\"edges\":[{}]}}", nodes.join(","), edges.join(","))
+  }
+}
+
+impl<T: Float> Var<T> {
+  pub fn graph(&self) -> Graph {
+    Graph::from_root(self)
+  }
+}
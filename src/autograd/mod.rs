@@ -0,0 +1,339 @@
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::rc::Rc;
+
+use crate::math::scalar::Float;
+use crate::tensor::Tensor;
+
+pub mod autocast;
+mod check;
+pub mod dual;
+pub mod function;
+pub mod graph;
+pub mod ops;
+pub mod scaler;
+
+pub use autocast::{autocast, autocast_precision, HalfPrecision};
+pub use check::{grad_check, GradCheckReport};
+pub use scaler::LossScaler;
+
+thread_local! {
+  static GRAD_ENABLED: Cell<bool> = const { Cell::new(true) };
+  static NEXT_ID: Cell<usize> = const { Cell::new(0) };
+}
+
+pub fn is_grad_enabled() -> bool {
+  GRAD_ENABLED.with(|enabled| enabled.get())
+}
+
+struct GradModeGuard {
+  previous: bool
+}
+
+impl Drop for GradModeGuard {
+  fn drop(&mut self) {
+    GRAD_ENABLED.with(|enabled| enabled.set(self.previous));
+  }
+}
+
+pub fn no_grad<R, F>(f: F) -> R
+where
+  F: FnOnce() -> R
+{
+  with_grad_mode(false, f)
+}
+
+pub fn enable_grad<R, F>(f: F) -> R
+where
+  F: FnOnce() -> R
+{
+  with_grad_mode(true, f)
+}
+
+pub(crate) fn with_grad_mode<R, F>(enabled: bool, f: F) -> R
+where
+  F: FnOnce() -> R
+{
+  let _guard = GradModeGuard { previous: GRAD_ENABLED.with(|e| e.replace(enabled)) };
+  f()
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BackwardOptions {
+  pub retain_graph: bool,
+  pub create_graph: bool
+}
+
+type BackwardFn<T> = Box<dyn Fn(&Var<T>) -> Result<Vec<Var<T>>, String>>;
+
+struct GradFn<T> {
+  parents: Vec<Var<T>>,
+  backward: BackwardFn<T>
+}
+
+struct Node<T> {
+  id: usize,
+  value: RefCell<Tensor<T>>,
+  op: &'static str,
+  requires_grad: Cell<bool>,
+  grad_fn: RefCell<Option<GradFn<T>>>,
+  freed: Cell<bool>,
+  grad: RefCell<Option<Var<T>>>
+}
+
+#[derive(Clone)]
+pub struct Var<T = f64> {
+  node: Rc<Node<T>>
+}
+
+impl<T: Float> Var<T> {
+  fn leaf(value: Tensor<T>, op: &'static str, requires_grad: bool) -> Self {
+    let id = NEXT_ID.with(|next| next.replace(next.get() + 1));
+    Var {
+      node: Rc::new(Node {
+        id,
+        value: RefCell::new(value),
+        op,
+        requires_grad: Cell::new(requires_grad),
+        grad_fn: RefCell::new(None),
+        freed: Cell::new(false),
+        grad: RefCell::new(None)
+      })
+    }
+  }
+
+  pub fn constant(value: Tensor<T>) -> Self {
+    Var::leaf(value, "constant", false)
+  }
+
+  pub fn param(value: Tensor<T>) -> Self {
+    Var::leaf(value, "parameter", true)
+  }
+
+  pub(crate) fn from_op<F>(value: Tensor<T>, op: &'static str, parents: Vec<Var<T>>, backward: F) -> Self
+  where
+    F: Fn(&Var<T>) -> Result<Vec<Var<T>>, String> + 'static
+  {
+    if !is_grad_enabled() || !parents.iter().any(|p| p.requires_grad()) {
+      return Var::leaf(value, op, false);
+    }
+
+    let var = Var::leaf(value, op, true);
+    *var.node.grad_fn.borrow_mut() = Some(GradFn { parents, backward: Box::new(backward) });
+    var
+  }
+
+  pub(crate) fn id(&self) -> usize {
+    self.node.id
+  }
+
+  pub fn value(&self) -> Tensor<T> {
+    self.node.value.borrow().clone()
+  }
+
+  pub fn shape(&self) -> Vec<usize> {
+    self.node.value.borrow().shape().to_vec()
+  }
+
+  pub fn set_value(&self, value: Tensor<T>) -> Result<(), String> {
+    if value.shape() != self.shape() {
+      return Err(format!("set_value: expected shape {:?}, found {:?}", self.shape(), value.shape()));
+    }
+    *self.node.value.borrow_mut() = value;
+    Ok(())
+  }
+
+  pub fn update_value<R, F>(&self, f: F) -> R
+  where
+    F: FnOnce(&mut Tensor<T>) -> R
+  {
+    f(&mut self.node.value.borrow_mut())
+  }
+
+  pub fn op(&self) -> &'static str {
+    self.node.op
+  }
+
+  pub fn requires_grad(&self) -> bool {
+    self.node.requires_grad.get()
+  }
+
+  pub fn set_requires_grad(&self, requires_grad: bool) -> Result<(), String> {
+    if !self.is_leaf() {
+      return Err(format!("set_requires_grad: '{}' is not a leaf variable, detach it first", self.op()));
+    }
+    self.node.requires_grad.set(requires_grad);
+    Ok(())
+  }
+
+  pub fn is_leaf(&self) -> bool {
+    self.node.grad_fn.borrow().is_none() && !self.node.freed.get()
+  }
+
+  pub fn detach(&self) -> Var<T> {
+    Var::constant(self.value())
+  }
+
+  pub fn free_graph(&self) {
+    for var in self.topo_order() {
+      var.free();
+    }
+  }
+
+  fn free(&self) {
+    if self.node.grad_fn.borrow_mut().take().is_some() {
+      self.node.freed.set(true);
+    }
+  }
+
+  pub fn grad(&self) -> Option<Tensor<T>> {
+    self.node.grad.borrow().as_ref().map(|g| g.value())
+  }
+
+  pub fn grad_var(&self) -> Option<Var<T>> {
+    self.node.grad.borrow().clone()
+  }
+
+  pub fn zero_grad(&self) {
+    *self.node.grad.borrow_mut() = None;
+  }
+
+  pub fn set_grad(&self, grad: Option<Tensor<T>>) -> Result<(), String> {
+    if let Some(grad) = &grad {
+      if grad.shape() != self.shape() {
+        return Err(format!("set_grad: expected shape {:?}, found {:?}", self.shape(), grad.shape()));
+      }
+    }
+    *self.node.grad.borrow_mut() = grad.map(Var::constant);
+    Ok(())
+  }
+
+  fn accumulate_grad(&self, grad: Var<T>) -> Result<(), String> {
+    let mut slot = self.node.grad.borrow_mut();
+    let total = match slot.take() {
+      Some(existing) => (&existing + &grad)?,
+      None => grad
+    };
+    *slot = Some(total);
+    Ok(())
+  }
+
+  fn topo_order(&self) -> Vec<Var<T>> {
+    let mut order = Vec::new();
+    let mut visited = HashSet::new();
+    let mut stack = vec![(self.clone(), false)];
+
+    while let Some((var, expanded)) = stack.pop() {
+      if expanded {
+        order.push(var);
+        continue;
+      }
+      if !visited.insert(var.id()) {
+        continue;
+      }
+
+      stack.push((var.clone(), true));
+      if let Some(grad_fn) = var.node.grad_fn.borrow().as_ref() {
+        for parent in &grad_fn.parents {
+          if parent.requires_grad() && !visited.contains(&parent.id()) {
+            stack.push((parent.clone(), false));
+          }
+        }
+      }
+    }
+
+    order
+  }
+
+  pub fn backward(&self) -> Result<(), String> {
+    self.backward_with(Tensor::full(&self.shape(), T::one()), BackwardOptions::default())
+  }
+
+  pub fn backward_with(&self, grad: Tensor<T>, options: BackwardOptions) -> Result<(), String> {
+    self.propagate(grad, options, &[]).map(|_| ())
+  }
+
+  fn propagate(&self, grad: Tensor<T>, options: BackwardOptions, capture: &[Var<T>]) -> Result<HashMap<usize, Var<T>>, String> {
+    if !self.requires_grad() {
+      return Err("backward: tensor does not require grad".to_string());
+    }
+    if grad.shape() != self.shape() {
+      return Err(format!("backward: gradient shape {:?} does not match output shape {:?}", grad.shape(), self.shape()));
+    }
+
+    let order = self.topo_order();
+    if order.iter().any(|var| var.node.freed.get()) {
+      return Err("backward: the graph has already been freed; use retain_graph to backward through it again".to_string());
+    }
+
+    let captured: HashSet<usize> = capture.iter().map(|var| var.id()).collect();
+    with_grad_mode(options.create_graph, || {
+      let mut grads: HashMap<usize, Var<T>> = HashMap::new();
+      let mut results = HashMap::new();
+      grads.insert(self.id(), Var::constant(grad));
+
+      for var in order.iter().rev() {
+        let Some(grad) = grads.remove(&var.id()) else {
+          continue;
+        };
+        if captured.contains(&var.id()) {
+          results.insert(var.id(), grad.clone());
+        }
+
+        let recorded = var.node.grad_fn.borrow();
+        let Some(grad_fn) = recorded.as_ref() else {
+          if captured.is_empty() {
+            var.accumulate_grad(grad)?;
+          }
+          continue;
+        };
+
+        let parent_grads = (grad_fn.backward)(&grad)?;
+        for (parent, parent_grad) in grad_fn.parents.iter().zip(parent_grads) {
+          if !parent.requires_grad() {
+            continue;
+          }
+          let total = match grads.remove(&parent.id()) {
+            Some(existing) => (&existing + &parent_grad)?,
+            None => parent_grad
+          };
+          grads.insert(parent.id(), total);
+        }
+
+        drop(recorded);
+        if !options.retain_graph && !options.create_graph {
+          var.free();
+        }
+      }
+
+      Ok(results)
+    })
+  }
+}
+
+pub fn grad<T: Float>(output: &Var<T>, inputs: &[Var<T>], options: BackwardOptions) -> Result<Vec<Var<T>>, String> {
+  let grads = output.propagate(Tensor::full(&output.shape(), T::one()), options, inputs)?;
+  Ok(
+    inputs
+      .iter()
+      .map(|input| grads.get(&input.id()).cloned().unwrap_or_else(|| Var::constant(Tensor::zeros(&input.shape()))))
+      .collect()
+  )
+}
+
+impl<T: Float> From<Tensor<T>> for Var<T> {
+  fn from(value: Tensor<T>) -> Self {
+    Var::constant(value)
+  }
+}
+
+impl<T: Float> fmt::Debug for Var<T> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("Var")
+      .field("op", &self.op())
+      .field("requires_grad", &self.requires_grad())
+      .field("value", &*self.node.value.borrow())
+      .finish()
+  }
+}
@@ -0,0 +1,391 @@
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use super::autocast::{autocast, autocast_precision};
+use super::Var;
+use crate::math::scalar::Float;
+use crate::tensor::conv::{conv_weight_dims, Conv2dOptions};
+use crate::tensor::pad::{pad_source, padded_shape, PadMode};
+use crate::tensor::{contiguous_strides, Tensor};
+
+fn add<T: Float>(a: &Var<T>, b: &Var<T>) -> Result<Var<T>, String> {
+  let (a_shape, b_shape) = (a.shape(), b.shape());
+  Ok(Var::from_op((&a.value() + &b.value())?, "add", vec![a.clone(), b.clone()], move |g| {
+    Ok(vec![g.sum_to(&a_shape)?, g.sum_to(&b_shape)?])
+  }))
+}
+
+fn sub<T: Float>(a: &Var<T>, b: &Var<T>) -> Result<Var<T>, String> {
+  let (a_shape, b_shape) = (a.shape(), b.shape());
+  Ok(Var::from_op((&a.value() - &b.value())?, "sub", vec![a.clone(), b.clone()], move |g| {
+    Ok(vec![g.sum_to(&a_shape)?, (-g).sum_to(&b_shape)?])
+  }))
+}
+
+fn mul<T: Float>(a: &Var<T>, b: &Var<T>) -> Result<Var<T>, String> {
+  let (lhs, rhs) = (a.clone(), b.clone());
+  Ok(Var::from_op((&a.value() * &b.value())?, "mul", vec![a.clone(), b.clone()], move |g| {
+    Ok(vec![(g * &rhs)?.sum_to(&lhs.shape())?, (g * &lhs)?.sum_to(&rhs.shape())?])
+  }))
+}
+
+fn div<T: Float>(a: &Var<T>, b: &Var<T>) -> Result<Var<T>, String> {
+  let (lhs, rhs) = (a.clone(), b.clone());
+  Ok(Var::from_op((&a.value() / &b.value())?, "div", vec![a.clone(), b.clone()], move |g| {
+    let g_over_b = (g / &rhs)?;
+    let quotient = (&(&g_over_b * &lhs)? / &rhs)?;
+    Ok(vec![g_over_b.sum_to(&lhs.shape())?, (-&quotient).sum_to(&rhs.shape())?])
+  }))
+}
+
+macro_rules! impl_var_op {
+  ($trait:ident, $method:ident, $f:ident) => {
+    impl<T: Float> $trait for &Var<T> {
+      type Output = Result<Var<T>, String>;
+
+      fn $method(self, rhs: Self) -> Self::Output {
+        $f(self, rhs)
+      }
+    }
+
+    impl<T: Float> $trait for Var<T> {
+      type Output = Result<Var<T>, String>;
+
+      fn $method(self, rhs: Self) -> Self::Output {
+        $f(&self, &rhs)
+      }
+    }
+  };
+}
+
+impl_var_op!(Add, add, add);
+impl_var_op!(Sub, sub, sub);
+impl_var_op!(Mul, mul, mul);
+impl_var_op!(Div, div, div);
+
+impl<T: Float> Neg for &Var<T> {
+  type Output = Var<T>;
+
+  fn neg(self) -> Self::Output {
+    Var::from_op(self.value().map(|&x| -x), "neg", vec![self.clone()], |g| Ok(vec![-g]))
+  }
+}
+
+impl<T: Float> Neg for Var<T> {
+  type Output = Var<T>;
+
+  fn neg(self) -> Self::Output {
+    -&self
+  }
+}
+
+impl<T: Float> Var<T> {
+  pub fn mul_scalar(&self, scalar: T) -> Var<T> {
+    Var::from_op(self.value().map(|&x| x * scalar), "mul_scalar", vec![self.clone()], move |g| {
+      Ok(vec![g.mul_scalar(scalar)])
+    })
+  }
+
+  pub fn add_scalar(&self, scalar: T) -> Var<T> {
+    Var::from_op(self.value().map(|&x| x + scalar), "add_scalar", vec![self.clone()], |g| Ok(vec![g.clone()]))
+  }
+
+  pub fn exp(&self) -> Var<T> {
+    let input = self.clone();
+    Var::from_op(self.value().map(|&x| x.exp()), "exp", vec![self.clone()], move |g| {
+      Ok(vec![(g * &input.exp())?])
+    })
+  }
+
+  pub fn ln(&self) -> Var<T> {
+    let input = self.clone();
+    Var::from_op(self.value().map(|&x| x.ln()), "ln", vec![self.clone()], move |g| Ok(vec![(g / &input)?]))
+  }
+
+  pub fn sqrt(&self) -> Var<T> {
+    let input = self.clone();
+    Var::from_op(self.value().map(|&x| x.sqrt()), "sqrt", vec![self.clone()], move |g| {
+      Ok(vec![(&g.mul_scalar(T::from_f64(0.5)) / &input.sqrt())?])
+    })
+  }
+
+  pub fn powi(&self, n: i32) -> Var<T> {
+    let input = self.clone();
+    Var::from_op(self.value().map(|&x| x.powi(n)), "powi", vec![self.clone()], move |g| {
+      Ok(vec![(g * &input.powi(n - 1).mul_scalar(T::from_f64(n as f64)))?])
+    })
+  }
+
+  pub fn tanh(&self) -> Var<T> {
+    let input = self.clone();
+    Var::from_op(self.value().map(|&x| x.tanh()), "tanh", vec![self.clone()], move |g| {
+      let slope = (-input.tanh().powi(2)).add_scalar(T::one());
+      Ok(vec![(g * &slope)?])
+    })
+  }
+
+  pub fn abs(&self) -> Var<T> {
+    let sign = self.value().map(|&x| {
+      if x > T::zero() {
+        T::one()
+      } else if x < T::zero() {
+        -T::one()
+      } else {
+        T::zero()
+      }
+    });
+    Var::from_op(self.value().map(|&x| x.abs()), "abs", vec![self.clone()], move |g| {
+      Ok(vec![(g * &Var::constant(sign.clone()))?])
+    })
+  }
+
+  pub fn clamp(&self, min: T, max: T) -> Var<T> {
+    let mask = self.value().map(|&x| if x >= min && x <= max { T::one() } else { T::zero() });
+    let value = self.value().map(|&x| x.max(min).min(max));
+    Var::from_op(value, "clamp", vec![self.clone()], move |g| Ok(vec![(g * &Var::constant(mask.clone()))?]))
+  }
+
+  pub fn sum(&self, axes: &[usize], keepdims: bool) -> Result<Var<T>, String> {
+    let value = self.value();
+    let input_shape = value.shape().to_vec();
+    let kept_shape = value.reduced_shape(&value.normalize_axes(axes)?, true);
+    Ok(Var::from_op(value.sum(axes, keepdims)?, "sum", vec![self.clone()], move |g| {
+      Ok(vec![g.reshape(&kept_shape)?.broadcast_to(&input_shape)?])
+    }))
+  }
+
+  pub fn mean(&self, axes: &[usize], keepdims: bool) -> Result<Var<T>, String> {
+    let sum = self.sum(axes, keepdims)?;
+    let count = self.value().len() / sum.value().len().max(1);
+    Ok(sum.mul_scalar(T::one() / T::from_f64(count as f64)))
+  }
+
+  pub fn reshape(&self, shape: &[usize]) -> Result<Var<T>, String> {
+    let input_shape = self.shape();
+    Ok(Var::from_op(self.value().reshape(shape)?, "reshape", vec![self.clone()], move |g| {
+      Ok(vec![g.reshape(&input_shape)?])
+    }))
+  }
+
+  pub fn permute(&self, axes: &[usize]) -> Result<Var<T>, String> {
+    let mut inverse = vec![0; axes.len()];
+    for (i, &axis) in axes.iter().enumerate() {
+      if let Some(slot) = inverse.get_mut(axis) {
+        *slot = i;
+      }
+    }
+    Ok(Var::from_op(self.value().permute(axes)?, "permute", vec![self.clone()], move |g| {
+      Ok(vec![g.permute(&inverse)?])
+    }))
+  }
+
+  pub fn swap_axes(&self, a: usize, b: usize) -> Result<Var<T>, String> {
+    let mut axes: Vec<usize> = (0..self.value().ndim()).collect();
+    if a >= axes.len() || b >= axes.len() {
+      return Err(format!("Cannot swap axes {} and {} of a tensor with {} dimensions", a, b, axes.len()));
+    }
+    axes.swap(a, b);
+    self.permute(&axes)
+  }
+
+  pub fn transpose(&self) -> Var<T> {
+    let axes: Vec<usize> = (0..self.value().ndim()).rev().collect();
+    self.permute(&axes).expect("reversed axes are always a valid permutation")
+  }
+
+  pub fn broadcast_to(&self, shape: &[usize]) -> Result<Var<T>, String> {
+    let input_shape = self.shape();
+    Ok(Var::from_op(self.value().broadcast_to(shape)?, "broadcast_to", vec![self.clone()], move |g| {
+      Ok(vec![g.sum_to(&input_shape)?])
+    }))
+  }
+
+  pub fn sum_to(&self, shape: &[usize]) -> Result<Var<T>, String> {
+    if self.shape() == shape {
+      return Ok(self.clone());
+    }
+
+    let input_shape = self.shape();
+    Ok(Var::from_op(self.value().sum_to(shape)?, "sum_to", vec![self.clone()], move |g| {
+      Ok(vec![g.broadcast_to(&input_shape)?])
+    }))
+  }
+
+  pub fn matmul(&self, other: &Var<T>) -> Result<Var<T>, String> {
+    if let Some(precision) = autocast_precision() {
+      let (lhs, rhs) = (self.round_to(precision), other.round_to(precision));
+      return autocast(None, || lhs.matmul(&rhs)).map(|output| output.round_to(precision));
+    }
+
+    let (lhs, rhs) = (self.clone(), other.clone());
+    Ok(Var::from_op(self.value().matmul(&other.value())?, "matmul", vec![self.clone(), other.clone()], move |g| {
+      let (l, r) = (lhs.value().ndim(), rhs.value().ndim());
+      let grad_lhs = g.matmul(&rhs.swap_axes(r - 2, r - 1)?)?.sum_to(&lhs.shape())?;
+      let grad_rhs = lhs.swap_axes(l - 2, l - 1)?.matmul(g)?.sum_to(&rhs.shape())?;
+      Ok(vec![grad_lhs, grad_rhs])
+    }))
+  }
+
+  pub fn cat(vars: &[&Var<T>], axis: usize) -> Result<Var<T>, String> {
+    let values: Vec<Tensor<T>> = vars.iter().map(|v| v.value()).collect();
+    let value = Tensor::cat(&values.iter().collect::<Vec<_>>(), axis)?;
+    let sizes: Vec<usize> = values.iter().map(|v| v.shape()[axis]).collect();
+    Ok(Var::from_op(value, "cat", vars.iter().map(|&v| v.clone()).collect(), move |g| {
+      let mut start = 0;
+      sizes
+        .iter()
+        .map(|&len| {
+          start += len;
+          g.narrow(axis, start - len, len)
+        })
+        .collect()
+    }))
+  }
+
+  pub fn stack(vars: &[&Var<T>], axis: usize) -> Result<Var<T>, String> {
+    let expanded = vars.iter().map(|v| v.unsqueeze(axis)).collect::<Result<Vec<_>, _>>()?;
+    Var::cat(&expanded.iter().collect::<Vec<_>>(), axis)
+  }
+
+  pub fn unsqueeze(&self, axis: usize) -> Result<Var<T>, String> {
+    let shape = self.value().unsqueeze(axis)?.shape().to_vec();
+    self.reshape(&shape)
+  }
+
+  pub fn narrow(&self, axis: usize, start: usize, len: usize) -> Result<Var<T>, String> {
+    let shape = self.shape();
+    Ok(Var::from_op(self.value().narrow(axis, start, len)?, "narrow", vec![self.clone()], move |g| {
+      let zeros = |n: usize| {
+        let mut padding = shape.clone();
+        padding[axis] = n;
+        Var::constant(Tensor::zeros(&padding))
+      };
+      let (before, after) = (zeros(start), zeros(shape[axis] - start - len));
+      Ok(vec![Var::cat(&[&before, g, &after], axis)?])
+    }))
+  }
+
+  pub fn gather(&self, axis: usize, index: &Tensor<usize>) -> Result<Var<T>, String> {
+    let (shape, index) = (self.shape(), index.clone());
+    Ok(Var::from_op(self.value().gather(axis, &index)?, "gather", vec![self.clone()], move |g| {
+      Ok(vec![Var::constant(Tensor::zeros(&shape)).scatter_add(axis, &index, g)?])
+    }))
+  }
+
+  pub fn scatter_add(&self, axis: usize, index: &Tensor<usize>, src: &Var<T>) -> Result<Var<T>, String> {
+    if src.shape() != index.shape() {
+      return Err(format!("scatter_add: source shape {:?} must match index shape {:?}", src.shape(), index.shape()));
+    }
+
+    let index = index.clone();
+    let value = self.value().scatter_add(axis, &index, &src.value())?;
+    Ok(Var::from_op(value, "scatter_add", vec![self.clone(), src.clone()], move |g| {
+      Ok(vec![g.clone(), g.gather(axis, &index)?])
+    }))
+  }
+
+  pub fn index_select(&self, axis: usize, indices: &[usize]) -> Result<Var<T>, String> {
+    if axis >= self.value().ndim() {
+      return Err(format!("index_select: axis {} out of range for a tensor with {} dimensions", axis, self.value().ndim()));
+    }
+
+    let mut shape = self.shape();
+    shape[axis] = indices.len();
+    let index = Tensor::from_fn(&shape, |position| indices[position[axis]]);
+    self.gather(axis, &index)
+  }
+
+  pub fn softmax(&self, axis: usize) -> Result<Var<T>, String> {
+    let input = self.clone();
+    Ok(Var::from_op(self.value().softmax(axis)?, "softmax", vec![self.clone()], move |g| {
+      let s = input.softmax(axis)?;
+      let dot = (g * &s)?.sum(&[axis], true)?;
+      Ok(vec![(&s * &(g - &dot)?)?])
+    }))
+  }
+
+  pub fn log_softmax(&self, axis: usize) -> Result<Var<T>, String> {
+    let input = self.clone();
+    Ok(Var::from_op(self.value().log_softmax(axis)?, "log_softmax", vec![self.clone()], move |g| {
+      let s = input.softmax(axis)?;
+      Ok(vec![(g - &(&s * &g.sum(&[axis], true)?)?)?])
+    }))
+  }
+
+  pub fn pad(&self, pads: &[(usize, usize)], mode: PadMode<T>) -> Result<Var<T>, String> {
+    let shape = self.shape();
+    let out_shape = padded_shape(&shape, pads, &mode)?;
+
+    if let PadMode::Constant(_) = mode {
+      let pads = pads.to_vec();
+      return Ok(Var::from_op(self.value().pad(&pads, mode)?, "pad", vec![self.clone()], move |g| {
+        let mut grad = g.clone();
+        for (axis, (&(before, _), &n)) in pads.iter().zip(shape.iter()).enumerate() {
+          grad = grad.narrow(axis, before, n)?;
+        }
+        Ok(vec![grad])
+      }));
+    }
+
+    let strides = contiguous_strides(&shape);
+    let len: usize = out_shape.iter().product();
+    let index = Tensor::from_fn(&out_shape, |index| {
+      let source = pad_source(index, &shape, pads, &mode).expect("reflect and replicate always have a source");
+      source.iter().zip(strides.iter()).map(|(i, s)| i * s).sum::<usize>()
+    });
+    self
+      .reshape(&[self.value().len()])?
+      .gather(0, &index.reshape(&[len])?)?
+      .reshape(&out_shape)
+  }
+
+  pub fn im2col(&self, options: &Conv2dOptions) -> Result<Var<T>, String> {
+    let (shape, options) = (self.shape(), *options);
+    Ok(Var::from_op(self.value().im2col(&options)?, "im2col", vec![self.clone()], move |g| {
+      Ok(vec![g.col2im(shape[1], (shape[2], shape[3]), &options)?])
+    }))
+  }
+
+  pub fn col2im(&self, channels: usize, size: (usize, usize), options: &Conv2dOptions) -> Result<Var<T>, String> {
+    let options = *options;
+    Ok(Var::from_op(self.value().col2im(channels, size, &options)?, "col2im", vec![self.clone()], move |g| {
+      Ok(vec![g.im2col(&options)?])
+    }))
+  }
+
+  pub fn conv2d(&self, weight: &Var<T>, bias: Option<&Var<T>>, options: &Conv2dOptions) -> Result<Var<T>, String> {
+    let (out_channels, kernel_rows) = conv_weight_dims(&weight.shape(), options)?;
+    let shape = self.shape();
+    if shape.len() != 4 {
+      return Err(format!("conv2d: expected an [N, C, H, W] tensor, found shape {:?}", shape));
+    }
+    let (oh, ow) = options.output_size(shape[2], shape[3])?;
+
+    let mut out = weight.reshape(&[out_channels, kernel_rows])?.matmul(&self.im2col(options)?)?;
+    if let Some(bias) = bias {
+      out = (&out + &bias.reshape(&[out_channels, 1])?)?;
+    }
+    out.reshape(&[shape[0], out_channels, oh, ow])
+  }
+
+  pub fn max_pool2d(&self, options: &Conv2dOptions) -> Result<(Var<T>, Tensor<usize>), String> {
+    let (values, indices) = self.value().max_pool2d(options)?;
+    let (shape, out_shape) = (self.shape(), values.shape().to_vec());
+    let flat_index = indices.reshape(&[out_shape[0], out_shape[1], out_shape[2] * out_shape[3]])?;
+    let pooled = self
+      .reshape(&[shape[0], shape[1], shape[2] * shape[3]])?
+      .gather(2, &flat_index)?
+      .reshape(&out_shape)?;
+    Ok((pooled, indices))
+  }
+
+  pub fn avg_pool2d(&self, options: &Conv2dOptions) -> Result<Var<T>, String> {
+    let shape = self.shape();
+    if shape.len() != 4 {
+      return Err(format!("avg_pool2d: expected an [N, C, H, W] tensor, found shape {:?}", shape));
+    }
+    let (oh, ow) = options.output_size(shape[2], shape[3])?;
+
+    let cols = self.reshape(&[shape[0] * shape[1], 1, shape[2], shape[3]])?.im2col(options)?;
+    cols.mean(&[1], false)?.reshape(&[shape[0], shape[1], oh, ow])
+  }
+}
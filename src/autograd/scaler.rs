@@ -0,0 +1,70 @@
+use super::Var;
+use crate::math::scalar::Float;
+use crate::tensor::Tensor;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LossScaler {
+  pub growth_factor: f64,
+  pub backoff_factor: f64,
+  pub growth_interval: usize,
+  scale: f64,
+  good_steps: usize
+}
+
+impl Default for LossScaler {
+  fn default() -> Self {
+    LossScaler::new(65536.0)
+  }
+}
+
+pub fn all_finite<T: Float>(tensor: &Tensor<T>) -> bool {
+  tensor.iter().all(|&x| !x.is_nan() && x.abs() != T::infinity())
+}
+
+impl LossScaler {
+  pub fn new(init_scale: f64) -> Self {
+    LossScaler {
+      growth_factor: 2.0,
+      backoff_factor: 0.5,
+      growth_interval: 2000,
+      scale: init_scale,
+      good_steps: 0
+    }
+  }
+
+  pub fn scale(&self) -> f64 {
+    self.scale
+  }
+
+  pub fn scale_loss<T: Float>(&self, loss: &Var<T>) -> Var<T> {
+    loss.mul_scalar(T::from_f64(self.scale))
+  }
+
+  pub fn unscale<T: Float>(&self, grad: &Tensor<T>) -> Tensor<T> {
+    let inv = T::from_f64(1.0 / self.scale);
+    grad.map(|&g| g * inv)
+  }
+
+  pub fn unscale_grads<T: Float>(&self, params: &[Var<T>]) -> Option<Vec<Tensor<T>>> {
+    params
+      .iter()
+      .map(|param| {
+        let grad = param.grad().unwrap_or_else(|| Tensor::zeros(&param.shape()));
+        all_finite(&grad).then(|| self.unscale(&grad))
+      })
+      .collect()
+  }
+
+  pub fn update(&mut self, found_overflow: bool) {
+    if found_overflow {
+      self.scale = (self.scale * self.backoff_factor).max(f64::MIN_POSITIVE);
+      self.good_steps = 0;
+    } else {
+      self.good_steps += 1;
+      if self.good_steps >= self.growth_interval {
+        self.scale *= self.growth_factor;
+        self.good_steps = 0;
+      }
+    }
+  }
+}
@@ -0,0 +1,141 @@
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use super::{Dataset, TensorDataset};
+use crate::io::checkpoint::{load_checkpoint, save_checkpoint};
+use crate::io::npy::NpyElement;
+use crate::math::scalar::Float;
+
+const CACHE_VERSION: u32 = 1;
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Fingerprint(u64);
+
+impl Fingerprint {
+  pub fn new() -> Self {
+    let mut fingerprint = Fingerprint(FNV_OFFSET);
+    fingerprint.update(&CACHE_VERSION.to_le_bytes());
+    fingerprint
+  }
+
+  pub fn update(&mut self, bytes: &[u8]) {
+    for &byte in (bytes.len() as u64).to_le_bytes().iter().chain(bytes) {
+      self.0 ^= byte as u64;
+      self.0 = self.0.wrapping_mul(FNV_PRIME);
+    }
+  }
+
+  pub fn update_file(&mut self, path: &Path) -> Result<(), String> {
+    let mut file = File::open(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    self.update(&bytes);
+    Ok(())
+  }
+
+  pub fn hex(&self) -> String {
+    format!("{:016x}", self.0)
+  }
+}
+
+impl Default for Fingerprint {
+  fn default() -> Self {
+    Fingerprint::new()
+  }
+}
+
+pub fn fingerprint<P: AsRef<Path>>(sources: &[P], config: &str) -> Result<String, String> {
+  let mut fingerprint = Fingerprint::new();
+  for source in sources {
+    fingerprint.update(source.as_ref().to_string_lossy().as_bytes());
+    fingerprint.update_file(source.as_ref())?;
+  }
+  fingerprint.update(config.as_bytes());
+  Ok(fingerprint.hex())
+}
+
+pub fn materialize<T: Float>(dataset: &dyn Dataset<T>) -> Result<TensorDataset<T>, String> {
+  let indices: Vec<usize> = (0..dataset.len()).collect();
+  let (features, labels) = dataset.get_batch(&indices)?;
+  TensorDataset::new(features, labels)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DatasetCache {
+  dir: PathBuf
+}
+
+impl DatasetCache {
+  pub fn new<P: AsRef<Path>>(dir: P) -> Result<Self, String> {
+    fs::create_dir_all(dir.as_ref()).map_err(|e| format!("Cannot create cache directory {}: {}", dir.as_ref().display(), e))?;
+    Ok(DatasetCache { dir: dir.as_ref().to_path_buf() })
+  }
+
+  pub fn dir(&self) -> &Path {
+    &self.dir
+  }
+
+  pub fn path(&self, key: &str) -> PathBuf {
+    self.dir.join(format!("{}.oxz", key))
+  }
+
+  pub fn contains(&self, key: &str) -> bool {
+    self.path(key).is_file()
+  }
+
+  pub fn load<T: Float + NpyElement>(&self, key: &str) -> Result<Option<TensorDataset<T>>, String> {
+    if !self.contains(key) {
+      return Ok(None);
+    }
+    let mut entries = load_checkpoint::<T, _>(self.path(key))?;
+    match (entries.pop(), entries.pop()) {
+      (Some((labels_name, labels)), Some((features_name, features))) if features_name == "features" && labels_name == "labels" => {
+        TensorDataset::new(features, labels).map(Some)
+      }
+      _ => Err(format!("DatasetCache: entry {} is not a cached dataset", key))
+    }
+  }
+
+  pub fn store<T: Float + NpyElement>(&self, key: &str, dataset: &TensorDataset<T>) -> Result<(), String> {
+    let staging = self.dir.join(format!("{}.oxz.partial", key));
+    save_checkpoint(&staging, &[("features", dataset.features()), ("labels", dataset.labels())])?;
+    fs::rename(&staging, self.path(key)).map_err(|e| format!("DatasetCache: cannot finalize entry {}: {}", key, e))
+  }
+
+  pub fn get_or_insert_with<T, F>(&self, key: &str, build: F) -> Result<TensorDataset<T>, String>
+  where
+    T: Float + NpyElement,
+    F: FnOnce() -> Result<TensorDataset<T>, String>
+  {
+    if let Some(dataset) = self.load(key)? {
+      return Ok(dataset);
+    }
+    let dataset = build()?;
+    self.store(key, &dataset)?;
+    Ok(dataset)
+  }
+
+  pub fn remove(&self, key: &str) -> Result<bool, String> {
+    if !self.contains(key) {
+      return Ok(false);
+    }
+    fs::remove_file(self.path(key)).map_err(|e| format!("DatasetCache: cannot remove entry {}: {}", key, e))?;
+    Ok(true)
+  }
+
+  pub fn clear(&self) -> Result<usize, String> {
+    let mut removed = 0;
+    for entry in fs::read_dir(&self.dir).map_err(|e| e.to_string())? {
+      let path = entry.map_err(|e| e.to_string())?.path();
+      let name = path.file_name().and_then(|name| name.to_str()).unwrap_or("");
+      if name.ends_with(".oxz") || name.ends_with(".oxz.partial") {
+        fs::remove_file(&path).map_err(|e| format!("DatasetCache: cannot remove {}: {}", path.display(), e))?;
+        removed += 1;
+      }
+    }
+    Ok(removed)
+  }
+}
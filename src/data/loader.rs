@@ -0,0 +1,228 @@
+use std::cell::Cell;
+use std::sync::mpsc::{sync_channel, Receiver};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use super::transforms::Transform;
+use super::Dataset;
+use crate::math::matrix::Matrix;
+use crate::math::scalar::Float;
+use crate::random::{with_rng, with_seed, Rng};
+use crate::tensor::Tensor;
+use crate::train::{Batch, BatchSource};
+
+enum Source<'a, T: Float> {
+  Borrowed(&'a dyn Dataset<T>),
+  Shared(Arc<dyn Dataset<T> + Send + Sync>)
+}
+
+pub struct DataLoader<'a, T: Float = f64> {
+  source: Source<'a, T>,
+  pub batch_size: usize,
+  pub shuffle: bool,
+  pub drop_last: bool,
+  pub seed: Option<u64>,
+  pub transform: Option<Arc<dyn Transform<T>>>,
+  pub num_workers: usize,
+  pub prefetch: usize,
+  epoch: Cell<u64>
+}
+
+impl<T: Float> DataLoader<'static, T> {
+  pub fn shared<D>(dataset: Arc<D>, batch_size: usize) -> Result<Self, String>
+  where
+    D: Dataset<T> + Send + Sync + 'static
+  {
+    DataLoader::with_source(Source::Shared(dataset), batch_size)
+  }
+}
+
+impl<'a, T: Float> DataLoader<'a, T> {
+  pub fn new(dataset: &'a dyn Dataset<T>, batch_size: usize) -> Result<Self, String> {
+    DataLoader::with_source(Source::Borrowed(dataset), batch_size)
+  }
+
+  fn with_source(source: Source<'a, T>, batch_size: usize) -> Result<Self, String> {
+    if batch_size == 0 {
+      return Err("DataLoader: batch_size must be positive".to_string());
+    }
+    Ok(DataLoader {
+      source,
+      batch_size,
+      shuffle: false,
+      drop_last: false,
+      seed: None,
+      transform: None,
+      num_workers: 0,
+      prefetch: 2,
+      epoch: Cell::new(0)
+    })
+  }
+
+  pub fn dataset(&self) -> &dyn Dataset<T> {
+    match &self.source {
+      Source::Borrowed(dataset) => *dataset,
+      Source::Shared(dataset) => dataset.as_ref()
+    }
+  }
+
+  pub fn len(&self) -> usize {
+    let samples = self.dataset().len();
+    if self.drop_last {
+      samples / self.batch_size
+    } else {
+      samples.div_ceil(self.batch_size)
+    }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  pub fn epoch(&self) -> u64 {
+    self.epoch.get()
+  }
+
+  pub fn set_epoch(&self, epoch: u64) {
+    self.epoch.set(epoch);
+  }
+
+  pub fn set_transform<F: Transform<T> + 'static>(&mut self, transform: F) {
+    self.transform = Some(Arc::new(transform));
+  }
+
+  fn order(&self) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..self.dataset().len()).collect();
+    if self.shuffle {
+      match self.seed {
+        Some(seed) => Rng::seed_from_u64(seed.wrapping_add(self.epoch.get())).shuffle(&mut order),
+        None => with_rng(|rng| rng.shuffle(&mut order))
+      }
+    }
+    self.epoch.set(self.epoch.get() + 1);
+    order
+  }
+
+  pub fn iter(&self) -> Box<dyn Iterator<Item = Result<Batch<T>, String>> + '_> {
+    let order = self.order();
+    let jobs: Vec<Job> = order
+      .chunks(self.batch_size)
+      .take(self.len())
+      .map(|indices| Job { indices: indices.to_vec(), seed: self.transform.as_ref().map(|_| with_rng(|rng| rng.next_u64())) })
+      .collect();
+    let transform = self.transform.as_deref();
+    match &self.source {
+      _ if self.num_workers == 0 => Box::new(jobs.into_iter().map(move |job| job.run(self.dataset(), transform))),
+      Source::Borrowed(_) => Box::new(std::iter::once(Err("DataLoader: num_workers requires a dataset created with DataLoader::shared".to_string()))),
+      Source::Shared(dataset) => Box::new(Prefetcher::spawn(dataset, self.transform.as_ref(), jobs, self.num_workers, self.prefetch))
+    }
+  }
+
+  pub fn matrices(&self) -> impl Iterator<Item = Result<(Matrix<T>, Matrix<T>), String>> + '_ {
+    let to_matrix = |tensor: Tensor<T>| -> Result<Matrix<T>, String> {
+      let rows = tensor.shape()[0];
+      let tensor = tensor.reshape(&[rows, tensor.len() / rows.max(1)])?;
+      Matrix::try_from(tensor)
+    };
+    self.iter().map(move |batch| {
+      let (features, labels) = batch?;
+      Ok((to_matrix(features)?, to_matrix(labels)?))
+    })
+  }
+}
+
+impl<T: Float> BatchSource<T> for DataLoader<'_, T> {
+  fn batches(&self) -> Box<dyn Iterator<Item = Result<Batch<T>, String>> + '_> {
+    self.iter()
+  }
+}
+
+struct Job {
+  indices: Vec<usize>,
+  seed: Option<u64>
+}
+
+impl Job {
+  fn run<T: Float>(&self, dataset: &dyn Dataset<T>, transform: Option<&dyn Transform<T>>) -> Result<Batch<T>, String> {
+    let batch = dataset.get_batch(&self.indices)?;
+    match (transform, self.seed) {
+      (Some(transform), Some(seed)) => with_seed(seed, || augment(transform, batch)),
+      (Some(transform), None) => augment(transform, batch),
+      (None, _) => Ok(batch)
+    }
+  }
+}
+
+fn augment<T: Float>(transform: &dyn Transform<T>, batch: Batch<T>) -> Result<Batch<T>, String> {
+  let (features, labels) = batch;
+  let samples = (0..features.shape()[0])
+    .map(|i| transform.apply(&features.narrow(0, i, 1)?.reshape(&features.shape()[1..])?))
+    .collect::<Result<Vec<_>, String>>()?;
+  let samples: Vec<&Tensor<T>> = samples.iter().collect();
+  Ok((Tensor::stack(&samples, 0)?, labels))
+}
+
+type BatchReceiver<T> = Receiver<Result<Batch<T>, String>>;
+
+struct Prefetcher<T: Float> {
+  receivers: Vec<BatchReceiver<T>>,
+  workers: Vec<JoinHandle<()>>,
+  next: usize,
+  total: usize
+}
+
+impl<T: Float> Prefetcher<T> {
+  fn spawn(
+    dataset: &Arc<dyn Dataset<T> + Send + Sync>,
+    transform: Option<&Arc<dyn Transform<T>>>,
+    jobs: Vec<Job>,
+    num_workers: usize,
+    prefetch: usize
+  ) -> Self {
+    let total = jobs.len();
+    let num_workers = num_workers.min(total).max(1);
+    let mut queues: Vec<Vec<Job>> = (0..num_workers).map(|_| Vec::new()).collect();
+    for (i, job) in jobs.into_iter().enumerate() {
+      queues[i % num_workers].push(job);
+    }
+
+    let mut receivers = Vec::with_capacity(num_workers);
+    let mut workers = Vec::with_capacity(num_workers);
+    for queue in queues {
+      let (sender, receiver) = sync_channel(prefetch);
+      let dataset = Arc::clone(dataset);
+      let transform = transform.cloned();
+      workers.push(thread::spawn(move || {
+        for job in queue {
+          if sender.send(job.run(dataset.as_ref(), transform.as_deref())).is_err() {
+            break;
+          }
+        }
+      }));
+      receivers.push(receiver);
+    }
+    Prefetcher { receivers, workers, next: 0, total }
+  }
+}
+
+impl<T: Float> Iterator for Prefetcher<T> {
+  type Item = Result<Batch<T>, String>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    if self.next >= self.total {
+      return None;
+    }
+    let receiver = &self.receivers[self.next % self.receivers.len()];
+    self.next += 1;
+    Some(receiver.recv().unwrap_or_else(|_| Err("DataLoader: prefetch worker stopped unexpectedly".to_string())))
+  }
+}
+
+impl<T: Float> Drop for Prefetcher<T> {
+  fn drop(&mut self) {
+    self.receivers.clear();
+    for worker in self.workers.drain(..) {
+      let _ = worker.join();
+    }
+  }
+}
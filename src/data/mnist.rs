@@ -0,0 +1,131 @@
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+use super::TensorDataset;
+use crate::math::scalar::Float;
+use crate::tensor::Tensor;
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+fn element_size(code: u8) -> Result<usize, String> {
+  match code {
+    0x08 | 0x09 => Ok(1),
+    0x0B => Ok(2),
+    0x0C | 0x0D => Ok(4),
+    0x0E => Ok(8),
+    _ => Err(format!("IDX: unknown element type 0x{:02X}", code))
+  }
+}
+
+fn decode(code: u8, bytes: &[u8]) -> f64 {
+  match code {
+    0x08 => bytes[0] as f64,
+    0x09 => bytes[0] as i8 as f64,
+    0x0B => i16::from_be_bytes([bytes[0], bytes[1]]) as f64,
+    0x0C => i32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+    0x0D => f32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+    _ => f64::from_be_bytes(bytes.try_into().expect("eight bytes per f64 element"))
+  }
+}
+
+pub fn read_idx<T, R>(mut reader: R) -> Result<Tensor<T>, String>
+where
+  T: Float,
+  R: Read
+{
+  let mut magic = [0u8; 4];
+  reader.read_exact(&mut magic).map_err(|e| e.to_string())?;
+  if magic[..2] == GZIP_MAGIC {
+    return Err("IDX: input is gzip-compressed, use load_idx or enable the gzip feature".to_string());
+  }
+  if magic[0] != 0 || magic[1] != 0 {
+    return Err(format!("IDX: bad magic number {:02X?}", magic));
+  }
+  let (code, ndim) = (magic[2], magic[3] as usize);
+  let size = element_size(code)?;
+
+  let mut dims = vec![0u8; 4 * ndim];
+  reader.read_exact(&mut dims).map_err(|e| e.to_string())?;
+  let shape: Vec<usize> = dims.chunks(4).map(|d| u32::from_be_bytes([d[0], d[1], d[2], d[3]]) as usize).collect();
+
+  let len = shape
+    .iter()
+    .try_fold(size, |len, &dim| len.checked_mul(dim))
+    .ok_or_else(|| format!("IDX: shape {:?} is too large", shape))?;
+  let mut bytes = Vec::new();
+  reader.take(len as u64).read_to_end(&mut bytes).map_err(|e| e.to_string())?;
+  if bytes.len() != len {
+    return Err(format!("IDX: truncated data: expected {} bytes, found {}", len, bytes.len()));
+  }
+  Tensor::from_vec(&shape, bytes.chunks(size).map(|chunk| T::from_f64(decode(code, chunk))).collect())
+}
+
+pub fn load_idx<T, P>(path: P) -> Result<Tensor<T>, String>
+where
+  T: Float,
+  P: AsRef<Path>
+{
+  let path = path.as_ref();
+  let mut bytes = Vec::new();
+  File::open(path)
+    .and_then(|file| BufReader::new(file).read_to_end(&mut bytes))
+    .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+
+  if !bytes.starts_with(&GZIP_MAGIC) {
+    return read_idx(bytes.as_slice());
+  }
+  #[cfg(feature = "gzip")]
+  {
+    read_idx(flate2::read::GzDecoder::new(bytes.as_slice())).map_err(|e| format!("{}: {}", path.display(), e))
+  }
+  #[cfg(not(feature = "gzip"))]
+  {
+    Err(format!("{}: gzip-compressed IDX files need the gzip feature", path.display()))
+  }
+}
+
+fn find_file(dir: &Path, stem: &str) -> Result<std::path::PathBuf, String> {
+  [stem.to_string(), format!("{}.gz", stem), stem.replacen("-idx", ".idx", 1)]
+    .iter()
+    .map(|name| dir.join(name))
+    .find(|path| path.is_file())
+    .ok_or_else(|| format!("MNIST: no {} file in {}", stem, dir.display()))
+}
+
+pub fn load_images<T, P>(path: P) -> Result<Tensor<T>, String>
+where
+  T: Float,
+  P: AsRef<Path>
+{
+  let raw: Tensor<T> = load_idx(path)?;
+  let &[count, height, width] = raw.shape() else {
+    return Err(format!("MNIST: expected images of shape (N, H, W), found {:?}", raw.shape()));
+  };
+  let scale = T::from_f64(1.0 / 255.0);
+  raw.map(|&x| x * scale).reshape(&[count, 1, height, width])
+}
+
+pub fn load_labels<T, P>(path: P) -> Result<Tensor<T>, String>
+where
+  T: Float,
+  P: AsRef<Path>
+{
+  let labels: Tensor<T> = load_idx(path)?;
+  if labels.ndim() != 1 {
+    return Err(format!("MNIST: expected a label vector, found shape {:?}", labels.shape()));
+  }
+  Ok(labels)
+}
+
+pub fn load_mnist<T, P>(dir: P, train: bool) -> Result<TensorDataset<T>, String>
+where
+  T: Float,
+  P: AsRef<Path>
+{
+  let prefix = if train { "train" } else { "t10k" };
+  let dir = dir.as_ref();
+  let images = load_images(find_file(dir, &format!("{}-images-idx3-ubyte", prefix))?)?;
+  let labels = load_labels(find_file(dir, &format!("{}-labels-idx1-ubyte", prefix))?)?;
+  TensorDataset::new(images, labels)
+}
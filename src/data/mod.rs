@@ -0,0 +1,118 @@
+use crate::math::matrix::Matrix;
+use crate::math::scalar::Float;
+use crate::tensor::Tensor;
+use crate::train::Batch;
+
+pub mod cache;
+pub mod loader;
+pub mod mnist;
+pub mod split;
+pub mod stream;
+pub mod tabular;
+pub mod text;
+pub mod transforms;
+
+pub use cache::{fingerprint, materialize, DatasetCache, Fingerprint};
+pub use loader::DataLoader;
+pub use split::{random_split, stratified_split, Subset};
+pub use stream::{IterableDataset, ShardFormat, ShardedDataset, StreamLoader, StreamState};
+pub use tabular::{ColumnInfo, ColumnType, TabularDataset, TabularOptions};
+pub use text::{TextBatch, TextDataset, TextFormat, TextLoader, TextOptions};
+pub use transforms::{ColorJitter, Compose, Normalize, RandomCrop, RandomHorizontalFlip, Resize, Transform};
+
+pub type Sample<T> = (Tensor<T>, Tensor<T>);
+
+pub trait Dataset<T: Float = f64> {
+  fn len(&self) -> usize;
+
+  fn get(&self, index: usize) -> Result<Sample<T>, String>;
+
+  fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  fn get_batch(&self, indices: &[usize]) -> Result<Batch<T>, String> {
+    let samples = indices.iter().map(|&index| self.get(index)).collect::<Result<Vec<_>, String>>()?;
+    let features: Vec<&Tensor<T>> = samples.iter().map(|(features, _)| features).collect();
+    let labels: Vec<&Tensor<T>> = samples.iter().map(|(_, label)| label).collect();
+    Ok((Tensor::stack(&features, 0)?, Tensor::stack(&labels, 0)?))
+  }
+}
+
+fn check_index(index: usize, len: usize) -> Result<(), String> {
+  if index >= len {
+    return Err(format!("Dataset: index {} out of range for {} samples", index, len));
+  }
+  Ok(())
+}
+
+#[derive(Clone, Debug)]
+pub struct TensorDataset<T: Float = f64> {
+  features: Tensor<T>,
+  labels: Tensor<T>
+}
+
+impl<T: Float> TensorDataset<T> {
+  pub fn new(features: Tensor<T>, labels: Tensor<T>) -> Result<Self, String> {
+    match (features.shape().first(), labels.shape().first()) {
+      (Some(a), Some(b)) if a == b => Ok(TensorDataset { features, labels }),
+      _ => Err(format!(
+        "TensorDataset: features {:?} and labels {:?} need a shared sample axis",
+        features.shape(),
+        labels.shape()
+      ))
+    }
+  }
+
+  pub fn from_matrices(features: Matrix<T>, labels: Matrix<T>) -> Result<Self, String> {
+    TensorDataset::new(features.into(), labels.into())
+  }
+
+  pub fn features(&self) -> &Tensor<T> {
+    &self.features
+  }
+
+  pub fn labels(&self) -> &Tensor<T> {
+    &self.labels
+  }
+}
+
+impl<T: Float> Dataset<T> for TensorDataset<T> {
+  fn len(&self) -> usize {
+    self.features.shape()[0]
+  }
+
+  fn get(&self, index: usize) -> Result<Sample<T>, String> {
+    check_index(index, self.len())?;
+    let row = |tensor: &Tensor<T>| tensor.index_select(0, &[index])?.reshape(&tensor.shape()[1..]);
+    Ok((row(&self.features)?, row(&self.labels)?))
+  }
+
+  fn get_batch(&self, indices: &[usize]) -> Result<Batch<T>, String> {
+    for &index in indices {
+      check_index(index, self.len())?;
+    }
+    Ok((self.features.index_select(0, indices)?, self.labels.index_select(0, indices)?))
+  }
+}
+
+impl<T: Float> Dataset<T> for [Sample<T>] {
+  fn len(&self) -> usize {
+    <[Sample<T>]>::len(self)
+  }
+
+  fn get(&self, index: usize) -> Result<Sample<T>, String> {
+    check_index(index, Dataset::<T>::len(self))?;
+    Ok(self[index].clone())
+  }
+}
+
+impl<T: Float> Dataset<T> for Vec<Sample<T>> {
+  fn len(&self) -> usize {
+    Vec::len(self)
+  }
+
+  fn get(&self, index: usize) -> Result<Sample<T>, String> {
+    Dataset::get(self.as_slice(), index)
+  }
+}
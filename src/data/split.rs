@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+
+use super::{Dataset, Sample};
+use crate::math::scalar::Float;
+use crate::random::{with_rng, Rng};
+use crate::train::Batch;
+
+pub struct Subset<'a, T: Float = f64> {
+  dataset: &'a dyn Dataset<T>,
+  indices: Vec<usize>
+}
+
+impl<'a, T: Float> Subset<'a, T> {
+  pub fn new(dataset: &'a dyn Dataset<T>, indices: Vec<usize>) -> Result<Self, String> {
+    if let Some(&index) = indices.iter().find(|&&index| index >= dataset.len()) {
+      return Err(format!("Subset: index {} out of range for {} samples", index, dataset.len()));
+    }
+    Ok(Subset { dataset, indices })
+  }
+
+  pub fn indices(&self) -> &[usize] {
+    &self.indices
+  }
+}
+
+impl<T: Float> Dataset<T> for Subset<'_, T> {
+  fn len(&self) -> usize {
+    self.indices.len()
+  }
+
+  fn get(&self, index: usize) -> Result<Sample<T>, String> {
+    let &inner = self
+      .indices
+      .get(index)
+      .ok_or_else(|| format!("Dataset: index {} out of range for {} samples", index, self.indices.len()))?;
+    self.dataset.get(inner)
+  }
+
+  fn get_batch(&self, indices: &[usize]) -> Result<Batch<T>, String> {
+    let inner = indices
+      .iter()
+      .map(|&index| {
+        self
+          .indices
+          .get(index)
+          .copied()
+          .ok_or_else(|| format!("Dataset: index {} out of range for {} samples", index, self.indices.len()))
+      })
+      .collect::<Result<Vec<_>, String>>()?;
+    self.dataset.get_batch(&inner)
+  }
+}
+
+fn shuffled(mut indices: Vec<usize>, rng: &mut Rng) -> Vec<usize> {
+  rng.shuffle(&mut indices);
+  indices
+}
+
+fn split_sizes(len: usize, fractions: &[f64]) -> Result<Vec<usize>, String> {
+  let total: f64 = fractions.iter().sum();
+  if fractions.is_empty() || fractions.iter().any(|&f| f.is_nan() || f <= 0.0) || (total - 1.0).abs() > 1e-9 {
+    return Err(format!("split: fractions must be positive and sum to 1, found {:?}", fractions));
+  }
+
+  let exact: Vec<f64> = fractions.iter().map(|f| f * len as f64).collect();
+  let mut sizes: Vec<usize> = exact.iter().map(|x| x.floor() as usize).collect();
+  let mut order: Vec<usize> = (0..fractions.len()).collect();
+  order.sort_by(|&a, &b| (exact[b] - exact[b].floor()).total_cmp(&(exact[a] - exact[a].floor())).then(a.cmp(&b)));
+  let missing = len - sizes.iter().sum::<usize>();
+  for &part in order.iter().take(missing) {
+    sizes[part] += 1;
+  }
+  Ok(sizes)
+}
+
+fn partition(indices: &[usize], sizes: &[usize]) -> Vec<Vec<usize>> {
+  let mut start = 0;
+  sizes
+    .iter()
+    .map(|&size| {
+      let part = indices[start..start + size].to_vec();
+      start += size;
+      part
+    })
+    .collect()
+}
+
+fn seeded<R, F>(seed: Option<u64>, f: F) -> R
+where
+  F: FnOnce(&mut Rng) -> R
+{
+  match seed {
+    Some(seed) => f(&mut Rng::seed_from_u64(seed)),
+    None => with_rng(f)
+  }
+}
+
+pub fn random_indices(len: usize, fractions: &[f64], seed: Option<u64>) -> Result<Vec<Vec<usize>>, String> {
+  let sizes = split_sizes(len, fractions)?;
+  let order = seeded(seed, |rng| shuffled((0..len).collect(), rng));
+  Ok(partition(&order, &sizes))
+}
+
+pub fn stratified_indices<T: Float>(dataset: &dyn Dataset<T>, fractions: &[f64], seed: Option<u64>) -> Result<Vec<Vec<usize>>, String> {
+  split_sizes(0, fractions)?;
+  let mut classes: HashMap<Vec<u64>, Vec<usize>> = HashMap::new();
+  let mut keys = Vec::new();
+  for index in 0..dataset.len() {
+    let (_, label) = dataset.get(index)?;
+    let key: Vec<u64> = label.iter().map(|x| x.to_f64().to_bits()).collect();
+    classes.entry(key.clone()).or_insert_with(|| {
+      keys.push(key);
+      Vec::new()
+    }).push(index);
+  }
+
+  seeded(seed, |rng| {
+    let mut parts = vec![Vec::new(); fractions.len()];
+    for key in &keys {
+      let members = shuffled(classes.remove(key).unwrap_or_default(), rng);
+      let sizes = split_sizes(members.len(), fractions)?;
+      for (part, chunk) in parts.iter_mut().zip(partition(&members, &sizes)) {
+        part.extend(chunk);
+      }
+    }
+    Ok(parts.into_iter().map(|part| shuffled(part, rng)).collect())
+  })
+}
+
+fn subsets<'a, T: Float>(dataset: &'a dyn Dataset<T>, parts: Vec<Vec<usize>>) -> Vec<Subset<'a, T>> {
+  parts.into_iter().map(|indices| Subset { dataset, indices }).collect()
+}
+
+pub fn random_split<'a, T: Float>(dataset: &'a dyn Dataset<T>, fractions: &[f64], seed: Option<u64>) -> Result<Vec<Subset<'a, T>>, String> {
+  Ok(subsets(dataset, random_indices(dataset.len(), fractions, seed)?))
+}
+
+pub fn stratified_split<'a, T: Float>(dataset: &'a dyn Dataset<T>, fractions: &[f64], seed: Option<u64>) -> Result<Vec<Subset<'a, T>>, String> {
+  Ok(subsets(dataset, stratified_indices(dataset, fractions, seed)?))
+}
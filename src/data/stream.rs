@@ -0,0 +1,257 @@
+use std::cell::Cell;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use super::Sample;
+use crate::io::csv::{record_lines, split_record, CsvOptions};
+use crate::io::npy::read_npy;
+use crate::math::scalar::Float;
+use crate::random::Rng;
+use crate::tensor::Tensor;
+use crate::train::{Batch, BatchSource};
+
+pub type SampleIter<'a, T> = Box<dyn Iterator<Item = Result<Sample<T>, String>> + 'a>;
+
+type RowIter = Box<dyn Iterator<Item = Result<Vec<f64>, String>>>;
+
+pub trait IterableDataset<T: Float = f64> {
+  fn num_shards(&self) -> usize;
+
+  fn read_shard(&self, shard: usize) -> Result<SampleIter<'_, T>, String>;
+}
+
+#[derive(Clone, Debug)]
+pub enum ShardFormat {
+  Csv(CsvOptions),
+  Npy,
+  #[cfg(feature = "arrow")]
+  Parquet(crate::io::arrow::ArrowOptions)
+}
+
+#[derive(Clone, Debug)]
+pub struct ShardedDataset {
+  shards: Vec<PathBuf>,
+  format: ShardFormat,
+  pub target: Option<usize>
+}
+
+fn split_row<T: Float>(row: &[f64], target: Option<usize>) -> Result<Sample<T>, String> {
+  let features: Vec<T> = row.iter().enumerate().filter(|&(i, _)| Some(i) != target).map(|(_, &x)| T::from_f64(x)).collect();
+  let label = match target {
+    Some(column) => {
+      let &value = row.get(column).ok_or_else(|| format!("ShardedDataset: target column {} out of range for {} columns", column, row.len()))?;
+      vec![T::from_f64(value)]
+    }
+    None => Vec::new()
+  };
+  Ok((Tensor::from_vec(&[features.len()], features)?, Tensor::from_vec(&[label.len()], label)?))
+}
+
+fn csv_rows(path: &Path, options: &CsvOptions) -> Result<impl Iterator<Item = Result<Vec<f64>, String>>, String> {
+  let file = File::open(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+  let options = options.clone();
+  let skip = usize::from(options.has_header);
+  let name = path.display().to_string();
+  Ok(
+    record_lines(file)
+      .filter(|record| record.as_ref().map_or(true, |(_, line)| !line.trim().is_empty()))
+      .skip(skip)
+      .map(move |record| {
+        let (line_no, line) = record?;
+        split_record(&line, &options)
+          .iter()
+          .map(|field| field.parse::<f64>().map_err(|e| format!("{}, line {}: cannot parse '{}': {}", name, line_no, field, e)))
+          .collect()
+      })
+  )
+}
+
+impl ShardedDataset {
+  pub fn new(shards: Vec<PathBuf>, format: ShardFormat) -> Result<Self, String> {
+    if shards.is_empty() {
+      return Err("ShardedDataset: no shards given".to_string());
+    }
+    Ok(ShardedDataset { shards, format, target: None })
+  }
+
+  pub fn shards(&self) -> &[PathBuf] {
+    &self.shards
+  }
+
+  fn rows(&self, path: &Path) -> Result<RowIter, String> {
+    match &self.format {
+      ShardFormat::Csv(options) => Ok(Box::new(csv_rows(path, options)?)),
+      ShardFormat::Npy => {
+        let file = File::open(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+        let matrix = read_npy::<f64, _>(BufReader::new(file))?;
+        let cols = matrix.cols.max(1);
+        Ok(Box::new(matrix.data.chunks(cols).map(|row| Ok(row.to_vec())).collect::<Vec<_>>().into_iter()))
+      }
+      #[cfg(feature = "arrow")]
+      ShardFormat::Parquet(options) => {
+        let batches = crate::io::arrow::parquet_batches(path.to_path_buf(), options)?;
+        Ok(Box::new(batches.flat_map(|batch| match batch {
+          Ok(matrix) => {
+            let cols = matrix.cols.max(1);
+            matrix.data.chunks(cols).map(|row| Ok(row.to_vec())).collect::<Vec<_>>()
+          }
+          Err(e) => vec![Err(e)]
+        })))
+      }
+    }
+  }
+}
+
+impl<T: Float> IterableDataset<T> for ShardedDataset {
+  fn num_shards(&self) -> usize {
+    self.shards.len()
+  }
+
+  fn read_shard(&self, shard: usize) -> Result<SampleIter<'_, T>, String> {
+    let path = self.shards.get(shard).ok_or_else(|| format!("ShardedDataset: shard {} out of range for {} shards", shard, self.shards.len()))?;
+    let target = self.target;
+    Ok(Box::new(self.rows(path)?.map(move |row| split_row(&row?, target))))
+  }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StreamState {
+  pub epoch: u64,
+  pub shard: usize,
+  pub offset: usize
+}
+
+pub struct StreamLoader<'a, T: Float = f64> {
+  dataset: &'a dyn IterableDataset<T>,
+  pub batch_size: usize,
+  pub drop_last: bool,
+  pub shuffle_shards: bool,
+  pub seed: u64,
+  state: Cell<StreamState>
+}
+
+impl<'a, T: Float> StreamLoader<'a, T> {
+  pub fn new(dataset: &'a dyn IterableDataset<T>, batch_size: usize) -> Result<Self, String> {
+    if batch_size == 0 {
+      return Err("StreamLoader: batch_size must be positive".to_string());
+    }
+    Ok(StreamLoader {
+      dataset,
+      batch_size,
+      drop_last: false,
+      shuffle_shards: false,
+      seed: 0,
+      state: Cell::new(StreamState::default())
+    })
+  }
+
+  pub fn state(&self) -> StreamState {
+    self.state.get()
+  }
+
+  pub fn resume(&self, state: StreamState) {
+    self.state.set(state);
+  }
+
+  pub fn shard_order(&self, epoch: u64) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..self.dataset.num_shards()).collect();
+    if self.shuffle_shards {
+      Rng::seed_from_u64(self.seed.wrapping_add(epoch)).shuffle(&mut order);
+    }
+    order
+  }
+
+  pub fn iter(&self) -> StreamBatches<'_, 'a, T> {
+    let state = self.state.get();
+    StreamBatches {
+      loader: self,
+      order: self.shard_order(state.epoch),
+      current: None,
+      done: false
+    }
+  }
+}
+
+pub struct StreamBatches<'l, 'a, T: Float> {
+  loader: &'l StreamLoader<'a, T>,
+  order: Vec<usize>,
+  current: Option<SampleIter<'a, T>>,
+  done: bool
+}
+
+impl<T: Float> StreamBatches<'_, '_, T> {
+  fn next_sample(&mut self) -> Result<Option<Sample<T>>, String> {
+    loop {
+      let mut state = self.loader.state.get();
+      if state.shard >= self.order.len() {
+        self.loader.state.set(StreamState { epoch: state.epoch + 1, shard: 0, offset: 0 });
+        return Ok(None);
+      }
+
+      if self.current.is_none() {
+        let mut samples = self.loader.dataset.read_shard(self.order[state.shard])?;
+        for _ in 0..state.offset {
+          if samples.next().transpose()?.is_none() {
+            break;
+          }
+        }
+        self.current = Some(samples);
+      }
+
+      match self.current.as_mut().and_then(Iterator::next).transpose()? {
+        Some(sample) => {
+          state.offset += 1;
+          self.loader.state.set(state);
+          return Ok(Some(sample));
+        }
+        None => {
+          self.current = None;
+          self.loader.state.set(StreamState { epoch: state.epoch, shard: state.shard + 1, offset: 0 });
+        }
+      }
+    }
+  }
+
+  fn next_batch(&mut self) -> Result<Option<Batch<T>>, String> {
+    let mut samples = Vec::with_capacity(self.loader.batch_size);
+    while samples.len() < self.loader.batch_size {
+      match self.next_sample()? {
+        Some(sample) => samples.push(sample),
+        None => {
+          self.done = true;
+          break;
+        }
+      }
+    }
+    if samples.is_empty() || (self.done && self.loader.drop_last && samples.len() < self.loader.batch_size) {
+      return Ok(None);
+    }
+
+    let features: Vec<&Tensor<T>> = samples.iter().map(|(features, _)| features).collect();
+    let labels: Vec<&Tensor<T>> = samples.iter().map(|(_, label)| label).collect();
+    Ok(Some((Tensor::stack(&features, 0)?, Tensor::stack(&labels, 0)?)))
+  }
+}
+
+impl<T: Float> Iterator for StreamBatches<'_, '_, T> {
+  type Item = Result<Batch<T>, String>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    if self.done {
+      return None;
+    }
+    let batch = self.next_batch();
+    if batch.is_err() {
+      self.done = true;
+    }
+    batch.transpose()
+  }
+}
+
+impl<T: Float> BatchSource<T> for StreamLoader<'_, T> {
+  fn batches(&self) -> Box<dyn Iterator<Item = Result<Batch<T>, String>> + '_> {
+    Box::new(self.iter())
+  }
+}
@@ -0,0 +1,188 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use super::{Dataset, Sample};
+use crate::io::csv::{read_records, CsvOptions, CsvRecords};
+use crate::math::matrix::Matrix;
+use crate::math::scalar::Float;
+use crate::preprocess::{HandleUnknown, LabelEncoder, OneHotEncoder};
+use crate::tensor::Tensor;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColumnType {
+  Numeric,
+  Categorical
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColumnInfo {
+  pub name: String,
+  pub kind: ColumnType,
+  pub missing: usize,
+  pub categories: Vec<String>
+}
+
+#[derive(Clone, Debug)]
+pub struct TabularOptions {
+  pub csv: CsvOptions,
+  pub target: Option<String>,
+  pub categorical: Vec<String>,
+  pub missing_values: Vec<String>
+}
+
+impl TabularOptions {
+  pub fn new() -> Self {
+    TabularOptions {
+      csv: CsvOptions::default(),
+      target: None,
+      categorical: Vec::new(),
+      missing_values: ["", "NA", "N/A", "NaN", "nan", "null", "?"].iter().map(|s| s.to_string()).collect()
+    }
+  }
+
+  fn is_missing(&self, field: &str) -> bool {
+    self.missing_values.iter().any(|missing| missing == field)
+  }
+}
+
+impl Default for TabularOptions {
+  fn default() -> Self {
+    TabularOptions::new()
+  }
+}
+
+#[derive(Clone, Debug)]
+pub struct TabularDataset<T: Float = f64> {
+  features: Matrix<T>,
+  target: Option<Matrix<T>>,
+  columns: Vec<ColumnInfo>,
+  feature_names: Vec<String>,
+  target_classes: Option<Vec<String>>
+}
+
+fn infer_column(name: &str, values: &[&str], options: &TabularOptions) -> ColumnInfo {
+  let present: Vec<&str> = values.iter().copied().filter(|v| !options.is_missing(v)).collect();
+  let numeric = !options.categorical.iter().any(|c| c == name) && present.iter().all(|v| v.parse::<f64>().is_ok());
+  ColumnInfo {
+    name: name.to_string(),
+    kind: if numeric { ColumnType::Numeric } else { ColumnType::Categorical },
+    missing: values.len() - present.len(),
+    categories: Vec::new()
+  }
+}
+
+impl<T: Float> TabularDataset<T> {
+  pub fn from_csv<R: Read>(reader: R, options: &TabularOptions) -> Result<Self, String> {
+    let CsvRecords { headers, rows: records } = read_records(reader, &options.csv)?;
+    let width = headers.as_ref().or(records.first()).map_or(0, Vec::len);
+    let names = headers.unwrap_or_else(|| (0..width).map(|i| format!("column_{}", i)).collect());
+    if records.is_empty() {
+      return Err("TabularDataset: no data rows".to_string());
+    }
+
+    let target_index = match &options.target {
+      Some(target) => Some(names.iter().position(|name| name == target).ok_or_else(|| format!("TabularDataset: no target column '{}'", target))?),
+      None => None
+    };
+
+    let mut columns = Vec::new();
+    let mut blocks: Vec<Vec<T>> = Vec::new();
+    let mut feature_names = Vec::new();
+    let mut target = None;
+    let mut target_classes = None;
+    for (index, name) in names.iter().enumerate() {
+      let values: Vec<&str> = records.iter().map(|record| record[index].as_str()).collect();
+      let mut info = infer_column(name, &values, options);
+      let numeric = |v: &&str| if options.is_missing(v) { T::from_f64(f64::NAN) } else { T::from_f64(v.parse::<f64>().unwrap_or(f64::NAN)) };
+
+      if Some(index) == target_index {
+        let column = match info.kind {
+          ColumnType::Numeric => values.iter().map(numeric).collect(),
+          ColumnType::Categorical => {
+            if info.missing > 0 {
+              return Err(format!("TabularDataset: target column '{}' has {} missing values", name, info.missing));
+            }
+            let mut encoder = LabelEncoder::new();
+            let labels: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+            let encoded = encoder.fit_transform(&labels)?;
+            info.categories = encoder.classes().to_vec();
+            target_classes = Some(info.categories.clone());
+            encoded.into_iter().map(|label| T::from_f64(label as f64)).collect()
+          }
+        };
+        target = Some(Matrix::from_vec(records.len(), 1, column)?);
+      } else {
+        match info.kind {
+          ColumnType::Numeric => {
+            blocks.push(values.iter().map(numeric).collect());
+            feature_names.push(name.clone());
+          }
+          ColumnType::Categorical => {
+            let present: Vec<[&str; 1]> = values.iter().filter(|v| !options.is_missing(v)).map(|&v| [v]).collect();
+            let mut encoder = OneHotEncoder::new();
+            encoder.handle_unknown = HandleUnknown::Ignore;
+            encoder.fit(&present).map_err(|_| format!("TabularDataset: column '{}' has no values", name))?;
+            let rows: Vec<[&str; 1]> = values.iter().map(|&v| [if options.is_missing(v) { "" } else { v }]).collect();
+            let encoded: Matrix<T> = encoder.transform(&rows)?;
+            info.categories = encoder.categories()[0].clone();
+            for (col, category) in info.categories.iter().enumerate() {
+              blocks.push((0..encoded.rows).map(|row| encoded[(row, col)]).collect());
+              feature_names.push(format!("{}={}", name, category));
+            }
+          }
+        }
+      }
+      columns.push(info);
+    }
+
+    let features = Matrix::from_fn(records.len(), blocks.len(), |row, col| blocks[col][row]);
+    Ok(TabularDataset { features, target, columns, feature_names, target_classes })
+  }
+
+  pub fn from_csv_path<P: AsRef<Path>>(path: P, options: &TabularOptions) -> Result<Self, String> {
+    let file = File::open(path.as_ref()).map_err(|e| format!("Cannot read {}: {}", path.as_ref().display(), e))?;
+    Self::from_csv(file, options)
+  }
+
+  pub fn features(&self) -> &Matrix<T> {
+    &self.features
+  }
+
+  pub fn target(&self) -> Option<&Matrix<T>> {
+    self.target.as_ref()
+  }
+
+  pub fn columns(&self) -> &[ColumnInfo] {
+    &self.columns
+  }
+
+  pub fn feature_names(&self) -> &[String] {
+    &self.feature_names
+  }
+
+  pub fn target_classes(&self) -> Option<&[String]> {
+    self.target_classes.as_deref()
+  }
+
+  fn row(matrix: &Matrix<T>, index: usize) -> Tensor<T> {
+    Tensor::from_fn(&[matrix.cols], |i| matrix[(index, i[0])])
+  }
+}
+
+impl<T: Float> Dataset<T> for TabularDataset<T> {
+  fn len(&self) -> usize {
+    self.features.rows
+  }
+
+  fn get(&self, index: usize) -> Result<Sample<T>, String> {
+    if index >= self.len() {
+      return Err(format!("Dataset: index {} out of range for {} samples", index, self.len()));
+    }
+    let target = match &self.target {
+      Some(target) => Self::row(target, index),
+      None => Tensor::from_fn(&[0], |_| T::zero())
+    };
+    Ok((Self::row(&self.features, index), target))
+  }
+}
@@ -0,0 +1,271 @@
+use std::cell::Cell;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+
+use super::{Dataset, Sample};
+use crate::math::scalar::Float;
+use crate::nn::{sequence_mask, PadSide};
+use crate::preprocess::LabelEncoder;
+use crate::random::{with_rng, Rng};
+use crate::tensor::Tensor;
+use crate::text::Tokenizer;
+use crate::train::{Batch, BatchSource};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TextFormat {
+  Lines { label_separator: Option<char> },
+  #[cfg(feature = "jsonl")]
+  Jsonl { text_field: String, label_field: Option<String> }
+}
+
+#[derive(Clone, Debug)]
+pub struct TextOptions {
+  pub format: TextFormat,
+  pub max_length: Option<usize>,
+  pub pad_id: usize
+}
+
+impl TextOptions {
+  pub fn new() -> Self {
+    TextOptions { format: TextFormat::Lines { label_separator: None }, max_length: None, pad_id: 0 }
+  }
+}
+
+impl Default for TextOptions {
+  fn default() -> Self {
+    TextOptions::new()
+  }
+}
+
+#[derive(Clone, Debug)]
+pub struct TextBatch<T: Float = f64> {
+  pub ids: Tensor<T>,
+  pub attention_mask: Tensor<T>,
+  pub labels: Tensor<T>
+}
+
+#[derive(Clone, Debug)]
+pub struct TextDataset<T: Float = f64> {
+  ids: Vec<Vec<usize>>,
+  labels: Option<Vec<T>>,
+  label_classes: Option<Vec<String>>,
+  pad_id: usize
+}
+
+type Document = (String, Option<String>);
+
+fn parse_line(line: &str, format: &TextFormat, number: usize) -> Result<Document, String> {
+  match format {
+    TextFormat::Lines { label_separator: None } => Ok((line.to_string(), None)),
+    TextFormat::Lines { label_separator: Some(separator) } => {
+      let (label, text) = line.split_once(*separator).ok_or_else(|| format!("TextDataset: line {} has no label separator {:?}", number, separator))?;
+      Ok((text.to_string(), Some(label.trim().to_string())))
+    }
+    #[cfg(feature = "jsonl")]
+    TextFormat::Jsonl { text_field, label_field } => {
+      let record: serde_json::Value = serde_json::from_str(line).map_err(|e| format!("TextDataset: line {}: {}", number, e))?;
+      let field = |name: &str| -> Result<String, String> {
+        match record.get(name) {
+          Some(serde_json::Value::String(value)) => Ok(value.clone()),
+          Some(serde_json::Value::Number(value)) => Ok(value.to_string()),
+          Some(serde_json::Value::Bool(value)) => Ok(value.to_string()),
+          _ => Err(format!("TextDataset: line {} has no string field '{}'", number, name))
+        }
+      };
+      Ok((field(text_field)?, label_field.as_deref().map(field).transpose()?))
+    }
+  }
+}
+
+impl<T: Float> TextDataset<T> {
+  pub fn from_reader<R: Read>(reader: R, tokenizer: &dyn Tokenizer, options: &TextOptions) -> Result<Self, String> {
+    let mut documents = Vec::new();
+    for (number, line) in BufReader::new(reader).lines().enumerate() {
+      let line = line.map_err(|e| e.to_string())?;
+      if !line.trim().is_empty() {
+        documents.push(parse_line(&line, &options.format, number + 1)?);
+      }
+    }
+    Self::from_documents(documents, tokenizer, options)
+  }
+
+  pub fn from_path<P: AsRef<Path>>(path: P, tokenizer: &dyn Tokenizer, options: &TextOptions) -> Result<Self, String> {
+    let file = File::open(path.as_ref()).map_err(|e| format!("Cannot read {}: {}", path.as_ref().display(), e))?;
+    Self::from_reader(file, tokenizer, options)
+  }
+
+  pub fn from_texts<S: AsRef<str>>(texts: &[S], labels: Option<&[S]>, tokenizer: &dyn Tokenizer, options: &TextOptions) -> Result<Self, String> {
+    if let Some(labels) = labels {
+      if labels.len() != texts.len() {
+        return Err(format!("TextDataset: {} texts but {} labels", texts.len(), labels.len()));
+      }
+    }
+    let documents = texts
+      .iter()
+      .enumerate()
+      .map(|(i, text)| (text.as_ref().to_string(), labels.map(|labels| labels[i].as_ref().to_string())))
+      .collect();
+    Self::from_documents(documents, tokenizer, options)
+  }
+
+  fn from_documents(documents: Vec<Document>, tokenizer: &dyn Tokenizer, options: &TextOptions) -> Result<Self, String> {
+    if documents.is_empty() {
+      return Err("TextDataset: no documents".to_string());
+    }
+    let ids = documents
+      .iter()
+      .map(|(text, _)| {
+        let mut ids = tokenizer.encode(text)?;
+        if let Some(max_length) = options.max_length {
+          ids.truncate(max_length);
+        }
+        Ok(ids)
+      })
+      .collect::<Result<Vec<_>, String>>()?;
+
+    let labels: Option<Vec<String>> = documents.into_iter().map(|(_, label)| label).collect();
+    let (labels, label_classes) = match labels {
+      None => (None, None),
+      Some(labels) if labels.iter().all(|label| label.parse::<f64>().is_ok()) => (Some(labels.iter().map(|label| T::from_f64(label.parse().unwrap_or(f64::NAN))).collect()), None),
+      Some(labels) => {
+        let mut encoder = LabelEncoder::new();
+        let encoded = encoder.fit_transform(&labels)?;
+        (Some(encoded.into_iter().map(|label| T::from_f64(label as f64)).collect()), Some(encoder.classes().to_vec()))
+      }
+    };
+    Ok(TextDataset { ids, labels, label_classes, pad_id: options.pad_id })
+  }
+
+  pub fn ids(&self, index: usize) -> Option<&[usize]> {
+    self.ids.get(index).map(Vec::as_slice)
+  }
+
+  pub fn lengths(&self) -> Vec<usize> {
+    self.ids.iter().map(Vec::len).collect()
+  }
+
+  pub fn labels(&self) -> Option<&[T]> {
+    self.labels.as_deref()
+  }
+
+  pub fn label_classes(&self) -> Option<&[String]> {
+    self.label_classes.as_deref()
+  }
+
+  pub fn pad_id(&self) -> usize {
+    self.pad_id
+  }
+
+  pub fn collate(&self, indices: &[usize]) -> Result<TextBatch<T>, String> {
+    let rows = indices
+      .iter()
+      .map(|&index| self.ids(index).ok_or_else(|| format!("Dataset: index {} out of range for {} samples", index, self.ids.len())))
+      .collect::<Result<Vec<_>, String>>()?;
+    let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+    let ids = Tensor::from_fn(&[rows.len(), width], |i| T::from_f64(rows[i[0]].get(i[1]).copied().unwrap_or(self.pad_id) as f64));
+    let lengths: Vec<usize> = rows.iter().map(|row| row.len()).collect();
+    let attention_mask = sequence_mask(&lengths, width, PadSide::Post);
+    let labels = match &self.labels {
+      Some(labels) => Tensor::from_vec(&[indices.len()], indices.iter().map(|&index| labels[index]).collect())?,
+      None => Tensor::from_fn(&[indices.len(), 0], |_| T::zero())
+    };
+    Ok(TextBatch { ids, attention_mask, labels })
+  }
+
+  pub fn bucket_indices(&self, batch_size: usize, mut rng: Option<&mut Rng>) -> Vec<Vec<usize>> {
+    let mut order: Vec<usize> = (0..self.ids.len()).collect();
+    if let Some(rng) = rng.as_deref_mut() {
+      rng.shuffle(&mut order);
+    }
+    order.sort_by_key(|&index| self.ids[index].len());
+    let mut batches: Vec<Vec<usize>> = order.chunks(batch_size.max(1)).map(<[usize]>::to_vec).collect();
+    if let Some(rng) = rng {
+      rng.shuffle(&mut batches);
+    }
+    batches
+  }
+}
+
+impl<T: Float> Dataset<T> for TextDataset<T> {
+  fn len(&self) -> usize {
+    self.ids.len()
+  }
+
+  fn get(&self, index: usize) -> Result<Sample<T>, String> {
+    let batch = self.collate(&[index])?;
+    let width = batch.ids.shape()[1];
+    let label = match self.labels {
+      Some(_) => batch.labels.reshape(&[])?,
+      None => Tensor::from_fn(&[0], |_| T::zero())
+    };
+    Ok((batch.ids.reshape(&[width])?, label))
+  }
+
+  fn get_batch(&self, indices: &[usize]) -> Result<Batch<T>, String> {
+    let batch = self.collate(indices)?;
+    Ok((batch.ids, batch.labels))
+  }
+}
+
+pub struct TextLoader<'a, T: Float = f64> {
+  dataset: &'a TextDataset<T>,
+  pub batch_size: usize,
+  pub shuffle: bool,
+  pub bucket_by_length: bool,
+  pub seed: Option<u64>,
+  epoch: Cell<u64>
+}
+
+impl<'a, T: Float> TextLoader<'a, T> {
+  pub fn new(dataset: &'a TextDataset<T>, batch_size: usize) -> Result<Self, String> {
+    if batch_size == 0 {
+      return Err("TextLoader: batch_size must be positive".to_string());
+    }
+    Ok(TextLoader { dataset, batch_size, shuffle: false, bucket_by_length: true, seed: None, epoch: Cell::new(0) })
+  }
+
+  pub fn len(&self) -> usize {
+    self.dataset.len().div_ceil(self.batch_size)
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  pub fn epoch(&self) -> u64 {
+    self.epoch.get()
+  }
+
+  pub fn set_epoch(&self, epoch: u64) {
+    self.epoch.set(epoch);
+  }
+
+  fn order(&self) -> Vec<Vec<usize>> {
+    let epoch = self.epoch.get();
+    self.epoch.set(epoch + 1);
+    let mut rng = match (self.shuffle, self.seed) {
+      (false, _) => None,
+      (true, Some(seed)) => Some(Rng::seed_from_u64(seed.wrapping_add(epoch))),
+      (true, None) => Some(Rng::seed_from_u64(with_rng(|rng| rng.next_u64())))
+    };
+    if self.bucket_by_length {
+      return self.dataset.bucket_indices(self.batch_size, rng.as_mut());
+    }
+    let mut order: Vec<usize> = (0..self.dataset.len()).collect();
+    if let Some(rng) = rng.as_mut() {
+      rng.shuffle(&mut order);
+    }
+    order.chunks(self.batch_size).map(<[usize]>::to_vec).collect()
+  }
+
+  pub fn iter(&self) -> impl Iterator<Item = Result<TextBatch<T>, String>> + '_ {
+    self.order().into_iter().map(move |indices| self.dataset.collate(&indices))
+  }
+}
+
+impl<T: Float> BatchSource<T> for TextLoader<'_, T> {
+  fn batches(&self) -> Box<dyn Iterator<Item = Result<Batch<T>, String>> + '_> {
+    Box::new(self.iter().map(|batch| batch.map(|batch| (batch.ids, batch.labels))))
+  }
+}
@@ -0,0 +1,245 @@
+use crate::math::scalar::Float;
+use crate::random::with_rng;
+use crate::tensor::pad::PadMode;
+use crate::tensor::Tensor;
+
+pub trait Transform<T: Float = f64>: Send + Sync {
+  fn apply(&self, image: &Tensor<T>) -> Result<Tensor<T>, String>;
+}
+
+impl<T, F> Transform<T> for F
+where
+  T: Float,
+  F: Fn(&Tensor<T>) -> Result<Tensor<T>, String> + Send + Sync
+{
+  fn apply(&self, image: &Tensor<T>) -> Result<Tensor<T>, String> {
+    self(image)
+  }
+}
+
+fn image_dims<T>(name: &str, image: &Tensor<T>) -> Result<(usize, usize, usize), String> {
+  match image.shape() {
+    &[c, h, w] => Ok((c, h, w)),
+    shape => Err(format!("{}: expected an image tensor of shape (C, H, W), found {:?}", name, shape))
+  }
+}
+
+fn check_probability(name: &str, p: f64) -> Result<(), String> {
+  if !(0.0..=1.0).contains(&p) {
+    return Err(format!("{}: probability must be in [0, 1], found {}", name, p));
+  }
+  Ok(())
+}
+
+#[derive(Default)]
+pub struct Compose<'a, T: Float = f64> {
+  transforms: Vec<Box<dyn Transform<T> + 'a>>
+}
+
+impl<'a, T: Float> Compose<'a, T> {
+  pub fn new() -> Self {
+    Compose { transforms: Vec::new() }
+  }
+
+  pub fn push<F: Transform<T> + 'a>(&mut self, transform: F) {
+    self.transforms.push(Box::new(transform));
+  }
+
+  pub fn len(&self) -> usize {
+    self.transforms.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.transforms.is_empty()
+  }
+}
+
+impl<T: Float> Transform<T> for Compose<'_, T> {
+  fn apply(&self, image: &Tensor<T>) -> Result<Tensor<T>, String> {
+    let mut image = image.clone();
+    for transform in &self.transforms {
+      image = transform.apply(&image)?;
+    }
+    Ok(image)
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RandomHorizontalFlip {
+  pub p: f64
+}
+
+impl RandomHorizontalFlip {
+  pub fn new(p: f64) -> Result<Self, String> {
+    check_probability("RandomHorizontalFlip", p)?;
+    Ok(RandomHorizontalFlip { p })
+  }
+}
+
+impl<T: Float> Transform<T> for RandomHorizontalFlip {
+  fn apply(&self, image: &Tensor<T>) -> Result<Tensor<T>, String> {
+    let (_, _, width) = image_dims("RandomHorizontalFlip", image)?;
+    if !with_rng(|rng| rng.bernoulli(self.p)) {
+      return Ok(image.clone());
+    }
+    Ok(Tensor::from_fn(image.shape(), |i| *image.get(&[i[0], i[1], width - 1 - i[2]]).expect("index within shape")))
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RandomCrop {
+  pub height: usize,
+  pub width: usize,
+  pub padding: usize
+}
+
+impl RandomCrop {
+  pub fn new(height: usize, width: usize) -> Self {
+    RandomCrop { height, width, padding: 0 }
+  }
+}
+
+impl<T: Float> Transform<T> for RandomCrop {
+  fn apply(&self, image: &Tensor<T>) -> Result<Tensor<T>, String> {
+    let (_, h, w) = image_dims("RandomCrop", image)?;
+    let pad = self.padding;
+    let (h, w) = (h + 2 * pad, w + 2 * pad);
+    if self.height > h || self.width > w {
+      return Err(format!("RandomCrop: crop {}x{} is larger than the padded image {}x{}", self.height, self.width, h, w));
+    }
+
+    let padded = if pad > 0 { image.pad(&[(0, 0), (pad, pad), (pad, pad)], PadMode::Constant(T::zero()))? } else { image.clone() };
+    let (top, left) = with_rng(|rng| (rng.gen_range(h - self.height + 1), rng.gen_range(w - self.width + 1)));
+    Ok(padded.narrow(1, top, self.height)?.narrow(2, left, self.width)?.contiguous())
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Resize {
+  pub height: usize,
+  pub width: usize
+}
+
+impl Resize {
+  pub fn new(height: usize, width: usize) -> Self {
+    Resize { height, width }
+  }
+}
+
+fn source_position(i: usize, scale: f64, size: usize) -> (usize, usize, f64) {
+  let x = ((i as f64 + 0.5) * scale - 0.5).clamp(0.0, (size - 1) as f64);
+  let low = x.floor() as usize;
+  (low, (low + 1).min(size - 1), x - low as f64)
+}
+
+impl<T: Float> Transform<T> for Resize {
+  fn apply(&self, image: &Tensor<T>) -> Result<Tensor<T>, String> {
+    let (channels, h, w) = image_dims("Resize", image)?;
+    if h == 0 || w == 0 || self.height == 0 || self.width == 0 {
+      return Err(format!("Resize: cannot resize {}x{} to {}x{}", h, w, self.height, self.width));
+    }
+
+    let (sy, sx) = (h as f64 / self.height as f64, w as f64 / self.width as f64);
+    let at = |c: usize, y: usize, x: usize| image.get(&[c, y, x]).expect("index within shape").to_f64();
+    Ok(Tensor::from_fn(&[channels, self.height, self.width], |i| {
+      let (y0, y1, fy) = source_position(i[1], sy, h);
+      let (x0, x1, fx) = source_position(i[2], sx, w);
+      let top = at(i[0], y0, x0) * (1.0 - fx) + at(i[0], y0, x1) * fx;
+      let bottom = at(i[0], y1, x0) * (1.0 - fx) + at(i[0], y1, x1) * fx;
+      T::from_f64(top * (1.0 - fy) + bottom * fy)
+    }))
+  }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Normalize {
+  pub mean: Vec<f64>,
+  pub std: Vec<f64>
+}
+
+impl Normalize {
+  pub fn new(mean: Vec<f64>, std: Vec<f64>) -> Result<Self, String> {
+    if mean.is_empty() || mean.len() != std.len() {
+      return Err(format!("Normalize: mean and std need the same non-zero length, found {} and {}", mean.len(), std.len()));
+    }
+    if std.iter().any(|&s| s <= 0.0) {
+      return Err("Normalize: std must be positive".to_string());
+    }
+    Ok(Normalize { mean, std })
+  }
+}
+
+impl<T: Float> Transform<T> for Normalize {
+  fn apply(&self, image: &Tensor<T>) -> Result<Tensor<T>, String> {
+    let (channels, h, w) = image_dims("Normalize", image)?;
+    if self.mean.len() != 1 && self.mean.len() != channels {
+      return Err(format!("Normalize: {} statistics for an image with {} channels", self.mean.len(), channels));
+    }
+    let stat = |values: &[f64], c: usize| values[c.min(values.len() - 1)];
+    Ok(Tensor::from_fn(&[channels, h, w], |i| {
+      let x = image.get(i).expect("index within shape").to_f64();
+      T::from_f64((x - stat(&self.mean, i[0])) / stat(&self.std, i[0]))
+    }))
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColorJitter {
+  pub brightness: f64,
+  pub contrast: f64,
+  pub saturation: f64
+}
+
+impl ColorJitter {
+  pub fn new() -> Self {
+    ColorJitter { brightness: 0.0, contrast: 0.0, saturation: 0.0 }
+  }
+}
+
+impl Default for ColorJitter {
+  fn default() -> Self {
+    ColorJitter::new()
+  }
+}
+
+fn grayscale(pixels: &[f64], channels: usize, plane: usize) -> Vec<f64> {
+  if channels < 3 {
+    return pixels[..plane].to_vec();
+  }
+  (0..plane).map(|p| 0.299 * pixels[p] + 0.587 * pixels[plane + p] + 0.114 * pixels[2 * plane + p]).collect()
+}
+
+impl<T: Float> Transform<T> for ColorJitter {
+  fn apply(&self, image: &Tensor<T>) -> Result<Tensor<T>, String> {
+    let (channels, h, w) = image_dims("ColorJitter", image)?;
+    if [self.brightness, self.contrast, self.saturation].iter().any(|&f| f < 0.0) {
+      return Err("ColorJitter: jitter strengths must be non-negative".to_string());
+    }
+
+    let factor = |strength: f64| with_rng(|rng| rng.uniform((1.0 - strength).max(0.0), 1.0 + strength));
+    let (brightness, contrast, saturation) = (factor(self.brightness), factor(self.contrast), factor(self.saturation));
+    let plane = h * w;
+    let color = if channels >= 3 { 3 } else { 1 };
+    let mut pixels: Vec<f64> = image.iter().map(|x| x.to_f64()).collect();
+
+    for x in &mut pixels[..color * plane] {
+      *x = (*x * brightness).clamp(0.0, 1.0);
+    }
+
+    let mean = grayscale(&pixels, channels, plane).iter().sum::<f64>() / plane.max(1) as f64;
+    for x in &mut pixels[..color * plane] {
+      *x = ((*x - mean) * contrast + mean).clamp(0.0, 1.0);
+    }
+
+    if channels >= 3 {
+      let gray = grayscale(&pixels, channels, plane);
+      for c in 0..3 {
+        for (x, g) in pixels[c * plane..(c + 1) * plane].iter_mut().zip(&gray) {
+          *x = ((*x - g) * saturation + g).clamp(0.0, 1.0);
+        }
+      }
+    }
+
+    Tensor::from_vec(&[channels, h, w], pixels.into_iter().map(T::from_f64).collect())
+  }
+}
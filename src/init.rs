@@ -0,0 +1,146 @@
+use crate::math::scalar::Float;
+use crate::random::{with_rng, Rng};
+use crate::tensor::Tensor;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Nonlinearity {
+  Linear,
+  Sigmoid,
+  Tanh,
+  Relu,
+  LeakyRelu(f64),
+  Selu
+}
+
+impl Nonlinearity {
+  pub fn gain(&self) -> f64 {
+    match self {
+      Nonlinearity::Linear | Nonlinearity::Sigmoid => 1.0,
+      Nonlinearity::Tanh => 5.0 / 3.0,
+      Nonlinearity::Relu => 2f64.sqrt(),
+      Nonlinearity::LeakyRelu(slope) => (2.0 / (1.0 + slope * slope)).sqrt(),
+      Nonlinearity::Selu => 0.75
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FanMode {
+  #[default]
+  FanIn,
+  FanOut
+}
+
+pub fn fans(shape: &[usize]) -> Result<(usize, usize), String> {
+  if shape.len() < 2 {
+    return Err(format!("Fan in/out requires at least 2 dimensions, found shape {:?}", shape));
+  }
+
+  let receptive: usize = shape[2..].iter().product();
+  Ok((shape[1] * receptive, shape[0] * receptive))
+}
+
+fn sample<T: Float, F>(shape: &[usize], mut f: F) -> Tensor<T>
+where
+  F: FnMut(&mut Rng) -> f64
+{
+  with_rng(|rng| Tensor::from_fn(shape, |_| T::from_f64(f(rng))))
+}
+
+pub fn uniform<T: Float>(shape: &[usize], low: f64, high: f64) -> Tensor<T> {
+  sample(shape, |rng| rng.uniform(low, high))
+}
+
+pub fn normal<T: Float>(shape: &[usize], mean: f64, std: f64) -> Tensor<T> {
+  sample(shape, |rng| rng.normal(mean, std))
+}
+
+pub fn trunc_normal<T: Float>(shape: &[usize], mean: f64, std: f64, low: f64, high: f64) -> Result<Tensor<T>, String> {
+  if low >= high {
+    return Err(format!("trunc_normal: empty interval [{}, {}]", low, high));
+  }
+
+  let mut failed = false;
+  let tensor = sample(shape, |rng| {
+    for _ in 0..1000 {
+      let x = rng.normal(mean, std);
+      if (low..=high).contains(&x) {
+        return x;
+      }
+    }
+    failed = true;
+    low
+  });
+
+  if failed {
+    return Err(format!("trunc_normal: interval [{}, {}] is too far in the tail of N({}, {})", low, high, mean, std));
+  }
+  Ok(tensor)
+}
+
+pub fn xavier_uniform<T: Float>(shape: &[usize], gain: f64) -> Result<Tensor<T>, String> {
+  let (fan_in, fan_out) = fans(shape)?;
+  let bound = gain * (6.0 / (fan_in + fan_out) as f64).sqrt();
+  Ok(uniform(shape, -bound, bound))
+}
+
+pub fn xavier_normal<T: Float>(shape: &[usize], gain: f64) -> Result<Tensor<T>, String> {
+  let (fan_in, fan_out) = fans(shape)?;
+  Ok(normal(shape, 0.0, gain * (2.0 / (fan_in + fan_out) as f64).sqrt()))
+}
+
+fn kaiming_std(shape: &[usize], mode: FanMode, nonlinearity: Nonlinearity) -> Result<f64, String> {
+  let (fan_in, fan_out) = fans(shape)?;
+  let fan = match mode {
+    FanMode::FanIn => fan_in,
+    FanMode::FanOut => fan_out
+  };
+  Ok(nonlinearity.gain() / (fan as f64).sqrt())
+}
+
+pub fn kaiming_uniform<T: Float>(shape: &[usize], mode: FanMode, nonlinearity: Nonlinearity) -> Result<Tensor<T>, String> {
+  let bound = 3f64.sqrt() * kaiming_std(shape, mode, nonlinearity)?;
+  Ok(uniform(shape, -bound, bound))
+}
+
+pub fn kaiming_normal<T: Float>(shape: &[usize], mode: FanMode, nonlinearity: Nonlinearity) -> Result<Tensor<T>, String> {
+  Ok(normal(shape, 0.0, kaiming_std(shape, mode, nonlinearity)?))
+}
+
+pub fn lecun_uniform<T: Float>(shape: &[usize]) -> Result<Tensor<T>, String> {
+  kaiming_uniform(shape, FanMode::FanIn, Nonlinearity::Linear)
+}
+
+pub fn lecun_normal<T: Float>(shape: &[usize]) -> Result<Tensor<T>, String> {
+  kaiming_normal(shape, FanMode::FanIn, Nonlinearity::Linear)
+}
+
+pub fn orthogonal<T: Float>(shape: &[usize], gain: f64) -> Result<Tensor<T>, String> {
+  if shape.len() < 2 {
+    return Err(format!("orthogonal: expected at least 2 dimensions, found shape {:?}", shape));
+  }
+
+  let rows = shape[0];
+  let cols: usize = shape[1..].iter().product();
+  let (long, short) = (rows.max(cols), rows.min(cols));
+
+  let mut basis: Vec<Vec<f64>> = Vec::with_capacity(short);
+  while basis.len() < short {
+    let mut v: Vec<f64> = with_rng(|rng| (0..long).map(|_| rng.normal(0.0, 1.0)).collect());
+    for q in &basis {
+      let proj: f64 = v.iter().zip(q).map(|(a, b)| a * b).sum();
+      v.iter_mut().zip(q).for_each(|(a, b)| *a -= proj * b);
+    }
+    let norm = v.iter().map(|x| x * x).sum::<f64>().sqrt();
+    if norm > 1e-10 {
+      basis.push(v.into_iter().map(|x| x / norm).collect());
+    }
+  }
+
+  let matrix = Tensor::from_fn(&[rows, cols], |index| {
+    let (i, j) = (index[0], index[1]);
+    let value = if rows >= cols { basis[j][i] } else { basis[i][j] };
+    T::from_f64(gain * value)
+  });
+  matrix.reshape(shape)
+}
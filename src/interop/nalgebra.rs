@@ -0,0 +1,56 @@
+use nalgebra::{DMatrix, DVector};
+
+use crate::math::matrix::Matrix;
+use crate::math::vector::Vector;
+
+impl<T> From<Matrix<T>> for DMatrix<T>
+where
+  T: nalgebra::Scalar
+{
+  fn from(matrix: Matrix<T>) -> Self {
+    DMatrix::from_row_iterator(matrix.rows, matrix.cols, matrix.data)
+  }
+}
+
+impl<T> From<&Matrix<T>> for DMatrix<T>
+where
+  T: nalgebra::Scalar
+{
+  fn from(matrix: &Matrix<T>) -> Self {
+    DMatrix::from_row_slice(matrix.rows, matrix.cols, &matrix.data)
+  }
+}
+
+impl<T> From<DMatrix<T>> for Matrix<T>
+where
+  T: nalgebra::Scalar
+{
+  fn from(matrix: DMatrix<T>) -> Self {
+    let (rows, cols) = matrix.shape();
+    let data = matrix.transpose().data.into();
+
+    Matrix {
+      rows,
+      cols,
+      data
+    }
+  }
+}
+
+impl<T> From<Vector<T>> for DVector<T>
+where
+  T: nalgebra::Scalar
+{
+  fn from(vector: Vector<T>) -> Self {
+    DVector::from_vec(vector.data)
+  }
+}
+
+impl<T> From<DVector<T>> for Vector<T>
+where
+  T: nalgebra::Scalar
+{
+  fn from(vector: DVector<T>) -> Self {
+    Vector::from(Vec::from(vector.data))
+  }
+}
@@ -0,0 +1,70 @@
+use ndarray::{Array1, Array2};
+
+use crate::math::matrix::Matrix;
+use crate::math::vector::Vector;
+
+impl<T> From<Matrix<T>> for Array2<T> {
+  fn from(matrix: Matrix<T>) -> Self {
+    Array2::from_shape_vec((matrix.rows, matrix.cols), matrix.data)
+      .expect("Matrix data length always matches its dimensions")
+  }
+}
+
+impl<T: Clone> From<Array2<T>> for Matrix<T> {
+  fn from(array: Array2<T>) -> Self {
+    let (rows, cols) = array.dim();
+    let data = if array.is_standard_layout() {
+      match array.into_raw_vec_and_offset() {
+        (data, Some(0) | None) if data.len() == rows * cols => data,
+        (data, offset) => {
+          let start = offset.unwrap_or(0);
+          data[start..start + rows * cols].to_vec()
+        }
+      }
+    } else {
+      array.iter().cloned().collect()
+    };
+
+    Matrix {
+      rows,
+      cols,
+      data
+    }
+  }
+}
+
+impl<T> From<Vector<T>> for Array1<T> {
+  fn from(vector: Vector<T>) -> Self {
+    Array1::from_vec(vector.data)
+  }
+}
+
+impl<T: Clone> From<Array1<T>> for Vector<T> {
+  fn from(array: Array1<T>) -> Self {
+    if array.is_standard_layout() {
+      let len = array.len();
+      match array.into_raw_vec_and_offset() {
+        (data, Some(0) | None) if data.len() == len => Vector::from(data),
+        (data, offset) => {
+          let start = offset.unwrap_or(0);
+          Vector::from(data[start..start + len].to_vec())
+        }
+      }
+    } else {
+      array.iter().cloned().collect()
+    }
+  }
+}
+
+impl<T> Matrix<T> {
+  pub fn view(&self) -> ndarray::ArrayView2<'_, T> {
+    ndarray::ArrayView2::from_shape((self.rows, self.cols), &self.data)
+      .expect("Matrix data length always matches its dimensions")
+  }
+}
+
+impl<T> Vector<T> {
+  pub fn view(&self) -> ndarray::ArrayView1<'_, T> {
+    ndarray::ArrayView1::from(&self.data)
+  }
+}
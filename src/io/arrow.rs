@@ -0,0 +1,143 @@
+use std::fs::File;
+use std::path::Path;
+
+use arrow_array::cast::AsArray;
+use arrow_array::types::{
+  Float32Type, Float64Type, Int16Type, Int32Type, Int64Type, Int8Type, UInt16Type, UInt32Type, UInt64Type, UInt8Type
+};
+use arrow_array::{Array, RecordBatch};
+use arrow_schema::DataType;
+use parquet::arrow::arrow_reader::{ParquetRecordBatchReader, ParquetRecordBatchReaderBuilder};
+use parquet::arrow::ProjectionMask;
+
+use crate::math::matrix::Matrix;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum NullPolicy {
+  #[default]
+  Error,
+  Fill(f64),
+  Nan,
+  DropRow
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ArrowOptions {
+  pub columns: Option<Vec<String>>,
+  pub null_policy: NullPolicy
+}
+
+macro_rules! primitive_values {
+  ($array:expr, $t:ty) => {
+    $array.as_primitive::<$t>().iter().map(|v| v.map(|x| x as f64)).collect()
+  };
+}
+
+fn column_values(name: &str, array: &dyn Array) -> Result<Vec<Option<f64>>, String> {
+  let values = match array.data_type() {
+    DataType::Int8 => primitive_values!(array, Int8Type),
+    DataType::Int16 => primitive_values!(array, Int16Type),
+    DataType::Int32 => primitive_values!(array, Int32Type),
+    DataType::Int64 => primitive_values!(array, Int64Type),
+    DataType::UInt8 => primitive_values!(array, UInt8Type),
+    DataType::UInt16 => primitive_values!(array, UInt16Type),
+    DataType::UInt32 => primitive_values!(array, UInt32Type),
+    DataType::UInt64 => primitive_values!(array, UInt64Type),
+    DataType::Float32 => primitive_values!(array, Float32Type),
+    DataType::Float64 => primitive_values!(array, Float64Type),
+    DataType::Boolean => array.as_boolean().iter().map(|v| v.map(|b| if b { 1.0 } else { 0.0 })).collect(),
+    other => return Err(format!("Column '{}' has non-numeric type {}", name, other))
+  };
+
+  Ok(values)
+}
+
+fn selected_columns(batch: &RecordBatch, options: &ArrowOptions) -> Result<Vec<usize>, String> {
+  let schema = batch.schema();
+  match &options.columns {
+    Some(names) => names
+      .iter()
+      .map(|name| schema.index_of(name).map_err(|_| format!("No column named '{}'", name)))
+      .collect(),
+    None => Ok((0..schema.fields().len()).collect())
+  }
+}
+
+fn append_batch(batch: &RecordBatch, options: &ArrowOptions, data: &mut Vec<f64>) -> Result<usize, String> {
+  let schema = batch.schema();
+  let columns = selected_columns(batch, options)?
+    .into_iter()
+    .map(|idx| {
+      let name = schema.field(idx).name();
+      column_values(name, batch.column(idx).as_ref()).map(|values| (name.as_str(), values))
+    })
+    .collect::<Result<Vec<_>, _>>()?;
+
+  let mut rows = 0;
+  'rows: for row in 0..batch.num_rows() {
+    let mut values = Vec::with_capacity(columns.len());
+    for (name, column) in &columns {
+      let value = match (column[row], options.null_policy) {
+        (Some(v), _) => v,
+        (None, NullPolicy::Fill(fill)) => fill,
+        (None, NullPolicy::Nan) => f64::NAN,
+        (None, NullPolicy::DropRow) => continue 'rows,
+        (None, NullPolicy::Error) => {
+          return Err(format!("Null value in column '{}' at row {}", name, row));
+        }
+      };
+      values.push(value);
+    }
+
+    data.extend(values);
+    rows += 1;
+  }
+
+  Ok(rows)
+}
+
+pub fn record_batches_to_matrix(batches: &[RecordBatch], options: &ArrowOptions) -> Result<Matrix<f64>, String> {
+  let cols = match batches.first() {
+    Some(batch) => selected_columns(batch, options)?.len(),
+    None => options.columns.as_ref().map_or(0, |names| names.len())
+  };
+
+  let mut data = Vec::new();
+  let mut rows = 0;
+  for batch in batches {
+    rows += append_batch(batch, options, &mut data)?;
+  }
+
+  Matrix::from_vec(rows, cols, data)
+}
+
+pub fn record_batch_to_matrix(batch: &RecordBatch, options: &ArrowOptions) -> Result<Matrix<f64>, String> {
+  record_batches_to_matrix(std::slice::from_ref(batch), options)
+}
+
+fn parquet_reader<P: AsRef<Path>>(path: P, options: &ArrowOptions) -> Result<ParquetRecordBatchReader, String> {
+  let file = File::open(path).map_err(|e| e.to_string())?;
+  let mut builder = ParquetRecordBatchReaderBuilder::try_new(file).map_err(|e| e.to_string())?;
+
+  if let Some(names) = &options.columns {
+    let schema = builder.schema().clone();
+    let indices = names
+      .iter()
+      .map(|name| schema.index_of(name).map_err(|_| format!("No column named '{}'", name)))
+      .collect::<Result<Vec<_>, _>>()?;
+    let mask = ProjectionMask::roots(builder.parquet_schema(), indices);
+    builder = builder.with_projection(mask);
+  }
+
+  builder.build().map_err(|e| e.to_string())
+}
+
+pub fn read_parquet<P: AsRef<Path>>(path: P, options: &ArrowOptions) -> Result<Matrix<f64>, String> {
+  let batches = parquet_reader(path, options)?.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())?;
+  record_batches_to_matrix(&batches, options)
+}
+
+pub fn parquet_batches<P: AsRef<Path>>(path: P, options: &ArrowOptions) -> Result<impl Iterator<Item = Result<Matrix<f64>, String>>, String> {
+  let options = options.clone();
+  Ok(parquet_reader(path, &options)?.map(move |batch| record_batch_to_matrix(&batch.map_err(|e| e.to_string())?, &options)))
+}
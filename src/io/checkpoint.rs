@@ -0,0 +1,187 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use super::npy::NpyElement;
+use crate::math::scalar::Float;
+use crate::nn::Module;
+use crate::tensor::Tensor;
+
+const MAGIC: &[u8] = b"OXZCKPT\0";
+pub const CHECKPOINT_VERSION: u32 = 1;
+
+const CRC_TABLE: [u32; 256] = {
+  let mut table = [0u32; 256];
+  let mut i = 0;
+  while i < 256 {
+    let mut crc = i as u32;
+    let mut bit = 0;
+    while bit < 8 {
+      crc = if crc & 1 != 0 { 0xEDB8_8320 ^ (crc >> 1) } else { crc >> 1 };
+      bit += 1;
+    }
+    table[i] = crc;
+    i += 1;
+  }
+  table
+};
+
+pub fn crc32(bytes: &[u8]) -> u32 {
+  !bytes.iter().fold(!0u32, |crc, &b| CRC_TABLE[((crc ^ b as u32) & 0xFF) as usize] ^ (crc >> 8))
+}
+
+struct Cursor<'a> {
+  bytes: &'a [u8],
+  pos: usize
+}
+
+impl<'a> Cursor<'a> {
+  fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+    let end = self.pos.checked_add(len).filter(|&end| end <= self.bytes.len());
+    let end = end.ok_or_else(|| "Checkpoint is truncated".to_string())?;
+    let slice = &self.bytes[self.pos..end];
+    self.pos = end;
+    Ok(slice)
+  }
+
+  fn u32(&mut self) -> Result<u32, String> {
+    Ok(u32::from_le_bytes(self.take(4)?.try_into().expect("4 bytes")))
+  }
+
+  fn u64(&mut self) -> Result<u64, String> {
+    Ok(u64::from_le_bytes(self.take(8)?.try_into().expect("8 bytes")))
+  }
+}
+
+pub fn write_checkpoint<T, W>(mut writer: W, tensors: &[(&str, &Tensor<T>)]) -> Result<(), String>
+where
+  T: NpyElement,
+  W: Write
+{
+  let mut out = Vec::new();
+  out.extend_from_slice(MAGIC);
+  out.extend_from_slice(&CHECKPOINT_VERSION.to_le_bytes());
+  out.extend_from_slice(&(tensors.len() as u32).to_le_bytes());
+
+  for (name, tensor) in tensors {
+    let start = out.len();
+    out.extend_from_slice(&(name.len() as u32).to_le_bytes());
+    out.extend_from_slice(name.as_bytes());
+    out.push(T::TYPE_CODE.len() as u8);
+    out.extend_from_slice(T::TYPE_CODE.as_bytes());
+    out.extend_from_slice(&(tensor.ndim() as u32).to_le_bytes());
+    for &dim in tensor.shape() {
+      out.extend_from_slice(&(dim as u64).to_le_bytes());
+    }
+    out.extend_from_slice(&((tensor.len() * T::SIZE) as u64).to_le_bytes());
+    for value in tensor.iter() {
+      value.write_le(&mut out);
+    }
+    let checksum = crc32(&out[start..]);
+    out.extend_from_slice(&checksum.to_le_bytes());
+  }
+
+  let checksum = crc32(&out);
+  out.extend_from_slice(&checksum.to_le_bytes());
+  writer.write_all(&out).map_err(|e| e.to_string())
+}
+
+pub fn read_checkpoint<T, R>(mut reader: R) -> Result<Vec<(String, Tensor<T>)>, String>
+where
+  T: NpyElement,
+  R: Read
+{
+  let mut bytes = Vec::new();
+  reader.read_to_end(&mut bytes).map_err(|e| e.to_string())?;
+  if !bytes.starts_with(MAGIC) {
+    return Err("Not an oxz checkpoint (bad magic)".to_string());
+  }
+
+  let mut cursor = Cursor { bytes: &bytes, pos: MAGIC.len() };
+  let version = cursor.u32()?;
+  if version != CHECKPOINT_VERSION {
+    return Err(format!("Unsupported checkpoint version {} (expected {})", version, CHECKPOINT_VERSION));
+  }
+
+  let count = cursor.u32()? as usize;
+  let mut tensors = Vec::with_capacity(count.min(1024));
+  for _ in 0..count {
+    let start = cursor.pos;
+    let name_len = cursor.u32()? as usize;
+    let name = String::from_utf8(cursor.take(name_len)?.to_vec()).map_err(|_| "Tensor name is not valid UTF-8".to_string())?;
+    let dtype_len = cursor.take(1)?[0] as usize;
+    let dtype = String::from_utf8_lossy(cursor.take(dtype_len)?).into_owned();
+    let ndim = cursor.u32()? as usize;
+    let shape = (0..ndim).map(|_| cursor.u64().map(|d| d as usize)).collect::<Result<Vec<_>, _>>()?;
+    let nbytes = cursor.u64()? as usize;
+    let data = cursor.take(nbytes)?;
+    let end = cursor.pos;
+    if cursor.u32()? != crc32(&bytes[start..end]) {
+      return Err(format!("Checksum mismatch for tensor '{}': checkpoint is corrupted", name));
+    }
+
+    if dtype != T::TYPE_CODE {
+      return Err(format!("Tensor '{}' has dtype {} but {} was requested", name, dtype, T::TYPE_CODE));
+    }
+    let size = shape
+      .iter()
+      .try_fold(T::SIZE, |len, &dim| len.checked_mul(dim))
+      .ok_or_else(|| format!("Tensor '{}' has shape {:?}, which is too large", name, shape))?;
+    if nbytes != size {
+      return Err(format!("Tensor '{}' has {} bytes of data, which does not fit shape {:?}", name, nbytes, shape));
+    }
+
+    let values = data.chunks_exact(T::SIZE).map(|chunk| T::read_bytes(chunk, false)).collect();
+    tensors.push((name, Tensor::from_vec(&shape, values)?));
+  }
+
+  let end = cursor.pos;
+  if cursor.u32()? != crc32(&bytes[..end]) {
+    return Err("Checksum mismatch in checkpoint footer: checkpoint is corrupted".to_string());
+  }
+  if cursor.pos != bytes.len() {
+    return Err("Trailing bytes after checkpoint footer".to_string());
+  }
+
+  Ok(tensors)
+}
+
+pub fn save_checkpoint<T, P>(path: P, tensors: &[(&str, &Tensor<T>)]) -> Result<(), String>
+where
+  T: NpyElement,
+  P: AsRef<Path>
+{
+  let file = File::create(path).map_err(|e| e.to_string())?;
+  let mut writer = BufWriter::new(file);
+  write_checkpoint(&mut writer, tensors)?;
+  writer.flush().map_err(|e| e.to_string())
+}
+
+pub fn load_checkpoint<T, P>(path: P) -> Result<Vec<(String, Tensor<T>)>, String>
+where
+  T: NpyElement,
+  P: AsRef<Path>
+{
+  let file = File::open(path).map_err(|e| e.to_string())?;
+  read_checkpoint(BufReader::new(file))
+}
+
+pub fn save_state_dict<T, M, P>(module: &M, path: P) -> Result<(), String>
+where
+  T: Float + NpyElement,
+  M: Module<T> + ?Sized,
+  P: AsRef<Path>
+{
+  let state = module.state_dict();
+  let entries: Vec<(&str, &Tensor<T>)> = state.iter().map(|(name, tensor)| (name.as_str(), tensor)).collect();
+  save_checkpoint(path, &entries)
+}
+
+pub fn load_state_dict<T, M, P>(module: &M, path: P, strict: bool) -> Result<(), String>
+where
+  T: Float + NpyElement,
+  M: Module<T> + ?Sized,
+  P: AsRef<Path>
+{
+  module.load_state_dict(&load_checkpoint(path)?, strict)
+}
@@ -0,0 +1,207 @@
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::math::matrix::Matrix;
+
+#[derive(Clone, Debug)]
+pub struct CsvOptions {
+  pub delimiter: char,
+  pub has_header: bool,
+  pub trim: bool
+}
+
+impl Default for CsvOptions {
+  fn default() -> Self {
+    CsvOptions {
+      delimiter: ',',
+      has_header: true,
+      trim: true
+    }
+  }
+}
+
+pub(crate) fn split_record(line: &str, options: &CsvOptions) -> Vec<String> {
+  let mut fields = Vec::new();
+  let mut field = String::new();
+  let mut in_quotes = false;
+  let mut chars = line.chars().peekable();
+
+  while let Some(c) = chars.next() {
+    if c == '"' {
+      if in_quotes && chars.peek() == Some(&'"') {
+        field.push('"');
+        chars.next();
+      } else {
+        in_quotes = !in_quotes;
+      }
+    } else if c == options.delimiter && !in_quotes {
+      fields.push(std::mem::take(&mut field));
+    } else {
+      field.push(c);
+    }
+  }
+  fields.push(field);
+
+  if options.trim {
+    fields.iter().map(|f| f.trim().to_string()).collect()
+  } else {
+    fields
+  }
+}
+
+// Physical lines are joined while a quoted field is still open, so a quoted
+// newline stays inside its record. Each record carries its first line number.
+pub(crate) fn record_lines<R: Read>(reader: R) -> impl Iterator<Item = Result<(usize, String), String>> {
+  let mut lines = BufReader::new(reader).lines().enumerate();
+  std::iter::from_fn(move || {
+    let (line_idx, line) = lines.next()?;
+    let mut record = match line {
+      Ok(line) => line,
+      Err(e) => return Some(Err(e.to_string()))
+    };
+    while record.matches('"').count() % 2 == 1 {
+      match lines.next() {
+        Some((_, Ok(line))) => {
+          record.push('\n');
+          record.push_str(&line);
+        }
+        Some((_, Err(e))) => return Some(Err(e.to_string())),
+        None => break
+      }
+    }
+    Some(Ok((line_idx + 1, record)))
+  })
+}
+
+fn quote_field(field: &str, delimiter: char) -> String {
+  if field.contains(delimiter) || field.contains('"') || field.contains('\n') {
+    format!("\"{}\"", field.replace('"', "\"\""))
+  } else {
+    field.to_string()
+  }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CsvRecords {
+  pub headers: Option<Vec<String>>,
+  pub rows: Vec<Vec<String>>
+}
+
+pub fn read_records<R: Read>(reader: R, options: &CsvOptions) -> Result<CsvRecords, String> {
+  let mut headers = None;
+  let mut records: Vec<Vec<String>> = Vec::new();
+  for record in record_lines(reader) {
+    let (line_no, line) = record?;
+    if line.trim().is_empty() {
+      continue;
+    }
+
+    let fields = split_record(&line, options);
+    if options.has_header && headers.is_none() {
+      headers = Some(fields);
+      continue;
+    }
+    let expected = headers.as_ref().or(records.first()).map_or(fields.len(), Vec::len);
+    if fields.len() != expected {
+      return Err(format!("Line {}: expected {} fields, found {}", line_no, expected, fields.len()));
+    }
+    records.push(fields);
+  }
+
+  Ok(CsvRecords { headers, rows: records })
+}
+
+impl<T> Matrix<T>
+where
+  T: FromStr,
+  T::Err: Display
+{
+  pub fn from_csv<R: Read>(reader: R, options: &CsvOptions) -> Result<Self, String> {
+    let mut lines = record_lines(reader);
+    let mut headers: Option<Vec<String>> = None;
+    let mut cols: Option<usize> = None;
+    let mut rows = 0;
+    let mut data = Vec::new();
+
+    if options.has_header {
+      for record in lines.by_ref() {
+        let (_, line) = record?;
+        if !line.trim().is_empty() {
+          let fields = split_record(&line, options);
+          cols = Some(fields.len());
+          headers = Some(fields);
+          break;
+        }
+      }
+    }
+
+    for record in lines {
+      let (line_no, line) = record?;
+      if line.trim().is_empty() {
+        continue;
+      }
+
+      let fields = split_record(&line, options);
+      let expected = *cols.get_or_insert(fields.len());
+      if fields.len() != expected {
+        return Err(format!(
+          "Line {}: expected {} fields, found {}",
+          line_no, expected, fields.len()
+        ));
+      }
+
+      for (col_idx, field) in fields.iter().enumerate() {
+        let value = field.parse::<T>().map_err(|e| {
+          let column = match &headers {
+            Some(names) => format!("column {} ('{}')", col_idx + 1, names[col_idx]),
+            None => format!("column {}", col_idx + 1)
+          };
+          format!("Line {}, {}: cannot parse '{}': {}", line_no, column, field, e)
+        })?;
+        data.push(value);
+      }
+      rows += 1;
+    }
+
+    Self::from_vec(rows, cols.unwrap_or(0), data)
+  }
+
+  pub fn from_csv_path<P: AsRef<Path>>(path: P, options: &CsvOptions) -> Result<Self, String> {
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    Self::from_csv(file, options)
+  }
+}
+
+impl<T: Display> Matrix<T> {
+  pub fn to_csv<W: Write>(&self, mut writer: W, header: Option<&[&str]>, options: &CsvOptions) -> Result<(), String> {
+    let delimiter = options.delimiter.to_string();
+
+    if let Some(names) = header {
+      if names.len() != self.cols {
+        return Err(format!("Header has {} names but matrix has {} columns", names.len(), self.cols));
+      }
+
+      let line = names
+        .iter()
+        .map(|name| quote_field(name, options.delimiter))
+        .collect::<Vec<_>>()
+        .join(&delimiter);
+      writeln!(writer, "{}", line).map_err(|e| e.to_string())?;
+    }
+
+    for row in self.data.chunks(self.cols.max(1)) {
+      let line = row.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(&delimiter);
+      writeln!(writer, "{}", line).map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+  }
+
+  pub fn to_csv_path<P: AsRef<Path>>(&self, path: P, header: Option<&[&str]>, options: &CsvOptions) -> Result<(), String> {
+    let file = File::create(path).map_err(|e| e.to_string())?;
+    self.to_csv(std::io::BufWriter::new(file), header, options)
+  }
+}
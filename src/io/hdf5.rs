@@ -0,0 +1,192 @@
+use std::ffi::CString;
+use std::ops::Range;
+use std::path::Path;
+
+use hdf5_metno_sys::h5::{hsize_t, H5open};
+use hdf5_metno_sys::h5d::{H5Dclose, H5Dget_space, H5Dopen2, H5Dread};
+use hdf5_metno_sys::h5f::{H5Fclose, H5Fopen, H5F_ACC_RDONLY};
+use hdf5_metno_sys::h5i::hid_t;
+use hdf5_metno_sys::h5p::H5P_DEFAULT;
+use hdf5_metno_sys::h5s::{
+  H5Sclose, H5Screate_simple, H5Sget_simple_extent_dims, H5Sget_simple_extent_ndims, H5Sselect_hyperslab,
+  H5S_SELECT_SET
+};
+use hdf5_metno_sys::h5t;
+
+use crate::math::matrix::Matrix;
+use crate::math::vector::Vector;
+
+pub trait Hdf5Element: Copy + Default {
+  fn native_type() -> hid_t;
+}
+
+impl Hdf5Element for f32 {
+  fn native_type() -> hid_t {
+    *h5t::H5T_NATIVE_FLOAT
+  }
+}
+
+impl Hdf5Element for f64 {
+  fn native_type() -> hid_t {
+    *h5t::H5T_NATIVE_DOUBLE
+  }
+}
+
+impl Hdf5Element for i32 {
+  fn native_type() -> hid_t {
+    *h5t::H5T_NATIVE_INT32
+  }
+}
+
+impl Hdf5Element for i64 {
+  fn native_type() -> hid_t {
+    *h5t::H5T_NATIVE_INT64
+  }
+}
+
+struct Handle {
+  id: hid_t,
+  close: unsafe extern "C" fn(hid_t) -> i32
+}
+
+impl Handle {
+  fn new(id: hid_t, close: unsafe extern "C" fn(hid_t) -> i32, what: &str) -> Result<Self, String> {
+    if id < 0 {
+      return Err(format!("HDF5: failed to open {}", what));
+    }
+
+    Ok(Handle {
+      id,
+      close
+    })
+  }
+}
+
+impl Drop for Handle {
+  fn drop(&mut self) {
+    unsafe {
+      (self.close)(self.id);
+    }
+  }
+}
+
+pub struct Hdf5File {
+  file: Handle
+}
+
+impl Hdf5File {
+  pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+    let path = path.as_ref();
+    let c_path = CString::new(path.to_string_lossy().as_bytes()).map_err(|e| e.to_string())?;
+
+    let file = unsafe {
+      H5open();
+      H5Fopen(c_path.as_ptr(), H5F_ACC_RDONLY, H5P_DEFAULT)
+    };
+
+    Ok(Hdf5File {
+      file: Handle::new(file, H5Fclose, &format!("file '{}'", path.display()))?
+    })
+  }
+
+  fn open_dataset(&self, name: &str) -> Result<(Handle, Handle, Vec<usize>), String> {
+    let c_name = CString::new(name).map_err(|e| e.to_string())?;
+    let dataset = Handle::new(
+      unsafe { H5Dopen2(self.file.id, c_name.as_ptr(), H5P_DEFAULT) },
+      H5Dclose,
+      &format!("dataset '{}'", name)
+    )?;
+    let space = Handle::new(unsafe { H5Dget_space(dataset.id) }, H5Sclose, &format!("dataspace of '{}'", name))?;
+
+    let rank = unsafe { H5Sget_simple_extent_ndims(space.id) };
+    if rank < 0 {
+      return Err(format!("HDF5: cannot query rank of '{}'", name));
+    }
+
+    let mut dims: Vec<hsize_t> = vec![0; rank as usize];
+    if unsafe { H5Sget_simple_extent_dims(space.id, dims.as_mut_ptr(), std::ptr::null_mut()) } < 0 {
+      return Err(format!("HDF5: cannot query dimensions of '{}'", name));
+    }
+
+    Ok((dataset, space, dims.into_iter().map(|d| d as usize).collect()))
+  }
+
+  fn read_hyperslab<T: Hdf5Element>(
+    &self,
+    name: &str,
+    start: &[usize],
+    count: &[usize]
+  ) -> Result<Vec<T>, String> {
+    let (dataset, space, _) = self.open_dataset(name)?;
+    let start: Vec<hsize_t> = start.iter().map(|&s| s as hsize_t).collect();
+    let count: Vec<hsize_t> = count.iter().map(|&c| c as hsize_t).collect();
+    let len = count.iter().product::<hsize_t>() as usize;
+
+    let selected = unsafe {
+      H5Sselect_hyperslab(space.id, H5S_SELECT_SET, start.as_ptr(), std::ptr::null(), count.as_ptr(), std::ptr::null())
+    };
+    if selected < 0 {
+      return Err(format!("HDF5: invalid selection for '{}'", name));
+    }
+
+    let memory = Handle::new(
+      unsafe { H5Screate_simple(count.len() as i32, count.as_ptr(), std::ptr::null()) },
+      H5Sclose,
+      "memory dataspace"
+    )?;
+
+    let mut data = vec![T::default(); len];
+    let status = unsafe {
+      H5Dread(dataset.id, T::native_type(), memory.id, space.id, H5P_DEFAULT, data.as_mut_ptr().cast())
+    };
+    if status < 0 {
+      return Err(format!("HDF5: failed to read '{}'", name));
+    }
+
+    Ok(data)
+  }
+
+  pub fn shape(&self, name: &str) -> Result<Vec<usize>, String> {
+    self.open_dataset(name).map(|(_, _, dims)| dims)
+  }
+
+  pub fn read_vector<T: Hdf5Element>(&self, name: &str) -> Result<Vector<T>, String> {
+    match self.shape(name)?.as_slice() {
+      [len] => self.read_slice(name, 0..*len),
+      dims => Err(format!("Dataset '{}' has {} dimensions, expected 1", name, dims.len()))
+    }
+  }
+
+  pub fn read_slice<T: Hdf5Element>(&self, name: &str, range: Range<usize>) -> Result<Vector<T>, String> {
+    let len = match self.shape(name)?.as_slice() {
+      [len] => *len,
+      dims => return Err(format!("Dataset '{}' has {} dimensions, expected 1", name, dims.len()))
+    };
+    if range.start > range.end || range.end > len {
+      return Err(format!("Range {:?} out of bounds for dataset '{}' of length {}", range, name, len));
+    }
+
+    self.read_hyperslab(name, &[range.start], &[range.len()]).map(Vector::from)
+  }
+
+  pub fn read_matrix<T: Hdf5Element>(&self, name: &str) -> Result<Matrix<T>, String> {
+    match self.shape(name)?.as_slice() {
+      [len] => Matrix::from_vec(1, *len, self.read_hyperslab(name, &[0], &[*len])?),
+      [rows, _] => self.read_rows(name, 0..*rows),
+      dims => Err(format!("Dataset '{}' has {} dimensions, expected 1 or 2", name, dims.len()))
+    }
+  }
+
+  pub fn read_rows<T: Hdf5Element>(&self, name: &str, rows: Range<usize>) -> Result<Matrix<T>, String> {
+    let (total_rows, cols) = match self.shape(name)?.as_slice() {
+      [r, c] => (*r, *c),
+      dims => return Err(format!("Dataset '{}' has {} dimensions, expected 2", name, dims.len()))
+    };
+    if rows.start > rows.end || rows.end > total_rows {
+      return Err(format!("Rows {:?} out of bounds for dataset '{}' with {} rows", rows, name, total_rows));
+    }
+
+    let data = self.read_hyperslab(name, &[rows.start, 0], &[rows.len(), cols])?;
+    Matrix::from_vec(rows.len(), cols, data)
+  }
+}
@@ -0,0 +1,179 @@
+use std::fs;
+use std::io::Cursor;
+use std::path::Path;
+
+use ::image::{DynamicImage, GrayAlphaImage, GrayImage, RgbImage, RgbaImage};
+
+use crate::math::scalar::Float;
+use crate::tensor::Tensor;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorMode {
+  Gray,
+  GrayAlpha,
+  Rgb,
+  Rgba
+}
+
+impl ColorMode {
+  pub fn channels(self) -> usize {
+    match self {
+      ColorMode::Gray => 1,
+      ColorMode::GrayAlpha => 2,
+      ColorMode::Rgb => 3,
+      ColorMode::Rgba => 4
+    }
+  }
+
+  fn from_channels(channels: usize) -> Result<Self, String> {
+    match channels {
+      1 => Ok(ColorMode::Gray),
+      2 => Ok(ColorMode::GrayAlpha),
+      3 => Ok(ColorMode::Rgb),
+      4 => Ok(ColorMode::Rgba),
+      _ => Err(format!("Images need 1 to 4 channels, found {}", channels))
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PixelRange {
+  Unit,
+  Signed,
+  Byte
+}
+
+impl PixelRange {
+  fn scale(self, x: f64) -> f64 {
+    match self {
+      PixelRange::Unit => x,
+      PixelRange::Signed => 2.0 * x - 1.0,
+      PixelRange::Byte => 255.0 * x
+    }
+  }
+
+  fn unscale(self, x: f64) -> f64 {
+    match self {
+      PixelRange::Unit => x,
+      PixelRange::Signed => (x + 1.0) / 2.0,
+      PixelRange::Byte => x / 255.0
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageFormat {
+  Png,
+  Jpeg
+}
+
+impl ImageFormat {
+  pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+    let extension = path.as_ref().extension().and_then(|ext| ext.to_str()).map(str::to_ascii_lowercase);
+    match extension.as_deref() {
+      Some("png") => Ok(ImageFormat::Png),
+      Some("jpg" | "jpeg") => Ok(ImageFormat::Jpeg),
+      _ => Err(format!("Cannot infer an image format from {}", path.as_ref().display()))
+    }
+  }
+
+  fn codec(self) -> ::image::ImageFormat {
+    match self {
+      ImageFormat::Png => ::image::ImageFormat::Png,
+      ImageFormat::Jpeg => ::image::ImageFormat::Jpeg
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ImageOptions {
+  pub color: Option<ColorMode>,
+  pub range: PixelRange
+}
+
+impl ImageOptions {
+  pub fn new() -> Self {
+    ImageOptions { color: None, range: PixelRange::Unit }
+  }
+}
+
+impl Default for ImageOptions {
+  fn default() -> Self {
+    ImageOptions::new()
+  }
+}
+
+fn to_tensor<T: Float>(image: &DynamicImage, options: &ImageOptions) -> Result<Tensor<T>, String> {
+  let color = match options.color {
+    Some(color) => color,
+    None => ColorMode::from_channels(image.color().channel_count() as usize)?
+  };
+  let (width, height) = (image.width() as usize, image.height() as usize);
+  let pixels = match color {
+    ColorMode::Gray => image.to_luma32f().into_raw(),
+    ColorMode::GrayAlpha => image.to_luma_alpha32f().into_raw(),
+    ColorMode::Rgb => image.to_rgb32f().into_raw(),
+    ColorMode::Rgba => image.to_rgba32f().into_raw()
+  };
+
+  let channels = color.channels();
+  Ok(Tensor::from_fn(&[channels, height, width], |i| {
+    let x = pixels[(i[1] * width + i[2]) * channels + i[0]] as f64;
+    T::from_f64(options.range.scale(x))
+  }))
+}
+
+pub fn decode_image<T: Float>(bytes: &[u8], options: &ImageOptions) -> Result<Tensor<T>, String> {
+  let image = ::image::load_from_memory(bytes).map_err(|e| e.to_string())?;
+  to_tensor(&image, options)
+}
+
+pub fn load_image<T, P>(path: P, options: &ImageOptions) -> Result<Tensor<T>, String>
+where
+  T: Float,
+  P: AsRef<Path>
+{
+  let image = ::image::open(path.as_ref()).map_err(|e| format!("Cannot read {}: {}", path.as_ref().display(), e))?;
+  to_tensor(&image, options)
+}
+
+fn to_image<T: Float>(tensor: &Tensor<T>, range: PixelRange) -> Result<DynamicImage, String> {
+  let &[channels, height, width] = tensor.shape() else {
+    return Err(format!("Expected an image tensor of shape (C, H, W), found {:?}", tensor.shape()));
+  };
+  let color = ColorMode::from_channels(channels)?;
+
+  let mut raw = vec![0u8; channels * height * width];
+  for c in 0..channels {
+    for y in 0..height {
+      for x in 0..width {
+        let value = range.unscale(tensor.get(&[c, y, x]).expect("index within shape").to_f64());
+        raw[(y * width + x) * channels + c] = (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+      }
+    }
+  }
+
+  let (w, h) = (width as u32, height as u32);
+  let image = match color {
+    ColorMode::Gray => GrayImage::from_raw(w, h, raw).map(DynamicImage::ImageLuma8),
+    ColorMode::GrayAlpha => GrayAlphaImage::from_raw(w, h, raw).map(DynamicImage::ImageLumaA8),
+    ColorMode::Rgb => RgbImage::from_raw(w, h, raw).map(DynamicImage::ImageRgb8),
+    ColorMode::Rgba => RgbaImage::from_raw(w, h, raw).map(DynamicImage::ImageRgba8)
+  };
+  image.ok_or_else(|| "Image buffer does not match its dimensions".to_string())
+}
+
+pub fn encode_image<T: Float>(tensor: &Tensor<T>, range: PixelRange, format: ImageFormat) -> Result<Vec<u8>, String> {
+  let mut bytes = Cursor::new(Vec::new());
+  to_image(tensor, range)?.write_to(&mut bytes, format.codec()).map_err(|e| e.to_string())?;
+  Ok(bytes.into_inner())
+}
+
+pub fn save_image<T, P>(tensor: &Tensor<T>, path: P, range: PixelRange) -> Result<(), String>
+where
+  T: Float,
+  P: AsRef<Path>
+{
+  let bytes = encode_image(tensor, range, ImageFormat::from_path(path.as_ref())?)?;
+  fs::write(path.as_ref(), bytes).map_err(|e| format!("Cannot write {}: {}", path.as_ref().display(), e))
+}
@@ -0,0 +1,12 @@
+#[cfg(feature = "arrow")]
+pub mod arrow;
+pub mod checkpoint;
+pub mod csv;
+#[cfg(feature = "hdf5")]
+pub mod hdf5;
+#[cfg(feature = "image")]
+pub mod image;
+pub mod mtx;
+pub mod npy;
+#[cfg(feature = "safetensors")]
+pub mod safetensors;
@@ -0,0 +1,209 @@
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::math::matrix::Matrix;
+use crate::math::scalar::Scalar;
+
+pub trait MtxField {
+  const FIELD: &'static str;
+}
+
+macro_rules! impl_mtx_field {
+  ($field:expr, $($t:ty),*) => {
+    $(impl MtxField for $t {
+      const FIELD: &'static str = $field;
+    })*
+  };
+}
+
+impl_mtx_field!("real", f32, f64);
+impl_mtx_field!("integer", i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Symmetry {
+  General,
+  Symmetric,
+  SkewSymmetric
+}
+
+fn parse_value<T>(token: Option<&str>, line: usize) -> Result<T, String>
+where
+  T: FromStr,
+  T::Err: Display
+{
+  let token = token.ok_or_else(|| format!("Line {}: missing value", line))?;
+  token
+    .parse::<T>()
+    .map_err(|e| format!("Line {}: cannot parse '{}': {}", line, token, e))
+}
+
+pub fn read_mtx<T, R>(reader: R) -> Result<Matrix<T>, String>
+where
+  T: FromStr + Scalar,
+  T::Err: Display,
+  R: Read
+{
+  let mut lines = BufReader::new(reader)
+    .lines()
+    .enumerate()
+    .map(|(idx, line)| line.map(|l| (idx + 1, l)).map_err(|e| e.to_string()));
+
+  let (_, banner) = lines.next().ok_or("Empty Matrix Market file")??;
+  let banner = banner.to_lowercase();
+  let tokens: Vec<&str> = banner.split_whitespace().collect();
+  if tokens.len() != 5 || tokens[0] != "%%matrixmarket" || tokens[1] != "matrix" {
+    return Err("Invalid Matrix Market banner".to_string());
+  }
+
+  let coordinate = match tokens[2] {
+    "coordinate" => true,
+    "array" => false,
+    format => return Err(format!("Unsupported Matrix Market format '{}'", format))
+  };
+  let pattern = match tokens[3] {
+    "real" | "integer" | "double" => false,
+    "pattern" if coordinate => true,
+    field => return Err(format!("Unsupported Matrix Market field '{}'", field))
+  };
+  let symmetry = match tokens[4] {
+    "general" => Symmetry::General,
+    "symmetric" => Symmetry::Symmetric,
+    "skew-symmetric" => Symmetry::SkewSymmetric,
+    symmetry => return Err(format!("Unsupported Matrix Market symmetry '{}'", symmetry))
+  };
+
+  let mut entries = lines.filter(|line| match line {
+    Ok((_, l)) => !l.trim().is_empty() && !l.starts_with('%'),
+    Err(_) => true
+  });
+
+  let (size_line, size) = entries.next().ok_or("Missing Matrix Market size line")??;
+  let dims = size
+    .split_whitespace()
+    .map(|t| t.parse::<usize>().map_err(|_| format!("Line {}: invalid size '{}'", size_line, t)))
+    .collect::<Result<Vec<_>, _>>()?;
+
+  let (rows, cols) = match (coordinate, dims.as_slice()) {
+    (true, [r, c, _]) | (false, [r, c]) => (*r, *c),
+    _ => return Err(format!("Line {}: malformed size line", size_line))
+  };
+  if symmetry != Symmetry::General && rows != cols {
+    return Err("Symmetric Matrix Market matrices must be square".to_string());
+  }
+
+  let len = rows
+    .checked_mul(cols)
+    .ok_or_else(|| format!("Line {}: size {} x {} is too large", size_line, rows, cols))?;
+  if coordinate && dims[2] > len {
+    return Err(format!("Line {}: {} entries do not fit in a {} x {} matrix", size_line, dims[2], rows, cols));
+  }
+
+  let mut matrix = Matrix::zeroes(rows, cols);
+  let mut place = |row: usize, col: usize, value: T, line: usize| {
+    matrix[(row, col)] = value;
+    if row != col {
+      match symmetry {
+        Symmetry::General => {}
+        Symmetry::Symmetric => matrix[(col, row)] = value,
+        Symmetry::SkewSymmetric => {
+          matrix[(col, row)] = value.checked_neg().ok_or_else(|| format!("Line {}: cannot negate {:?}", line, value))?
+        }
+      }
+    }
+    Ok::<(), String>(())
+  };
+
+  if coordinate {
+    let nnz = dims[2];
+    for _ in 0..nnz {
+      let (line, entry) = entries.next().ok_or("Unexpected end of Matrix Market data")??;
+      let mut parts = entry.split_whitespace();
+      let row: usize = parse_value(parts.next(), line)?;
+      let col: usize = parse_value(parts.next(), line)?;
+      if row == 0 || row > rows || col == 0 || col > cols {
+        return Err(format!("Line {}: entry ({}, {}) out of bounds", line, row, col));
+      }
+
+      let value = if pattern {
+        T::one()
+      } else {
+        parse_value(parts.next(), line)?
+      };
+      place(row - 1, col - 1, value, line)?;
+    }
+  } else {
+    for col in 0..cols {
+      let start = if symmetry == Symmetry::General { 0 } else { col };
+      for row in start..rows {
+        if symmetry == Symmetry::SkewSymmetric && row == col {
+          continue;
+        }
+
+        let (line, entry) = entries.next().ok_or("Unexpected end of Matrix Market data")??;
+        place(row, col, parse_value(entry.split_whitespace().next(), line)?, line)?;
+      }
+    }
+  }
+
+  Ok(matrix)
+}
+
+pub fn write_mtx<T, W>(mut writer: W, matrix: &Matrix<T>) -> Result<(), String>
+where
+  T: MtxField + Display,
+  W: Write
+{
+  writeln!(writer, "%%MatrixMarket matrix array {} general", T::FIELD).map_err(|e| e.to_string())?;
+  writeln!(writer, "{} {}", matrix.rows, matrix.cols).map_err(|e| e.to_string())?;
+  for col in 0..matrix.cols {
+    for row in 0..matrix.rows {
+      writeln!(writer, "{}", matrix[(row, col)]).map_err(|e| e.to_string())?;
+    }
+  }
+
+  Ok(())
+}
+
+pub fn write_mtx_coordinate<T, W>(mut writer: W, matrix: &Matrix<T>) -> Result<(), String>
+where
+  T: MtxField + Display + Default + PartialEq,
+  W: Write
+{
+  let zero = T::default();
+  let nnz = matrix.data.iter().filter(|x| **x != zero).count();
+
+  writeln!(writer, "%%MatrixMarket matrix coordinate {} general", T::FIELD).map_err(|e| e.to_string())?;
+  writeln!(writer, "{} {} {}", matrix.rows, matrix.cols, nnz).map_err(|e| e.to_string())?;
+  for row in 0..matrix.rows {
+    for col in 0..matrix.cols {
+      let value = &matrix[(row, col)];
+      if *value != zero {
+        writeln!(writer, "{} {} {}", row + 1, col + 1, value).map_err(|e| e.to_string())?;
+      }
+    }
+  }
+
+  Ok(())
+}
+
+pub fn load_mtx<T, P>(path: P) -> Result<Matrix<T>, String>
+where
+  T: FromStr + Scalar,
+  T::Err: Display,
+  P: AsRef<Path>
+{
+  let file = File::open(path).map_err(|e| e.to_string())?;
+  read_mtx(file)
+}
+
+pub fn save_mtx<T, P>(path: P, matrix: &Matrix<T>) -> Result<(), String>
+where
+  T: MtxField + Display,
+  P: AsRef<Path>
+{
+  let file = File::create(path).map_err(|e| e.to_string())?;
+  write_mtx(BufWriter::new(file), matrix)
+}
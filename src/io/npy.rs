@@ -0,0 +1,269 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::math::half::f16;
+use crate::math::matrix::Matrix;
+
+const MAGIC: &[u8] = b"\x93NUMPY";
+
+pub trait NpyElement: Copy {
+  const TYPE_CODE: &'static str;
+  const SIZE: usize;
+
+  fn read_bytes(bytes: &[u8], big_endian: bool) -> Self;
+  fn write_le(&self, out: &mut Vec<u8>);
+}
+
+macro_rules! impl_npy_element {
+  ($t:ty, $code:expr) => {
+    impl NpyElement for $t {
+      const TYPE_CODE: &'static str = $code;
+      const SIZE: usize = std::mem::size_of::<$t>();
+
+      fn read_bytes(bytes: &[u8], big_endian: bool) -> Self {
+        let mut buf = [0u8; std::mem::size_of::<$t>()];
+        buf.copy_from_slice(bytes);
+        if big_endian {
+          <$t>::from_be_bytes(buf)
+        } else {
+          <$t>::from_le_bytes(buf)
+        }
+      }
+
+      fn write_le(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_le_bytes());
+      }
+    }
+  };
+}
+
+impl_npy_element!(f32, "f4");
+impl_npy_element!(f64, "f8");
+impl_npy_element!(i32, "i4");
+impl_npy_element!(i64, "i8");
+
+impl NpyElement for f16 {
+  const TYPE_CODE: &'static str = "f2";
+  const SIZE: usize = 2;
+
+  fn read_bytes(bytes: &[u8], big_endian: bool) -> Self {
+    let buf = [bytes[0], bytes[1]];
+    f16::from_bits(if big_endian { u16::from_be_bytes(buf) } else { u16::from_le_bytes(buf) })
+  }
+
+  fn write_le(&self, out: &mut Vec<u8>) {
+    out.extend_from_slice(&self.to_bits().to_le_bytes());
+  }
+}
+
+struct Header {
+  descr: String,
+  fortran_order: bool,
+  shape: Vec<usize>
+}
+
+fn dict_value<'a>(header: &'a str, key: &str) -> Result<&'a str, String> {
+  let pattern = format!("'{}':", key);
+  let start = header
+    .find(&pattern)
+    .ok_or_else(|| format!("Missing '{}' in .npy header", key))?;
+
+  Ok(header[start + pattern.len()..].trim_start())
+}
+
+fn parse_header(header: &str) -> Result<Header, String> {
+  let descr_value = dict_value(header, "descr")?;
+  let descr = descr_value
+    .strip_prefix('\'')
+    .and_then(|rest| rest.split('\'').next())
+    .ok_or_else(|| "Malformed 'descr' in .npy header".to_string())?
+    .to_string();
+
+  let fortran_value = dict_value(header, "fortran_order")?;
+  let fortran_order = if fortran_value.starts_with("True") {
+    true
+  } else if fortran_value.starts_with("False") {
+    false
+  } else {
+    return Err("Malformed 'fortran_order' in .npy header".to_string());
+  };
+
+  let shape_value = dict_value(header, "shape")?;
+  let shape_str = shape_value
+    .strip_prefix('(')
+    .and_then(|rest| rest.split(')').next())
+    .ok_or_else(|| "Malformed 'shape' in .npy header".to_string())?;
+  let shape = shape_str
+    .split(',')
+    .map(|dim| dim.trim())
+    .filter(|dim| !dim.is_empty())
+    .map(|dim| dim.parse::<usize>().map_err(|_| format!("Invalid dimension '{}' in .npy header", dim)))
+    .collect::<Result<Vec<_>, _>>()?;
+
+  Ok(Header {
+    descr,
+    fortran_order,
+    shape
+  })
+}
+
+fn read_len<R: Read>(reader: &mut R, len: usize, what: &str) -> Result<Vec<u8>, String> {
+  let mut bytes = Vec::new();
+  reader.take(len as u64).read_to_end(&mut bytes).map_err(|e| e.to_string())?;
+  if bytes.len() != len {
+    return Err(format!("Truncated .npy {}: expected {} bytes, found {}", what, len, bytes.len()));
+  }
+  Ok(bytes)
+}
+
+pub fn read_npy<T, R>(mut reader: R) -> Result<Matrix<T>, String>
+where
+  T: NpyElement,
+  R: Read
+{
+  let mut preamble = [0u8; 8];
+  reader.read_exact(&mut preamble).map_err(|e| e.to_string())?;
+  if &preamble[..6] != MAGIC {
+    return Err("Not a .npy file (bad magic string)".to_string());
+  }
+
+  let header_len = match preamble[6] {
+    1 => {
+      let mut len = [0u8; 2];
+      reader.read_exact(&mut len).map_err(|e| e.to_string())?;
+      u16::from_le_bytes(len) as usize
+    }
+    2 | 3 => {
+      let mut len = [0u8; 4];
+      reader.read_exact(&mut len).map_err(|e| e.to_string())?;
+      u32::from_le_bytes(len) as usize
+    }
+    version => return Err(format!("Unsupported .npy format version {}", version))
+  };
+
+  let header_bytes = read_len(&mut reader, header_len, "header")?;
+  let header = parse_header(&String::from_utf8_lossy(&header_bytes))?;
+
+  let mut descr = header.descr.chars();
+  let big_endian = match descr.next() {
+    Some('<' | '=' | '|') => false,
+    Some('>') => true,
+    _ => return Err(format!("Unsupported dtype '{}'", header.descr))
+  };
+  let code = descr.as_str();
+  if code != T::TYPE_CODE {
+    return Err(format!("dtype mismatch: file has '{}', expected '{}'", code, T::TYPE_CODE));
+  }
+
+  let (rows, cols) = match header.shape.as_slice() {
+    [] => (1, 1),
+    [n] => (1, *n),
+    [r, c] => (*r, *c),
+    shape => return Err(format!("Expected at most 2 dimensions, found {}", shape.len()))
+  };
+
+  let len = rows
+    .checked_mul(cols)
+    .and_then(|n| n.checked_mul(T::SIZE))
+    .ok_or_else(|| format!("Shape ({}, {}) in .npy header is too large", rows, cols))?;
+  let bytes = read_len(&mut reader, len, "data")?;
+  let data: Vec<T> = bytes
+    .chunks_exact(T::SIZE)
+    .map(|chunk| T::read_bytes(chunk, big_endian))
+    .collect();
+
+  if header.fortran_order {
+    Ok(Matrix::from_vec(cols, rows, data)?.transpose())
+  } else {
+    Matrix::from_vec(rows, cols, data)
+  }
+}
+
+pub fn write_npy<T, W>(mut writer: W, matrix: &Matrix<T>) -> Result<(), String>
+where
+  T: NpyElement,
+  W: Write
+{
+  let mut header = format!(
+    "{{'descr': '<{}', 'fortran_order': False, 'shape': ({}, {}), }}",
+    T::TYPE_CODE, matrix.rows, matrix.cols
+  );
+  let unpadded = MAGIC.len() + 2 + 2 + header.len() + 1;
+  header.push_str(&" ".repeat((64 - unpadded % 64) % 64));
+  header.push('\n');
+
+  let mut out = Vec::with_capacity(MAGIC.len() + 4 + header.len() + matrix.data.len() * T::SIZE);
+  out.extend_from_slice(MAGIC);
+  out.extend_from_slice(&[1, 0]);
+  out.extend_from_slice(&(header.len() as u16).to_le_bytes());
+  out.extend_from_slice(header.as_bytes());
+  for value in &matrix.data {
+    value.write_le(&mut out);
+  }
+
+  writer.write_all(&out).map_err(|e| e.to_string())
+}
+
+pub fn load_npy<T, P>(path: P) -> Result<Matrix<T>, String>
+where
+  T: NpyElement,
+  P: AsRef<Path>
+{
+  let file = File::open(path).map_err(|e| e.to_string())?;
+  read_npy(BufReader::new(file))
+}
+
+pub fn save_npy<T, P>(path: P, matrix: &Matrix<T>) -> Result<(), String>
+where
+  T: NpyElement,
+  P: AsRef<Path>
+{
+  let file = File::create(path).map_err(|e| e.to_string())?;
+  write_npy(BufWriter::new(file), matrix)
+}
+
+#[cfg(feature = "npz")]
+pub fn load_npz<T, P>(path: P) -> Result<Vec<(String, Matrix<T>)>, String>
+where
+  T: NpyElement,
+  P: AsRef<Path>
+{
+  let file = File::open(path).map_err(|e| e.to_string())?;
+  let mut archive = zip::ZipArchive::new(BufReader::new(file)).map_err(|e| e.to_string())?;
+
+  let mut arrays = Vec::with_capacity(archive.len());
+  for i in 0..archive.len() {
+    let entry = archive.by_index(i).map_err(|e| e.to_string())?;
+    let name = entry.name();
+    let name = name.strip_suffix(".npy").unwrap_or(name).to_string();
+    let matrix = read_npy(entry).map_err(|e| format!("{}: {}", name, e))?;
+    arrays.push((name, matrix));
+  }
+
+  Ok(arrays)
+}
+
+#[cfg(feature = "npz")]
+pub fn save_npz<T, P>(path: P, arrays: &[(&str, &Matrix<T>)], compressed: bool) -> Result<(), String>
+where
+  T: NpyElement,
+  P: AsRef<Path>
+{
+  let file = File::create(path).map_err(|e| e.to_string())?;
+  let mut archive = zip::ZipWriter::new(BufWriter::new(file));
+  let method = if compressed {
+    zip::CompressionMethod::Deflated
+  } else {
+    zip::CompressionMethod::Stored
+  };
+  let options = zip::write::SimpleFileOptions::default().compression_method(method);
+
+  for (name, matrix) in arrays {
+    archive.start_file(format!("{}.npy", name), options).map_err(|e| e.to_string())?;
+    write_npy(&mut archive, matrix)?;
+  }
+
+  archive.finish().map_err(|e| e.to_string())?;
+  Ok(())
+}
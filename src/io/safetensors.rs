@@ -0,0 +1,233 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use serde_json::{json, Map, Value};
+
+use super::npy::NpyElement;
+use crate::math::matrix::Matrix;
+
+const MAX_HEADER_LEN: u64 = 100_000_000;
+
+pub trait SafetensorsElement: NpyElement {
+  const DTYPE: &'static str;
+}
+
+impl SafetensorsElement for f32 {
+  const DTYPE: &'static str = "F32";
+}
+
+impl SafetensorsElement for f64 {
+  const DTYPE: &'static str = "F64";
+}
+
+impl SafetensorsElement for i32 {
+  const DTYPE: &'static str = "I32";
+}
+
+impl SafetensorsElement for i64 {
+  const DTYPE: &'static str = "I64";
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TensorInfo {
+  pub dtype: String,
+  pub shape: Vec<usize>,
+  pub data_offsets: (usize, usize)
+}
+
+pub struct SafetensorsReader<R> {
+  reader: R,
+  data_start: u64,
+  data_len: u64,
+  tensors: Vec<(String, TensorInfo)>,
+  metadata: HashMap<String, String>
+}
+
+impl SafetensorsReader<BufReader<File>> {
+  pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    Self::new(BufReader::new(file))
+  }
+}
+
+impl<R: Read + Seek> SafetensorsReader<R> {
+  pub fn new(mut reader: R) -> Result<Self, String> {
+    let mut len = [0u8; 8];
+    reader.read_exact(&mut len).map_err(|e| e.to_string())?;
+    let header_len = u64::from_le_bytes(len);
+    if header_len > MAX_HEADER_LEN {
+      return Err(format!("Safetensors header of {} bytes exceeds the {} byte limit", header_len, MAX_HEADER_LEN));
+    }
+    let start = reader.stream_position().map_err(|e| e.to_string())?;
+    let end = reader.seek(SeekFrom::End(0)).map_err(|e| e.to_string())?;
+    reader.seek(SeekFrom::Start(start)).map_err(|e| e.to_string())?;
+    if header_len > end.saturating_sub(start) {
+      return Err(format!("Safetensors header of {} bytes but only {} bytes follow", header_len, end.saturating_sub(start)));
+    }
+
+    let mut header = vec![0u8; header_len as usize];
+    reader.read_exact(&mut header).map_err(|e| e.to_string())?;
+    let header: Map<String, Value> =
+      serde_json::from_slice(&header).map_err(|e| format!("Invalid safetensors header: {}", e))?;
+
+    let mut tensors = Vec::with_capacity(header.len());
+    let mut metadata = HashMap::new();
+    for (name, value) in header {
+      if name == "__metadata__" {
+        for (key, value) in value.as_object().into_iter().flatten() {
+          if let Some(value) = value.as_str() {
+            metadata.insert(key.clone(), value.to_string());
+          }
+        }
+        continue;
+      }
+
+      let info = parse_info(&value).ok_or_else(|| format!("Malformed header entry for tensor '{}'", name))?;
+      tensors.push((name, info));
+    }
+    tensors.sort_by_key(|(_, info)| info.data_offsets.0);
+
+    Ok(SafetensorsReader {
+      reader,
+      data_start: 8 + header_len,
+      data_len: end - start - header_len,
+      tensors,
+      metadata
+    })
+  }
+
+  pub fn names(&self) -> Vec<&str> {
+    self.tensors.iter().map(|(name, _)| name.as_str()).collect()
+  }
+
+  pub fn info(&self, name: &str) -> Option<&TensorInfo> {
+    self.tensors.iter().find(|(n, _)| n == name).map(|(_, info)| info)
+  }
+
+  pub fn metadata(&self) -> &HashMap<String, String> {
+    &self.metadata
+  }
+
+  pub fn load<T: SafetensorsElement>(&mut self, name: &str) -> Result<Matrix<T>, String> {
+    let info = self.info(name).ok_or_else(|| format!("No tensor named '{}'", name))?.clone();
+    if info.dtype != T::DTYPE {
+      return Err(format!("dtype mismatch for '{}': file has {}, expected {}", name, info.dtype, T::DTYPE));
+    }
+
+    let (rows, cols) = match info.shape.as_slice() {
+      [] => (1, 1),
+      [n] => (1, *n),
+      [r, c] => (*r, *c),
+      shape => return Err(format!("Tensor '{}' has {} dimensions, expected at most 2", name, shape.len()))
+    };
+
+    let size = rows
+      .checked_mul(cols)
+      .and_then(|n| n.checked_mul(T::SIZE))
+      .ok_or_else(|| format!("Tensor '{}' has shape ({}, {}), which is too large", name, rows, cols))?;
+    let (begin, end) = info.data_offsets;
+    if end < begin || end - begin != size {
+      return Err(format!("Tensor '{}' has inconsistent data offsets", name));
+    }
+    if end as u64 > self.data_len {
+      return Err(format!("Tensor '{}' ends at byte {} but the data section has only {} bytes", name, end, self.data_len));
+    }
+
+    let mut bytes = vec![0u8; end - begin];
+    self.reader.seek(SeekFrom::Start(self.data_start + begin as u64)).map_err(|e| e.to_string())?;
+    self.reader.read_exact(&mut bytes).map_err(|e| e.to_string())?;
+
+    let data = bytes.chunks_exact(T::SIZE).map(|chunk| T::read_bytes(chunk, false)).collect();
+    Matrix::from_vec(rows, cols, data)
+  }
+
+  pub fn load_all<T: SafetensorsElement>(&mut self) -> Result<Vec<(String, Matrix<T>)>, String> {
+    let names: Vec<String> = self.tensors.iter().map(|(name, _)| name.clone()).collect();
+    names
+      .into_iter()
+      .map(|name| self.load(&name).map(|matrix| (name, matrix)))
+      .collect()
+  }
+}
+
+fn parse_info(value: &Value) -> Option<TensorInfo> {
+  let dtype = value.get("dtype")?.as_str()?.to_string();
+  let shape = value
+    .get("shape")?
+    .as_array()?
+    .iter()
+    .map(|dim| dim.as_u64().map(|d| d as usize))
+    .collect::<Option<Vec<_>>>()?;
+  let offsets = value.get("data_offsets")?.as_array()?;
+  if offsets.len() != 2 {
+    return None;
+  }
+
+  Some(TensorInfo {
+    dtype,
+    shape,
+    data_offsets: (offsets[0].as_u64()? as usize, offsets[1].as_u64()? as usize)
+  })
+}
+
+pub fn write_safetensors<T, W>(
+  mut writer: W,
+  tensors: &[(&str, &Matrix<T>)],
+  metadata: Option<&HashMap<String, String>>
+) -> Result<(), String>
+where
+  T: SafetensorsElement,
+  W: Write
+{
+  let mut header = Map::new();
+  if let Some(metadata) = metadata {
+    header.insert("__metadata__".to_string(), json!(metadata));
+  }
+
+  let mut offset = 0;
+  for (name, matrix) in tensors {
+    if header.contains_key(*name) {
+      return Err(format!("Duplicate tensor name '{}'", name));
+    }
+
+    let len = matrix.data.len() * T::SIZE;
+    header.insert(
+      name.to_string(),
+      json!({
+        "dtype": T::DTYPE,
+        "shape": [matrix.rows, matrix.cols],
+        "data_offsets": [offset, offset + len]
+      })
+    );
+    offset += len;
+  }
+
+  let mut header = serde_json::to_string(&header).map_err(|e| e.to_string())?;
+  header.push_str(&" ".repeat((8 - header.len() % 8) % 8));
+
+  let mut out = Vec::with_capacity(8 + header.len() + offset);
+  out.extend_from_slice(&(header.len() as u64).to_le_bytes());
+  out.extend_from_slice(header.as_bytes());
+  for (_, matrix) in tensors {
+    for value in &matrix.data {
+      value.write_le(&mut out);
+    }
+  }
+
+  writer.write_all(&out).map_err(|e| e.to_string())
+}
+
+pub fn save_safetensors<T, P>(
+  path: P,
+  tensors: &[(&str, &Matrix<T>)],
+  metadata: Option<&HashMap<String, String>>
+) -> Result<(), String>
+where
+  T: SafetensorsElement,
+  P: AsRef<Path>
+{
+  let file = File::create(path).map_err(|e| e.to_string())?;
+  write_safetensors(BufWriter::new(file), tensors, metadata)
+}
@@ -1,4 +1,22 @@
+pub mod audio;
+pub mod autograd;
+pub mod data;
+pub mod init;
+pub mod interop;
+pub mod io;
 pub mod math;
+pub mod metrics;
+pub mod ml;
+pub mod nn;
+pub mod onnx;
+pub mod optim;
+pub mod preprocess;
+pub mod random;
+pub mod tensor;
+pub mod text;
+pub mod train;
+
+pub use random::{is_deterministic, seed, set_deterministic};
 
 pub fn add(left: u64, right: u64) -> u64 {
     left + right
@@ -36,6 +54,4467 @@ mod tests {
         println!("{}", m3.determinant().unwrap());
     }
 
+    #[test]
+    fn matrix_eq_test() {
+        let a: Matrix<i32> = Matrix::from_vec(2, 2, vec![1, 2, 3, 4]).unwrap();
+        let b: Matrix<i32> = Matrix::from_vec(2, 2, vec![1, 2, 3, 4]).unwrap();
+        let c: Matrix<i32> = Matrix::from_vec(1, 4, vec![1, 2, 3, 4]).unwrap();
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+
+        let x: Matrix<f64> = Matrix::from_vec(1, 2, vec![0.1 + 0.2, 1.0]).unwrap();
+        let y: Matrix<f64> = Matrix::from_vec(1, 2, vec![0.3, 1.0]).unwrap();
+        assert!(x != y);
+        assert!(x.approx_eq(&y, 1e-12));
+        let z: Matrix<f64> = Matrix::from_vec(2, 1, vec![0.3, 1.0]).unwrap();
+        assert!(!x.approx_eq(&z, 1e-12));
+        assert_matrix_approx_eq!(x, y);
+        assert_matrix_approx_eq!(x, y, 1e-9);
+    }
+
+    #[test]
+    #[should_panic]
+    fn matrix_approx_eq_panics_test() {
+        let x: Matrix<f32> = Matrix::from_vec(1, 1, vec![1.0]).unwrap();
+        let y: Matrix<f32> = Matrix::from_vec(1, 1, vec![1.1]).unwrap();
+        assert_matrix_approx_eq!(x, y, 1e-3);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_test() {
+        let m: Matrix<f64> = Matrix::from_vec(2, 2, vec![1.0, 2.0, 3.0, 4.0]).unwrap();
+        let json = serde_json::to_string(&m).unwrap();
+        let back: Matrix<f64> = serde_json::from_str(&json).unwrap();
+        assert_eq!(m, back);
+
+        let v: Vector<i32> = Vector::from(vec![1, 2, 3]);
+        let back: Vector<i32> = serde_json::from_str(&serde_json::to_string(&v).unwrap()).unwrap();
+        assert_eq!(v, back);
+
+        let bad = r#"{"rows":2,"cols":3,"data":[1.0,2.0]}"#;
+        assert!(serde_json::from_str::<Matrix<f64>>(bad).is_err());
+    }
+
+    #[test]
+    fn npy_test() {
+        use io::npy::{read_npy, write_npy};
+
+        let m: Matrix<f64> = Matrix::from_vec(2, 3, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]).unwrap();
+        let mut bytes = Vec::new();
+        write_npy(&mut bytes, &m).unwrap();
+        assert_eq!(bytes.len() % 64, 48);
+        assert_eq!((bytes.len() - 48) % 64, 0);
+        assert_eq!(read_npy::<f64, _>(bytes.as_slice()).unwrap(), m);
+        assert!(read_npy::<f32, _>(bytes.as_slice()).is_err());
+
+        let header = "{'descr': '<i4', 'fortran_order': True, 'shape': (2, 2), }";
+        let mut raw = b"\x93NUMPY\x01\x00".to_vec();
+        raw.extend_from_slice(&(header.len() as u16).to_le_bytes());
+        raw.extend_from_slice(header.as_bytes());
+        for x in [1i32, 3, 2, 4] {
+            raw.extend_from_slice(&x.to_le_bytes());
+        }
+        let expected: Matrix<i32> = Matrix::from_vec(2, 2, vec![1, 2, 3, 4]).unwrap();
+        assert_eq!(read_npy::<i32, _>(raw.as_slice()).unwrap(), expected);
+
+        let malformed = |header: &str| {
+            let mut raw = b"\x93NUMPY\x01\x00".to_vec();
+            raw.extend_from_slice(&(header.len() as u16).to_le_bytes());
+            raw.extend_from_slice(header.as_bytes());
+            read_npy::<f64, _>(raw.as_slice())
+        };
+        assert!(malformed("{'descr': '', 'fortran_order': False, 'shape': (1, 1), }").is_err());
+        assert!(malformed("{'descr': 'é8', 'fortran_order': False, 'shape': (1, 1), }").is_err());
+        let huge = format!("{{'descr': '<f8', 'fortran_order': False, 'shape': ({}, {}), }}", usize::MAX, 2);
+        assert!(malformed(&huge).unwrap_err().contains("too large"));
+        assert!(malformed("{'descr': '<f8', 'fortran_order': False, 'shape': (1000000, 1000000), }").unwrap_err().contains("Truncated"));
+    }
+
+    #[cfg(feature = "npz")]
+    #[test]
+    fn npz_test() {
+        use io::npy::{load_npz, save_npz};
+
+        let a: Matrix<f32> = Matrix::from_vec(1, 2, vec![1.0, 2.0]).unwrap();
+        let b: Matrix<f32> = Matrix::from_vec(2, 2, vec![1.0, 0.0, 0.0, 1.0]).unwrap();
+        let path = std::env::temp_dir().join("oxidizeai_npz_test.npz");
+        save_npz(&path, &[("a", &a), ("b", &b)], true).unwrap();
+        let arrays = load_npz::<f32, _>(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(arrays, vec![("a".to_string(), a), ("b".to_string(), b)]);
+    }
+
+    #[test]
+    fn csv_test() {
+        use io::csv::CsvOptions;
+
+        let input = "a;b\n1.5;2\n\n3;-4\n";
+        let options = CsvOptions { delimiter: ';', ..CsvOptions::default() };
+        let m: Matrix<f64> = Matrix::from_csv(input.as_bytes(), &options).unwrap();
+        assert_eq!(m, Matrix::from_vec(2, 2, vec![1.5, 2.0, 3.0, -4.0]).unwrap());
+
+        let mut out = Vec::new();
+        m.to_csv(&mut out, Some(&["a", "b"]), &options).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "a;b\n1.5;2\n3;-4\n");
+
+        let err = Matrix::<i32>::from_csv("x,y\n1,oops\n".as_bytes(), &CsvOptions::default()).unwrap_err();
+        assert!(err.contains("Line 2, column 2 ('y')"), "{}", err);
+        let err = Matrix::<i32>::from_csv("1,2\n3\n".as_bytes(), &CsvOptions { has_header: false, ..CsvOptions::default() }).unwrap_err();
+        assert!(err.contains("expected 2 fields"), "{}", err);
+
+        let mut out = Vec::new();
+        m.to_csv(&mut out, Some(&["first\nline", "b"]), &options).unwrap();
+        assert_eq!(Matrix::<f64>::from_csv(out.as_slice(), &options).unwrap(), m);
+        let records = io::csv::read_records(out.as_slice(), &options).unwrap();
+        assert_eq!(records.headers.unwrap(), vec!["first\nline", "b"]);
+        let err = Matrix::<i32>::from_csv("\"x\nz\",y\n1,2\n3,oops\n".as_bytes(), &CsvOptions::default()).unwrap_err();
+        assert!(err.contains("Line 4"), "{}", err);
+    }
+
+    #[test]
+    fn mtx_test() {
+        use io::mtx::{read_mtx, write_mtx, write_mtx_coordinate};
+
+        let input = "%%MatrixMarket matrix coordinate real symmetric\n% comment\n3 3 3\n1 1 2.0\n3 1 -1.5\n2 2 4\n";
+        let m: Matrix<f64> = read_mtx(input.as_bytes()).unwrap();
+        assert_eq!(m, Matrix::from_vec(3, 3, vec![2.0, 0.0, -1.5, 0.0, 4.0, 0.0, -1.5, 0.0, 0.0]).unwrap());
+
+        let mut out = Vec::new();
+        write_mtx(&mut out, &m).unwrap();
+        assert_eq!(read_mtx::<f64, _>(out.as_slice()).unwrap(), m);
+
+        let mut out = Vec::new();
+        write_mtx_coordinate(&mut out, &m).unwrap();
+        assert!(String::from_utf8(out.clone()).unwrap().contains("3 3 4\n"));
+        assert_eq!(read_mtx::<f64, _>(out.as_slice()).unwrap(), m);
+
+        let skew = "%%MatrixMarket matrix array integer skew-symmetric\n2 2\n5\n";
+        let s: Matrix<i32> = read_mtx(skew.as_bytes()).unwrap();
+        assert_eq!(s, Matrix::from_vec(2, 2, vec![0, -5, 5, 0]).unwrap());
+
+        let pattern = "%%MatrixMarket matrix coordinate pattern general\n2 2 2\n1 2\n2 1\n";
+        let p: Matrix<i8> = read_mtx(pattern.as_bytes()).unwrap();
+        assert_eq!(p, Matrix::from_vec(2, 2, vec![0, 1, 1, 0]).unwrap());
+
+        assert!(read_mtx::<u32, _>(skew.as_bytes()).unwrap_err().contains("cannot negate"));
+        let huge = format!("%%MatrixMarket matrix coordinate real general\n{} 2 1\n", usize::MAX);
+        assert!(read_mtx::<f64, _>(huge.as_bytes()).unwrap_err().contains("too large"));
+        let crowded = "%%MatrixMarket matrix coordinate real general\n2 2 5\n";
+        assert!(read_mtx::<f64, _>(crowded.as_bytes()).unwrap_err().contains("do not fit"));
+    }
+
+    #[test]
+    fn matrix_display_test() {
+        use math::matrix::PrintOptions;
+
+        let m: Matrix<f64> = Matrix::from_vec(2, 3, vec![1.0, -20.5, 3.0, 400.0, 5.0, 6.25]).unwrap();
+        assert_eq!(format!("{}", m), "[[  1, -20.5,    3],\n [400,     5, 6.25]]");
+        assert_eq!(format!("{:.1}", m), "[[  1.0, -20.5, 3.0],\n [400.0,   5.0, 6.2]]");
+        assert_eq!(format!("{:.1e}", m), "[[1.0e0, -2.0e1, 3.0e0],\n [4.0e2,  5.0e0, 6.2e0]]");
+        assert_eq!(format!("{}", Matrix::<i32>::new(0, 0)), "[]");
+
+        let big: Matrix<i32> = Matrix::from_vec(5, 5, (0..25).collect()).unwrap();
+        let options = PrintOptions { threshold: 10, edge_items: 1, ..PrintOptions::default() };
+        assert_eq!(
+            format!("{}", big.display_with(options)),
+            "[[ 0, ...,  4],\n [...],\n [20, ..., 24]]"
+        );
+    }
+
+    #[test]
+    fn matrix_conversions_test() {
+        let expected: Matrix<i32> = Matrix::from_vec(2, 3, vec![1, 2, 3, 4, 5, 6]).unwrap();
+        assert_eq!(Matrix::from([[1, 2, 3], [4, 5, 6]]), expected);
+        assert_eq!(Matrix::try_from(vec![vec![1, 2, 3], vec![4, 5, 6]]).unwrap(), expected);
+        assert!(Matrix::try_from(vec![vec![1, 2], vec![3]]).is_err());
+        assert_eq!(Matrix::from_fn(2, 3, |i, j| (i * 3 + j + 1) as i32), expected);
+    }
+
+    #[test]
+    fn matrix_unchecked_index_test() {
+        let mut m: Matrix<i32> = Matrix::from_vec(2, 3, vec![1, 2, 3, 4, 5, 6]).unwrap();
+        unsafe {
+            assert_eq!(*m.get_unchecked(1, 2), 6);
+            *m.get_unchecked_mut(0, 1) = 20;
+        }
+        assert_eq!(m[(0, 1)], 20);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "index (0, 3) out of bounds for 2x3 matrix")]
+    fn matrix_index_out_of_bounds_test() {
+        let m: Matrix<i32> = Matrix::from_vec(2, 3, vec![1, 2, 3, 4, 5, 6]).unwrap();
+        let _ = m[(0, 3)];
+    }
+
+    #[test]
+    fn shape_test() {
+        use math::shape::{Shape, ShapeError};
+
+        let a: Matrix<i32> = Matrix::from_vec(2, 3, vec![1, 2, 3, 4, 5, 6]).unwrap();
+        let b: Matrix<i32> = Matrix::from_vec(3, 2, vec![1, 2, 3, 4, 5, 6]).unwrap();
+        assert_eq!(a.shape(), Shape::new(2, 3));
+        assert!(!a.same_shape(&b));
+        assert_eq!(a.check_matmul_compat(&b), Ok(Shape::new(2, 2)));
+        assert_eq!(
+            a.check_same_shape(&b, "add"),
+            Err(ShapeError::Mismatch { op: "add", left: Shape::new(2, 3), right: Shape::new(3, 2) })
+        );
+        assert_eq!((a.clone() + b.clone()).unwrap_err(), "add: shape mismatch between 2x3 and 3x2");
+        assert_eq!(a.trace().unwrap_err(), "trace: expected a square matrix, found 2x3");
+        assert!(a.check_matmul_compat(&a).is_err());
+    }
+
+    #[test]
+    fn scalar_test() {
+        use math::scalar::{Float, Scalar};
+
+        fn sum_of_squares<T: Scalar>(xs: &[T]) -> T {
+            xs.iter().fold(T::zero(), |acc, &x| acc + x * x)
+        }
+
+        assert_eq!(sum_of_squares(&[1u8, 2, 3]), 14);
+        assert_eq!(sum_of_squares(&[0.5f32, 1.5]), 2.5);
+
+        let v: Vector<f32> = Vector::from(vec![3.0, 4.0]);
+        assert_eq!(v.l2_norm(), 5.0);
+        assert_eq!(v.normalize(), Vector::from(vec![0.6, 0.8]));
+        assert_eq!(<f64 as Float>::from_f64(2.0).sqrt().powi(2).round(), 2.0);
+
+        let m: Matrix<u8> = Matrix::from_vec(2, 2, vec![1, 2, 3, 4]).unwrap();
+        assert_eq!(m.trace().unwrap(), 5);
+    }
+
+    #[test]
+    fn ones_identity_test() {
+        let ones: Matrix<u8> = Matrix::ones(2, 2);
+        assert_eq!(ones, Matrix::from_vec(2, 2, vec![1, 1, 1, 1]).unwrap());
+        let id: Matrix<f32> = Matrix::identity(2);
+        assert_eq!(id, Matrix::from_vec(2, 2, vec![1.0, 0.0, 0.0, 1.0]).unwrap());
+        let signed: Matrix<i8> = Matrix::identity(2);
+        assert_eq!(signed.trace().unwrap(), 2);
+    }
+
+    #[cfg(feature = "ndarray")]
+    #[test]
+    fn ndarray_interop_test() {
+        use ndarray::{array, s, Array1, Array2};
+
+        let m: Matrix<i32> = Matrix::from_vec(2, 3, vec![1, 2, 3, 4, 5, 6]).unwrap();
+        let a: Array2<i32> = m.clone().into();
+        assert_eq!(a, array![[1, 2, 3], [4, 5, 6]]);
+        assert_eq!(m.view(), a.view());
+        assert_eq!(Matrix::from(a.clone()), m);
+        assert_eq!(Matrix::from(a.t().to_owned()), m.transpose());
+        assert_eq!(Matrix::from(a.slice(s![.., 1..]).to_owned()), Matrix::from([[2, 3], [5, 6]]));
+
+        let v: Vector<f64> = Vector::from(vec![1.0, 2.0]);
+        let b: Array1<f64> = v.clone().into();
+        assert_eq!(Vector::from(b), v);
+    }
+
+    #[cfg(feature = "nalgebra")]
+    #[test]
+    fn nalgebra_interop_test() {
+        use nalgebra::{DMatrix, DVector};
+
+        let m: Matrix<f64> = Matrix::from_vec(2, 3, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]).unwrap();
+        let d: DMatrix<f64> = m.clone().into();
+        assert_eq!(d, DMatrix::from_row_slice(2, 3, &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]));
+        assert_eq!(DMatrix::from(&m), d);
+        assert_eq!(Matrix::from(d.clone()), m);
+        assert_eq!(Matrix::from(d.transpose()), m.transpose());
+
+        let v: Vector<f64> = Vector::from(vec![1.0, 2.0]);
+        let dv: DVector<f64> = v.clone().into();
+        assert_eq!(Vector::from(dv), v);
+    }
+
+    #[cfg(feature = "hdf5")]
+    #[test]
+    fn hdf5_test() {
+        use hdf5_metno_sys::h5::{hsize_t, H5open};
+        use hdf5_metno_sys::h5d::{H5Dclose, H5Dcreate2, H5Dwrite};
+        use hdf5_metno_sys::h5f::{H5Fclose, H5Fcreate, H5F_ACC_TRUNC};
+        use hdf5_metno_sys::h5i::hid_t;
+        use hdf5_metno_sys::h5p::H5P_DEFAULT;
+        use hdf5_metno_sys::h5s::{H5Sclose, H5Screate_simple, H5S_ALL};
+        use hdf5_metno_sys::h5t;
+        use io::hdf5::Hdf5File;
+        use std::ffi::CString;
+
+        let path = std::env::temp_dir().join("oxidizeai_hdf5_test.h5");
+        let c_path = CString::new(path.to_string_lossy().as_bytes()).unwrap();
+        let write = |file: hid_t, name: &str, dims: &[hsize_t], dtype: hid_t, data: *const std::ffi::c_void| unsafe {
+            let c_name = CString::new(name).unwrap();
+            let space = H5Screate_simple(dims.len() as i32, dims.as_ptr(), std::ptr::null());
+            let dataset = H5Dcreate2(file, c_name.as_ptr(), dtype, space, H5P_DEFAULT, H5P_DEFAULT, H5P_DEFAULT);
+            assert!(space >= 0 && dataset >= 0);
+            assert!(H5Dwrite(dataset, dtype, H5S_ALL, H5S_ALL, H5P_DEFAULT, data) >= 0);
+            H5Dclose(dataset);
+            H5Sclose(space);
+        };
+        let grid: Vec<f64> = (0..12).map(|i| i as f64 * 0.5).collect();
+        let counts: Vec<i32> = vec![3, 1, 4, 1, 5];
+        unsafe {
+            H5open();
+            let file = H5Fcreate(c_path.as_ptr(), H5F_ACC_TRUNC, H5P_DEFAULT, H5P_DEFAULT);
+            assert!(file >= 0);
+            write(file, "grid", &[4, 3], *h5t::H5T_NATIVE_DOUBLE, grid.as_ptr().cast());
+            write(file, "counts", &[5], *h5t::H5T_NATIVE_INT32, counts.as_ptr().cast());
+            H5Fclose(file);
+        }
+
+        let file = Hdf5File::open(&path).unwrap();
+        assert_eq!(file.shape("grid").unwrap(), vec![4, 3]);
+        assert_eq!(file.read_matrix::<f64>("grid").unwrap(), Matrix::from_vec(4, 3, grid.clone()).unwrap());
+        assert_eq!(file.read_rows::<f64>("grid", 1..3).unwrap(), Matrix::from_vec(2, 3, grid[3..9].to_vec()).unwrap());
+        assert_eq!(file.read_rows::<f32>("grid", 3..4).unwrap(), Matrix::from_vec(1, 3, vec![4.5f32, 5.0, 5.5]).unwrap());
+        assert_eq!(file.read_vector::<i32>("counts").unwrap(), Vector::from(counts.clone()));
+        assert_eq!(file.read_slice::<i32>("counts", 1..4).unwrap(), Vector::from(vec![1, 4, 1]));
+        assert_eq!(file.read_matrix::<i64>("counts").unwrap(), Matrix::from_vec(1, 5, vec![3i64, 1, 4, 1, 5]).unwrap());
+
+        assert!(file.read_rows::<f64>("grid", 2..5).is_err());
+        assert!(file.read_slice::<i32>("counts", 4..6).is_err());
+        assert!(file.read_vector::<f64>("grid").is_err());
+        assert!(file.read_matrix::<f64>("missing").is_err());
+        drop(file);
+        std::fs::remove_file(&path).unwrap();
+        assert!(Hdf5File::open(&path).is_err());
+    }
+
+    #[cfg(feature = "safetensors")]
+    #[test]
+    fn safetensors_test() {
+        use io::safetensors::{write_safetensors, SafetensorsReader};
+        use std::collections::HashMap;
+        use std::io::Cursor;
+
+        let w: Matrix<f32> = Matrix::from_vec(2, 3, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]).unwrap();
+        let b: Matrix<f32> = Matrix::from_vec(1, 3, vec![0.5, 0.25, 0.125]).unwrap();
+        let metadata = HashMap::from([("format".to_string(), "pt".to_string())]);
+
+        let mut bytes = Vec::new();
+        write_safetensors(&mut bytes, &[("weight", &w), ("bias", &b)], Some(&metadata)).unwrap();
+        let header_len = u64::from_le_bytes(bytes[..8].try_into().unwrap()) as usize;
+        assert_eq!(header_len % 8, 0);
+
+        let mut reader = SafetensorsReader::new(Cursor::new(bytes)).unwrap();
+        assert_eq!(reader.names(), vec!["weight", "bias"]);
+        assert_eq!(reader.metadata(), &metadata);
+        assert_eq!(reader.load::<f32>("bias").unwrap(), b);
+        assert_eq!(reader.load::<f32>("weight").unwrap(), w);
+        assert!(reader.load::<f64>("weight").is_err());
+        assert!(reader.load::<f32>("missing").is_err());
+
+        assert!(SafetensorsReader::new(Cursor::new(u64::MAX.to_le_bytes().to_vec())).is_err());
+        let mut truncated = 64u64.to_le_bytes().to_vec();
+        truncated.extend_from_slice(b"{}");
+        assert!(SafetensorsReader::new(Cursor::new(truncated)).err().unwrap().contains("only 2 bytes"));
+
+        let crafted = |header: &str| {
+            let mut bytes = (header.len() as u64).to_le_bytes().to_vec();
+            bytes.extend_from_slice(header.as_bytes());
+            bytes.extend_from_slice(&[0; 8]);
+            SafetensorsReader::new(Cursor::new(bytes)).unwrap()
+        };
+        let mut huge = crafted(r#"{"x":{"dtype":"F32","shape":[4294967296,4294967296],"data_offsets":[0,0]}}"#);
+        assert!(huge.load::<f32>("x").unwrap_err().contains("too large"));
+        let mut past_end = crafted(r#"{"x":{"dtype":"F32","shape":[1,1024],"data_offsets":[0,4096]}}"#);
+        assert!(past_end.load::<f32>("x").unwrap_err().contains("only 8 bytes"));
+    }
+
+    #[cfg(feature = "arrow")]
+    #[test]
+    fn arrow_test() {
+        use arrow_array::{ArrayRef, Float32Array, Int64Array, RecordBatch, StringArray};
+        use io::arrow::{read_parquet, record_batch_to_matrix, ArrowOptions, NullPolicy};
+        use std::sync::Arc;
+
+        let batch = RecordBatch::try_from_iter(vec![
+            ("a", Arc::new(Int64Array::from(vec![Some(1), None, Some(3)])) as ArrayRef),
+            ("b", Arc::new(Float32Array::from(vec![0.5, 1.5, 2.5])) as ArrayRef),
+            ("name", Arc::new(StringArray::from(vec!["x", "y", "z"])) as ArrayRef),
+        ])
+        .unwrap();
+
+        let options = ArrowOptions { columns: Some(vec!["b".to_string(), "a".to_string()]), ..ArrowOptions::default() };
+        assert!(record_batch_to_matrix(&batch, &options).unwrap_err().contains("'a' at row 1"));
+        assert!(record_batch_to_matrix(&batch, &ArrowOptions::default()).unwrap_err().contains("non-numeric"));
+
+        let fill = ArrowOptions { null_policy: NullPolicy::Fill(-1.0), ..options.clone() };
+        let expected = Matrix::from([[0.5, 1.0], [1.5, -1.0], [2.5, 3.0]]);
+        assert_eq!(record_batch_to_matrix(&batch, &fill).unwrap(), expected);
+
+        let drop = ArrowOptions { null_policy: NullPolicy::DropRow, ..options.clone() };
+        assert_eq!(record_batch_to_matrix(&batch, &drop).unwrap(), Matrix::from([[0.5, 1.0], [2.5, 3.0]]));
+
+        let path = std::env::temp_dir().join("oxidizeai_arrow_test.parquet");
+        let file = std::fs::File::create(&path).unwrap();
+        let mut writer = parquet::arrow::ArrowWriter::try_new(file, batch.schema(), None).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+        let loaded = read_parquet(&path, &fill).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, expected);
+    }
+
+    #[test]
+    fn broadcast_test() {
+        use tensor::Tensor;
+
+        let activations: Matrix<f64> = Matrix::from_fn(32, 10, |i, j| (i * 10 + j) as f64);
+        let bias: Vector<f64> = Vector::from((0..10).map(|j| j as f64).collect::<Vec<_>>());
+        let out = (activations.clone() + bias).unwrap();
+        assert_eq!(out.shape(), activations.shape());
+        assert_eq!(out[(3, 4)], 38.0);
+        assert!((activations.clone() + Vector::from(vec![1.0; 3])).unwrap_err().contains("32x10 and 1x3"));
+
+        let col: Matrix<f64> = Matrix::from_vec(32, 1, vec![1.0; 32]).unwrap();
+        assert_eq!((activations.clone() - col).unwrap()[(0, 0)], -1.0);
+
+        let a: Tensor<i32> = Tensor::from_vec(&[2, 1, 3], vec![1, 2, 3, 4, 5, 6]).unwrap();
+        let b: Tensor<i32> = Tensor::from_vec(&[2, 1], vec![10, 20]).unwrap();
+        let sum = (&a + &b).unwrap();
+        assert_eq!(sum.shape(), &[2, 2, 3]);
+        assert_eq!(sum.to_vec(), vec![11, 12, 13, 21, 22, 23, 14, 15, 16, 24, 25, 26]);
+
+        let bias: Tensor<i32> = Tensor::from(Vector::from(vec![1, 2]));
+        let err = (&a + &bias).unwrap_err();
+        assert_eq!(err, "cannot broadcast shapes [2, 1, 3] and [2]");
+
+        let expanded = b.broadcast_to(&[4, 2, 3]).unwrap();
+        assert!(expanded.shares_storage(&b));
+        assert_eq!(expanded.get(&[3, 1, 2]), Some(&20));
+    }
+
+    #[test]
+    fn tensor_reduce_test() {
+        use tensor::Tensor;
+
+        let t: Tensor<f64> = Tensor::from_vec(&[2, 3], vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]).unwrap();
+        assert_eq!(t.sum(&[0], false).unwrap().to_vec(), vec![5.0, 7.0, 9.0]);
+        let rows = t.sum(&[1], true).unwrap();
+        assert_eq!(rows.shape(), &[2, 1]);
+        assert_eq!(rows.to_vec(), vec![6.0, 15.0]);
+        assert_eq!(t.sum(&[], false).unwrap().shape(), &[] as &[usize]);
+        assert_eq!(t.sum_all(), 21.0);
+        assert_eq!(t.mean(&[0, 1], false).unwrap().to_vec(), vec![3.5]);
+        assert_eq!(t.max(&[1], false).unwrap().to_vec(), vec![3.0, 6.0]);
+        assert_eq!(t.min(&[0], true).unwrap().to_vec(), vec![1.0, 2.0, 3.0]);
+        assert_eq!(t.var(&[1], false).unwrap().to_vec(), vec![2.0 / 3.0, 2.0 / 3.0]);
+        assert_eq!(t.prod(&[0], false).unwrap().to_vec(), vec![4.0, 10.0, 18.0]);
+        assert_eq!(t.transpose().sum(&[0], false).unwrap().to_vec(), vec![6.0, 15.0]);
+        assert!(t.sum(&[2], false).is_err());
+    }
+
+    #[test]
+    fn tensor_slice_test() {
+        use tensor::Tensor;
+
+        let t: Tensor<i32> = Tensor::from_vec(&[2, 3, 4], (0..24).collect()).unwrap();
+        let view = t.slice(&s![.., 0..2, 3]).unwrap();
+        assert_eq!(view.shape(), &[2, 2]);
+        assert_eq!(view.to_vec(), vec![3, 7, 15, 19]);
+        assert!(view.shares_storage(&t));
+
+        let stepped = t.slice(&s![1, .., tensor::slice::SliceArg::from(0..4).step_by(2)]).unwrap();
+        assert_eq!(stepped.to_vec(), vec![12, 14, 16, 18, 20, 22]);
+        assert_eq!(t.slice(&s![1]).unwrap().shape(), &[3, 4]);
+        assert!(t.slice(&s![2]).is_err());
+        assert!(t.slice(&s![.., 1..4, ..5]).is_err());
+
+        let rows = t.take(&[2, 0], 1).unwrap();
+        assert_eq!(rows.shape(), &[2, 2, 4]);
+        assert_eq!(rows.slice(&s![0, .., 0]).unwrap().to_vec(), vec![8, 0]);
+        assert_eq!(t.compress(&[false, true], 0).unwrap(), t.slice(&s![1..2]).unwrap());
+
+        let m: Tensor<i32> = Tensor::from_vec(&[2, 2], vec![1, -2, 3, -4]).unwrap();
+        let mask = m.map(|&x| x > 0);
+        assert_eq!(m.masked_select(&mask).unwrap().to_vec(), vec![1, 3]);
+        let column_mask = Tensor::from_vec(&[2], vec![false, true]).unwrap();
+        assert_eq!(m.masked_select(&column_mask).unwrap().to_vec(), vec![-2, -4]);
+    }
+
+    #[test]
+    fn dual_test() {
+        use autograd::dual::{derivative, jacobian, jvp, Dual};
+        use math::scalar::Float;
+
+        let (y, dy) = derivative(|x: Dual| x * x * x + x.exp(), 2.0);
+        assert!((y - (8.0 + 2f64.exp())).abs() < 1e-12);
+        assert!((dy - (12.0 + 2f64.exp())).abs() < 1e-12);
+        assert!((derivative(|x: Dual| x.sqrt().ln(), 4.0).1 - 0.125).abs() < 1e-12);
+
+        let a: Matrix<Dual> = Matrix::from_vec(2, 2, vec![Dual::variable(1.0), Dual::constant(2.0), Dual::constant(3.0), Dual::constant(4.0)]).unwrap();
+        let det = a.determinant().unwrap();
+        assert_eq!((det.value, det.deriv), (-2.0, 4.0));
+
+        let f = |v: &Vector<Dual>| Vector::from(vec![v[0] * v[1], v[0].tanh() + v[1]]);
+        let x = Vector::from(vec![0.5, 3.0]);
+        let (out, tangent) = jvp(f, &x, &Vector::from(vec![1.0, 0.0])).unwrap();
+        assert_eq!(out[0], 1.5);
+        assert_eq!(tangent[0], 3.0);
+        let jac = jacobian(f, &x);
+        assert_eq!(jac[(0, 1)], 0.5);
+        assert!((jac[(1, 0)] - (1.0 - 0.5f64.tanh().powi(2))).abs() < 1e-12);
+        assert!(jvp(f, &x, &Vector::from(vec![1.0])).is_err());
+    }
+
+    #[test]
+    fn grad_check_test() {
+        use autograd::{grad_check, Var};
+        use tensor::Tensor;
+
+        let x: Tensor<f64> = Tensor::from_vec(&[2, 3], vec![0.5, -1.0, 2.0, 1.5, 0.3, -0.7]).unwrap();
+        let w: Tensor<f64> = Tensor::from_vec(&[3, 2], vec![0.1, 0.2, -0.3, 0.4, 0.5, -0.6]).unwrap();
+        let b: Tensor<f64> = Tensor::from_vec(&[2], vec![0.05, -0.05]).unwrap();
+        let f = |v: &[Var]| {
+            let hidden = (&v[0].matmul(&v[1])? + &v[2])?.tanh();
+            (&hidden.powi(2) / &v[0].exp().sum(&[1], true)?)?.mean(&[], false)
+        };
+        let report = grad_check(f, &[x.clone(), w.clone(), b.clone()], 1e-6, 1e-6).unwrap();
+        assert!(report.passed, "{}", report);
+
+        let wx = Var::param(x.clone());
+        let loss = (&wx * &wx).unwrap().sum(&[], false).unwrap();
+        loss.backward().unwrap();
+        assert_eq!(wx.grad().unwrap(), x.map(|&v| 2.0 * v));
+        assert!(Var::constant(x.clone()).backward().is_err());
+
+        let broken = |v: &[Var]| Ok(Var::constant(v[0].value().map(|&x| x * x)));
+        let report = grad_check(broken, &[x], 1e-6, 1e-6).unwrap();
+        assert!(!report.passed);
+        assert_eq!((report.input, report.analytic), (0, 0.0));
+        assert_eq!(report.index, vec![0, 0]);
+        assert!((report.numeric - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn graph_export_test() {
+        use autograd::Var;
+        use tensor::Tensor;
+
+        let w = Var::param(Tensor::from_vec(&[2, 2], vec![1.0, 2.0, 3.0, 4.0]).unwrap());
+        let x = Var::constant(Tensor::from_vec(&[1, 2], vec![0.5, -0.5]).unwrap());
+        let y = x.matmul(&w).unwrap().tanh().sum(&[], false).unwrap();
+        let graph = y.graph();
+        assert_eq!(graph.nodes.iter().map(|n| n.op).collect::<Vec<_>>(), vec!["parameter", "constant", "matmul", "tanh", "sum"]);
+        assert_eq!(graph.edges.len(), 4);
+        assert!(graph.nodes[0].is_param && !graph.nodes[1].is_param);
+
+        let dot = graph.to_dot();
+        assert!(dot.starts_with("digraph autograd {"));
+        assert!(dot.contains("label=\"matmul\\n[1, 2]\""));
+        assert!(dot.contains("fillcolor=lightblue"));
+        let json: serde_json::Value = serde_json::from_str(&graph.to_json()).unwrap();
+        assert_eq!(json["nodes"][2]["op"], "matmul");
+        assert_eq!(json["edges"].as_array().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn no_grad_test() {
+        use autograd::{is_grad_enabled, no_grad, BackwardOptions, Var};
+        use tensor::Tensor;
+
+        let w = Var::param(Tensor::from_vec(&[2], vec![1.0, 2.0]).unwrap());
+        let y = no_grad(|| {
+            assert!(!is_grad_enabled());
+            w.exp()
+        });
+        assert!(is_grad_enabled());
+        assert!(!y.requires_grad() && y.is_leaf());
+
+        let d = w.mul_scalar(3.0).detach();
+        assert!(!d.requires_grad());
+        assert_eq!(d.value().to_vec(), vec![3.0, 6.0]);
+
+        let loss = (&w * &w).unwrap().sum(&[], false).unwrap();
+        loss.backward_with(Tensor::scalar(1.0), BackwardOptions { retain_graph: true, ..BackwardOptions::default() }).unwrap();
+        loss.backward().unwrap();
+        assert_eq!(w.grad().unwrap().to_vec(), vec![4.0, 8.0]);
+        assert!(loss.backward().is_err());
+        assert!(!loss.is_leaf());
+
+        let other = w.tanh().sum(&[], false).unwrap();
+        other.free_graph();
+        assert_eq!(other.graph().nodes.len(), 1);
+        assert!(other.backward().is_err());
+    }
+
+    #[test]
+    fn higher_order_grad_test() {
+        use autograd::{grad, BackwardOptions, Var};
+        use tensor::Tensor;
+
+        let create = BackwardOptions { create_graph: true, ..BackwardOptions::default() };
+        let x = Var::param(Tensor::from_vec(&[3], vec![1.0, -2.0, 0.5]).unwrap());
+        let y = x.powi(3).sum(&[], false).unwrap();
+        let dx = grad(&y, std::slice::from_ref(&x), create).unwrap().remove(0);
+        assert!(dx.requires_grad());
+        assert_eq!(dx.value().to_vec(), vec![3.0, 12.0, 0.75]);
+        assert!(x.grad().is_none());
+
+        let v = Var::constant(Tensor::from_vec(&[3], vec![1.0, 1.0, 2.0]).unwrap());
+        (&dx * &v).unwrap().sum(&[], false).unwrap().backward().unwrap();
+        assert_eq!(x.grad().unwrap().to_vec(), vec![6.0, -12.0, 6.0]);
+
+        x.zero_grad();
+        let z = (&x * &x.exp()).unwrap().sum(&[], false).unwrap();
+        z.backward_with(Tensor::scalar(1.0), create).unwrap();
+        let first = x.grad_var().unwrap();
+        x.zero_grad();
+        first.sum(&[], false).unwrap().backward().unwrap();
+        let expected: Vec<f64> = [1.0f64, -2.0, 0.5].iter().map(|&v| (v + 2.0) * v.exp()).collect();
+        for (a, b) in x.grad().unwrap().iter().zip(expected.iter()) {
+            assert!((a - b).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn custom_function_test() {
+        use autograd::function::{apply, Function};
+        use autograd::{grad_check, Var};
+        use tensor::Tensor;
+
+        struct ScaledSoftplus {
+            scale: f64
+        }
+
+        impl Function<f64> for ScaledSoftplus {
+            fn name(&self) -> &'static str {
+                "scaled_softplus"
+            }
+
+            fn forward(&self, inputs: &[Tensor<f64>]) -> Result<Tensor<f64>, String> {
+                Ok(inputs[0].map(|&x| self.scale * (1.0 + x.exp()).ln()))
+            }
+
+            fn backward(&self, inputs: &[Var<f64>], grad_output: &Var<f64>) -> Result<Vec<Var<f64>>, String> {
+                let exp = inputs[0].exp();
+                let sigmoid = (&exp / &exp.add_scalar(1.0))?;
+                Ok(vec![(grad_output * &sigmoid.mul_scalar(self.scale))?])
+            }
+        }
+
+        let x: Tensor<f64> = Tensor::from_vec(&[2, 2], vec![-1.0, 0.0, 0.5, 2.0]).unwrap();
+        let f = |v: &[Var]| apply(ScaledSoftplus { scale: 2.0 }, v)?.powi(2).sum(&[], false);
+        let report = grad_check(f, std::slice::from_ref(&x), 1e-6, 1e-6).unwrap();
+        assert!(report.passed, "{}", report);
+
+        let y = apply(ScaledSoftplus { scale: 1.0 }, &[Var::param(x)]).unwrap();
+        assert_eq!(y.op(), "scaled_softplus");
+        assert_eq!(y.graph().edges.len(), 1);
+
+        struct Broken;
+
+        impl Function<f64> for Broken {
+            fn name(&self) -> &'static str {
+                "broken"
+            }
+
+            fn forward(&self, inputs: &[Tensor<f64>]) -> Result<Tensor<f64>, String> {
+                Ok(inputs[0].clone())
+            }
+
+            fn backward(&self, _: &[Var<f64>], _: &Var<f64>) -> Result<Vec<Var<f64>>, String> {
+                Ok(Vec::new())
+            }
+        }
+
+        let z = apply(Broken, &[Var::param(Tensor::scalar(1.0))]).unwrap();
+        assert!(z.backward().unwrap_err().contains("broken: backward returned 0 gradients for 1 inputs"));
+    }
+
+    #[test]
+    fn cat_stack_test() {
+        use autograd::{grad_check, Var};
+        use tensor::Tensor;
+
+        let a: Tensor<f64> = Tensor::from_vec(&[2, 2], vec![1.0, 2.0, 3.0, 4.0]).unwrap();
+        let b: Tensor<f64> = Tensor::from_vec(&[2, 1], vec![5.0, 6.0]).unwrap();
+        let joined = Tensor::cat(&[&a, &b], 1).unwrap();
+        assert_eq!(joined.shape(), &[2, 3]);
+        assert_eq!(joined.to_vec(), vec![1.0, 2.0, 5.0, 3.0, 4.0, 6.0]);
+        assert_eq!(Tensor::cat(&[&a, &a.transpose()], 0).unwrap().to_vec(), vec![1.0, 2.0, 3.0, 4.0, 1.0, 3.0, 2.0, 4.0]);
+        assert!(Tensor::cat(&[&a, &b], 0).is_err());
+
+        let stacked = Tensor::stack(&[&a, &a], 1).unwrap();
+        assert_eq!(stacked.shape(), &[2, 2, 2]);
+        assert_eq!(stacked.to_vec(), vec![1.0, 2.0, 1.0, 2.0, 3.0, 4.0, 3.0, 4.0]);
+        assert_eq!(a.narrow(1, 1, 1).unwrap().to_vec(), vec![2.0, 4.0]);
+
+        let f = |v: &[Var]| {
+            let joined = Var::cat(&[&v[0], &v[1].tanh()], 1)?;
+            let stacked = Var::stack(&[&joined, &joined.exp()], 0)?;
+            (&stacked * &stacked)?.sum(&[], false)
+        };
+        let report = grad_check(f, &[a, b], 1e-6, 1e-6).unwrap();
+        assert!(report.passed, "{}", report);
+    }
+
+    #[test]
+    fn gather_scatter_test() {
+        use autograd::{grad_check, Var};
+        use tensor::Tensor;
+
+        let t: Tensor<f64> = Tensor::from_vec(&[2, 3], vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]).unwrap();
+        let index = Tensor::from_vec(&[2, 2], vec![2, 0, 1, 1]).unwrap();
+        assert_eq!(t.gather(1, &index).unwrap().to_vec(), vec![3.0, 1.0, 5.0, 5.0]);
+        assert!(t.gather(1, &Tensor::from_vec(&[1, 1], vec![3]).unwrap()).is_err());
+        assert_eq!(t.index_select(1, &[2, 2, 0]).unwrap().to_vec(), vec![3.0, 3.0, 1.0, 6.0, 6.0, 4.0]);
+
+        let ones = Tensor::full(&[2, 2], 1.0);
+        let scattered = Tensor::zeros(&[2, 3]).scatter_add(1, &index, &ones).unwrap();
+        assert_eq!(scattered.to_vec(), vec![1.0, 0.0, 1.0, 0.0, 2.0, 0.0]);
+
+        let m: Matrix<i32> = Matrix::from_vec(2, 2, vec![1, 2, 3, 4]).unwrap();
+        let rows: Matrix<usize> = Matrix::from_vec(1, 2, vec![1, 0]).unwrap();
+        assert_eq!(m.gather(0, &rows).unwrap(), Matrix::from_vec(1, 2, vec![3, 2]).unwrap());
+        assert_eq!(m.index_select(0, &[1]).unwrap(), Matrix::from_vec(1, 2, vec![3, 4]).unwrap());
+        assert_eq!(m.scatter_add(0, &rows, &Matrix::from_vec(1, 2, vec![10, 20]).unwrap()).unwrap().data, vec![1, 22, 13, 4]);
+
+        let f = |v: &[Var]| {
+            let picked = v[0].gather(1, &index)?;
+            let rows = v[0].index_select(0, &[1, 1, 0])?;
+            let base = Var::constant(Tensor::zeros(&[2, 3])).scatter_add(1, &index, &picked.exp())?;
+            (&rows.sum(&[0], false)? * &base.sum(&[0], false)?)?.sum(&[], false)
+        };
+        let report = grad_check(f, &[t], 1e-6, 1e-6).unwrap();
+        assert!(report.passed, "{}", report);
+    }
+
+    #[test]
+    fn topk_test() {
+        use tensor::Tensor;
+
+        let t: Tensor<f64> = Tensor::from_vec(&[2, 4], vec![0.1, 0.9, 0.4, 0.9, 3.0, -1.0, 2.0, 0.0]).unwrap();
+        assert_eq!(t.argmax(1).unwrap().to_vec(), vec![1, 0]);
+        assert_eq!(t.argmin(1).unwrap().to_vec(), vec![0, 1]);
+        assert_eq!(t.argmax(0).unwrap().to_vec(), vec![1, 0, 1, 0]);
+
+        let (values, indices) = t.topk(2, 1).unwrap();
+        assert_eq!(values.shape(), &[2, 2]);
+        assert_eq!(values.to_vec(), vec![0.9, 0.9, 3.0, 2.0]);
+        assert_eq!(indices.to_vec(), vec![1, 3, 0, 2]);
+        let (values, indices) = t.bottomk(1, 0).unwrap();
+        assert_eq!((values.to_vec(), indices.to_vec()), (vec![0.1, -1.0, 0.4, 0.0], vec![0, 1, 0, 1]));
+        assert!(t.topk(5, 1).is_err());
+
+        let m: Matrix<i32> = Matrix::from_vec(2, 3, vec![5, 1, 7, 2, 8, 3]).unwrap();
+        assert_eq!(m.argmax(1).unwrap(), Vector::from(vec![2, 1]));
+        assert_eq!(m.argmin(0).unwrap(), Vector::from(vec![1, 0, 1]));
+        let (values, indices) = m.topk(2, 1).unwrap();
+        assert_eq!(values, Matrix::from_vec(2, 2, vec![7, 5, 8, 3]).unwrap());
+        assert_eq!(indices, Matrix::from_vec(2, 2, vec![2, 0, 1, 2]).unwrap());
+    }
+
+    #[test]
+    fn softmax_test() {
+        use autograd::{grad_check, Var};
+        use tensor::Tensor;
+
+        let logits: Tensor<f64> = Tensor::from_vec(&[2, 3], vec![1000.0, 1001.0, 1002.0, -5.0, 0.0, 5.0]).unwrap();
+        let probs = logits.softmax(1).unwrap();
+        assert!(probs.iter().all(|p| p.is_finite()));
+        let row_sums = probs.sum(&[1], false).unwrap();
+        assert!(row_sums.iter().all(|s| (s - 1.0).abs() < 1e-12));
+        assert!((probs.get(&[0, 2]).unwrap() - 1.0 / (1.0 + (-1f64).exp() + (-2f64).exp())).abs() < 1e-12);
+
+        let log_probs = logits.log_softmax(1).unwrap();
+        assert!((log_probs.get(&[1, 0]).unwrap() - (-10.0 - (1.0 + (-5f64).exp() + (-10f64).exp()).ln())).abs() < 1e-9);
+        assert!(Tensor::full(&[1, 2], f64::NEG_INFINITY).logsumexp(1, false).unwrap().to_vec()[0] == f64::NEG_INFINITY);
+
+        let m: Matrix<f32> = Matrix::from_vec(2, 2, vec![0.0, 0.0, 1.0, 3.0]).unwrap();
+        assert_eq!(m.softmax(0).unwrap().data[0], 1.0 / (1.0 + 1f32.exp()));
+
+        let x: Tensor<f64> = Tensor::from_vec(&[2, 3], vec![0.5, -1.0, 2.0, 1.5, 0.3, -0.7]).unwrap();
+        let weights = Var::constant(Tensor::from_vec(&[3], vec![1.0, -2.0, 0.5]).unwrap());
+        let f = |v: &[Var]| (&(&v[0].softmax(1)? + &v[0].log_softmax(0)?)? * &weights)?.sum(&[], false);
+        let report = grad_check(f, &[x], 1e-6, 1e-6).unwrap();
+        assert!(report.passed, "{}", report);
+    }
+
+    #[test]
+    fn init_test() {
+        use init::{FanMode, Nonlinearity};
+        use random::{with_rng, Rng};
+        use tensor::Tensor;
+
+        let mut a = Rng::seed_from_u64(7);
+        let mut b = Rng::seed_from_u64(7);
+        assert_eq!((0..5).map(|_| a.next_u64()).collect::<Vec<_>>(), (0..5).map(|_| b.next_u64()).collect::<Vec<_>>());
+        let mut items: Vec<usize> = (0..10).collect();
+        a.shuffle(&mut items);
+        items.sort();
+        assert_eq!(items, (0..10).collect::<Vec<_>>());
+        assert!(with_rng(|rng| rng.gen_range(3)) < 3);
+
+        assert_eq!(init::fans(&[16, 8, 3, 3]).unwrap(), (72, 144));
+        assert!(init::fans(&[4]).is_err());
+
+        let w: Tensor<f64> = init::xavier_uniform(&[200, 300], 1.0).unwrap();
+        let bound = (6.0f64 / 500.0).sqrt();
+        assert!(w.iter().all(|x| x.abs() <= bound));
+        let var = w.var(&[], false).unwrap().to_vec()[0];
+        assert!((var - bound * bound / 3.0).abs() < 1e-4);
+
+        let k: Tensor<f64> = init::kaiming_normal(&[256, 512], FanMode::FanIn, Nonlinearity::Relu).unwrap();
+        assert!((k.var(&[], false).unwrap().to_vec()[0] - 2.0 / 512.0).abs() < 2e-4);
+        let l: Matrix<f32> = init::lecun_uniform(&[3, 4]).unwrap().try_into().unwrap();
+        assert_eq!((l.rows, l.cols), (3, 4));
+
+        let t: Tensor<f64> = init::trunc_normal(&[1000], 0.0, 1.0, -0.5, 0.5).unwrap();
+        assert!(t.iter().all(|x| (-0.5..=0.5).contains(x)));
+        assert!(init::trunc_normal::<f64>(&[1], 0.0, 1.0, 50.0, 51.0).is_err());
+
+        for shape in [[3, 5], [5, 3]] {
+            let q: Tensor<f64> = init::orthogonal(&shape, 1.0).unwrap();
+            let gram = if shape[0] <= shape[1] { q.matmul(&q.transpose()) } else { q.transpose().matmul(&q) }.unwrap();
+            for (idx, value) in tensor::IndexIter::new(gram.shape()).zip(gram.iter()) {
+                let expected = if idx[0] == idx[1] { 1.0 } else { 0.0 };
+                assert!((value - expected).abs() < 1e-10);
+            }
+        }
+    }
+
+    #[test]
+    fn pad_test() {
+        use autograd::{grad_check, Var};
+        use tensor::pad::PadMode;
+        use tensor::Tensor;
+
+        let t: Tensor<f64> = Tensor::from_vec(&[3], vec![1.0, 2.0, 3.0]).unwrap();
+        assert_eq!(t.pad(&[(2, 1)], PadMode::Constant(0.0)).unwrap().to_vec(), vec![0.0, 0.0, 1.0, 2.0, 3.0, 0.0]);
+        assert_eq!(t.pad(&[(2, 2)], PadMode::Reflect).unwrap().to_vec(), vec![3.0, 2.0, 1.0, 2.0, 3.0, 2.0, 1.0]);
+        assert_eq!(t.pad(&[(2, 1)], PadMode::Replicate).unwrap().to_vec(), vec![1.0, 1.0, 1.0, 2.0, 3.0, 3.0]);
+        assert!(t.pad(&[(1, 1), (0, 0)], PadMode::Reflect).is_err());
+
+        let m: Matrix<i32> = Matrix::from_vec(2, 2, vec![1, 2, 3, 4]).unwrap();
+        let padded = m.pad((1, 0), (0, 1), PadMode::Replicate).unwrap();
+        assert_eq!(padded, Matrix::from_vec(3, 3, vec![1, 2, 2, 1, 2, 2, 3, 4, 4]).unwrap());
+
+        let x: Tensor<f64> = Tensor::from_vec(&[2, 3], vec![0.5, -1.0, 2.0, 1.5, 0.3, -0.7]).unwrap();
+        for mode in [PadMode::Constant(1.5), PadMode::Reflect, PadMode::Replicate] {
+            let f = |v: &[Var]| {
+                let padded = v[0].pad(&[(1, 1), (2, 1)], mode)?;
+                let weights = Var::constant(Tensor::from_fn(&padded.shape(), |i| (i[0] * 5 + i[1]) as f64));
+                (&padded.powi(2) * &weights)?.sum(&[], false)
+            };
+            let report = grad_check(f, std::slice::from_ref(&x), 1e-6, 1e-6).unwrap();
+            assert!(report.passed, "{:?}: {}", mode, report);
+        }
+    }
+
+    #[test]
+    fn conv2d_test() {
+        use autograd::{grad_check, Var};
+        use tensor::conv::Conv2dOptions;
+        use tensor::Tensor;
+
+        let image: Tensor<f64> = Tensor::from_vec(&[1, 1, 3, 3], (1..=9).map(|x| x as f64).collect()).unwrap();
+        let options = Conv2dOptions::new((2, 2));
+        let cols = image.im2col(&options).unwrap();
+        assert_eq!(cols.shape(), &[1, 4, 4]);
+        assert_eq!(cols.to_vec()[..4], [1.0, 2.0, 4.0, 5.0]);
+        let counts = Tensor::full(&[1, 4, 4], 1.0).col2im(1, (3, 3), &options).unwrap();
+        assert_eq!(counts.to_vec(), vec![1.0, 2.0, 1.0, 2.0, 4.0, 2.0, 1.0, 2.0, 1.0]);
+
+        let weight = Tensor::from_vec(&[1, 1, 2, 2], vec![1.0, 0.0, 0.0, -1.0]).unwrap();
+        let out = image.conv2d(&weight, Some(&Tensor::from_vec(&[1], vec![0.5]).unwrap()), &options).unwrap();
+        assert_eq!(out.shape(), &[1, 1, 2, 2]);
+        assert_eq!(out.to_vec(), vec![-3.5, -3.5, -3.5, -3.5]);
+
+        let padded = Conv2dOptions { stride: (2, 2), padding: (1, 1), ..Conv2dOptions::new((3, 3)) };
+        assert_eq!(padded.output_size(5, 4).unwrap(), (3, 2));
+        assert!(Conv2dOptions::new((4, 4)).output_size(3, 3).is_err());
+
+        let x: Tensor<f64> = Tensor::from_fn(&[2, 2, 4, 3], |i| ((i[0] * 7 + i[1] * 5 + i[2] * 3 + i[3]) % 5) as f64 * 0.3 - 0.6);
+        let w: Tensor<f64> = Tensor::from_fn(&[3, 2, 3, 2], |i| ((i[0] + 2 * i[1] + i[2] * i[3]) % 4) as f64 * 0.25 - 0.4);
+        let b: Tensor<f64> = Tensor::from_vec(&[3], vec![0.1, -0.2, 0.3]).unwrap();
+        let opts = Conv2dOptions { stride: (2, 1), padding: (1, 1), dilation: (1, 2), ..Conv2dOptions::new((3, 2)) };
+        let f = |v: &[Var]| v[0].conv2d(&v[1], Some(&v[2]), &opts)?.tanh().sum(&[], false);
+        let report = grad_check(f, &[x, w, b], 1e-6, 1e-6).unwrap();
+        assert!(report.passed, "{}", report);
+    }
+
+    #[test]
+    fn pooling_test() {
+        use autograd::{grad_check, Var};
+        use tensor::conv::Conv2dOptions;
+        use tensor::Tensor;
+
+        let image: Tensor<f64> = Tensor::from_vec(&[1, 1, 4, 4], vec![
+            1.0, 3.0, 2.0, 0.0,
+            4.0, 2.0, 1.0, 5.0,
+            0.0, 1.0, 7.0, 6.0,
+            2.0, 2.0, 8.0, 1.0,
+        ]).unwrap();
+        let options = Conv2dOptions::pooling((2, 2));
+        let (values, indices) = image.max_pool2d(&options).unwrap();
+        assert_eq!(values.to_vec(), vec![4.0, 5.0, 2.0, 8.0]);
+        assert_eq!(indices.to_vec(), vec![4, 7, 12, 14]);
+        assert_eq!(image.avg_pool2d(&options).unwrap().to_vec(), vec![2.5, 2.0, 1.25, 5.5]);
+
+        let padded = Conv2dOptions { padding: (1, 1), ..Conv2dOptions::pooling((3, 3)) };
+        let (values, _) = image.max_pool2d(&Conv2dOptions { stride: (1, 1), ..padded }).unwrap();
+        assert_eq!(values.shape(), &[1, 1, 4, 4]);
+        assert_eq!(values.get(&[0, 0, 0, 0]), Some(&4.0));
+        assert!(image.reshape(&[4, 4]).unwrap().max_pool2d(&options).is_err());
+        let empty: Tensor<f64> = Tensor::zeros(&[1, 1, 0, 4]);
+        assert!(empty.max_pool2d(&Conv2dOptions { padding: (1, 1), ..options }).unwrap_err().contains("zero spatial size"));
+
+        let x: Tensor<f64> = Tensor::from_fn(&[2, 2, 5, 4], |i| ((i[0] * 13 + i[1] * 7 + i[2] * 5 + i[3] * 3) % 11) as f64 * 0.1);
+        let f = |v: &[Var]| {
+            let (max, _) = v[0].max_pool2d(&padded)?;
+            let avg = v[0].avg_pool2d(&Conv2dOptions { stride: (1, 2), ..Conv2dOptions::new((2, 2)) })?;
+            &max.powi(2).sum(&[], false)? + &avg.exp().sum(&[], false)?
+        };
+        let report = grad_check(f, &[x], 1e-6, 1e-6).unwrap();
+        assert!(report.passed, "{}", report);
+    }
+
+    #[test]
+    fn checkpoint_test() {
+        use io::checkpoint::{read_checkpoint, write_checkpoint};
+        use tensor::Tensor;
+
+        let w = Tensor::from_vec(&[2, 3], vec![1.0f32, -2.0, 3.5, 0.0, 4.25, -1.0]).unwrap();
+        let b = Tensor::from_vec(&[3], vec![0.5f32, 0.25, -0.75]).unwrap();
+        let mut bytes = Vec::new();
+        write_checkpoint(&mut bytes, &[("layer.weight", &w), ("layer.bias", &b)]).unwrap();
+
+        let loaded = read_checkpoint::<f32, _>(bytes.as_slice()).unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[0].0, "layer.weight");
+        assert_eq!(loaded[0].1, w);
+        assert_eq!(loaded[1].0, "layer.bias");
+        assert_eq!(loaded[1].1, b);
+
+        assert!(read_checkpoint::<f64, _>(bytes.as_slice()).unwrap_err().contains("dtype"));
+
+        let mut corrupted = bytes.clone();
+        let last_data = corrupted.len() - 12;
+        corrupted[last_data] ^= 0x40;
+        assert!(read_checkpoint::<f32, _>(corrupted.as_slice()).unwrap_err().contains("layer.bias"));
+
+        let mut corrupted = bytes.clone();
+        corrupted[8] = 2;
+        assert!(read_checkpoint::<f32, _>(corrupted.as_slice()).unwrap_err().contains("version"));
+
+        assert!(read_checkpoint::<f32, _>(&bytes[..bytes.len() - 2]).is_err());
+
+        let mut huge = Vec::new();
+        write_checkpoint(&mut huge, &[("x", &Tensor::from_vec(&[1, 1], vec![1.0f32]).unwrap())]).unwrap();
+        let (end, dims) = (huge.len() - 8, huge.len() - 36);
+        huge[dims..dims + 16].copy_from_slice(&[(1u64 << 32).to_le_bytes(), (1u64 << 32).to_le_bytes()].concat());
+        let checksum = io::checkpoint::crc32(&huge[16..end]);
+        huge[end..end + 4].copy_from_slice(&checksum.to_le_bytes());
+        huge.truncate(end + 4);
+        let checksum = io::checkpoint::crc32(&huge);
+        huge.extend_from_slice(&checksum.to_le_bytes());
+        assert!(read_checkpoint::<f32, _>(huge.as_slice()).unwrap_err().contains("too large"));
+    }
+
+    #[test]
+    fn mixed_precision_test() {
+        use autograd::{LossScaler, Var};
+        use math::half::{bf16, f16};
+        use tensor::Tensor;
+
+        assert_eq!(f16::from_f32(1.0).to_bits(), 0x3C00);
+        assert_eq!(f16::from_f32(-2.5).to_f32(), -2.5);
+        assert_eq!(f16::from_f32(65504.0).to_bits(), f16::MAX.to_bits());
+        assert_eq!(f16::from_f32(1e6).to_bits(), f16::INFINITY.to_bits());
+        assert_eq!(f16::from_f32(5.960_464_5e-8).to_bits(), 0x0001);
+        assert_eq!(f16::from_bits(0x0001).to_f32(), 5.960_464_5e-8);
+        assert_eq!(f16::from_f32(1.0 + 1.0 / 4096.0).to_bits(), 0x3C00);
+        assert!(f16::from_f32(f32::NAN).is_nan());
+        assert_eq!(bf16::from_f32(1.0).to_bits(), 0x3F80);
+        assert_eq!(bf16::from_f32(3.0e38).to_bits(), 0x7F62);
+        assert!(bf16::from_f32(f32::NAN).is_nan());
+
+        let a = Tensor::from_vec(&[2, 3], vec![1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0]).unwrap().to_f16();
+        let b = Tensor::from_vec(&[3, 2], vec![0.5f32, -1.0, 1.5, 2.0, -0.5, 0.25]).unwrap().to_bf16();
+        assert_eq!(a.to_f32().to_vec(), vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        assert_eq!(a.matmul(&a.transpose()).unwrap().to_f32().to_vec(), vec![14.0, 32.0, 32.0, 77.0]);
+        assert_eq!(b.sum(&[0], false).unwrap().to_f32().to_vec(), vec![1.5, 1.25]);
+        assert_eq!(a.mean(&[1], false).unwrap().to_f32().to_vec(), vec![2.0, 5.0]);
+
+        // 4096 + 1 + 1 + ... is lost in f16 arithmetic but survives an f32 accumulator.
+        let mut values = vec![4096.0f32];
+        values.extend(std::iter::repeat_n(1.0, 64));
+        let many = Tensor::from_vec(&[65], values).unwrap().to_f16();
+        assert_eq!(many.sum_all(), 4160.0);
+        assert_eq!(many.sum(&[0], false).unwrap().to_f32().to_vec(), vec![4160.0]);
+
+        let mut scaler = LossScaler::new(1024.0);
+        scaler.growth_interval = 2;
+        let w = Var::param(Tensor::from_vec(&[2], vec![1.0, 2.0]).unwrap());
+        let loss = w.powi(2).sum(&[0], false).unwrap();
+        scaler.scale_loss(&loss).backward().unwrap();
+        assert_eq!(w.grad().unwrap().to_vec(), vec![2048.0, 4096.0]);
+        let grads = scaler.unscale_grads(std::slice::from_ref(&w)).unwrap();
+        assert_eq!(grads[0].to_vec(), vec![2.0, 4.0]);
+
+        scaler.update(false);
+        scaler.update(false);
+        assert_eq!(scaler.scale(), 2048.0);
+        scaler.update(true);
+        assert_eq!(scaler.scale(), 1024.0);
+
+        let overflow = Var::param(Tensor::from_vec(&[1], vec![f64::MAX]).unwrap());
+        overflow.mul_scalar(4.0).sum(&[0], false).unwrap().mul_scalar(f64::MAX).backward().unwrap();
+        assert!(scaler.unscale_grads(&[overflow]).is_none());
+    }
+
+    #[test]
+    fn lazy_fusion_test() {
+        use tensor::Tensor;
+
+        let a = Tensor::from_vec(&[2, 3], vec![1.0, -2.0, 0.5, 3.0, 1.0, -1.0]).unwrap();
+        let b = Tensor::from_vec(&[3, 2], vec![1.0, 0.0, 0.5, -1.0, 2.0, 1.0]).unwrap();
+        let bias = Tensor::from_vec(&[2], vec![-0.5, 1.0]).unwrap();
+
+        let lazy = (&a.lazy().matmul(&b.lazy()).unwrap() + &bias.lazy()).unwrap().relu();
+        assert_eq!(lazy.shape(), &[2, 2]);
+        assert_eq!(lazy.explain(), vec!["matmul [2, 3] x [3, 2] -> [2, 2]", "fused [add, relu] over 2 inputs -> [2, 2]"]);
+        let eager = (&a.matmul(&b).unwrap() + &bias).unwrap().map(|&x: &f64| x.max(0.0));
+        assert_eq!(lazy.eval().unwrap(), eager);
+
+        let x = a.lazy();
+        let chain = (&x.exp().mul_scalar(2.0) - &x.powi(2)).unwrap().tanh().sum(&[1], false).unwrap();
+        assert_eq!(chain.explain().len(), 2);
+        let expected = a.map(|&v: &f64| (2.0 * v.exp() - v * v).tanh()).sum(&[1], false).unwrap();
+        let result = chain.eval().unwrap();
+        for (r, e) in result.iter().zip(expected.iter()) {
+            assert!((r - e).abs() < 1e-12);
+        }
+
+        let shared = x.sigmoid();
+        let twice = (&shared * &shared).unwrap().add_scalar(1.0);
+        assert_eq!(twice.explain(), vec!["fused [sigmoid] over 1 inputs -> [2, 3]", "fused [mul, add_scalar] over 1 inputs -> [2, 3]"]);
+        let expected = a.map(|&v: &f64| 1.0 / (1.0 + (-v).exp())).map(|&s| s * s + 1.0);
+        let result = twice.eval().unwrap();
+        for (r, e) in result.iter().zip(expected.iter()) {
+            assert!((r - e).abs() < 1e-12);
+        }
+
+        assert!(a.lazy().matmul(&a.lazy()).is_err());
+        assert!((&a.lazy() + &b.lazy()).is_err());
+    }
+
+    #[test]
+    fn device_test() {
+        use tensor::device::Device;
+        use tensor::Tensor;
+
+        assert_eq!("cpu".parse::<Device>().unwrap(), Device::Cpu);
+        assert_eq!("GPU:1".parse::<Device>().unwrap(), Device::Gpu(1));
+        assert_eq!("cuda".parse::<Device>().unwrap(), Device::Gpu(0));
+        assert!("tpu".parse::<Device>().is_err());
+        assert!("gpu:x".parse::<Device>().is_err());
+        assert_eq!(Device::Gpu(2).to_string(), "gpu:2");
+        assert!(Device::Cpu.is_available());
+        assert!(!Device::Gpu(0).is_available());
+
+        let t = Tensor::from_vec(&[2, 2], vec![1.0, 2.0, 3.0, 4.0]).unwrap();
+        assert_eq!(t.device(), Device::Cpu);
+        assert_eq!(t.transpose().device(), Device::Cpu);
+        assert_eq!(t.to(Device::Cpu).unwrap(), t);
+        assert!(t.to(Device::Gpu(0)).unwrap_err().contains("gpu:0"));
+        assert_eq!(t.matmul(&t).unwrap().device(), Device::Cpu);
+    }
+
+    #[test]
+    fn quantization_test() {
+        use tensor::quant::QuantParams;
+        use tensor::Tensor;
+
+        let a = Tensor::from_vec(&[2, 3], vec![0.5, -1.0, 0.25, 1.27, 0.0, -0.64]).unwrap();
+        let q = a.quantize(0.01, 0).unwrap();
+        assert_eq!(q.values().to_vec(), vec![50, -100, 25, 127, 0, -64]);
+        assert_eq!(q.dequantize::<f64>().map(|x| (x * 100.0).round()), a.map(|x: &f64| (x * 100.0).round()));
+        assert!(a.quantize(0.0, 0).is_err());
+        assert!(a.quantize(0.1, 200).is_err());
+        assert_eq!(a.quantize(0.001, 0).unwrap().values().to_vec()[3], 127);
+
+        let sym = a.quantize_symmetric();
+        assert_eq!(sym.params(), QuantParams::symmetric(1.27));
+        assert_eq!(sym.params().zero_point, 0);
+        assert_eq!(sym.values().to_vec()[3], 127);
+
+        let positive = Tensor::from_vec(&[4], vec![0.0, 1.0, 2.0, 2.55]).unwrap();
+        let asym = positive.quantize_asymmetric();
+        assert_eq!(asym.params().zero_point, -128);
+        assert_eq!(asym.values().to_vec(), vec![-128, -28, 72, 127]);
+        for (x, y) in asym.dequantize::<f64>().iter().zip(positive.iter()) {
+            assert!((x - y).abs() <= asym.params().scale / 2.0 + 1e-12);
+        }
+
+        let b = Tensor::from_vec(&[3, 2], vec![1.0, -0.5, 0.25, 0.75, -1.0, 0.5]).unwrap();
+        let (qa, qb) = (a.quantize_symmetric(), b.quantize_asymmetric());
+        let raw = qa.matmul_i32(&qb).unwrap();
+        assert_eq!(raw.shape(), &[2, 2]);
+        let exact = a.matmul(&b).unwrap();
+        let approx = qa.matmul::<f64>(&qb).unwrap();
+        for (x, y) in approx.iter().zip(exact.iter()) {
+            assert!((x - y).abs() < 0.05);
+        }
+        let reference = qa.dequantize::<f64>().matmul(&qb.dequantize()).unwrap();
+        for (x, y) in approx.iter().zip(reference.iter()) {
+            assert!((x - y).abs() < 1e-9);
+        }
+
+        let ints = Tensor::from_vec(&[1, 2], vec![100i8, -100]).unwrap();
+        let out = ints.matmul_i32(&ints.transpose(), 0, 0).unwrap();
+        assert_eq!(out.to_vec(), vec![20000]);
+    }
+
+    #[test]
+    fn linear_test() {
+        use autograd::Var;
+        use nn::{Linear, Module};
+        use tensor::Tensor;
+
+        let weight = Tensor::from_vec(&[2, 3], vec![1.0, 0.0, -1.0, 0.5, 2.0, 1.0]).unwrap();
+        let bias = Tensor::from_vec(&[2], vec![0.5, -1.0]).unwrap();
+        let layer = Linear::from_weights(weight, Some(bias)).unwrap();
+        assert_eq!((layer.in_features(), layer.out_features()), (3, 2));
+        assert_eq!(layer.parameters().len(), 2);
+        assert_eq!(layer.named_parameters()[1].0, "bias");
+
+        let x = Var::constant(Tensor::from_vec(&[2, 3], vec![1.0, 2.0, 3.0, -1.0, 0.0, 1.0]).unwrap());
+        let y = layer.forward(&x).unwrap();
+        assert_eq!(y.value().to_vec(), vec![-1.5, 6.5, -1.5, -0.5]);
+
+        y.sum(&[], false).unwrap().backward().unwrap();
+        assert_eq!(layer.weight.grad().unwrap().to_vec(), vec![0.0, 2.0, 4.0, 0.0, 2.0, 4.0]);
+        assert_eq!(layer.bias.as_ref().unwrap().grad().unwrap().to_vec(), vec![2.0, 2.0]);
+
+        let single = layer.predict(&Tensor::from_vec(&[3], vec![1.0, 2.0, 3.0]).unwrap()).unwrap();
+        assert_eq!(single.shape(), &[2]);
+        assert_eq!(single.to_vec(), vec![-1.5, 6.5]);
+        assert!(layer.predict(&Tensor::zeros(&[2, 4])).is_err());
+        assert!(Linear::from_weights(Tensor::<f64>::zeros(&[2, 3]), Some(Tensor::zeros(&[3]))).is_err());
+
+        let random = Linear::<f64>::new(4, 8, false);
+        assert!(random.bias.is_none());
+        assert_eq!(random.weight.shape(), vec![8, 4]);
+        assert!(random.weight.value().iter().all(|w| w.abs() <= 0.5));
+    }
+
+    #[test]
+    fn activations_test() {
+        use autograd::{grad_check, Var};
+        use nn::activations::{gelu, leaky_relu, relu, sigmoid, silu, softmax};
+        use nn::{LeakyReLU, Module, ReLU, Sigmoid, Softmax, GELU};
+        use tensor::Tensor;
+
+        let x = Tensor::from_vec(&[2, 3], vec![-2.0, -0.5, 0.3, 0.7, 1.5, -1.2]).unwrap();
+        let v = Var::constant(x.clone());
+        assert_eq!(relu(&v).value().to_vec(), vec![0.0, 0.0, 0.3, 0.7, 1.5, 0.0]);
+        assert_eq!(leaky_relu(&v, 0.1).value().to_vec()[0], -0.2);
+        assert_eq!(ReLU.forward(&v).unwrap().value(), relu(&v).value());
+        assert_eq!(LeakyReLU::default().forward(&v).unwrap().value().to_vec()[1], -0.005);
+
+        let extreme = Var::constant(Tensor::from_vec(&[2], vec![-1000.0, 1000.0]).unwrap());
+        assert_eq!(sigmoid(&extreme).value().to_vec(), vec![0.0, 1.0]);
+        let s: Tensor = Sigmoid.forward(&v).unwrap().value();
+        assert!((s.get(&[0, 0]).unwrap() - 1.0 / (1.0 + 2f64.exp())).abs() < 1e-12);
+        let g: Tensor = GELU.forward(&v).unwrap().value();
+        assert!((g.get(&[1, 1]).unwrap() - 1.399_572_2).abs() < 1e-6);
+        let rows = Softmax { axis: 1 }.forward(&v).unwrap().value().sum(&[1], false).unwrap();
+        assert!(rows.iter().all(|r: &f64| (r - 1.0).abs() < 1e-12));
+
+        type Activation = fn(&[Var]) -> Result<Var, String>;
+        let fs: [Activation; 6] = [
+            |v| Ok(relu(&v[0])),
+            |v| Ok(leaky_relu(&v[0], 0.2)),
+            |v| Ok(sigmoid(&v[0])),
+            |v| gelu(&v[0]),
+            |v| silu(&v[0]),
+            |v| softmax(&v[0], 1)?.powi(2).sum(&[], false)
+        ];
+        for f in fs {
+            let report = grad_check(f, std::slice::from_ref(&x), 1e-6, 1e-6).unwrap();
+            assert!(report.passed, "{}", report);
+        }
+
+        let p = Var::param(x.clone());
+        relu(&p).sum(&[], false).unwrap().backward().unwrap();
+        assert_eq!(p.grad().unwrap().to_vec(), vec![0.0, 0.0, 1.0, 1.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn loss_test() {
+        use autograd::{grad_check, Var};
+        use nn::activations::sigmoid;
+        use nn::loss::*;
+        use tensor::Tensor;
+
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+        let scalar = |v: Var| v.value().to_vec()[0];
+        let t = |data: Vec<f64>| Tensor::from_vec(&[data.len()], data).unwrap();
+
+        let pred = Var::param(t(vec![1.0, 2.0, 3.0]));
+        let ones = t(vec![1.0, 1.0, 1.0]);
+        assert!(close(scalar(mse_loss(&pred, &ones, None).unwrap()), 5.0 / 3.0));
+        assert!(close(scalar(mse_loss(&pred, &ones, Some(&t(vec![1.0, 0.0, 1.0]))).unwrap()), 2.0));
+        assert!(close(scalar(mae_loss(&pred, &ones, None).unwrap()), 1.0));
+        assert!(close(scalar(huber_loss(&pred, &ones, 1.0, None).unwrap()), 2.0 / 3.0));
+        assert!(mse_loss(&pred, &t(vec![1.0, 1.0]), None).is_err());
+        assert!(mse_loss(&pred, &ones, Some(&t(vec![1.0]))).is_err());
+
+        let probs = Var::param(t(vec![0.9, 0.2]));
+        let labels = t(vec![1.0, 0.0]);
+        let expected = -(0.9f64.ln() + 0.8f64.ln()) / 2.0;
+        assert!(close(scalar(binary_cross_entropy(&probs, &labels, None).unwrap()), expected));
+
+        let logits = Var::param(t(vec![0.0, 2.0]));
+        let loss = binary_cross_entropy_with_logits(&logits, &labels, None).unwrap();
+        assert!(close(scalar(loss.clone()), (2f64.ln() + 2.0 + (-2f64).exp().ln_1p()) / 2.0));
+        loss.backward().unwrap();
+        let grad = logits.grad().unwrap().to_vec();
+        assert!(close(grad[0], (0.5 - 1.0) / 2.0));
+        assert!(close(grad[1], 1.0 / (1.0 + (-2f64).exp()) / 2.0));
+        let huge = Var::constant(t(vec![1000.0, -1000.0]));
+        assert!(close(scalar(binary_cross_entropy_with_logits(&huge, &labels, None).unwrap()), 0.0));
+
+        let scores = Var::constant(Tensor::from_vec(&[2, 3], vec![1.0, 2.0, 3.0, 1.0, 1.0, 1.0]).unwrap());
+        let lse = (1f64.exp() + 2f64.exp() + 3f64.exp()).ln();
+        assert!(close(scalar(cross_entropy(&scores, &[2, 0], None).unwrap()), (lse - 3.0 + 3f64.ln()) / 2.0));
+        assert!(cross_entropy(&scores, &[3, 0], None).is_err());
+        assert!(cross_entropy(&scores, &[0], None).is_err());
+
+        let margins = Var::constant(t(vec![0.5, -2.0, 2.0]));
+        assert!(close(scalar(hinge_loss(&margins, &t(vec![1.0, 1.0, -1.0]), None).unwrap()), 6.5 / 3.0));
+        assert!(hinge_loss(&margins, &t(vec![1.0, 0.0, -1.0]), None).is_err());
+
+        let x = Tensor::from_vec(&[3, 2], vec![0.3, -1.2, 0.8, 0.1, -0.4, 1.7]).unwrap();
+        let y = Tensor::from_vec(&[3, 2], vec![0.0, 1.0, 1.0, 0.0, 1.0, 1.0]).unwrap();
+        let w = t(vec![0.5, 1.0, 2.0]);
+        type Loss<'a> = &'a dyn Fn(&[Var]) -> Result<Var, String>;
+        let checks: [Loss; 6] = [
+            &|v| mse_loss(&v[0], &y, Some(&w)),
+            &|v| huber_loss(&v[0], &y, 0.5, Some(&w)),
+            &|v| binary_cross_entropy(&sigmoid(&v[0]), &y, Some(&w)),
+            &|v| binary_cross_entropy_with_logits(&v[0], &y, Some(&w)),
+            &|v| cross_entropy(&v[0], &[1, 0, 1], Some(&w)),
+            &|v| hinge_loss(&v[0], &y.map(|&l| 2.0 * l - 1.0), Some(&w))
+        ];
+        for f in checks {
+            let report = grad_check(f, std::slice::from_ref(&x), 1e-6, 1e-5).unwrap();
+            assert!(report.passed, "{}", report);
+        }
+    }
+
+    #[test]
+    fn sgd_test() {
+        use autograd::Var;
+        use optim::{Optimizer, SGD};
+        use tensor::Tensor;
+
+        let run = |momentum: f64, nesterov: bool, weight_decay: f64, steps: usize| {
+            let w = Var::param(Tensor::from_vec(&[1], vec![1.0]).unwrap());
+            let mut sgd = SGD::new(vec![w.clone()], 0.1);
+            sgd.momentum = momentum;
+            sgd.nesterov = nesterov;
+            sgd.weight_decay = weight_decay;
+            for _ in 0..steps {
+                sgd.zero_grad();
+                w.add_scalar(-3.0).powi(2).sum(&[], false).unwrap().backward().unwrap();
+                sgd.step().unwrap();
+            }
+            w.value().to_vec()[0]
+        };
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-12;
+
+        assert!(close(run(0.0, false, 0.0, 1), 1.4));
+        assert!(close(run(0.0, false, 0.1, 1), 1.39));
+        assert!(close(run(0.9, false, 0.0, 2), 2.08));
+        assert!(close(run(0.9, true, 0.0, 1), 1.76));
+        assert!((run(0.9, true, 0.0, 200) - 3.0).abs() < 1e-6);
+
+        let idle = Var::param(Tensor::from_vec(&[2], vec![1.0, 2.0]).unwrap());
+        let mut sgd = SGD::new(vec![idle.clone()], 0.5);
+        sgd.step().unwrap();
+        assert_eq!(idle.value().to_vec(), vec![1.0, 2.0]);
+        sgd.set_learning_rate(0.01);
+        assert_eq!(sgd.learning_rate(), 0.01);
+        sgd.nesterov = true;
+        assert!(sgd.step().is_err());
+    }
+
+    #[test]
+    fn adam_test() {
+        use autograd::Var;
+        use io::checkpoint::{read_checkpoint, write_checkpoint};
+        use optim::{Adam, AdamW, Optimizer, SGD};
+        use tensor::Tensor;
+
+        fn train(opt: &mut dyn Optimizer, w: &Var, steps: usize) {
+            for _ in 0..steps {
+                opt.zero_grad();
+                let loss = (&w.add_scalar(-3.0).powi(2) + &w.powi(4).mul_scalar(0.01)).unwrap();
+                loss.sum(&[], false).unwrap().backward().unwrap();
+                opt.step().unwrap();
+            }
+        }
+        let start = || Var::param(Tensor::from_vec(&[2], vec![1.0, -2.0]).unwrap());
+
+        let w = start();
+        let mut adam = Adam::new(vec![w.clone()], 0.1);
+        train(&mut adam, &w, 1);
+        assert!(w.value().iter().zip([1.1, -1.9]).all(|(a, b)| (a - b).abs() < 1e-6));
+        assert_eq!(adam.steps(), 1);
+
+        let w = start();
+        let mut adamw = AdamW::new(vec![w.clone()], 0.1);
+        adamw.weight_decay = 0.1;
+        train(&mut adamw, &w, 1);
+        assert!(w.value().iter().zip([1.09, -1.88]).all(|(a, b)| (a - b).abs() < 1e-6));
+
+        let w = start();
+        let mut adam = Adam::new(vec![w.clone()], 0.05);
+        train(&mut adam, &w, 3);
+        let mut saved = Vec::new();
+        let state = adam.state_dict();
+        let named: Vec<(&str, &Tensor)> = state.iter().map(|(n, t)| (n.as_str(), t)).collect();
+        write_checkpoint(&mut saved, &named).unwrap();
+        let snapshot = w.value();
+        train(&mut adam, &w, 3);
+
+        let resumed = Var::param(snapshot);
+        let mut restored = Adam::new(vec![resumed.clone()], 1.0);
+        restored.load_state_dict(&read_checkpoint(saved.as_slice()).unwrap()).unwrap();
+        assert_eq!(restored.steps(), 3);
+        assert_eq!(restored.learning_rate(), 0.05);
+        train(&mut restored, &resumed, 3);
+        assert_eq!(resumed.value(), w.value());
+
+        let other = Var::param(Tensor::zeros(&[3]));
+        assert!(Adam::new(vec![other], 0.1).load_state_dict(&state).is_err());
+
+        let w = start();
+        let mut sgd = SGD::new(vec![w.clone()], 0.1);
+        sgd.momentum = 0.9;
+        train(&mut sgd, &w, 2);
+        let mut copy = SGD::new(vec![Var::param(w.value())], 0.0);
+        copy.load_state_dict(&sgd.state_dict()).unwrap();
+        assert_eq!((copy.momentum, copy.learning_rate()), (0.9, 0.1));
+        assert_eq!(copy.state_dict().len(), 5);
+    }
+
+    #[test]
+    fn adaptive_optimizers_test() {
+        use autograd::Var;
+        use optim::{Adagrad, Optimizer, RMSProp};
+        use tensor::Tensor;
+
+        fn train(opt: &mut dyn Optimizer, w: &Var, steps: usize) -> f64 {
+            for _ in 0..steps {
+                opt.zero_grad();
+                w.add_scalar(-3.0).powi(2).sum(&[], false).unwrap().backward().unwrap();
+                opt.step().unwrap();
+            }
+            w.value().to_vec()[0]
+        }
+        let start = || Var::param(Tensor::from_vec(&[1], vec![1.0]).unwrap());
+
+        let w = start();
+        let mut rmsprop = RMSProp::new(vec![w.clone()], 0.01);
+        assert!((train(&mut rmsprop, &w, 1) - 1.1).abs() < 1e-6);
+
+        let w = start();
+        let mut adagrad = Adagrad::new(vec![w.clone()], 0.1);
+        assert!((train(&mut adagrad, &w, 1) - 1.1).abs() < 1e-9);
+        let expected = 1.1 + 0.1 * 3.8 / (16.0f64 + 3.8 * 3.8).sqrt();
+        assert!((train(&mut adagrad, &w, 1) - expected).abs() < 1e-9);
+
+        let w = start();
+        let mut adagrad = Adagrad::new(vec![w.clone()], 0.5);
+        assert!((train(&mut adagrad, &w, 500) - 3.0).abs() < 1e-3);
+
+        for (momentum, centered) in [(0.0, false), (0.5, false), (0.0, true), (0.5, true)] {
+            let w = start();
+            let mut rmsprop = RMSProp::new(vec![w.clone()], 0.01);
+            rmsprop.momentum = momentum;
+            rmsprop.centered = centered;
+            assert!((train(&mut rmsprop, &w, 2000) - 3.0).abs() < 0.05, "momentum {} centered {}", momentum, centered);
+        }
+
+        let w = start();
+        let mut rmsprop = RMSProp::new(vec![w.clone()], 0.01);
+        rmsprop.centered = true;
+        train(&mut rmsprop, &w, 2);
+        let resumed = Var::param(w.value());
+        let mut copy = RMSProp::new(vec![resumed.clone()], 0.0);
+        copy.load_state_dict(&rmsprop.state_dict()).unwrap();
+        assert!(copy.centered);
+        assert_eq!(train(&mut copy, &resumed, 2), train(&mut rmsprop, &w, 2));
+    }
+
+    #[test]
+    fn lr_scheduler_test() {
+        use autograd::Var;
+        use optim::lr_scheduler::*;
+        use optim::{Optimizer, SGD};
+        use tensor::Tensor;
+
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-12;
+        let mut sgd = SGD::<f64>::new(vec![Var::param(Tensor::zeros(&[1]))], 0.1);
+
+        let mut step = StepLR::new(0.1, 2, 0.5);
+        let mut lrs = Vec::new();
+        for _ in 0..5 {
+            step.step(&mut sgd).unwrap();
+            lrs.push(sgd.learning_rate());
+        }
+        assert_eq!(lrs, vec![0.1, 0.05, 0.05, 0.025, 0.025]);
+
+        let mut exp = ExponentialLR::new(1.0, 0.9);
+        exp.advance(None).unwrap();
+        exp.advance(None).unwrap();
+        assert!(close(exp.lr(), 0.81));
+
+        let mut cosine = CosineAnnealingLR::new(1.0, 4, 0.0);
+        assert!(close(cosine.lr(), 1.0));
+        cosine.advance(None).unwrap();
+        cosine.advance(None).unwrap();
+        assert!(close(cosine.lr(), 0.5));
+        (0..4).for_each(|_| cosine.advance(None).unwrap());
+        assert!(close(cosine.lr(), 0.0));
+
+        let mut warm = WarmupLR::new(Box::new(ExponentialLR::new(1.0, 0.5)), 4, 0.0);
+        let mut lrs = vec![warm.lr()];
+        for _ in 0..6 {
+            warm.advance(None).unwrap();
+            lrs.push(warm.lr());
+        }
+        assert_eq!(lrs, vec![0.0, 0.25, 0.5, 0.75, 1.0, 0.5, 0.25]);
+
+        let mut plateau = ReduceLROnPlateau::new(1.0);
+        plateau.patience = 1;
+        plateau.min_lr = 0.05;
+        assert!(plateau.advance(None).is_err());
+        for loss in [1.0, 0.5, 0.5, 0.5, 0.4, 0.4, 0.4, 0.4, 0.4] {
+            plateau.step_with_metric(&mut sgd, loss).unwrap();
+        }
+        assert!(close(sgd.learning_rate(), 0.05));
+
+        let mut accuracy = ReduceLROnPlateau::new(1.0);
+        accuracy.mode = PlateauMode::Max;
+        accuracy.patience = 0;
+        accuracy.advance(Some(0.5)).unwrap();
+        accuracy.advance(Some(0.6)).unwrap();
+        assert!(close(accuracy.lr(), 1.0));
+        accuracy.advance(Some(0.55)).unwrap();
+        assert!(close(accuracy.lr(), 0.1));
+    }
+
+    #[test]
+    fn conv2d_layer_test() {
+        use autograd::{grad_check, Var};
+        use nn::{Conv2d, Module};
+        use tensor::conv::Conv2dOptions;
+        use tensor::Tensor;
+
+        let x = Tensor::from_fn(&[2, 4, 5, 5], |i| ((i[0] * 7 + i[1] * 5 + i[2] * 3 + i[3]) % 11) as f64 / 11.0 - 0.5);
+        let layer = Conv2d::<f64>::new(4, 6, (3, 3)).unwrap();
+        assert_eq!(layer.weight.shape(), vec![6, 4, 3, 3]);
+        assert_eq!(layer.predict(&x).unwrap().shape(), &[2, 6, 3, 3]);
+        let bound = (6.0f64 / 36.0).sqrt();
+        assert!(layer.weight.value().iter().all(|w| w.abs() <= bound));
+
+        let options = Conv2dOptions { stride: (2, 2), padding: (1, 1), ..Conv2dOptions::new((3, 3)) };
+        let strided = Conv2d::<f64>::with_options(4, 2, options, 1, false).unwrap();
+        assert_eq!(strided.predict(&x).unwrap().shape(), &[2, 2, 3, 3]);
+        assert_eq!(strided.parameters().len(), 1);
+
+        let grouped = Conv2d::<f64>::with_options(4, 4, Conv2dOptions::new((3, 3)), 2, true).unwrap();
+        assert_eq!(grouped.weight.shape(), vec![4, 2, 3, 3]);
+        let out = grouped.predict(&x).unwrap();
+        let (w, b) = (grouped.weight.value(), grouped.bias.as_ref().unwrap().value());
+        for g in 0..2 {
+            let expected = x
+                .narrow(1, 2 * g, 2)
+                .unwrap()
+                .conv2d(&w.narrow(0, 2 * g, 2).unwrap(), Some(&b.narrow(0, 2 * g, 2).unwrap()), &Conv2dOptions::new((3, 3)))
+                .unwrap();
+            assert_eq!(out.narrow(1, 2 * g, 2).unwrap(), expected);
+        }
+        assert!(Conv2d::<f64>::with_options(4, 3, Conv2dOptions::new((3, 3)), 2, true).is_err());
+        assert!(grouped.predict(&Tensor::zeros(&[1, 3, 5, 5])).is_err());
+
+        let small = x.narrow(0, 0, 1).unwrap();
+        let report = grad_check(|v| grouped.forward(&v[0]), std::slice::from_ref(&small), 1e-6, 1e-6).unwrap();
+        assert!(report.passed, "{}", report);
+        grouped.parameters().iter().for_each(|p| p.zero_grad());
+        grouped.forward(&Var::constant(small)).unwrap().sum(&[], false).unwrap().backward().unwrap();
+        assert_eq!(grouped.weight.grad().unwrap().shape(), &[4, 2, 3, 3]);
+        assert_eq!(grouped.bias.as_ref().unwrap().grad().unwrap().to_vec(), vec![9.0; 4]);
+    }
+
+    #[test]
+    fn normalization_test() {
+        use autograd::{grad_check, Var};
+        use nn::{BatchNorm1d, BatchNorm2d, LayerNorm, Module};
+        use tensor::Tensor;
+
+        let x = Tensor::from_vec(&[4, 2], vec![1.0, 10.0, 2.0, 20.0, 3.0, 30.0, 4.0, 40.0]).unwrap();
+        let bn = BatchNorm1d::<f64>::new(2, true);
+        let y = bn.predict(&x).unwrap();
+        let mean = y.mean(&[0], false).unwrap();
+        let var = y.var(&[0], false).unwrap();
+        assert!(mean.iter().all(|m| m.abs() < 1e-9));
+        assert!(var.iter().all(|v| (v - 1.0).abs() < 1e-4));
+        assert!((bn.running_mean().to_vec()[0] - 0.25).abs() < 1e-12);
+        assert!((bn.running_mean().to_vec()[1] - 2.5).abs() < 1e-12);
+        assert!((bn.running_var().to_vec()[0] - (0.9 + 0.1 * 5.0 / 3.0)).abs() < 1e-12);
+
+        bn.eval();
+        let before = bn.running_mean();
+        let z = bn.predict(&x).unwrap();
+        assert_eq!(bn.running_mean(), before);
+        let expected = (1.0 - 0.25) / (0.9 + 0.1 * 5.0 / 3.0 + 1e-5f64).sqrt();
+        assert!((z.to_vec()[0] - expected).abs() < 1e-12);
+        bn.train();
+        assert!(bn.is_training());
+        assert!(bn.predict(&Tensor::zeros(&[1, 2])).is_err());
+        assert!(bn.predict(&Tensor::zeros(&[4, 3])).is_err());
+
+        let images = Tensor::from_fn(&[2, 3, 2, 2], |i| ((i[0] * 5 + i[1] * 3 + i[2] * 2 + i[3]) % 7) as f64);
+        let bn2 = BatchNorm2d::<f64>::new(3, true);
+        let out = bn2.predict(&images).unwrap();
+        assert!(out.mean(&[0, 2, 3], false).unwrap().iter().all(|m| m.abs() < 1e-9));
+        assert!(bn2.predict(&Tensor::zeros(&[2, 3, 4])).is_err());
+        assert_eq!(bn2.parameters().len(), 2);
+        let report = grad_check(|v| bn2.forward(&v[0])?.powi(3).sum(&[], false), std::slice::from_ref(&images), 1e-6, 1e-5).unwrap();
+        assert!(report.passed, "{}", report);
+
+        let ln = LayerNorm::<f64>::new(&[3], true);
+        let seq = Tensor::from_fn(&[2, 2, 3], |i| (i[0] * 6 + i[1] * 3 + i[2] * i[2]) as f64);
+        let normed = ln.predict(&seq).unwrap();
+        assert!(normed.mean(&[2], false).unwrap().iter().all(|m| m.abs() < 1e-9));
+        assert!(ln.predict(&Tensor::zeros(&[2, 4])).is_err());
+        ln.weight.as_ref().unwrap().set_value(Tensor::full(&[3], 2.0)).unwrap();
+        let scaled = ln.predict(&seq).unwrap();
+        assert_eq!(scaled, normed.map(|&v| v * 2.0));
+        let report = grad_check(|v| ln.forward(&v[0])?.powi(3).sum(&[], false), std::slice::from_ref(&seq), 1e-6, 1e-5).unwrap();
+        assert!(report.passed, "{}", report);
+        assert!(LayerNorm::<f64>::new(&[3], false).forward(&Var::constant(seq)).is_ok());
+    }
+
+    #[test]
+    fn dropout_test() {
+        use autograd::Var;
+        use nn::{BatchNorm1d, Dropout, Linear, Module, ReLU, Sequential};
+        use tensor::Tensor;
+
+        let dropout = Dropout::new(0.5).unwrap();
+        let x = Var::param(Tensor::full(&[2000], 1.0));
+        let y = Module::<f64>::forward(&dropout, &x).unwrap();
+        let kept = y.value().iter().filter(|&&v| v != 0.0).count();
+        assert!(y.value().iter().all(|&v| v == 0.0 || v == 2.0));
+        assert!((800..1200).contains(&kept), "kept {}", kept);
+        y.sum(&[], false).unwrap().backward().unwrap();
+        assert_eq!(x.grad().unwrap(), y.value());
+
+        Module::<f64>::eval(&dropout);
+        assert!(!dropout.is_training());
+        assert_eq!(Module::<f64>::predict(&dropout, &x.value()).unwrap(), x.value());
+        let all = Dropout::new(1.0).unwrap();
+        assert!(Module::<f64>::predict(&all, &x.value()).unwrap().iter().all(|&v| v == 0.0));
+        assert!(Dropout::new(1.5).is_err());
+
+        let mut model = Sequential::<f64>::new();
+        model.push(Linear::new(4, 8, true));
+        model.push(BatchNorm1d::new(8, true));
+        model.push(ReLU);
+        model.push(Dropout::new(0.5).unwrap());
+        model.push(Linear::new(8, 2, false));
+        assert_eq!(model.len(), 5);
+        let names: Vec<String> = model.named_parameters().into_iter().map(|(name, _)| name).collect();
+        assert_eq!(names, vec!["0.weight", "0.bias", "1.weight", "1.bias", "4.weight"]);
+
+        let batch = Tensor::from_fn(&[3, 4], |i| (i[0] * 4 + i[1]) as f64 / 6.0 - 1.0);
+        assert_eq!(model.predict(&batch).unwrap().shape(), &[3, 2]);
+        model.eval();
+        let first = model.predict(&batch).unwrap();
+        assert_eq!(model.predict(&batch).unwrap(), first);
+        model.train();
+        assert!(model.predict(&batch.narrow(0, 0, 1).unwrap()).is_err());
+    }
+
+    #[test]
+    fn attention_test() {
+        use autograd::{grad_check, Var};
+        use nn::attention::{causal_mask, scaled_dot_product_attention};
+        use nn::{Module, MultiheadAttention};
+        use tensor::Tensor;
+
+        let q = Var::constant(Tensor::from_vec(&[1, 2], vec![1.0, 0.0]).unwrap());
+        let k = Var::constant(Tensor::from_vec(&[2, 2], vec![1.0, 0.0, 0.0, 1.0]).unwrap());
+        let v = Var::constant(Tensor::from_vec(&[2, 1], vec![10.0, 20.0]).unwrap());
+        let out = scaled_dot_product_attention(&q, &k, &v, None, false).unwrap().value();
+        let w = 1.0 / (1.0 + (-1.0f64 / 2f64.sqrt()).exp());
+        assert!((out.to_vec()[0] - (10.0 * w + 20.0 * (1.0 - w))).abs() < 1e-12);
+        let masked = Tensor::from_vec(&[1, 2], vec![0.0, f64::NEG_INFINITY]).unwrap();
+        assert_eq!(scaled_dot_product_attention(&q, &k, &v, Some(&masked), false).unwrap().value().to_vec(), vec![10.0]);
+
+        assert!(MultiheadAttention::<f64>::new(6, 4).is_err());
+        let mut mha = MultiheadAttention::<f64>::new(8, 2).unwrap();
+        assert_eq!(mha.head_dim(), 4);
+        assert_eq!(mha.parameters().len(), 8);
+        assert_eq!(mha.named_parameters()[0].0, "q_proj.weight");
+
+        let x = Tensor::from_fn(&[2, 5, 8], |i| ((i[0] * 13 + i[1] * 7 + i[2] * 3) % 10) as f64 / 10.0 - 0.45);
+        assert_eq!(mha.predict(&x).unwrap().shape(), &[2, 5, 8]);
+        let memory = Var::constant(x.narrow(1, 0, 3).unwrap());
+        let cross = mha.attend(&Var::constant(x.clone()), &memory, &memory, None).unwrap();
+        assert_eq!(cross.shape(), vec![2, 5, 8]);
+        assert!(mha.attend(&Var::constant(x.clone()), &memory, &Var::constant(x.clone()), None).is_err());
+
+        mha.causal = true;
+        let causal = mha.predict(&x).unwrap();
+        let xv = Var::constant(x.clone());
+        mha.causal = false;
+        let mask: Tensor<f64> = causal_mask(5, 5);
+        assert_eq!(mha.attend(&xv, &xv, &xv, Some(&mask)).unwrap().value(), causal);
+        mha.causal = true;
+        let mut altered = x.clone();
+        *altered.get_mut(&[0, 4, 0]).unwrap() += 1.0;
+        let prefix = mha.predict(&altered).unwrap().narrow(1, 0, 4).unwrap();
+        assert_eq!(prefix, causal.narrow(1, 0, 4).unwrap());
+
+        let small = x.narrow(1, 0, 3).unwrap();
+        let report = grad_check(|v| mha.forward(&v[0])?.powi(2).sum(&[], false), std::slice::from_ref(&small), 1e-6, 1e-5).unwrap();
+        assert!(report.passed, "{}", report);
+    }
+
+    #[test]
+    fn transformer_test() {
+        use autograd::{grad_check, Var};
+        use nn::{FeedForwardActivation, LayerNorm, Module, TransformerDecoderLayer, TransformerEncoder, TransformerEncoderLayer};
+        use tensor::Tensor;
+
+        let x = Tensor::from_fn(&[2, 4, 8], |i| ((i[0] * 11 + i[1] * 5 + i[2] * 3) % 9) as f64 / 9.0 - 0.5);
+        let layer = TransformerEncoderLayer::<f64>::new(8, 2, 16, 0.0).unwrap();
+        let out = layer.predict(&x).unwrap();
+        assert_eq!(out.shape(), &[2, 4, 8]);
+        assert!(out.mean(&[2], false).unwrap().iter().all(|m| m.abs() < 1e-9));
+        assert_eq!(layer.parameters().len(), 16);
+        assert!(TransformerEncoderLayer::<f64>::new(8, 3, 16, 0.1).is_err());
+
+        let mut pre_norm = TransformerEncoderLayer::<f64>::new(8, 4, 16, 0.0).unwrap();
+        pre_norm.norm_first = true;
+        pre_norm.activation = FeedForwardActivation::Gelu;
+        let report = grad_check(|v| pre_norm.forward(&v[0])?.powi(2).sum(&[], false), std::slice::from_ref(&x), 1e-6, 1e-5).unwrap();
+        assert!(report.passed, "{}", report);
+
+        let encoder = TransformerEncoder::<f64>::new(2, 8, 2, 16, 0.5).unwrap();
+        let names: Vec<String> = encoder.named_parameters().into_iter().map(|(name, _)| name).collect();
+        assert_eq!(names.len(), 32);
+        assert_eq!(names[0], "layers.0.self_attn.q_proj.weight");
+        assert_eq!(names[31], "layers.1.norm2.bias");
+        encoder.eval();
+        assert_eq!(encoder.predict(&x).unwrap(), encoder.predict(&x).unwrap());
+        encoder.train();
+        assert_ne!(encoder.predict(&x).unwrap(), encoder.predict(&x).unwrap());
+
+        let stacked = TransformerEncoder::from_layers(vec![layer], Some(LayerNorm::new(&[8], true)));
+        assert_eq!(stacked.parameters().len(), 18);
+        let mask = Tensor::from_fn(&[4, 4], |i| if i[1] == 3 { f64::NEG_INFINITY } else { 0.0 });
+        let masked = stacked.forward_with_mask(&Var::constant(x.clone()), Some(&mask)).unwrap().value();
+        let mut altered = x.clone();
+        *altered.get_mut(&[1, 3, 2]).unwrap() += 1.0;
+        let changed = stacked.forward_with_mask(&Var::constant(altered), Some(&mask)).unwrap().value();
+        assert_eq!(masked.narrow(1, 0, 3).unwrap(), changed.narrow(1, 0, 3).unwrap());
+
+        let decoder = TransformerDecoderLayer::<f64>::new(8, 2, 16, 0.0).unwrap();
+        assert_eq!(decoder.parameters().len(), 26);
+        let memory = Var::constant(x.narrow(1, 0, 3).unwrap());
+        let target = Tensor::from_fn(&[2, 5, 8], |i| ((i[0] + i[1] * 3 + i[2] * 7) % 6) as f64 / 6.0);
+        let decoded = decoder.forward_with_memory(&Var::constant(target.clone()), &memory, None, None).unwrap().value();
+        assert_eq!(decoded.shape(), &[2, 5, 8]);
+        let mut future = target.clone();
+        *future.get_mut(&[0, 4, 1]).unwrap() -= 2.0;
+        let redecoded = decoder.forward_with_memory(&Var::constant(future), &memory, None, None).unwrap().value();
+        assert_eq!(decoded.narrow(1, 0, 4).unwrap(), redecoded.narrow(1, 0, 4).unwrap());
+        assert!(decoder.predict(&target).is_err());
+    }
+
+    #[test]
+    fn module_apply_test() {
+        use nn::{BatchNorm2d, Conv2d, Linear, Module, MultiheadAttention, ReLU, Sequential};
+        use tensor::Tensor;
+
+        let mut model = Sequential::<f64>::new();
+        model.push(Conv2d::new(1, 2, (3, 3)).unwrap());
+        model.push(BatchNorm2d::new(2, true));
+        model.push(ReLU);
+        model.push(Linear::new(4, 3, true));
+        model.push(MultiheadAttention::new(3, 1).unwrap());
+
+        let mut visited = Vec::new();
+        model
+            .apply(|m| {
+                visited.push(m.name());
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(
+            visited,
+            vec!["Conv2d", "BatchNorm", "ReLU", "Linear", "Linear", "Linear", "Linear", "Linear", "MultiheadAttention", "Sequential"]
+        );
+
+        model
+            .apply(|m| {
+                for (name, param) in m.named_parameters() {
+                    match (m.name(), name.as_str()) {
+                        ("Linear", "weight") => param.set_value(init::xavier_uniform(&param.shape(), 1.0)?)?,
+                        ("Linear", "bias") | ("Conv2d", "bias") => param.set_value(Tensor::zeros(&param.shape()))?,
+                        ("Conv2d", "weight") => param.set_value(init::kaiming_normal(&param.shape(), init::FanMode::FanOut, init::Nonlinearity::Relu)?)?,
+                        _ => {}
+                    }
+                }
+                Ok(())
+            })
+            .unwrap();
+        let params = model.named_parameters();
+        let value = |name: &str| params.iter().find(|(n, _)| n == name).unwrap().1.value();
+        assert!(value("0.bias").iter().all(|&b| b == 0.0));
+        assert!(value("4.out_proj.bias").iter().all(|&b| b == 0.0));
+        let bound = (6.0f64 / 7.0).sqrt();
+        assert!(value("3.weight").iter().all(|w| w.abs() <= bound));
+
+        params.iter().for_each(|(_, p)| p.set_value(Tensor::full(&p.shape(), 5.0)).unwrap());
+        model.apply(|m| m.reset_parameters()).unwrap();
+        let params = model.named_parameters();
+        let value = |name: &str| params.iter().find(|(n, _)| n == name).unwrap().1.value();
+        assert!(value("1.weight").iter().all(|&w| w == 1.0));
+        assert!(value("1.bias").iter().all(|&b| b == 0.0));
+        assert!(value("3.weight").iter().all(|w| w.abs() <= 0.5));
+        assert!(value("0.weight").iter().all(|w| w.abs() <= 2f64.sqrt() * (3.0f64 / 9.0).sqrt()));
+        assert!(value("4.q_proj.weight").iter().all(|&w| w != 5.0));
+    }
+
+    #[test]
+    fn trainer_test() {
+        use nn::loss::mse_loss;
+        use nn::{Linear, Module};
+        use optim::SGD;
+        use tensor::device::Device;
+        use tensor::Tensor;
+        use train::{Batch, Trainer};
+
+        let batch = |start: usize| -> Batch<f64> {
+            let x = Tensor::from_fn(&[4, 1], |i| (start + i[0]) as f64 / 8.0);
+            let y = x.map(|&v| 2.0 * v + 1.0);
+            (x, y)
+        };
+        let train_data = vec![batch(0), batch(4)];
+        let val_data = vec![batch(2)];
+
+        let model = Linear::<f64>::new(1, 1, true);
+        let mut sgd = SGD::new(model.parameters(), 0.5);
+        let mut trainer = Trainer::new(&model, &mut sgd, |output, target| mse_loss(output, target, None));
+        trainer.add_metric("mae", |output, target| Ok(output.zip_map(target, |a, b| (a - b).abs())?.mean(&[], false)?.to_vec()[0]));
+        trainer.to(Device::Cpu).unwrap();
+        assert!(trainer.to(Device::Gpu(0)).is_err());
+
+        let history = trainer.fit(&train_data, Some(&val_data), 60).unwrap();
+        assert_eq!(history.len(), 60);
+        assert_eq!(history[59].epoch, 59);
+        assert!(history[59].get("loss").unwrap() < history[0].get("loss").unwrap());
+        assert!(history[59].get("val_loss").unwrap() < 1e-3);
+        assert!(history[59].get("val_mae").is_some());
+
+        let weight = model.weight.value();
+        let logs = trainer.evaluate(&val_data).unwrap();
+        assert_eq!(model.weight.value(), weight);
+        assert!(logs.get("mae").unwrap() < 0.05);
+        assert!((weight.to_vec()[0] - 2.0).abs() < 0.1);
+        assert!(trainer.evaluate(&Vec::<Batch<f64>>::new()).is_err());
+        assert_eq!(trainer.optimizer().learning_rate(), 0.5);
+    }
+
+    #[test]
+    fn callbacks_test() {
+        use io::checkpoint::load_checkpoint;
+        use nn::loss::mse_loss;
+        use nn::{Linear, Module};
+        use optim::SGD;
+        use tensor::Tensor;
+        use train::{Batch, Callback, CallbackContext, CsvLogger, EarlyStopping, EpochLogs, ModelCheckpoint, Trainer};
+
+        struct Counter {
+            batches: usize,
+            epochs: Vec<usize>
+        }
+
+        impl Callback<f64> for Counter {
+            fn on_batch_end(&mut self, _ctx: &mut CallbackContext<'_, f64>, _batch: usize, loss: f64) -> Result<(), String> {
+                assert!(loss.is_finite());
+                self.batches += 1;
+                Ok(())
+            }
+
+            fn on_epoch_end(&mut self, ctx: &mut CallbackContext<'_, f64>, logs: &EpochLogs) -> Result<(), String> {
+                self.epochs.push(logs.epoch);
+                ctx.stop_training = logs.epoch == 4;
+                Ok(())
+            }
+        }
+
+        let x = Tensor::from_fn(&[6, 1], |i| i[0] as f64 / 6.0);
+        let data: Vec<Batch<f64>> = vec![(x.clone(), x.map(|&v| 1.0 - v)), (x.clone(), x.map(|&v| 1.0 - v))];
+        let model = Linear::<f64>::new(1, 1, true);
+        let mut sgd = SGD::new(model.parameters(), 0.1);
+        let mut counter = Counter { batches: 0, epochs: Vec::new() };
+        let csv = std::env::temp_dir().join("oxidizeai_callbacks_test.csv");
+        let ckpt = std::env::temp_dir().join("oxidizeai_callbacks_test.ckpt");
+        {
+            let mut trainer = Trainer::new(&model, &mut sgd, |output, target| mse_loss(output, target, None));
+            trainer.add_callback(&mut counter);
+            trainer.add_callback(CsvLogger::new(&csv));
+            trainer.add_callback(ModelCheckpoint::new(&ckpt, "val_loss"));
+            assert_eq!(trainer.fit(&data, Some(&data), 20).unwrap().len(), 5);
+        }
+        assert_eq!(counter.batches, 10);
+        assert_eq!(counter.epochs, vec![0, 1, 2, 3, 4]);
+
+        let log = std::fs::read_to_string(&csv).unwrap();
+        let lines: Vec<&str> = log.lines().collect();
+        assert_eq!(lines.len(), 6);
+        assert_eq!(lines[0], "epoch,loss,val_loss");
+        assert!(lines[5].starts_with("4,"));
+        let saved = load_checkpoint::<f64, _>(&ckpt).unwrap();
+        assert_eq!(saved.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>(), vec!["weight", "bias"]);
+        assert_eq!(saved[0].1, model.weight.value());
+
+        let diverging = Linear::<f64>::new(1, 1, true);
+        let mut fast = SGD::new(diverging.parameters(), 10.0);
+        let mut stopper = EarlyStopping::new("loss", 2);
+        stopper.restore_best_weights = true;
+        let mut trainer = Trainer::new(&diverging, &mut fast, |output, target| mse_loss(output, target, None));
+        trainer.add_callback(&mut stopper);
+        let epochs = trainer.fit(&data, None, 50).unwrap().len();
+        let best = trainer.history().iter().map(|logs| logs.get("loss").unwrap()).fold(f64::INFINITY, f64::min);
+        drop(trainer);
+        assert!(epochs < 50);
+        assert_eq!(stopper.stopped_epoch(), Some(epochs - 1));
+        assert_eq!(stopper.best(), Some(best));
+
+        let mut missing = EarlyStopping::<f64>::new("val_accuracy", 1);
+        let mut sgd = SGD::new(model.parameters(), 0.1);
+        let mut trainer = Trainer::new(&model, &mut sgd, |output, target| mse_loss(output, target, None));
+        trainer.add_callback(&mut missing);
+        assert!(trainer.fit(&data, None, 1).unwrap_err().contains("val_accuracy"));
+    }
+
+    #[test]
+    fn state_dict_test() {
+        use io::checkpoint::{load_state_dict, save_state_dict};
+        use nn::{BatchNorm1d, Linear, Module, Sequential};
+        use tensor::Tensor;
+
+        let build = || {
+            let mut model = Sequential::<f64>::new();
+            model.push(Linear::new(3, 4, true));
+            model.push(BatchNorm1d::new(4, true));
+            model.push(Linear::new(4, 2, false));
+            model
+        };
+        let model = build();
+        let x = Tensor::from_fn(&[5, 3], |i| (i[0] * 3 + i[1]) as f64 / 7.0 - 1.0);
+        model.predict(&x).unwrap();
+
+        let state = model.state_dict();
+        let names: Vec<&str> = state.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["0.weight", "0.bias", "1.weight", "1.bias", "2.weight", "1.running_mean", "1.running_var"]);
+        assert_eq!(model.named_buffers().len(), 2);
+
+        let path = std::env::temp_dir().join("oxidizeai_state_dict_test.ckpt");
+        save_state_dict(&model, &path).unwrap();
+        let restored = build();
+        load_state_dict(&restored, &path, true).unwrap();
+        assert_eq!(restored.state_dict(), state);
+        model.eval();
+        restored.eval();
+        assert_eq!(restored.predict(&x).unwrap(), model.predict(&x).unwrap());
+
+        let mut extra = state.clone();
+        extra.push(("head.weight".to_string(), Tensor::zeros(&[1])));
+        let err = build().load_state_dict(&extra, true).unwrap_err();
+        assert!(err.contains("unexpected keys [head.weight]"), "{}", err);
+        assert!(build().load_state_dict(&extra, false).is_ok());
+
+        let partial: Vec<_> = state.iter().filter(|(name, _)| name.starts_with("0.")).cloned().collect();
+        let err = build().load_state_dict(&partial, true).unwrap_err();
+        assert!(err.contains("missing keys [1.weight, 1.bias, 2.weight, 1.running_mean, 1.running_var]"), "{}", err);
+        let fresh = build();
+        fresh.load_state_dict(&partial, false).unwrap();
+        assert_eq!(fresh.state_dict()[0], state[0]);
+
+        let mut wrong = state.clone();
+        wrong[4].1 = Tensor::zeros(&[2, 5]);
+        let untouched = build();
+        let before = untouched.state_dict();
+        let err = untouched.load_state_dict(&wrong, false).unwrap_err();
+        assert!(err.contains("'2.weight' has shape [2, 5] in the state dict but [2, 4] in the model"), "{}", err);
+        assert_eq!(untouched.state_dict(), before);
+    }
+
+    #[test]
+    fn regularization_test() {
+        use autograd::Var;
+        use nn::loss::{mse_loss, penalty};
+        use optim::{Adagrad, Adam, AdamW, Optimizer, ParamGroup, RMSProp, SGD};
+        use tensor::Tensor;
+
+        let w = Var::param(Tensor::from_vec(&[2], vec![1.0f64, -2.0]).unwrap());
+        let b = Var::param(Tensor::from_vec(&[1], vec![3.0]).unwrap());
+        let reg = penalty(&[w.clone(), b.clone()], 0.1, 0.01).unwrap();
+        assert!((reg.value().to_vec()[0] - (0.1 * 6.0 + 0.01 * 14.0)).abs() < 1e-12);
+        reg.backward().unwrap();
+        assert_eq!(w.grad().unwrap().to_vec(), vec![0.1 + 0.02, -0.1 - 0.04]);
+        assert_eq!(penalty::<f64>(&[], 0.1, 0.1).unwrap().value().to_vec(), vec![0.0]);
+        assert!(penalty(std::slice::from_ref(&w), -1.0, 0.0).is_err());
+
+        let target = Tensor::from_vec(&[2], vec![0.5, -1.0]).unwrap();
+        let data_loss = mse_loss(&w, &target, None).unwrap();
+        let total = (&data_loss + &penalty(std::slice::from_ref(&w), 0.0, 0.5).unwrap()).unwrap();
+        w.zero_grad();
+        total.backward().unwrap();
+        assert_eq!(w.grad().unwrap().to_vec(), vec![0.5 + 1.0, -1.0 - 2.0]);
+
+        let groups = || {
+            let weight = Var::param(Tensor::full(&[2], 1.0));
+            let bias = Var::param(Tensor::full(&[1], 1.0));
+            (weight.clone(), bias.clone(), vec![ParamGroup::with_weight_decay(vec![weight], 0.1), ParamGroup::with_weight_decay(vec![bias], 0.0)])
+        };
+        type Build = fn(Vec<ParamGroup<f64>>) -> Box<dyn Optimizer<f64>>;
+        let builders: Vec<Build> = vec![
+            |g| Box::new(SGD::from_groups(g, 0.5)),
+            |g| Box::new(Adam::from_groups(g, 0.5)),
+            |g| Box::new(AdamW::from_groups(g, 0.5)),
+            |g| Box::new(RMSProp::from_groups(g, 0.5)),
+            |g| Box::new(Adagrad::from_groups(g, 0.5))
+        ];
+        for build in builders {
+            let (weight, bias, groups) = groups();
+            let mut opt = build(groups);
+            assert_eq!(opt.params().len(), 2);
+            for p in opt.params() {
+                (p * &Var::constant(Tensor::full(&p.shape(), 0.0))).unwrap().sum(&[], false).unwrap().backward().unwrap();
+            }
+            opt.step().unwrap();
+            assert!(weight.value().iter().all(|&v| v < 1.0));
+            assert_eq!(bias.value().to_vec(), vec![1.0]);
+        }
+
+        let weight = Var::param(Tensor::full(&[2], 1.0));
+        let bias = Var::param(Tensor::full(&[1], 1.0));
+        let mut sgd = SGD::from_groups(vec![ParamGroup::with_weight_decay(vec![weight.clone()], 0.1), ParamGroup::new(vec![bias])], 0.5);
+        sgd.weight_decay = 1.0;
+        for p in sgd.params() {
+            (p * &Var::constant(Tensor::full(&p.shape(), 0.0))).unwrap().sum(&[], false).unwrap().backward().unwrap();
+        }
+        sgd.step().unwrap();
+        assert_eq!(weight.value().to_vec(), vec![0.95, 0.95]);
+        assert_eq!(sgd.params()[1].value().to_vec(), vec![0.5]);
+    }
+
+    #[test]
+    fn meters_test() {
+        use metrics::{Accuracy, Average, MeanMetric, Meter};
+        use nn::loss::cross_entropy;
+        use nn::{Linear, Module};
+        use optim::Adam;
+        use tensor::Tensor;
+        use train::{Batch, Trainer};
+
+        let mut average = Average::new();
+        assert!(average.value().is_nan());
+        average.add(1.0, 3.0);
+        average.add(3.0, 1.0);
+        assert_eq!(average.value(), 1.5);
+        assert_eq!(average.weight(), 4.0);
+
+        let logits = Tensor::from_vec(&[3, 2], vec![0.9, 0.1, 0.2, 0.8, 0.6, 0.4]).unwrap();
+        let mut acc = Accuracy::new();
+        Meter::<f64>::update(&mut acc, &logits, &Tensor::from_vec(&[3], vec![0.0, 1.0, 1.0]).unwrap()).unwrap();
+        let one_hot = Tensor::from_vec(&[3, 2], vec![1.0, 0.0, 0.0, 1.0, 1.0, 0.0]).unwrap();
+        Meter::<f64>::update(&mut acc, &logits, &one_hot).unwrap();
+        assert_eq!((acc.correct(), acc.total()), (5, 6));
+        assert!((Meter::<f64>::value(&acc) - 5.0 / 6.0).abs() < 1e-12);
+        assert!(Meter::<f64>::update(&mut acc, &logits, &Tensor::zeros(&[2])).is_err());
+        Meter::<f64>::reset(&mut acc);
+        let probs = Tensor::from_vec(&[4], vec![0.9, 0.3, 0.6, 0.1]).unwrap();
+        Meter::<f64>::update(&mut acc, &probs, &Tensor::from_vec(&[4], vec![1.0, 1.0, 1.0, 0.0]).unwrap()).unwrap();
+        assert_eq!(Meter::<f64>::value(&acc), 0.75);
+
+        let mut max_error = MeanMetric::new("max_error", |o: &Tensor<f64>, t: &Tensor<f64>| {
+            Ok(o.zip_map(t, |a, b| (a - b).abs())?.iter().fold(0.0, |m: f64, &x| m.max(x)))
+        });
+        max_error.update(&probs, &Tensor::zeros(&[4])).unwrap();
+        max_error.update(&Tensor::zeros(&[2]), &Tensor::full(&[2], 0.5)).unwrap();
+        assert!((max_error.value() - (0.9 * 4.0 + 0.5 * 2.0) / 6.0).abs() < 1e-12);
+        assert_eq!(max_error.name(), "max_error");
+
+        let x = Tensor::from_fn(&[8, 2], |i| if (i[0] % 2 == 0) == (i[1] == 0) { 1.0 } else { -1.0 });
+        let y = Tensor::from_fn(&[8], |i| (i[0] % 2) as f64);
+        let data: Vec<Batch<f64>> = vec![(x.narrow(0, 0, 4).unwrap(), y.narrow(0, 0, 4).unwrap()), (x.narrow(0, 4, 4).unwrap(), y.narrow(0, 4, 4).unwrap())];
+        let model = Linear::<f64>::new(2, 2, true);
+        let mut adam = Adam::new(model.parameters(), 0.1);
+        let mut trainer = Trainer::new(&model, &mut adam, |output, target| {
+            let labels: Vec<usize> = target.iter().map(|&t| t as usize).collect();
+            cross_entropy(output, &labels, None)
+        });
+        trainer.add_meter(Accuracy::new());
+        trainer.add_metric("batch_size", |o, _| Ok(o.shape()[0] as f64));
+        trainer.fit(&data, Some(&data), 30).unwrap();
+        let accuracy = trainer.metric_history("val_accuracy");
+        assert_eq!(accuracy.len(), 30);
+        assert_eq!(accuracy[29], 1.0);
+        assert_eq!(trainer.metric_history("batch_size"), vec![4.0; 30]);
+        assert_eq!(trainer.meters()[0].name(), "accuracy");
+        assert_eq!(trainer.meters()[0].value(), 1.0);
+        assert!(trainer.metric_history("f1").is_empty());
+    }
+
+    #[test]
+    fn data_parallel_test() {
+        use nn::loss::mse_loss;
+        use nn::{BatchNorm1d, Linear, Module, Sequential, Tanh};
+        use optim::SGD;
+        use tensor::Tensor;
+        use train::{Batch, Trainer};
+
+        let build = || {
+            let mut model = Sequential::<f64>::new();
+            model.push(Linear::new(3, 5, true));
+            model.push(Tanh);
+            model.push(Linear::new(5, 2, true));
+            model
+        };
+        let x = Tensor::from_fn(&[7, 3], |i| ((i[0] * 5 + i[1] * 3) % 7) as f64 / 7.0 - 0.5);
+        let y = Tensor::from_fn(&[7, 2], |i| if i[1] == 0 { (i[0] as f64).sin() } else { 0.5 });
+        let data: Vec<Batch<f64>> = vec![(x.clone(), y.clone()), (x.narrow(0, 0, 5).unwrap(), y.narrow(0, 0, 5).unwrap())];
+
+        let serial = build();
+        let parallel = build();
+        parallel.load_state_dict(&serial.state_dict(), true).unwrap();
+
+        let mut serial_sgd = SGD::new(serial.parameters(), 0.3);
+        let mut trainer = Trainer::new(&serial, &mut serial_sgd, |output, target| mse_loss(output, target, None));
+        let serial_loss = trainer.fit(&data, None, 3).unwrap()[2].get("loss").unwrap();
+
+        let mut parallel_sgd = SGD::new(parallel.parameters(), 0.3);
+        let mut trainer = Trainer::new(&parallel, &mut parallel_sgd, |output, target| mse_loss(output, target, None));
+        assert!(trainer.data_parallel(0, build).is_err());
+        trainer.data_parallel(3, build).unwrap();
+        assert!(trainer.is_data_parallel());
+        let parallel_loss = trainer.fit(&data, None, 3).unwrap()[2].get("loss").unwrap();
+        assert!((serial_loss - parallel_loss).abs() < 1e-12);
+        for ((_, a), (_, b)) in serial.state_dict().iter().zip(parallel.state_dict().iter()) {
+            assert!(a.zip_map(b, |p, q| (p - q).abs()).unwrap().iter().all(|&d| d < 1e-12));
+        }
+
+        let normed = || {
+            let mut model = Sequential::<f64>::new();
+            model.push(Linear::new(3, 2, true));
+            model.push(BatchNorm1d::new(2, true));
+            model
+        };
+        let model = normed();
+        let mut sgd = SGD::new(model.parameters(), 0.1);
+        let mut trainer = Trainer::new(&model, &mut sgd, |output, target| mse_loss(output, target, None));
+        trainer.data_parallel(2, normed).unwrap();
+        trainer.fit(&data, None, 1).unwrap();
+        assert_ne!(model.state_dict()[4].1.to_vec(), vec![0.0, 0.0]);
+        trainer.single_device();
+        assert!(!trainer.is_data_parallel());
+    }
+
+    #[test]
+    fn gradient_accumulation_test() {
+        use nn::loss::mse_loss;
+        use nn::{Linear, Module, Sequential, Tanh};
+        use optim::SGD;
+        use tensor::Tensor;
+        use train::{Batch, Trainer};
+
+        let build = || {
+            let mut model = Sequential::<f64>::new();
+            model.push(Linear::new(3, 4, true));
+            model.push(Tanh);
+            model.push(Linear::new(4, 1, true));
+            model
+        };
+        let x = Tensor::from_fn(&[8, 3], |i| ((i[0] * 3 + i[1] * 5) % 7) as f64 / 7.0 - 0.5);
+        let y = Tensor::from_fn(&[8, 1], |i| (i[0] as f64 * 0.7).cos());
+        let slice = |start: usize, len: usize| -> Batch<f64> { (x.narrow(0, start, len).unwrap(), y.narrow(0, start, len).unwrap()) };
+
+        let full = build();
+        let accumulated = build();
+        let parallel = build();
+        accumulated.load_state_dict(&full.state_dict(), true).unwrap();
+        parallel.load_state_dict(&full.state_dict(), true).unwrap();
+
+        let large: Vec<Batch<f64>> = vec![slice(0, 5), slice(5, 3)];
+        let mut sgd = SGD::new(full.parameters(), 0.2);
+        let mut trainer = Trainer::new(&full, &mut sgd, |output, target| mse_loss(output, target, None));
+        let full_loss = trainer.fit(&large, None, 2).unwrap()[1].get("loss").unwrap();
+
+        let micro: Vec<Batch<f64>> = vec![slice(0, 2), slice(2, 3), slice(5, 3)];
+        let mut sgd = SGD::new(accumulated.parameters(), 0.2);
+        let mut trainer = Trainer::new(&accumulated, &mut sgd, |output, target| mse_loss(output, target, None));
+        assert!(trainer.accumulate_gradients(0).is_err());
+        trainer.accumulate_gradients(2).unwrap();
+        assert_eq!(trainer.accumulation_steps(), 2);
+        let accumulated_loss = trainer.fit(&micro, None, 2).unwrap()[1].get("loss").unwrap();
+
+        let mut sgd = SGD::new(parallel.parameters(), 0.2);
+        let mut trainer = Trainer::new(&parallel, &mut sgd, |output, target| mse_loss(output, target, None));
+        trainer.accumulate_gradients(2).unwrap();
+        trainer.data_parallel(2, build).unwrap();
+        trainer.fit(&micro, None, 2).unwrap();
+
+        assert!(full_loss.is_finite() && accumulated_loss.is_finite());
+        for model in [&accumulated, &parallel] {
+            for ((_, a), (_, b)) in full.state_dict().iter().zip(model.state_dict().iter()) {
+                assert!(a.zip_map(b, |p, q| (p - q).abs()).unwrap().iter().all(|&d| d < 1e-12));
+            }
+        }
+    }
+
+    #[test]
+    fn onnx_export_test() {
+        use nn::{BatchNorm2d, Conv2d, Dropout, Linear, ReLU, Sequential, Softmax, TransformerDecoderLayer, TransformerEncoder};
+        use onnx::proto::{AttributeValue, DataType, Dimension};
+        use tensor::Tensor;
+
+        let mut mlp = Sequential::<f64>::new();
+        mlp.push(Linear::new(4, 8, true));
+        mlp.push(ReLU);
+        mlp.push(Dropout::new(0.5).unwrap());
+        mlp.push(Linear::new(8, 3, false));
+        mlp.push(Softmax { axis: 1 });
+        let x = Tensor::from_fn(&[2, 4], |i| (i[0] + i[1]) as f64 * 0.1);
+
+        let model = onnx::to_model(&mlp, &x).unwrap();
+        let ops: Vec<&str> = model.graph.nodes.iter().map(|node| node.op_type.as_str()).collect();
+        assert_eq!(ops, ["MatMul", "Add", "Relu", "Identity", "MatMul", "Softmax", "Identity"]);
+        let names: Vec<&str> = model.graph.initializers.iter().map(|init| init.name.as_str()).collect();
+        assert_eq!(names, ["0.weight", "0.bias", "3.weight"]);
+        assert_eq!(model.graph.initializers[0].dims, [4, 8]);
+        assert_eq!(model.graph.initializers[0].raw_data.len(), 32 * 4);
+        assert_eq!(model.graph.inputs[0].shape, [Dimension::Symbolic("batch".to_string()), Dimension::Fixed(4)]);
+        assert_eq!(model.graph.outputs[0].shape[1], Dimension::Fixed(3));
+        assert_eq!(model.graph.nodes.last().unwrap().outputs, ["output"]);
+
+        let path = std::env::temp_dir().join("oxidizeai_export_test.onnx");
+        onnx::export(&mlp, &x, &path).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        assert_eq!(&bytes[..2], &[0x08, onnx::IR_VERSION as u8]);
+        assert_eq!(bytes, model.encode());
+        std::fs::remove_file(&path).unwrap();
+
+        let mut cnn = Sequential::<f64>::new();
+        cnn.push(Conv2d::new(1, 2, (3, 3)).unwrap());
+        cnn.push(BatchNorm2d::new(2, true));
+        cnn.push(ReLU);
+        let model = onnx::to_model(&cnn, &Tensor::zeros(&[1, 1, 5, 5])).unwrap();
+        let conv = &model.graph.nodes[0];
+        assert_eq!(conv.op_type, "Conv");
+        assert_eq!(conv.inputs, ["input", "0.weight", "0.bias"]);
+        assert_eq!(conv.attribute("kernel_shape"), Some(&AttributeValue::Ints(vec![3, 3])));
+        assert_eq!(model.graph.nodes[1].op_type, "BatchNormalization");
+        assert!(model.graph.initializers.iter().any(|init| init.name == "1.running_var" && init.data_type == DataType::Float));
+
+        let encoder = TransformerEncoder::<f64>::new(2, 4, 2, 8, 0.1).unwrap();
+        let model = onnx::to_model(&encoder, &Tensor::zeros(&[1, 3, 4])).unwrap();
+        assert_eq!(model.graph.nodes.iter().filter(|node| node.op_type == "LayerNormalization").count(), 4);
+        assert!(model.graph.initializers.iter().any(|init| init.name == "layers.1.self_attn.out_proj.weight"));
+
+        let decoder = TransformerDecoderLayer::<f64>::new(4, 2, 8, 0.0).unwrap();
+        assert!(onnx::to_model(&decoder, &Tensor::zeros(&[1, 3, 4])).is_err());
+        assert!(!decoder.dropout.is_training());
+    }
+
+    #[test]
+    fn onnx_import_test() {
+        use nn::{BatchNorm1d, BatchNorm2d, Conv2d, Linear, Module, MultiheadAttention, ReLU, Sequential, Sigmoid, TransformerEncoder, GELU};
+        use onnx::proto::{AttributeProto, AttributeValue, DataType, GraphProto, ModelProto, NodeProto, TensorProto, ValueInfoProto};
+        use onnx::OnnxModel;
+        use tensor::conv::Conv2dOptions;
+        use tensor::Tensor;
+
+        let close = |a: &Tensor<f64>, b: &Tensor<f64>| {
+            assert_eq!(a.shape(), b.shape());
+            assert!(a.zip_map(b, |p, q| (p - q).abs()).unwrap().iter().all(|&d| d < 1e-4));
+        };
+        let roundtrip = |model: &dyn Module<f64>, x: &Tensor<f64>| {
+            let proto = onnx::to_model(model, x).unwrap();
+            let imported = OnnxModel::<f64>::from_bytes(&proto.encode()).unwrap();
+            assert_eq!(ModelProto::decode(&proto.encode()).unwrap(), proto);
+            close(&imported.predict(x).unwrap(), &model.predict(x).unwrap());
+        };
+
+        let mut mlp = Sequential::<f64>::new();
+        mlp.push(Linear::new(4, 6, true));
+        mlp.push(BatchNorm1d::new(6, true));
+        mlp.push(GELU);
+        mlp.push(Linear::new(6, 2, true));
+        mlp.push(Sigmoid);
+        mlp.train();
+        mlp.forward(&autograd::Var::constant(Tensor::from_fn(&[5, 4], |i| (i[0] * i[1]) as f64 * 0.3))).unwrap();
+        roundtrip(&mlp, &Tensor::from_fn(&[3, 4], |i| (i[0] as f64 - i[1] as f64) * 0.4));
+
+        let mut options = Conv2dOptions::new((3, 3));
+        options.padding = (1, 1);
+        options.stride = (2, 2);
+        let mut cnn = Sequential::<f64>::new();
+        cnn.push(Conv2d::with_options(2, 4, options, 2, true).unwrap());
+        cnn.push(BatchNorm2d::new(4, false));
+        cnn.push(ReLU);
+        roundtrip(&cnn, &Tensor::from_fn(&[2, 2, 5, 5], |i| ((i[1] + i[2] * 3 + i[3]) % 5) as f64 - 2.0));
+
+        let mut attention = MultiheadAttention::<f64>::new(4, 2).unwrap();
+        attention.causal = true;
+        let tokens = Tensor::from_fn(&[2, 3, 4], |i| ((i[0] + 2 * i[1] + 3 * i[2]) % 5) as f64 * 0.2);
+        roundtrip(&attention, &tokens);
+        let mut encoder = TransformerEncoder::<f64>::new(2, 4, 2, 8, 0.1).unwrap();
+        encoder.layers[1].norm_first = true;
+        roundtrip(&encoder, &tokens);
+
+        let path = std::env::temp_dir().join("oxidizeai_import_test.onnx");
+        onnx::export(&mlp, &Tensor::zeros(&[1, 4]), &path).unwrap();
+        let loaded: OnnxModel<f64> = onnx::load(&path).unwrap();
+        assert_eq!(loaded.input_names(), ["input"]);
+        assert_eq!(loaded.output_names(), ["output"]);
+        std::fs::remove_file(&path).unwrap();
+
+        let floats = |name: &str, dims: &[i64], data: Vec<f32>| TensorProto {
+            name: name.to_string(),
+            dims: dims.to_vec(),
+            float_data: data,
+            ..TensorProto::default()
+        };
+        let node = |op: &str, inputs: &[&str], output: &str, attributes: Vec<AttributeProto>| NodeProto {
+            name: output.to_string(),
+            op_type: op.to_string(),
+            inputs: inputs.iter().map(|s| s.to_string()).collect(),
+            outputs: vec![output.to_string()],
+            attributes
+        };
+        let info = |name: &str| ValueInfoProto { name: name.to_string(), ..ValueInfoProto::default() };
+        let ints = |name: &str, values: &[i64]| AttributeProto::new(name, AttributeValue::Ints(values.to_vec()));
+        let graph = GraphProto {
+            name: "python".to_string(),
+            nodes: vec![
+                node("MaxPool", &["x"], "pooled", vec![ints("kernel_shape", &[2, 2]), ints("strides", &[2, 2])]),
+                node("Flatten", &["pooled"], "flat", Vec::new()),
+                node("Gemm", &["flat", "w", "b"], "gemm", vec![AttributeProto::new("transB", AttributeValue::Int(1))]),
+                node("Shape", &["gemm"], "shape", Vec::new()),
+                node("Reshape", &["gemm", "target"], "reshaped", Vec::new()),
+                node("Softmax", &["reshaped"], "y", Vec::new())
+            ],
+            initializers: vec![
+                floats("w", &[3, 4], (0..12).map(|i| i as f32 * 0.1).collect()),
+                floats("b", &[3], vec![0.5, -0.5, 0.0]),
+                TensorProto { name: "target".to_string(), dims: vec![2], data_type: DataType::Int64, int64_data: vec![-1, 3], ..TensorProto::default() }
+            ],
+            inputs: vec![info("x"), info("w")],
+            outputs: vec![info("y"), info("shape")]
+        };
+        let model = OnnxModel::<f64>::from_proto(ModelProto { opset_version: 13, graph, ..ModelProto::default() }).unwrap();
+        assert_eq!(model.input_names(), ["x"]);
+        let x = Tensor::from_fn(&[2, 1, 4, 4], |i| (i[0] * 16 + i[2] * 4 + i[3]) as f64);
+        let outputs = model.run(&[("x", x.clone())]).unwrap();
+        assert_eq!(outputs[1].to_vec(), vec![2.0, 3.0]);
+
+        let pooled = Tensor::from_fn(&[2, 4], |i| (i[0] * 16 + (i[1] / 2) * 8 + (i[1] % 2) * 2 + 5) as f64);
+        let w = Tensor::from_fn(&[3, 4], |i| (i[0] * 4 + i[1]) as f32 as f64 * 0.1f32 as f64);
+        let logits = (&pooled.matmul(&w.transpose()).unwrap() + &Tensor::from_vec(&[3], vec![0.5, -0.5, 0.0]).unwrap()).unwrap();
+        close(&outputs[0], &logits.softmax(1).unwrap());
+        assert!(model.run(&[]).unwrap_err().contains("missing graph input 'x'"));
+
+        let mut unsupported = model.proto().clone();
+        unsupported.graph.nodes.push(node("Loop", &["y"], "z", Vec::new()));
+        assert!(OnnxModel::<f64>::from_proto(unsupported).unwrap_err().contains("Loop"));
+
+        let mut oversized = model.proto().clone();
+        oversized.graph.initializers[1].dims = vec![1 << 32, 1 << 32];
+        assert!(OnnxModel::<f64>::from_proto(oversized).unwrap_err().contains("invalid dimension"));
+        assert!(Tensor::from_vec(&[usize::MAX, 2], Vec::<f64>::new()).is_err());
+    }
+
+    #[test]
+    fn freeze_test() {
+        use autograd::Var;
+        use nn::loss::mse_loss;
+        use nn::{Linear, Module, Sequential, Tanh};
+        use optim::{Adam, Optimizer};
+        use tensor::Tensor;
+
+        let mut model = Sequential::<f64>::new();
+        model.push(Linear::new(3, 4, true));
+        model.push(Tanh);
+        model.push(Linear::new(4, 1, true));
+        model.freeze().unwrap();
+        assert!(model.trainable_parameters().is_empty());
+        model.layers()[2].unfreeze().unwrap();
+        assert_eq!(model.trainable_parameters().len(), 2);
+
+        let before = model.state_dict();
+        let mut adam = Adam::new(model.parameters(), 0.1);
+        adam.weight_decay = 0.5;
+        let x = Var::constant(Tensor::from_fn(&[5, 3], |i| (i[0] + i[1]) as f64 * 0.2));
+        let y = Tensor::from_fn(&[5, 1], |i| i[0] as f64);
+        for _ in 0..3 {
+            adam.zero_grad();
+            mse_loss(&model.forward(&x).unwrap(), &y, None).unwrap().backward().unwrap();
+            adam.step().unwrap();
+        }
+        let after = model.state_dict();
+        assert_eq!(before[0].1, after[0].1);
+        assert_eq!(before[1].1, after[1].1);
+        assert!(model.parameters()[0].grad().is_none());
+        assert_ne!(before[2].1, after[2].1);
+
+        let backbone = &model.parameters()[0];
+        backbone.set_grad(Some(Tensor::full(&[4, 3], 1.0))).unwrap();
+        adam.step().unwrap();
+        assert_eq!(backbone.value(), before[0].1);
+
+        model.unfreeze().unwrap();
+        assert_eq!(model.trainable_parameters().len(), 4);
+        let hidden = model.forward(&x).unwrap();
+        assert!(hidden.requires_grad());
+        assert!(hidden.set_requires_grad(false).is_err());
+        assert!(hidden.detach().set_requires_grad(true).is_ok());
+    }
+
+    #[test]
+    fn summary_test() {
+        use nn::{BatchNorm1d, Linear, Module, ReLU, Sequential};
+
+        let mut block = Sequential::<f64>::new();
+        block.push(Linear::new(8, 4, true));
+        block.push(ReLU);
+        let mut model = Sequential::<f64>::new();
+        model.push(Linear::new(3, 8, false));
+        model.push(BatchNorm1d::new(8, true));
+        model.push(block);
+        model.layers()[0].freeze().unwrap();
+
+        let before = model.state_dict();
+        let summary = model.summary(&[2, 3]).unwrap();
+        assert_eq!(model.state_dict(), before);
+
+        let names: Vec<&str> = summary.layers.iter().map(|layer| layer.name.as_str()).collect();
+        assert_eq!(names, ["0", "1", "2.0", "2.1"]);
+        assert_eq!(summary.layers[0].kind, "Linear");
+        assert_eq!(summary.layers[1].kind, "BatchNorm");
+        assert_eq!(summary.layers[2].output_shape, [2, 4]);
+        assert_eq!(summary.output_shape(), [2, 4]);
+        assert_eq!(summary.layers.iter().map(|layer| layer.params).collect::<Vec<_>>(), [24, 16, 36, 0]);
+        assert!(!summary.layers[0].is_trainable() && summary.layers[2].is_trainable());
+        assert_eq!((summary.total_params, summary.trainable_params, summary.non_trainable_params()), (76, 52, 24));
+
+        let table = summary.to_string();
+        assert!(table.starts_with("Layer"));
+        assert!(table.contains("2.0    Linear     [2, 4]        36      yes"));
+        assert!(table.contains("0      Linear     [2, 8]        24      no"));
+        assert!(table.ends_with("Non-trainable params: 24"));
+
+        let single = Linear::<f64>::new(3, 2, true).summary(&[3]).unwrap();
+        assert_eq!(single.layers[0].name, "Linear");
+        assert!(model.summary(&[2, 5]).is_err());
+    }
+
+    #[test]
+    fn amp_trainer_test() {
+        use autograd::{autocast, autocast_precision, HalfPrecision, LossScaler, Var};
+        use nn::loss::mse_loss;
+        use nn::{Linear, Module, Sequential, Tanh};
+        use optim::SGD;
+        use tensor::Tensor;
+        use train::{Batch, Trainer};
+
+        let x = Var::param(Tensor::from_vec(&[3], vec![1.0001f64, 70000.0, -0.1]).unwrap());
+        let rounded = x.round_to(HalfPrecision::F16);
+        assert_eq!(*rounded.value().get(&[0]).unwrap(), 1.0);
+        assert!(rounded.value().get(&[1]).unwrap().is_infinite());
+        assert_eq!(*HalfPrecision::BF16.round(&Tensor::scalar(1.001f64)).get(&[]).unwrap(), 1.0);
+
+        let a = Tensor::from_fn(&[2, 3], |i| 0.1 * (i[0] * 3 + i[1]) as f64 + 0.001);
+        let b = Tensor::from_fn(&[3, 2], |i| 0.3 - 0.07 * (i[0] * 2 + i[1]) as f64);
+        let half = autocast(Some(HalfPrecision::F16), || {
+            assert_eq!(autocast_precision(), Some(HalfPrecision::F16));
+            Var::constant(a.clone()).matmul(&Var::constant(b.clone())).unwrap().value()
+        });
+        assert_eq!(autocast_precision(), None);
+        let round = |t: &Tensor<f64>| HalfPrecision::F16.round(t);
+        assert_eq!(half, round(&round(&a).matmul(&round(&b)).unwrap()));
+        assert_ne!(half, a.matmul(&b).unwrap());
+
+        let build = || {
+            let mut model = Sequential::<f64>::new();
+            model.push(Linear::new(3, 8, true));
+            model.push(Tanh);
+            model.push(Linear::new(8, 1, true));
+            model
+        };
+        let inputs = Tensor::from_fn(&[16, 3], |i| ((i[0] * 3 + i[1] * 5) % 7) as f64 / 7.0 - 0.5);
+        let targets = Tensor::from_fn(&[16, 1], |i| 0.8 * inputs.get(&[i[0], 0]).unwrap() - 0.5 * inputs.get(&[i[0], 2]).unwrap() + 0.1);
+        let batches: Vec<Batch<f64>> = vec![(inputs.narrow(0, 0, 8).unwrap(), targets.narrow(0, 0, 8).unwrap()), (inputs.narrow(0, 8, 8).unwrap(), targets.narrow(0, 8, 8).unwrap())];
+
+        let model = build();
+        let mut sgd = SGD::new(model.parameters(), 0.2);
+        let mut trainer = Trainer::new(&model, &mut sgd, |output, target| mse_loss(output, target, None));
+        trainer.mixed_precision(HalfPrecision::F16, LossScaler::new(2f64.powi(24)));
+        assert_eq!(trainer.precision(), Some(HalfPrecision::F16));
+        let history = trainer.fit(&batches, None, 30).unwrap();
+        let (first, last) = (history[0].get("loss").unwrap(), history[29].get("loss").unwrap());
+        assert!(trainer.skipped_steps() > 0);
+        assert!(trainer.loss_scaler().unwrap().scale() < 2f64.powi(24));
+        assert!(last.is_finite() && last < first);
+        assert!(model.parameters().iter().all(|param| param.value().iter().all(|x| x.is_finite())));
+
+        trainer.data_parallel(2, build).unwrap();
+        assert!(trainer.fit(&batches, None, 1).is_err());
+        trainer.full_precision();
+        assert_eq!(trainer.precision(), None);
+        assert!(trainer.fit(&batches, None, 1).is_ok());
+    }
+
+    #[test]
+    fn reproducibility_test() {
+        use nn::loss::mse_loss;
+        use nn::{Dropout, Linear, Module, ReLU, Sequential};
+        use optim::SGD;
+        use random::{with_rng, with_seed};
+        use tensor::Tensor;
+        use train::{Batch, Trainer};
+
+        let build = || {
+            let mut model = Sequential::<f64>::new();
+            model.push(Linear::new(3, 6, true));
+            model.push(ReLU);
+            model.push(Dropout::new(0.3).unwrap());
+            model.push(Linear::new(6, 1, true));
+            model
+        };
+        let x = Tensor::from_fn(&[8, 3], |i| ((i[0] * 3 + i[1] * 5) % 7) as f64 / 7.0 - 0.5);
+        let y = Tensor::from_fn(&[8, 1], |i| (i[0] as f64 * 0.5).cos());
+        let batches: Vec<Batch<f64>> = vec![(x.clone(), y.clone())];
+        let run = |parallel: bool| {
+            seed(1234);
+            let model = build();
+            let mut sgd = SGD::new(model.parameters(), 0.1);
+            let mut trainer = Trainer::new(&model, &mut sgd, |output, target| mse_loss(output, target, None));
+            if parallel {
+                trainer.data_parallel(3, build).unwrap();
+            }
+            let loss = trainer.fit(&batches, None, 3).unwrap()[2].get("loss").unwrap();
+            (loss, model.state_dict())
+        };
+
+        let (loss, state) = run(false);
+        assert_eq!(run(false), (loss, state.clone()));
+        let (parallel_loss, parallel_state) = run(true);
+        set_deterministic(true);
+        assert!(is_deterministic());
+        assert_eq!(run(true), (parallel_loss, parallel_state));
+        set_deterministic(false);
+
+        seed(99);
+        let first = with_rng(|rng| rng.next_u64());
+        let inner = with_seed(5, || with_rng(|rng| rng.next_u64()));
+        assert_eq!(inner, with_seed(5, || with_rng(|rng| rng.next_u64())));
+        seed(99);
+        assert_eq!(with_rng(|rng| rng.next_u64()), first);
+        let second = with_rng(|rng| rng.next_u64());
+        seed(99);
+        with_rng(|rng| rng.next_u64());
+        with_seed(5, || with_rng(|rng| rng.next_u64()));
+        assert_eq!(with_rng(|rng| rng.next_u64()), second);
+    }
+
+    #[test]
+    fn data_loader_test() {
+        use data::{DataLoader, Dataset, Sample, TensorDataset};
+        use nn::loss::mse_loss;
+        use nn::{Linear, Module};
+        use optim::SGD;
+        use tensor::Tensor;
+        use train::{BatchSource, Trainer};
+
+        let features = Tensor::from_fn(&[10, 3], |i| (i[0] * 3 + i[1]) as f64);
+        let labels = Tensor::from_fn(&[10], |i| i[0] as f64);
+        let dataset = TensorDataset::new(features.clone(), labels.clone()).unwrap();
+        assert!(TensorDataset::new(features.clone(), Tensor::<f64>::zeros(&[9])).is_err());
+        assert_eq!(dataset.len(), 10);
+        let (x, y) = dataset.get(4).unwrap();
+        assert_eq!(x.to_vec(), vec![12.0, 13.0, 14.0]);
+        assert_eq!(y.shape(), &[] as &[usize]);
+        assert!(dataset.get(10).is_err());
+
+        let mut loader = DataLoader::new(&dataset, 4).unwrap();
+        assert!(DataLoader::new(&dataset, 0).is_err());
+        assert_eq!(loader.len(), 3);
+        let batches: Vec<_> = loader.iter().map(Result::unwrap).collect();
+        assert_eq!(batches.iter().map(|(x, _)| x.shape()[0]).collect::<Vec<_>>(), vec![4, 4, 2]);
+        assert_eq!(batches[1].1.to_vec(), vec![4.0, 5.0, 6.0, 7.0]);
+
+        loader.drop_last = true;
+        loader.shuffle = true;
+        loader.seed = Some(3);
+        assert_eq!(loader.len(), 2);
+        let epoch = |loader: &DataLoader<f64>| -> Vec<f64> { loader.iter().flat_map(|batch| batch.unwrap().1.to_vec()).collect() };
+        loader.set_epoch(0);
+        let first = epoch(&loader);
+        assert_eq!(loader.epoch(), 1);
+        let second = epoch(&loader);
+        assert_eq!(first.len(), 8);
+        assert_ne!(first, second);
+        loader.set_epoch(0);
+        assert_eq!(epoch(&loader), first);
+        for (x, y) in loader.iter().map(Result::unwrap) {
+            for (row, label) in y.to_vec().into_iter().enumerate() {
+                assert_eq!(*x.get(&[row, 0]).unwrap(), label * 3.0);
+            }
+        }
+
+        let (x, y) = loader.matrices().next().unwrap().unwrap();
+        assert_eq!((x.rows, x.cols, y.rows, y.cols), (4, 3, 4, 1));
+
+        let samples: Vec<Sample<f64>> = (0..5).map(|i| (Tensor::full(&[2], i as f64), Tensor::full(&[1], i as f64))).collect();
+        let loader = DataLoader::new(&samples, 2).unwrap();
+        let (x, y) = loader.batches().last().unwrap().unwrap();
+        assert_eq!((x.shape(), y.to_vec()), (&[1, 2][..], vec![4.0]));
+
+        let targets = features.narrow(1, 0, 1).unwrap().map(|&x| x * 0.01);
+        let regression = TensorDataset::new(features.map(|&x| x * 0.01), targets).unwrap();
+        let mut loader = DataLoader::new(&regression, 3).unwrap();
+        loader.shuffle = true;
+        let model = Linear::<f64>::new(3, 1, true);
+        let mut sgd = SGD::new(model.parameters(), 0.1);
+        let mut trainer = Trainer::new(&model, &mut sgd, |output, target| mse_loss(output, target, None));
+        let history = trainer.fit(&loader, None, 20).unwrap();
+        assert!(history[19].get("loss").unwrap() < history[0].get("loss").unwrap());
+    }
+
+    #[test]
+    fn data_split_test() {
+        use data::split::{random_indices, stratified_indices};
+        use data::{random_split, stratified_split, DataLoader, Dataset, Subset, TensorDataset};
+        use tensor::Tensor;
+
+        let labels = Tensor::from_fn(&[20], |i| if i[0] < 15 { 0.0 } else { 1.0 });
+        let dataset = TensorDataset::new(Tensor::from_fn(&[20, 2], |i| (i[0] * 2 + i[1]) as f64), labels).unwrap();
+
+        let parts = random_indices(20, &[0.7, 0.2, 0.1], Some(5)).unwrap();
+        assert_eq!(parts.iter().map(Vec::len).collect::<Vec<_>>(), vec![14, 4, 2]);
+        let mut all: Vec<usize> = parts.concat();
+        all.sort_unstable();
+        assert_eq!(all, (0..20).collect::<Vec<_>>());
+        assert_eq!(random_indices(20, &[0.7, 0.2, 0.1], Some(5)).unwrap(), parts);
+        assert_ne!(random_indices(20, &[0.7, 0.2, 0.1], Some(6)).unwrap(), parts);
+        assert_eq!(random_indices(7, &[0.5, 0.5], Some(1)).unwrap().iter().map(Vec::len).sum::<usize>(), 7);
+        assert!(random_indices(10, &[0.5, 0.6], None).is_err());
+        assert!(random_indices(10, &[1.0, 0.0], None).is_err());
+
+        let parts = stratified_indices(&dataset, &[0.8, 0.2], Some(2)).unwrap();
+        let positives = |part: &Vec<usize>| part.iter().filter(|&&i| i >= 15).count();
+        assert_eq!((parts[0].len(), positives(&parts[0])), (16, 4));
+        assert_eq!((parts[1].len(), positives(&parts[1])), (4, 1));
+
+        let splits = stratified_split(&dataset, &[0.6, 0.4], Some(9)).unwrap();
+        let (train, test) = (&splits[0], &splits[1]);
+        assert_eq!((train.len(), test.len()), (12, 8));
+        let (x, y) = test.get(0).unwrap();
+        let source = test.indices()[0];
+        assert_eq!(x.to_vec(), vec![(source * 2) as f64, (source * 2 + 1) as f64]);
+        assert_eq!(*y.get(&[]).unwrap(), if source < 15 { 0.0 } else { 1.0 });
+        assert_eq!(test.get_batch(&[0, 1]).unwrap().0.shape(), &[2, 2]);
+        assert!(test.get(8).is_err());
+
+        let splits = random_split(&dataset, &[0.5, 0.5], None).unwrap();
+        let loader = DataLoader::new(&splits[0], 4).unwrap();
+        assert_eq!(loader.iter().map(|batch| batch.unwrap().0.shape()[0]).sum::<usize>(), 10);
+        assert!(Subset::new(&dataset, vec![3, 20]).is_err());
+    }
+
+    #[test]
+    fn scaler_test() {
+        use preprocess::{MinMaxScaler, RobustScaler, Scaler, StandardScaler};
+
+        let data: Matrix<f64> = Matrix::from_vec(4, 2, vec![1.0, 10.0, 2.0, 10.0, 3.0, 10.0, 10.0, 10.0]).unwrap();
+
+        let mut standard = StandardScaler::new();
+        assert!(!standard.is_fitted());
+        assert!(standard.transform(&data).is_err());
+        let scaled = standard.fit_transform(&data).unwrap();
+        assert_eq!(standard.mean().unwrap(), &[4.0, 10.0]);
+        let column: Vec<f64> = (0..4).map(|row| scaled[(row, 0)]).collect();
+        assert!(column.iter().sum::<f64>().abs() < 1e-12);
+        assert!((column.iter().map(|x| x * x).sum::<f64>() / 4.0 - 1.0).abs() < 1e-12);
+        assert!((0..4).all(|row| scaled[(row, 1)] == 0.0));
+        assert!(standard.inverse_transform(&scaled).unwrap().approx_eq(&data, 1e-12));
+        assert!(standard.transform(&Matrix::<f64>::ones(2, 3)).is_err());
+
+        let mut minmax = MinMaxScaler::new();
+        minmax.feature_range = (-1.0, 1.0);
+        let scaled = minmax.fit_transform(&data).unwrap();
+        assert_eq!((scaled[(0, 0)], scaled[(3, 0)]), (-1.0, 1.0));
+        assert_eq!(minmax.data_min().unwrap(), &[1.0, 10.0]);
+        assert_eq!(minmax.data_max().unwrap(), &[10.0, 10.0]);
+        let fresh: Matrix<f32> = Matrix::from_vec(1, 2, vec![5.5, 10.0]).unwrap();
+        assert_eq!(minmax.transform(&fresh).unwrap().data, vec![0.0, -1.0]);
+        assert!(minmax.inverse_transform(&scaled).unwrap().approx_eq(&data, 1e-12));
+
+        let mut robust = RobustScaler::new();
+        let scaled = robust.fit_transform(&data).unwrap();
+        assert_eq!(robust.center().unwrap(), &[2.5, 10.0]);
+        assert_eq!(robust.scale().unwrap(), &[3.0, 1.0]);
+        assert!((scaled[(3, 0)] - 2.5).abs() < 1e-12);
+        assert!(robust.inverse_transform(&scaled).unwrap().approx_eq(&data, 1e-12));
+        robust.quantile_range = (80.0, 20.0);
+        assert!(robust.fit(&data).is_err());
+
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_string(&standard).unwrap();
+            let restored: StandardScaler = serde_json::from_str(&json).unwrap();
+            assert_eq!(restored.transform(&data).unwrap(), standard.transform(&data).unwrap());
+            let restored: MinMaxScaler = serde_json::from_str(&serde_json::to_string(&minmax).unwrap()).unwrap();
+            assert_eq!(restored, minmax);
+        }
+    }
+
+    #[test]
+    fn encoder_test() {
+        use preprocess::encoders::{from_one_hot, one_hot};
+        use preprocess::{HandleUnknown, LabelEncoder, OneHotEncoder};
+
+        let encoded: Matrix<f64> = one_hot(&[2, 0, 1], 3).unwrap();
+        assert_eq!(encoded.data, vec![0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0]);
+        assert_eq!(from_one_hot(&encoded), vec![2, 0, 1]);
+        assert!(one_hot::<f64>(&[3], 3).is_err());
+
+        let mut labels = LabelEncoder::new();
+        assert!(labels.transform(&["cat"]).is_err());
+        assert_eq!(labels.fit_transform(&["dog", "cat", "dog", "bird"]).unwrap(), vec![2, 1, 2, 0]);
+        assert_eq!(labels.classes(), &["bird", "cat", "dog"]);
+        assert_eq!(labels.inverse_transform(&[1, 0]).unwrap(), vec!["cat", "bird"]);
+        assert!(labels.inverse_transform(&[3]).is_err());
+        assert!(labels.transform(&["fish"]).is_err());
+        labels.unknown_value = Some(3);
+        assert_eq!(labels.transform(&["fish", "cat"]).unwrap(), vec![3, 1]);
+        labels.unknown_value = None;
+        let matrix: Matrix<f32> = labels.transform_one_hot(&["cat", "bird"]).unwrap();
+        assert_eq!((matrix.rows, matrix.cols), (2, 3));
+        assert_eq!(labels.inverse_one_hot(&matrix).unwrap(), vec!["cat", "bird"]);
+
+        let mut numeric = LabelEncoder::<i64>::new();
+        assert_eq!(numeric.fit_transform(&[10, -1, 10, 7]).unwrap(), vec![2, 0, 2, 1]);
+
+        let rows = vec![vec!["red", "small"], vec!["green", "large"], vec!["red", "large"]];
+        let mut encoder = OneHotEncoder::new();
+        let matrix: Matrix<f64> = encoder.fit_transform(&rows).unwrap();
+        assert_eq!(encoder.categories(), &[vec!["green".to_string(), "red".to_string()], vec!["large".to_string(), "small".to_string()]]);
+        assert_eq!(encoder.n_features_out(), 4);
+        assert_eq!(matrix.data, vec![0.0, 1.0, 0.0, 1.0, 1.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0]);
+        let back = encoder.inverse_transform(&matrix).unwrap();
+        assert_eq!(back[1], vec![Some("green".to_string()), Some("large".to_string())]);
+
+        let unseen = [["blue", "small"]];
+        assert!(encoder.transform::<f64, _, _>(&unseen).is_err());
+        assert!(encoder.transform::<f64, _, _>(&[["red"]]).is_err());
+        encoder.handle_unknown = HandleUnknown::Ignore;
+        let matrix: Matrix<f64> = encoder.transform(&unseen).unwrap();
+        assert_eq!(matrix.data, vec![0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(encoder.inverse_transform(&matrix).unwrap(), vec![vec![None, Some("small".to_string())]]);
+
+        #[cfg(feature = "serde")]
+        {
+            let restored: OneHotEncoder = serde_json::from_str(&serde_json::to_string(&encoder).unwrap()).unwrap();
+            assert_eq!(restored, encoder);
+            let restored: LabelEncoder<i64> = serde_json::from_str(&serde_json::to_string(&numeric).unwrap()).unwrap();
+            assert_eq!(restored.classes(), &[-1, 7, 10]);
+        }
+    }
+
+    #[test]
+    fn mnist_test() {
+        use data::mnist::{load_idx, load_mnist, read_idx};
+        use data::Dataset;
+        use tensor::Tensor;
+
+        let mut images = vec![0, 0, 0x08, 3, 0, 0, 0, 2, 0, 0, 0, 2, 0, 0, 0, 3];
+        images.extend((0..12).map(|i| (i * 20) as u8));
+        let labels = vec![0, 0, 0x08, 1, 0, 0, 0, 2, 7, 3];
+
+        let raw: Tensor<f64> = read_idx(images.as_slice()).unwrap();
+        assert_eq!(raw.shape(), &[2, 2, 3]);
+        assert_eq!(*raw.get(&[1, 0, 1]).unwrap(), 140.0);
+        let floats = [0u8, 0, 0x0D, 1, 0, 0, 0, 1, 0x40, 0x49, 0x0F, 0xDB];
+        assert!((*read_idx::<f32, _>(floats.as_slice()).unwrap().get(&[0]).unwrap() - std::f32::consts::PI).abs() < 1e-6);
+        assert!(read_idx::<f64, _>(&images[..20]).is_err());
+        assert!(read_idx::<f64, _>([1u8, 0, 0x08, 1].as_slice()).is_err());
+        let mut huge = vec![0u8, 0, 0x0E, 4];
+        huge.extend([0xFFu8; 16]);
+        assert!(read_idx::<f64, _>(huge.as_slice()).unwrap_err().contains("too large"));
+        assert!(read_idx::<f64, _>([0u8, 0, 0x08, 1, 0xFF, 0xFF, 0xFF, 0xFF, 7].as_slice()).unwrap_err().contains("truncated"));
+
+        let dir = std::env::temp_dir().join("oxidizeai_mnist_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("t10k-images-idx3-ubyte"), &images).unwrap();
+        std::fs::write(dir.join("t10k-labels-idx1-ubyte"), &labels).unwrap();
+        let dataset = load_mnist::<f32, _>(&dir, false).unwrap();
+        assert_eq!(dataset.features().shape(), &[2, 1, 2, 3]);
+        let (image, label) = dataset.get(1).unwrap();
+        assert_eq!((image.shape(), *label.get(&[]).unwrap()), (&[1, 2, 3][..], 3.0));
+        assert!((*image.get(&[0, 1, 2]).unwrap() - 220.0 / 255.0).abs() < 1e-6);
+        assert!(load_mnist::<f32, _>(&dir, true).is_err());
+
+        #[cfg(feature = "gzip")]
+        {
+            use std::io::Write;
+            let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(&labels).unwrap();
+            let path = dir.join("train-labels-idx1-ubyte.gz");
+            std::fs::write(&path, encoder.finish().unwrap()).unwrap();
+            assert_eq!(load_idx::<f64, _>(&path).unwrap().to_vec(), vec![7.0, 3.0]);
+        }
+        assert_eq!(load_idx::<f64, _>(dir.join("t10k-labels-idx1-ubyte")).unwrap().to_vec(), vec![7.0, 3.0]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn image_io_test() {
+        use io::image::{decode_image, encode_image, load_image, save_image, ColorMode, ImageFormat, ImageOptions, PixelRange};
+        use tensor::Tensor;
+
+        let rgb = Tensor::from_fn(&[3, 2, 4], |i| ((i[0] * 8 + i[1] * 4 + i[2]) * 10) as f64 / 255.0);
+        let png = encode_image(&rgb, PixelRange::Unit, ImageFormat::Png).unwrap();
+        let decoded: Tensor<f64> = decode_image(&png, &ImageOptions::new()).unwrap();
+        assert_eq!(decoded.shape(), &[3, 2, 4]);
+        assert!(decoded.zip_map(&rgb, |a, b| (a - b).abs()).unwrap().iter().all(|&d| d < 1e-6));
+
+        let mut options = ImageOptions::new();
+        options.range = PixelRange::Byte;
+        let bytes: Tensor<f32> = decode_image(&png, &options).unwrap();
+        assert_eq!(*bytes.get(&[1, 1, 3]).unwrap(), 150.0);
+        options.color = Some(ColorMode::Gray);
+        assert_eq!(decode_image::<f32>(&png, &options).unwrap().shape(), &[1, 2, 4]);
+        options.color = Some(ColorMode::Rgba);
+        options.range = PixelRange::Signed;
+        let rgba: Tensor<f64> = decode_image(&png, &options).unwrap();
+        assert_eq!((rgba.shape()[0], *rgba.get(&[3, 0, 0]).unwrap(), *rgba.get(&[0, 0, 0]).unwrap()), (4, 1.0, -1.0));
+
+        let path = std::env::temp_dir().join("oxidizeai_image_test.jpg");
+        save_image(&rgb.map(|&x| x * 2.0 - 1.0), &path, PixelRange::Signed).unwrap();
+        let jpeg: Tensor<f64> = load_image(&path, &ImageOptions::new()).unwrap();
+        assert_eq!(jpeg.shape(), &[3, 2, 4]);
+        assert!(jpeg.zip_map(&rgb, |a, b| (a - b).abs()).unwrap().iter().all(|&d| d < 0.15));
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(save_image(&rgb, "image.bmp", PixelRange::Unit).is_err());
+        assert!(encode_image(&Tensor::<f64>::zeros(&[5, 2, 2]), PixelRange::Unit, ImageFormat::Png).is_err());
+        assert!(decode_image::<f64>(b"not an image", &ImageOptions::new()).is_err());
+    }
+
+    #[test]
+    fn augmentation_test() {
+        use data::{ColorJitter, Compose, DataLoader, Normalize, RandomCrop, RandomHorizontalFlip, Resize, TensorDataset, Transform};
+        use tensor::Tensor;
+
+        let image = Tensor::from_fn(&[3, 4, 6], |i| (i[0] * 24 + i[1] * 6 + i[2]) as f64 / 72.0);
+
+        let flipped = RandomHorizontalFlip::new(1.0).unwrap().apply(&image).unwrap();
+        assert_eq!(*flipped.get(&[1, 2, 0]).unwrap(), *image.get(&[1, 2, 5]).unwrap());
+        assert_eq!(RandomHorizontalFlip::new(0.0).unwrap().apply(&image).unwrap(), image);
+        assert!(RandomHorizontalFlip::new(1.5).is_err());
+
+        seed(8);
+        let crop = RandomCrop::new(2, 3).apply(&image).unwrap();
+        assert_eq!(crop.shape(), &[3, 2, 3]);
+        let (top, left) = (0..3)
+            .flat_map(|y| (0..4).map(move |x| (y, x)))
+            .find(|&(y, x)| image.narrow(1, y, 2).unwrap().narrow(2, x, 3).unwrap() == crop)
+            .unwrap();
+        seed(8);
+        assert_eq!(RandomCrop::new(2, 3).apply(&image).unwrap(), image.narrow(1, top, 2).unwrap().narrow(2, left, 3).unwrap());
+        let mut padded = RandomCrop::new(4, 6);
+        padded.padding = 2;
+        assert_eq!(padded.apply(&image).unwrap().shape(), &[3, 4, 6]);
+        assert!(RandomCrop::new(5, 6).apply(&image).is_err());
+
+        let resized = Resize::new(2, 3).apply(&image).unwrap();
+        assert_eq!(resized.shape(), &[3, 2, 3]);
+        assert!((*resized.get(&[0, 0, 0]).unwrap() - 3.5 / 72.0).abs() < 1e-12);
+        assert_eq!(Resize::new(4, 6).apply(&image).unwrap(), image);
+
+        let normalize = Normalize::new(vec![0.5, 0.5, 0.5], vec![0.25, 0.5, 1.0]).unwrap();
+        let normalized = normalize.apply(&image).unwrap();
+        assert!((*normalized.get(&[0, 0, 0]).unwrap() + 2.0).abs() < 1e-12);
+        assert!(normalize.apply(&Tensor::<f64>::zeros(&[2, 2, 2])).is_err());
+        assert!(Normalize::new(vec![0.5], vec![0.0]).is_err());
+
+        assert!(ColorJitter::new().apply(&image).unwrap().zip_map(&image, |a, b| (a - b).abs()).unwrap().iter().all(|&d| d < 1e-12));
+        let mut jitter = ColorJitter::new();
+        jitter.brightness = 0.5;
+        jitter.saturation = 0.5;
+        let jittered = jitter.apply(&image).unwrap();
+        assert_eq!(jittered.shape(), image.shape());
+        assert!(jittered.iter().all(|&x| (0.0..=1.0).contains(&x)));
+
+        let mut pipeline = Compose::new();
+        pipeline.push(RandomCrop::new(4, 4));
+        pipeline.push(RandomHorizontalFlip::new(0.5).unwrap());
+        pipeline.push(|image: &Tensor<f64>| Ok(image.map(|&x| x * 2.0)));
+        assert_eq!(pipeline.len(), 3);
+        assert_eq!(pipeline.apply(&image).unwrap().shape(), &[3, 4, 4]);
+
+        let images = Tensor::from_fn(&[5, 3, 4, 6], |i| (i[0] + i[3]) as f64 / 10.0);
+        let dataset = TensorDataset::new(images, Tensor::from_fn(&[5], |i| i[0] as f64)).unwrap();
+        let mut loader = DataLoader::new(&dataset, 2).unwrap();
+        loader.set_transform(pipeline);
+        let shapes: Vec<Vec<usize>> = loader.iter().map(|batch| batch.unwrap().0.shape().to_vec()).collect();
+        assert_eq!(shapes, vec![vec![2, 3, 4, 4], vec![2, 3, 4, 4], vec![1, 3, 4, 4]]);
+    }
+
+    #[test]
+    fn tabular_dataset_test() {
+        use data::{ColumnType, DataLoader, Dataset, TabularDataset, TabularOptions};
+
+        let csv = "age,city,income,zip,label\n34,Paris,52000.5,75001,yes\n29,Lyon,NA,69001,no\n41,Paris,61000,75002,yes\n,Nice,48000,06000,no\n";
+        let mut options = TabularOptions::new();
+        options.target = Some("label".to_string());
+        options.categorical = vec!["zip".to_string()];
+        let dataset = TabularDataset::<f64>::from_csv(csv.as_bytes(), &options).unwrap();
+
+        let kinds: Vec<ColumnType> = dataset.columns().iter().map(|column| column.kind).collect();
+        assert_eq!(kinds, vec![ColumnType::Numeric, ColumnType::Categorical, ColumnType::Numeric, ColumnType::Categorical, ColumnType::Categorical]);
+        assert_eq!(dataset.columns()[0].missing, 1);
+        assert_eq!(dataset.columns()[1].categories, vec!["Lyon", "Nice", "Paris"]);
+        assert_eq!(
+            dataset.feature_names(),
+            &["age", "city=Lyon", "city=Nice", "city=Paris", "income", "zip=06000", "zip=69001", "zip=75001", "zip=75002"]
+        );
+        assert_eq!(dataset.target_classes().unwrap(), &["no", "yes"]);
+
+        let features = dataset.features();
+        assert_eq!((features.rows, features.cols), (4, 9));
+        assert_eq!(&features.data[..9], &[34.0, 0.0, 0.0, 1.0, 52000.5, 0.0, 0.0, 1.0, 0.0]);
+        assert!(features[(1, 4)].is_nan() && features[(3, 0)].is_nan());
+        assert_eq!(dataset.target().unwrap().data, vec![1.0, 0.0, 1.0, 0.0]);
+
+        let (x, y) = dataset.get(2).unwrap();
+        assert_eq!((x.shape(), y.to_vec()), (&[9][..], vec![1.0]));
+        let loader = DataLoader::new(&dataset, 3).unwrap();
+        assert_eq!(loader.iter().next().unwrap().unwrap().1.shape(), &[3, 1]);
+
+        let mut unlabeled = TabularOptions::new();
+        unlabeled.csv.has_header = false;
+        let dataset = TabularDataset::<f32>::from_csv("1,a\n2,b\n".as_bytes(), &unlabeled).unwrap();
+        assert_eq!(dataset.feature_names(), &["column_0", "column_1=a", "column_1=b"]);
+        assert!(dataset.target().is_none());
+        assert_eq!(dataset.get(0).unwrap().1.shape(), &[0]);
+
+        options.target = Some("missing".to_string());
+        assert!(TabularDataset::<f64>::from_csv(csv.as_bytes(), &options).is_err());
+        assert!(TabularDataset::<f64>::from_csv("a,b\n1\n".as_bytes(), &TabularOptions::new()).is_err());
+    }
+
+    #[test]
+    fn imputer_test() {
+        use preprocess::{ImputeStrategy, Imputer};
+        use tensor::Tensor;
+
+        let nan = f64::NAN;
+        let data: Matrix<f64> = Matrix::from_vec(4, 3, vec![1.0, 5.0, nan, nan, 5.0, 2.0, 3.0, 7.0, nan, 8.0, nan, nan]).unwrap();
+        assert_eq!(data.nanmean(0).unwrap().data[..2], [4.0, 17.0 / 3.0]);
+        assert_eq!(data.nansum(1).unwrap().data, vec![6.0, 7.0, 10.0, 8.0]);
+        assert_eq!(data.nanmax(0).unwrap().data[..2], [8.0, 7.0]);
+        assert_eq!(data.nanmin(0).unwrap().data, vec![1.0, 5.0, 2.0]);
+        assert_eq!(data.count_nan(0).unwrap().data, vec![1, 1, 3]);
+        assert!(data.nansum(2).is_err());
+        let empty = Tensor::from_vec(&[2], vec![nan, nan]).unwrap();
+        assert!(empty.nanmean(&[], false).unwrap().get(&[]).unwrap().is_nan());
+
+        let mut imputer = Imputer::new(ImputeStrategy::Mean);
+        assert!(imputer.transform(&data).is_err());
+        imputer.set_column_strategy(1, ImputeStrategy::MostFrequent);
+        imputer.set_column_strategy(2, ImputeStrategy::Constant(-1.0));
+        let filled = imputer.fit_transform(&data).unwrap();
+        assert_eq!(imputer.statistics().unwrap(), &[4.0, 5.0, -1.0]);
+        assert_eq!(filled.data, vec![1.0, 5.0, -1.0, 4.0, 5.0, 2.0, 3.0, 7.0, -1.0, 8.0, 5.0, -1.0]);
+
+        let mut median = Imputer::new(ImputeStrategy::Median);
+        median.fit(&data).unwrap();
+        assert_eq!(median.statistics().unwrap(), &[3.0, 5.0, 2.0]);
+        let fresh: Matrix<f32> = Matrix::from_vec(1, 3, vec![f32::NAN, 1.0, f32::NAN]).unwrap();
+        assert_eq!(median.transform(&fresh).unwrap().data, vec![3.0, 1.0, 2.0]);
+        assert!(median.transform(&Matrix::<f64>::ones(1, 2)).is_err());
+
+        let all_missing: Matrix<f64> = Matrix::from_vec(2, 1, vec![nan, nan]).unwrap();
+        assert!(Imputer::new(ImputeStrategy::Mean).fit(&all_missing).is_err());
+        assert_eq!(Imputer::new(ImputeStrategy::Constant(0.0)).fit_transform(&all_missing).unwrap().data, vec![0.0, 0.0]);
+        let mut bad = Imputer::new(ImputeStrategy::Mean);
+        bad.set_column_strategy(5, ImputeStrategy::Median);
+        assert!(bad.fit(&data).is_err());
+    }
+
+    #[test]
+    fn categorical_encoding_test() {
+        use preprocess::{FeatureHasher, TargetEncoder};
+
+        let rows = vec![vec!["user_17", "fr"], vec!["user_3", "de"], vec!["user_17", "de"]];
+        let mut hasher = FeatureHasher::new(16).unwrap();
+        assert!(FeatureHasher::new(0).is_err());
+        let hashed: Matrix<f64> = hasher.transform(&rows).unwrap();
+        assert_eq!((hashed.rows, hashed.cols), (3, 16));
+        let row_norm = |m: &Matrix<f64>, r: usize| (0..m.cols).map(|c| m[(r, c)].abs()).sum::<f64>();
+        assert!((0..3).all(|r| row_norm(&hashed, r) <= 2.0 && row_norm(&hashed, r) > 0.0));
+        let (bucket, sign) = hasher.bucket(0, "user_17");
+        assert_eq!(hasher.bucket(0, "user_17"), (bucket, sign));
+        assert_ne!(hasher.bucket(0, "fr"), hasher.bucket(1, "fr"));
+        assert_eq!(hashed.data[..16], hasher.transform::<f64, _, _>(&rows[..1]).unwrap().data[..]);
+        hasher.alternate_sign = false;
+        let unsigned: Matrix<f32> = hasher.transform(&rows).unwrap();
+        assert!(unsigned.data.iter().all(|&x| x >= 0.0));
+        assert_eq!((0..16).map(|c| unsigned[(1, c)]).sum::<f32>(), 2.0);
+
+        let rows = vec![["a"], ["a"], ["b"], ["b"], ["b"], ["c"]];
+        let target = [1.0, 0.0, 1.0, 1.0, 1.0, 0.0];
+        let mut encoder = TargetEncoder::new();
+        assert!(encoder.fit(&rows, &target[..3]).is_err());
+        encoder.fit(&rows, &target).unwrap();
+        assert!((encoder.prior() - 4.0 / 6.0).abs() < 1e-12);
+        assert!((encoder.encoding(0, "a").unwrap() - (1.0 + 4.0 / 6.0) / 3.0).abs() < 1e-12);
+        assert!((encoder.encoding(0, "b").unwrap() - (3.0 + 4.0 / 6.0) / 4.0).abs() < 1e-12);
+        let encoded: Matrix<f64> = encoder.transform(&[["b"], ["unseen"]]).unwrap();
+        assert_eq!(encoded.data, vec![encoder.encoding(0, "b").unwrap(), encoder.prior()]);
+
+        encoder.smoothing = 0.0;
+        encoder.folds = 0;
+        let plain: Matrix<f64> = encoder.fit_transform(&rows, &target).unwrap();
+        assert_eq!(plain.data, vec![0.5, 0.5, 1.0, 1.0, 1.0, 0.0]);
+        encoder.folds = 2;
+        let crossed: Matrix<f64> = encoder.fit_transform(&rows, &target).unwrap();
+        assert_eq!(crossed.data, vec![0.0, 1.0, 1.0, 1.0, 1.0, 1.0]);
+        assert_eq!(encoder.encoding(0, "c"), Some(0.0));
+    }
+
+    #[test]
+    fn streaming_dataset_test() {
+        use data::{IterableDataset, ShardFormat, ShardedDataset, StreamLoader, StreamState};
+        use io::csv::CsvOptions;
+        use io::npy::save_npy;
+        use nn::loss::mse_loss;
+        use nn::{Linear, Module};
+        use optim::SGD;
+        use train::Trainer;
+
+        let dir = std::env::temp_dir().join("oxidizeai_stream_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a.csv"), "x1,x2,y\n0,1,10\n2,3,11\n\n4,5,12\n").unwrap();
+        std::fs::write(dir.join("b.csv"), "x1,x2,y\n6,7,13\n8,9,14\n").unwrap();
+        let shards = vec![dir.join("a.csv"), dir.join("b.csv")];
+
+        let mut dataset = ShardedDataset::new(shards.clone(), ShardFormat::Csv(CsvOptions::default())).unwrap();
+        dataset.target = Some(2);
+        assert!(ShardedDataset::new(Vec::new(), ShardFormat::Npy).is_err());
+        assert_eq!(IterableDataset::<f64>::num_shards(&dataset), 2);
+        let (x, y) = IterableDataset::<f64>::read_shard(&dataset, 1).unwrap().next().unwrap().unwrap();
+        assert_eq!((x.to_vec(), y.to_vec()), (vec![6.0, 7.0], vec![13.0]));
+
+        let loader = StreamLoader::<f64>::new(&dataset, 2).unwrap();
+        let labels = |loader: &StreamLoader<f64>| -> Vec<Vec<f64>> { loader.iter().map(|batch| batch.unwrap().1.to_vec()).collect() };
+        assert_eq!(labels(&loader), vec![vec![10.0, 11.0], vec![12.0, 13.0], vec![14.0]]);
+        assert_eq!(loader.state(), StreamState { epoch: 1, shard: 0, offset: 0 });
+
+        let mut batches = loader.iter();
+        batches.next().unwrap().unwrap();
+        batches.next().unwrap().unwrap();
+        let checkpoint = loader.state();
+        assert_eq!(checkpoint, StreamState { epoch: 1, shard: 1, offset: 1 });
+        let mut resumed = StreamLoader::<f64>::new(&dataset, 2).unwrap();
+        resumed.resume(checkpoint);
+        assert_eq!(labels(&resumed), vec![vec![14.0]]);
+        resumed.drop_last = true;
+        assert_eq!(labels(&resumed), vec![vec![10.0, 11.0], vec![12.0, 13.0]]);
+
+        resumed.shuffle_shards = true;
+        resumed.seed = 4;
+        let orders: Vec<Vec<usize>> = (0..8).map(|epoch| resumed.shard_order(epoch)).collect();
+        assert!(orders.contains(&vec![1, 0]));
+        assert_eq!(resumed.shard_order(3), orders[3]);
+
+        let matrix: Matrix<f64> = Matrix::from_vec(2, 3, vec![1.0, 0.5, 0.25, 2.0, 1.0, 0.5]).unwrap();
+        save_npy(dir.join("c.npy"), &matrix).unwrap();
+        let npy = ShardedDataset::new(vec![dir.join("c.npy")], ShardFormat::Npy).unwrap();
+        let loader = StreamLoader::<f32>::new(&npy, 4).unwrap();
+        let (x, y) = loader.iter().next().unwrap().unwrap();
+        assert_eq!((x.shape(), y.shape()), (&[2, 3][..], &[2, 0][..]));
+
+        std::fs::write(dir.join("bad.csv"), "x1,x2,y\n1,oops,2\n").unwrap();
+        let mut bad = ShardedDataset::new(vec![dir.join("bad.csv")], ShardFormat::Csv(CsvOptions::default())).unwrap();
+        bad.target = Some(2);
+        let loader = StreamLoader::<f64>::new(&bad, 2).unwrap();
+        let results: Vec<_> = loader.iter().collect();
+        assert!(results.len() == 1 && results[0].as_ref().unwrap_err().contains("oops"));
+
+        let model = Linear::<f64>::new(2, 1, true);
+        let mut sgd = SGD::new(model.parameters(), 0.001);
+        let mut trainer = Trainer::new(&model, &mut sgd, |output, target| mse_loss(output, target, None));
+        let loader = StreamLoader::<f64>::new(&dataset, 2).unwrap();
+        let history = trainer.fit(&loader, None, 5).unwrap();
+        assert!(history[4].get("loss").unwrap() < history[0].get("loss").unwrap());
+        assert_eq!(loader.state().epoch, 5);
+
+        #[cfg(feature = "arrow")]
+        {
+            use arrow_array::{ArrayRef, Float64Array, RecordBatch};
+            use io::arrow::ArrowOptions;
+            use std::sync::Arc;
+
+            let batch = RecordBatch::try_from_iter(vec![
+                ("x", Arc::new(Float64Array::from(vec![1.0, 2.0, 3.0])) as ArrayRef),
+                ("y", Arc::new(Float64Array::from(vec![4.0, 5.0, 6.0])) as ArrayRef)
+            ])
+            .unwrap();
+            let file = std::fs::File::create(dir.join("d.parquet")).unwrap();
+            let mut writer = parquet::arrow::ArrowWriter::try_new(file, batch.schema(), None).unwrap();
+            writer.write(&batch).unwrap();
+            writer.close().unwrap();
+            let mut parquet = ShardedDataset::new(vec![dir.join("d.parquet")], ShardFormat::Parquet(ArrowOptions::default())).unwrap();
+            parquet.target = Some(1);
+            let loader = StreamLoader::<f64>::new(&parquet, 2).unwrap();
+            assert_eq!(labels(&loader), vec![vec![4.0, 5.0], vec![6.0]]);
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn prefetch_loader_test() {
+        use data::{DataLoader, RandomHorizontalFlip, TensorDataset};
+        use nn::loss::mse_loss;
+        use nn::{Linear, Module};
+        use optim::SGD;
+        use std::sync::Arc;
+        use tensor::Tensor;
+        use train::Trainer;
+
+        let features = Tensor::from_fn(&[23, 1, 2, 2], |i| (i[0] * 4 + i[2] * 2 + i[3]) as f64);
+        let labels = Tensor::from_fn(&[23], |i| i[0] as f64);
+        let dataset = Arc::new(TensorDataset::new(features.clone(), labels.clone()).unwrap());
+        let run = |workers: usize, prefetch: usize| -> Vec<Vec<f64>> {
+            let mut loader = DataLoader::shared(Arc::clone(&dataset), 4).unwrap();
+            loader.shuffle = true;
+            loader.seed = Some(11);
+            loader.num_workers = workers;
+            loader.prefetch = prefetch;
+            loader.set_transform(RandomHorizontalFlip::new(0.5).unwrap());
+            random::seed(5);
+            loader.iter().map(|batch| {
+                let (x, y) = batch.unwrap();
+                x.to_vec().into_iter().chain(y.to_vec()).collect()
+            }).collect()
+        };
+        let synchronous = run(0, 2);
+        assert_eq!(synchronous.len(), 6);
+        assert_eq!(run(3, 2), synchronous);
+        assert_eq!(run(8, 0), synchronous);
+        assert_eq!(run(2, 1), synchronous);
+
+        let mut loader = DataLoader::shared(Arc::clone(&dataset), 5).unwrap();
+        loader.num_workers = 2;
+        assert_eq!(loader.iter().take(2).count(), 2);
+        assert_eq!(loader.iter().map(|batch| batch.unwrap().0.shape()[0]).sum::<usize>(), 23);
+
+        let borrowed = TensorDataset::new(features.clone(), labels.clone()).unwrap();
+        let mut loader = DataLoader::new(&borrowed, 4).unwrap();
+        loader.num_workers = 2;
+        assert!(loader.iter().next().unwrap().is_err());
+
+        let regression = TensorDataset::new(features.reshape(&[23, 4]).unwrap().map(|&x| x * 0.01), labels.reshape(&[23, 1]).unwrap().map(|&x| x * 0.04)).unwrap();
+        let mut loader = DataLoader::shared(Arc::new(regression), 4).unwrap();
+        loader.shuffle = true;
+        loader.num_workers = 2;
+        let model = Linear::<f64>::new(4, 1, true);
+        let mut sgd = SGD::new(model.parameters(), 0.1);
+        let mut trainer = Trainer::new(&model, &mut sgd, |output, target| mse_loss(output, target, None));
+        let history = trainer.fit(&loader, None, 20).unwrap();
+        assert!(history[19].get("loss").unwrap() < history[0].get("loss").unwrap());
+    }
+
+    #[test]
+    fn text_dataset_test() {
+        use data::{Dataset, TextDataset, TextFormat, TextLoader, TextOptions};
+        use text::{Tokenizer, WhitespaceTokenizer};
+
+        let corpus = ["pos\tThe movie was great", "neg\tboring", "pos\tgreat fun", "neg\tthe plot was thin and dull", "pos\tloved it"];
+        let texts: Vec<&str> = corpus.iter().map(|line| line.split_once('\t').unwrap().1).collect();
+        let mut tokenizer = WhitespaceTokenizer::new();
+        tokenizer.fit(&texts);
+        assert_eq!(tokenizer.id_to_token(2), Some("the"));
+        assert_eq!(tokenizer.encode("The GREAT unseen").unwrap(), vec![2, 5, tokenizer.unk_id()]);
+        assert_eq!(tokenizer.decode(&[2, 3, 0]).unwrap(), "the movie");
+
+        let mut options = TextOptions::new();
+        options.format = TextFormat::Lines { label_separator: Some('\t') };
+        options.max_length = Some(4);
+        let dataset = TextDataset::<f64>::from_reader(corpus.join("\n\n").as_bytes(), &tokenizer, &options).unwrap();
+        assert_eq!(dataset.len(), 5);
+        assert_eq!(dataset.lengths(), vec![4, 1, 2, 4, 2]);
+        assert_eq!(dataset.label_classes().unwrap(), &["neg".to_string(), "pos".to_string()]);
+        let (ids, label) = dataset.get(1).unwrap();
+        assert_eq!((ids.to_vec(), label.to_vec()), (vec![6.0], vec![0.0]));
+
+        let batch = dataset.collate(&[1, 2, 0]).unwrap();
+        assert_eq!(batch.ids.shape(), &[3, 4]);
+        assert_eq!(batch.ids.to_vec()[..8], [6.0, 0.0, 0.0, 0.0, 5.0, 7.0, 0.0, 0.0]);
+        assert_eq!(batch.attention_mask.to_vec()[..8], [1.0, 0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0]);
+        assert_eq!(batch.labels.to_vec(), vec![0.0, 1.0, 1.0]);
+        assert!(dataset.collate(&[5]).is_err());
+
+        let closure = |text: &str| -> Result<Vec<usize>, String> { Ok(text.bytes().map(usize::from).collect()) };
+        let bytes = TextDataset::<f64>::from_texts(&["ab", "c"], Some(&["1.5", "2"]), &closure, &TextOptions::new()).unwrap();
+        assert_eq!(bytes.labels().unwrap(), &[1.5, 2.0]);
+        assert!(bytes.label_classes().is_none());
+        assert!(TextDataset::<f64>::from_texts(&["a"], Some(&[]), &closure, &TextOptions::new()).is_err());
+
+        let unlabeled: Vec<String> = (0..20).map(|i| vec!["word"; 1 + i % 7].join(" ")).collect();
+        let dataset = TextDataset::<f64>::from_texts(&unlabeled, None, &tokenizer, &TextOptions::new()).unwrap();
+        let mut loader = TextLoader::new(&dataset, 4).unwrap();
+        loader.shuffle = true;
+        loader.seed = Some(2);
+        let padded = |loader: &TextLoader<f64>| -> (usize, Vec<f64>) {
+            let mut cells = 0;
+            let mut seen = Vec::new();
+            for batch in loader.iter() {
+                let batch = batch.unwrap();
+                cells += batch.ids.len();
+                assert_eq!(batch.labels.shape(), &[batch.ids.shape()[0], 0]);
+                seen.extend(batch.attention_mask.sum(&[1], false).unwrap().to_vec());
+            }
+            (cells, seen)
+        };
+        let (bucketed, lengths) = padded(&loader);
+        assert_eq!(lengths.iter().sum::<f64>(), dataset.lengths().iter().sum::<usize>() as f64);
+        loader.bucket_by_length = false;
+        let (unbucketed, _) = padded(&loader);
+        assert!(bucketed < unbucketed);
+        assert_eq!(loader.len(), 5);
+        loader.bucket_by_length = true;
+        loader.set_epoch(0);
+        assert_eq!(padded(&loader).1, lengths);
+
+        #[cfg(feature = "jsonl")]
+        {
+            let jsonl = "{\"text\": \"great fun\", \"label\": 1}\n{\"text\": \"boring\", \"label\": 0}\n";
+            let mut options = TextOptions::new();
+            options.format = TextFormat::Jsonl { text_field: "text".to_string(), label_field: Some("label".to_string()) };
+            let dataset = TextDataset::<f64>::from_reader(jsonl.as_bytes(), &tokenizer, &options).unwrap();
+            assert_eq!(dataset.labels().unwrap(), &[1.0, 0.0]);
+            assert_eq!(dataset.ids(0).unwrap(), &[5, 7]);
+            assert!(TextDataset::<f64>::from_reader("{\"body\": \"x\"}".as_bytes(), &tokenizer, &options).is_err());
+        }
+    }
+
+    #[test]
+    fn subword_tokenizer_test() {
+        use text::{Tokenizer, UnigramTokenizer, WordPieceTokenizer};
+
+        let vocab = "[PAD]\n[UNK]\n[CLS]\n[SEP]\nun\n##aff\n##able\nhello\n,\nworld\n!\n的\n";
+        let mut wordpiece = WordPieceTokenizer::read_vocab(vocab.as_bytes()).unwrap();
+        assert_eq!(wordpiece.vocab_size(), 12);
+        assert_eq!(wordpiece.tokenize("Unaffable, WORLD!"), vec!["[CLS]", "un", "##aff", "##able", ",", "world", "!", "[SEP]"]);
+        let ids = wordpiece.encode("hello 的的 xyz").unwrap();
+        assert_eq!(ids, vec![2, 7, 11, 11, 1, 3]);
+        assert_eq!(wordpiece.decode(&ids).unwrap(), "hello 的 的 [UNK]");
+        wordpiece.add_special_tokens = false;
+        wordpiece.lowercase = false;
+        assert_eq!(wordpiece.encode("Hello unaffable").unwrap(), vec![1, 4, 5, 6]);
+        assert_eq!(wordpiece.decode(&[4, 5, 6, 9]).unwrap(), "unaffable world");
+        assert!(WordPieceTokenizer::read_vocab("a\nb\n".as_bytes()).is_err());
+        assert!(WordPieceTokenizer::read_vocab("[UNK]\na\na\n".as_bytes()).is_err());
+
+        let pieces = "<unk>\t0\n<s>\t0\n</s>\t0\n\u{2581}\t-2.0\n\u{2581}hello\t-3.0\n\u{2581}he\t-2.5\nllo\t-2.5\n\u{2581}world\t-4.0\nw\t-3.0\no\t-3.0\nr\t-3.0\nl\t-3.0\nd\t-3.0\n";
+        let unigram = UnigramTokenizer::read_vocab(pieces.as_bytes()).unwrap();
+        assert_eq!(unigram.vocab_size(), 13);
+        assert_eq!(unigram.tokenize("hello  world"), vec!["\u{2581}hello", "\u{2581}world"]);
+        assert_eq!(unigram.tokenize("hello wordqz"), vec!["\u{2581}hello", "\u{2581}", "w", "o", "r", "d", "qz"]);
+        let ids = unigram.encode("hello wordqz").unwrap();
+        assert_eq!(ids, vec![4, 3, 8, 9, 10, 12, 0]);
+        assert_eq!(unigram.decode(&[1, 4, 7, 2]).unwrap(), "hello world");
+        assert!(UnigramTokenizer::read_vocab("<unk>\tx\n".as_bytes()).is_err());
+        assert!(UnigramTokenizer::read_vocab("a\t0\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn text_vectorizer_test() {
+        use math::matrix::Matrix;
+        use math::sparse::CsrMatrix;
+        use text::{CountVectorizer, TfidfVectorizer};
+
+        let sparse = CsrMatrix::<f64>::from_rows(3, vec![vec![(2, 1.0), (0, 2.0), (2, 0.5)], vec![], vec![(1, 4.0)]]).unwrap();
+        assert_eq!((sparse.nnz(), sparse.indptr()), (3, &[0, 2, 2, 3][..]));
+        assert_eq!(sparse.get(0, 2), 1.5);
+        assert_eq!(sparse.to_dense().data, vec![2.0, 0.0, 1.5, 0.0, 0.0, 0.0, 0.0, 4.0, 0.0]);
+        assert_eq!(sparse.matmul(&Matrix::from_vec(3, 1, vec![1.0, 1.0, 2.0]).unwrap()).unwrap().data, vec![5.0, 0.0, 4.0]);
+        assert!(CsrMatrix::<f64>::new(1, 2, vec![0, 1], vec![2], vec![1.0]).is_err());
+
+        let documents = ["The cat sat on the mat.", "The dog sat!", "A cat and a dog, a CAT."];
+        let mut counts = CountVectorizer::new();
+        let matrix: CsrMatrix<f64> = counts.fit_transform(&documents).unwrap();
+        assert_eq!(counts.feature_names(), vec!["and", "cat", "dog", "mat", "on", "sat", "the"]);
+        assert_eq!(matrix.to_dense().data[14..], [1.0, 2.0, 1.0, 0.0, 0.0, 0.0, 0.0]);
+        assert_eq!(counts.transform::<f64, _>(&["unseen words only"]).unwrap().data, vec![0.0; 7]);
+
+        counts.english_stop_words();
+        counts.ngram_range = (1, 2);
+        counts.min_df = 2;
+        counts.fit(&documents).unwrap();
+        assert_eq!(counts.feature_names(), vec!["cat", "dog", "sat"]);
+        counts.min_df = 1;
+        counts.max_features = Some(2);
+        counts.fit(&documents).unwrap();
+        assert_eq!(counts.feature_names(), vec!["cat", "dog"]);
+        counts.ngram_range = (2, 1);
+        assert!(counts.fit(&documents).is_err());
+        counts.binary = true;
+        counts.ngram_range = (2, 2);
+        counts.max_features = None;
+        counts.fit(&documents).unwrap();
+        assert!(counts.vocabulary().contains_key("cat dog"));
+        assert!(CountVectorizer::new().transform::<f64, _>(&documents).is_err());
+
+        let mut tfidf = TfidfVectorizer::new();
+        let weights = tfidf.fit_transform::<f64, _>(&documents).unwrap().to_dense();
+        let the = tfidf.vocabulary()["the"];
+        let mat = tfidf.vocabulary()["mat"];
+        assert!((tfidf.idf()[mat] - (4.0f64 / 2.0).ln() - 1.0).abs() < 1e-12);
+        assert!(weights.data[mat] > weights.data[the] / 2.0);
+        for row in 0..3 {
+            let norm: f64 = (0..weights.cols).map(|col| weights.data[row * weights.cols + col].powi(2)).sum();
+            assert!((norm - 1.0).abs() < 1e-12);
+        }
+        tfidf.l2_normalize = false;
+        tfidf.smooth_idf = false;
+        tfidf.fit(&documents).unwrap();
+        let raw = tfidf.transform::<f64, _>(&["the the mat"]).unwrap();
+        assert!((raw.data[the] - 2.0 * ((1.5f64).ln() + 1.0)).abs() < 1e-12);
+        assert!((raw.data[mat] - (3.0f64.ln() + 1.0)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn pretrained_embedding_test() {
+        use autograd::Var;
+        use nn::{Embedding, Module};
+        use onnx::OnnxModel;
+        use tensor::Tensor;
+        use text::{read_embeddings, EmbeddingFormat, EmbeddingOptions, OovInit};
+
+        let glove = "the 1.0 0.0 0.0\ncat 0.0 3.0 4.0\ndog 0.0 1.0 0.0\n";
+        let embeddings = read_embeddings::<f64, _>(glove.as_bytes(), EmbeddingFormat::Text, &EmbeddingOptions::new()).unwrap();
+        assert_eq!(embeddings.embedding.weight.shape(), vec![4, 3]);
+        assert_eq!(embeddings.row("cat"), Some(1));
+        assert_eq!(embeddings.row("zebra"), Some(3));
+        assert_eq!(embeddings.oov, vec!["<unk>".to_string()]);
+        assert_eq!(embeddings.vector("zebra").unwrap().to_vec(), vec![0.0; 3]);
+        assert_eq!(embeddings.encode(&["dog", "the", "emu"]).unwrap(), vec![2, 0, 3]);
+        assert!(!embeddings.embedding.weight.requires_grad());
+
+        let mut options = EmbeddingOptions::new();
+        options.vocab = Some(vec!["dog".to_string(), "cat".to_string(), "emu".to_string()]);
+        options.unk_token = None;
+        options.oov = OovInit::Mean;
+        options.normalize = true;
+        let word2vec = format!("3 3\n{}", glove);
+        let aligned = read_embeddings::<f64, _>(word2vec.as_bytes(), EmbeddingFormat::Text, &options).unwrap();
+        assert_eq!(aligned.vector("cat").unwrap().to_vec(), vec![0.0, 0.6, 0.8]);
+        assert_eq!(aligned.oov, vec!["emu".to_string()]);
+        let mean = aligned.vector("emu").unwrap().to_vec();
+        assert!((mean[1] - 1.6 / 3.0).abs() < 1e-12);
+        assert!(aligned.row("zebra").is_none());
+        assert!(aligned.encode(&["zebra"]).is_err());
+        assert!(read_embeddings::<f64, _>("a 1 2\nb 1\n".as_bytes(), EmbeddingFormat::Text, &options).is_err());
+
+        let mut binary = b"2 2\n".to_vec();
+        for (word, vector) in [("hi", [0.5f32, -1.0]), ("yo", [2.0, 0.25])] {
+            binary.extend(word.as_bytes());
+            binary.push(b' ');
+            vector.iter().for_each(|x| binary.extend(x.to_le_bytes()));
+            binary.push(b'\n');
+        }
+        let mut options = EmbeddingOptions::new();
+        options.freeze = false;
+        options.limit = Some(2);
+        let loaded = read_embeddings::<f64, _>(binary.as_slice(), EmbeddingFormat::Binary, &options).unwrap();
+        assert_eq!(loaded.vector("yo").unwrap().to_vec(), vec![2.0, 0.25]);
+        assert!(read_embeddings::<f64, _>(&binary[..20], EmbeddingFormat::Binary, &options).is_err());
+        let huge = format!("{} {}\nhi ", usize::MAX, usize::MAX / 2);
+        assert!(read_embeddings::<f64, _>(huge.as_bytes(), EmbeddingFormat::Binary, &options).err().unwrap().contains("too large"));
+        let oversized = format!("{} {}\nhi ", usize::MAX, 1usize << 40);
+        assert!(read_embeddings::<f64, _>(oversized.as_bytes(), EmbeddingFormat::Binary, &options).err().unwrap().contains("truncated"));
+
+        let embedding = loaded.embedding;
+        let ids = Var::constant(Tensor::from_vec(&[2, 2], vec![1.0, 0.0, 1.0, 2.0]).unwrap());
+        let output = embedding.forward(&ids).unwrap();
+        assert_eq!(output.shape(), vec![2, 2, 2]);
+        assert_eq!(output.value().to_vec()[..4], [2.0, 0.25, 0.5, -1.0]);
+        output.sum(&[0, 1, 2], false).unwrap().backward().unwrap();
+        assert_eq!(embedding.weight.grad().unwrap().to_vec(), vec![1.0, 1.0, 2.0, 2.0, 1.0, 1.0]);
+        assert!(embedding.forward(&Var::constant(Tensor::from_vec(&[1], vec![3.0]).unwrap())).is_err());
+
+        let mut padded = Embedding::<f64>::new(5, 4);
+        padded.padding_idx = Some(0);
+        let output = padded.predict(&Tensor::from_vec(&[3], vec![0.0, 4.0, 0.0]).unwrap()).unwrap();
+        assert_eq!(output.to_vec()[..4], [0.0; 4]);
+        assert!(Embedding::<f64>::from_pretrained(Tensor::zeros(&[3]), true).is_err());
+
+        let x = Tensor::from_vec(&[2, 3], vec![0.0, 4.0, 2.0, 1.0, 1.0, 3.0]).unwrap();
+        let imported = OnnxModel::<f64>::from_bytes(&onnx::to_model(&padded, &x).unwrap().encode()).unwrap();
+        let difference = imported.predict(&x).unwrap().zip_map(&padded.predict(&x).unwrap(), |a, b| (a - b).abs()).unwrap();
+        assert!(difference.iter().all(|&d| d < 1e-6));
+    }
+
+    #[test]
+    fn sequence_padding_test() {
+        use autograd::Var;
+        use nn::{key_padding_mask, pad_sequences, sequence_mask, MultiheadAttention, PackedSequence, PadOptions, PadSide};
+        use tensor::Tensor;
+
+        let seq = |values: &[f64]| Tensor::from_vec(&[values.len()], values.to_vec()).unwrap();
+        let sequences = vec![seq(&[1.0, 2.0, 3.0]), seq(&[4.0]), seq(&[5.0, 6.0])];
+        let (padded, lengths) = pad_sequences(&sequences, &PadOptions::new()).unwrap();
+        assert_eq!(padded.shape(), &[3, 3]);
+        assert_eq!(padded.to_vec(), vec![1.0, 2.0, 3.0, 4.0, 0.0, 0.0, 5.0, 6.0, 0.0]);
+        assert_eq!(lengths, vec![3, 1, 2]);
+
+        let mut options = PadOptions::new();
+        options.max_length = Some(2);
+        options.padding = PadSide::Pre;
+        options.truncating = PadSide::Pre;
+        options.value = -1.0;
+        let (padded, lengths) = pad_sequences(&sequences, &options).unwrap();
+        assert_eq!(padded.to_vec(), vec![2.0, 3.0, -1.0, 4.0, 5.0, 6.0]);
+        assert_eq!(lengths, vec![2, 1, 2]);
+        assert_eq!(sequence_mask::<f64>(&lengths, 2, PadSide::Pre).to_vec(), vec![1.0, 1.0, 0.0, 1.0, 1.0, 1.0]);
+        assert_eq!(sequence_mask::<f64>(&[1, 3], 3, PadSide::Post).to_vec(), vec![1.0, 0.0, 0.0, 1.0, 1.0, 1.0]);
+        assert!(pad_sequences(&[seq(&[1.0]), Tensor::zeros(&[1, 2])], &PadOptions::new()).is_err());
+        assert!(pad_sequences::<f64>(&[], &PadOptions::new()).is_err());
+
+        let features = vec![Tensor::from_fn(&[2, 4], |i| (i[0] * 4 + i[1]) as f64 * 0.1), Tensor::from_fn(&[4, 4], |i| (i[0] + i[1]) as f64 * -0.1)];
+        let (padded, lengths) = pad_sequences(&features, &PadOptions::new()).unwrap();
+        assert_eq!(padded.shape(), &[2, 4, 4]);
+        let attention = MultiheadAttention::<f64>::new(4, 2).unwrap();
+        let mask = key_padding_mask(&sequence_mask(&lengths, 4, PadSide::Post)).unwrap();
+        assert_eq!(mask.shape(), &[2, 1, 1, 4]);
+        let x = Var::constant(padded.clone());
+        let batched = attention.attend(&x, &x, &x, Some(&mask)).unwrap().value();
+        let alone = Var::constant(features[0].reshape(&[1, 2, 4]).unwrap());
+        let reference = attention.attend(&alone, &alone, &alone, None).unwrap().value();
+        let valid = batched.narrow(0, 0, 1).unwrap().narrow(1, 0, 2).unwrap();
+        assert!(valid.zip_map(&reference, |a, b| (a - b).abs()).unwrap().iter().all(|&d| d < 1e-12));
+        assert!(key_padding_mask(&Tensor::<f64>::zeros(&[3])).is_err());
+
+        let packed = PackedSequence::pack(&features).unwrap();
+        assert_eq!(packed.batch_sizes, vec![2, 2, 1, 1]);
+        assert_eq!(packed.sorted_indices, vec![1, 0]);
+        assert_eq!(packed.data.shape(), &[6, 4]);
+        assert_eq!(packed.lengths(), vec![2, 4]);
+        assert_eq!(packed.step(2).unwrap().to_vec(), features[1].narrow(0, 2, 1).unwrap().to_vec());
+        assert!(packed.step(4).is_err());
+        let (unpacked, unpacked_lengths) = packed.unpack(0.0).unwrap();
+        assert_eq!((unpacked, unpacked_lengths), (padded.clone(), lengths.clone()));
+        assert_eq!(PackedSequence::pack_padded(&padded, &lengths).unwrap(), packed);
+        assert!(PackedSequence::pack(&[seq(&[1.0]), seq(&[])]).is_err());
+    }
+
+    #[test]
+    fn audio_features_test() {
+        use audio::{mel_filterbank, mel_spectrogram, mfcc, read_wav, spectrogram, write_wav, AudioBuffer, MelOptions, SpectrogramOptions};
+        use math::fft::{fft, ifft, irfft, rfft, Complex};
+        use math::matrix::Matrix;
+
+        for n in [8, 12] {
+            let signal: Vec<f64> = (0..n).map(|i| ((i * 7) % 5) as f64 - 1.5).collect();
+            let mut buffer: Vec<Complex> = signal.iter().map(|&x| Complex::new(x, 0.0)).collect();
+            fft(&mut buffer);
+            for (k, bin) in buffer.iter().enumerate() {
+                let naive = signal.iter().enumerate().fold(Complex::default(), |acc, (t, &x)| {
+                    acc + Complex::from_polar(x, -2.0 * std::f64::consts::PI * (k * t) as f64 / n as f64)
+                });
+                assert!((*bin - naive).abs() < 1e-9);
+            }
+            ifft(&mut buffer);
+            assert!(buffer.iter().zip(&signal).all(|(x, &y)| (x.re - y).abs() < 1e-9 && x.im.abs() < 1e-9));
+            let restored = irfft(&rfft(&signal), n);
+            assert!(restored.iter().zip(&signal).all(|(x, y)| (x - y).abs() < 1e-9));
+        }
+
+        let rate = 8000;
+        let tone: Vec<f64> = (0..rate).map(|i| 0.5 * (2.0 * std::f64::consts::PI * 1000.0 * i as f64 / rate as f64).sin()).collect();
+        let audio = AudioBuffer::new(rate as u32, vec![tone.clone(), tone.iter().map(|x| -x).collect()]).unwrap();
+        assert_eq!(audio.duration(), 1.0);
+        assert!(audio.to_mono().iter().all(|&x| x == 0.0));
+        let mut bytes = Vec::new();
+        write_wav(&mut bytes, &audio).unwrap();
+        assert_eq!(bytes.len(), 44 + rate * 4);
+        let decoded = read_wav(bytes.as_slice()).unwrap();
+        assert_eq!((decoded.sample_rate, decoded.num_channels(), decoded.num_frames()), (8000, 2, 8000));
+        assert!(decoded.channels[0].iter().zip(&tone).all(|(a, b)| (a - b).abs() < 1e-4));
+        assert!(read_wav(&bytes[..30]).is_err());
+        assert!(AudioBuffer::new(8000, vec![vec![0.0], vec![]]).is_err());
+
+        let mut float_wav = b"RIFF\0\0\0\0WAVEfmt ".to_vec();
+        float_wav.extend(16u32.to_le_bytes());
+        for value in [3u16, 1] {
+            float_wav.extend(value.to_le_bytes());
+        }
+        float_wav.extend(16000u32.to_le_bytes());
+        float_wav.extend(64000u32.to_le_bytes());
+        float_wav.extend(4u16.to_le_bytes());
+        float_wav.extend(32u16.to_le_bytes());
+        float_wav.extend(b"data");
+        float_wav.extend(8u32.to_le_bytes());
+        float_wav.extend(0.25f32.to_le_bytes());
+        float_wav.extend((-1.0f32).to_le_bytes());
+        assert_eq!(read_wav(float_wav.as_slice()).unwrap().channels, vec![vec![0.25, -1.0]]);
+
+        let mut options = SpectrogramOptions::new();
+        options.n_fft = 256;
+        options.hop_length = 128;
+        let power: Matrix<f64> = spectrogram(&tone, &options).unwrap();
+        assert_eq!((power.rows, power.cols), (1 + rate / 128, 129));
+        let frame = &power.data[10 * 129..11 * 129];
+        let peak = (0..129).max_by(|&a, &b| frame[a].total_cmp(&frame[b])).unwrap();
+        assert_eq!(peak, 32);
+        options.win_length = Some(512);
+        assert!(spectrogram::<f64>(&tone, &options).is_err());
+
+        let filters: Matrix<f64> = mel_filterbank(10, 256, 8000, 0.0, 4000.0).unwrap();
+        assert_eq!((filters.rows, filters.cols), (10, 129));
+        assert!((0..10).all(|mel| filters.data[mel * 129..(mel + 1) * 129].iter().any(|&w| w > 0.5)));
+        assert!(mel_filterbank::<f64>(10, 256, 8000, 0.0, 5000.0).is_err());
+
+        let mut mel_options = MelOptions::new();
+        mel_options.spectrogram.n_fft = 256;
+        mel_options.spectrogram.hop_length = 128;
+        mel_options.n_mels = 20;
+        let mel: Matrix<f64> = mel_spectrogram(&tone, 8000, &mel_options).unwrap();
+        assert_eq!((mel.rows, mel.cols), (63, 20));
+        let coefficients: Matrix<f32> = mfcc(&tone, 8000, 13, &mel_options).unwrap();
+        assert_eq!((coefficients.rows, coefficients.cols), (63, 13));
+        assert!(coefficients.data.iter().all(|x| x.is_finite()));
+        assert!(mfcc::<f64>(&tone, 8000, 21, &mel_options).is_err());
+    }
+
+    #[test]
+    fn dataset_cache_test() {
+        use data::{fingerprint, materialize, DatasetCache, Dataset, TextDataset, TextFormat, TextOptions};
+        use std::cell::Cell;
+        use text::WhitespaceTokenizer;
+
+        let dir = std::env::temp_dir().join("oxidizeai_dataset_cache_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        let corpus = dir.join("corpus.txt");
+        let cache = DatasetCache::new(dir.join("cache")).unwrap();
+        std::fs::write(&corpus, "1\tgood movie\n0\tbad\n1\tgreat fun film\n").unwrap();
+
+        let mut tokenizer = WhitespaceTokenizer::new();
+        tokenizer.fit(&["good movie", "bad", "great fun film"]);
+        let mut options = TextOptions::new();
+        options.format = TextFormat::Lines { label_separator: Some('\t') };
+        let builds = Cell::new(0);
+        let build = |options: &TextOptions| {
+            builds.set(builds.get() + 1);
+            materialize(&TextDataset::<f32>::from_path(&corpus, &tokenizer, options)?)
+        };
+
+        let key = fingerprint(&[&corpus], &format!("{:?}", options)).unwrap();
+        assert_eq!(key.len(), 16);
+        assert!(!cache.contains(&key));
+        let first = cache.get_or_insert_with(&key, || build(&options)).unwrap();
+        assert_eq!(first.features().shape(), &[3, 3]);
+        assert_eq!(first.labels().to_vec(), vec![1.0, 0.0, 1.0]);
+        let second = cache.get_or_insert_with(&key, || build(&options)).unwrap();
+        assert_eq!(builds.get(), 1);
+        assert_eq!((second.features(), second.labels()), (first.features(), first.labels()));
+        assert_eq!(second.get(1).unwrap().0.to_vec(), vec![4.0, 0.0, 0.0]);
+        assert!(cache.load::<f32>(&key).unwrap().is_some());
+
+        options.max_length = Some(2);
+        let truncated_key = fingerprint(&[&corpus], &format!("{:?}", options)).unwrap();
+        assert_ne!(truncated_key, key);
+        assert_eq!(cache.get_or_insert_with(&truncated_key, || build(&options)).unwrap().features().shape(), &[3, 2]);
+        assert_eq!(builds.get(), 2);
+
+        std::fs::write(&corpus, "0\tbad film\n").unwrap();
+        assert_ne!(fingerprint(&[&corpus], &format!("{:?}", options)).unwrap(), truncated_key);
+        assert!(fingerprint(&[dir.join("missing.txt")], "").is_err());
+
+        assert!(cache.remove(&key).unwrap());
+        assert!(!cache.remove(&key).unwrap());
+        assert!(cache.load::<f32>(&key).unwrap().is_none());
+        assert_eq!(cache.clear().unwrap(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn linear_regression_test() {
+        use math::linalg::{cholesky, cholesky_solve, lstsq, qr};
+        use math::matrix::Matrix;
+        use math::vector::Vector;
+        use ml::{LinearRegression, LinearSolver, Regressor};
+
+        let a = Matrix::from_vec(3, 2, vec![1.0, 2.0, 3.0, 4.0, 5.0, 7.0]).unwrap();
+        let (q, r) = qr(&a).unwrap();
+        let product = Matrix::from_fn(3, 2, |i, j| (0..2).map(|k| q[(i, k)] * r[(k, j)]).sum::<f64>());
+        assert!(product.approx_eq(&a, 1e-12));
+        let qtq = Matrix::from_fn(2, 2, |i, j| (0..3).map(|k| q[(k, i)] * q[(k, j)]).sum::<f64>());
+        assert!(qtq.approx_eq(&Matrix::identity(2), 1e-12));
+        assert_eq!(r[(1, 0)], 0.0);
+        let spd = Matrix::from_vec(2, 2, vec![4.0, 2.0, 2.0, 3.0]).unwrap();
+        let l = cholesky(&spd).unwrap();
+        assert_eq!((l[(0, 0)], l[(1, 0)], l[(0, 1)]), (2.0, 1.0, 0.0));
+        let x: Vec<f64> = cholesky_solve(&spd, &[2.0, 5.0]).unwrap();
+        assert!((x[0] + 0.5).abs() < 1e-12 && (x[1] - 2.0).abs() < 1e-12);
+        assert!(cholesky(&Matrix::from_vec(2, 2, vec![1.0, 2.0, 2.0, 1.0]).unwrap()).is_err());
+        assert!(lstsq(&Matrix::from_vec(3, 2, vec![1.0, 2.0, 2.0, 4.0, 3.0, 6.0]).unwrap(), &[1.0, 2.0, 3.0]).is_err());
+
+        let x = Matrix::from_fn(20, 2, |i, j| if j == 0 { i as f64 * 0.1 } else { ((i * 7) % 5) as f64 * 0.2 });
+        let y = Vector::from((0..20).map(|i| 1.5 + 2.0 * x[(i, 0)] - 3.0 * x[(i, 1)]).collect::<Vec<f64>>());
+        for solver in [LinearSolver::Qr, LinearSolver::Cholesky, LinearSolver::GradientDescent] {
+            let mut model = LinearRegression::new();
+            model.solver = solver;
+            model.learning_rate = 0.3;
+            model.max_iter = 20_000;
+            model.tol = 1e-12;
+            model.fit(&x, &y).unwrap();
+            let coefficients = model.coefficients().unwrap();
+            assert!((coefficients[0] - 2.0).abs() < 1e-6 && (coefficients[1] + 3.0).abs() < 1e-6, "{:?}", solver);
+            assert!((model.intercept() - 1.5).abs() < 1e-6);
+            assert!(model.score(&x, &y).unwrap() > 1.0 - 1e-9);
+        }
+
+        let mut model = LinearRegression::new();
+        assert!(model.predict(&x).is_err());
+        model.fit_intercept = false;
+        let x32: Matrix<f32> = Matrix::from_vec(3, 1, vec![1.0, 2.0, 3.0]).unwrap();
+        model.fit(&x32, &Vector::from(vec![2.0f32, 4.1, 5.9])).unwrap();
+        assert_eq!(model.intercept(), 0.0);
+        let predicted = model.predict(&x32).unwrap();
+        assert!((predicted.data[1] - 2.0 * model.coefficients().unwrap()[0] as f32).abs() < 1e-6);
+        assert!(model.score(&x32, &Vector::from(vec![2.0, 4.1, 5.9])).unwrap() > 0.99);
+        assert!(model.predict(&x).is_err());
+        assert!(model.fit(&x32, &Vector::from(vec![1.0f32])).is_err());
+        let collinear = Matrix::from_fn(4, 2, |i, j| (i * (j + 1)) as f64);
+        assert!(LinearRegression::new().fit(&collinear, &Vector::from(vec![0.0, 1.0, 2.0, 3.0])).is_err());
+    }
+
+    #[test]
+    fn regularized_regression_test() {
+        use math::matrix::Matrix;
+        use math::vector::Vector;
+        use ml::{alpha_grid, enet_path, ElasticNet, ElasticNetCV, Lasso, LinearRegression, Regressor, Ridge, RidgeCV};
+
+        let x = Matrix::from_fn(30, 3, |i, j| match j {
+            0 => i as f64 * 0.1,
+            1 => ((i * 7) % 5) as f64 * 0.2,
+            _ => ((i * 11) % 13) as f64 * 0.05
+        });
+        let y = Vector::from((0..30).map(|i| 1.0 + 2.0 * x[(i, 0)] - 1.5 * x[(i, 1)]).collect::<Vec<f64>>());
+
+        let mut ols = LinearRegression::new();
+        ols.fit(&x, &y).unwrap();
+        let mut ridge = Ridge::new(0.0);
+        ridge.fit(&x, &y).unwrap();
+        for (a, b) in ridge.coefficients().unwrap().iter().zip(ols.coefficients().unwrap()) {
+            assert!((a - b).abs() < 1e-8);
+        }
+        ridge.alpha = 10.0;
+        ridge.fit(&x, &y).unwrap();
+        let norm = |c: &[f64]| c.iter().map(|v| v * v).sum::<f64>();
+        assert!(norm(ridge.coefficients().unwrap()) < norm(ols.coefficients().unwrap()));
+
+        let mut lasso = Lasso::new(0.02);
+        lasso.tol = 1e-10;
+        lasso.fit(&x, &y).unwrap();
+        let coefficients = lasso.coefficients().unwrap();
+        assert_eq!(coefficients[2], 0.0);
+        assert!(coefficients[0] > 1.5 && coefficients[1] < -1.0, "{:?}", coefficients);
+        assert!(lasso.score(&x, &y).unwrap() > 0.95);
+
+        let mut enet = ElasticNet::new(1e-9, 0.5);
+        enet.tol = 1e-12;
+        enet.max_iter = 100_000;
+        enet.fit(&x, &y).unwrap();
+        assert!((enet.coefficients().unwrap()[0] - 2.0).abs() < 1e-4 && (enet.intercept() - 1.0).abs() < 1e-4);
+        assert!(ElasticNet::new(1.0, 1.5).fit(&x, &y).is_err());
+        assert!(Ridge::new(-1.0).fit(&x, &y).is_err());
+
+        let alphas = alpha_grid(&x, &y, 1.0, 10, true).unwrap();
+        assert_eq!(alphas.len(), 10);
+        let path = enet_path(&x, &y, 1.0, &alphas, true).unwrap();
+        assert!(path.coefficients[0].iter().all(|&c| c.abs() < 1e-12));
+        let active = |c: &Vec<f64>| c.iter().filter(|v| v.abs() > 0.0).count();
+        assert!(active(&path.coefficients[9]) >= active(&path.coefficients[5]));
+        assert!(active(&path.coefficients[9]) >= 2);
+
+        let mut cv = ElasticNetCV::new(1.0);
+        cv.n_alphas = 20;
+        cv.folds = 3;
+        cv.fit(&x, &y).unwrap();
+        assert_eq!(cv.mse().len(), 20);
+        assert!(cv.alpha().unwrap() < alphas[0] * 0.1);
+        assert!(cv.score(&x, &y).unwrap() > 0.99);
+
+        let mut ridge_cv = RidgeCV::new(vec![100.0, 1.0, 1e-4]);
+        ridge_cv.fit(&x, &y).unwrap();
+        assert_eq!(ridge_cv.alpha(), Some(1e-4));
+        assert!(ridge_cv.predict(&x).unwrap().data.len() == 30);
+    }
+
+    #[test]
+    fn logistic_regression_test() {
+        use math::matrix::Matrix;
+        use math::optimize::lbfgs;
+        use ml::{ClassWeight, Classifier, LogisticRegression, LogisticSolver};
+        use std::collections::BTreeMap;
+
+        let rosenbrock = |w: &[f64]| {
+            let (a, b) = (w[0], w[1]);
+            ((1.0 - a).powi(2) + 100.0 * (b - a * a).powi(2), vec![-2.0 * (1.0 - a) - 400.0 * a * (b - a * a), 200.0 * (b - a * a)])
+        };
+        let minimum = lbfgs(rosenbrock, vec![-1.2, 1.0], 10, 500, 1e-8);
+        assert!(minimum.converged);
+        assert!((minimum.x[0] - 1.0).abs() < 1e-5 && (minimum.x[1] - 1.0).abs() < 1e-5);
+
+        let x = Matrix::from_fn(40, 2, |i, j| {
+            let t = (i % 20) as f64 * 0.1;
+            if j == 0 { t } else { ((i * 7) % 11) as f64 * 0.1 }
+        });
+        let y: Vec<usize> = (0..40).map(|i| if x[(i, 0)] + 0.5 * x[(i, 1)] > 1.2 { 7 } else { 3 }).collect();
+        let mut lbfgs_model = LogisticRegression::new();
+        lbfgs_model.fit(&x, &y).unwrap();
+        assert_eq!(lbfgs_model.classes(), &[3, 7]);
+        assert!(lbfgs_model.score(&x, &y).unwrap() > 0.95);
+        let proba = lbfgs_model.predict_proba(&x).unwrap();
+        assert_eq!((proba.rows, proba.cols), (40, 2));
+        assert!((0..40).all(|i| (proba[(i, 0)] + proba[(i, 1)] - 1.0).abs() < 1e-12));
+
+        let mut gd = LogisticRegression::new();
+        gd.solver = LogisticSolver::GradientDescent;
+        gd.alpha = 0.1;
+        gd.learning_rate = 1.0;
+        gd.max_iter = 20_000;
+        gd.tol = 1e-9;
+        gd.fit(&x, &y).unwrap();
+        let mut reference = LogisticRegression::new();
+        reference.alpha = 0.1;
+        reference.tol = 1e-9;
+        reference.fit(&x, &y).unwrap();
+        assert!(gd.coefficients().unwrap().approx_eq(reference.coefficients().unwrap(), 1e-5));
+        assert!((gd.intercepts()[0] - reference.intercepts()[0]).abs() < 1e-5);
+        let mut strong = reference.clone();
+        strong.alpha = 10.0;
+        strong.fit(&x, &y).unwrap();
+        assert!(strong.coefficients().unwrap()[(0, 0)].abs() < reference.coefficients().unwrap()[(0, 0)].abs());
+
+        let centers = [(0.0, 0.0), (3.0, 0.0), (0.0, 3.0)];
+        let x3 = Matrix::from_fn(30, 2, |i, j| {
+            let (cx, cy) = centers[i % 3];
+            let jitter = ((i * 13) % 7) as f64 * 0.1 - 0.3;
+            if j == 0 { cx + jitter } else { cy - jitter }
+        });
+        let y3: Vec<usize> = (0..30).map(|i| i % 3).collect();
+        let mut multi = LogisticRegression::new();
+        multi.fit(&x3, &y3).unwrap();
+        assert_eq!(multi.coefficients().unwrap().rows, 3);
+        assert_eq!(multi.predict(&x3).unwrap(), y3);
+        let proba = multi.predict_proba(&Matrix::from_vec(1, 2, vec![3.0f32, 0.0]).unwrap()).unwrap();
+        assert!(proba[(0, 1)] > 0.9 && ((proba.data.iter().sum::<f32>()) - 1.0).abs() < 1e-5);
+
+        let imbalanced: Vec<usize> = (0..40).map(|i| (x[(i, 0)] > 1.7) as usize).collect();
+        let mut plain = LogisticRegression::new();
+        plain.alpha = 1.0;
+        plain.fit(&x, &imbalanced).unwrap();
+        let mut balanced = plain.clone();
+        balanced.class_weight = ClassWeight::Balanced;
+        balanced.fit(&x, &imbalanced).unwrap();
+        let positive = |model: &LogisticRegression| model.predict(&x).unwrap().iter().filter(|&&c| c == 1).count();
+        assert!(positive(&balanced) > positive(&plain));
+        let mut custom = plain.clone();
+        custom.class_weight = ClassWeight::Custom(BTreeMap::from([(1, 10.0)]));
+        custom.fit(&x, &imbalanced).unwrap();
+        assert!(positive(&custom) > positive(&plain));
+
+        assert!(LogisticRegression::new().fit(&x, &[1; 40]).is_err());
+        assert!(LogisticRegression::new().predict(&x).is_err());
+        assert!(multi.predict_proba(&Matrix::<f64>::from_vec(1, 3, vec![0.0; 3]).unwrap()).is_err());
+    }
+
+    #[test]
+    fn kmeans_test() {
+        use math::matrix::Matrix;
+        use ml::{KMeans, KMeansInit};
+
+        let centers = [(0.0, 0.0), (5.0, 5.0), (0.0, 6.0)];
+        let x = Matrix::from_fn(60, 2, |i, j| {
+            let (cx, cy) = centers[i % 3];
+            let jitter = ((i * 17) % 9) as f64 * 0.1 - 0.4;
+            if j == 0 { cx + jitter } else { cy + 0.5 * jitter }
+        });
+        let same_partition = |labels: &[usize]| (0..60).all(|i| (0..60).all(|j| (labels[i] == labels[j]) == (i % 3 == j % 3)));
+
+        let mut model = KMeans::new(3);
+        model.seed = Some(7);
+        let labels = model.fit_predict(&x).unwrap();
+        assert!(same_partition(&labels));
+        assert_eq!(model.predict(&x).unwrap(), labels);
+        let fitted = model.centers().unwrap();
+        assert_eq!((fitted.rows, fitted.cols), (3, 2));
+        let expected_inertia: f64 = (0..60).map(|i| (0..2).map(|j| (x[(i, j)] - fitted[(labels[i], j)]).powi(2)).sum::<f64>()).sum();
+        assert!((model.inertia() - expected_inertia).abs() < 1e-9);
+        let distances = model.transform(&x).unwrap();
+        assert!((0..60).all(|i| (0..3).all(|c| distances[(i, c)] >= distances[(i, labels[i])])));
+
+        let mut again = KMeans::new(3);
+        again.seed = Some(7);
+        again.fit(&x).unwrap();
+        assert_eq!(again.labels(), model.labels());
+
+        let mut single = KMeans::new(3);
+        single.seed = Some(1);
+        single.init = KMeansInit::Random;
+        single.n_init = 1;
+        single.fit(&x).unwrap();
+        assert!(single.inertia() >= model.inertia() - 1e-9);
+
+        let mut mini = KMeans::new(3);
+        mini.seed = Some(3);
+        mini.batch_size = Some(16);
+        mini.max_iter = 100;
+        mini.fit(&x).unwrap();
+        assert!(same_partition(mini.labels()));
+        assert!(mini.inertia() < model.inertia() * 1.5);
+
+        assert!(KMeans::new(61).fit(&x).is_err());
+        assert!(KMeans::new(2).predict(&x).is_err());
+        assert!(model.predict(&Matrix::<f64>::from_vec(1, 3, vec![0.0; 3]).unwrap()).is_err());
+    }
+
+    #[test]
+    fn dbscan_test() {
+        use math::matrix::Matrix;
+        use ml::{KdTree, NeighborSearch, DBSCAN};
+
+        let mut points = Vec::new();
+        for i in 0..40 {
+            let angle = i as f64 * std::f64::consts::TAU / 40.0;
+            points.extend([5.0 * angle.cos(), 5.0 * angle.sin()]);
+        }
+        for i in 0..10 {
+            points.extend([(i % 5) as f64 * 0.2 - 0.4, (i / 5) as f64 * 0.2]);
+        }
+        points.extend([20.0, 20.0]);
+        let x = Matrix::from_vec(51, 2, points).unwrap();
+
+        let tree = KdTree::new(x.clone(), 4);
+        for i in [0, 17, 45, 50] {
+            let brute: Vec<usize> = (0..51).filter(|&j| (0..2).map(|k| (x[(i, k)] - x[(j, k)]).powi(2)).sum::<f64>() <= 1.0).collect();
+            assert_eq!(tree.within_radius(tree.point(i), 1.0), brute);
+        }
+
+        let mut model = DBSCAN::new(1.0, 3);
+        let labels = model.fit_predict(&x).unwrap();
+        assert_eq!(model.n_clusters(), 2);
+        assert_eq!(model.n_noise(), 1);
+        assert_eq!(labels[50], None);
+        assert!(labels[..40].iter().all(|&label| label == labels[0]));
+        assert!(labels[40..50].iter().all(|&label| label == labels[40] && label != labels[0]));
+        assert_eq!(model.core_samples().len(), 50);
+
+        let mut brute = DBSCAN::new(1.0, 3);
+        brute.search = NeighborSearch::BruteForce;
+        assert_eq!(brute.fit_predict(&x).unwrap(), labels);
+        let mut strict = DBSCAN::new(0.3, 3);
+        strict.search = NeighborSearch::KdTree;
+        strict.fit(&x).unwrap();
+        assert_eq!(strict.n_clusters(), 1);
+        assert_eq!(strict.n_noise(), 41);
+        assert!(DBSCAN::new(0.0, 3).fit(&x).is_err());
+    }
+
+    #[test]
+    fn gaussian_mixture_test() {
+        use math::matrix::Matrix;
+        use ml::{CovarianceType, GaussianMixture};
+        use random::Rng;
+
+        let mut rng = Rng::seed_from_u64(11);
+        let x = Matrix::from_fn(300, 2, |i, j| {
+            let center = if i % 3 == 0 { [6.0, 6.0] } else { [0.0, 0.0] };
+            center[j] + rng.normal(0.0, if j == 0 { 1.0 } else { 0.5 })
+        });
+
+        let mut model = GaussianMixture::new(2);
+        model.seed = Some(5);
+        model.tol = 1e-6;
+        let labels = model.fit_predict(&x).unwrap();
+        assert!(model.converged());
+        let history = model.log_likelihood_history();
+        assert!(history.windows(2).all(|w| w[1] >= w[0] - 1e-9));
+        let small = (0..2).min_by(|&a, &b| model.weights()[a].total_cmp(&model.weights()[b])).unwrap();
+        assert!((model.weights()[small] - 1.0 / 3.0).abs() < 0.02);
+        let means = model.means().unwrap();
+        assert!((means[(small, 0)] - 6.0).abs() < 0.3 && (means[(small, 1)] - 6.0).abs() < 0.3);
+        let covariance = &model.covariances()[1 - small];
+        assert!((covariance[(0, 0)] - 1.0).abs() < 0.3 && (covariance[(1, 1)] - 0.25).abs() < 0.1);
+        assert!((0..300).all(|i| (labels[i] == small) == (i % 3 == 0)));
+
+        let proba = model.predict_proba(&x).unwrap();
+        assert!((0..300).all(|i| (proba[(i, 0)] + proba[(i, 1)] - 1.0).abs() < 1e-9));
+        let score = model.score(&x).unwrap();
+        assert!((score - history[history.len() - 1]).abs() < 1e-4);
+
+        let mut diag = GaussianMixture::new(2);
+        diag.covariance_type = CovarianceType::Diag;
+        diag.seed = Some(5);
+        diag.n_init = 3;
+        diag.fit(&x).unwrap();
+        assert!(diag.covariances().iter().all(|c| c[(0, 1)] == 0.0 && c[(1, 0)] == 0.0));
+        assert_eq!(diag.predict(&x).unwrap().iter().filter(|&&c| c == diag.predict(&x).unwrap()[0]).count(), 100);
+
+        let (samples, components) = model.sample::<f64>(3000, &mut Rng::seed_from_u64(2)).unwrap();
+        assert_eq!((samples.rows, samples.cols), (3000, 2));
+        let fraction = components.iter().filter(|&&c| c == small).count() as f64 / 3000.0;
+        assert!((fraction - model.weights()[small]).abs() < 0.03);
+        let sampled_mean = (0..3000).filter(|&i| components[i] == small).map(|i| samples[(i, 0)]).sum::<f64>() / (fraction * 3000.0);
+        assert!((sampled_mean - means[(small, 0)]).abs() < 0.1);
+
+        assert!(GaussianMixture::new(2).predict(&x).is_err());
+        assert!(GaussianMixture::new(301).fit(&x).is_err());
+    }
+
+    #[test]
+    fn tsne_test() {
+        use math::matrix::Matrix;
+        use ml::TSNE;
+        use random::Rng;
+
+        let mut rng = Rng::seed_from_u64(4);
+        let x = Matrix::from_fn(90, 10, |i, j| if j == i % 3 { 10.0 } else { 0.0 } + rng.normal(0.0, 0.5));
+        let mut tsne = TSNE::new(2);
+        tsne.perplexity = 10.0;
+        tsne.max_iter = 400;
+        tsne.seed = Some(1);
+        let embedded = tsne.fit_transform(&x).unwrap();
+        assert_eq!((embedded.rows, embedded.cols), (90, 2));
+        assert!(tsne.kl_divergence() > 0.0 && tsne.kl_divergence() < 2.0);
+
+        let distance = |a: usize, b: usize| (embedded[(a, 0)] - embedded[(b, 0)]).powi(2) + (embedded[(a, 1)] - embedded[(b, 1)]).powi(2);
+        for i in 0..90 {
+            let mut others: Vec<usize> = (0..90).filter(|&j| j != i).collect();
+            others.sort_by(|&a, &b| distance(i, a).total_cmp(&distance(i, b)));
+            assert!(others[..5].iter().all(|&j| j % 3 == i % 3), "{}", i);
+        }
+        let spread = (0..90).map(|i| distance(i, (i + 3) % 90)).fold(0.0, f64::max);
+        assert!((0..90).all(|i| distance(i, (i + 1) % 90) > spread * 0.1));
+
+        let mut exact = tsne.clone();
+        exact.theta = 0.0;
+        exact.n_components = 3;
+        exact.max_iter = 50;
+        assert_eq!(exact.fit_transform(&x).unwrap().cols, 3);
+        tsne.perplexity = 100.0;
+        assert!(tsne.fit_transform(&x).is_err());
+        assert!(TSNE::new(4).fit_transform(&x).is_err());
+        let mut single = TSNE::new(2);
+        single.perplexity = 0.75;
+        assert!(single.fit_transform(&Matrix::from_vec(1, 2, vec![1.0, 2.0]).unwrap()).unwrap_err().contains("at least 2"));
+    }
+
+    #[test]
+    fn decision_tree_test() {
+        use math::matrix::Matrix;
+        use math::vector::Vector;
+        use ml::{Classifier, Criterion, DecisionTreeClassifier, DecisionTreeRegressor, Regressor};
+
+        let x = Matrix::from_fn(40, 3, |i, j| match j {
+            0 => (i % 10) as f64,
+            1 => (i / 10) as f64,
+            _ => ((i * 7) % 13) as f64
+        });
+        let y: Vec<usize> = (0..40).map(|i| if x[(i, 0)] < 5.0 { 0 } else if x[(i, 1)] < 2.0 { 4 } else { 9 }).collect();
+        for criterion in [Criterion::Gini, Criterion::Entropy] {
+            let mut model = DecisionTreeClassifier::new();
+            model.criterion = criterion;
+            model.fit(&x, &y).unwrap();
+            assert_eq!(model.predict(&x).unwrap(), y);
+            let tree = model.tree().unwrap();
+            assert_eq!((tree.depth(), tree.n_leaves()), (2, 3));
+            assert_eq!(tree.root().split.unwrap().feature, 0);
+            assert!((tree.root().split.unwrap().threshold - 4.5).abs() < 1e-12);
+            let importances = model.feature_importances().unwrap();
+            assert!(importances[2] == 0.0 && (importances.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+        }
+
+        let mut model = DecisionTreeClassifier::new();
+        model.fit(&x, &y).unwrap();
+        let tree = model.tree().unwrap();
+        let path = tree.decision_path(&[7.0, 3.0, 0.0]);
+        assert_eq!(path.len(), 3);
+        assert!(tree.nodes[path[2]].is_leaf() && tree.nodes[path[2]].samples == 10);
+        let text = model.export_text(&["a", "b"]).unwrap();
+        assert!(text.starts_with("|--- a <= 4.5000\n|   |--- class: 0\n|--- a >  4.5000\n|   |--- b <= 1.5000\n"));
+        assert!(text.contains("|   |   |--- class: 9"));
+
+        let mut stump = DecisionTreeClassifier::new();
+        stump.max_depth = Some(1);
+        stump.fit(&x, &y).unwrap();
+        let proba = stump.predict_proba(&x).unwrap();
+        assert_eq!(stump.classes(), &[0, 4, 9]);
+        assert!((proba[(9, 1)] - 0.5).abs() < 1e-12 && (proba[(9, 2)] - 0.5).abs() < 1e-12);
+        let mut leafy = DecisionTreeClassifier::new();
+        leafy.min_samples_leaf = 15;
+        leafy.fit(&x, &y).unwrap();
+        assert!(leafy.tree().unwrap().nodes.iter().all(|node| node.samples >= 15));
+
+        let target = Vector::from((0..40).map(|i| if x[(i, 2)] > 6.0 { 3.0 } else { -1.0 } + 0.1 * x[(i, 1)]).collect::<Vec<f64>>());
+        let mut regressor = DecisionTreeRegressor::new();
+        regressor.max_depth = Some(1);
+        regressor.fit(&x, &target).unwrap();
+        let root = regressor.tree().unwrap().root();
+        assert_eq!(root.split.unwrap().feature, 2);
+        assert!((root.value[0] - target.data.iter().sum::<f64>() / 40.0).abs() < 1e-12);
+        let mut deep = DecisionTreeRegressor::new();
+        deep.fit(&x, &target).unwrap();
+        assert!(deep.score(&x, &target).unwrap() > 1.0 - 1e-12);
+        assert!(deep.export_text::<&str>(&[]).unwrap().contains("feature_2 <= 6.5000"));
+
+        let mut bad = DecisionTreeClassifier::new();
+        bad.criterion = Criterion::Mse;
+        assert!(bad.fit(&x, &y).is_err());
+        assert!(DecisionTreeRegressor::new().predict(&x).is_err());
+    }
+
+    #[test]
+    fn naive_bayes_test() {
+        use math::matrix::Matrix;
+        use ml::{BernoulliNB, Classifier, GaussianNB, MultinomialNB};
+
+        let x: Matrix<f64> = Matrix::from_vec(6, 2, vec![1.0, 2.0, 1.2, 1.8, 0.8, 2.2, 5.0, 8.0, 5.5, 7.5, 4.5, 8.5]).unwrap();
+        let y = [2, 2, 2, 5, 5, 5];
+        let mut gaussian = GaussianNB::new();
+        gaussian.fit(&x, &y).unwrap();
+        assert_eq!(gaussian.predict(&x).unwrap(), y);
+        let means = gaussian.means().unwrap();
+        assert!((means[(0, 0)] - 1.0).abs() < 1e-12 && (means[(1, 1)] - 8.0).abs() < 1e-12);
+        let variances = gaussian.variances().unwrap();
+        assert!((variances[(0, 0)] - 0.08 / 3.0).abs() < 1e-6);
+        assert!((gaussian.class_log_prior()[0] - 0.5f64.ln()).abs() < 1e-12);
+        let far = Matrix::from_vec(1, 2, vec![500.0f64, -400.0]).unwrap();
+        let log_proba = gaussian.predict_log_proba(&far).unwrap();
+        assert!(log_proba.data.iter().all(|v| v.is_finite()));
+        let proba = gaussian.predict_proba(&x).unwrap();
+        assert!((0..6).all(|i| (proba[(i, 0)] + proba[(i, 1)] - 1.0).abs() < 1e-12));
+        gaussian.priors = Some(vec![0.5, 0.6]);
+        assert!(gaussian.fit(&x, &y).is_err());
+
+        let counts = Matrix::from_vec(4, 3, vec![3.0, 0.0, 1.0, 4.0, 1.0, 0.0, 0.0, 2.0, 3.0, 1.0, 3.0, 4.0]).unwrap();
+        let labels = [0, 0, 1, 1];
+        let mut multinomial = MultinomialNB::new();
+        multinomial.fit(&counts, &labels).unwrap();
+        let log_prob = multinomial.feature_log_prob().unwrap();
+        assert!((log_prob[(0, 0)] - (8.0f64 / 12.0).ln()).abs() < 1e-12);
+        assert!((log_prob[(1, 2)] - (8.0f64 / 16.0).ln()).abs() < 1e-12);
+        assert_eq!(multinomial.predict(&counts).unwrap(), labels);
+        let long = Matrix::from_vec(1, 3, vec![2000.0f64, 0.0, 1000.0]).unwrap();
+        assert_eq!(multinomial.predict(&long).unwrap(), vec![0]);
+        assert!(multinomial.predict_proba(&long).unwrap().data.iter().all(|v| v.is_finite()));
+        assert!(multinomial.fit(&Matrix::from_vec(2, 1, vec![-1.0, 1.0]).unwrap(), &[0, 1]).is_err());
+
+        let mut bernoulli = BernoulliNB::new();
+        bernoulli.fit(&counts, &labels).unwrap();
+        let log_prob = bernoulli.feature_log_prob().unwrap();
+        assert!((log_prob[(0, 1)] - (2.0f64 / 4.0).ln()).abs() < 1e-12);
+        assert!((log_prob[(1, 2)] - (3.0f64 / 4.0).ln()).abs() < 1e-12);
+        assert_eq!(bernoulli.predict(&counts).unwrap(), labels);
+        bernoulli.fit_prior = false;
+        bernoulli.fit(&counts, &[0, 1, 1, 1]).unwrap();
+        assert!((bernoulli.class_log_prior()[0] - 0.5f64.ln()).abs() < 1e-12);
+        assert!(BernoulliNB::new().predict(&counts).is_err());
+
+        let sparse = Matrix::from_vec(4, 2, vec![2.0f64, 0.0, 3.0, 0.0, 0.0, 4.0, 0.0, 1.0]).unwrap();
+        let mut unsmoothed = MultinomialNB::new();
+        unsmoothed.alpha = 0.0;
+        unsmoothed.fit(&sparse, &labels).unwrap();
+        assert!(unsmoothed.predict_proba(&sparse).unwrap().data.iter().all(|v| v.is_finite()));
+        assert_eq!(unsmoothed.predict(&sparse).unwrap(), labels);
+        let mut unsmoothed = BernoulliNB::new();
+        unsmoothed.alpha = 0.0;
+        unsmoothed.fit(&sparse, &labels).unwrap();
+        assert!(unsmoothed.predict_proba(&sparse).unwrap().data.iter().all(|v| v.is_finite()));
+        assert_eq!(unsmoothed.predict(&sparse).unwrap(), labels);
+    }
+
+    #[test]
+    fn svm_test() {
+        use math::matrix::Matrix;
+        use math::vector::Vector;
+        use ml::{Classifier, Kernel, Regressor, SVC, SVR};
+
+        let x = Matrix::from_vec(4, 2, vec![0.0, 0.0, 0.0, 1.0, 2.0, 0.0, 2.0, 1.0]).unwrap();
+        let y = [3, 3, 8, 8];
+        let mut linear = SVC::new(Kernel::Linear);
+        linear.c = 100.0;
+        linear.tol = 1e-8;
+        linear.fit(&x, &y).unwrap();
+        assert_eq!(linear.predict(&x).unwrap(), y);
+        let w = linear.coefficients().unwrap();
+        assert!((w[(0, 0)] - 1.0).abs() < 1e-6 && w[(0, 1)].abs() < 1e-6);
+        assert!((linear.intercepts()[0] + 1.0).abs() < 1e-6);
+        assert!((linear.margins()[0] - 2.0).abs() < 1e-6);
+        let on_margin = linear.decision_function(linear.support_vectors().unwrap()).unwrap();
+        assert!(!linear.support().is_empty() && on_margin.data.iter().all(|f| (f.abs() - 1.0).abs() < 1e-6));
+        let scores = linear.decision_function(&Matrix::from_vec(2, 2, vec![1.0f64, 5.0, 3.0, -2.0]).unwrap()).unwrap();
+        assert!(scores[(0, 0)].abs() < 1e-6 && (scores[(1, 0)] - 2.0).abs() < 1e-6);
+        let dual = linear.dual_coef().unwrap();
+        assert!(dual.data.iter().sum::<f64>().abs() < 1e-9);
+
+        let ring = Matrix::from_fn(40, 2, |i, j| {
+            let angle = i as f64 * 0.7;
+            let radius = if i % 2 == 0 { 1.0 } else { 3.0 };
+            if j == 0 { radius * angle.cos() } else { radius * angle.sin() }
+        });
+        let inside: Vec<usize> = (0..40).map(|i| i % 2).collect();
+        let mut rbf = SVC::new(Kernel::Rbf { gamma: None });
+        rbf.c = 10.0;
+        rbf.fit(&ring, &inside).unwrap();
+        assert_eq!(rbf.predict(&ring).unwrap(), inside);
+        assert!(rbf.coefficients().is_none());
+        assert!(rbf.support().len() < 40);
+        let mut flat = SVC::new(Kernel::Linear);
+        flat.fit(&ring, &inside).unwrap();
+        assert!(flat.score(&ring, &inside).unwrap() < 0.8);
+
+        let centers = [[0.0, 0.0], [4.0, 0.0], [0.0, 4.0]];
+        let blobs = Matrix::from_fn(30, 2, |i, j| centers[i % 3][j] + ((i * 7) % 5) as f64 * 0.1);
+        let labels: Vec<usize> = (0..30).map(|i| i % 3).collect();
+        let mut multi = SVC::new(Kernel::Rbf { gamma: Some(0.5) });
+        multi.fit(&blobs, &labels).unwrap();
+        assert_eq!(multi.decision_function(&blobs).unwrap().cols, 3);
+        assert_eq!(multi.predict(&blobs).unwrap(), labels);
+
+        let line = Matrix::from_fn(20, 1, |i, _| i as f64 * 0.5);
+        let target = Vector::from((0..20).map(|i| 2.0 * line[(i, 0)] + 1.0 + if i % 2 == 0 { 0.05 } else { -0.05 }).collect::<Vec<f64>>());
+        let mut svr = SVR::new(Kernel::Linear);
+        svr.c = 100.0;
+        svr.epsilon = 0.1;
+        svr.tol = 1e-8;
+        svr.fit(&line, &target).unwrap();
+        assert!((svr.coefficients().unwrap()[0] - 2.0).abs() < 0.02 && (svr.intercept() - 1.0).abs() < 0.1);
+        let residuals: Vec<f64> = svr.predict(&line).unwrap().data.iter().zip(&target.data).map(|(p, t)| p - t).collect();
+        assert!(residuals.iter().all(|r| r.abs() <= 0.1 + 1e-6));
+        assert!(svr.support().len() < 20);
+        let mut curve = SVR::new(Kernel::Rbf { gamma: Some(1.0) });
+        curve.c = 100.0;
+        curve.epsilon = 0.01;
+        let wave = Vector::from((0..20).map(|i| (line[(i, 0)]).sin()).collect::<Vec<f64>>());
+        curve.fit(&line, &wave).unwrap();
+        assert!(curve.score(&line, &wave).unwrap() > 0.99);
+
+        assert!(SVC::new(Kernel::Linear).predict(&x).is_err());
+        let mut bad = SVR::new(Kernel::Linear);
+        bad.c = 0.0;
+        assert!(bad.fit(&line, &target).is_err());
+    }
+
+    #[test]
+    fn sgd_learners_test() {
+        use math::matrix::Matrix;
+        use math::vector::Vector;
+        use ml::{ClassificationLoss, Classifier, LearningRate, Penalty, RegressionLoss, Regressor, SGDClassifier, SGDRegressor};
+
+        let x = Matrix::from_fn(60, 2, |i, j| if j == 0 { (i % 12) as f64 / 6.0 - 1.0 } else { ((i * 7) % 10) as f64 / 5.0 - 1.0 });
+        let y: Vec<usize> = (0..60).map(|i| if x[(i, 0)] + x[(i, 1)] > 0.1 { 1 } else { 0 }).collect();
+        for loss in [ClassificationLoss::Hinge, ClassificationLoss::Log, ClassificationLoss::SquaredHinge, ClassificationLoss::ModifiedHuber] {
+            let mut model = SGDClassifier::new();
+            model.loss = loss;
+            model.seed = Some(3);
+            model.fit(&x, &y).unwrap();
+            assert!(model.score(&x, &y).unwrap() > 0.9, "{:?}", loss);
+            assert!(model.n_iter() >= 1);
+        }
+        let mut logistic = SGDClassifier::new();
+        logistic.loss = ClassificationLoss::Log;
+        logistic.seed = Some(3);
+        logistic.fit(&x, &y).unwrap();
+        let proba = logistic.predict_proba(&x).unwrap();
+        assert!((0..60).all(|i| (proba[(i, 0)] + proba[(i, 1)] - 1.0).abs() < 1e-12));
+        assert!(SGDClassifier::new().predict_proba(&x).is_err());
+
+        let mut online = SGDClassifier::new();
+        online.learning_rate = LearningRate::Constant(0.05);
+        assert!(online.partial_fit(&x, &y, None).is_err());
+        for epoch in 0..30 {
+            for start in (0..60).step_by(15) {
+                let batch = Matrix::from_fn(15, 2, |i, j| x[(start + i, j)]);
+                let classes: &[usize] = &[0, 1, 2];
+                online.partial_fit(&batch, &y[start..start + 15], if epoch == 0 && start == 0 { Some(classes) } else { None }).unwrap();
+            }
+        }
+        assert_eq!(online.classes(), &[0, 1, 2]);
+        assert_eq!(online.coefficients().unwrap().rows, 3);
+        assert_eq!(online.n_iter(), 120);
+        assert!(online.score(&x, &y).unwrap() > 0.85);
+        assert!(online.partial_fit(&x, &vec![5; 60], None).is_err());
+        assert!(online.partial_fit(&x, &y, Some(&[0, 1])).is_err());
+
+        let target = Vector::from((0..60).map(|i| 3.0 * x[(i, 0)] - 2.0 * x[(i, 1)] + 0.5).collect::<Vec<f64>>());
+        let mut regressor = SGDRegressor::new();
+        regressor.seed = Some(1);
+        regressor.alpha = 0.0;
+        regressor.tol = None;
+        regressor.max_iter = 200;
+        regressor.learning_rate = LearningRate::InvScaling { eta0: 0.1, power_t: 0.1 };
+        regressor.fit(&x, &target).unwrap();
+        let w = regressor.coefficients().unwrap();
+        assert!((w[0] - 3.0).abs() < 0.05 && (w[1] + 2.0).abs() < 0.05 && (regressor.intercept() - 0.5).abs() < 0.05);
+
+        let mut streaming = SGDRegressor::new();
+        streaming.loss = RegressionLoss::Huber(1.0);
+        streaming.learning_rate = LearningRate::Constant(0.05);
+        for _ in 0..100 {
+            streaming.partial_fit(&x, &target).unwrap();
+        }
+        assert!(streaming.score(&x, &target).unwrap() > 0.99);
+        let before = streaming.coefficients().unwrap().to_vec();
+        streaming.partial_fit(&Matrix::from_vec(1, 2, vec![0.0, 0.0]).unwrap(), &Vector::from(vec![0.5])).unwrap();
+        assert!((streaming.coefficients().unwrap()[0] - before[0]).abs() < 0.01);
+
+        let mut sparse = SGDRegressor::new();
+        sparse.penalty = Penalty::L1;
+        sparse.alpha = 0.05;
+        sparse.seed = Some(2);
+        sparse.learning_rate = LearningRate::Constant(0.01);
+        let wide = Matrix::from_fn(60, 3, |i, j| if j < 2 { x[(i, j)] } else { ((i * 5) % 7) as f64 / 7.0 - 0.5 });
+        sparse.fit(&wide, &target).unwrap();
+        assert!(sparse.coefficients().unwrap()[2].abs() < 0.05);
+        let mut optimal = SGDRegressor::new();
+        optimal.learning_rate = LearningRate::Optimal;
+        optimal.alpha = 0.0;
+        assert!(optimal.fit(&x, &target).is_err());
+        assert!(SGDRegressor::new().predict(&x).is_err());
+    }
+
+    #[test]
+    fn cross_validation_test() {
+        use math::matrix::Matrix;
+        use ml::model_selection::{cross_val_score, KFold, StratifiedKFold};
+        use ml::{DecisionTreeClassifier, LinearRegression, LogisticRegression};
+
+        let folds = KFold::new(3).split(10).unwrap();
+        assert_eq!(folds.iter().map(|fold| fold.test.len()).collect::<Vec<_>>(), vec![3, 3, 4]);
+        assert_eq!(folds[1].test, vec![3, 4, 5]);
+        let mut covered: Vec<usize> = folds.iter().flat_map(|fold| fold.test.clone()).collect();
+        covered.sort_unstable();
+        assert_eq!(covered, (0..10).collect::<Vec<_>>());
+        assert!(folds.iter().all(|fold| fold.train.len() + fold.test.len() == 10 && fold.train.iter().all(|i| !fold.test.contains(i))));
+        let mut shuffled = KFold::new(3);
+        shuffled.shuffle = true;
+        shuffled.seed = Some(9);
+        assert_eq!(shuffled.split(10).unwrap(), shuffled.split(10).unwrap());
+        assert_ne!(shuffled.split(10).unwrap(), folds);
+        assert!(KFold::new(11).split(10).is_err());
+
+        let labels: Vec<usize> = (0..30).map(|i| if i < 20 { 0 } else { 1 }).collect();
+        let stratified = StratifiedKFold::new(5).split(&labels).unwrap();
+        for fold in &stratified {
+            assert_eq!(fold.test.len(), 6);
+            assert_eq!(fold.test.iter().filter(|&&i| labels[i] == 1).count(), 2);
+        }
+
+        let x = Matrix::from_fn(30, 2, |i, j| if j == 0 { i as f64 / 10.0 } else { ((i * 7) % 11) as f64 / 10.0 });
+        let y: Vec<usize> = (0..30).map(|i| (x[(i, 0)] > 1.45) as usize).collect();
+        let mut stratify = StratifiedKFold::new(3);
+        stratify.shuffle = true;
+        stratify.seed = Some(1);
+        let scores = cross_val_score(&LogisticRegression::new(), &x, &y, &stratify.split(&y).unwrap()).unwrap();
+        assert_eq!(scores.len(), 3);
+        assert!(scores.iter().all(|&s| s > 0.8));
+        let tree_scores = cross_val_score(&DecisionTreeClassifier::new(), &x, &y, &stratify.split(&y).unwrap()).unwrap();
+        assert!(tree_scores.iter().all(|&s| s > 0.8));
+
+        let target: Vec<f64> = (0..30).map(|i| 2.0 * x[(i, 0)] - x[(i, 1)]).collect();
+        let r2 = cross_val_score(&LinearRegression::new(), &x, &target, &KFold::new(5).split(30).unwrap()).unwrap();
+        assert!(r2.iter().all(|&s| s > 1.0 - 1e-9));
+        assert!(cross_val_score(&LinearRegression::new(), &x, &target[..10], &folds).is_err());
+        assert!(cross_val_score(&LogisticRegression::new(), &x, &vec![0; 30], &KFold::new(3).split(30).unwrap()).is_err());
+
+        let mut sgd = ml::SGDClassifier::new();
+        sgd.max_iter = 3;
+        let sgd_folds = KFold::new(5).split(30).unwrap();
+        let seeded = |deterministic: bool| {
+            set_deterministic(deterministic);
+            seed(7);
+            let scores = cross_val_score(&sgd, &x, &y, &sgd_folds).unwrap();
+            set_deterministic(false);
+            scores
+        };
+        let first = seeded(false);
+        assert_eq!(seeded(false), first);
+        assert_eq!(seeded(true), first);
+    }
+
+    #[test]
+    fn hyperparameter_search_test() {
+        use math::matrix::Matrix;
+        use ml::model_selection::{param, Distribution, GridSearch, KFold, ParamDistributions, ParamGrid, ParamValue, RandomSearch, StratifiedKFold};
+        use ml::{Classifier, DecisionTreeClassifier, Ridge};
+
+        let mut grid = ParamGrid::new();
+        grid.insert("max_depth", vec![ParamValue::Int(1), ParamValue::Int(3)]);
+        grid.insert("min_samples_leaf", vec![ParamValue::Int(1), ParamValue::Int(2), ParamValue::Int(40)]);
+        assert_eq!(grid.len(), 6);
+        let combinations = grid.combinations();
+        assert_eq!(combinations.len(), 6);
+        assert_eq!(combinations[5]["max_depth"], ParamValue::Int(3));
+        assert_eq!(combinations[5]["min_samples_leaf"], ParamValue::Int(40));
+
+        let x = Matrix::from_fn(60, 2, |i, j| if j == 0 { (i % 10) as f64 } else { (i / 10) as f64 });
+        let y: Vec<usize> = (0..60).map(|i| ((x[(i, 0)] > 4.5) ^ (x[(i, 1)] > 2.5)) as usize).collect();
+        let build = |params: &ml::model_selection::ParamSet| {
+            let mut tree = DecisionTreeClassifier::new();
+            tree.max_depth = Some(param(params, "max_depth")?.as_usize()?);
+            tree.min_samples_leaf = param(params, "min_samples_leaf")?.as_usize()?;
+            Ok(tree)
+        };
+        let mut splitter = StratifiedKFold::new(3);
+        splitter.shuffle = true;
+        splitter.seed = Some(4);
+        let folds = splitter.split(&y).unwrap();
+        let mut search = GridSearch::new(grid.clone());
+        search.fit(build, &x, &y, &folds).unwrap();
+        assert_eq!(search.results().rows.len(), 6);
+        let best = search.best_params().unwrap();
+        assert_eq!(best["max_depth"], ParamValue::Int(3));
+        assert!(search.best_score().unwrap() > 0.95);
+        assert!(search.best_estimator().unwrap().score(&x, &y).unwrap() > 0.99);
+        let mut ranks: Vec<usize> = search.results().rows.iter().map(|row| row.rank).collect();
+        ranks.sort_unstable();
+        assert_eq!(ranks, (1..=6).collect::<Vec<_>>());
+        let table = search.results().to_string();
+        assert!(table.starts_with("Rank  Mean score  Std"));
+        assert_eq!(table.lines().count(), 8);
+        assert!(table.contains("max_depth=3, min_samples_leaf="));
+
+        let mut no_refit = GridSearch::new(grid);
+        no_refit.refit = false;
+        no_refit.fit(build, &x, &y, &folds).unwrap();
+        assert!(no_refit.best_estimator().is_none());
+        let mut broken = ParamGrid::new();
+        broken.insert("max_depth", vec![ParamValue::Bool(true)]);
+        broken.insert("min_samples_leaf", vec![ParamValue::Int(1)]);
+        assert!(GridSearch::new(broken).fit(build, &x, &y, &folds).is_err());
+
+        let target: Vec<f64> = (0..60).map(|i| 2.0 * x[(i, 0)] - x[(i, 1)] + ((i * 13) % 7) as f64 * 0.1).collect();
+        let mut space = ParamDistributions::new();
+        space.insert("alpha", Distribution::LogUniform(1e-4, 1e3));
+        space.insert("fit_intercept", Distribution::Choice(vec![ParamValue::Bool(true), ParamValue::Bool(false)]));
+        let mut random = RandomSearch::new(space, 12);
+        random.seed = Some(8);
+        let build_ridge = |params: &ml::model_selection::ParamSet| {
+            let mut ridge = Ridge::new(param(params, "alpha")?.as_f64()?);
+            ridge.fit_intercept = param(params, "fit_intercept")?.as_bool()?;
+            Ok(ridge)
+        };
+        random.fit(build_ridge, &x, &target, &KFold::new(4).split(60).unwrap()).unwrap();
+        assert_eq!(random.results().rows.len(), 12);
+        assert!(random.results().rows.iter().all(|row| (1e-4..=1e3).contains(&row.params["alpha"].as_f64().unwrap())));
+        let best = random.best_params().unwrap();
+        assert!(best["alpha"].as_f64().unwrap() < 10.0);
+        assert!(random.best_estimator().unwrap().alpha == best["alpha"].as_f64().unwrap());
+        assert!(Distribution::LogUniform(0.0, 1.0).sample(&mut random::Rng::seed_from_u64(0)).is_err());
+    }
+
+    #[test]
+    fn classification_metrics_test() {
+        use crate::metrics::{accuracy, classification_report, confusion_matrix, f1, precision, recall, Averaging};
+
+        let y_true = [0, 0, 1, 1, 2, 2];
+        let y_pred = [0, 1, 1, 1, 2, 0];
+        let matrix = confusion_matrix(&y_true, &y_pred).unwrap();
+        assert_eq!(matrix.labels, vec![0, 1, 2]);
+        assert_eq!(matrix.counts, vec![vec![1, 1, 0], vec![0, 2, 0], vec![1, 0, 1]]);
+        assert_eq!(matrix.get(2, 0), 1);
+        assert_eq!(matrix.predicted(1), 3);
+
+        assert!((accuracy(&y_true, &y_pred).unwrap() - 4.0 / 6.0).abs() < 1e-12);
+        assert!((precision(&y_true, &y_pred, Averaging::Micro).unwrap() - 4.0 / 6.0).abs() < 1e-12);
+        assert!((precision(&y_true, &y_pred, Averaging::Macro).unwrap() - (0.5 + 2.0 / 3.0 + 1.0) / 3.0).abs() < 1e-12);
+        assert!((recall(&y_true, &y_pred, Averaging::Weighted).unwrap() - 2.0 / 3.0).abs() < 1e-12);
+        assert!((f1(&y_true, &y_pred, Averaging::Macro).unwrap() - (0.5 + 0.8 + 2.0 / 3.0) / 3.0).abs() < 1e-12);
+
+        let weighted = precision(&[0, 0, 0, 1], &[0, 0, 1, 1], Averaging::Weighted).unwrap();
+        assert!((weighted - (0.75 * 1.0 + 0.25 * 0.5)).abs() < 1e-12);
+        assert_eq!(precision(&[0, 1], &[0, 0], Averaging::Macro).unwrap(), 0.25);
+
+        let report = classification_report(&y_true, &y_pred).unwrap();
+        assert_eq!(report.get(1).unwrap().support, 2);
+        assert!((report.get(2).unwrap().precision - 1.0).abs() < 1e-12);
+        assert_eq!(report.macro_avg.support, 6);
+        let text = report.to_string();
+        assert!(text.contains("weighted avg"));
+        assert!(text.lines().next().unwrap().trim_start().starts_with("precision"));
+
+        assert!(accuracy(&[0, 1], &[0]).is_err());
+        assert!(confusion_matrix(&[], &[]).is_err());
+    }
+
+    #[test]
+    fn ranking_metrics_test() {
+        use crate::math::matrix::Matrix;
+        use crate::metrics::{average_precision, average_precision_ovr, precision_recall_curve, roc_auc, roc_auc_ovr, roc_curve, Averaging};
+
+        let y_true = [false, false, true, true];
+        let scores = [0.1, 0.4, 0.35, 0.8];
+        let curve = roc_curve(&y_true, &scores).unwrap();
+        assert_eq!(curve.fpr, vec![0.0, 0.0, 0.5, 0.5, 1.0]);
+        assert_eq!(curve.tpr, vec![0.0, 0.5, 0.5, 1.0, 1.0]);
+        assert!(curve.thresholds[0].is_infinite());
+        assert!((roc_auc(&y_true, &scores).unwrap() - 0.75).abs() < 1e-12);
+
+        let pr = precision_recall_curve(&y_true, &scores).unwrap();
+        assert_eq!(pr.recall, vec![0.0, 0.5, 0.5, 1.0, 1.0]);
+        assert!((pr.precision[3] - 2.0 / 3.0).abs() < 1e-12);
+        assert!((average_precision(&y_true, &scores).unwrap() - (0.5 + 0.5 * 2.0 / 3.0)).abs() < 1e-12);
+
+        let tied = roc_curve(&[true, false, true], &[0.5f32, 0.5, 0.2]).unwrap();
+        assert_eq!(tied.thresholds.len(), 3);
+        assert!((roc_auc(&[true, false], &[0.5, 0.5]).unwrap() - 0.5).abs() < 1e-12);
+        assert!(roc_auc(&[true, true], &[0.1, 0.2]).is_err());
+        assert!(average_precision(&[true], &[f64::NAN]).is_err());
+
+        let labels = [3, 5, 7, 3, 5, 7];
+        let proba = Matrix::from_vec(6, 3, vec![
+            0.8, 0.1, 0.1, 0.2, 0.7, 0.1, 0.1, 0.2, 0.7,
+            0.6, 0.3, 0.1, 0.3, 0.4, 0.3, 0.2, 0.2, 0.6
+        ]).unwrap();
+        for averaging in [Averaging::Micro, Averaging::Macro, Averaging::Weighted] {
+            assert!((roc_auc_ovr(&labels, &proba, &[3, 5, 7], averaging).unwrap() - 1.0).abs() < 1e-12);
+            assert!((average_precision_ovr(&labels, &proba, &[3, 5, 7], averaging).unwrap() - 1.0).abs() < 1e-12);
+        }
+        let shuffled = roc_auc_ovr(&labels, &proba, &[5, 3, 7], Averaging::Macro).unwrap();
+        assert!(shuffled < 1.0);
+        assert!(roc_auc_ovr(&[3, 4], &Matrix::from_vec(2, 2, vec![0.5, 0.5, 0.5, 0.5]).unwrap(), &[3, 5], Averaging::Macro).is_err());
+    }
+
+    #[test]
+    fn regression_metrics_test() {
+        use crate::metrics::{explained_variance, mae, mape, mse, r2_score, rmse};
+
+        let y_true = [3.0, -0.5, 2.0, 7.0];
+        let y_pred = [2.5, 0.0, 2.0, 8.0];
+        assert!((mse(&y_true, &y_pred, None).unwrap() - 0.375).abs() < 1e-12);
+        assert!((rmse(&y_true, &y_pred, None).unwrap() - 0.375f64.sqrt()).abs() < 1e-12);
+        assert!((mae(&y_true, &y_pred, None).unwrap() - 0.5).abs() < 1e-12);
+        assert!((mape(&y_true, &y_pred, None).unwrap() - (0.5 / 3.0 + 1.0 + 1.0 / 7.0) / 4.0).abs() < 1e-12);
+        assert!((r2_score(&y_true, &y_pred, None).unwrap() - 0.9486081370449679).abs() < 1e-12);
+        assert!((explained_variance(&y_true, &y_pred, None).unwrap() - 0.9571734475374732).abs() < 1e-12);
+
+        let weights = [2.0, 1.0, 1.0, 0.0];
+        assert!((mae(&y_true, &y_pred, Some(&weights)).unwrap() - 0.375).abs() < 1e-12);
+        assert!((mse(&y_true, &y_pred, Some(&weights)).unwrap() - 0.1875).abs() < 1e-12);
+        assert_eq!(r2_score(&[1.0f32, 1.0], &[1.0, 1.0], None).unwrap(), 1.0);
+        assert_eq!(r2_score(&[1.0, 1.0], &[1.0, 2.0], None).unwrap(), 0.0);
+
+        assert!(mse(&[1.0, 2.0], &[1.0], None).is_err());
+        assert!(mse(&[1.0, 2.0], &[1.0, 2.0], Some(&[1.0])).is_err());
+        assert!(mae(&[1.0], &[1.0], Some(&[-1.0])).is_err());
+        assert!(mae(&[1.0], &[1.0], Some(&[0.0])).is_err());
+    }
+
+    #[test]
+    fn calibrator_test() {
+        use crate::math::matrix::Matrix;
+        use crate::metrics::brier_score;
+        use crate::ml::{CalibrationMethod, Calibrator, Classifier, LogisticRegression};
+        use crate::random::Rng;
+
+        let mut rng = Rng::seed_from_u64(11);
+        let mut sample = |n: usize| {
+            let scores: Vec<f64> = (0..n).map(|_| rng.next_f64() * 4.0 - 2.0).collect();
+            let labels: Vec<usize> = scores.iter().map(|&s| (rng.next_f64() < 1.0 / (1.0 + (1.0 - 3.0 * s).exp())) as usize).collect();
+            (Matrix::from_vec(n, 1, scores).unwrap(), labels)
+        };
+        let (train, y_train) = sample(2000);
+        let (test, y_test) = sample(2000);
+        let positive: Vec<bool> = y_test.iter().map(|&t| t == 1).collect();
+        let raw: Vec<f64> = test.data.iter().map(|&s| 1.0 / (1.0 + (-s).exp())).collect();
+        let raw_brier = brier_score(&positive, &raw).unwrap();
+
+        let mut platt = Calibrator::new(CalibrationMethod::Platt);
+        platt.fit(&train, &y_train).unwrap();
+        let (a, b) = platt.platt_parameters()[0];
+        assert!((a - 3.0).abs() < 0.5 && (b + 1.0).abs() < 0.3);
+        let mut isotonic = Calibrator::new(CalibrationMethod::Isotonic);
+        isotonic.fit(&train, &y_train).unwrap();
+
+        for calibrator in [&platt, &isotonic] {
+            let proba = calibrator.predict_proba(&test).unwrap();
+            assert_eq!((proba.rows, proba.cols), (2000, 2));
+            assert!((proba.data[0] + proba.data[1] - 1.0).abs() < 1e-12);
+            let calibrated: Vec<f64> = (0..proba.rows).map(|i| proba.data[i * 2 + 1]).collect();
+            assert!(brier_score(&positive, &calibrated).unwrap() < raw_brier);
+        }
+
+        let mut steps = Calibrator::new(CalibrationMethod::Isotonic);
+        steps.fit(&Matrix::from_vec(5, 1, vec![1.0, 2.0, 2.0, 3.0, 4.0]).unwrap(), &[1, 1, 0, 0, 1]).unwrap();
+        let proba = steps.predict_proba(&Matrix::from_vec(4, 1, vec![0.0, 1.5, 3.5, 9.0]).unwrap()).unwrap();
+        let positive: Vec<f64> = (0..4).map(|i| proba.data[i * 2 + 1]).collect();
+        for (p, expected) in positive.iter().zip([0.5, 0.5, 0.75, 1.0]) {
+            assert!((p - expected).abs() < 1e-12);
+        }
+
+        let x = Matrix::from_vec(9, 2, vec![
+            0.0, 0.0, 0.2, 0.1, 0.1, 0.3, 3.0, 3.0, 3.2, 2.9, 2.8, 3.1, 0.0, 3.0, 0.2, 3.1, 0.1, 2.8
+        ]).unwrap();
+        let y = [0, 0, 0, 1, 1, 1, 2, 2, 2];
+        let mut model = LogisticRegression::new();
+        model.fit(&x, &y).unwrap();
+        let mut calibrator = Calibrator::default();
+        calibrator.fit(&model.decision_function(&x).unwrap(), &y).unwrap();
+        let proba = calibrator.predict_proba(&model.decision_function(&x).unwrap()).unwrap();
+        assert_eq!(calibrator.n_classes(), 3);
+        for (row, &label) in proba.data.chunks(3).zip(&y) {
+            assert!((row.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+            assert_eq!(crate::ml::argmax(row), label);
+        }
+
+        assert!(Calibrator::default().predict_proba(&x).is_err());
+        assert!(steps.predict_proba(&Matrix::from_vec(1, 1, vec![f64::NAN]).unwrap()).is_err());
+        assert!(platt.predict_proba(&Matrix::from_vec(1, 1, vec![f64::INFINITY]).unwrap()).is_err());
+        assert!(Calibrator::default().fit(&Matrix::from_vec(2, 1, vec![0.0, 1.0]).unwrap(), &[1, 1]).is_err());
+        assert!(brier_score(&[true], &[1.5]).is_err());
+    }
+
     #[test]
     fn it_works() {
         let result = add(2, 2);
@@ -0,0 +1,148 @@
+use std::f64::consts::PI;
+use std::ops::{Add, Mul, Sub};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Complex {
+  pub re: f64,
+  pub im: f64
+}
+
+impl Complex {
+  pub fn new(re: f64, im: f64) -> Self {
+    Complex { re, im }
+  }
+
+  pub fn from_polar(magnitude: f64, phase: f64) -> Self {
+    Complex { re: magnitude * phase.cos(), im: magnitude * phase.sin() }
+  }
+
+  pub fn conj(self) -> Self {
+    Complex { re: self.re, im: -self.im }
+  }
+
+  pub fn norm_sqr(self) -> f64 {
+    self.re * self.re + self.im * self.im
+  }
+
+  pub fn abs(self) -> f64 {
+    self.norm_sqr().sqrt()
+  }
+
+  pub fn scale(self, factor: f64) -> Self {
+    Complex { re: self.re * factor, im: self.im * factor }
+  }
+}
+
+impl Add for Complex {
+  type Output = Complex;
+
+  fn add(self, other: Complex) -> Complex {
+    Complex { re: self.re + other.re, im: self.im + other.im }
+  }
+}
+
+impl Sub for Complex {
+  type Output = Complex;
+
+  fn sub(self, other: Complex) -> Complex {
+    Complex { re: self.re - other.re, im: self.im - other.im }
+  }
+}
+
+impl Mul for Complex {
+  type Output = Complex;
+
+  fn mul(self, other: Complex) -> Complex {
+    Complex { re: self.re * other.re - self.im * other.im, im: self.re * other.im + self.im * other.re }
+  }
+}
+
+fn radix2(buffer: &mut [Complex], inverse: bool) {
+  let n = buffer.len();
+  let bits = n.trailing_zeros();
+  for i in 0..n {
+    let j = i.reverse_bits() >> (usize::BITS - bits);
+    if j > i {
+      buffer.swap(i, j);
+    }
+  }
+
+  let sign = if inverse { 1.0 } else { -1.0 };
+  let mut len = 2;
+  while len <= n {
+    let step = Complex::from_polar(1.0, sign * 2.0 * PI / len as f64);
+    for start in (0..n).step_by(len) {
+      let mut twiddle = Complex::new(1.0, 0.0);
+      for k in 0..len / 2 {
+        let even = buffer[start + k];
+        let odd = buffer[start + k + len / 2] * twiddle;
+        buffer[start + k] = even + odd;
+        buffer[start + k + len / 2] = even - odd;
+        twiddle = twiddle * step;
+      }
+    }
+    len <<= 1;
+  }
+}
+
+fn bluestein(buffer: &mut [Complex], inverse: bool) {
+  let n = buffer.len();
+  let m = (2 * n - 1).next_power_of_two();
+  let sign = if inverse { 1.0 } else { -1.0 };
+  let chirp: Vec<Complex> = (0..n).map(|k| Complex::from_polar(1.0, sign * PI * ((k * k) % (2 * n)) as f64 / n as f64)).collect();
+
+  let mut a = vec![Complex::default(); m];
+  let mut b = vec![Complex::default(); m];
+  for k in 0..n {
+    a[k] = buffer[k] * chirp[k];
+    b[k] = chirp[k].conj();
+    if k > 0 {
+      b[m - k] = chirp[k].conj();
+    }
+  }
+  radix2(&mut a, false);
+  radix2(&mut b, false);
+  for (x, y) in a.iter_mut().zip(&b) {
+    *x = *x * *y;
+  }
+  radix2(&mut a, true);
+  for k in 0..n {
+    buffer[k] = a[k].scale(1.0 / m as f64) * chirp[k];
+  }
+}
+
+fn transform(buffer: &mut [Complex], inverse: bool) {
+  match buffer.len() {
+    0 | 1 => {}
+    n if n.is_power_of_two() => radix2(buffer, inverse),
+    _ => bluestein(buffer, inverse)
+  }
+}
+
+pub fn fft(buffer: &mut [Complex]) {
+  transform(buffer, false);
+}
+
+pub fn ifft(buffer: &mut [Complex]) {
+  transform(buffer, true);
+  let n = buffer.len().max(1) as f64;
+  buffer.iter_mut().for_each(|x| *x = x.scale(1.0 / n));
+}
+
+pub fn rfft(signal: &[f64]) -> Vec<Complex> {
+  let mut buffer: Vec<Complex> = signal.iter().map(|&x| Complex::new(x, 0.0)).collect();
+  fft(&mut buffer);
+  buffer.truncate(signal.len() / 2 + 1);
+  buffer
+}
+
+pub fn irfft(spectrum: &[Complex], n: usize) -> Vec<f64> {
+  let mut buffer: Vec<Complex> = (0..n)
+    .map(|k| match spectrum.get(k) {
+      Some(&value) => value,
+      None => spectrum.get(n - k).map_or(Complex::default(), |value| value.conj())
+    })
+    .collect();
+  ifft(&mut buffer);
+  buffer.into_iter().map(|x| x.re).collect()
+}
@@ -0,0 +1,180 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+pub trait HalfFloat: Copy + Default + PartialEq + PartialOrd + fmt::Debug + 'static {
+  fn from_f32(value: f32) -> Self;
+  fn to_f32(self) -> f32;
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Default)]
+pub struct f16(u16);
+
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Default)]
+pub struct bf16(u16);
+
+fn round_shift(value: u32, shift: u32) -> u32 {
+  let kept = value >> shift;
+  let rest = value & ((1 << shift) - 1);
+  let half = 1 << (shift - 1);
+  if rest > half || (rest == half && kept & 1 == 1) {
+    kept + 1
+  } else {
+    kept
+  }
+}
+
+impl f16 {
+  pub const ZERO: f16 = f16(0x0000);
+  pub const ONE: f16 = f16(0x3C00);
+  pub const MAX: f16 = f16(0x7BFF);
+  pub const INFINITY: f16 = f16(0x7C00);
+  pub const NEG_INFINITY: f16 = f16(0xFC00);
+  pub const NAN: f16 = f16(0x7E00);
+
+  pub const fn from_bits(bits: u16) -> Self {
+    f16(bits)
+  }
+
+  pub const fn to_bits(self) -> u16 {
+    self.0
+  }
+
+  pub fn from_f32(value: f32) -> Self {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exp = ((bits >> 23) & 0xFF) as i32;
+    let man = bits & 0x7F_FFFF;
+
+    if exp == 0xFF {
+      let nan = if man != 0 { 0x0200 | (man >> 13) as u16 } else { 0 };
+      return f16(sign | 0x7C00 | nan);
+    }
+
+    let e = exp - 127 + 15;
+    if e >= 0x1F {
+      return f16(sign | 0x7C00);
+    }
+    if e <= 0 {
+      if e < -10 {
+        return f16(sign);
+      }
+      return f16(sign | round_shift(man | 0x80_0000, (14 - e) as u32) as u16);
+    }
+
+    f16(sign | round_shift(((e as u32) << 23) | man, 13) as u16)
+  }
+
+  pub fn to_f32(self) -> f32 {
+    let sign = ((self.0 & 0x8000) as u32) << 16;
+    let exp = ((self.0 >> 10) & 0x1F) as u32;
+    let man = (self.0 & 0x03FF) as u32;
+
+    match exp {
+      0 => {
+        let magnitude = man as f32 / (1 << 24) as f32;
+        if sign != 0 { -magnitude } else { magnitude }
+      }
+      0x1F => f32::from_bits(sign | 0x7F80_0000 | (man << 13)),
+      _ => f32::from_bits(sign | ((exp + 112) << 23) | (man << 13))
+    }
+  }
+
+  pub fn is_nan(self) -> bool {
+    self.0 & 0x7C00 == 0x7C00 && self.0 & 0x03FF != 0
+  }
+
+  pub fn is_finite(self) -> bool {
+    self.0 & 0x7C00 != 0x7C00
+  }
+}
+
+impl bf16 {
+  pub const ZERO: bf16 = bf16(0x0000);
+  pub const ONE: bf16 = bf16(0x3F80);
+  pub const MAX: bf16 = bf16(0x7F7F);
+  pub const INFINITY: bf16 = bf16(0x7F80);
+  pub const NEG_INFINITY: bf16 = bf16(0xFF80);
+  pub const NAN: bf16 = bf16(0x7FC0);
+
+  pub const fn from_bits(bits: u16) -> Self {
+    bf16(bits)
+  }
+
+  pub const fn to_bits(self) -> u16 {
+    self.0
+  }
+
+  pub fn from_f32(value: f32) -> Self {
+    let bits = value.to_bits();
+    if value.is_nan() {
+      return bf16((bits >> 16) as u16 | 0x0040);
+    }
+    bf16((round_shift(bits & 0x7FFF_FFFF, 16) | ((bits >> 16) & 0x8000)) as u16)
+  }
+
+  pub fn to_f32(self) -> f32 {
+    f32::from_bits((self.0 as u32) << 16)
+  }
+
+  pub fn is_nan(self) -> bool {
+    self.0 & 0x7F80 == 0x7F80 && self.0 & 0x007F != 0
+  }
+
+  pub fn is_finite(self) -> bool {
+    self.0 & 0x7F80 != 0x7F80
+  }
+}
+
+macro_rules! impl_half_float {
+  ($($t:ident),*) => {
+    $(impl HalfFloat for $t {
+      fn from_f32(value: f32) -> Self {
+        $t::from_f32(value)
+      }
+
+      fn to_f32(self) -> f32 {
+        $t::to_f32(self)
+      }
+    }
+
+    impl From<$t> for f32 {
+      fn from(value: $t) -> f32 {
+        value.to_f32()
+      }
+    }
+
+    impl From<$t> for f64 {
+      fn from(value: $t) -> f64 {
+        value.to_f32() as f64
+      }
+    }
+
+    impl PartialEq for $t {
+      fn eq(&self, other: &Self) -> bool {
+        self.to_f32() == other.to_f32()
+      }
+    }
+
+    impl PartialOrd for $t {
+      fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.to_f32().partial_cmp(&other.to_f32())
+      }
+    }
+
+    impl fmt::Debug for $t {
+      fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.to_f32(), f)
+      }
+    }
+
+    impl fmt::Display for $t {
+      fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.to_f32(), f)
+      }
+    })*
+  };
+}
+
+impl_half_float!(f16, bf16);
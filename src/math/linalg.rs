@@ -0,0 +1,120 @@
+use super::matrix::Matrix;
+use super::scalar::Float;
+
+fn check_square<T>(name: &str, a: &Matrix<T>) -> Result<(), String> {
+  if a.rows != a.cols {
+    return Err(format!("{}: expected a square matrix, found {}x{}", name, a.rows, a.cols));
+  }
+  Ok(())
+}
+
+pub fn cholesky<T: Float>(a: &Matrix<T>) -> Result<Matrix<T>, String> {
+  check_square("cholesky", a)?;
+  let n = a.rows;
+  let mut l = Matrix::from_fn(n, n, |_, _| T::zero());
+  for i in 0..n {
+    for j in 0..=i {
+      let mut sum = a.data[i * n + j];
+      for k in 0..j {
+        sum -= l.data[i * n + k] * l.data[j * n + k];
+      }
+      if i == j {
+        if sum <= T::zero() || sum.is_nan() {
+          return Err("cholesky: matrix is not positive definite".to_string());
+        }
+        l.data[i * n + i] = sum.sqrt();
+      } else {
+        l.data[i * n + j] = sum / l.data[j * n + j];
+      }
+    }
+  }
+  Ok(l)
+}
+
+pub fn forward_substitution<T: Float>(l: &Matrix<T>, b: &[T]) -> Result<Vec<T>, String> {
+  check_square("forward_substitution", l)?;
+  let n = l.rows;
+  let mut x = b.to_vec();
+  for i in 0..n {
+    for k in 0..i {
+      x[i] = x[i] - l.data[i * n + k] * x[k];
+    }
+    x[i] /= l.data[i * n + i];
+  }
+  Ok(x)
+}
+
+pub fn back_substitution<T: Float>(u: &Matrix<T>, b: &[T]) -> Result<Vec<T>, String> {
+  if u.cols > u.rows || b.len() < u.cols {
+    return Err(format!("back_substitution: cannot solve a {}x{} system with {} values", u.rows, u.cols, b.len()));
+  }
+  let n = u.cols;
+  let mut x = b[..n].to_vec();
+  for i in (0..n).rev() {
+    for k in i + 1..n {
+      x[i] = x[i] - u.data[i * u.cols + k] * x[k];
+    }
+    let pivot = u.data[i * u.cols + i];
+    if pivot.abs() <= T::epsilon() {
+      return Err("back_substitution: matrix is singular".to_string());
+    }
+    x[i] /= pivot;
+  }
+  Ok(x)
+}
+
+pub fn cholesky_solve<T: Float>(a: &Matrix<T>, b: &[T]) -> Result<Vec<T>, String> {
+  let l = cholesky(a)?;
+  let y = forward_substitution(&l, b)?;
+  back_substitution(&l.transpose(), &y)
+}
+
+pub fn qr<T: Float>(a: &Matrix<T>) -> Result<(Matrix<T>, Matrix<T>), String> {
+  let (m, n) = (a.rows, a.cols);
+  if m < n {
+    return Err(format!("qr: expected at least as many rows as columns, found {}x{}", m, n));
+  }
+  let mut r = a.clone();
+  let mut reflectors: Vec<Vec<T>> = Vec::with_capacity(n);
+  for j in 0..n {
+    let mut v: Vec<T> = (j..m).map(|i| r.data[i * n + j]).collect();
+    let norm = v.iter().fold(T::zero(), |acc, &x| acc + x * x).sqrt();
+    if norm > T::zero() {
+      v[0] = if v[0] < T::zero() { v[0] - norm } else { v[0] + norm };
+      let v_norm = v.iter().fold(T::zero(), |acc, &x| acc + x * x).sqrt();
+      v.iter_mut().for_each(|x| *x /= v_norm);
+      for col in j..n {
+        let dot = (j..m).fold(T::zero(), |acc, i| acc + v[i - j] * r.data[i * n + col]);
+        for i in j..m {
+          r.data[i * n + col] -= T::from_f64(2.0) * v[i - j] * dot;
+        }
+      }
+    }
+    reflectors.push(v);
+  }
+
+  let mut q = Matrix::from_fn(m, n, |i, j| if i == j { T::one() } else { T::zero() });
+  for (j, v) in reflectors.iter().enumerate().rev() {
+    for col in 0..n {
+      let dot = (j..m).fold(T::zero(), |acc, i| acc + v[i - j] * q.data[i * n + col]);
+      for i in j..m {
+        q.data[i * n + col] -= T::from_f64(2.0) * v[i - j] * dot;
+      }
+    }
+  }
+  let r = Matrix::from_fn(n, n, |i, j| if j >= i { r.data[i * n + j] } else { T::zero() });
+  Ok((q, r))
+}
+
+pub fn lstsq<T: Float>(a: &Matrix<T>, b: &[T]) -> Result<Vec<T>, String> {
+  if b.len() != a.rows {
+    return Err(format!("lstsq: matrix has {} rows but {} targets", a.rows, b.len()));
+  }
+  let (q, r) = qr(a)?;
+  let qtb: Vec<T> = (0..a.cols).map(|j| (0..a.rows).fold(T::zero(), |acc, i| acc + q.data[i * a.cols + j] * b[i])).collect();
+  let scale = (0..r.rows).map(|i| r.data[i * r.cols + i].abs()).fold(T::zero(), T::max);
+  if (0..r.rows).any(|i| r.data[i * r.cols + i].abs() <= scale * T::from_f64(1e-10)) {
+    return Err("lstsq: matrix is rank deficient".to_string());
+  }
+  back_substitution(&r, &qtb)
+}
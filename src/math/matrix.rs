@@ -1,15 +1,36 @@
 use std::fmt;
 use std::ops::{Index, IndexMut, Add, Sub, Mul};
 
+use super::scalar::{Float, Scalar};
+use super::shape::{Shape, ShapeError};
 use super::vector::Vector;
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "MatrixData<T>"))]
 pub struct Matrix<T = f64> {
   pub rows: usize,
   pub cols: usize,
   pub data: Vec<T>
 }
 
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct MatrixData<T> {
+  rows: usize,
+  cols: usize,
+  data: Vec<T>
+}
+
+#[cfg(feature = "serde")]
+impl<T> TryFrom<MatrixData<T>> for Matrix<T> {
+  type Error = String;
+
+  fn try_from(raw: MatrixData<T>) -> Result<Self, Self::Error> {
+    Self::from_vec(raw.rows, raw.cols, raw.data)
+  }
+}
+
 impl<T> Matrix<T> {
   pub fn new(rows: usize, cols: usize) -> Self {
     Matrix {
@@ -32,22 +53,22 @@ impl<T> Matrix<T> {
 
   pub fn ones(rows: usize, cols: usize) -> Self
   where
-    T: Clone + From<i32>
+    T: Scalar
   {
     Matrix {
       rows,
       cols,
-      data: vec![T::from(1); rows * cols]
+      data: vec![T::one(); rows * cols]
     }
   }
 
   pub fn identity(size: usize) -> Self
   where
-    T: Clone + From<i32> + Default
+    T: Scalar
   {
     let mut matrix = Self::zeroes(size, size);
     for i in 0..size {
-      matrix[(i, i)] = T::from(1);
+      matrix[(i, i)] = T::one();
     }
 
     matrix
@@ -55,7 +76,7 @@ impl<T> Matrix<T> {
 
   pub fn from_vec(rows: usize, cols: usize, data: Vec<T>) -> Result<Self, String> {
     if data.len() != rows * cols {
-      return Err("Data length does not match specified dimensions.".to_string());
+      return Err(ShapeError::SizeMismatch { expected: Shape::new(rows, cols), len: data.len() }.into());
     }
 
     Ok(Self {
@@ -115,6 +136,40 @@ impl<T> Matrix<T> {
     })
   }
 
+  pub fn from_fn<F>(rows: usize, cols: usize, mut f: F) -> Self
+  where
+    F: FnMut(usize, usize) -> T
+  {
+    let mut data = Vec::with_capacity(rows * cols);
+    for i in 0..rows {
+      for j in 0..cols {
+        data.push(f(i, j));
+      }
+    }
+
+    Self {
+      rows,
+      cols,
+      data
+    }
+  }
+
+  pub fn shape(&self) -> Shape {
+    Shape::new(self.rows, self.cols)
+  }
+
+  pub fn same_shape<U>(&self, other: &Matrix<U>) -> bool {
+    self.rows == other.rows && self.cols == other.cols
+  }
+
+  pub fn check_same_shape<U>(&self, other: &Matrix<U>, op: &'static str) -> Result<(), ShapeError> {
+    self.shape().check_same(&other.shape(), op)
+  }
+
+  pub fn check_matmul_compat<U>(&self, other: &Matrix<U>) -> Result<Shape, ShapeError> {
+    self.shape().check_matmul(&other.shape())
+  }
+
   pub fn get(&self, row: usize, col: usize) -> Option<&T> {
     if row >= self.rows || col >= self.cols {
       return None;
@@ -131,6 +186,20 @@ impl<T> Matrix<T> {
     Some(&mut self[(row, col)])
   }
 
+  /// # Safety
+  ///
+  /// `row` must be less than `self.rows` and `col` less than `self.cols`.
+  pub unsafe fn get_unchecked(&self, row: usize, col: usize) -> &T {
+    self.data.get_unchecked(row * self.cols + col)
+  }
+
+  /// # Safety
+  ///
+  /// `row` must be less than `self.rows` and `col` less than `self.cols`.
+  pub unsafe fn get_unchecked_mut(&mut self, row: usize, col: usize) -> &mut T {
+    self.data.get_unchecked_mut(row * self.cols + col)
+  }
+
   pub fn set(&mut self, row: usize, col: usize, value: T) -> Result<(), String> {
     if row >= self.rows || col >= self.cols {
       return Err("Index out of bounds".to_string());
@@ -183,8 +252,9 @@ where
   }
 
   pub fn reshape(&self, new_rows: usize, new_cols: usize) -> Result<Self, String> {
-    if self.rows * self.cols != new_rows * new_cols {
-      return Err("Cannot reshape matrix".to_string());
+    let new_shape = Shape::new(new_rows, new_cols);
+    if self.shape().size() != new_shape.size() {
+      return Err(ShapeError::SizeMismatch { expected: new_shape, len: self.data.len() }.into());
     }
 
     Ok(Self {
@@ -195,6 +265,59 @@ where
   }
 }
 
+impl<T> TryFrom<Vec<Vec<T>>> for Matrix<T> {
+  type Error = String;
+
+  fn try_from(rows: Vec<Vec<T>>) -> Result<Self, Self::Error> {
+    Self::from_rows(rows.into_iter().map(Vector::from).collect())
+  }
+}
+
+impl<T, const R: usize, const C: usize> From<[[T; C]; R]> for Matrix<T> {
+  fn from(rows: [[T; C]; R]) -> Self {
+    Self {
+      rows: R,
+      cols: C,
+      data: rows.into_iter().flatten().collect()
+    }
+  }
+}
+
+impl<T> Matrix<T>
+where
+  T: Copy
+{
+  pub fn broadcast_zip<F>(&self, other: &Self, op: &'static str, f: F) -> Result<Self, String>
+  where
+    F: Fn(T, T) -> T
+  {
+    if self.same_shape(other) {
+      return Ok(Self {
+        rows: self.rows,
+        cols: self.cols,
+        data: self.data.iter().zip(other.data.iter()).map(|(a, b)| f(*a, *b)).collect()
+      });
+    }
+
+    let dim = |a: usize, b: usize| match (a, b) {
+      (a, b) if a == b => Some(a),
+      (1, b) => Some(b),
+      (a, 1) => Some(a),
+      _ => None
+    };
+    let (rows, cols) = match (dim(self.rows, other.rows), dim(self.cols, other.cols)) {
+      (Some(rows), Some(cols)) => (rows, cols),
+      _ => return Err(self.check_same_shape(other, op).unwrap_err().into())
+    };
+
+    Ok(Self::from_fn(rows, cols, |i, j| {
+      let a = self[(if self.rows == 1 { 0 } else { i }, if self.cols == 1 { 0 } else { j })];
+      let b = other[(if other.rows == 1 { 0 } else { i }, if other.cols == 1 { 0 } else { j })];
+      f(a, b)
+    }))
+  }
+}
+
 impl<T> Add for Matrix<T>
 where
   T: Add<Output = T> + Copy
@@ -202,21 +325,7 @@ where
   type Output = Result<Self, String>;
 
   fn add(self, rhs: Self) -> Self::Output {
-    if self.rows != rhs.rows || self.cols != rhs.cols {
-      return Err("Cannot add 2 matrices with incompatible dimensions".to_string());
-    }
-
-    let new_data = self.data
-      .iter()
-      .zip(rhs.data.iter())
-      .map(|(a, b)| *a + *b)
-      .collect();
-
-    Ok(Self {
-      rows: self.rows,
-      cols: self.cols,
-      data: new_data
-    })
+    self.broadcast_zip(&rhs, "add", |a, b| a + b)
   }
 }
 
@@ -227,42 +336,50 @@ where
   type Output = Result<Self, String>;
 
   fn sub(self, rhs: Self) -> Self::Output {
-    if self.rows != rhs.rows || self.cols != rhs.cols {
-      return Err("Cannot substract 2 matrices with incompatible matrices".to_string());
-    }
+    self.broadcast_zip(&rhs, "sub", |a, b| a - b)
+  }
+}
 
-    let new_data = self.data
-      .iter()
-      .zip(rhs.data.iter())
-      .map(|(a, b)| *a - *b)
-      .collect();
+impl<T> Add<Vector<T>> for Matrix<T>
+where
+  T: Add<Output = T> + Copy
+{
+  type Output = Result<Self, String>;
 
-    Ok(Self {
-      rows: self.rows,
-      cols: self.cols,
-      data: new_data
-    })
+  fn add(self, rhs: Vector<T>) -> Self::Output {
+    let row = Matrix { rows: 1, cols: rhs.len(), data: rhs.data };
+    self.broadcast_zip(&row, "add", |a, b| a + b)
+  }
+}
+
+impl<T> Sub<Vector<T>> for Matrix<T>
+where
+  T: Sub<Output = T> + Copy
+{
+  type Output = Result<Self, String>;
+
+  fn sub(self, rhs: Vector<T>) -> Self::Output {
+    let row = Matrix { rows: 1, cols: rhs.len(), data: rhs.data };
+    self.broadcast_zip(&row, "sub", |a, b| a - b)
   }
 }
 
 impl<T> Mul for Matrix<T>
 where
-  T: Mul<Output = T> + Add<Output = T> + Copy + Default
+  T: Scalar
 {
   type Output = Result<Self, String>;
 
   fn mul(self, rhs: Self) -> Self::Output {
-    if self.cols != rhs.rows {
-      return Err("Cannot multiply matrices".to_string());
-    }
+    self.check_matmul_compat(&rhs)?;
 
-    let mut new_data = vec![T::default(); self.rows * rhs.cols];
+    let mut new_data = vec![T::zero(); self.rows * rhs.cols];
 
     for i in 0..self.rows {
       for j in 0..rhs.cols {
         new_data[i * rhs.cols + j] = (0..self.cols)
           .map(|k| self[(i, k)] * rhs[(k, j)])
-          .fold(T::default(), |acc, x| acc + x);
+          .fold(T::zero(), |acc, x| acc + x);
       }
     }
 
@@ -276,7 +393,7 @@ where
 
 impl<T> Matrix<T>
 where
-  T: Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Copy + Default
+  T: Scalar
 {
   pub fn scalar_multiply(&self, scalar: T) -> Self {
     Matrix {
@@ -287,57 +404,37 @@ where
   }
 
   pub fn dot(&self, other: &Self) -> Result<T, String> {
-    if self.rows != other.rows || self.cols != other.cols {
-      return Err("Matrices must have the same dimensions for dot product".to_string());
-    }
+    self.check_same_shape(other, "dot")?;
 
     Ok(self.data
       .iter()
       .zip(other.data.iter())
       .map(|(a, b)| *a * *b)
-      .fold(T::default(), |acc, x| acc + x)
+      .fold(T::zero(), |acc, x| acc + x)
     )
   }
 
   pub fn hadamard_product(&self, other: &Self) -> Result<Self, String> {
-    if self.rows != other.rows || self.cols != other.cols {
-      return Err("Matrices must have the same dimensions for Hadamard product".to_string());
-    }
-
-    let new_data = self.data
-      .iter()
-      .zip(other.data.iter())
-      .map(|(a, b)| *a * *b)
-      .collect();
-
-    Ok(Self {
-      rows: self.rows,
-      cols: self.cols,
-      data: new_data
-    })
+    self.broadcast_zip(other, "hadamard_product", |a, b| a * b)
   }
 
   pub fn trace(&self) -> Result<T, String> {
-    if self.rows != self.cols {
-      return Err("Matrix must be square to compute trace".to_string());
-    }
+    self.shape().check_square("trace")?;
 
     Ok(
       (0..self.rows)
         .map(|i| self[(i, i)])
-        .fold(T::default(), |acc, x| acc + x)
+        .fold(T::zero(), |acc, x| acc + x)
     )
   }
 }
 
 impl<T> Matrix<T>
 where
-  T: Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Copy + Default + PartialEq + std::fmt::Debug
+  T: Scalar
 {
   pub fn determinant(&self) -> Result<T, String> {
-    if self.rows != self.cols {
-      return Err("Matrix must be square to computer determinant".to_string());
-    }
+    self.shape().check_square("determinant")?;
 
     let n = self.rows;
     if n == 1 {
@@ -348,7 +445,7 @@ where
       return Ok(self[(0, 0)] * self[(1, 1)] - self[(0, 1)] * self[(1, 0)]);
     }
 
-    let mut det = T::default();
+    let mut det = T::zero();
     for j in 0..n {
       let mut submatrix = Vec::with_capacity((n - 1) * (n -1));
       for i in 1..n {
@@ -361,9 +458,9 @@ where
 
       let subdet = Matrix { rows: n - 1, cols: n - 1, data: submatrix }.determinant()?;
       if j % 2 == 0 {
-        det = det + self[(0, j)] * subdet;
+        det += self[(0, j)] * subdet;
       } else {
-        det = det - self[(0, j)] * subdet;
+        det -= self[(0, j)] * subdet;
       }
     }
 
@@ -371,28 +468,196 @@ where
   }
 }
 
+impl<T> Matrix<T>
+where
+  T: Float
+{
+  pub fn approx_eq(&self, other: &Self, tol: T) -> bool {
+    self.same_shape(other)
+      && self.data.iter().zip(other.data.iter()).all(|(a, b)| (*a - *b).abs() <= tol)
+  }
+}
+
+#[macro_export]
+macro_rules! assert_matrix_approx_eq {
+  ($left:expr, $right:expr) => {
+    $crate::assert_matrix_approx_eq!($left, $right, 1e-6)
+  };
+  ($left:expr, $right:expr, $tol:expr) => {{
+    let (left, right) = (&$left, &$right);
+    if !left.approx_eq(right, $tol) {
+      panic!(
+        "assertion failed: matrices are not approximately equal (tol = {})\nleft ({}x{}):\n{}\nright ({}x{}):\n{}",
+        $tol, left.rows, left.cols, left, right.rows, right.cols, right
+      );
+    }
+  }};
+}
+
 impl<T> Index<(usize, usize)> for Matrix<T> {
   type Output = T;
   
   fn index(&self, (row, col): (usize, usize)) -> &Self::Output {
+    debug_assert!(
+      row < self.rows && col < self.cols,
+      "index ({}, {}) out of bounds for {}x{} matrix",
+      row, col, self.rows, self.cols
+    );
     &self.data[row * self.cols + col]
   }
 }
 
 impl<T> IndexMut<(usize, usize)> for Matrix<T> {
   fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut Self::Output {
+    debug_assert!(
+      row < self.rows && col < self.cols,
+      "index ({}, {}) out of bounds for {}x{} matrix",
+      row, col, self.rows, self.cols
+    );
     &mut self.data[row * self.cols + col]
   }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PrintOptions {
+  pub precision: Option<usize>,
+  pub scientific: bool,
+  pub threshold: usize,
+  pub edge_items: usize
+}
+
+impl Default for PrintOptions {
+  fn default() -> Self {
+    PrintOptions {
+      precision: None,
+      scientific: false,
+      threshold: 1000,
+      edge_items: 3
+    }
+  }
+}
+
+pub struct MatrixDisplay<'a, T> {
+  matrix: &'a Matrix<T>,
+  options: PrintOptions
+}
+
+impl<T> Matrix<T> {
+  pub fn display_with(&self, options: PrintOptions) -> MatrixDisplay<'_, T> {
+    MatrixDisplay {
+      matrix: self,
+      options
+    }
+  }
+
+  fn visible_indices(len: usize, elide: bool, edge_items: usize) -> Vec<Option<usize>> {
+    if elide && len > 2 * edge_items {
+      (0..edge_items)
+        .map(Some)
+        .chain(std::iter::once(None))
+        .chain((len - edge_items..len).map(Some))
+        .collect()
+    } else {
+      (0..len).map(Some).collect()
+    }
+  }
+
+  fn write_grid<F>(&self, f: &mut fmt::Formatter<'_>, options: &PrintOptions, cell: F) -> fmt::Result
+  where
+    F: Fn(&T) -> String
+  {
+    if self.rows == 0 || self.cols == 0 {
+      return write!(f, "[]");
+    }
+
+    let elide = self.rows * self.cols > options.threshold;
+    let rows = Self::visible_indices(self.rows, elide, options.edge_items);
+    let cols = Self::visible_indices(self.cols, elide, options.edge_items);
+
+    let cells: Vec<Vec<String>> = rows
+      .iter()
+      .map(|row| {
+        cols
+          .iter()
+          .map(|col| match (row, col) {
+            (Some(i), Some(j)) => cell(&self[(*i, *j)]),
+            _ => "...".to_string()
+          })
+          .collect()
+      })
+      .collect();
+
+    let widths: Vec<usize> = (0..cols.len())
+      .map(|j| {
+        cells
+          .iter()
+          .zip(rows.iter())
+          .filter(|(_, row)| row.is_some())
+          .map(|(cells, _)| cells[j].chars().count())
+          .max()
+          .unwrap_or(0)
+      })
+      .collect();
+
+    for (i, row) in cells.iter().enumerate() {
+      write!(f, "{}", if i == 0 { "[[" } else { " [" })?;
+      if rows[i].is_none() {
+        write!(f, "...")?;
+      } else {
+        for (j, value) in row.iter().enumerate() {
+          if j > 0 {
+            write!(f, ", ")?;
+          }
+          write!(f, "{:>width$}", value, width = widths[j])?;
+        }
+      }
+      write!(f, "]")?;
+      if i + 1 < cells.len() {
+        writeln!(f, ",")?;
+      }
+    }
+
+    write!(f, "]")
+  }
+}
+
 impl<T: fmt::Display> fmt::Display for Matrix<T> {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-    for i in 0..self.rows {
-      for j in 0..self.cols {
-        write!(f, "{}", self[(i, j)])?;
-      }
-      writeln!(f)?;
+    let options = PrintOptions {
+      precision: f.precision(),
+      ..PrintOptions::default()
+    };
+
+    match options.precision {
+      Some(p) => self.write_grid(f, &options, |x| format!("{:.*}", p, x)),
+      None => self.write_grid(f, &options, |x| format!("{}", x))
+    }
+  }
+}
+
+impl<T: fmt::LowerExp> fmt::LowerExp for Matrix<T> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let options = PrintOptions {
+      precision: f.precision(),
+      scientific: true,
+      ..PrintOptions::default()
+    };
+
+    match options.precision {
+      Some(p) => self.write_grid(f, &options, |x| format!("{:.*e}", p, x)),
+      None => self.write_grid(f, &options, |x| format!("{:e}", x))
+    }
+  }
+}
+
+impl<T: fmt::Display + fmt::LowerExp> fmt::Display for MatrixDisplay<'_, T> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let options = &self.options;
+    match (options.scientific, options.precision) {
+      (true, Some(p)) => self.matrix.write_grid(f, options, |x| format!("{:.*e}", p, x)),
+      (true, None) => self.matrix.write_grid(f, options, |x| format!("{:e}", x)),
+      (false, Some(p)) => self.matrix.write_grid(f, options, |x| format!("{:.*}", p, x)),
+      (false, None) => self.matrix.write_grid(f, options, |x| format!("{}", x))
     }
-    Ok(())
   }
 }
\ No newline at end of file
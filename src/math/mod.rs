@@ -1,2 +1,9 @@
+pub mod fft;
+pub mod half;
+pub mod linalg;
 pub mod matrix;
+pub mod optimize;
+pub mod scalar;
+pub mod shape;
+pub mod sparse;
 pub mod vector;
\ No newline at end of file
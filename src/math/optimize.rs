@@ -0,0 +1,101 @@
+use std::collections::VecDeque;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Minimum {
+  pub x: Vec<f64>,
+  pub value: f64,
+  pub n_iter: usize,
+  pub converged: bool
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+  a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+fn max_abs(values: &[f64]) -> f64 {
+  values.iter().fold(0.0f64, |acc, v| acc.max(v.abs()))
+}
+
+pub fn gradient_descent<F>(mut f: F, x0: Vec<f64>, learning_rate: f64, max_iter: usize, tol: f64) -> Minimum
+where
+  F: FnMut(&[f64]) -> (f64, Vec<f64>)
+{
+  let mut x = x0;
+  let (mut value, mut grad) = f(&x);
+  for iter in 0..max_iter {
+    if max_abs(&grad) <= tol {
+      return Minimum { x, value, n_iter: iter, converged: true };
+    }
+    for (xi, gi) in x.iter_mut().zip(&grad) {
+      *xi -= learning_rate * gi;
+    }
+    (value, grad) = f(&x);
+  }
+  let converged = max_abs(&grad) <= tol;
+  Minimum { x, value, n_iter: max_iter, converged }
+}
+
+pub fn lbfgs<F>(mut f: F, x0: Vec<f64>, memory: usize, max_iter: usize, tol: f64) -> Minimum
+where
+  F: FnMut(&[f64]) -> (f64, Vec<f64>)
+{
+  let mut x = x0;
+  let (mut value, mut grad) = f(&x);
+  let mut history: VecDeque<(Vec<f64>, Vec<f64>, f64)> = VecDeque::with_capacity(memory);
+  for iter in 0..max_iter {
+    if max_abs(&grad) <= tol {
+      return Minimum { x, value, n_iter: iter, converged: true };
+    }
+
+    let mut direction: Vec<f64> = grad.iter().map(|g| -g).collect();
+    let mut alphas = Vec::with_capacity(history.len());
+    for (s, y, rho) in history.iter().rev() {
+      let alpha = rho * dot(s, &direction);
+      direction.iter_mut().zip(y).for_each(|(d, yi)| *d -= alpha * yi);
+      alphas.push(alpha);
+    }
+    let scale = match history.back() {
+      Some((s, y, _)) => dot(s, y) / dot(y, y),
+      None => 1.0 / max_abs(&grad).max(1.0)
+    };
+    direction.iter_mut().for_each(|d| *d *= scale);
+    for ((s, y, rho), alpha) in history.iter().zip(alphas.iter().rev()) {
+      let beta = rho * dot(y, &direction);
+      direction.iter_mut().zip(s).for_each(|(d, si)| *d += (alpha - beta) * si);
+    }
+    let mut slope = dot(&grad, &direction);
+    if slope >= 0.0 {
+      direction = grad.iter().map(|g| -g).collect();
+      slope = -dot(&grad, &grad);
+      history.clear();
+    }
+
+    let mut step = 1.0;
+    let (candidate, next_value, next_grad) = loop {
+      let candidate: Vec<f64> = x.iter().zip(&direction).map(|(xi, d)| xi + step * d).collect();
+      let (next_value, next_grad) = f(&candidate);
+      if next_value <= value + 1e-4 * step * slope || step < 1e-12 {
+        break (candidate, next_value, next_grad);
+      }
+      step *= 0.5;
+    };
+    if step < 1e-12 {
+      return Minimum { x, value, n_iter: iter + 1, converged: false };
+    }
+
+    let s: Vec<f64> = candidate.iter().zip(&x).map(|(a, b)| a - b).collect();
+    let y: Vec<f64> = next_grad.iter().zip(&grad).map(|(a, b)| a - b).collect();
+    let sy = dot(&s, &y);
+    if sy > 1e-10 {
+      if history.len() == memory {
+        history.pop_front();
+      }
+      history.push_back((s, y, 1.0 / sy));
+    }
+    x = candidate;
+    value = next_value;
+    grad = next_grad;
+  }
+  let converged = max_abs(&grad) <= tol;
+  Minimum { x, value, n_iter: max_iter, converged }
+}
@@ -0,0 +1,140 @@
+use std::fmt::Debug;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+pub trait Scalar:
+  Copy
+  + Default
+  + PartialEq
+  + Debug
+  + Add<Output = Self>
+  + Sub<Output = Self>
+  + Mul<Output = Self>
+  + Div<Output = Self>
+  + AddAssign
+  + SubAssign
+  + MulAssign
+  + DivAssign
+{
+  fn zero() -> Self;
+  fn one() -> Self;
+  fn checked_neg(self) -> Option<Self>;
+}
+
+pub trait RealScalar: Scalar + PartialOrd + Neg<Output = Self> {
+  fn abs(self) -> Self;
+}
+
+pub trait Float: RealScalar + Send + Sync + 'static {
+  fn epsilon() -> Self;
+  fn infinity() -> Self;
+  fn neg_infinity() -> Self;
+  fn from_f64(value: f64) -> Self;
+  fn to_f64(self) -> f64;
+  fn sqrt(self) -> Self;
+  fn exp(self) -> Self;
+  fn ln(self) -> Self;
+  fn powi(self, n: i32) -> Self;
+  fn powf(self, n: Self) -> Self;
+  fn tanh(self) -> Self;
+  fn is_nan(self) -> bool;
+  fn max(self, other: Self) -> Self;
+  fn min(self, other: Self) -> Self;
+}
+
+macro_rules! impl_scalar {
+  ($zero:expr, $one:expr, |$x:ident| $neg:expr, $($t:ty),*) => {
+    $(impl Scalar for $t {
+      fn zero() -> Self {
+        $zero
+      }
+
+      fn one() -> Self {
+        $one
+      }
+
+      fn checked_neg(self) -> Option<Self> {
+        let $x = self;
+        $neg
+      }
+    })*
+  };
+}
+
+impl_scalar!(0, 1, |x| x.checked_neg(), i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+impl_scalar!(0.0, 1.0, |x| Some(-x), f32, f64);
+
+macro_rules! impl_real_scalar {
+  ($($t:ty),*) => {
+    $(impl RealScalar for $t {
+      fn abs(self) -> Self {
+        <$t>::abs(self)
+      }
+    })*
+  };
+}
+
+impl_real_scalar!(i8, i16, i32, i64, i128, isize, f32, f64);
+
+macro_rules! impl_float {
+  ($($t:ident),*) => {
+    $(impl Float for $t {
+      fn epsilon() -> Self {
+        $t::EPSILON
+      }
+
+      fn infinity() -> Self {
+        $t::INFINITY
+      }
+
+      fn neg_infinity() -> Self {
+        $t::NEG_INFINITY
+      }
+
+      fn from_f64(value: f64) -> Self {
+        value as $t
+      }
+
+      fn to_f64(self) -> f64 {
+        self as f64
+      }
+
+      fn sqrt(self) -> Self {
+        $t::sqrt(self)
+      }
+
+      fn exp(self) -> Self {
+        $t::exp(self)
+      }
+
+      fn ln(self) -> Self {
+        $t::ln(self)
+      }
+
+      fn powi(self, n: i32) -> Self {
+        $t::powi(self, n)
+      }
+
+      fn powf(self, n: Self) -> Self {
+        $t::powf(self, n)
+      }
+
+      fn tanh(self) -> Self {
+        $t::tanh(self)
+      }
+
+      fn is_nan(self) -> bool {
+        $t::is_nan(self)
+      }
+
+      fn max(self, other: Self) -> Self {
+        $t::max(self, other)
+      }
+
+      fn min(self, other: Self) -> Self {
+        $t::min(self, other)
+      }
+    })*
+  };
+}
+
+impl_float!(f32, f64);
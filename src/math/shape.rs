@@ -0,0 +1,104 @@
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Shape {
+  pub rows: usize,
+  pub cols: usize
+}
+
+impl Shape {
+  pub fn new(rows: usize, cols: usize) -> Self {
+    Shape {
+      rows,
+      cols
+    }
+  }
+
+  pub fn size(&self) -> usize {
+    self.rows * self.cols
+  }
+
+  pub fn is_square(&self) -> bool {
+    self.rows == self.cols
+  }
+
+  pub fn check_same(&self, other: &Shape, op: &'static str) -> Result<(), ShapeError> {
+    if self != other {
+      return Err(ShapeError::Mismatch { op, left: *self, right: *other });
+    }
+
+    Ok(())
+  }
+
+  pub fn check_matmul(&self, other: &Shape) -> Result<Shape, ShapeError> {
+    if self.cols != other.rows {
+      return Err(ShapeError::MatmulIncompatible { left: *self, right: *other });
+    }
+
+    Ok(Shape::new(self.rows, other.cols))
+  }
+
+  pub fn check_square(&self, op: &'static str) -> Result<(), ShapeError> {
+    if !self.is_square() {
+      return Err(ShapeError::NotSquare { op, shape: *self });
+    }
+
+    Ok(())
+  }
+}
+
+impl From<(usize, usize)> for Shape {
+  fn from((rows, cols): (usize, usize)) -> Self {
+    Shape::new(rows, cols)
+  }
+}
+
+impl fmt::Display for Shape {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}x{}", self.rows, self.cols)
+  }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ShapeError {
+  Mismatch { op: &'static str, left: Shape, right: Shape },
+  MatmulIncompatible { left: Shape, right: Shape },
+  NotSquare { op: &'static str, shape: Shape },
+  SizeMismatch { expected: Shape, len: usize },
+  TensorSize { shape: Vec<usize>, len: usize },
+  Broadcast { left: Vec<usize>, right: Vec<usize> }
+}
+
+impl fmt::Display for ShapeError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      ShapeError::Mismatch { op, left, right } => {
+        write!(f, "{}: shape mismatch between {} and {}", op, left, right)
+      }
+      ShapeError::MatmulIncompatible { left, right } => {
+        write!(f, "matmul: cannot multiply {} by {} (inner dimensions {} != {})", left, right, left.cols, right.rows)
+      }
+      ShapeError::NotSquare { op, shape } => {
+        write!(f, "{}: expected a square matrix, found {}", op, shape)
+      }
+      ShapeError::SizeMismatch { expected, len } => {
+        write!(f, "{} elements do not fit a {} shape ({} expected)", len, expected, expected.size())
+      }
+      ShapeError::TensorSize { shape, len } => match shape.iter().try_fold(1usize, |n, &dim| n.checked_mul(dim)) {
+        Some(expected) => write!(f, "{} elements do not fit a {:?} shape ({} expected)", len, shape, expected),
+        None => write!(f, "{} elements do not fit a {:?} shape (too many elements)", len, shape)
+      },
+      ShapeError::Broadcast { left, right } => {
+        write!(f, "cannot broadcast shapes {:?} and {:?}", left, right)
+      }
+    }
+  }
+}
+
+impl std::error::Error for ShapeError {}
+
+impl From<ShapeError> for String {
+  fn from(err: ShapeError) -> Self {
+    err.to_string()
+  }
+}
@@ -0,0 +1,96 @@
+use super::matrix::Matrix;
+use super::scalar::Float;
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CsrMatrix<T = f64> {
+  pub rows: usize,
+  pub cols: usize,
+  indptr: Vec<usize>,
+  indices: Vec<usize>,
+  values: Vec<T>
+}
+
+impl<T: Float> CsrMatrix<T> {
+  pub fn new(rows: usize, cols: usize, indptr: Vec<usize>, indices: Vec<usize>, values: Vec<T>) -> Result<Self, String> {
+    if indptr.len() != rows + 1 || indptr[0] != 0 || indptr.windows(2).any(|w| w[0] > w[1]) {
+      return Err(format!("CsrMatrix: indptr must be {} non-decreasing offsets starting at 0", rows + 1));
+    }
+    if indices.len() != values.len() || indptr[rows] != values.len() {
+      return Err(format!("CsrMatrix: {} indices and {} values for {} stored entries", indices.len(), values.len(), indptr[rows]));
+    }
+    if let Some(&col) = indices.iter().find(|&&col| col >= cols) {
+      return Err(format!("CsrMatrix: column index {} out of range for {} columns", col, cols));
+    }
+    Ok(CsrMatrix { rows, cols, indptr, indices, values })
+  }
+
+  pub fn from_rows(cols: usize, rows: Vec<Vec<(usize, T)>>) -> Result<Self, String> {
+    let mut indptr = vec![0];
+    let mut indices = Vec::new();
+    let mut values = Vec::new();
+    for mut row in rows {
+      row.sort_by_key(|&(col, _)| col);
+      for (col, value) in row {
+        if indices.len() > *indptr.last().unwrap_or(&0) && indices.last() == Some(&col) {
+          let last = values.len() - 1;
+          values[last] += value;
+        } else {
+          indices.push(col);
+          values.push(value);
+        }
+      }
+      indptr.push(values.len());
+    }
+    CsrMatrix::new(indptr.len() - 1, cols, indptr, indices, values)
+  }
+
+  pub fn nnz(&self) -> usize {
+    self.values.len()
+  }
+
+  pub fn indptr(&self) -> &[usize] {
+    &self.indptr
+  }
+
+  pub fn indices(&self) -> &[usize] {
+    &self.indices
+  }
+
+  pub fn values(&self) -> &[T] {
+    &self.values
+  }
+
+  pub fn row(&self, row: usize) -> impl Iterator<Item = (usize, T)> + '_ {
+    let range = self.indptr[row]..self.indptr[row + 1];
+    self.indices[range.clone()].iter().copied().zip(self.values[range].iter().copied())
+  }
+
+  pub fn get(&self, row: usize, col: usize) -> T {
+    self.row(row).find(|&(c, _)| c == col).map_or(T::zero(), |(_, value)| value)
+  }
+
+  pub fn to_dense(&self) -> Matrix<T> {
+    let mut dense = Matrix::from_fn(self.rows, self.cols, |_, _| T::zero());
+    for row in 0..self.rows {
+      for (col, value) in self.row(row) {
+        dense.data[row * self.cols + col] = value;
+      }
+    }
+    dense
+  }
+  pub fn matmul(&self, other: &Matrix<T>) -> Result<Matrix<T>, String> {
+    if other.rows != self.cols {
+      return Err(format!("CsrMatrix: cannot multiply {}x{} by {}x{}", self.rows, self.cols, other.rows, other.cols));
+    }
+    let mut product = Matrix::from_fn(self.rows, other.cols, |_, _| T::zero());
+    for row in 0..self.rows {
+      for (k, value) in self.row(row) {
+        for col in 0..other.cols {
+          product.data[row * other.cols + col] += value * other.data[k * other.cols + col];
+        }
+      }
+    }
+    Ok(product)
+  }
+}
@@ -1,6 +1,9 @@
 use std::ops::{Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Sub, SubAssign};
 
-#[derive(Clone, PartialEq)]
+use super::scalar::{Float, Scalar};
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vector<T = f64> {
   pub data: Vec<T>
 }
@@ -13,6 +16,12 @@ impl<T> From<Vec<T>> for Vector<T> {
   }
 }
 
+impl<T> Default for Vector<T> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
 impl<T> Vector<T> {
   pub fn new() -> Self {
     Vector {
@@ -71,6 +80,7 @@ impl<T> Vector<T> {
     self.data.iter_mut()
   }
 
+  #[allow(clippy::inherent_to_string)]
   pub fn to_string(&self) -> String
   where
     T: std::fmt::Display
@@ -325,41 +335,32 @@ where
 
 impl<T> Vector<T>
 where
-  T: Mul<Output = T> + Add<Output = T> + Default + Copy
+  T: Scalar
 {
   pub fn dot(&self, rhs: &Self) -> T {
     self.data
       .iter()
       .zip(rhs.data.iter())
       .map(|(a, b)| *a * *b)
-      .fold(T::default(), |acc, x| acc + x)
+      .fold(T::zero(), |acc, x| acc + x)
   }
 }
 
 impl<T> Vector<T>
 where
-  T: Mul<Output = T> + Add<Output = T> + Div<Output = T> + Copy + Default + PartialEq + From<f64> + Into<f64>
+  T: Float
 {
-  pub fn normalize(&self) -> Self {
-    let length_squared: f64 = self.dot(self).into();
-    let length = length_squared.sqrt();
-    if length > 0.0 {
-      self.scalar_div(T::from(length))
-    } else {
-      Self::from_elem(T::default(), self.len())
-    }
-  }
-}
-
-impl Vector<f32> {
-  pub fn l2_norm(&self) -> f32 {
+  pub fn l2_norm(&self) -> T {
     self.dot(self).sqrt()
   }
-}
 
-impl Vector<f64> {
-  pub fn l2_norm(&self) -> f64 {
-    self.dot(self).sqrt()
+  pub fn normalize(&self) -> Self {
+    let length = self.l2_norm();
+    if length > T::zero() {
+      self.scalar_div(length)
+    } else {
+      Self::from_elem(T::zero(), self.len())
+    }
   }
 }
 
@@ -0,0 +1,251 @@
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Averaging {
+  Micro,
+  #[default]
+  Macro,
+  Weighted
+}
+
+fn check_labels(name: &str, y_true: &[usize], y_pred: &[usize]) -> Result<(), String> {
+  if y_true.is_empty() {
+    return Err(format!("{}: no labels", name));
+  }
+  if y_true.len() != y_pred.len() {
+    return Err(format!("{}: {} targets but {} predictions", name, y_true.len(), y_pred.len()));
+  }
+  Ok(())
+}
+
+fn divide(numerator: f64, denominator: f64) -> f64 {
+  if denominator > 0.0 {
+    numerator / denominator
+  } else {
+    0.0
+  }
+}
+
+pub fn accuracy(y_true: &[usize], y_pred: &[usize]) -> Result<f64, String> {
+  check_labels("accuracy", y_true, y_pred)?;
+  let correct = y_true.iter().zip(y_pred).filter(|(t, p)| t == p).count();
+  Ok(correct as f64 / y_true.len() as f64)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConfusionMatrix {
+  pub labels: Vec<usize>,
+  pub counts: Vec<Vec<usize>>
+}
+
+impl ConfusionMatrix {
+  pub fn n_classes(&self) -> usize {
+    self.labels.len()
+  }
+
+  pub fn total(&self) -> usize {
+    self.counts.iter().flatten().sum()
+  }
+
+  pub fn get(&self, actual: usize, predicted: usize) -> usize {
+    match (self.index(actual), self.index(predicted)) {
+      (Some(i), Some(j)) => self.counts[i][j],
+      _ => 0
+    }
+  }
+
+  pub fn true_positives(&self, label: usize) -> usize {
+    self.get(label, label)
+  }
+
+  pub fn support(&self, label: usize) -> usize {
+    self.index(label).map_or(0, |i| self.counts[i].iter().sum())
+  }
+
+  pub fn predicted(&self, label: usize) -> usize {
+    self.index(label).map_or(0, |j| self.counts.iter().map(|row| row[j]).sum())
+  }
+
+  fn index(&self, label: usize) -> Option<usize> {
+    self.labels.binary_search(&label).ok()
+  }
+
+  fn average<F>(&self, averaging: Averaging, score: F) -> f64
+  where
+    F: Fn(f64, f64, f64) -> f64
+  {
+    let counts = self
+      .labels
+      .iter()
+      .map(|&label| (self.true_positives(label) as f64, self.predicted(label) as f64, self.support(label) as f64));
+    match averaging {
+      Averaging::Micro => {
+        let (tp, predicted, actual) = counts.fold((0.0, 0.0, 0.0), |acc, c| (acc.0 + c.0, acc.1 + c.1, acc.2 + c.2));
+        score(tp, predicted, actual)
+      }
+      Averaging::Macro => counts.map(|(tp, predicted, actual)| score(tp, predicted, actual)).sum::<f64>() / self.n_classes() as f64,
+      Averaging::Weighted => {
+        let weighted: f64 = counts.map(|(tp, predicted, actual)| actual * score(tp, predicted, actual)).sum();
+        divide(weighted, self.total() as f64)
+      }
+    }
+  }
+
+  pub fn precision(&self, averaging: Averaging) -> f64 {
+    self.average(averaging, |tp, predicted, _| divide(tp, predicted))
+  }
+
+  pub fn recall(&self, averaging: Averaging) -> f64 {
+    self.average(averaging, |tp, _, actual| divide(tp, actual))
+  }
+
+  pub fn f1(&self, averaging: Averaging) -> f64 {
+    self.average(averaging, |tp, predicted, actual| divide(2.0 * tp, predicted + actual))
+  }
+
+  pub fn accuracy(&self) -> f64 {
+    divide(self.labels.iter().map(|&label| self.true_positives(label)).sum::<usize>() as f64, self.total() as f64)
+  }
+}
+
+impl fmt::Display for ConfusionMatrix {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let width = self
+      .counts
+      .iter()
+      .flatten()
+      .map(|c| c.to_string().len())
+      .chain(self.labels.iter().map(|l| l.to_string().len()))
+      .max()
+      .unwrap_or(1);
+    write!(f, "{:>width$}", "")?;
+    for label in &self.labels {
+      write!(f, "  {:>width$}", label)?;
+    }
+    for (label, row) in self.labels.iter().zip(&self.counts) {
+      write!(f, "\n{:>width$}", label)?;
+      for count in row {
+        write!(f, "  {:>width$}", count)?;
+      }
+    }
+    Ok(())
+  }
+}
+
+pub fn confusion_matrix(y_true: &[usize], y_pred: &[usize]) -> Result<ConfusionMatrix, String> {
+  check_labels("confusion_matrix", y_true, y_pred)?;
+  let mut labels: Vec<usize> = y_true.iter().chain(y_pred).copied().collect();
+  labels.sort_unstable();
+  labels.dedup();
+
+  let mut counts = vec![vec![0; labels.len()]; labels.len()];
+  for (t, p) in y_true.iter().zip(y_pred) {
+    let i = labels.binary_search(t).unwrap();
+    let j = labels.binary_search(p).unwrap();
+    counts[i][j] += 1;
+  }
+  Ok(ConfusionMatrix { labels, counts })
+}
+
+pub fn precision(y_true: &[usize], y_pred: &[usize], averaging: Averaging) -> Result<f64, String> {
+  Ok(confusion_matrix(y_true, y_pred)?.precision(averaging))
+}
+
+pub fn recall(y_true: &[usize], y_pred: &[usize], averaging: Averaging) -> Result<f64, String> {
+  Ok(confusion_matrix(y_true, y_pred)?.recall(averaging))
+}
+
+pub fn f1(y_true: &[usize], y_pred: &[usize], averaging: Averaging) -> Result<f64, String> {
+  Ok(confusion_matrix(y_true, y_pred)?.f1(averaging))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClassScores {
+  pub precision: f64,
+  pub recall: f64,
+  pub f1: f64,
+  pub support: usize
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClassificationReport {
+  pub labels: Vec<usize>,
+  pub classes: Vec<ClassScores>,
+  pub accuracy: f64,
+  pub macro_avg: ClassScores,
+  pub weighted_avg: ClassScores
+}
+
+impl ClassificationReport {
+  pub fn get(&self, label: usize) -> Option<&ClassScores> {
+    self.labels.binary_search(&label).ok().map(|i| &self.classes[i])
+  }
+}
+
+impl fmt::Display for ClassificationReport {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let header = ["", "precision", "recall", "f1-score", "support"];
+    let scores = |name: String, s: &ClassScores| {
+      [name, format!("{:.4}", s.precision), format!("{:.4}", s.recall), format!("{:.4}", s.f1), s.support.to_string()]
+    };
+    let rows: Vec<[String; 5]> = self.labels.iter().zip(&self.classes).map(|(label, s)| scores(label.to_string(), s)).collect();
+    let accuracy = [
+      "accuracy".to_string(),
+      String::new(),
+      String::new(),
+      format!("{:.4}", self.accuracy),
+      self.macro_avg.support.to_string()
+    ];
+    let averages = [scores("macro avg".to_string(), &self.macro_avg), scores("weighted avg".to_string(), &self.weighted_avg)];
+
+    let mut widths = header.map(str::len);
+    for row in rows.iter().chain([&accuracy]).chain(&averages) {
+      for (width, cell) in widths.iter_mut().zip(row) {
+        *width = (*width).max(cell.len());
+      }
+    }
+    let line = |cells: &[String; 5]| {
+      let padded: Vec<String> = cells.iter().zip(&widths).map(|(cell, &width)| format!("{:>width$}", cell)).collect();
+      padded.join("  ")
+    };
+
+    let mut lines = vec![line(&header.map(str::to_string)), String::new()];
+    lines.extend(rows.iter().map(line));
+    lines.push(String::new());
+    lines.push(line(&accuracy));
+    lines.extend(averages.iter().map(line));
+    write!(f, "{}", lines.join("\n"))
+  }
+}
+
+pub fn classification_report(y_true: &[usize], y_pred: &[usize]) -> Result<ClassificationReport, String> {
+  check_labels("classification_report", y_true, y_pred)?;
+  let matrix = confusion_matrix(y_true, y_pred)?;
+  let classes = matrix
+    .labels
+    .iter()
+    .map(|&label| {
+      let (tp, predicted, actual) = (matrix.true_positives(label) as f64, matrix.predicted(label) as f64, matrix.support(label));
+      ClassScores {
+        precision: divide(tp, predicted),
+        recall: divide(tp, actual as f64),
+        f1: divide(2.0 * tp, predicted + actual as f64),
+        support: actual
+      }
+    })
+    .collect();
+  let averaged = |averaging| ClassScores {
+    precision: matrix.precision(averaging),
+    recall: matrix.recall(averaging),
+    f1: matrix.f1(averaging),
+    support: matrix.total()
+  };
+
+  Ok(ClassificationReport {
+    accuracy: matrix.accuracy(),
+    macro_avg: averaged(Averaging::Macro),
+    weighted_avg: averaged(Averaging::Weighted),
+    labels: matrix.labels.clone(),
+    classes
+  })
+}
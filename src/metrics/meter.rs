@@ -0,0 +1,169 @@
+use crate::math::scalar::Float;
+use crate::tensor::Tensor;
+
+pub type MetricFn<T> = Box<dyn Fn(&Tensor<T>, &Tensor<T>) -> Result<f64, String>>;
+
+pub trait Meter<T: Float = f64> {
+  fn name(&self) -> &str;
+
+  fn update(&mut self, output: &Tensor<T>, target: &Tensor<T>) -> Result<(), String>;
+
+  fn value(&self) -> f64;
+
+  fn reset(&mut self);
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Average {
+  sum: f64,
+  weight: f64
+}
+
+impl Average {
+  pub fn new() -> Self {
+    Average::default()
+  }
+
+  pub fn add(&mut self, value: f64, weight: f64) {
+    self.sum += value * weight;
+    self.weight += weight;
+  }
+
+  pub fn value(&self) -> f64 {
+    if self.weight > 0.0 {
+      self.sum / self.weight
+    } else {
+      f64::NAN
+    }
+  }
+
+  pub fn weight(&self) -> f64 {
+    self.weight
+  }
+
+  pub fn reset(&mut self) {
+    *self = Average::default();
+  }
+}
+
+fn batch_size<T>(tensor: &Tensor<T>) -> f64 {
+  tensor.shape().first().copied().unwrap_or(1) as f64
+}
+
+pub struct MeanMetric<T: Float = f64> {
+  name: String,
+  metric: MetricFn<T>,
+  average: Average
+}
+
+impl<T: Float> MeanMetric<T> {
+  pub fn new<F>(name: &str, metric: F) -> Self
+  where
+    F: Fn(&Tensor<T>, &Tensor<T>) -> Result<f64, String> + 'static
+  {
+    MeanMetric { name: name.to_string(), metric: Box::new(metric), average: Average::new() }
+  }
+}
+
+impl<T: Float> Meter<T> for MeanMetric<T> {
+  fn name(&self) -> &str {
+    &self.name
+  }
+
+  fn update(&mut self, output: &Tensor<T>, target: &Tensor<T>) -> Result<(), String> {
+    self.average.add((self.metric)(output, target)?, batch_size(output));
+    Ok(())
+  }
+
+  fn value(&self) -> f64 {
+    self.average.value()
+  }
+
+  fn reset(&mut self) {
+    self.average.reset();
+  }
+}
+
+pub struct Accuracy {
+  pub threshold: f64,
+  name: String,
+  correct: usize,
+  total: usize
+}
+
+impl Accuracy {
+  pub fn new() -> Self {
+    Accuracy::named("accuracy")
+  }
+
+  pub fn named(name: &str) -> Self {
+    Accuracy { threshold: 0.5, name: name.to_string(), correct: 0, total: 0 }
+  }
+
+  pub fn correct(&self) -> usize {
+    self.correct
+  }
+
+  pub fn total(&self) -> usize {
+    self.total
+  }
+
+  fn labels<T: Float>(&self, tensor: &Tensor<T>, classes: usize) -> Result<Vec<usize>, String> {
+    if classes > 1 {
+      Ok(tensor.argmax(tensor.ndim() - 1)?.to_vec())
+    } else {
+      Ok(tensor.iter().map(|&x| (x.to_f64() >= self.threshold) as usize).collect())
+    }
+  }
+}
+
+impl Default for Accuracy {
+  fn default() -> Self {
+    Accuracy::new()
+  }
+}
+
+impl<T: Float> Meter<T> for Accuracy {
+  fn name(&self) -> &str {
+    &self.name
+  }
+
+  fn update(&mut self, output: &Tensor<T>, target: &Tensor<T>) -> Result<(), String> {
+    let classes = if output.ndim() > 1 { output.shape()[output.ndim() - 1] } else { 1 };
+    let predicted = self.labels(output, classes)?;
+    let expected = if target.shape() == output.shape() && classes > 1 {
+      self.labels(target, classes)?
+    } else if classes > 1 {
+      target.iter().map(|&x| x.to_f64().round() as usize).collect()
+    } else {
+      self.labels(target, 1)?
+    };
+
+    if predicted.len() != expected.len() {
+      return Err(format!(
+        "Accuracy: {} predictions from output {:?} but {} labels from target {:?}",
+        predicted.len(),
+        output.shape(),
+        expected.len(),
+        target.shape()
+      ));
+    }
+
+    self.correct += predicted.iter().zip(&expected).filter(|(p, e)| p == e).count();
+    self.total += predicted.len();
+    Ok(())
+  }
+
+  fn value(&self) -> f64 {
+    if self.total == 0 {
+      f64::NAN
+    } else {
+      self.correct as f64 / self.total as f64
+    }
+  }
+
+  fn reset(&mut self) {
+    self.correct = 0;
+    self.total = 0;
+  }
+}
@@ -0,0 +1,13 @@
+pub mod classification;
+pub mod meter;
+pub mod ranking;
+pub mod regression;
+
+pub use classification::{
+  accuracy, classification_report, confusion_matrix, f1, precision, recall, Averaging, ClassScores, ClassificationReport, ConfusionMatrix
+};
+pub use meter::{Accuracy, Average, MeanMetric, Meter};
+pub use ranking::{
+  average_precision, average_precision_ovr, brier_score, precision_recall_curve, roc_auc, roc_auc_ovr, roc_curve, PrecisionRecallCurve, RocCurve
+};
+pub use regression::{explained_variance, mae, mape, mse, r2_score, rmse};
@@ -0,0 +1,187 @@
+use super::classification::Averaging;
+use crate::math::matrix::Matrix;
+use crate::math::scalar::Float;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RocCurve {
+  pub fpr: Vec<f64>,
+  pub tpr: Vec<f64>,
+  pub thresholds: Vec<f64>
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PrecisionRecallCurve {
+  pub precision: Vec<f64>,
+  pub recall: Vec<f64>,
+  pub thresholds: Vec<f64>
+}
+
+struct Operating {
+  threshold: f64,
+  tp: f64,
+  fp: f64
+}
+
+// Cumulative true/false positives at every distinct score, highest first, so
+// tied scores always move together and never get an arbitrary order.
+fn operating_points(name: &str, y_true: &[bool], scores: &[f64]) -> Result<Vec<Operating>, String> {
+  if y_true.is_empty() {
+    return Err(format!("{}: no samples", name));
+  }
+  if y_true.len() != scores.len() {
+    return Err(format!("{}: {} targets but {} scores", name, y_true.len(), scores.len()));
+  }
+  if scores.iter().any(|s| s.is_nan()) {
+    return Err(format!("{}: scores contain NaN", name));
+  }
+
+  let mut order: Vec<usize> = (0..scores.len()).collect();
+  order.sort_by(|&a, &b| scores[b].total_cmp(&scores[a]));
+
+  let mut points = Vec::new();
+  let (mut tp, mut fp) = (0.0, 0.0);
+  for (k, &i) in order.iter().enumerate() {
+    if y_true[i] {
+      tp += 1.0;
+    } else {
+      fp += 1.0;
+    }
+    if order.get(k + 1).is_none_or(|&next| scores[next] != scores[i]) {
+      points.push(Operating { threshold: scores[i], tp, fp });
+    }
+  }
+  Ok(points)
+}
+
+fn counts(name: &str, y_true: &[bool]) -> Result<(f64, f64), String> {
+  let positives = y_true.iter().filter(|&&t| t).count() as f64;
+  let negatives = y_true.len() as f64 - positives;
+  if positives == 0.0 {
+    return Err(format!("{}: no positive samples", name));
+  }
+  Ok((positives, negatives))
+}
+
+fn binary_roc(name: &str, y_true: &[bool], scores: &[f64]) -> Result<RocCurve, String> {
+  let points = operating_points(name, y_true, scores)?;
+  let (positives, negatives) = counts(name, y_true)?;
+  if negatives == 0.0 {
+    return Err(format!("{}: no negative samples", name));
+  }
+
+  let mut curve = RocCurve { fpr: vec![0.0], tpr: vec![0.0], thresholds: vec![f64::INFINITY] };
+  for point in points {
+    curve.fpr.push(point.fp / negatives);
+    curve.tpr.push(point.tp / positives);
+    curve.thresholds.push(point.threshold);
+  }
+  Ok(curve)
+}
+
+fn binary_pr(name: &str, y_true: &[bool], scores: &[f64]) -> Result<PrecisionRecallCurve, String> {
+  let points = operating_points(name, y_true, scores)?;
+  let (positives, _) = counts(name, y_true)?;
+
+  let mut curve = PrecisionRecallCurve { precision: vec![1.0], recall: vec![0.0], thresholds: vec![f64::INFINITY] };
+  for point in points {
+    curve.precision.push(point.tp / (point.tp + point.fp));
+    curve.recall.push(point.tp / positives);
+    curve.thresholds.push(point.threshold);
+  }
+  Ok(curve)
+}
+
+fn binary_auc(name: &str, y_true: &[bool], scores: &[f64]) -> Result<f64, String> {
+  let curve = binary_roc(name, y_true, scores)?;
+  Ok(curve.fpr.windows(2).zip(curve.tpr.windows(2)).map(|(x, y)| (x[1] - x[0]) * (y[1] + y[0]) / 2.0).sum())
+}
+
+fn binary_ap(name: &str, y_true: &[bool], scores: &[f64]) -> Result<f64, String> {
+  let curve = binary_pr(name, y_true, scores)?;
+  Ok(curve.recall.windows(2).zip(&curve.precision[1..]).map(|(r, p)| (r[1] - r[0]) * p).sum())
+}
+
+fn to_f64<T: Float>(scores: &[T]) -> Vec<f64> {
+  scores.iter().map(|s| s.to_f64()).collect()
+}
+
+pub fn brier_score<T: Float>(y_true: &[bool], proba: &[T]) -> Result<f64, String> {
+  if y_true.is_empty() {
+    return Err("brier_score: no samples".to_string());
+  }
+  if y_true.len() != proba.len() {
+    return Err(format!("brier_score: {} targets but {} probabilities", y_true.len(), proba.len()));
+  }
+  let proba = to_f64(proba);
+  if proba.iter().any(|p| !(0.0..=1.0).contains(p)) {
+    return Err("brier_score: probabilities must lie in [0, 1]".to_string());
+  }
+  Ok(y_true.iter().zip(&proba).map(|(&t, p)| (p - if t { 1.0 } else { 0.0 }).powi(2)).sum::<f64>() / y_true.len() as f64)
+}
+
+pub fn roc_curve<T: Float>(y_true: &[bool], scores: &[T]) -> Result<RocCurve, String> {
+  binary_roc("roc_curve", y_true, &to_f64(scores))
+}
+
+pub fn precision_recall_curve<T: Float>(y_true: &[bool], scores: &[T]) -> Result<PrecisionRecallCurve, String> {
+  binary_pr("precision_recall_curve", y_true, &to_f64(scores))
+}
+
+pub fn roc_auc<T: Float>(y_true: &[bool], scores: &[T]) -> Result<f64, String> {
+  binary_auc("roc_auc", y_true, &to_f64(scores))
+}
+
+pub fn average_precision<T: Float>(y_true: &[bool], scores: &[T]) -> Result<f64, String> {
+  binary_ap("average_precision", y_true, &to_f64(scores))
+}
+
+fn one_vs_rest<T, F>(name: &str, y_true: &[usize], scores: &Matrix<T>, classes: &[usize], averaging: Averaging, metric: F) -> Result<f64, String>
+where
+  T: Float,
+  F: Fn(&str, &[bool], &[f64]) -> Result<f64, String>
+{
+  if scores.rows != y_true.len() {
+    return Err(format!("{}: {} targets but {} rows of scores", name, y_true.len(), scores.rows));
+  }
+  if scores.cols != classes.len() || classes.len() < 2 {
+    return Err(format!("{}: {} score columns for {} classes", name, scores.cols, classes.len()));
+  }
+  if let Some(label) = y_true.iter().find(|label| !classes.contains(label)) {
+    return Err(format!("{}: label {} is not one of the classes {:?}", name, label, classes));
+  }
+
+  let column = |j: usize| -> (Vec<bool>, Vec<f64>) {
+    let truth = y_true.iter().map(|&label| label == classes[j]).collect();
+    let score = (0..scores.rows).map(|i| scores.data[i * scores.cols + j].to_f64()).collect();
+    (truth, score)
+  };
+
+  match averaging {
+    Averaging::Micro => {
+      let (truth, score): (Vec<Vec<bool>>, Vec<Vec<f64>>) = (0..classes.len()).map(column).unzip();
+      metric(name, &truth.concat(), &score.concat())
+    }
+    Averaging::Macro | Averaging::Weighted => {
+      let mut total = 0.0;
+      let mut weights = 0.0;
+      for j in 0..classes.len() {
+        let (truth, score) = column(j);
+        let weight = match averaging {
+          Averaging::Weighted => truth.iter().filter(|&&t| t).count() as f64,
+          _ => 1.0
+        };
+        total += weight * metric(name, &truth, &score)?;
+        weights += weight;
+      }
+      Ok(total / weights)
+    }
+  }
+}
+
+pub fn roc_auc_ovr<T: Float>(y_true: &[usize], scores: &Matrix<T>, classes: &[usize], averaging: Averaging) -> Result<f64, String> {
+  one_vs_rest("roc_auc_ovr", y_true, scores, classes, averaging, binary_auc)
+}
+
+pub fn average_precision_ovr<T: Float>(y_true: &[usize], scores: &Matrix<T>, classes: &[usize], averaging: Averaging) -> Result<f64, String> {
+  one_vs_rest("average_precision_ovr", y_true, scores, classes, averaging, binary_ap)
+}
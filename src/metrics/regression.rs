@@ -0,0 +1,84 @@
+use crate::math::scalar::Float;
+
+struct Pairs {
+  y_true: Vec<f64>,
+  y_pred: Vec<f64>,
+  weights: Vec<f64>,
+  total_weight: f64
+}
+
+impl Pairs {
+  fn new<T: Float>(name: &str, y_true: &[T], y_pred: &[T], weights: Option<&[f64]>) -> Result<Self, String> {
+    if y_true.is_empty() {
+      return Err(format!("{}: no samples", name));
+    }
+    if y_true.len() != y_pred.len() {
+      return Err(format!("{}: {} targets but {} predictions", name, y_true.len(), y_pred.len()));
+    }
+    let weights = match weights {
+      Some(weights) if weights.len() != y_true.len() => {
+        return Err(format!("{}: {} targets but {} sample weights", name, y_true.len(), weights.len()));
+      }
+      Some(weights) if weights.iter().any(|w| !(w.is_finite() && *w >= 0.0)) => {
+        return Err(format!("{}: sample weights must be finite and non-negative", name));
+      }
+      Some(weights) => weights.to_vec(),
+      None => vec![1.0; y_true.len()]
+    };
+    let total_weight: f64 = weights.iter().sum();
+    if total_weight <= 0.0 {
+      return Err(format!("{}: sample weights sum to zero", name));
+    }
+
+    Ok(Pairs {
+      y_true: y_true.iter().map(|t| t.to_f64()).collect(),
+      y_pred: y_pred.iter().map(|p| p.to_f64()).collect(),
+      weights,
+      total_weight
+    })
+  }
+
+  fn mean<F>(&self, f: F) -> f64
+  where
+    F: Fn(f64, f64) -> f64
+  {
+    self.y_true.iter().zip(&self.y_pred).zip(&self.weights).map(|((&t, &p), w)| w * f(t, p)).sum::<f64>() / self.total_weight
+  }
+}
+
+fn fraction_explained(residual: f64, total: f64) -> f64 {
+  if total == 0.0 {
+    if residual == 0.0 { 1.0 } else { 0.0 }
+  } else {
+    1.0 - residual / total
+  }
+}
+
+pub fn mse<T: Float>(y_true: &[T], y_pred: &[T], weights: Option<&[f64]>) -> Result<f64, String> {
+  Ok(Pairs::new("mse", y_true, y_pred, weights)?.mean(|t, p| (t - p).powi(2)))
+}
+
+pub fn rmse<T: Float>(y_true: &[T], y_pred: &[T], weights: Option<&[f64]>) -> Result<f64, String> {
+  Ok(Pairs::new("rmse", y_true, y_pred, weights)?.mean(|t, p| (t - p).powi(2)).sqrt())
+}
+
+pub fn mae<T: Float>(y_true: &[T], y_pred: &[T], weights: Option<&[f64]>) -> Result<f64, String> {
+  Ok(Pairs::new("mae", y_true, y_pred, weights)?.mean(|t, p| (t - p).abs()))
+}
+
+pub fn mape<T: Float>(y_true: &[T], y_pred: &[T], weights: Option<&[f64]>) -> Result<f64, String> {
+  Ok(Pairs::new("mape", y_true, y_pred, weights)?.mean(|t, p| (t - p).abs() / t.abs().max(f64::EPSILON)))
+}
+
+pub fn r2_score<T: Float>(y_true: &[T], y_pred: &[T], weights: Option<&[f64]>) -> Result<f64, String> {
+  let pairs = Pairs::new("r2_score", y_true, y_pred, weights)?;
+  let mean = pairs.mean(|t, _| t);
+  Ok(fraction_explained(pairs.mean(|t, p| (t - p).powi(2)), pairs.mean(|t, _| (t - mean).powi(2))))
+}
+
+pub fn explained_variance<T: Float>(y_true: &[T], y_pred: &[T], weights: Option<&[f64]>) -> Result<f64, String> {
+  let pairs = Pairs::new("explained_variance", y_true, y_pred, weights)?;
+  let mean = pairs.mean(|t, _| t);
+  let mean_error = pairs.mean(|t, p| t - p);
+  Ok(fraction_explained(pairs.mean(|t, p| (t - p - mean_error).powi(2)), pairs.mean(|t, _| (t - mean).powi(2))))
+}
@@ -0,0 +1,209 @@
+use super::check_samples;
+use super::logistic::sigmoid;
+use crate::math::matrix::Matrix;
+use crate::math::optimize::lbfgs;
+use crate::math::scalar::Float;
+
+const LBFGS_MEMORY: usize = 5;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CalibrationMethod {
+  #[default]
+  Platt,
+  Isotonic
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+enum Mapping {
+  Platt { a: f64, b: f64 },
+  Isotonic { scores: Vec<f64>, proba: Vec<f64> }
+}
+
+impl Mapping {
+  fn apply(&self, score: f64) -> f64 {
+    match self {
+      Mapping::Platt { a, b } => sigmoid(a * score + b),
+      Mapping::Isotonic { scores, proba } => {
+        let last = scores.len() - 1;
+        if score <= scores[0] {
+          return proba[0];
+        }
+        if score >= scores[last] {
+          return proba[last];
+        }
+        let hi = scores.partition_point(|&s| s <= score);
+        let t = (score - scores[hi - 1]) / (scores[hi] - scores[hi - 1]);
+        proba[hi - 1] + t * (proba[hi] - proba[hi - 1])
+      }
+    }
+  }
+}
+
+fn softplus(z: f64) -> f64 {
+  z.max(0.0) + (-z.abs()).exp().ln_1p()
+}
+
+// Platt's smoothed targets keep the fit from collapsing to a step function
+// when the scores separate the classes perfectly.
+fn platt(scores: &[f64], positive: &[bool], max_iter: usize, tol: f64) -> Mapping {
+  let n_pos = positive.iter().filter(|&&p| p).count() as f64;
+  let n_neg = positive.len() as f64 - n_pos;
+  let targets: Vec<f64> = positive.iter().map(|&p| if p { (n_pos + 1.0) / (n_pos + 2.0) } else { 1.0 / (n_neg + 2.0) }).collect();
+  let n = scores.len() as f64;
+
+  let loss = |w: &[f64]| {
+    let mut value = 0.0;
+    let mut grad = vec![0.0; 2];
+    for (&s, &t) in scores.iter().zip(&targets) {
+      let z = w[0] * s + w[1];
+      value += t * softplus(-z) + (1.0 - t) * softplus(z);
+      let residual = sigmoid(z) - t;
+      grad[0] += residual * s / n;
+      grad[1] += residual / n;
+    }
+    (value / n, grad)
+  };
+  let minimum = lbfgs(loss, vec![0.0, ((n_pos + 1.0) / (n_neg + 1.0)).ln()], LBFGS_MEMORY, max_iter, tol);
+  Mapping::Platt { a: minimum.x[0], b: minimum.x[1] }
+}
+
+// Pool-adjacent-violators over the sorted scores; tied scores start out as a
+// single block so the result does not depend on their order.
+fn isotonic(scores: &[f64], positive: &[bool]) -> Mapping {
+  let mut order: Vec<usize> = (0..scores.len()).collect();
+  order.sort_by(|&a, &b| scores[a].total_cmp(&scores[b]));
+
+  // (lowest score, highest score, sum of targets, weight)
+  let mut blocks: Vec<(f64, f64, f64, f64)> = Vec::new();
+  for i in order {
+    let target = if positive[i] { 1.0 } else { 0.0 };
+    match blocks.last_mut() {
+      Some(block) if block.1 == scores[i] => {
+        block.2 += target;
+        block.3 += 1.0;
+      }
+      _ => blocks.push((scores[i], scores[i], target, 1.0))
+    }
+    while blocks.len() > 1 {
+      let (last, previous) = (blocks[blocks.len() - 1], blocks[blocks.len() - 2]);
+      if previous.2 / previous.3 < last.2 / last.3 {
+        break;
+      }
+      blocks.pop();
+      *blocks.last_mut().unwrap() = (previous.0, last.1, previous.2 + last.2, previous.3 + last.3);
+    }
+  }
+
+  let mut knots = Vec::new();
+  let mut proba = Vec::new();
+  for (lo, hi, sum, weight) in blocks {
+    knots.push(lo);
+    proba.push(sum / weight);
+    if hi > lo {
+      knots.push(hi);
+      proba.push(sum / weight);
+    }
+  }
+  Mapping::Isotonic { scores: knots, proba }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Calibrator {
+  pub method: CalibrationMethod,
+  pub max_iter: usize,
+  pub tol: f64,
+  mappings: Vec<Mapping>
+}
+
+impl Calibrator {
+  pub fn new(method: CalibrationMethod) -> Self {
+    Calibrator { method, max_iter: 100, tol: 1e-8, mappings: Vec::new() }
+  }
+
+  pub fn is_fitted(&self) -> bool {
+    !self.mappings.is_empty()
+  }
+
+  pub fn n_classes(&self) -> usize {
+    match self.mappings.len() {
+      1 => 2,
+      k => k
+    }
+  }
+
+  pub fn platt_parameters(&self) -> Vec<(f64, f64)> {
+    self
+      .mappings
+      .iter()
+      .filter_map(|mapping| match mapping {
+        Mapping::Platt { a, b } => Some((*a, *b)),
+        Mapping::Isotonic { .. } => None
+      })
+      .collect()
+  }
+
+  // A single score column is a binary problem with labels 0 and 1; otherwise
+  // column j holds the scores for label j and each is calibrated one-vs-rest.
+  pub fn fit<T: Float>(&mut self, scores: &Matrix<T>, y: &[usize]) -> Result<(), String> {
+    check_samples("Calibrator", scores, y)?;
+    let classes = if scores.cols == 1 { 2 } else { scores.cols };
+    if let Some(label) = y.iter().find(|&&label| label >= classes) {
+      return Err(format!("Calibrator: label {} out of range for {} classes", label, classes));
+    }
+
+    let mut mappings = Vec::new();
+    for j in 0..scores.cols {
+      let label = if scores.cols == 1 { 1 } else { j };
+      let column: Vec<f64> = (0..scores.rows).map(|i| scores.data[i * scores.cols + j].to_f64()).collect();
+      if column.iter().any(|s| !s.is_finite()) {
+        return Err("Calibrator: scores must be finite".to_string());
+      }
+      let positive: Vec<bool> = y.iter().map(|&t| t == label).collect();
+      if positive.iter().all(|&p| p) || !positive.iter().any(|&p| p) {
+        return Err(format!("Calibrator: label {} needs both positive and negative samples", label));
+      }
+      mappings.push(match self.method {
+        CalibrationMethod::Platt => platt(&column, &positive, self.max_iter, self.tol),
+        CalibrationMethod::Isotonic => isotonic(&column, &positive)
+      });
+    }
+    self.mappings = mappings;
+    Ok(())
+  }
+
+  pub fn predict_proba<T: Float>(&self, scores: &Matrix<T>) -> Result<Matrix<T>, String> {
+    if !self.is_fitted() {
+      return Err("Calibrator: predict_proba called before fit".to_string());
+    }
+    if scores.cols != self.mappings.len() {
+      return Err(format!("Calibrator: fitted on {} score columns, got {}", self.mappings.len(), scores.cols));
+    }
+    if scores.data.iter().any(|s| !s.to_f64().is_finite()) {
+      return Err("Calibrator: scores must be finite".to_string());
+    }
+
+    let k = self.n_classes();
+    let mut proba = Vec::with_capacity(scores.rows * k);
+    for i in 0..scores.rows {
+      let row = &scores.data[i * scores.cols..(i + 1) * scores.cols];
+      if self.mappings.len() == 1 {
+        let positive = self.mappings[0].apply(row[0].to_f64());
+        proba.extend([T::from_f64(1.0 - positive), T::from_f64(positive)]);
+        continue;
+      }
+      let calibrated: Vec<f64> = self.mappings.iter().zip(row).map(|(mapping, s)| mapping.apply(s.to_f64())).collect();
+      let total: f64 = calibrated.iter().sum();
+      proba.extend(calibrated.iter().map(|&p| T::from_f64(if total > 0.0 { p / total } else { 1.0 / k as f64 })));
+    }
+    Matrix::from_vec(scores.rows, k, proba)
+  }
+}
+
+impl Default for Calibrator {
+  fn default() -> Self {
+    Calibrator::new(CalibrationMethod::default())
+  }
+}
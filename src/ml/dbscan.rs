@@ -0,0 +1,106 @@
+use std::collections::VecDeque;
+
+use super::kdtree::KdTree;
+use super::{squared_distance, to_f64_matrix};
+use crate::math::matrix::Matrix;
+use crate::math::scalar::Float;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NeighborSearch {
+  Auto,
+  BruteForce,
+  KdTree
+}
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DBSCAN {
+  pub eps: f64,
+  pub min_samples: usize,
+  pub search: NeighborSearch,
+  pub leaf_size: usize,
+  labels: Vec<Option<usize>>,
+  core_samples: Vec<usize>,
+  n_clusters: usize
+}
+
+impl DBSCAN {
+  pub fn new(eps: f64, min_samples: usize) -> Self {
+    DBSCAN { eps, min_samples, search: NeighborSearch::Auto, leaf_size: 30, labels: Vec::new(), core_samples: Vec::new(), n_clusters: 0 }
+  }
+
+  pub fn labels(&self) -> &[Option<usize>] {
+    &self.labels
+  }
+
+  pub fn core_samples(&self) -> &[usize] {
+    &self.core_samples
+  }
+
+  pub fn n_clusters(&self) -> usize {
+    self.n_clusters
+  }
+
+  pub fn n_noise(&self) -> usize {
+    self.labels.iter().filter(|label| label.is_none()).count()
+  }
+
+  fn neighborhoods(&self, x: Matrix<f64>) -> Vec<Vec<usize>> {
+    let use_tree = match self.search {
+      NeighborSearch::Auto => x.cols <= 16,
+      NeighborSearch::BruteForce => false,
+      NeighborSearch::KdTree => true
+    };
+    if use_tree {
+      let tree = KdTree::new(x, self.leaf_size);
+      (0..tree.len()).map(|i| tree.within_radius(tree.point(i), self.eps)).collect()
+    } else {
+      let row = |i: usize| &x.data[i * x.cols..(i + 1) * x.cols];
+      let eps_sq = self.eps * self.eps;
+      (0..x.rows).map(|i| (0..x.rows).filter(|&j| squared_distance(row(i), row(j)) <= eps_sq).collect()).collect()
+    }
+  }
+
+  pub fn fit<T: Float>(&mut self, x: &Matrix<T>) -> Result<(), String> {
+    if !(self.eps > 0.0 && self.eps.is_finite()) || self.min_samples == 0 {
+      return Err(format!("DBSCAN: needs eps > 0 and min_samples > 0, got {} and {}", self.eps, self.min_samples));
+    }
+    if x.cols == 0 {
+      return Err("DBSCAN: cannot cluster samples without features".to_string());
+    }
+    let neighbors = self.neighborhoods(to_f64_matrix(x));
+    let is_core: Vec<bool> = neighbors.iter().map(|n| n.len() >= self.min_samples).collect();
+    let mut labels = vec![None; x.rows];
+    let mut cluster = 0;
+    for start in 0..x.rows {
+      if !is_core[start] || labels[start].is_some() {
+        continue;
+      }
+      labels[start] = Some(cluster);
+      let mut queue = VecDeque::from([start]);
+      while let Some(point) = queue.pop_front() {
+        if !is_core[point] {
+          continue;
+        }
+        for &neighbor in &neighbors[point] {
+          if labels[neighbor].is_none() {
+            labels[neighbor] = Some(cluster);
+            queue.push_back(neighbor);
+          }
+        }
+      }
+      cluster += 1;
+    }
+    self.core_samples = (0..x.rows).filter(|&i| is_core[i]).collect();
+    self.labels = labels;
+    self.n_clusters = cluster;
+    Ok(())
+  }
+
+  pub fn fit_predict<T: Float>(&mut self, x: &Matrix<T>) -> Result<Vec<Option<usize>>, String> {
+    self.fit(x)?;
+    Ok(self.labels.clone())
+  }
+}
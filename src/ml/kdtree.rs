@@ -0,0 +1,81 @@
+use super::squared_distance;
+use crate::math::matrix::Matrix;
+
+#[derive(Clone, Debug)]
+enum Node {
+  Leaf(Vec<usize>),
+  Split { axis: usize, value: f64, left: Box<Node>, right: Box<Node> }
+}
+
+#[derive(Clone, Debug)]
+pub struct KdTree {
+  points: Matrix<f64>,
+  root: Node,
+  leaf_size: usize
+}
+
+impl KdTree {
+  pub fn new(points: Matrix<f64>, leaf_size: usize) -> Self {
+    let leaf_size = leaf_size.max(1);
+    let indices: Vec<usize> = (0..points.rows).collect();
+    let root = build(&points, indices, leaf_size);
+    KdTree { points, root, leaf_size }
+  }
+
+  pub fn len(&self) -> usize {
+    self.points.rows
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.points.rows == 0
+  }
+
+  pub fn leaf_size(&self) -> usize {
+    self.leaf_size
+  }
+
+  pub fn point(&self, i: usize) -> &[f64] {
+    &self.points.data[i * self.points.cols..(i + 1) * self.points.cols]
+  }
+
+  pub fn within_radius(&self, query: &[f64], radius: f64) -> Vec<usize> {
+    let mut found = Vec::new();
+    self.search(&self.root, query, radius * radius, &mut found);
+    found.sort_unstable();
+    found
+  }
+
+  fn search(&self, node: &Node, query: &[f64], radius_sq: f64, found: &mut Vec<usize>) {
+    match node {
+      Node::Leaf(indices) => found.extend(indices.iter().filter(|&&i| squared_distance(self.point(i), query) <= radius_sq)),
+      Node::Split { axis, value, left, right } => {
+        let offset = query[*axis] - value;
+        let (near, far) = if offset <= 0.0 { (left, right) } else { (right, left) };
+        self.search(near, query, radius_sq, found);
+        if offset * offset <= radius_sq {
+          self.search(far, query, radius_sq, found);
+        }
+      }
+    }
+  }
+}
+
+fn build(points: &Matrix<f64>, mut indices: Vec<usize>, leaf_size: usize) -> Node {
+  if indices.len() <= leaf_size {
+    return Node::Leaf(indices);
+  }
+  let p = points.cols;
+  let spread = |j: usize| {
+    let (lo, hi) = indices.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &i| (lo.min(points.data[i * p + j]), hi.max(points.data[i * p + j])));
+    hi - lo
+  };
+  let axis = (0..p).fold(0, |best, j| if spread(j) > spread(best) { j } else { best });
+  if spread(axis) == 0.0 {
+    return Node::Leaf(indices);
+  }
+  let mid = indices.len() / 2;
+  indices.select_nth_unstable_by(mid, |&a, &b| points.data[a * p + axis].total_cmp(&points.data[b * p + axis]));
+  let value = points.data[indices[mid] * p + axis];
+  let right = indices.split_off(mid);
+  Node::Split { axis, value, left: Box::new(build(points, indices, leaf_size)), right: Box::new(build(points, right, leaf_size)) }
+}
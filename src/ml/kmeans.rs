@@ -0,0 +1,225 @@
+use super::{check_features, seeded_rng, squared_distance, to_f64_matrix};
+use crate::math::matrix::Matrix;
+use crate::math::scalar::Float;
+use crate::random::Rng;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum KMeansInit {
+  KMeansPlusPlus,
+  Random
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KMeans {
+  pub n_clusters: usize,
+  pub init: KMeansInit,
+  pub n_init: usize,
+  pub max_iter: usize,
+  pub tol: f64,
+  pub batch_size: Option<usize>,
+  pub seed: Option<u64>,
+  centers: Option<Matrix<f64>>,
+  labels: Vec<usize>,
+  inertia: f64,
+  n_iter: usize
+}
+
+struct Run {
+  centers: Matrix<f64>,
+  labels: Vec<usize>,
+  inertia: f64,
+  n_iter: usize
+}
+
+fn row(x: &Matrix<f64>, i: usize) -> &[f64] {
+  &x.data[i * x.cols..(i + 1) * x.cols]
+}
+
+fn nearest(centers: &Matrix<f64>, point: &[f64]) -> (usize, f64) {
+  (0..centers.rows).map(|c| (c, squared_distance(row(centers, c), point))).fold((0, f64::INFINITY), |best, next| if next.1 < best.1 { next } else { best })
+}
+
+fn assign(centers: &Matrix<f64>, x: &Matrix<f64>) -> (Vec<usize>, Vec<f64>) {
+  (0..x.rows).map(|i| nearest(centers, row(x, i))).unzip()
+}
+
+fn plus_plus(x: &Matrix<f64>, k: usize, rng: &mut Rng) -> Matrix<f64> {
+  let mut chosen = vec![rng.gen_range(x.rows)];
+  let mut distances: Vec<f64> = (0..x.rows).map(|i| squared_distance(row(x, i), row(x, chosen[0]))).collect();
+  while chosen.len() < k {
+    let total: f64 = distances.iter().sum();
+    let next = if total > 0.0 {
+      let mut target = rng.next_f64() * total;
+      distances.iter().position(|&d| {
+        target -= d;
+        target < 0.0
+      })
+      .unwrap_or(x.rows - 1)
+    } else {
+      rng.gen_range(x.rows)
+    };
+    chosen.push(next);
+    for (i, distance) in distances.iter_mut().enumerate() {
+      *distance = distance.min(squared_distance(row(x, i), row(x, next)));
+    }
+  }
+  Matrix::from_fn(k, x.cols, |c, j| x.data[chosen[c] * x.cols + j])
+}
+
+fn random_init(x: &Matrix<f64>, k: usize, rng: &mut Rng) -> Matrix<f64> {
+  let mut order: Vec<usize> = (0..x.rows).collect();
+  rng.shuffle(&mut order);
+  Matrix::from_fn(k, x.cols, |c, j| x.data[order[c] * x.cols + j])
+}
+
+impl KMeans {
+  pub fn new(n_clusters: usize) -> Self {
+    KMeans {
+      n_clusters,
+      init: KMeansInit::KMeansPlusPlus,
+      n_init: 10,
+      max_iter: 300,
+      tol: 1e-4,
+      batch_size: None,
+      seed: None,
+      centers: None,
+      labels: Vec::new(),
+      inertia: 0.0,
+      n_iter: 0
+    }
+  }
+
+  pub fn centers(&self) -> Option<&Matrix<f64>> {
+    self.centers.as_ref()
+  }
+
+  pub fn labels(&self) -> &[usize] {
+    &self.labels
+  }
+
+  pub fn inertia(&self) -> f64 {
+    self.inertia
+  }
+
+  pub fn n_iter(&self) -> usize {
+    self.n_iter
+  }
+
+  fn initial_centers(&self, x: &Matrix<f64>, rng: &mut Rng) -> Matrix<f64> {
+    match self.init {
+      KMeansInit::KMeansPlusPlus => plus_plus(x, self.n_clusters, rng),
+      KMeansInit::Random => random_init(x, self.n_clusters, rng)
+    }
+  }
+
+  fn lloyd(&self, x: &Matrix<f64>, mut centers: Matrix<f64>, threshold: f64) -> Run {
+    let (k, p) = (self.n_clusters, x.cols);
+    let mut n_iter = 0;
+    for _ in 0..self.max_iter {
+      n_iter += 1;
+      let (labels, distances) = assign(&centers, x);
+      let mut sums = vec![0.0; k * p];
+      let mut counts = vec![0usize; k];
+      for (i, &c) in labels.iter().enumerate() {
+        counts[c] += 1;
+        sums[c * p..(c + 1) * p].iter_mut().zip(row(x, i)).for_each(|(s, v)| *s += v);
+      }
+      let mut farthest: Vec<usize> = (0..x.rows).collect();
+      farthest.sort_by(|&a, &b| distances[b].total_cmp(&distances[a]));
+      let mut spare = farthest.into_iter();
+      let mut updated = Matrix::from_fn(k, p, |c, j| if counts[c] > 0 { sums[c * p + j] / counts[c] as f64 } else { 0.0 });
+      for c in (0..k).filter(|&c| counts[c] == 0) {
+        let donor = spare.next().unwrap_or(0);
+        updated.data[c * p..(c + 1) * p].copy_from_slice(row(x, donor));
+      }
+      let shift = squared_distance(&centers.data, &updated.data);
+      centers = updated;
+      if shift <= threshold {
+        break;
+      }
+    }
+    let (labels, distances) = assign(&centers, x);
+    Run { centers, labels, inertia: distances.iter().sum(), n_iter }
+  }
+
+  fn mini_batch(&self, x: &Matrix<f64>, mut centers: Matrix<f64>, batch_size: usize, threshold: f64, rng: &mut Rng) -> Run {
+    let p = x.cols;
+    let mut counts = vec![0usize; self.n_clusters];
+    let mut n_iter = 0;
+    for _ in 0..self.max_iter {
+      n_iter += 1;
+      let previous = centers.clone();
+      for _ in 0..batch_size {
+        let i = rng.gen_range(x.rows);
+        let (c, _) = nearest(&centers, row(x, i));
+        counts[c] += 1;
+        let rate = 1.0 / counts[c] as f64;
+        centers.data[c * p..(c + 1) * p].iter_mut().zip(row(x, i)).for_each(|(center, v)| *center += rate * (v - *center));
+      }
+      if squared_distance(&previous.data, &centers.data) <= threshold {
+        break;
+      }
+    }
+    let (labels, distances) = assign(&centers, x);
+    Run { centers, labels, inertia: distances.iter().sum(), n_iter }
+  }
+
+  pub fn fit<T: Float>(&mut self, x: &Matrix<T>) -> Result<(), String> {
+    if self.n_clusters == 0 || x.rows < self.n_clusters {
+      return Err(format!("KMeans: cannot form {} clusters from {} samples", self.n_clusters, x.rows));
+    }
+    if x.cols == 0 || self.n_init == 0 {
+      return Err(format!("KMeans: needs at least one feature and one initialization, got {} and {}", x.cols, self.n_init));
+    }
+    if self.batch_size == Some(0) {
+      return Err("KMeans: batch_size must be positive".to_string());
+    }
+    let x = to_f64_matrix(x);
+    let (n, p) = (x.rows, x.cols);
+    let variance = (0..p)
+      .map(|j| {
+        let mean = (0..n).map(|i| x.data[i * p + j]).sum::<f64>() / n as f64;
+        (0..n).map(|i| (x.data[i * p + j] - mean).powi(2)).sum::<f64>() / n as f64
+      })
+      .sum::<f64>()
+      / p as f64;
+    let threshold = self.tol * variance;
+
+    let mut rng = seeded_rng(self.seed);
+    let best = (0..self.n_init)
+      .map(|_| {
+        let centers = self.initial_centers(&x, &mut rng);
+        match self.batch_size {
+          Some(batch_size) => self.mini_batch(&x, centers, batch_size, threshold, &mut rng),
+          None => self.lloyd(&x, centers, threshold)
+        }
+      })
+      .min_by(|a, b| a.inertia.total_cmp(&b.inertia))
+      .ok_or_else(|| "KMeans: no initialization ran".to_string())?;
+    self.centers = Some(best.centers);
+    self.labels = best.labels;
+    self.inertia = best.inertia;
+    self.n_iter = best.n_iter;
+    Ok(())
+  }
+
+  pub fn predict<T: Float>(&self, x: &Matrix<T>) -> Result<Vec<usize>, String> {
+    let centers = self.centers.as_ref().ok_or_else(|| "KMeans: predict called before fit".to_string())?;
+    check_features("KMeans", centers.cols, x)?;
+    Ok(assign(centers, &to_f64_matrix(x)).0)
+  }
+
+  pub fn fit_predict<T: Float>(&mut self, x: &Matrix<T>) -> Result<Vec<usize>, String> {
+    self.fit(x)?;
+    Ok(self.labels.clone())
+  }
+
+  pub fn transform<T: Float>(&self, x: &Matrix<T>) -> Result<Matrix<T>, String> {
+    let centers = self.centers.as_ref().ok_or_else(|| "KMeans: transform called before fit".to_string())?;
+    check_features("KMeans", centers.cols, x)?;
+    let x = to_f64_matrix(x);
+    Ok(Matrix::from_fn(x.rows, centers.rows, |i, c| T::from_f64(squared_distance(row(&x, i), row(centers, c)).sqrt())))
+  }
+}
@@ -0,0 +1,151 @@
+use super::{check_features, check_samples, to_f64, Regressor};
+use crate::math::linalg::{cholesky_solve, lstsq};
+use crate::math::matrix::Matrix;
+use crate::math::scalar::Float;
+use crate::math::vector::Vector;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LinearSolver {
+  Qr,
+  Cholesky,
+  GradientDescent
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Centered {
+  pub x: Matrix<f64>,
+  pub y: Vec<f64>,
+  pub x_mean: Vec<f64>,
+  pub y_mean: f64
+}
+
+pub(crate) fn center<T: Float>(x: &Matrix<T>, y: &Vector<T>, fit_intercept: bool) -> Centered {
+  let (n, p) = (x.rows, x.cols);
+  let y = to_f64(&y.data);
+  let (x_mean, y_mean) = if fit_intercept {
+    let x_mean = (0..p).map(|j| (0..n).map(|i| x.data[i * p + j].to_f64()).sum::<f64>() / n as f64).collect();
+    (x_mean, y.iter().sum::<f64>() / n as f64)
+  } else {
+    (vec![0.0; p], 0.0)
+  };
+  Centered {
+    x: Matrix::from_fn(n, p, |i, j| x.data[i * p + j].to_f64() - x_mean[j]),
+    y: y.iter().map(|v| v - y_mean).collect(),
+    x_mean,
+    y_mean
+  }
+}
+
+pub(crate) fn gram(x: &Matrix<f64>, y: &[f64]) -> (Matrix<f64>, Vec<f64>) {
+  let (n, p) = (x.rows, x.cols);
+  let xtx = Matrix::from_fn(p, p, |a, b| (0..n).map(|i| x.data[i * p + a] * x.data[i * p + b]).sum());
+  let xty = (0..p).map(|a| (0..n).map(|i| x.data[i * p + a] * y[i]).sum()).collect();
+  (xtx, xty)
+}
+
+pub(crate) fn linear_predict<T: Float>(name: &str, x: &Matrix<T>, coefficients: Option<&[f64]>, intercept: f64) -> Result<Vector<T>, String> {
+  let coefficients = coefficients.ok_or_else(|| format!("{}: predict called before fit", name))?;
+  check_features(name, coefficients.len(), x)?;
+  Ok(Vector::from(
+    (0..x.rows)
+      .map(|i| T::from_f64(intercept + coefficients.iter().enumerate().map(|(j, c)| c * x.data[i * x.cols + j].to_f64()).sum::<f64>()))
+      .collect::<Vec<_>>()
+  ))
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LinearRegression {
+  pub fit_intercept: bool,
+  pub solver: LinearSolver,
+  pub learning_rate: f64,
+  pub max_iter: usize,
+  pub tol: f64,
+  coefficients: Option<Vec<f64>>,
+  intercept: f64,
+  n_iter: usize
+}
+
+impl LinearRegression {
+  pub fn new() -> Self {
+    LinearRegression {
+      fit_intercept: true,
+      solver: LinearSolver::Qr,
+      learning_rate: 0.01,
+      max_iter: 1000,
+      tol: 1e-6,
+      coefficients: None,
+      intercept: 0.0,
+      n_iter: 0
+    }
+  }
+
+  pub fn coefficients(&self) -> Option<&[f64]> {
+    self.coefficients.as_deref()
+  }
+
+  pub fn intercept(&self) -> f64 {
+    self.intercept
+  }
+
+  pub fn n_iter(&self) -> usize {
+    self.n_iter
+  }
+
+  pub fn is_fitted(&self) -> bool {
+    self.coefficients.is_some()
+  }
+
+  fn gradient_descent(&mut self, x: &Matrix<f64>, y: &[f64]) -> Vec<f64> {
+    let (n, p) = (x.rows, x.cols);
+    let mut beta = vec![0.0; p];
+    self.n_iter = self.max_iter;
+    for iter in 0..self.max_iter {
+      let residual: Vec<f64> = (0..n).map(|i| (0..p).map(|j| x.data[i * p + j] * beta[j]).sum::<f64>() - y[i]).collect();
+      let mut largest = 0.0f64;
+      for (j, b) in beta.iter_mut().enumerate() {
+        let gradient = 2.0 / n as f64 * (0..n).map(|i| x.data[i * p + j] * residual[i]).sum::<f64>();
+        let step = self.learning_rate * gradient;
+        *b -= step;
+        largest = largest.max(step.abs());
+      }
+      if largest < self.tol {
+        self.n_iter = iter + 1;
+        break;
+      }
+    }
+    beta
+  }
+}
+
+impl Default for LinearRegression {
+  fn default() -> Self {
+    LinearRegression::new()
+  }
+}
+
+impl Regressor for LinearRegression {
+  fn fit<T: Float>(&mut self, x: &Matrix<T>, y: &Vector<T>) -> Result<(), String> {
+    check_samples("LinearRegression", x, &y.data)?;
+    let data = center(x, y, self.fit_intercept);
+    let coefficients = match self.solver {
+      LinearSolver::Qr => lstsq(&data.x, &data.y).map_err(|e| format!("LinearRegression: {}, consider Ridge", e))?,
+      LinearSolver::Cholesky => {
+        let (xtx, xty) = gram(&data.x, &data.y);
+        cholesky_solve(&xtx, &xty).map_err(|e| format!("LinearRegression: {}, consider Ridge", e))?
+      }
+      LinearSolver::GradientDescent => self.gradient_descent(&data.x, &data.y)
+    };
+    if self.solver != LinearSolver::GradientDescent {
+      self.n_iter = 1;
+    }
+    self.intercept = data.y_mean - coefficients.iter().zip(&data.x_mean).map(|(c, m)| c * m).sum::<f64>();
+    self.coefficients = Some(coefficients);
+    Ok(())
+  }
+
+  fn predict<T: Float>(&self, x: &Matrix<T>) -> Result<Vector<T>, String> {
+    linear_predict("LinearRegression", x, self.coefficients(), self.intercept)
+  }
+}
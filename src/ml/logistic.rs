@@ -0,0 +1,216 @@
+use super::{check_features, check_samples, encode_classes, to_f64_matrix, ClassWeight, Classifier};
+use crate::math::matrix::Matrix;
+use crate::math::optimize::{gradient_descent, lbfgs};
+use crate::math::scalar::Float;
+
+const LBFGS_MEMORY: usize = 10;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LogisticSolver {
+  GradientDescent,
+  Lbfgs
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LogisticRegression {
+  pub fit_intercept: bool,
+  pub alpha: f64,
+  pub solver: LogisticSolver,
+  pub class_weight: ClassWeight,
+  pub learning_rate: f64,
+  pub max_iter: usize,
+  pub tol: f64,
+  classes: Vec<usize>,
+  coefficients: Option<Matrix<f64>>,
+  intercepts: Vec<f64>,
+  n_iter: usize
+}
+
+pub(crate) fn sigmoid(z: f64) -> f64 {
+  if z >= 0.0 { 1.0 / (1.0 + (-z).exp()) } else { z.exp() / (1.0 + z.exp()) }
+}
+
+fn softmax(logits: &mut [f64]) {
+  let max = logits.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+  logits.iter_mut().for_each(|z| *z = (*z - max).exp());
+  let total: f64 = logits.iter().sum();
+  logits.iter_mut().for_each(|z| *z /= total);
+}
+
+struct Problem<'a> {
+  x: &'a Matrix<f64>,
+  y: &'a [usize],
+  weights: &'a [f64],
+  outputs: usize,
+  alpha: f64,
+  fit_intercept: bool
+}
+
+impl Problem<'_> {
+  fn stride(&self) -> usize {
+    self.x.cols + 1
+  }
+
+  fn logits(&self, params: &[f64], row: usize, out: &mut [f64]) {
+    let p = self.x.cols;
+    let features = &self.x.data[row * p..(row + 1) * p];
+    for (k, z) in out.iter_mut().enumerate() {
+      let w = &params[k * self.stride()..(k + 1) * self.stride()];
+      *z = w[p] + features.iter().zip(w).map(|(a, b)| a * b).sum::<f64>();
+    }
+  }
+
+  fn loss(&self, params: &[f64]) -> (f64, Vec<f64>) {
+    let (p, stride) = (self.x.cols, self.stride());
+    let total: f64 = self.weights.iter().sum();
+    let mut loss = 0.0;
+    let mut grad = vec![0.0; params.len()];
+    let mut scores = vec![0.0; self.outputs];
+    for i in 0..self.x.rows {
+      self.logits(params, i, &mut scores);
+      let weight = self.weights[i] / total;
+      if self.outputs == 1 {
+        let (z, target) = (scores[0], (self.y[i] == 1) as u8 as f64);
+        loss += weight * (z.max(0.0) + (-z.abs()).exp().ln_1p() - target * z);
+        scores[0] = sigmoid(z) - target;
+      } else {
+        let logit = scores[self.y[i]];
+        let max = scores.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        loss += weight * (max + scores.iter().map(|z| (z - max).exp()).sum::<f64>().ln() - logit);
+        softmax(&mut scores);
+        scores[self.y[i]] -= 1.0;
+      }
+      for (k, residual) in scores.iter().enumerate() {
+        let g = &mut grad[k * stride..(k + 1) * stride];
+        for (gj, xj) in g.iter_mut().zip(&self.x.data[i * p..(i + 1) * p]) {
+          *gj += weight * residual * xj;
+        }
+        g[p] += weight * residual;
+      }
+    }
+    for k in 0..self.outputs {
+      for j in 0..p {
+        let w = params[k * stride + j];
+        loss += 0.5 * self.alpha * w * w;
+        grad[k * stride + j] += self.alpha * w;
+      }
+      if !self.fit_intercept {
+        grad[k * stride + p] = 0.0;
+      }
+    }
+    (loss, grad)
+  }
+}
+
+impl LogisticRegression {
+  pub fn new() -> Self {
+    LogisticRegression {
+      fit_intercept: true,
+      alpha: 1e-4,
+      solver: LogisticSolver::Lbfgs,
+      class_weight: ClassWeight::Uniform,
+      learning_rate: 0.1,
+      max_iter: 1000,
+      tol: 1e-6,
+      classes: Vec::new(),
+      coefficients: None,
+      intercepts: Vec::new(),
+      n_iter: 0
+    }
+  }
+
+  pub fn classes(&self) -> &[usize] {
+    &self.classes
+  }
+
+  pub fn coefficients(&self) -> Option<&Matrix<f64>> {
+    self.coefficients.as_ref()
+  }
+
+  pub fn intercepts(&self) -> &[f64] {
+    &self.intercepts
+  }
+
+  pub fn n_iter(&self) -> usize {
+    self.n_iter
+  }
+
+  pub fn is_fitted(&self) -> bool {
+    self.coefficients.is_some()
+  }
+
+  pub fn decision_function<T: Float>(&self, x: &Matrix<T>) -> Result<Matrix<T>, String> {
+    let coefficients = self.coefficients.as_ref().ok_or_else(|| "LogisticRegression: predict called before fit".to_string())?;
+    check_features("LogisticRegression", coefficients.cols, x)?;
+    Ok(Matrix::from_fn(x.rows, coefficients.rows, |i, k| {
+      let row = &x.data[i * x.cols..(i + 1) * x.cols];
+      let w = &coefficients.data[k * coefficients.cols..(k + 1) * coefficients.cols];
+      T::from_f64(self.intercepts[k] + row.iter().zip(w).map(|(a, b)| a.to_f64() * b).sum::<f64>())
+    }))
+  }
+
+  pub fn predict_proba<T: Float>(&self, x: &Matrix<T>) -> Result<Matrix<T>, String> {
+    let scores = self.decision_function(x)?;
+    let k = self.classes.len();
+    let mut proba = Vec::with_capacity(x.rows * k);
+    let mut row = vec![0.0; k];
+    for i in 0..x.rows {
+      if scores.cols == 1 {
+        let positive = sigmoid(scores.data[i].to_f64());
+        row = vec![1.0 - positive, positive];
+      } else {
+        row.iter_mut().enumerate().for_each(|(c, z)| *z = scores.data[i * k + c].to_f64());
+        softmax(&mut row);
+      }
+      proba.extend(row.iter().map(|&p| T::from_f64(p)));
+    }
+    Matrix::from_vec(x.rows, k, proba)
+  }
+}
+
+impl Default for LogisticRegression {
+  fn default() -> Self {
+    LogisticRegression::new()
+  }
+}
+
+impl Classifier for LogisticRegression {
+  fn fit<T: Float>(&mut self, x: &Matrix<T>, y: &[usize]) -> Result<(), String> {
+    check_samples("LogisticRegression", x, y)?;
+    if !(self.alpha >= 0.0 && self.alpha.is_finite()) {
+      return Err(format!("LogisticRegression: alpha must be a non-negative number, got {}", self.alpha));
+    }
+    let (classes, encoded) = encode_classes("LogisticRegression", y)?;
+    let weights = self.class_weight.sample_weights(&classes, &encoded);
+    let x = to_f64_matrix(x);
+    let outputs = if classes.len() == 2 { 1 } else { classes.len() };
+    let problem = Problem { x: &x, y: &encoded, weights: &weights, outputs, alpha: self.alpha, fit_intercept: self.fit_intercept };
+    let init = vec![0.0; outputs * problem.stride()];
+    let minimum = match self.solver {
+      LogisticSolver::GradientDescent => gradient_descent(|w| problem.loss(w), init, self.learning_rate, self.max_iter, self.tol),
+      LogisticSolver::Lbfgs => lbfgs(|w| problem.loss(w), init, LBFGS_MEMORY, self.max_iter, self.tol)
+    };
+
+    let stride = problem.stride();
+    self.coefficients = Some(Matrix::from_fn(outputs, x.cols, |k, j| minimum.x[k * stride + j]));
+    self.intercepts = (0..outputs).map(|k| minimum.x[k * stride + x.cols]).collect();
+    self.classes = classes;
+    self.n_iter = minimum.n_iter;
+    Ok(())
+  }
+
+  fn predict<T: Float>(&self, x: &Matrix<T>) -> Result<Vec<usize>, String> {
+    let proba = self.predict_proba(x)?;
+    Ok(
+      (0..proba.rows)
+        .map(|i| {
+          let row = &proba.data[i * proba.cols..(i + 1) * proba.cols];
+          let best = (0..row.len()).fold(0, |best, c| if row[c] > row[best] { c } else { best });
+          self.classes[best]
+        })
+        .collect()
+    )
+  }
+}
@@ -0,0 +1,238 @@
+use super::{argmax, check_features, log_sum_exp, seeded_rng, to_f64_matrix, KMeans};
+use crate::math::linalg::{cholesky, forward_substitution};
+use crate::math::matrix::Matrix;
+use crate::math::scalar::Float;
+use crate::random::Rng;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CovarianceType {
+  Full,
+  Diag
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GaussianMixture {
+  pub n_components: usize,
+  pub covariance_type: CovarianceType,
+  pub max_iter: usize,
+  pub tol: f64,
+  pub reg_covar: f64,
+  pub n_init: usize,
+  pub seed: Option<u64>,
+  weights: Vec<f64>,
+  means: Option<Matrix<f64>>,
+  covariances: Vec<Matrix<f64>>,
+  log_likelihood: Vec<f64>,
+  converged: bool
+}
+
+struct Params {
+  weights: Vec<f64>,
+  means: Matrix<f64>,
+  covariances: Vec<Matrix<f64>>
+}
+
+struct Fit {
+  params: Params,
+  log_likelihood: Vec<f64>,
+  converged: bool
+}
+
+impl Params {
+  fn factors(&self) -> Result<Vec<Matrix<f64>>, String> {
+    self.covariances.iter().map(|c| cholesky(c).map_err(|e| format!("GaussianMixture: {}", e))).collect()
+  }
+
+  fn weighted_log_prob(&self, x: &Matrix<f64>) -> Result<Matrix<f64>, String> {
+    let (k, p) = (self.weights.len(), x.cols);
+    let factors = self.factors()?;
+    let constant = p as f64 * (2.0 * std::f64::consts::PI).ln();
+    let log_dets: Vec<f64> = factors.iter().map(|l| 2.0 * (0..p).map(|j| l.data[j * p + j].ln()).sum::<f64>()).collect();
+    let mut out = Matrix::from_fn(x.rows, k, |_, _| 0.0);
+    for i in 0..x.rows {
+      for c in 0..k {
+        let centered: Vec<f64> = (0..p).map(|j| x.data[i * p + j] - self.means.data[c * p + j]).collect();
+        let whitened = forward_substitution(&factors[c], &centered)?;
+        let mahalanobis: f64 = whitened.iter().map(|v| v * v).sum();
+        out.data[i * k + c] = self.weights[c].ln() - 0.5 * (constant + log_dets[c] + mahalanobis);
+      }
+    }
+    Ok(out)
+  }
+
+  fn maximize(x: &Matrix<f64>, resp: &Matrix<f64>, covariance_type: CovarianceType, reg_covar: f64) -> Params {
+    let (n, p, k) = (x.rows, x.cols, resp.cols);
+    let counts: Vec<f64> = (0..k).map(|c| (0..n).map(|i| resp.data[i * k + c]).sum::<f64>() + 10.0 * f64::EPSILON).collect();
+    let means = Matrix::from_fn(k, p, |c, j| (0..n).map(|i| resp.data[i * k + c] * x.data[i * p + j]).sum::<f64>() / counts[c]);
+    let covariances = (0..k)
+      .map(|c| {
+        Matrix::from_fn(p, p, |a, b| {
+          if a != b && covariance_type == CovarianceType::Diag {
+            return 0.0;
+          }
+          let spread = (0..n).map(|i| resp.data[i * k + c] * (x.data[i * p + a] - means.data[c * p + a]) * (x.data[i * p + b] - means.data[c * p + b])).sum::<f64>();
+          spread / counts[c] + if a == b { reg_covar } else { 0.0 }
+        })
+      })
+      .collect();
+    Params { weights: counts.iter().map(|count| count / n as f64).collect(), means, covariances }
+  }
+}
+
+impl GaussianMixture {
+  pub fn new(n_components: usize) -> Self {
+    GaussianMixture {
+      n_components,
+      covariance_type: CovarianceType::Full,
+      max_iter: 100,
+      tol: 1e-3,
+      reg_covar: 1e-6,
+      n_init: 1,
+      seed: None,
+      weights: Vec::new(),
+      means: None,
+      covariances: Vec::new(),
+      log_likelihood: Vec::new(),
+      converged: false
+    }
+  }
+
+  pub fn weights(&self) -> &[f64] {
+    &self.weights
+  }
+
+  pub fn means(&self) -> Option<&Matrix<f64>> {
+    self.means.as_ref()
+  }
+
+  pub fn covariances(&self) -> &[Matrix<f64>] {
+    &self.covariances
+  }
+
+  pub fn log_likelihood_history(&self) -> &[f64] {
+    &self.log_likelihood
+  }
+
+  pub fn converged(&self) -> bool {
+    self.converged
+  }
+
+  pub fn n_iter(&self) -> usize {
+    self.log_likelihood.len()
+  }
+
+  fn params(&self) -> Result<Params, String> {
+    let means = self.means.clone().ok_or_else(|| "GaussianMixture: called before fit".to_string())?;
+    Ok(Params { weights: self.weights.clone(), means, covariances: self.covariances.clone() })
+  }
+
+  fn run(&self, x: &Matrix<f64>, seed: u64) -> Result<Fit, String> {
+    let mut kmeans = KMeans::new(self.n_components);
+    kmeans.n_init = 1;
+    kmeans.seed = Some(seed);
+    let labels = kmeans.fit_predict(x)?;
+    let resp = Matrix::from_fn(x.rows, self.n_components, |i, c| if labels[i] == c { 1.0 } else { 0.0 });
+    let mut params = Params::maximize(x, &resp, self.covariance_type, self.reg_covar);
+    let mut log_likelihood = Vec::new();
+    for _ in 0..self.max_iter {
+      let mut resp = params.weighted_log_prob(x)?;
+      let mut total = 0.0;
+      for i in 0..x.rows {
+        let row = &mut resp.data[i * resp.cols..(i + 1) * resp.cols];
+        let norm = log_sum_exp(row);
+        total += norm;
+        row.iter_mut().for_each(|v| *v = (*v - norm).exp());
+      }
+      let mean = total / x.rows as f64;
+      let previous = log_likelihood.last().copied();
+      log_likelihood.push(mean);
+      if previous.is_some_and(|previous: f64| (mean - previous).abs() < self.tol) {
+        return Ok(Fit { params, log_likelihood, converged: true });
+      }
+      params = Params::maximize(x, &resp, self.covariance_type, self.reg_covar);
+    }
+    Ok(Fit { params, log_likelihood, converged: false })
+  }
+
+  pub fn fit<T: Float>(&mut self, x: &Matrix<T>) -> Result<(), String> {
+    if self.n_components == 0 || x.rows < self.n_components || x.cols == 0 {
+      return Err(format!("GaussianMixture: cannot fit {} components to a {}x{} matrix", self.n_components, x.rows, x.cols));
+    }
+    if self.n_init == 0 || !(self.reg_covar >= 0.0 && self.reg_covar.is_finite()) {
+      return Err(format!("GaussianMixture: needs n_init > 0 and reg_covar >= 0, got {} and {}", self.n_init, self.reg_covar));
+    }
+    let x = to_f64_matrix(x);
+    let mut rng = seeded_rng(self.seed);
+    let mut best: Option<Fit> = None;
+    for _ in 0..self.n_init {
+      let fit = self.run(&x, rng.next_u64())?;
+      let score = |fit: &Fit| fit.log_likelihood.last().copied().unwrap_or(f64::NEG_INFINITY);
+      if best.as_ref().is_none_or(|b| score(&fit) > score(b)) {
+        best = Some(fit);
+      }
+    }
+    let best = best.ok_or_else(|| "GaussianMixture: no initialization ran".to_string())?;
+    self.weights = best.params.weights;
+    self.means = Some(best.params.means);
+    self.covariances = best.params.covariances;
+    self.log_likelihood = best.log_likelihood;
+    self.converged = best.converged;
+    Ok(())
+  }
+
+  fn log_prob<T: Float>(&self, x: &Matrix<T>) -> Result<Matrix<f64>, String> {
+    let params = self.params()?;
+    check_features("GaussianMixture", params.means.cols, x)?;
+    params.weighted_log_prob(&to_f64_matrix(x))
+  }
+
+  pub fn score_samples<T: Float>(&self, x: &Matrix<T>) -> Result<Vec<f64>, String> {
+    let log_prob = self.log_prob(x)?;
+    Ok((0..x.rows).map(|i| log_sum_exp(&log_prob.data[i * log_prob.cols..(i + 1) * log_prob.cols])).collect())
+  }
+
+  pub fn score<T: Float>(&self, x: &Matrix<T>) -> Result<f64, String> {
+    Ok(self.score_samples(x)?.iter().sum::<f64>() / x.rows.max(1) as f64)
+  }
+
+  pub fn predict_proba<T: Float>(&self, x: &Matrix<T>) -> Result<Matrix<T>, String> {
+    let log_prob = self.log_prob(x)?;
+    let k = log_prob.cols;
+    let norms: Vec<f64> = (0..x.rows).map(|i| log_sum_exp(&log_prob.data[i * k..(i + 1) * k])).collect();
+    Ok(Matrix::from_fn(x.rows, k, |i, c| T::from_f64((log_prob.data[i * k + c] - norms[i]).exp())))
+  }
+
+  pub fn predict<T: Float>(&self, x: &Matrix<T>) -> Result<Vec<usize>, String> {
+    let log_prob = self.log_prob(x)?;
+    let k = log_prob.cols;
+    Ok((0..x.rows).map(|i| argmax(&log_prob.data[i * k..(i + 1) * k])).collect())
+  }
+
+  pub fn fit_predict<T: Float>(&mut self, x: &Matrix<T>) -> Result<Vec<usize>, String> {
+    self.fit(x)?;
+    self.predict(x)
+  }
+
+  pub fn sample<T: Float>(&self, n: usize, rng: &mut Rng) -> Result<(Matrix<T>, Vec<usize>), String> {
+    let params = self.params()?;
+    let factors = params.factors()?;
+    let p = params.means.cols;
+    let mut data = Vec::with_capacity(n * p);
+    let mut components = Vec::with_capacity(n);
+    for _ in 0..n {
+      let mut target = rng.next_f64();
+      let c = params.weights.iter().position(|&w| {
+        target -= w;
+        target < 0.0
+      });
+      let c = c.unwrap_or(params.weights.len() - 1);
+      let z: Vec<f64> = (0..p).map(|_| rng.normal(0.0, 1.0)).collect();
+      let l = &factors[c];
+      data.extend((0..p).map(|a| T::from_f64(params.means.data[c * p + a] + (0..=a).map(|b| l.data[a * p + b] * z[b]).sum::<f64>())));
+      components.push(c);
+    }
+    Ok((Matrix::from_vec(n, p, data)?, components))
+  }
+}
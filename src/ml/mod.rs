@@ -0,0 +1,147 @@
+use std::collections::BTreeMap;
+
+use crate::math::matrix::Matrix;
+use crate::math::scalar::Float;
+use crate::math::vector::Vector;
+use crate::metrics::r2_score;
+use crate::random::{with_rng, Rng};
+
+pub mod calibration;
+pub mod dbscan;
+pub mod kdtree;
+pub mod kmeans;
+pub mod linear;
+pub mod logistic;
+pub mod mixture;
+pub mod model_selection;
+pub mod naive_bayes;
+pub mod regularized;
+pub mod sgd;
+pub mod svm;
+pub mod tree;
+pub mod tsne;
+
+pub use calibration::{CalibrationMethod, Calibrator};
+pub use dbscan::{NeighborSearch, DBSCAN};
+pub use kdtree::KdTree;
+pub use kmeans::{KMeans, KMeansInit};
+pub use linear::{LinearRegression, LinearSolver};
+pub use logistic::{LogisticRegression, LogisticSolver};
+pub use mixture::{CovarianceType, GaussianMixture};
+pub use naive_bayes::{BernoulliNB, GaussianNB, MultinomialNB};
+pub use regularized::{alpha_grid, cross_validate_alpha, enet_path, ElasticNet, ElasticNetCV, Lasso, RegularizationPath, Ridge, RidgeCV};
+pub use sgd::{ClassificationLoss, LearningRate, Penalty, RegressionLoss, SGDClassifier, SGDRegressor};
+pub use svm::{Kernel, SVC, SVR};
+pub use tree::{Criterion, DecisionTreeClassifier, DecisionTreeRegressor, Split, Tree, TreeNode};
+pub use tsne::TSNE;
+
+pub trait Regressor {
+  fn fit<T: Float>(&mut self, x: &Matrix<T>, y: &Vector<T>) -> Result<(), String>;
+
+  fn predict<T: Float>(&self, x: &Matrix<T>) -> Result<Vector<T>, String>;
+
+  fn score<T: Float>(&self, x: &Matrix<T>, y: &Vector<T>) -> Result<f64, String> {
+    let predicted = self.predict(x)?;
+    r2_score(&y.data, &predicted.data, None)
+  }
+}
+
+pub trait Classifier {
+  fn fit<T: Float>(&mut self, x: &Matrix<T>, y: &[usize]) -> Result<(), String>;
+
+  fn predict<T: Float>(&self, x: &Matrix<T>) -> Result<Vec<usize>, String>;
+
+  fn score<T: Float>(&self, x: &Matrix<T>, y: &[usize]) -> Result<f64, String> {
+    let predicted = self.predict(x)?;
+    if predicted.len() != y.len() || y.is_empty() {
+      return Err(format!("score: {} predictions for {} labels", predicted.len(), y.len()));
+    }
+    Ok(predicted.iter().zip(y).filter(|(p, t)| p == t).count() as f64 / y.len() as f64)
+  }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ClassWeight {
+  #[default]
+  Uniform,
+  Balanced,
+  Custom(BTreeMap<usize, f64>)
+}
+
+impl ClassWeight {
+  pub fn sample_weights(&self, classes: &[usize], encoded: &[usize]) -> Vec<f64> {
+    match self {
+      ClassWeight::Uniform => vec![1.0; encoded.len()],
+      ClassWeight::Balanced => {
+        let mut counts = vec![0usize; classes.len()];
+        for &c in encoded {
+          counts[c] += 1;
+        }
+        let scale = encoded.len() as f64 / classes.len() as f64;
+        encoded.iter().map(|&c| scale / counts[c] as f64).collect()
+      }
+      ClassWeight::Custom(weights) => encoded.iter().map(|&c| weights.get(&classes[c]).copied().unwrap_or(1.0)).collect()
+    }
+  }
+}
+
+pub(crate) fn encode_classes(name: &str, y: &[usize]) -> Result<(Vec<usize>, Vec<usize>), String> {
+  let mut classes = y.to_vec();
+  classes.sort_unstable();
+  classes.dedup();
+  if classes.len() < 2 {
+    return Err(format!("{}: needs at least two classes, got {}", name, classes.len()));
+  }
+  let encoded = y.iter().map(|label| classes.binary_search(label).unwrap_or_default()).collect();
+  Ok((classes, encoded))
+}
+
+pub(crate) fn to_f64<T: Float>(values: &[T]) -> Vec<f64> {
+  values.iter().map(|x| x.to_f64()).collect()
+}
+
+pub(crate) fn to_f64_matrix<T: Float>(x: &Matrix<T>) -> Matrix<f64> {
+  Matrix::from_fn(x.rows, x.cols, |i, j| x.data[i * x.cols + j].to_f64())
+}
+
+pub(crate) fn seeded_rng(seed: Option<u64>) -> Rng {
+  Rng::seed_from_u64(seed.unwrap_or_else(|| with_rng(|rng| rng.next_u64())))
+}
+
+pub(crate) fn squared_distance(a: &[f64], b: &[f64]) -> f64 {
+  a.iter().zip(b).map(|(x, y)| (x - y).powi(2)).sum()
+}
+
+pub(crate) fn log_sum_exp(values: &[f64]) -> f64 {
+  let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+  if max == f64::NEG_INFINITY {
+    return max;
+  }
+  max + values.iter().map(|v| (v - max).exp()).sum::<f64>().ln()
+}
+
+pub(crate) fn argmax(values: &[f64]) -> usize {
+  (0..values.len()).fold(0, |best, i| if values[i] > values[best] { i } else { best })
+}
+
+pub(crate) fn select_rows<T: Copy>(x: &Matrix<T>, rows: &[usize]) -> Matrix<T> {
+  Matrix::from_fn(rows.len(), x.cols, |i, j| x.data[rows[i] * x.cols + j])
+}
+
+pub(crate) fn check_samples<T, Y>(name: &str, x: &Matrix<T>, targets: &[Y]) -> Result<(), String> {
+  if x.rows == 0 || x.cols == 0 {
+    return Err(format!("{}: cannot fit on an empty {}x{} matrix", name, x.rows, x.cols));
+  }
+  if targets.len() != x.rows {
+    return Err(format!("{}: {} samples but {} targets", name, x.rows, targets.len()));
+  }
+  Ok(())
+}
+
+pub(crate) fn check_features<T>(name: &str, expected: usize, x: &Matrix<T>) -> Result<(), String> {
+  if x.cols != expected {
+    return Err(format!("{}: fitted on {} features, got {}", name, expected, x.cols));
+  }
+  Ok(())
+}
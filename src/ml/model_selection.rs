@@ -0,0 +1,466 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::thread;
+
+use super::{seeded_rng, select_rows, Classifier, Regressor};
+use crate::math::matrix::Matrix;
+use crate::math::scalar::Float;
+use crate::math::vector::Vector;
+use crate::random::{is_deterministic, with_rng, with_seed, Rng};
+
+pub trait Estimator<T: Float, Y> {
+  fn fit_targets(&mut self, x: &Matrix<T>, y: &[Y]) -> Result<(), String>;
+
+  fn score_targets(&self, x: &Matrix<T>, y: &[Y]) -> Result<f64, String>;
+}
+
+impl<T: Float, C: Classifier> Estimator<T, usize> for C {
+  fn fit_targets(&mut self, x: &Matrix<T>, y: &[usize]) -> Result<(), String> {
+    self.fit(x, y)
+  }
+
+  fn score_targets(&self, x: &Matrix<T>, y: &[usize]) -> Result<f64, String> {
+    self.score(x, y)
+  }
+}
+
+impl<T: Float, R: Regressor> Estimator<T, T> for R {
+  fn fit_targets(&mut self, x: &Matrix<T>, y: &[T]) -> Result<(), String> {
+    self.fit(x, &Vector::from(y.to_vec()))
+  }
+
+  fn score_targets(&self, x: &Matrix<T>, y: &[T]) -> Result<f64, String> {
+    self.score(x, &Vector::from(y.to_vec()))
+  }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Fold {
+  pub train: Vec<usize>,
+  pub test: Vec<usize>
+}
+
+fn check_splits(name: &str, n_splits: usize, n_samples: usize) -> Result<(), String> {
+  if n_splits < 2 || n_splits > n_samples {
+    return Err(format!("{}: need 2 <= n_splits <= {}, got {}", name, n_samples, n_splits));
+  }
+  Ok(())
+}
+
+fn folds_from_groups(n_samples: usize, groups: Vec<Vec<usize>>) -> Vec<Fold> {
+  groups
+    .into_iter()
+    .map(|mut test| {
+      test.sort_unstable();
+      let mut in_test = vec![false; n_samples];
+      test.iter().for_each(|&i| in_test[i] = true);
+      Fold { train: (0..n_samples).filter(|&i| !in_test[i]).collect(), test }
+    })
+    .collect()
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KFold {
+  pub n_splits: usize,
+  pub shuffle: bool,
+  pub seed: Option<u64>
+}
+
+impl KFold {
+  pub fn new(n_splits: usize) -> Self {
+    KFold { n_splits, shuffle: false, seed: None }
+  }
+
+  pub fn split(&self, n_samples: usize) -> Result<Vec<Fold>, String> {
+    check_splits("KFold", self.n_splits, n_samples)?;
+    let mut order: Vec<usize> = (0..n_samples).collect();
+    if self.shuffle {
+      seeded_rng(self.seed).shuffle(&mut order);
+    }
+    let groups = (0..self.n_splits).map(|k| order[k * n_samples / self.n_splits..(k + 1) * n_samples / self.n_splits].to_vec()).collect();
+    Ok(folds_from_groups(n_samples, groups))
+  }
+}
+
+impl Default for KFold {
+  fn default() -> Self {
+    KFold::new(5)
+  }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StratifiedKFold {
+  pub n_splits: usize,
+  pub shuffle: bool,
+  pub seed: Option<u64>
+}
+
+impl StratifiedKFold {
+  pub fn new(n_splits: usize) -> Self {
+    StratifiedKFold { n_splits, shuffle: false, seed: None }
+  }
+
+  pub fn split(&self, labels: &[usize]) -> Result<Vec<Fold>, String> {
+    check_splits("StratifiedKFold", self.n_splits, labels.len())?;
+    let mut classes = labels.to_vec();
+    classes.sort_unstable();
+    classes.dedup();
+    let mut rng: Option<Rng> = self.shuffle.then(|| seeded_rng(self.seed));
+    let mut groups = vec![Vec::new(); self.n_splits];
+    let mut next = 0;
+    for class in classes {
+      let mut members: Vec<usize> = (0..labels.len()).filter(|&i| labels[i] == class).collect();
+      if let Some(rng) = rng.as_mut() {
+        rng.shuffle(&mut members);
+      }
+      for member in members {
+        groups[next].push(member);
+        next = (next + 1) % self.n_splits;
+      }
+    }
+    Ok(folds_from_groups(labels.len(), groups))
+  }
+}
+
+impl Default for StratifiedKFold {
+  fn default() -> Self {
+    StratifiedKFold::new(5)
+  }
+}
+
+pub fn cross_val_score<T, Y, E>(estimator: &E, x: &Matrix<T>, y: &[Y], folds: &[Fold]) -> Result<Vec<f64>, String>
+where
+  T: Float,
+  Y: Clone + Send + Sync,
+  E: Estimator<T, Y> + Clone + Send + Sync
+{
+  if y.len() != x.rows {
+    return Err(format!("cross_val_score: {} samples but {} targets", x.rows, y.len()));
+  }
+  if let Some(bad) = folds.iter().flat_map(|fold| fold.train.iter().chain(&fold.test)).find(|&&i| i >= x.rows) {
+    return Err(format!("cross_val_score: fold index {} out of range for {} samples", bad, x.rows));
+  }
+  let evaluate = |fold: &Fold| -> Result<f64, String> {
+    let mut model = estimator.clone();
+    let targets = |indices: &[usize]| indices.iter().map(|&i| y[i].clone()).collect::<Vec<Y>>();
+    model.fit_targets(&select_rows(x, &fold.train), &targets(&fold.train))?;
+    model.score_targets(&select_rows(x, &fold.test), &targets(&fold.test))
+  };
+  let seeds: Vec<u64> = with_rng(|rng| folds.iter().map(|_| rng.next_u64()).collect());
+  if is_deterministic() {
+    return folds.iter().zip(seeds).map(|(fold, seed)| with_seed(seed, || evaluate(fold))).collect();
+  }
+  let jobs: Vec<(&Fold, u64)> = folds.iter().zip(seeds).collect();
+  let workers = thread::available_parallelism().map_or(1, |n| n.get()).min(folds.len()).max(1);
+  let chunk = jobs.len().div_ceil(workers).max(1);
+  thread::scope(|scope| {
+    let handles: Vec<_> = jobs
+      .chunks(chunk)
+      .map(|group| scope.spawn(move || group.iter().map(|&(fold, seed)| with_seed(seed, || evaluate(fold))).collect::<Vec<_>>()))
+      .collect();
+    handles.into_iter().flat_map(|handle| handle.join().unwrap_or_else(|_| vec![Err("cross_val_score: a fold panicked".to_string())])).collect()
+  })
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ParamValue {
+  Float(f64),
+  Int(i64),
+  Bool(bool),
+  Text(String)
+}
+
+impl ParamValue {
+  pub fn as_f64(&self) -> Result<f64, String> {
+    match self {
+      ParamValue::Float(value) => Ok(*value),
+      ParamValue::Int(value) => Ok(*value as f64),
+      other => Err(format!("ParamValue: expected a number, got {}", other))
+    }
+  }
+
+  pub fn as_usize(&self) -> Result<usize, String> {
+    match self {
+      ParamValue::Int(value) if *value >= 0 => Ok(*value as usize),
+      other => Err(format!("ParamValue: expected a non-negative integer, got {}", other))
+    }
+  }
+
+  pub fn as_bool(&self) -> Result<bool, String> {
+    match self {
+      ParamValue::Bool(value) => Ok(*value),
+      other => Err(format!("ParamValue: expected a bool, got {}", other))
+    }
+  }
+
+  pub fn as_str(&self) -> Result<&str, String> {
+    match self {
+      ParamValue::Text(value) => Ok(value),
+      other => Err(format!("ParamValue: expected text, got {}", other))
+    }
+  }
+}
+
+impl fmt::Display for ParamValue {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      ParamValue::Float(value) => write!(f, "{}", value),
+      ParamValue::Int(value) => write!(f, "{}", value),
+      ParamValue::Bool(value) => write!(f, "{}", value),
+      ParamValue::Text(value) => write!(f, "{}", value)
+    }
+  }
+}
+
+pub type ParamSet = BTreeMap<String, ParamValue>;
+
+pub fn param<'a>(params: &'a ParamSet, name: &str) -> Result<&'a ParamValue, String> {
+  params.get(name).ok_or_else(|| format!("ParamSet: missing parameter {}", name))
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ParamGrid {
+  pub params: Vec<(String, Vec<ParamValue>)>
+}
+
+impl ParamGrid {
+  pub fn new() -> Self {
+    ParamGrid::default()
+  }
+
+  pub fn insert(&mut self, name: &str, values: Vec<ParamValue>) {
+    self.params.retain(|(existing, _)| existing != name);
+    self.params.push((name.to_string(), values));
+  }
+
+  pub fn len(&self) -> usize {
+    self.params.iter().map(|(_, values)| values.len()).product()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  pub fn combinations(&self) -> Vec<ParamSet> {
+    self.params.iter().fold(vec![ParamSet::new()], |sets, (name, values)| {
+      sets
+        .iter()
+        .flat_map(|set| {
+          values.iter().map(move |value| {
+            let mut next = set.clone();
+            next.insert(name.clone(), value.clone());
+            next
+          })
+        })
+        .collect()
+    })
+  }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Distribution {
+  Choice(Vec<ParamValue>),
+  Uniform(f64, f64),
+  LogUniform(f64, f64),
+  IntRange(i64, i64)
+}
+
+impl Distribution {
+  pub fn sample(&self, rng: &mut Rng) -> Result<ParamValue, String> {
+    match self {
+      Distribution::Choice(values) if !values.is_empty() => Ok(values[rng.gen_range(values.len())].clone()),
+      Distribution::Uniform(low, high) if low <= high => Ok(ParamValue::Float(rng.uniform(*low, *high))),
+      Distribution::LogUniform(low, high) if 0.0 < *low && low <= high => Ok(ParamValue::Float(rng.uniform(low.ln(), high.ln()).exp())),
+      Distribution::IntRange(low, high) if low <= high => Ok(ParamValue::Int(low + rng.gen_range((high - low + 1) as usize) as i64)),
+      other => Err(format!("Distribution: cannot sample from {:?}", other))
+    }
+  }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ParamDistributions {
+  pub params: Vec<(String, Distribution)>
+}
+
+impl ParamDistributions {
+  pub fn new() -> Self {
+    ParamDistributions::default()
+  }
+
+  pub fn insert(&mut self, name: &str, distribution: Distribution) {
+    self.params.retain(|(existing, _)| existing != name);
+    self.params.push((name.to_string(), distribution));
+  }
+
+  pub fn sample(&self, rng: &mut Rng) -> Result<ParamSet, String> {
+    self.params.iter().map(|(name, distribution)| Ok((name.clone(), distribution.sample(rng)?))).collect()
+  }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchResult {
+  pub params: ParamSet,
+  pub scores: Vec<f64>,
+  pub mean_score: f64,
+  pub std_score: f64,
+  pub rank: usize
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SearchResults {
+  pub rows: Vec<SearchResult>
+}
+
+impl SearchResults {
+  pub fn best(&self) -> Option<&SearchResult> {
+    self.rows.iter().find(|row| row.rank == 1)
+  }
+}
+
+impl fmt::Display for SearchResults {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let header = ["Rank", "Mean score", "Std", "Params"];
+    let rows: Vec<[String; 4]> = self
+      .rows
+      .iter()
+      .map(|row| {
+        let params = row.params.iter().map(|(name, value)| format!("{}={}", name, value)).collect::<Vec<_>>().join(", ");
+        [row.rank.to_string(), format!("{:.4}", row.mean_score), format!("{:.4}", row.std_score), params]
+      })
+      .collect();
+    let mut widths = header.map(str::len);
+    for row in &rows {
+      for (width, cell) in widths.iter_mut().zip(row) {
+        *width = (*width).max(cell.len());
+      }
+    }
+    let line = |cells: Vec<&str>| cells.iter().zip(&widths).map(|(cell, &width)| format!("{:<width$}", cell)).collect::<Vec<_>>().join("  ");
+    writeln!(f, "{}", line(header.to_vec()).trim_end())?;
+    write!(f, "{}", "-".repeat(widths.iter().sum::<usize>() + 2 * (widths.len() - 1)))?;
+    for row in &rows {
+      write!(f, "\n{}", line(row.iter().map(String::as_str).collect()).trim_end())?;
+    }
+    Ok(())
+  }
+}
+
+fn search<T, Y, E, F>(candidates: Vec<ParamSet>, build: F, x: &Matrix<T>, y: &[Y], folds: &[Fold], refit: bool) -> Result<(SearchResults, Option<E>), String>
+where
+  T: Float,
+  Y: Clone + Send + Sync,
+  E: Estimator<T, Y> + Clone + Send + Sync,
+  F: Fn(&ParamSet) -> Result<E, String>
+{
+  if candidates.is_empty() {
+    return Err("search: the parameter space is empty".to_string());
+  }
+  let mut rows = candidates
+    .into_iter()
+    .map(|params| {
+      let scores = cross_val_score(&build(&params)?, x, y, folds)?;
+      let mean_score = scores.iter().sum::<f64>() / scores.len().max(1) as f64;
+      let std_score = (scores.iter().map(|s| (s - mean_score).powi(2)).sum::<f64>() / scores.len().max(1) as f64).sqrt();
+      Ok(SearchResult { params, scores, mean_score, std_score, rank: 0 })
+    })
+    .collect::<Result<Vec<_>, String>>()?;
+  let mut order: Vec<usize> = (0..rows.len()).collect();
+  order.sort_by(|&a, &b| rows[b].mean_score.total_cmp(&rows[a].mean_score));
+  for (rank, &index) in order.iter().enumerate() {
+    rows[index].rank = rank + 1;
+  }
+  let best = if refit {
+    let mut model = build(&rows[order[0]].params)?;
+    model.fit_targets(x, y)?;
+    Some(model)
+  } else {
+    None
+  };
+  Ok((SearchResults { rows }, best))
+}
+
+#[derive(Clone, Debug)]
+pub struct GridSearch<E> {
+  pub grid: ParamGrid,
+  pub refit: bool,
+  results: SearchResults,
+  best_estimator: Option<E>
+}
+
+impl<E> GridSearch<E> {
+  pub fn new(grid: ParamGrid) -> Self {
+    GridSearch { grid, refit: true, results: SearchResults::default(), best_estimator: None }
+  }
+
+  pub fn fit<T, Y, F>(&mut self, build: F, x: &Matrix<T>, y: &[Y], folds: &[Fold]) -> Result<(), String>
+  where
+    T: Float,
+    Y: Clone + Send + Sync,
+    E: Estimator<T, Y> + Clone + Send + Sync,
+    F: Fn(&ParamSet) -> Result<E, String>
+  {
+    (self.results, self.best_estimator) = search(self.grid.combinations(), build, x, y, folds, self.refit)?;
+    Ok(())
+  }
+
+  pub fn results(&self) -> &SearchResults {
+    &self.results
+  }
+
+  pub fn best_params(&self) -> Option<&ParamSet> {
+    self.results.best().map(|row| &row.params)
+  }
+
+  pub fn best_score(&self) -> Option<f64> {
+    self.results.best().map(|row| row.mean_score)
+  }
+
+  pub fn best_estimator(&self) -> Option<&E> {
+    self.best_estimator.as_ref()
+  }
+}
+
+#[derive(Clone, Debug)]
+pub struct RandomSearch<E> {
+  pub distributions: ParamDistributions,
+  pub n_iter: usize,
+  pub seed: Option<u64>,
+  pub refit: bool,
+  results: SearchResults,
+  best_estimator: Option<E>
+}
+
+impl<E> RandomSearch<E> {
+  pub fn new(distributions: ParamDistributions, n_iter: usize) -> Self {
+    RandomSearch { distributions, n_iter, seed: None, refit: true, results: SearchResults::default(), best_estimator: None }
+  }
+
+  pub fn fit<T, Y, F>(&mut self, build: F, x: &Matrix<T>, y: &[Y], folds: &[Fold]) -> Result<(), String>
+  where
+    T: Float,
+    Y: Clone + Send + Sync,
+    E: Estimator<T, Y> + Clone + Send + Sync,
+    F: Fn(&ParamSet) -> Result<E, String>
+  {
+    let mut rng = seeded_rng(self.seed);
+    let candidates = (0..self.n_iter).map(|_| self.distributions.sample(&mut rng)).collect::<Result<Vec<_>, _>>()?;
+    (self.results, self.best_estimator) = search(candidates, build, x, y, folds, self.refit)?;
+    Ok(())
+  }
+
+  pub fn results(&self) -> &SearchResults {
+    &self.results
+  }
+
+  pub fn best_params(&self) -> Option<&ParamSet> {
+    self.results.best().map(|row| &row.params)
+  }
+
+  pub fn best_score(&self) -> Option<f64> {
+    self.results.best().map(|row| row.mean_score)
+  }
+
+  pub fn best_estimator(&self) -> Option<&E> {
+    self.best_estimator.as_ref()
+  }
+}
@@ -0,0 +1,263 @@
+use super::{argmax, check_features, check_samples, encode_classes, log_sum_exp, to_f64_matrix, Classifier};
+use crate::math::matrix::Matrix;
+use crate::math::scalar::Float;
+
+const MIN_ALPHA: f64 = 1e-10;
+
+fn class_log_prior(name: &str, priors: Option<&[f64]>, fit_prior: bool, counts: &[f64]) -> Result<Vec<f64>, String> {
+  match priors {
+    Some(priors) => {
+      if priors.len() != counts.len() || priors.iter().any(|&p| p.is_nan() || p <= 0.0) || (priors.iter().sum::<f64>() - 1.0).abs() > 1e-6 {
+        return Err(format!("{}: priors must be {} positive values summing to 1", name, counts.len()));
+      }
+      Ok(priors.iter().map(|p| p.ln()).collect())
+    }
+    None if fit_prior => {
+      let total: f64 = counts.iter().sum();
+      Ok(counts.iter().map(|c| (c / total).ln()).collect())
+    }
+    None => Ok(vec![-(counts.len() as f64).ln(); counts.len()])
+  }
+}
+
+fn normalize(joint: Matrix<f64>) -> Matrix<f64> {
+  let k = joint.cols;
+  let norms: Vec<f64> = (0..joint.rows).map(|i| log_sum_exp(&joint.data[i * k..(i + 1) * k])).collect();
+  Matrix::from_fn(joint.rows, k, |i, c| joint.data[i * k + c] - norms[i])
+}
+
+fn predict_labels(classes: &[usize], joint: &Matrix<f64>) -> Vec<usize> {
+  (0..joint.rows).map(|i| classes[argmax(&joint.data[i * joint.cols..(i + 1) * joint.cols])]).collect()
+}
+
+fn class_totals(x: &Matrix<f64>, encoded: &[usize], n_classes: usize) -> (Vec<f64>, Matrix<f64>) {
+  let p = x.cols;
+  let mut counts = vec![0.0; n_classes];
+  let mut sums = Matrix::from_fn(n_classes, p, |_, _| 0.0);
+  for (i, &c) in encoded.iter().enumerate() {
+    counts[c] += 1.0;
+    sums.data[c * p..(c + 1) * p].iter_mut().zip(&x.data[i * p..(i + 1) * p]).for_each(|(s, v)| *s += v);
+  }
+  (counts, sums)
+}
+
+macro_rules! impl_naive_bayes {
+  ($model:ident) => {
+    impl $model {
+      pub fn classes(&self) -> &[usize] {
+        &self.classes
+      }
+
+      pub fn class_log_prior(&self) -> &[f64] {
+        &self.class_log_prior
+      }
+
+      pub fn predict_log_proba<T: Float>(&self, x: &Matrix<T>) -> Result<Matrix<T>, String> {
+        let log_proba = normalize(self.joint_log_likelihood(x)?);
+        Ok(Matrix::from_fn(log_proba.rows, log_proba.cols, |i, c| T::from_f64(log_proba.data[i * log_proba.cols + c])))
+      }
+
+      pub fn predict_proba<T: Float>(&self, x: &Matrix<T>) -> Result<Matrix<T>, String> {
+        let log_proba = normalize(self.joint_log_likelihood(x)?);
+        Ok(Matrix::from_fn(log_proba.rows, log_proba.cols, |i, c| T::from_f64(log_proba.data[i * log_proba.cols + c].exp())))
+      }
+    }
+
+    impl Default for $model {
+      fn default() -> Self {
+        $model::new()
+      }
+    }
+
+    impl Classifier for $model {
+      fn fit<T: Float>(&mut self, x: &Matrix<T>, y: &[usize]) -> Result<(), String> {
+        check_samples(stringify!($model), x, y)?;
+        let (classes, encoded) = encode_classes(stringify!($model), y)?;
+        self.fit_encoded(&to_f64_matrix(x), &encoded, classes.len())?;
+        self.classes = classes;
+        Ok(())
+      }
+
+      fn predict<T: Float>(&self, x: &Matrix<T>) -> Result<Vec<usize>, String> {
+        Ok(predict_labels(&self.classes, &self.joint_log_likelihood(x)?))
+      }
+    }
+  };
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GaussianNB {
+  pub priors: Option<Vec<f64>>,
+  pub var_smoothing: f64,
+  classes: Vec<usize>,
+  class_log_prior: Vec<f64>,
+  means: Option<Matrix<f64>>,
+  variances: Option<Matrix<f64>>
+}
+
+impl GaussianNB {
+  pub fn new() -> Self {
+    GaussianNB { priors: None, var_smoothing: 1e-9, classes: Vec::new(), class_log_prior: Vec::new(), means: None, variances: None }
+  }
+
+  pub fn means(&self) -> Option<&Matrix<f64>> {
+    self.means.as_ref()
+  }
+
+  pub fn variances(&self) -> Option<&Matrix<f64>> {
+    self.variances.as_ref()
+  }
+
+  fn fit_encoded(&mut self, x: &Matrix<f64>, encoded: &[usize], n_classes: usize) -> Result<(), String> {
+    let p = x.cols;
+    let (counts, sums) = class_totals(x, encoded, n_classes);
+    let means = Matrix::from_fn(n_classes, p, |c, j| sums.data[c * p + j] / counts[c]);
+    let mut squares = Matrix::from_fn(n_classes, p, |_, _| 0.0);
+    for (i, &c) in encoded.iter().enumerate() {
+      for j in 0..p {
+        squares.data[c * p + j] += (x.data[i * p + j] - means.data[c * p + j]).powi(2);
+      }
+    }
+    let overall_mean = (0..p).map(|j| (0..x.rows).map(|i| x.data[i * p + j]).sum::<f64>() / x.rows as f64).collect::<Vec<_>>();
+    let max_variance = (0..p).map(|j| (0..x.rows).map(|i| (x.data[i * p + j] - overall_mean[j]).powi(2)).sum::<f64>() / x.rows as f64).fold(0.0, f64::max);
+    let epsilon = self.var_smoothing * max_variance.max(f64::MIN_POSITIVE);
+    self.class_log_prior = class_log_prior("GaussianNB", self.priors.as_deref(), true, &counts)?;
+    self.variances = Some(Matrix::from_fn(n_classes, p, |c, j| squares.data[c * p + j] / counts[c] + epsilon));
+    self.means = Some(means);
+    Ok(())
+  }
+
+  fn joint_log_likelihood<T: Float>(&self, x: &Matrix<T>) -> Result<Matrix<f64>, String> {
+    let (means, variances) = self.means.as_ref().zip(self.variances.as_ref()).ok_or_else(|| "GaussianNB: predict called before fit".to_string())?;
+    check_features("GaussianNB", means.cols, x)?;
+    let (p, log_two_pi) = (x.cols, (2.0 * std::f64::consts::PI).ln());
+    Ok(Matrix::from_fn(x.rows, means.rows, |i, c| {
+      self.class_log_prior[c]
+        - 0.5
+          * (0..p)
+            .map(|j| {
+              let variance = variances.data[c * p + j];
+              log_two_pi + variance.ln() + (x.data[i * p + j].to_f64() - means.data[c * p + j]).powi(2) / variance
+            })
+            .sum::<f64>()
+    }))
+  }
+}
+
+impl_naive_bayes!(GaussianNB);
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MultinomialNB {
+  pub alpha: f64,
+  pub fit_prior: bool,
+  pub priors: Option<Vec<f64>>,
+  classes: Vec<usize>,
+  class_log_prior: Vec<f64>,
+  feature_log_prob: Option<Matrix<f64>>
+}
+
+impl MultinomialNB {
+  pub fn new() -> Self {
+    MultinomialNB { alpha: 1.0, fit_prior: true, priors: None, classes: Vec::new(), class_log_prior: Vec::new(), feature_log_prob: None }
+  }
+
+  pub fn feature_log_prob(&self) -> Option<&Matrix<f64>> {
+    self.feature_log_prob.as_ref()
+  }
+
+  fn fit_encoded(&mut self, x: &Matrix<f64>, encoded: &[usize], n_classes: usize) -> Result<(), String> {
+    if !(self.alpha >= 0.0 && self.alpha.is_finite()) {
+      return Err(format!("MultinomialNB: alpha must be a non-negative number, got {}", self.alpha));
+    }
+    let alpha = self.alpha.max(MIN_ALPHA);
+    if x.data.iter().any(|&v| v < 0.0) {
+      return Err("MultinomialNB: features must be non-negative counts".to_string());
+    }
+    let p = x.cols;
+    let (counts, sums) = class_totals(x, encoded, n_classes);
+    let totals: Vec<f64> = (0..n_classes).map(|c| sums.data[c * p..(c + 1) * p].iter().sum::<f64>() + alpha * p as f64).collect();
+    self.class_log_prior = class_log_prior("MultinomialNB", self.priors.as_deref(), self.fit_prior, &counts)?;
+    self.feature_log_prob = Some(Matrix::from_fn(n_classes, p, |c, j| ((sums.data[c * p + j] + alpha) / totals[c]).ln()));
+    Ok(())
+  }
+
+  fn joint_log_likelihood<T: Float>(&self, x: &Matrix<T>) -> Result<Matrix<f64>, String> {
+    let log_prob = self.feature_log_prob.as_ref().ok_or_else(|| "MultinomialNB: predict called before fit".to_string())?;
+    check_features("MultinomialNB", log_prob.cols, x)?;
+    let p = x.cols;
+    Ok(Matrix::from_fn(x.rows, log_prob.rows, |i, c| {
+      self.class_log_prior[c] + (0..p).map(|j| x.data[i * p + j].to_f64() * log_prob.data[c * p + j]).sum::<f64>()
+    }))
+  }
+}
+
+impl_naive_bayes!(MultinomialNB);
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BernoulliNB {
+  pub alpha: f64,
+  pub binarize: Option<f64>,
+  pub fit_prior: bool,
+  pub priors: Option<Vec<f64>>,
+  classes: Vec<usize>,
+  class_log_prior: Vec<f64>,
+  feature_log_prob: Option<Matrix<f64>>
+}
+
+impl BernoulliNB {
+  pub fn new() -> Self {
+    BernoulliNB {
+      alpha: 1.0,
+      binarize: Some(0.0),
+      fit_prior: true,
+      priors: None,
+      classes: Vec::new(),
+      class_log_prior: Vec::new(),
+      feature_log_prob: None
+    }
+  }
+
+  pub fn feature_log_prob(&self) -> Option<&Matrix<f64>> {
+    self.feature_log_prob.as_ref()
+  }
+
+  fn indicator(&self, value: f64) -> f64 {
+    match self.binarize {
+      Some(threshold) => (value > threshold) as u8 as f64,
+      None => value
+    }
+  }
+
+  fn fit_encoded(&mut self, x: &Matrix<f64>, encoded: &[usize], n_classes: usize) -> Result<(), String> {
+    if !(self.alpha >= 0.0 && self.alpha.is_finite()) {
+      return Err(format!("BernoulliNB: alpha must be a non-negative number, got {}", self.alpha));
+    }
+    let alpha = self.alpha.max(MIN_ALPHA);
+    let p = x.cols;
+    let binary = Matrix::from_fn(x.rows, p, |i, j| self.indicator(x.data[i * p + j]));
+    let (counts, sums) = class_totals(&binary, encoded, n_classes);
+    self.class_log_prior = class_log_prior("BernoulliNB", self.priors.as_deref(), self.fit_prior, &counts)?;
+    self.feature_log_prob = Some(Matrix::from_fn(n_classes, p, |c, j| ((sums.data[c * p + j] + alpha) / (counts[c] + 2.0 * alpha)).ln()));
+    Ok(())
+  }
+
+  fn joint_log_likelihood<T: Float>(&self, x: &Matrix<T>) -> Result<Matrix<f64>, String> {
+    let log_prob = self.feature_log_prob.as_ref().ok_or_else(|| "BernoulliNB: predict called before fit".to_string())?;
+    check_features("BernoulliNB", log_prob.cols, x)?;
+    let p = x.cols;
+    Ok(Matrix::from_fn(x.rows, log_prob.rows, |i, c| {
+      self.class_log_prior[c]
+        + (0..p)
+          .map(|j| {
+            let (present, log_p) = (self.indicator(x.data[i * p + j].to_f64()), log_prob.data[c * p + j]);
+            present * log_p + (1.0 - present) * (-log_p.exp()).ln_1p()
+          })
+          .sum::<f64>()
+    }))
+  }
+}
+
+impl_naive_bayes!(BernoulliNB);
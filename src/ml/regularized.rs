@@ -0,0 +1,399 @@
+use super::linear::{center, gram, linear_predict};
+use super::model_selection::{Fold, KFold};
+use super::{check_samples, select_rows, to_f64, Regressor};
+use crate::math::linalg::cholesky_solve;
+use crate::math::matrix::Matrix;
+use crate::math::scalar::Float;
+use crate::math::vector::Vector;
+
+fn check_alpha(name: &str, alpha: f64) -> Result<(), String> {
+  if !(alpha >= 0.0 && alpha.is_finite()) {
+    return Err(format!("{}: alpha must be a non-negative number, got {}", name, alpha));
+  }
+  Ok(())
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ridge {
+  pub alpha: f64,
+  pub fit_intercept: bool,
+  coefficients: Option<Vec<f64>>,
+  intercept: f64
+}
+
+impl Ridge {
+  pub fn new(alpha: f64) -> Self {
+    Ridge { alpha, fit_intercept: true, coefficients: None, intercept: 0.0 }
+  }
+
+  pub fn coefficients(&self) -> Option<&[f64]> {
+    self.coefficients.as_deref()
+  }
+
+  pub fn intercept(&self) -> f64 {
+    self.intercept
+  }
+}
+
+impl Default for Ridge {
+  fn default() -> Self {
+    Ridge::new(1.0)
+  }
+}
+
+impl Regressor for Ridge {
+  fn fit<T: Float>(&mut self, x: &Matrix<T>, y: &Vector<T>) -> Result<(), String> {
+    check_samples("Ridge", x, &y.data)?;
+    check_alpha("Ridge", self.alpha)?;
+    let data = center(x, y, self.fit_intercept);
+    let (mut xtx, xty) = gram(&data.x, &data.y);
+    for j in 0..xtx.cols {
+      xtx.data[j * xtx.cols + j] += self.alpha;
+    }
+    let coefficients = cholesky_solve(&xtx, &xty).map_err(|e| format!("Ridge: {}", e))?;
+    self.intercept = data.y_mean - coefficients.iter().zip(&data.x_mean).map(|(c, m)| c * m).sum::<f64>();
+    self.coefficients = Some(coefficients);
+    Ok(())
+  }
+
+  fn predict<T: Float>(&self, x: &Matrix<T>) -> Result<Vector<T>, String> {
+    linear_predict("Ridge", x, self.coefficients(), self.intercept)
+  }
+}
+
+fn soft_threshold(value: f64, threshold: f64) -> f64 {
+  value.signum() * (value.abs() - threshold).max(0.0)
+}
+
+pub(crate) fn coordinate_descent(x: &Matrix<f64>, y: &[f64], l1: f64, l2: f64, max_iter: usize, tol: f64, mut beta: Vec<f64>) -> (Vec<f64>, usize) {
+  let (n, p) = (x.rows, x.cols);
+  let norms: Vec<f64> = (0..p).map(|j| (0..n).map(|i| x.data[i * p + j].powi(2)).sum::<f64>() / n as f64).collect();
+  let mut residual: Vec<f64> = (0..n).map(|i| y[i] - (0..p).map(|j| x.data[i * p + j] * beta[j]).sum::<f64>()).collect();
+  for iter in 0..max_iter {
+    let mut largest_step = 0.0f64;
+    let mut largest_weight = 0.0f64;
+    for j in 0..p {
+      if norms[j] == 0.0 {
+        continue;
+      }
+      let rho = (0..n).map(|i| x.data[i * p + j] * residual[i]).sum::<f64>() / n as f64 + norms[j] * beta[j];
+      let updated = soft_threshold(rho, l1) / (norms[j] + l2);
+      let step = updated - beta[j];
+      if step != 0.0 {
+        for (i, r) in residual.iter_mut().enumerate() {
+          *r -= step * x.data[i * p + j];
+        }
+        beta[j] = updated;
+      }
+      largest_step = largest_step.max(step.abs());
+      largest_weight = largest_weight.max(updated.abs());
+    }
+    if largest_step <= tol * largest_weight.max(1.0) {
+      return (beta, iter + 1);
+    }
+  }
+  (beta, max_iter)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ElasticNet {
+  pub alpha: f64,
+  pub l1_ratio: f64,
+  pub fit_intercept: bool,
+  pub max_iter: usize,
+  pub tol: f64,
+  pub warm_start: bool,
+  coefficients: Option<Vec<f64>>,
+  intercept: f64,
+  n_iter: usize
+}
+
+impl ElasticNet {
+  pub fn new(alpha: f64, l1_ratio: f64) -> Self {
+    ElasticNet { alpha, l1_ratio, fit_intercept: true, max_iter: 1000, tol: 1e-6, warm_start: false, coefficients: None, intercept: 0.0, n_iter: 0 }
+  }
+
+  pub fn coefficients(&self) -> Option<&[f64]> {
+    self.coefficients.as_deref()
+  }
+
+  pub fn intercept(&self) -> f64 {
+    self.intercept
+  }
+
+  pub fn n_iter(&self) -> usize {
+    self.n_iter
+  }
+
+  fn name(&self) -> &'static str {
+    if self.l1_ratio == 1.0 { "Lasso" } else { "ElasticNet" }
+  }
+}
+
+impl Default for ElasticNet {
+  fn default() -> Self {
+    ElasticNet::new(1.0, 0.5)
+  }
+}
+
+impl Regressor for ElasticNet {
+  fn fit<T: Float>(&mut self, x: &Matrix<T>, y: &Vector<T>) -> Result<(), String> {
+    check_samples(self.name(), x, &y.data)?;
+    check_alpha(self.name(), self.alpha)?;
+    if !(0.0..=1.0).contains(&self.l1_ratio) {
+      return Err(format!("{}: l1_ratio must be in [0, 1], got {}", self.name(), self.l1_ratio));
+    }
+    let data = center(x, y, self.fit_intercept);
+    let init = match &self.coefficients {
+      Some(previous) if self.warm_start && previous.len() == x.cols => previous.clone(),
+      _ => vec![0.0; x.cols]
+    };
+    let (coefficients, n_iter) =
+      coordinate_descent(&data.x, &data.y, self.alpha * self.l1_ratio, self.alpha * (1.0 - self.l1_ratio), self.max_iter, self.tol, init);
+    self.n_iter = n_iter;
+    self.intercept = data.y_mean - coefficients.iter().zip(&data.x_mean).map(|(c, m)| c * m).sum::<f64>();
+    self.coefficients = Some(coefficients);
+    Ok(())
+  }
+
+  fn predict<T: Float>(&self, x: &Matrix<T>) -> Result<Vector<T>, String> {
+    linear_predict(self.name(), x, self.coefficients(), self.intercept)
+  }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Lasso {
+  pub alpha: f64,
+  pub fit_intercept: bool,
+  pub max_iter: usize,
+  pub tol: f64,
+  model: ElasticNet
+}
+
+impl Lasso {
+  pub fn new(alpha: f64) -> Self {
+    Lasso { alpha, fit_intercept: true, max_iter: 1000, tol: 1e-6, model: ElasticNet::new(alpha, 1.0) }
+  }
+
+  pub fn coefficients(&self) -> Option<&[f64]> {
+    self.model.coefficients()
+  }
+
+  pub fn intercept(&self) -> f64 {
+    self.model.intercept()
+  }
+
+  pub fn n_iter(&self) -> usize {
+    self.model.n_iter()
+  }
+}
+
+impl Default for Lasso {
+  fn default() -> Self {
+    Lasso::new(1.0)
+  }
+}
+
+impl Regressor for Lasso {
+  fn fit<T: Float>(&mut self, x: &Matrix<T>, y: &Vector<T>) -> Result<(), String> {
+    self.model.alpha = self.alpha;
+    self.model.fit_intercept = self.fit_intercept;
+    self.model.max_iter = self.max_iter;
+    self.model.tol = self.tol;
+    self.model.fit(x, y)
+  }
+
+  fn predict<T: Float>(&self, x: &Matrix<T>) -> Result<Vector<T>, String> {
+    self.model.predict(x)
+  }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RegularizationPath {
+  pub alphas: Vec<f64>,
+  pub coefficients: Vec<Vec<f64>>,
+  pub intercepts: Vec<f64>
+}
+
+pub fn alpha_grid<T: Float>(x: &Matrix<T>, y: &Vector<T>, l1_ratio: f64, n_alphas: usize, fit_intercept: bool) -> Result<Vec<f64>, String> {
+  check_samples("alpha_grid", x, &y.data)?;
+  if l1_ratio <= 0.0 || n_alphas == 0 {
+    return Err(format!("alpha_grid: needs l1_ratio > 0 and n_alphas > 0, got {} and {}", l1_ratio, n_alphas));
+  }
+  let data = center(x, y, fit_intercept);
+  let (_, xty) = gram(&data.x, &data.y);
+  let alpha_max = xty.iter().fold(0.0f64, |acc, v| acc.max(v.abs())) / (x.rows as f64 * l1_ratio);
+  if alpha_max == 0.0 {
+    return Ok(vec![0.0; n_alphas]);
+  }
+  let ratio: f64 = 1e-3;
+  Ok((0..n_alphas).map(|i| alpha_max * ratio.powf(i as f64 / (n_alphas - 1).max(1) as f64)).collect())
+}
+
+pub fn enet_path<T: Float>(x: &Matrix<T>, y: &Vector<T>, l1_ratio: f64, alphas: &[f64], fit_intercept: bool) -> Result<RegularizationPath, String> {
+  let mut model = ElasticNet::new(0.0, l1_ratio);
+  model.fit_intercept = fit_intercept;
+  model.warm_start = true;
+  let mut alphas = alphas.to_vec();
+  alphas.sort_by(|a, b| b.total_cmp(a));
+  let mut path = RegularizationPath { alphas: Vec::new(), coefficients: Vec::new(), intercepts: Vec::new() };
+  for alpha in alphas {
+    model.alpha = alpha;
+    model.fit(x, y)?;
+    path.alphas.push(alpha);
+    path.coefficients.push(model.coefficients().unwrap_or_default().to_vec());
+    path.intercepts.push(model.intercept());
+  }
+  Ok(path)
+}
+
+pub fn cross_validate_alpha<T, M, F>(x: &Matrix<T>, y: &Vector<T>, alphas: &[f64], folds: usize, make: F) -> Result<Vec<f64>, String>
+where
+  T: Float,
+  M: Regressor,
+  F: Fn(f64) -> M
+{
+  check_samples("cross_validate_alpha", x, &y.data)?;
+  if alphas.is_empty() {
+    return Err("cross_validate_alpha: needs at least one alpha".to_string());
+  }
+  let mut errors = vec![0.0; alphas.len()];
+  for Fold { train, test } in KFold::new(folds).split(x.rows)? {
+    let (x_train, x_test) = (select_rows(x, &train), select_rows(x, &test));
+    let y_train = Vector::from(train.iter().map(|&i| y.data[i]).collect::<Vec<_>>());
+    let y_test = to_f64(&test.iter().map(|&i| y.data[i]).collect::<Vec<_>>());
+    for (error, &alpha) in errors.iter_mut().zip(alphas) {
+      let mut model = make(alpha);
+      model.fit(&x_train, &y_train)?;
+      let predicted = to_f64(&model.predict(&x_test)?.data);
+      *error += predicted.iter().zip(&y_test).map(|(p, t)| (p - t).powi(2)).sum::<f64>() / x.rows as f64;
+    }
+  }
+  Ok(errors)
+}
+
+fn best_alpha(alphas: &[f64], errors: &[f64]) -> f64 {
+  let best = (0..alphas.len()).min_by(|&a, &b| errors[a].total_cmp(&errors[b])).unwrap_or(0);
+  alphas[best]
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RidgeCV {
+  pub alphas: Vec<f64>,
+  pub folds: usize,
+  pub fit_intercept: bool,
+  mse: Vec<f64>,
+  model: Option<Ridge>
+}
+
+impl RidgeCV {
+  pub fn new(alphas: Vec<f64>) -> Self {
+    RidgeCV { alphas, folds: 5, fit_intercept: true, mse: Vec::new(), model: None }
+  }
+
+  pub fn alpha(&self) -> Option<f64> {
+    self.model.as_ref().map(|model| model.alpha)
+  }
+
+  pub fn mse(&self) -> &[f64] {
+    &self.mse
+  }
+
+  pub fn model(&self) -> Option<&Ridge> {
+    self.model.as_ref()
+  }
+}
+
+impl Regressor for RidgeCV {
+  fn fit<T: Float>(&mut self, x: &Matrix<T>, y: &Vector<T>) -> Result<(), String> {
+    let make = |alpha| {
+      let mut model = Ridge::new(alpha);
+      model.fit_intercept = self.fit_intercept;
+      model
+    };
+    self.mse = cross_validate_alpha(x, y, &self.alphas, self.folds, make)?;
+    let mut model = make(best_alpha(&self.alphas, &self.mse));
+    model.fit(x, y)?;
+    self.model = Some(model);
+    Ok(())
+  }
+
+  fn predict<T: Float>(&self, x: &Matrix<T>) -> Result<Vector<T>, String> {
+    self.model.as_ref().ok_or_else(|| "RidgeCV: predict called before fit".to_string())?.predict(x)
+  }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ElasticNetCV {
+  pub l1_ratio: f64,
+  pub alphas: Option<Vec<f64>>,
+  pub n_alphas: usize,
+  pub folds: usize,
+  pub fit_intercept: bool,
+  pub max_iter: usize,
+  pub tol: f64,
+  path: Vec<f64>,
+  mse: Vec<f64>,
+  model: Option<ElasticNet>
+}
+
+impl ElasticNetCV {
+  pub fn new(l1_ratio: f64) -> Self {
+    ElasticNetCV {
+      l1_ratio,
+      alphas: None,
+      n_alphas: 100,
+      folds: 5,
+      fit_intercept: true,
+      max_iter: 1000,
+      tol: 1e-6,
+      path: Vec::new(),
+      mse: Vec::new(),
+      model: None
+    }
+  }
+
+  pub fn alpha(&self) -> Option<f64> {
+    self.model.as_ref().map(|model| model.alpha)
+  }
+
+  pub fn alphas_tried(&self) -> &[f64] {
+    &self.path
+  }
+
+  pub fn mse(&self) -> &[f64] {
+    &self.mse
+  }
+
+  pub fn model(&self) -> Option<&ElasticNet> {
+    self.model.as_ref()
+  }
+}
+
+impl Regressor for ElasticNetCV {
+  fn fit<T: Float>(&mut self, x: &Matrix<T>, y: &Vector<T>) -> Result<(), String> {
+    self.path = match &self.alphas {
+      Some(alphas) => alphas.clone(),
+      None => alpha_grid(x, y, self.l1_ratio, self.n_alphas, self.fit_intercept)?
+    };
+    let make = |alpha| {
+      let mut model = ElasticNet::new(alpha, self.l1_ratio);
+      model.fit_intercept = self.fit_intercept;
+      model.max_iter = self.max_iter;
+      model.tol = self.tol;
+      model
+    };
+    self.mse = cross_validate_alpha(x, y, &self.path, self.folds, make)?;
+    let mut model = make(best_alpha(&self.path, &self.mse));
+    model.fit(x, y)?;
+    self.model = Some(model);
+    Ok(())
+  }
+
+  fn predict<T: Float>(&self, x: &Matrix<T>) -> Result<Vector<T>, String> {
+    self.model.as_ref().ok_or_else(|| "ElasticNetCV: predict called before fit".to_string())?.predict(x)
+  }
+}
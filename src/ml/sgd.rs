@@ -0,0 +1,477 @@
+use super::{argmax, check_features, check_samples, encode_classes, seeded_rng, to_f64, to_f64_matrix, Classifier, Regressor};
+use crate::math::matrix::Matrix;
+use crate::math::scalar::Float;
+use crate::math::vector::Vector;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LearningRate {
+  Constant(f64),
+  Optimal,
+  InvScaling { eta0: f64, power_t: f64 }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Penalty {
+  None,
+  L2,
+  L1,
+  ElasticNet
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ClassificationLoss {
+  Hinge,
+  Log,
+  SquaredHinge,
+  ModifiedHuber
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RegressionLoss {
+  SquaredError,
+  Huber(f64),
+  EpsilonInsensitive(f64)
+}
+
+impl ClassificationLoss {
+  fn eval(self, p: f64, y: f64) -> (f64, f64) {
+    let z = y * p;
+    match self {
+      ClassificationLoss::Hinge => if z < 1.0 { (1.0 - z, -y) } else { (0.0, 0.0) },
+      ClassificationLoss::Log => ((-z).max(0.0) + (-z.abs()).exp().ln_1p(), -y / (1.0 + z.exp())),
+      ClassificationLoss::SquaredHinge => if z < 1.0 { ((1.0 - z).powi(2), -2.0 * y * (1.0 - z)) } else { (0.0, 0.0) },
+      ClassificationLoss::ModifiedHuber => {
+        if z >= 1.0 {
+          (0.0, 0.0)
+        } else if z >= -1.0 {
+          ((1.0 - z).powi(2), -2.0 * y * (1.0 - z))
+        } else {
+          (-4.0 * z, -4.0 * y)
+        }
+      }
+    }
+  }
+}
+
+impl RegressionLoss {
+  fn eval(self, p: f64, y: f64) -> (f64, f64) {
+    let r = p - y;
+    match self {
+      RegressionLoss::SquaredError => (0.5 * r * r, r),
+      RegressionLoss::Huber(epsilon) => if r.abs() <= epsilon { (0.5 * r * r, r) } else { (epsilon * (r.abs() - 0.5 * epsilon), epsilon * r.signum()) },
+      RegressionLoss::EpsilonInsensitive(epsilon) => if r.abs() > epsilon { (r.abs() - epsilon, r.signum()) } else { (0.0, 0.0) }
+    }
+  }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct State {
+  weights: Matrix<f64>,
+  intercepts: Vec<f64>,
+  t: f64,
+  t0: f64
+}
+
+struct Schedule {
+  penalty: Penalty,
+  alpha: f64,
+  l1_ratio: f64,
+  fit_intercept: bool,
+  learning_rate: LearningRate
+}
+
+impl Schedule {
+  fn check(&self, name: &str) -> Result<(), String> {
+    if !(self.alpha >= 0.0 && self.alpha.is_finite() && (0.0..=1.0).contains(&self.l1_ratio)) {
+      return Err(format!("{}: needs alpha >= 0 and l1_ratio in [0, 1], got {} and {}", name, self.alpha, self.l1_ratio));
+    }
+    if self.learning_rate == LearningRate::Optimal && self.alpha <= 0.0 {
+      return Err(format!("{}: the optimal learning rate needs alpha > 0", name));
+    }
+    Ok(())
+  }
+
+  fn initial_t0<L>(&self, loss: L) -> f64
+  where
+    L: Fn(f64, f64) -> (f64, f64)
+  {
+    if self.learning_rate != LearningRate::Optimal {
+      return 0.0;
+    }
+    let typical = (1.0 / self.alpha.sqrt()).sqrt();
+    let eta0 = typical / loss(-typical, 1.0).1.abs().max(1.0);
+    1.0 / (eta0 * self.alpha)
+  }
+
+  fn eta(&self, state: &State) -> f64 {
+    match self.learning_rate {
+      LearningRate::Constant(eta) => eta,
+      LearningRate::Optimal => 1.0 / (self.alpha * (state.t0 + state.t)),
+      LearningRate::InvScaling { eta0, power_t } => eta0 / (state.t + 1.0).powf(power_t)
+    }
+  }
+
+  fn strengths(&self) -> (f64, f64) {
+    match self.penalty {
+      Penalty::None => (0.0, 0.0),
+      Penalty::L2 => (0.0, self.alpha),
+      Penalty::L1 => (self.alpha, 0.0),
+      Penalty::ElasticNet => (self.alpha * self.l1_ratio, self.alpha * (1.0 - self.l1_ratio))
+    }
+  }
+
+  fn epoch<L, Y>(&self, state: &mut State, x: &Matrix<f64>, order: &[usize], target: Y, loss: L) -> f64
+  where
+    L: Fn(f64, f64) -> (f64, f64),
+    Y: Fn(usize, usize) -> f64
+  {
+    let p = x.cols;
+    let (l1, l2) = self.strengths();
+    let mut total = 0.0;
+    for &i in order {
+      let eta = self.eta(state);
+      let features = &x.data[i * p..(i + 1) * p];
+      for (m, intercept) in state.intercepts.iter_mut().enumerate() {
+        let weights = &mut state.weights.data[m * p..(m + 1) * p];
+        let prediction = *intercept + weights.iter().zip(features).map(|(w, v)| w * v).sum::<f64>();
+        let (value, gradient) = loss(prediction, target(i, m));
+        total += value;
+        for (w, v) in weights.iter_mut().zip(features) {
+          *w = *w * (1.0 - eta * l2) - eta * gradient * v;
+          if l1 > 0.0 {
+            *w = w.signum() * (w.abs() - eta * l1).max(0.0);
+          }
+        }
+        if self.fit_intercept {
+          *intercept -= eta * gradient;
+        }
+      }
+      state.t += 1.0;
+    }
+    total / order.len().max(1) as f64
+  }
+}
+
+struct Stopping {
+  max_iter: usize,
+  tol: Option<f64>,
+  n_iter_no_change: usize,
+  shuffle: bool,
+  seed: Option<u64>
+}
+
+impl Stopping {
+  fn run<F>(&self, n: usize, mut epoch: F) -> usize
+  where
+    F: FnMut(&[usize]) -> f64
+  {
+    let mut rng = seeded_rng(self.seed);
+    let mut order: Vec<usize> = (0..n).collect();
+    let (mut best, mut stale) = (f64::INFINITY, 0);
+    for iter in 0..self.max_iter {
+      if self.shuffle {
+        rng.shuffle(&mut order);
+      }
+      let loss = epoch(&order);
+      if let Some(tol) = self.tol {
+        stale = if loss > best - tol { stale + 1 } else { 0 };
+        if stale >= self.n_iter_no_change {
+          return iter + 1;
+        }
+      }
+      best = best.min(loss);
+    }
+    self.max_iter
+  }
+}
+
+fn linear_scores(name: &str, state: Option<&State>, x: &Matrix<f64>) -> Result<Matrix<f64>, String> {
+  let state = state.ok_or_else(|| format!("{}: predict called before fit", name))?;
+  check_features(name, state.weights.cols, x)?;
+  let p = x.cols;
+  Ok(Matrix::from_fn(x.rows, state.intercepts.len(), |i, m| {
+    state.intercepts[m] + x.data[i * p..(i + 1) * p].iter().zip(&state.weights.data[m * p..(m + 1) * p]).map(|(a, b)| a * b).sum::<f64>()
+  }))
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SGDClassifier {
+  pub loss: ClassificationLoss,
+  pub penalty: Penalty,
+  pub alpha: f64,
+  pub l1_ratio: f64,
+  pub fit_intercept: bool,
+  pub learning_rate: LearningRate,
+  pub max_iter: usize,
+  pub tol: Option<f64>,
+  pub n_iter_no_change: usize,
+  pub shuffle: bool,
+  pub seed: Option<u64>,
+  classes: Vec<usize>,
+  state: Option<State>,
+  n_iter: usize
+}
+
+impl SGDClassifier {
+  pub fn new() -> Self {
+    SGDClassifier {
+      loss: ClassificationLoss::Hinge,
+      penalty: Penalty::L2,
+      alpha: 1e-4,
+      l1_ratio: 0.15,
+      fit_intercept: true,
+      learning_rate: LearningRate::Optimal,
+      max_iter: 1000,
+      tol: Some(1e-3),
+      n_iter_no_change: 5,
+      shuffle: true,
+      seed: None,
+      classes: Vec::new(),
+      state: None,
+      n_iter: 0
+    }
+  }
+
+  pub fn classes(&self) -> &[usize] {
+    &self.classes
+  }
+
+  pub fn coefficients(&self) -> Option<&Matrix<f64>> {
+    self.state.as_ref().map(|state| &state.weights)
+  }
+
+  pub fn intercepts(&self) -> &[f64] {
+    self.state.as_ref().map_or(&[], |state| &state.intercepts)
+  }
+
+  pub fn n_iter(&self) -> usize {
+    self.n_iter
+  }
+
+  pub fn reset(&mut self) {
+    self.classes.clear();
+    self.state = None;
+    self.n_iter = 0;
+  }
+
+  fn schedule(&self) -> Schedule {
+    Schedule { penalty: self.penalty, alpha: self.alpha, l1_ratio: self.l1_ratio, fit_intercept: self.fit_intercept, learning_rate: self.learning_rate }
+  }
+
+  fn prepare<T: Float>(&mut self, x: &Matrix<T>, y: &[usize], classes: Option<&[usize]>) -> Result<(Matrix<f64>, Vec<usize>), String> {
+    check_samples("SGDClassifier", x, y)?;
+    let schedule = self.schedule();
+    schedule.check("SGDClassifier")?;
+    if self.state.is_none() {
+      let classes = match classes {
+        Some(classes) => encode_classes("SGDClassifier", classes)?.0,
+        None => return Err("SGDClassifier: the first call to partial_fit must list every class".to_string())
+      };
+      let outputs = if classes.len() == 2 { 1 } else { classes.len() };
+      let loss = self.loss;
+      self.state = Some(State {
+        weights: Matrix::from_fn(outputs, x.cols, |_, _| 0.0),
+        intercepts: vec![0.0; outputs],
+        t: 1.0,
+        t0: schedule.initial_t0(|p, y| loss.eval(p, y))
+      });
+      self.classes = classes;
+    } else if let Some(classes) = classes {
+      if encode_classes("SGDClassifier", classes)?.0 != self.classes {
+        return Err(format!("SGDClassifier: classes {:?} differ from the fitted classes {:?}", classes, self.classes));
+      }
+    }
+    let encoded = y
+      .iter()
+      .map(|label| self.classes.binary_search(label).map_err(|_| format!("SGDClassifier: label {} is not one of {:?}", label, self.classes)))
+      .collect::<Result<Vec<_>, _>>()?;
+    check_features("SGDClassifier", self.state.as_ref().map_or(0, |state| state.weights.cols), x)?;
+    Ok((to_f64_matrix(x), encoded))
+  }
+
+  fn epoch(&mut self, x: &Matrix<f64>, encoded: &[usize], order: &[usize]) -> f64 {
+    let (schedule, loss) = (self.schedule(), self.loss);
+    let binary = self.classes.len() == 2;
+    let target = |i: usize, m: usize| if (binary && encoded[i] == 1) || (!binary && encoded[i] == m) { 1.0 } else { -1.0 };
+    match self.state.as_mut() {
+      Some(state) => schedule.epoch(state, x, order, target, |p, y| loss.eval(p, y)),
+      None => 0.0
+    }
+  }
+
+  pub fn partial_fit<T: Float>(&mut self, x: &Matrix<T>, y: &[usize], classes: Option<&[usize]>) -> Result<(), String> {
+    let (x, encoded) = self.prepare(x, y, classes)?;
+    let order: Vec<usize> = (0..x.rows).collect();
+    self.epoch(&x, &encoded, &order);
+    self.n_iter += 1;
+    Ok(())
+  }
+
+  pub fn decision_function<T: Float>(&self, x: &Matrix<T>) -> Result<Matrix<T>, String> {
+    let scores = linear_scores("SGDClassifier", self.state.as_ref(), &to_f64_matrix(x))?;
+    Ok(Matrix::from_fn(scores.rows, scores.cols, |i, m| T::from_f64(scores.data[i * scores.cols + m])))
+  }
+
+  pub fn predict_proba<T: Float>(&self, x: &Matrix<T>) -> Result<Matrix<T>, String> {
+    if self.loss != ClassificationLoss::Log {
+      return Err(format!("SGDClassifier: predict_proba needs the log loss, not {:?}", self.loss));
+    }
+    let scores = linear_scores("SGDClassifier", self.state.as_ref(), &to_f64_matrix(x))?;
+    let sigmoid = |z: f64| 1.0 / (1.0 + (-z).exp());
+    let k = self.classes.len();
+    Ok(Matrix::from_fn(x.rows, k, |i, c| {
+      let row = &scores.data[i * scores.cols..(i + 1) * scores.cols];
+      T::from_f64(match row {
+        [score] => if c == 1 { sigmoid(*score) } else { 1.0 - sigmoid(*score) },
+        _ => sigmoid(row[c]) / row.iter().map(|&z| sigmoid(z)).sum::<f64>()
+      })
+    }))
+  }
+}
+
+impl Default for SGDClassifier {
+  fn default() -> Self {
+    SGDClassifier::new()
+  }
+}
+
+impl Classifier for SGDClassifier {
+  fn fit<T: Float>(&mut self, x: &Matrix<T>, y: &[usize]) -> Result<(), String> {
+    self.reset();
+    let (x, encoded) = self.prepare(x, y, Some(y))?;
+    let stopping = Stopping { max_iter: self.max_iter, tol: self.tol, n_iter_no_change: self.n_iter_no_change, shuffle: self.shuffle, seed: self.seed };
+    self.n_iter = stopping.run(x.rows, |order| self.epoch(&x, &encoded, order));
+    Ok(())
+  }
+
+  fn predict<T: Float>(&self, x: &Matrix<T>) -> Result<Vec<usize>, String> {
+    let scores = linear_scores("SGDClassifier", self.state.as_ref(), &to_f64_matrix(x))?;
+    Ok(
+      (0..scores.rows)
+        .map(|i| match &scores.data[i * scores.cols..(i + 1) * scores.cols] {
+          [score] => self.classes[(*score > 0.0) as usize],
+          row => self.classes[argmax(row)]
+        })
+        .collect()
+    )
+  }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SGDRegressor {
+  pub loss: RegressionLoss,
+  pub penalty: Penalty,
+  pub alpha: f64,
+  pub l1_ratio: f64,
+  pub fit_intercept: bool,
+  pub learning_rate: LearningRate,
+  pub max_iter: usize,
+  pub tol: Option<f64>,
+  pub n_iter_no_change: usize,
+  pub shuffle: bool,
+  pub seed: Option<u64>,
+  state: Option<State>,
+  n_iter: usize
+}
+
+impl SGDRegressor {
+  pub fn new() -> Self {
+    SGDRegressor {
+      loss: RegressionLoss::SquaredError,
+      penalty: Penalty::L2,
+      alpha: 1e-4,
+      l1_ratio: 0.15,
+      fit_intercept: true,
+      learning_rate: LearningRate::InvScaling { eta0: 0.01, power_t: 0.25 },
+      max_iter: 1000,
+      tol: Some(1e-3),
+      n_iter_no_change: 5,
+      shuffle: true,
+      seed: None,
+      state: None,
+      n_iter: 0
+    }
+  }
+
+  pub fn coefficients(&self) -> Option<&[f64]> {
+    self.state.as_ref().map(|state| state.weights.data.as_slice())
+  }
+
+  pub fn intercept(&self) -> f64 {
+    self.state.as_ref().map_or(0.0, |state| state.intercepts[0])
+  }
+
+  pub fn n_iter(&self) -> usize {
+    self.n_iter
+  }
+
+  pub fn reset(&mut self) {
+    self.state = None;
+    self.n_iter = 0;
+  }
+
+  fn schedule(&self) -> Schedule {
+    Schedule { penalty: self.penalty, alpha: self.alpha, l1_ratio: self.l1_ratio, fit_intercept: self.fit_intercept, learning_rate: self.learning_rate }
+  }
+
+  fn prepare<T: Float>(&mut self, x: &Matrix<T>, y: &Vector<T>) -> Result<(Matrix<f64>, Vec<f64>), String> {
+    check_samples("SGDRegressor", x, &y.data)?;
+    let schedule = self.schedule();
+    schedule.check("SGDRegressor")?;
+    if self.state.is_none() {
+      let loss = self.loss;
+      self.state = Some(State {
+        weights: Matrix::from_fn(1, x.cols, |_, _| 0.0),
+        intercepts: vec![0.0],
+        t: 1.0,
+        t0: schedule.initial_t0(|p, y| loss.eval(p, y))
+      });
+    }
+    check_features("SGDRegressor", self.state.as_ref().map_or(0, |state| state.weights.cols), x)?;
+    Ok((to_f64_matrix(x), to_f64(&y.data)))
+  }
+
+  fn epoch(&mut self, x: &Matrix<f64>, targets: &[f64], order: &[usize]) -> f64 {
+    let (schedule, loss) = (self.schedule(), self.loss);
+    match self.state.as_mut() {
+      Some(state) => schedule.epoch(state, x, order, |i, _| targets[i], |p, y| loss.eval(p, y)),
+      None => 0.0
+    }
+  }
+
+  pub fn partial_fit<T: Float>(&mut self, x: &Matrix<T>, y: &Vector<T>) -> Result<(), String> {
+    let (x, targets) = self.prepare(x, y)?;
+    let order: Vec<usize> = (0..x.rows).collect();
+    self.epoch(&x, &targets, &order);
+    self.n_iter += 1;
+    Ok(())
+  }
+}
+
+impl Default for SGDRegressor {
+  fn default() -> Self {
+    SGDRegressor::new()
+  }
+}
+
+impl Regressor for SGDRegressor {
+  fn fit<T: Float>(&mut self, x: &Matrix<T>, y: &Vector<T>) -> Result<(), String> {
+    self.reset();
+    let (x, targets) = self.prepare(x, y)?;
+    let stopping = Stopping { max_iter: self.max_iter, tol: self.tol, n_iter_no_change: self.n_iter_no_change, shuffle: self.shuffle, seed: self.seed };
+    self.n_iter = stopping.run(x.rows, |order| self.epoch(&x, &targets, order));
+    Ok(())
+  }
+
+  fn predict<T: Float>(&self, x: &Matrix<T>) -> Result<Vector<T>, String> {
+    let scores = linear_scores("SGDRegressor", self.state.as_ref(), &to_f64_matrix(x))?;
+    Ok(Vector::from(scores.data.iter().map(|&v| T::from_f64(v)).collect::<Vec<_>>()))
+  }
+}
@@ -0,0 +1,381 @@
+use super::{argmax, check_features, check_samples, encode_classes, squared_distance, to_f64, to_f64_matrix, Classifier, Regressor};
+use crate::math::matrix::Matrix;
+use crate::math::scalar::Float;
+use crate::math::vector::Vector;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Kernel {
+  Linear,
+  Rbf { gamma: Option<f64> }
+}
+
+impl Kernel {
+  fn resolve(self, x: &Matrix<f64>) -> Kernel {
+    match self {
+      Kernel::Rbf { gamma: None } => {
+        let n = x.data.len() as f64;
+        let mean = x.data.iter().sum::<f64>() / n;
+        let variance = x.data.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+        Kernel::Rbf { gamma: Some(if variance > 0.0 { 1.0 / (x.cols as f64 * variance) } else { 1.0 }) }
+      }
+      kernel => kernel
+    }
+  }
+
+  pub fn apply(&self, a: &[f64], b: &[f64]) -> f64 {
+    match self {
+      Kernel::Linear => a.iter().zip(b).map(|(x, y)| x * y).sum(),
+      Kernel::Rbf { gamma } => (-gamma.unwrap_or(1.0) * squared_distance(a, b)).exp()
+    }
+  }
+}
+
+fn row(x: &Matrix<f64>, i: usize) -> &[f64] {
+  &x.data[i * x.cols..(i + 1) * x.cols]
+}
+
+fn gram_matrix(x: &Matrix<f64>, kernel: Kernel) -> Matrix<f64> {
+  let mut k = Matrix::from_fn(x.rows, x.rows, |_, _| 0.0);
+  for i in 0..x.rows {
+    for j in 0..=i {
+      let value = kernel.apply(row(x, i), row(x, j));
+      k.data[i * x.rows + j] = value;
+      k.data[j * x.rows + i] = value;
+    }
+  }
+  k
+}
+
+struct Dual {
+  alpha: Vec<f64>,
+  rho: f64,
+  n_iter: usize
+}
+
+// Solves min 0.5 a'Qa + p'a subject to y'a = 0 and 0 <= a <= c, where Q[i][j] = y[i] y[j] K[i % n][j % n].
+fn smo(k: &Matrix<f64>, y: &[f64], p: &[f64], c: f64, tol: f64, max_iter: usize) -> Dual {
+  let (n, len) = (k.rows, y.len());
+  let q = |i: usize, j: usize| y[i] * y[j] * k.data[(i % n) * n + j % n];
+  let mut alpha = vec![0.0; len];
+  let mut grad = p.to_vec();
+  let mut n_iter = 0;
+  while n_iter < max_iter {
+    let up = |t: usize, alpha: &[f64]| (y[t] > 0.0 && alpha[t] < c) || (y[t] < 0.0 && alpha[t] > 0.0);
+    let low = |t: usize, alpha: &[f64]| (y[t] > 0.0 && alpha[t] > 0.0) || (y[t] < 0.0 && alpha[t] < c);
+    let (mut i, mut max) = (None, f64::NEG_INFINITY);
+    let (mut j, mut min) = (None, f64::INFINITY);
+    for t in 0..len {
+      let violation = -y[t] * grad[t];
+      if up(t, &alpha) && violation > max {
+        (i, max) = (Some(t), violation);
+      }
+      if low(t, &alpha) && violation < min {
+        (j, min) = (Some(t), violation);
+      }
+    }
+    let (Some(i), Some(j)) = (i, j) else { break };
+    if max - min < tol {
+      break;
+    }
+    n_iter += 1;
+
+    let (old_i, old_j) = (alpha[i], alpha[j]);
+    if y[i] != y[j] {
+      let quad = (q(i, i) + q(j, j) + 2.0 * q(i, j)).max(1e-12);
+      let delta = (-grad[i] - grad[j]) / quad;
+      let diff = alpha[i] - alpha[j];
+      alpha[i] += delta;
+      alpha[j] += delta;
+      if diff > 0.0 {
+        if alpha[j] < 0.0 {
+          (alpha[j], alpha[i]) = (0.0, diff);
+        }
+      } else if alpha[i] < 0.0 {
+        (alpha[i], alpha[j]) = (0.0, -diff);
+      }
+      if diff > 0.0 {
+        if alpha[i] > c {
+          (alpha[i], alpha[j]) = (c, c - diff);
+        }
+      } else if alpha[j] > c {
+        (alpha[j], alpha[i]) = (c, c + diff);
+      }
+    } else {
+      let quad = (q(i, i) + q(j, j) - 2.0 * q(i, j)).max(1e-12);
+      let delta = (grad[i] - grad[j]) / quad;
+      let sum = alpha[i] + alpha[j];
+      alpha[i] -= delta;
+      alpha[j] += delta;
+      if sum > c {
+        if alpha[i] > c {
+          (alpha[i], alpha[j]) = (c, sum - c);
+        }
+      } else if alpha[j] < 0.0 {
+        (alpha[j], alpha[i]) = (0.0, sum);
+      }
+      if sum > c {
+        if alpha[j] > c {
+          (alpha[j], alpha[i]) = (c, sum - c);
+        }
+      } else if alpha[i] < 0.0 {
+        (alpha[i], alpha[j]) = (0.0, sum);
+      }
+    }
+    let (delta_i, delta_j) = (alpha[i] - old_i, alpha[j] - old_j);
+    for (t, g) in grad.iter_mut().enumerate() {
+      *g += q(t, i) * delta_i + q(t, j) * delta_j;
+    }
+  }
+
+  let free: Vec<usize> = (0..len).filter(|&t| alpha[t] > 0.0 && alpha[t] < c).collect();
+  let rho = if free.is_empty() {
+    let (mut upper, mut lower) = (f64::INFINITY, f64::NEG_INFINITY);
+    for t in 0..len {
+      let value = y[t] * grad[t];
+      let at_upper = alpha[t] >= c;
+      if (y[t] > 0.0) == at_upper {
+        lower = lower.max(value);
+      } else {
+        upper = upper.min(value);
+      }
+    }
+    (upper + lower) / 2.0
+  } else {
+    free.iter().map(|&t| y[t] * grad[t]).sum::<f64>() / free.len() as f64
+  };
+  Dual { alpha, rho, n_iter }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Machines {
+  kernel: Kernel,
+  support: Vec<usize>,
+  support_vectors: Matrix<f64>,
+  dual_coef: Matrix<f64>,
+  intercepts: Vec<f64>
+}
+
+impl Machines {
+  fn new(x: &Matrix<f64>, kernel: Kernel, coefficients: Vec<Vec<f64>>, intercepts: Vec<f64>) -> Machines {
+    let support: Vec<usize> = (0..x.rows).filter(|&i| coefficients.iter().any(|coef| coef[i] != 0.0)).collect();
+    Machines {
+      kernel,
+      support_vectors: Matrix::from_fn(support.len(), x.cols, |s, j| x.data[support[s] * x.cols + j]),
+      dual_coef: Matrix::from_fn(coefficients.len(), support.len(), |m, s| coefficients[m][support[s]]),
+      support,
+      intercepts
+    }
+  }
+
+  fn decision_function<T: Float>(&self, name: &str, x: &Matrix<T>) -> Result<Matrix<f64>, String> {
+    check_features(name, self.support_vectors.cols, x)?;
+    let x = to_f64_matrix(x);
+    let n_support = self.support.len();
+    Ok(Matrix::from_fn(x.rows, self.intercepts.len(), |i, m| {
+      let point = row(&x, i);
+      self.intercepts[m]
+        + (0..n_support).map(|s| self.dual_coef.data[m * n_support + s] * self.kernel.apply(row(&self.support_vectors, s), point)).sum::<f64>()
+    }))
+  }
+
+  fn margins(&self) -> Vec<f64> {
+    let n_support = self.support.len();
+    (0..self.intercepts.len())
+      .map(|m| {
+        let coef = &self.dual_coef.data[m * n_support..(m + 1) * n_support];
+        let norm_sq: f64 = (0..n_support)
+          .flat_map(|a| (0..n_support).map(move |b| (a, b)))
+          .map(|(a, b)| coef[a] * coef[b] * self.kernel.apply(row(&self.support_vectors, a), row(&self.support_vectors, b)))
+          .sum();
+        if norm_sq > 0.0 { 2.0 / norm_sq.sqrt() } else { f64::INFINITY }
+      })
+      .collect()
+  }
+
+  fn coefficients(&self) -> Option<Matrix<f64>> {
+    if self.kernel != Kernel::Linear {
+      return None;
+    }
+    let (n_support, p) = (self.support.len(), self.support_vectors.cols);
+    Some(Matrix::from_fn(self.intercepts.len(), p, |m, j| {
+      (0..n_support).map(|s| self.dual_coef.data[m * n_support + s] * self.support_vectors.data[s * p + j]).sum()
+    }))
+  }
+}
+
+fn check_params(name: &str, c: f64, tol: f64) -> Result<(), String> {
+  if !(c > 0.0 && c.is_finite() && tol > 0.0) {
+    return Err(format!("{}: needs c > 0 and tol > 0, got {} and {}", name, c, tol));
+  }
+  Ok(())
+}
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SVC {
+  pub c: f64,
+  pub kernel: Kernel,
+  pub tol: f64,
+  pub max_iter: usize,
+  classes: Vec<usize>,
+  machines: Option<Machines>,
+  n_iter: usize
+}
+
+impl SVC {
+  pub fn new(kernel: Kernel) -> Self {
+    SVC { c: 1.0, kernel, tol: 1e-3, max_iter: 100_000, classes: Vec::new(), machines: None, n_iter: 0 }
+  }
+
+  pub fn classes(&self) -> &[usize] {
+    &self.classes
+  }
+
+  pub fn support(&self) -> &[usize] {
+    self.machines.as_ref().map_or(&[], |m| &m.support)
+  }
+
+  pub fn support_vectors(&self) -> Option<&Matrix<f64>> {
+    self.machines.as_ref().map(|m| &m.support_vectors)
+  }
+
+  pub fn dual_coef(&self) -> Option<&Matrix<f64>> {
+    self.machines.as_ref().map(|m| &m.dual_coef)
+  }
+
+  pub fn intercepts(&self) -> &[f64] {
+    self.machines.as_ref().map_or(&[], |m| &m.intercepts)
+  }
+
+  pub fn coefficients(&self) -> Option<Matrix<f64>> {
+    self.machines.as_ref().and_then(Machines::coefficients)
+  }
+
+  pub fn margins(&self) -> Vec<f64> {
+    self.machines.as_ref().map_or_else(Vec::new, Machines::margins)
+  }
+
+  pub fn n_iter(&self) -> usize {
+    self.n_iter
+  }
+
+  pub fn decision_function<T: Float>(&self, x: &Matrix<T>) -> Result<Matrix<T>, String> {
+    let machines = self.machines.as_ref().ok_or_else(|| "SVC: predict called before fit".to_string())?;
+    let scores = machines.decision_function("SVC", x)?;
+    Ok(Matrix::from_fn(scores.rows, scores.cols, |i, m| T::from_f64(scores.data[i * scores.cols + m])))
+  }
+}
+
+impl Classifier for SVC {
+  fn fit<T: Float>(&mut self, x: &Matrix<T>, y: &[usize]) -> Result<(), String> {
+    check_samples("SVC", x, y)?;
+    check_params("SVC", self.c, self.tol)?;
+    let (classes, encoded) = encode_classes("SVC", y)?;
+    let x = to_f64_matrix(x);
+    let kernel = self.kernel.resolve(&x);
+    let k = gram_matrix(&x, kernel);
+    let positives: Vec<usize> = if classes.len() == 2 { vec![1] } else { (0..classes.len()).collect() };
+    let (mut coefficients, mut intercepts) = (Vec::new(), Vec::new());
+    self.n_iter = 0;
+    for positive in positives {
+      let signs: Vec<f64> = encoded.iter().map(|&c| if c == positive { 1.0 } else { -1.0 }).collect();
+      let dual = smo(&k, &signs, &vec![-1.0; x.rows], self.c, self.tol, self.max_iter);
+      coefficients.push(dual.alpha.iter().zip(&signs).map(|(a, s)| a * s).collect());
+      intercepts.push(-dual.rho);
+      self.n_iter = self.n_iter.max(dual.n_iter);
+    }
+    self.machines = Some(Machines::new(&x, kernel, coefficients, intercepts));
+    self.classes = classes;
+    Ok(())
+  }
+
+  fn predict<T: Float>(&self, x: &Matrix<T>) -> Result<Vec<usize>, String> {
+    let machines = self.machines.as_ref().ok_or_else(|| "SVC: predict called before fit".to_string())?;
+    let scores = machines.decision_function("SVC", x)?;
+    Ok(
+      (0..scores.rows)
+        .map(|i| {
+          let row = &scores.data[i * scores.cols..(i + 1) * scores.cols];
+          match row {
+            [score] => self.classes[(*score > 0.0) as usize],
+            _ => self.classes[argmax(row)]
+          }
+        })
+        .collect()
+    )
+  }
+}
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SVR {
+  pub c: f64,
+  pub epsilon: f64,
+  pub kernel: Kernel,
+  pub tol: f64,
+  pub max_iter: usize,
+  machines: Option<Machines>,
+  n_iter: usize
+}
+
+impl SVR {
+  pub fn new(kernel: Kernel) -> Self {
+    SVR { c: 1.0, epsilon: 0.1, kernel, tol: 1e-3, max_iter: 100_000, machines: None, n_iter: 0 }
+  }
+
+  pub fn support(&self) -> &[usize] {
+    self.machines.as_ref().map_or(&[], |m| &m.support)
+  }
+
+  pub fn support_vectors(&self) -> Option<&Matrix<f64>> {
+    self.machines.as_ref().map(|m| &m.support_vectors)
+  }
+
+  pub fn dual_coef(&self) -> Option<&[f64]> {
+    self.machines.as_ref().map(|m| m.dual_coef.data.as_slice())
+  }
+
+  pub fn intercept(&self) -> f64 {
+    self.machines.as_ref().map_or(0.0, |m| m.intercepts[0])
+  }
+
+  pub fn coefficients(&self) -> Option<Vec<f64>> {
+    self.machines.as_ref().and_then(Machines::coefficients).map(|w| w.data)
+  }
+
+  pub fn n_iter(&self) -> usize {
+    self.n_iter
+  }
+}
+
+impl Regressor for SVR {
+  fn fit<T: Float>(&mut self, x: &Matrix<T>, y: &Vector<T>) -> Result<(), String> {
+    check_samples("SVR", x, &y.data)?;
+    check_params("SVR", self.c, self.tol)?;
+    if !(self.epsilon >= 0.0 && self.epsilon.is_finite()) {
+      return Err(format!("SVR: epsilon must be a non-negative number, got {}", self.epsilon));
+    }
+    let x = to_f64_matrix(x);
+    let targets = to_f64(&y.data);
+    let kernel = self.kernel.resolve(&x);
+    let k = gram_matrix(&x, kernel);
+    let n = x.rows;
+    let signs: Vec<f64> = (0..2 * n).map(|t| if t < n { 1.0 } else { -1.0 }).collect();
+    let linear: Vec<f64> = (0..2 * n).map(|t| if t < n { self.epsilon - targets[t] } else { self.epsilon + targets[t - n] }).collect();
+    let dual = smo(&k, &signs, &linear, self.c, self.tol, self.max_iter);
+    let coefficients = (0..n).map(|i| dual.alpha[i] - dual.alpha[i + n]).collect();
+    self.machines = Some(Machines::new(&x, kernel, vec![coefficients], vec![-dual.rho]));
+    self.n_iter = dual.n_iter;
+    Ok(())
+  }
+
+  fn predict<T: Float>(&self, x: &Matrix<T>) -> Result<Vector<T>, String> {
+    let machines = self.machines.as_ref().ok_or_else(|| "SVR: predict called before fit".to_string())?;
+    let scores = machines.decision_function("SVR", x)?;
+    Ok(Vector::from(scores.data.iter().map(|&v| T::from_f64(v)).collect::<Vec<_>>()))
+  }
+}
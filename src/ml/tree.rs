@@ -0,0 +1,374 @@
+use super::{argmax, check_features, check_samples, encode_classes, to_f64, to_f64_matrix, Classifier, Regressor};
+use crate::math::matrix::Matrix;
+use crate::math::scalar::Float;
+use crate::math::vector::Vector;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Criterion {
+  Gini,
+  Entropy,
+  Mse
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Split {
+  pub feature: usize,
+  pub threshold: f64,
+  pub left: usize,
+  pub right: usize
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TreeNode {
+  pub split: Option<Split>,
+  pub value: Vec<f64>,
+  pub samples: usize,
+  pub impurity: f64,
+  pub depth: usize
+}
+
+impl TreeNode {
+  pub fn is_leaf(&self) -> bool {
+    self.split.is_none()
+  }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Tree {
+  pub nodes: Vec<TreeNode>,
+  pub n_features: usize
+}
+
+impl Tree {
+  pub fn root(&self) -> &TreeNode {
+    &self.nodes[0]
+  }
+
+  pub fn depth(&self) -> usize {
+    self.nodes.iter().map(|node| node.depth).max().unwrap_or(0)
+  }
+
+  pub fn n_leaves(&self) -> usize {
+    self.nodes.iter().filter(|node| node.is_leaf()).count()
+  }
+
+  pub fn decision_path(&self, sample: &[f64]) -> Vec<usize> {
+    let mut path = vec![0];
+    while let Some(split) = self.nodes[path[path.len() - 1]].split {
+      path.push(if sample[split.feature] <= split.threshold { split.left } else { split.right });
+    }
+    path
+  }
+
+  pub fn apply(&self, sample: &[f64]) -> usize {
+    self.decision_path(sample).pop().unwrap_or(0)
+  }
+
+  pub fn feature_importances(&self) -> Vec<f64> {
+    let mut importances = vec![0.0; self.n_features];
+    for node in &self.nodes {
+      if let Some(split) = node.split {
+        let (left, right) = (&self.nodes[split.left], &self.nodes[split.right]);
+        importances[split.feature] +=
+          node.samples as f64 * node.impurity - left.samples as f64 * left.impurity - right.samples as f64 * right.impurity;
+      }
+    }
+    let total: f64 = importances.iter().sum();
+    if total > 0.0 {
+      importances.iter_mut().for_each(|v| *v /= total);
+    }
+    importances
+  }
+
+  pub fn export_text<S, F>(&self, feature_names: &[S], leaf: F) -> String
+  where
+    S: AsRef<str>,
+    F: Fn(&TreeNode) -> String
+  {
+    let name = |feature: usize| feature_names.get(feature).map_or_else(|| format!("feature_{}", feature), |s| s.as_ref().to_string());
+    let mut out = String::new();
+    let mut stack: Vec<(usize, usize, Option<String>)> = vec![(0, 0, None)];
+    while let Some((index, depth, condition)) = stack.pop() {
+      let indent = "|   ".repeat(depth.saturating_sub(1));
+      if let Some(condition) = condition {
+        out.push_str(&format!("{}|--- {}\n", indent, condition));
+      }
+      let node = &self.nodes[index];
+      match node.split {
+        Some(split) => {
+          stack.push((split.right, depth + 1, Some(format!("{} >  {:.4}", name(split.feature), split.threshold))));
+          stack.push((split.left, depth + 1, Some(format!("{} <= {:.4}", name(split.feature), split.threshold))));
+        }
+        None => out.push_str(&format!("{}|--- {}\n", "|   ".repeat(depth), leaf(node)))
+      }
+    }
+    out
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Limits {
+  max_depth: Option<usize>,
+  min_samples_split: usize,
+  min_samples_leaf: usize,
+  min_impurity_decrease: f64
+}
+
+#[derive(Clone)]
+struct Stats {
+  counts: Vec<f64>,
+  sum: f64,
+  sum_sq: f64,
+  n: f64
+}
+
+impl Stats {
+  fn new(n_classes: usize) -> Self {
+    Stats { counts: vec![0.0; n_classes], sum: 0.0, sum_sq: 0.0, n: 0.0 }
+  }
+
+  fn add(&mut self, target: f64, sign: f64) {
+    if !self.counts.is_empty() {
+      self.counts[target as usize] += sign;
+    }
+    self.sum += sign * target;
+    self.sum_sq += sign * target * target;
+    self.n += sign;
+  }
+
+  fn impurity(&self, criterion: Criterion) -> f64 {
+    if self.n <= 0.0 {
+      return 0.0;
+    }
+    match criterion {
+      Criterion::Gini => 1.0 - self.counts.iter().map(|c| (c / self.n).powi(2)).sum::<f64>(),
+      Criterion::Entropy => -self.counts.iter().filter(|&&c| c > 0.0).map(|c| c / self.n * (c / self.n).log2()).sum::<f64>(),
+      Criterion::Mse => (self.sum_sq / self.n - (self.sum / self.n).powi(2)).max(0.0)
+    }
+  }
+
+  fn value(&self) -> Vec<f64> {
+    if self.counts.is_empty() {
+      vec![self.sum / self.n]
+    } else {
+      self.counts.iter().map(|c| c / self.n).collect()
+    }
+  }
+}
+
+struct Builder<'a> {
+  x: &'a Matrix<f64>,
+  targets: &'a [f64],
+  n_classes: usize,
+  criterion: Criterion,
+  limits: Limits,
+  nodes: Vec<TreeNode>
+}
+
+impl Builder<'_> {
+  fn stats(&self, indices: &[usize]) -> Stats {
+    let mut stats = Stats::new(self.n_classes);
+    indices.iter().for_each(|&i| stats.add(self.targets[i], 1.0));
+    stats
+  }
+
+  fn best_split(&self, indices: &mut [usize], parent: &Stats) -> Option<(usize, f64, f64)> {
+    let p = self.x.cols;
+    let min_leaf = self.limits.min_samples_leaf.max(1);
+    let mut best: Option<(usize, f64, f64)> = None;
+    for feature in 0..p {
+      indices.sort_by(|&a, &b| self.x.data[a * p + feature].total_cmp(&self.x.data[b * p + feature]));
+      let mut left = Stats::new(self.n_classes);
+      let mut right = parent.clone();
+      for k in 0..indices.len() - 1 {
+        let target = self.targets[indices[k]];
+        left.add(target, 1.0);
+        right.add(target, -1.0);
+        let (here, next) = (self.x.data[indices[k] * p + feature], self.x.data[indices[k + 1] * p + feature]);
+        if here == next || k + 1 < min_leaf || indices.len() - k - 1 < min_leaf {
+          continue;
+        }
+        let child = left.n * left.impurity(self.criterion) + right.n * right.impurity(self.criterion);
+        if best.is_none_or(|(_, _, score)| child < score - 1e-12) {
+          best = Some((feature, (here + next) / 2.0, child));
+        }
+      }
+    }
+    best
+  }
+
+  fn grow(&mut self, mut indices: Vec<usize>, depth: usize) -> usize {
+    let stats = self.stats(&indices);
+    let impurity = stats.impurity(self.criterion);
+    let id = self.nodes.len();
+    self.nodes.push(TreeNode { split: None, value: stats.value(), samples: indices.len(), impurity, depth });
+    let can_split = self.limits.max_depth.is_none_or(|max| depth < max) && indices.len() >= self.limits.min_samples_split.max(2) && impurity > 0.0;
+    if !can_split {
+      return id;
+    }
+    let Some((feature, threshold, child)) = self.best_split(&mut indices, &stats) else {
+      return id;
+    };
+    let decrease = (stats.n * impurity - child) / self.x.rows as f64;
+    if decrease < self.limits.min_impurity_decrease {
+      return id;
+    }
+    let (left, right): (Vec<usize>, Vec<usize>) = indices.iter().partition(|&&i| self.x.data[i * self.x.cols + feature] <= threshold);
+    let left = self.grow(left, depth + 1);
+    let right = self.grow(right, depth + 1);
+    self.nodes[id].split = Some(Split { feature, threshold, left, right });
+    id
+  }
+}
+
+fn build_tree(x: &Matrix<f64>, targets: &[f64], n_classes: usize, criterion: Criterion, limits: Limits) -> Tree {
+  let mut builder = Builder { x, targets, n_classes, criterion, limits, nodes: Vec::new() };
+  builder.grow((0..x.rows).collect(), 0);
+  Tree { nodes: builder.nodes, n_features: x.cols }
+}
+
+fn leaf_values<'a, T: Float>(name: &str, tree: Option<&'a Tree>, x: &Matrix<T>) -> Result<Vec<&'a [f64]>, String> {
+  let tree = tree.ok_or_else(|| format!("{}: predict called before fit", name))?;
+  check_features(name, tree.n_features, x)?;
+  let x = to_f64_matrix(x);
+  Ok((0..x.rows).map(|i| tree.nodes[tree.apply(&x.data[i * x.cols..(i + 1) * x.cols])].value.as_slice()).collect())
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DecisionTreeClassifier {
+  pub criterion: Criterion,
+  pub max_depth: Option<usize>,
+  pub min_samples_split: usize,
+  pub min_samples_leaf: usize,
+  pub min_impurity_decrease: f64,
+  classes: Vec<usize>,
+  tree: Option<Tree>
+}
+
+impl DecisionTreeClassifier {
+  pub fn new() -> Self {
+    DecisionTreeClassifier {
+      criterion: Criterion::Gini,
+      max_depth: None,
+      min_samples_split: 2,
+      min_samples_leaf: 1,
+      min_impurity_decrease: 0.0,
+      classes: Vec::new(),
+      tree: None
+    }
+  }
+
+  pub fn classes(&self) -> &[usize] {
+    &self.classes
+  }
+
+  pub fn tree(&self) -> Option<&Tree> {
+    self.tree.as_ref()
+  }
+
+  pub fn feature_importances(&self) -> Option<Vec<f64>> {
+    self.tree.as_ref().map(Tree::feature_importances)
+  }
+
+  pub fn predict_proba<T: Float>(&self, x: &Matrix<T>) -> Result<Matrix<T>, String> {
+    let leaves = leaf_values("DecisionTreeClassifier", self.tree.as_ref(), x)?;
+    Ok(Matrix::from_fn(x.rows, self.classes.len(), |i, c| T::from_f64(leaves[i][c])))
+  }
+
+  pub fn export_text<S: AsRef<str>>(&self, feature_names: &[S]) -> Result<String, String> {
+    let tree = self.tree.as_ref().ok_or_else(|| "DecisionTreeClassifier: export called before fit".to_string())?;
+    Ok(tree.export_text(feature_names, |node| {
+      format!("class: {}", self.classes[argmax(&node.value)])
+    }))
+  }
+}
+
+impl Default for DecisionTreeClassifier {
+  fn default() -> Self {
+    DecisionTreeClassifier::new()
+  }
+}
+
+impl Classifier for DecisionTreeClassifier {
+  fn fit<T: Float>(&mut self, x: &Matrix<T>, y: &[usize]) -> Result<(), String> {
+    check_samples("DecisionTreeClassifier", x, y)?;
+    if self.criterion == Criterion::Mse {
+      return Err("DecisionTreeClassifier: criterion must be Gini or Entropy".to_string());
+    }
+    let (classes, encoded) = encode_classes("DecisionTreeClassifier", y)?;
+    let targets: Vec<f64> = encoded.iter().map(|&c| c as f64).collect();
+    let limits = Limits {
+      max_depth: self.max_depth,
+      min_samples_split: self.min_samples_split,
+      min_samples_leaf: self.min_samples_leaf,
+      min_impurity_decrease: self.min_impurity_decrease
+    };
+    self.tree = Some(build_tree(&to_f64_matrix(x), &targets, classes.len(), self.criterion, limits));
+    self.classes = classes;
+    Ok(())
+  }
+
+  fn predict<T: Float>(&self, x: &Matrix<T>) -> Result<Vec<usize>, String> {
+    let leaves = leaf_values("DecisionTreeClassifier", self.tree.as_ref(), x)?;
+    Ok(leaves.iter().map(|value| self.classes[argmax(value)]).collect())
+  }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DecisionTreeRegressor {
+  pub max_depth: Option<usize>,
+  pub min_samples_split: usize,
+  pub min_samples_leaf: usize,
+  pub min_impurity_decrease: f64,
+  tree: Option<Tree>
+}
+
+impl DecisionTreeRegressor {
+  pub fn new() -> Self {
+    DecisionTreeRegressor { max_depth: None, min_samples_split: 2, min_samples_leaf: 1, min_impurity_decrease: 0.0, tree: None }
+  }
+
+  pub fn tree(&self) -> Option<&Tree> {
+    self.tree.as_ref()
+  }
+
+  pub fn feature_importances(&self) -> Option<Vec<f64>> {
+    self.tree.as_ref().map(Tree::feature_importances)
+  }
+
+  pub fn export_text<S: AsRef<str>>(&self, feature_names: &[S]) -> Result<String, String> {
+    let tree = self.tree.as_ref().ok_or_else(|| "DecisionTreeRegressor: export called before fit".to_string())?;
+    Ok(tree.export_text(feature_names, |node| format!("value: {:.4}", node.value[0])))
+  }
+}
+
+impl Default for DecisionTreeRegressor {
+  fn default() -> Self {
+    DecisionTreeRegressor::new()
+  }
+}
+
+impl Regressor for DecisionTreeRegressor {
+  fn fit<T: Float>(&mut self, x: &Matrix<T>, y: &Vector<T>) -> Result<(), String> {
+    check_samples("DecisionTreeRegressor", x, &y.data)?;
+    let limits = Limits {
+      max_depth: self.max_depth,
+      min_samples_split: self.min_samples_split,
+      min_samples_leaf: self.min_samples_leaf,
+      min_impurity_decrease: self.min_impurity_decrease
+    };
+    self.tree = Some(build_tree(&to_f64_matrix(x), &to_f64(&y.data), 0, Criterion::Mse, limits));
+    Ok(())
+  }
+
+  fn predict<T: Float>(&self, x: &Matrix<T>) -> Result<Vector<T>, String> {
+    let leaves = leaf_values("DecisionTreeRegressor", self.tree.as_ref(), x)?;
+    Ok(Vector::from(leaves.iter().map(|value| T::from_f64(value[0])).collect::<Vec<_>>()))
+  }
+}
@@ -0,0 +1,222 @@
+use std::collections::BTreeMap;
+
+use super::{seeded_rng, squared_distance, to_f64_matrix};
+use crate::math::matrix::Matrix;
+use crate::math::scalar::Float;
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TSNE {
+  pub n_components: usize,
+  pub perplexity: f64,
+  pub learning_rate: Option<f64>,
+  pub max_iter: usize,
+  pub early_exaggeration: f64,
+  pub exaggeration_iter: usize,
+  pub theta: f64,
+  pub seed: Option<u64>,
+  embedding: Option<Matrix<f64>>,
+  kl_divergence: f64
+}
+
+type Affinities = Vec<Vec<(usize, f64)>>;
+
+struct Cell {
+  half_width: f64,
+  center_of_mass: Vec<f64>,
+  count: usize,
+  points: Vec<usize>,
+  children: Vec<Cell>
+}
+
+impl Cell {
+  fn build(y: &[f64], dims: usize, indices: Vec<usize>, center: Vec<f64>, half_width: f64) -> Cell {
+    let mut center_of_mass = vec![0.0; dims];
+    for &i in &indices {
+      center_of_mass.iter_mut().zip(&y[i * dims..(i + 1) * dims]).for_each(|(m, v)| *m += v);
+    }
+    center_of_mass.iter_mut().for_each(|m| *m /= indices.len().max(1) as f64);
+    let count = indices.len();
+    if count <= 1 || half_width < 1e-10 {
+      return Cell { half_width, center_of_mass, count, points: indices, children: Vec::new() };
+    }
+    let mut buckets: Vec<Vec<usize>> = vec![Vec::new(); 1 << dims];
+    for &i in &indices {
+      let slot = (0..dims).fold(0, |slot, d| slot | (((y[i * dims + d] > center[d]) as usize) << d));
+      buckets[slot].push(i);
+    }
+    let children = buckets
+      .into_iter()
+      .enumerate()
+      .filter(|(_, bucket)| !bucket.is_empty())
+      .map(|(slot, bucket)| {
+        let child_center = (0..dims).map(|d| center[d] + if slot >> d & 1 == 1 { 0.5 } else { -0.5 } * half_width).collect();
+        Cell::build(y, dims, bucket, child_center, half_width / 2.0)
+      })
+      .collect();
+    Cell { half_width, center_of_mass, count, points: Vec::new(), children }
+  }
+
+  fn repulsion(&self, y: &[f64], i: usize, theta: f64, force: &mut [f64]) -> f64 {
+    let dims = force.len();
+    let point = &y[i * dims..(i + 1) * dims];
+    if self.children.is_empty() {
+      let mut sum_q = 0.0;
+      for &j in self.points.iter().filter(|&&j| j != i) {
+        let other = &y[j * dims..(j + 1) * dims];
+        let q = 1.0 / (1.0 + squared_distance(point, other));
+        sum_q += q;
+        force.iter_mut().enumerate().for_each(|(d, f)| *f += q * q * (point[d] - other[d]));
+      }
+      return sum_q;
+    }
+    let distance_sq = squared_distance(point, &self.center_of_mass);
+    if 4.0 * self.half_width * self.half_width < theta * theta * distance_sq {
+      let q = 1.0 / (1.0 + distance_sq);
+      let mass = self.count as f64;
+      force.iter_mut().enumerate().for_each(|(d, f)| *f += mass * q * q * (point[d] - self.center_of_mass[d]));
+      return mass * q;
+    }
+    self.children.iter().map(|child| child.repulsion(y, i, theta, force)).sum()
+  }
+}
+
+fn conditional_row(distances: &[f64], perplexity: f64) -> Vec<f64> {
+  let target = perplexity.ln();
+  let (mut beta, mut lo, mut hi) = (1.0, 0.0, f64::INFINITY);
+  let mut row = vec![0.0; distances.len()];
+  let min = distances.iter().cloned().fold(f64::INFINITY, f64::min);
+  for _ in 0..200 {
+    row.iter_mut().zip(distances).for_each(|(p, d)| *p = (-(d - min) * beta).exp());
+    let total: f64 = row.iter().sum();
+    let entropy = total.ln() + beta * row.iter().zip(distances).map(|(p, d)| p * (d - min)).sum::<f64>() / total;
+    row.iter_mut().for_each(|p| *p /= total);
+    if (entropy - target).abs() < 1e-5 {
+      break;
+    }
+    if entropy > target {
+      lo = beta;
+      beta = if hi.is_finite() { (beta + hi) / 2.0 } else { beta * 2.0 };
+    } else {
+      hi = beta;
+      beta = (beta + lo) / 2.0;
+    }
+  }
+  row
+}
+
+fn affinities(x: &Matrix<f64>, perplexity: f64) -> Affinities {
+  let n = x.rows;
+  let k = ((3.0 * perplexity) as usize).clamp(1, n - 1);
+  let row = |i: usize| &x.data[i * x.cols..(i + 1) * x.cols];
+  let mut joint: Vec<BTreeMap<usize, f64>> = vec![BTreeMap::new(); n];
+  for i in 0..n {
+    let mut neighbors: Vec<(usize, f64)> = (0..n).filter(|&j| j != i).map(|j| (j, squared_distance(row(i), row(j)))).collect();
+    neighbors.select_nth_unstable_by(k - 1, |a, b| a.1.total_cmp(&b.1));
+    neighbors.truncate(k);
+    let distances: Vec<f64> = neighbors.iter().map(|(_, d)| *d).collect();
+    for ((j, _), p) in neighbors.iter().zip(conditional_row(&distances, perplexity)) {
+      *joint[i].entry(*j).or_default() += p / (2.0 * n as f64);
+      *joint[*j].entry(i).or_default() += p / (2.0 * n as f64);
+    }
+  }
+  joint.into_iter().map(|row| row.into_iter().collect()).collect()
+}
+
+impl TSNE {
+  pub fn new(n_components: usize) -> Self {
+    TSNE {
+      n_components,
+      perplexity: 30.0,
+      learning_rate: None,
+      max_iter: 1000,
+      early_exaggeration: 12.0,
+      exaggeration_iter: 250,
+      theta: 0.5,
+      seed: None,
+      embedding: None,
+      kl_divergence: 0.0
+    }
+  }
+
+  pub fn embedding(&self) -> Option<&Matrix<f64>> {
+    self.embedding.as_ref()
+  }
+
+  pub fn kl_divergence(&self) -> f64 {
+    self.kl_divergence
+  }
+
+  fn gradient(&self, y: &[f64], p: &Affinities, exaggeration: f64, grad: &mut [f64]) -> f64 {
+    let dims = self.n_components;
+    let n = p.len();
+    let (lo, hi) = y.chunks(dims).fold((vec![f64::INFINITY; dims], vec![f64::NEG_INFINITY; dims]), |(mut lo, mut hi), point| {
+      for d in 0..dims {
+        lo[d] = lo[d].min(point[d]);
+        hi[d] = hi[d].max(point[d]);
+      }
+      (lo, hi)
+    });
+    let center: Vec<f64> = lo.iter().zip(&hi).map(|(a, b)| (a + b) / 2.0).collect();
+    let half_width = lo.iter().zip(&hi).map(|(a, b)| (b - a) / 2.0).fold(0.0, f64::max) + 1e-5;
+    let tree = Cell::build(y, dims, (0..n).collect(), center, half_width);
+
+    let mut repulsive = vec![0.0; n * dims];
+    let sum_q: f64 = (0..n).map(|i| tree.repulsion(y, i, self.theta, &mut repulsive[i * dims..(i + 1) * dims])).sum();
+    let mut kl = 0.0;
+    for i in 0..n {
+      let point = &y[i * dims..(i + 1) * dims];
+      let g = &mut grad[i * dims..(i + 1) * dims];
+      g.iter_mut().zip(&repulsive[i * dims..(i + 1) * dims]).for_each(|(g, r)| *g = -4.0 * r / sum_q);
+      for &(j, pij) in &p[i] {
+        let other = &y[j * dims..(j + 1) * dims];
+        let q = 1.0 / (1.0 + squared_distance(point, other));
+        g.iter_mut().enumerate().for_each(|(d, g)| *g += 4.0 * exaggeration * pij * q * (point[d] - other[d]));
+        kl += pij * (pij / (q / sum_q).max(f64::MIN_POSITIVE)).ln();
+      }
+    }
+    kl
+  }
+
+  pub fn fit_transform<T: Float>(&mut self, x: &Matrix<T>) -> Result<Matrix<T>, String> {
+    if !(1..=3).contains(&self.n_components) {
+      return Err(format!("TSNE: n_components must be 1, 2 or 3, got {}", self.n_components));
+    }
+    if x.rows < 2 {
+      return Err(format!("TSNE: needs at least 2 samples, got {}", x.rows));
+    }
+    if !(self.perplexity > 0.0 && self.perplexity < x.rows as f64) {
+      return Err(format!("TSNE: perplexity must be in (0, {}), got {}", x.rows, self.perplexity));
+    }
+    let learning_rate = self.learning_rate.unwrap_or_else(|| (x.rows as f64 / self.early_exaggeration / 4.0).max(50.0));
+    if !(learning_rate > 0.0 && self.theta >= 0.0) {
+      return Err(format!("TSNE: needs learning_rate > 0 and theta >= 0, got {} and {}", learning_rate, self.theta));
+    }
+    let p = affinities(&to_f64_matrix(x), self.perplexity);
+    let (n, dims) = (x.rows, self.n_components);
+    let mut rng = seeded_rng(self.seed);
+    let mut y: Vec<f64> = (0..n * dims).map(|_| rng.normal(0.0, 1e-4)).collect();
+    let mut update = vec![0.0; n * dims];
+    let mut gains = vec![1.0; n * dims];
+    let mut grad = vec![0.0; n * dims];
+    for iter in 0..self.max_iter {
+      let (exaggeration, momentum) = if iter < self.exaggeration_iter { (self.early_exaggeration, 0.5) } else { (1.0, 0.8) };
+      self.kl_divergence = self.gradient(&y, &p, exaggeration, &mut grad);
+      for ((value, step), (gain, g)) in y.iter_mut().zip(update.iter_mut()).zip(gains.iter_mut().zip(&grad)) {
+        *gain = if (*g > 0.0) != (*step > 0.0) { *gain + 0.2 } else { (*gain * 0.8).max(0.01) };
+        *step = momentum * *step - learning_rate * *gain * g;
+        *value += *step;
+      }
+      for d in 0..dims {
+        let mean = (0..n).map(|i| y[i * dims + d]).sum::<f64>() / n as f64;
+        (0..n).for_each(|i| y[i * dims + d] -= mean);
+      }
+    }
+    self.kl_divergence = self.gradient(&y, &p, 1.0, &mut grad);
+    let embedding = Matrix::from_vec(n, dims, y)?;
+    let out = Matrix::from_fn(n, dims, |i, d| T::from_f64(embedding.data[i * dims + d]));
+    self.embedding = Some(embedding);
+    Ok(out)
+  }
+}
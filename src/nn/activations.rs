@@ -0,0 +1,174 @@
+use super::Module;
+use crate::autograd::Var;
+use crate::math::scalar::Float;
+use crate::onnx::{float_attr, int_attr, GraphBuilder, Value};
+
+fn masked<T: Float>(x: &Var<T>, op: &'static str, negative_slope: T) -> Var<T> {
+  let mask = x.value().map(|&v| if v > T::zero() { T::one() } else { negative_slope });
+  let value = x.value().zip_map(&mask, |&v, &m| v * m).expect("mask has the input shape");
+  Var::from_op(value, op, vec![x.clone()], move |g| Ok(vec![(g * &Var::constant(mask.clone()))?]))
+}
+
+pub fn relu<T: Float>(x: &Var<T>) -> Var<T> {
+  masked(x, "relu", T::zero())
+}
+
+pub fn leaky_relu<T: Float>(x: &Var<T>, negative_slope: T) -> Var<T> {
+  masked(x, "leaky_relu", negative_slope)
+}
+
+pub fn sigmoid<T: Float>(x: &Var<T>) -> Var<T> {
+  let value = x.value().map(|&v| {
+    if v >= T::zero() {
+      T::one() / (T::one() + (-v).exp())
+    } else {
+      v.exp() / (T::one() + v.exp())
+    }
+  });
+  let input = x.clone();
+  Var::from_op(value, "sigmoid", vec![x.clone()], move |g| {
+    let s = sigmoid(&input);
+    let slope = (&s * &(-&s).add_scalar(T::one()))?;
+    Ok(vec![(g * &slope)?])
+  })
+}
+
+pub fn tanh<T: Float>(x: &Var<T>) -> Var<T> {
+  x.tanh()
+}
+
+pub fn gelu<T: Float>(x: &Var<T>) -> Result<Var<T>, String> {
+  let c = T::from_f64((2.0 / std::f64::consts::PI).sqrt());
+  let inner = (x + &x.powi(3).mul_scalar(T::from_f64(0.044715)))?.mul_scalar(c);
+  Ok((x * &inner.tanh().add_scalar(T::one()))?.mul_scalar(T::from_f64(0.5)))
+}
+
+pub fn silu<T: Float>(x: &Var<T>) -> Result<Var<T>, String> {
+  x * &sigmoid(x)
+}
+
+pub fn softmax<T: Float>(x: &Var<T>, axis: usize) -> Result<Var<T>, String> {
+  x.softmax(axis)
+}
+
+pub(crate) fn export_gelu(graph: &mut GraphBuilder, input: &str) -> String {
+  let (three, coeff) = (graph.scalar("three", 3.0), graph.scalar("coeff", 0.044715));
+  let (scale, one, half) = (graph.scalar("scale", (2.0 / std::f64::consts::PI).sqrt()), graph.scalar("one", 1.0), graph.scalar("half", 0.5));
+  let cube = graph.node("Pow", &[input, &three], Vec::new());
+  let cube = graph.node("Mul", &[&cube, &coeff], Vec::new());
+  let inner = graph.node("Add", &[input, &cube], Vec::new());
+  let inner = graph.node("Mul", &[&inner, &scale], Vec::new());
+  let gate = graph.node("Tanh", &[&inner], Vec::new());
+  let gate = graph.node("Add", &[&gate, &one], Vec::new());
+  let output = graph.node("Mul", &[input, &gate], Vec::new());
+  graph.node("Mul", &[&output, &half], Vec::new())
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ReLU;
+
+#[derive(Clone, Copy, Debug)]
+pub struct LeakyReLU {
+  pub negative_slope: f64
+}
+
+impl Default for LeakyReLU {
+  fn default() -> Self {
+    LeakyReLU { negative_slope: 0.01 }
+  }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Sigmoid;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Tanh;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GELU;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SiLU;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Softmax {
+  pub axis: usize
+}
+
+impl<T: Float> Module<T> for ReLU {
+  fn forward(&self, input: &Var<T>) -> Result<Var<T>, String> {
+    Ok(relu(input))
+  }
+
+  fn export_onnx(&self, graph: &mut GraphBuilder, input: &Value<T>) -> Result<Value<T>, String> {
+    let output = graph.node("Relu", &[&input.name], Vec::new());
+    graph.output(self, output, input)
+  }
+}
+
+impl<T: Float> Module<T> for LeakyReLU {
+  fn forward(&self, input: &Var<T>) -> Result<Var<T>, String> {
+    Ok(leaky_relu(input, T::from_f64(self.negative_slope)))
+  }
+
+  fn export_onnx(&self, graph: &mut GraphBuilder, input: &Value<T>) -> Result<Value<T>, String> {
+    let output = graph.node("LeakyRelu", &[&input.name], vec![float_attr("alpha", self.negative_slope)]);
+    graph.output(self, output, input)
+  }
+}
+
+impl<T: Float> Module<T> for Sigmoid {
+  fn forward(&self, input: &Var<T>) -> Result<Var<T>, String> {
+    Ok(sigmoid(input))
+  }
+
+  fn export_onnx(&self, graph: &mut GraphBuilder, input: &Value<T>) -> Result<Value<T>, String> {
+    let output = graph.node("Sigmoid", &[&input.name], Vec::new());
+    graph.output(self, output, input)
+  }
+}
+
+impl<T: Float> Module<T> for Tanh {
+  fn forward(&self, input: &Var<T>) -> Result<Var<T>, String> {
+    Ok(tanh(input))
+  }
+
+  fn export_onnx(&self, graph: &mut GraphBuilder, input: &Value<T>) -> Result<Value<T>, String> {
+    let output = graph.node("Tanh", &[&input.name], Vec::new());
+    graph.output(self, output, input)
+  }
+}
+
+impl<T: Float> Module<T> for GELU {
+  fn forward(&self, input: &Var<T>) -> Result<Var<T>, String> {
+    gelu(input)
+  }
+
+  fn export_onnx(&self, graph: &mut GraphBuilder, input: &Value<T>) -> Result<Value<T>, String> {
+    let output = export_gelu(graph, &input.name);
+    graph.output(self, output, input)
+  }
+}
+
+impl<T: Float> Module<T> for SiLU {
+  fn forward(&self, input: &Var<T>) -> Result<Var<T>, String> {
+    silu(input)
+  }
+
+  fn export_onnx(&self, graph: &mut GraphBuilder, input: &Value<T>) -> Result<Value<T>, String> {
+    let gate = graph.node("Sigmoid", &[&input.name], Vec::new());
+    let output = graph.node("Mul", &[&input.name, &gate], Vec::new());
+    graph.output(self, output, input)
+  }
+}
+
+impl<T: Float> Module<T> for Softmax {
+  fn forward(&self, input: &Var<T>) -> Result<Var<T>, String> {
+    softmax(input, self.axis)
+  }
+
+  fn export_onnx(&self, graph: &mut GraphBuilder, input: &Value<T>) -> Result<Value<T>, String> {
+    let output = graph.node("Softmax", &[&input.name], vec![int_attr("axis", self.axis as i64)]);
+    graph.output(self, output, input)
+  }
+}
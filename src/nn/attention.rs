@@ -0,0 +1,158 @@
+use super::{prefixed, Linear, Module};
+use crate::autograd::{no_grad, Var};
+use crate::math::scalar::Float;
+use crate::onnx::{int_attr, ints_attr, GraphBuilder, Value};
+use crate::tensor::Tensor;
+
+pub fn causal_mask<T: Float>(query_len: usize, key_len: usize) -> Tensor<T> {
+  Tensor::from_fn(&[query_len, key_len], |i| if i[1] > i[0] { T::neg_infinity() } else { T::zero() })
+}
+
+pub fn scaled_dot_product_attention<T: Float>(
+  query: &Var<T>,
+  key: &Var<T>,
+  value: &Var<T>,
+  mask: Option<&Tensor<T>>,
+  causal: bool
+) -> Result<Var<T>, String> {
+  let (q_shape, k_shape) = (query.shape(), key.shape());
+  if q_shape.len() < 2 || k_shape.len() != q_shape.len() || q_shape.last() != k_shape.last() {
+    return Err(format!(
+      "scaled_dot_product_attention: query {:?} and key {:?} must share rank and feature size",
+      q_shape, k_shape
+    ));
+  }
+
+  let ndim = q_shape.len();
+  let (query_len, key_len, dim) = (q_shape[ndim - 2], k_shape[ndim - 2], q_shape[ndim - 1]);
+  let mut scores = query.matmul(&key.swap_axes(ndim - 2, ndim - 1)?)?.mul_scalar(T::one() / T::from_f64(dim as f64).sqrt());
+  if let Some(mask) = mask {
+    scores = (&scores + &Var::constant(mask.clone()))?;
+  }
+  if causal {
+    scores = (&scores + &Var::constant(causal_mask(query_len, key_len)))?;
+  }
+
+  scores.softmax(ndim - 1)?.matmul(value)
+}
+
+pub struct MultiheadAttention<T: Float = f64> {
+  pub q_proj: Linear<T>,
+  pub k_proj: Linear<T>,
+  pub v_proj: Linear<T>,
+  pub out_proj: Linear<T>,
+  pub causal: bool,
+  embed_dim: usize,
+  num_heads: usize
+}
+
+impl<T: Float> MultiheadAttention<T> {
+  pub fn new(embed_dim: usize, num_heads: usize) -> Result<Self, String> {
+    if num_heads == 0 || !embed_dim.is_multiple_of(num_heads) {
+      return Err(format!("MultiheadAttention: embed_dim {} is not divisible by {} heads", embed_dim, num_heads));
+    }
+
+    Ok(MultiheadAttention {
+      q_proj: Linear::new(embed_dim, embed_dim, true),
+      k_proj: Linear::new(embed_dim, embed_dim, true),
+      v_proj: Linear::new(embed_dim, embed_dim, true),
+      out_proj: Linear::new(embed_dim, embed_dim, true),
+      causal: false,
+      embed_dim,
+      num_heads
+    })
+  }
+
+  pub fn embed_dim(&self) -> usize {
+    self.embed_dim
+  }
+
+  pub fn num_heads(&self) -> usize {
+    self.num_heads
+  }
+
+  pub fn head_dim(&self) -> usize {
+    self.embed_dim / self.num_heads
+  }
+
+  fn split_heads(&self, x: &Var<T>) -> Result<Var<T>, String> {
+    let shape = x.shape();
+    x.reshape(&[shape[0], shape[1], self.num_heads, self.head_dim()])?.permute(&[0, 2, 1, 3])
+  }
+
+  fn merged_heads(&self, query: &Var<T>, key: &Var<T>, value: &Var<T>, mask: Option<&Tensor<T>>) -> Result<Var<T>, String> {
+    let (q_shape, k_shape, v_shape) = (query.shape(), key.shape(), value.shape());
+    for (name, shape) in [("query", &q_shape), ("key", &k_shape), ("value", &v_shape)] {
+      if shape.len() != 3 || shape[2] != self.embed_dim {
+        return Err(format!(
+          "MultiheadAttention: expected a (batch, seq, {}) {}, found shape {:?}",
+          self.embed_dim, name, shape
+        ));
+      }
+    }
+    if k_shape[..2] != v_shape[..2] || q_shape[0] != k_shape[0] {
+      return Err(format!(
+        "MultiheadAttention: incompatible query {:?}, key {:?} and value {:?}",
+        q_shape, k_shape, v_shape
+      ));
+    }
+
+    let q = self.split_heads(&self.q_proj.forward(query)?)?;
+    let k = self.split_heads(&self.k_proj.forward(key)?)?;
+    let v = self.split_heads(&self.v_proj.forward(value)?)?;
+    let heads = scaled_dot_product_attention(&q, &k, &v, mask, self.causal)?;
+    heads.permute(&[0, 2, 1, 3])?.reshape(&[q_shape[0], q_shape[1], self.embed_dim])
+  }
+
+  pub fn attend(&self, query: &Var<T>, key: &Var<T>, value: &Var<T>, mask: Option<&Tensor<T>>) -> Result<Var<T>, String> {
+    self.out_proj.forward(&self.merged_heads(query, key, value, mask)?)
+  }
+}
+
+impl<T: Float> Module<T> for MultiheadAttention<T> {
+  fn forward(&self, input: &Var<T>) -> Result<Var<T>, String> {
+    self.attend(input, input, input, None)
+  }
+
+  fn export_onnx(&self, graph: &mut GraphBuilder, input: &Value<T>) -> Result<Value<T>, String> {
+    let q = graph.scoped("q_proj", |graph| self.q_proj.export_onnx(graph, input))?;
+    let k = graph.scoped("k_proj", |graph| self.k_proj.export_onnx(graph, input))?;
+    let v = graph.scoped("v_proj", |graph| self.v_proj.export_onnx(graph, input))?;
+
+    let heads_shape = graph.ints("heads_shape", &[0, 0, self.num_heads as i64, self.head_dim() as i64]);
+    let mut split = |name: &str, perm: &[i64]| {
+      let reshaped = graph.node("Reshape", &[name, &heads_shape], Vec::new());
+      graph.node("Transpose", &[&reshaped], vec![ints_attr("perm", perm)])
+    };
+    let (q, k, v) = (split(&q.name, &[0, 2, 1, 3]), split(&k.name, &[0, 2, 3, 1]), split(&v.name, &[0, 2, 1, 3]));
+
+    let scale = graph.scalar("scale", 1.0 / (self.head_dim() as f64).sqrt());
+    let scores = graph.node("MatMul", &[&q, &k], Vec::new());
+    let mut scores = graph.node("Mul", &[&scores, &scale], Vec::new());
+    if self.causal {
+      let seq_len = input.sample.shape()[1];
+      let mask = graph.initializer("causal_mask", &causal_mask::<T>(seq_len, seq_len));
+      scores = graph.node("Add", &[&scores, &mask], Vec::new());
+    }
+    let weights = graph.node("Softmax", &[&scores], vec![int_attr("axis", -1)]);
+    let heads = graph.node("MatMul", &[&weights, &v], Vec::new());
+    let heads = graph.node("Transpose", &[&heads], vec![ints_attr("perm", &[0, 2, 1, 3])]);
+    let merged_shape = graph.ints("merged_shape", &[0, 0, self.embed_dim as i64]);
+    let merged = graph.node("Reshape", &[&heads, &merged_shape], Vec::new());
+
+    let x = Var::constant(input.sample.clone());
+    let sample = no_grad(|| self.merged_heads(&x, &x, &x, None))?.value();
+    graph.scoped("out_proj", |graph| self.out_proj.export_onnx(graph, &Value { name: merged, sample }))
+  }
+
+  fn named_parameters(&self) -> Vec<(String, Var<T>)> {
+    [("q_proj", &self.q_proj), ("k_proj", &self.k_proj), ("v_proj", &self.v_proj), ("out_proj", &self.out_proj)]
+      .into_iter()
+      .flat_map(|(prefix, layer)| prefixed(prefix, layer))
+      .collect()
+  }
+
+  fn children(&self) -> Vec<&dyn Module<T>> {
+    vec![&self.q_proj, &self.k_proj, &self.v_proj, &self.out_proj]
+  }
+}
@@ -0,0 +1,116 @@
+use super::Module;
+use crate::autograd::Var;
+use crate::init::{self, FanMode, Nonlinearity};
+use crate::math::scalar::Float;
+use crate::onnx::{int_attr, ints_attr, GraphBuilder, Value};
+use crate::tensor::conv::Conv2dOptions;
+use crate::tensor::Tensor;
+
+pub struct Conv2d<T: Float = f64> {
+  pub weight: Var<T>,
+  pub bias: Option<Var<T>>,
+  pub options: Conv2dOptions,
+  in_channels: usize,
+  out_channels: usize,
+  groups: usize
+}
+
+impl<T: Float> Conv2d<T> {
+  pub fn new(in_channels: usize, out_channels: usize, kernel: (usize, usize)) -> Result<Self, String> {
+    Conv2d::with_options(in_channels, out_channels, Conv2dOptions::new(kernel), 1, true)
+  }
+
+  pub fn with_options(in_channels: usize, out_channels: usize, options: Conv2dOptions, groups: usize, bias: bool) -> Result<Self, String> {
+    if groups == 0 || !in_channels.is_multiple_of(groups) || !out_channels.is_multiple_of(groups) {
+      return Err(format!(
+        "Conv2d: {} input and {} output channels cannot be split into {} groups",
+        in_channels, out_channels, groups
+      ));
+    }
+
+    let conv = Conv2d {
+      weight: Var::param(Tensor::zeros(&[out_channels, in_channels / groups, options.kernel.0, options.kernel.1])),
+      bias: bias.then(|| Var::param(Tensor::zeros(&[out_channels]))),
+      options,
+      in_channels,
+      out_channels,
+      groups
+    };
+    conv.reset_parameters()?;
+    Ok(conv)
+  }
+
+  pub fn in_channels(&self) -> usize {
+    self.in_channels
+  }
+
+  pub fn out_channels(&self) -> usize {
+    self.out_channels
+  }
+
+  pub fn groups(&self) -> usize {
+    self.groups
+  }
+}
+
+impl<T: Float> Module<T> for Conv2d<T> {
+  fn forward(&self, input: &Var<T>) -> Result<Var<T>, String> {
+    let shape = input.shape();
+    if shape.len() != 4 || shape[1] != self.in_channels {
+      return Err(format!("Conv2d: expected an [N, {}, H, W] input, found shape {:?}", self.in_channels, shape));
+    }
+
+    if self.groups == 1 {
+      return input.conv2d(&self.weight, self.bias.as_ref(), &self.options);
+    }
+
+    let (in_per_group, out_per_group) = (self.in_channels / self.groups, self.out_channels / self.groups);
+    let outputs = (0..self.groups)
+      .map(|g| {
+        let x = input.narrow(1, g * in_per_group, in_per_group)?;
+        let w = self.weight.narrow(0, g * out_per_group, out_per_group)?;
+        let b = self.bias.as_ref().map(|b| b.narrow(0, g * out_per_group, out_per_group)).transpose()?;
+        x.conv2d(&w, b.as_ref(), &self.options)
+      })
+      .collect::<Result<Vec<_>, String>>()?;
+    Var::cat(&outputs.iter().collect::<Vec<_>>(), 1)
+  }
+
+  fn reset_parameters(&self) -> Result<(), String> {
+    let shape = self.weight.shape();
+    self.weight.set_value(init::kaiming_uniform(&shape, FanMode::FanIn, Nonlinearity::Relu)?)?;
+    if let Some(bias) = &self.bias {
+      let bound = 1.0 / (init::fans(&shape)?.0.max(1) as f64).sqrt();
+      bias.set_value(init::uniform(&[self.out_channels], -bound, bound))?;
+    }
+    Ok(())
+  }
+
+  fn export_onnx(&self, graph: &mut GraphBuilder, input: &Value<T>) -> Result<Value<T>, String> {
+    let options = &self.options;
+    let pair = |(a, b): (usize, usize)| [a as i64, b as i64];
+    let (pad_h, pad_w) = (options.padding.0 as i64, options.padding.1 as i64);
+    let mut inputs = vec![graph.initializer("weight", &self.weight.value())];
+    if let Some(bias) = &self.bias {
+      inputs.push(graph.initializer("bias", &bias.value()));
+    }
+    let attributes = vec![
+      ints_attr("kernel_shape", &pair(options.kernel)),
+      ints_attr("strides", &pair(options.stride)),
+      ints_attr("pads", &[pad_h, pad_w, pad_h, pad_w]),
+      ints_attr("dilations", &pair(options.dilation)),
+      int_attr("group", self.groups as i64)
+    ];
+    let inputs: Vec<&str> = [input.name.as_str()].into_iter().chain(inputs.iter().map(String::as_str)).collect();
+    let output = graph.node("Conv", &inputs, attributes);
+    graph.output(self, output, input)
+  }
+
+  fn named_parameters(&self) -> Vec<(String, Var<T>)> {
+    let mut params = vec![("weight".to_string(), self.weight.clone())];
+    if let Some(bias) = &self.bias {
+      params.push(("bias".to_string(), bias.clone()));
+    }
+    params
+  }
+}
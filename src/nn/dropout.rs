@@ -0,0 +1,52 @@
+use std::cell::Cell;
+
+use super::Module;
+use crate::autograd::Var;
+use crate::math::scalar::Float;
+use crate::onnx::{GraphBuilder, Value};
+use crate::random::with_rng;
+use crate::tensor::Tensor;
+
+pub struct Dropout {
+  p: f64,
+  training: Cell<bool>
+}
+
+impl Dropout {
+  pub fn new(p: f64) -> Result<Self, String> {
+    if !(0.0..=1.0).contains(&p) {
+      return Err(format!("Dropout: probability must be in [0, 1], found {}", p));
+    }
+
+    Ok(Dropout { p, training: Cell::new(true) })
+  }
+
+  pub fn p(&self) -> f64 {
+    self.p
+  }
+
+  pub fn is_training(&self) -> bool {
+    self.training.get()
+  }
+}
+
+impl<T: Float> Module<T> for Dropout {
+  fn forward(&self, input: &Var<T>) -> Result<Var<T>, String> {
+    if !self.is_training() || self.p == 0.0 {
+      return Ok(input.clone());
+    }
+
+    let keep = 1.0 - self.p;
+    let scale = if keep > 0.0 { T::from_f64(1.0 / keep) } else { T::zero() };
+    let mask = with_rng(|rng| Tensor::from_fn(&input.shape(), |_| if rng.bernoulli(keep) { scale } else { T::zero() }));
+    input * &Var::constant(mask)
+  }
+
+  fn set_training(&self, training: bool) {
+    self.training.set(training);
+  }
+
+  fn export_onnx(&self, graph: &mut GraphBuilder, input: &Value<T>) -> Result<Value<T>, String> {
+    Ok(Value { name: graph.node("Identity", &[&input.name], Vec::new()), sample: input.sample.clone() })
+  }
+}
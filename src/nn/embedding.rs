@@ -0,0 +1,90 @@
+use super::Module;
+use crate::autograd::Var;
+use crate::init;
+use crate::math::scalar::Float;
+use crate::onnx::{int_attr, GraphBuilder, Value};
+use crate::tensor::Tensor;
+
+pub struct Embedding<T: Float = f64> {
+  pub weight: Var<T>,
+  pub padding_idx: Option<usize>,
+  num_embeddings: usize,
+  embedding_dim: usize
+}
+
+impl<T: Float> Embedding<T> {
+  pub fn new(num_embeddings: usize, embedding_dim: usize) -> Self {
+    Embedding {
+      weight: Var::param(init::normal(&[num_embeddings, embedding_dim], 0.0, 1.0)),
+      padding_idx: None,
+      num_embeddings,
+      embedding_dim
+    }
+  }
+
+  pub fn from_pretrained(weight: Tensor<T>, freeze: bool) -> Result<Self, String> {
+    let (num_embeddings, embedding_dim) = match weight.shape() {
+      &[n, d] => (n, d),
+      shape => return Err(format!("Embedding: expected a [num_embeddings, embedding_dim] weight, found shape {:?}", shape))
+    };
+    let embedding = Embedding { weight: Var::param(weight), padding_idx: None, num_embeddings, embedding_dim };
+    if freeze {
+      embedding.freeze()?;
+    }
+    Ok(embedding)
+  }
+
+  pub fn num_embeddings(&self) -> usize {
+    self.num_embeddings
+  }
+
+  pub fn embedding_dim(&self) -> usize {
+    self.embedding_dim
+  }
+
+  fn indices(&self, input: &Tensor<T>) -> Result<Vec<usize>, String> {
+    input
+      .iter()
+      .map(|&x| {
+        let index = x.to_f64();
+        if index < 0.0 || index.fract() != 0.0 || index as usize >= self.num_embeddings {
+          return Err(format!("Embedding: index {} out of range for {} embeddings", index, self.num_embeddings));
+        }
+        Ok(index as usize)
+      })
+      .collect()
+  }
+}
+
+impl<T: Float> Module<T> for Embedding<T> {
+  fn forward(&self, input: &Var<T>) -> Result<Var<T>, String> {
+    let indices = self.indices(&input.value())?;
+    let mut output = self.weight.index_select(0, &indices)?;
+    if let Some(padding_idx) = self.padding_idx {
+      let mask = Tensor::from_fn(&[indices.len(), self.embedding_dim], |i| if indices[i[0]] == padding_idx { T::zero() } else { T::one() });
+      output = (&output * &Var::constant(mask))?;
+    }
+    let mut shape = input.shape();
+    shape.push(self.embedding_dim);
+    output.reshape(&shape)
+  }
+
+  fn reset_parameters(&self) -> Result<(), String> {
+    self.weight.set_value(init::normal(&[self.num_embeddings, self.embedding_dim], 0.0, 1.0))
+  }
+
+  fn export_onnx(&self, graph: &mut GraphBuilder, input: &Value<T>) -> Result<Value<T>, String> {
+    let mut weight = self.weight.value();
+    if let Some(padding_idx) = self.padding_idx {
+      weight = Tensor::from_fn(weight.shape(), |i| if i[0] == padding_idx { T::zero() } else { *weight.get(i).unwrap_or(&T::zero()) });
+    }
+    let weight = graph.initializer("weight", &weight);
+    let indices = graph.node("Cast", &[&input.name], vec![int_attr("to", 7)]);
+    let output = graph.node("Gather", &[&weight, &indices], vec![int_attr("axis", 0)]);
+    graph.output(self, output, input)
+  }
+
+  fn named_parameters(&self) -> Vec<(String, Var<T>)> {
+    vec![("weight".to_string(), self.weight.clone())]
+  }
+}
@@ -0,0 +1,104 @@
+use super::Module;
+use crate::autograd::Var;
+use crate::init;
+use crate::math::scalar::Float;
+use crate::onnx::{GraphBuilder, Value};
+use crate::tensor::Tensor;
+
+pub struct Linear<T: Float = f64> {
+  pub weight: Var<T>,
+  pub bias: Option<Var<T>>,
+  in_features: usize,
+  out_features: usize
+}
+
+fn default_bound(in_features: usize) -> f64 {
+  1.0 / (in_features.max(1) as f64).sqrt()
+}
+
+impl<T: Float> Linear<T> {
+  pub fn new(in_features: usize, out_features: usize, bias: bool) -> Self {
+    let bound = default_bound(in_features);
+    Linear {
+      weight: Var::param(init::uniform(&[out_features, in_features], -bound, bound)),
+      bias: bias.then(|| Var::param(init::uniform(&[out_features], -bound, bound))),
+      in_features,
+      out_features
+    }
+  }
+
+  pub fn from_weights(weight: Tensor<T>, bias: Option<Tensor<T>>) -> Result<Self, String> {
+    let (out_features, in_features) = match weight.shape() {
+      &[o, i] => (o, i),
+      shape => return Err(format!("Linear: expected an [out, in] weight, found shape {:?}", shape))
+    };
+    if let Some(bias) = &bias {
+      if bias.shape() != [out_features] {
+        return Err(format!("Linear: expected a bias of shape [{}], found {:?}", out_features, bias.shape()));
+      }
+    }
+
+    Ok(Linear {
+      weight: Var::param(weight),
+      bias: bias.map(Var::param),
+      in_features,
+      out_features
+    })
+  }
+
+  pub fn in_features(&self) -> usize {
+    self.in_features
+  }
+
+  pub fn out_features(&self) -> usize {
+    self.out_features
+  }
+}
+
+impl<T: Float> Module<T> for Linear<T> {
+  fn forward(&self, input: &Var<T>) -> Result<Var<T>, String> {
+    let shape = input.shape();
+    if shape.last() != Some(&self.in_features) {
+      return Err(format!("Linear: expected input with {} features, found shape {:?}", self.in_features, shape));
+    }
+
+    let batched = if shape.len() == 1 { input.unsqueeze(0)? } else { input.clone() };
+    let mut output = batched.matmul(&self.weight.transpose())?;
+    if let Some(bias) = &self.bias {
+      output = (&output + bias)?;
+    }
+
+    if shape.len() == 1 {
+      output.reshape(&[self.out_features])
+    } else {
+      Ok(output)
+    }
+  }
+
+  fn reset_parameters(&self) -> Result<(), String> {
+    let bound = default_bound(self.in_features);
+    self.weight.set_value(init::uniform(&[self.out_features, self.in_features], -bound, bound))?;
+    if let Some(bias) = &self.bias {
+      bias.set_value(init::uniform(&[self.out_features], -bound, bound))?;
+    }
+    Ok(())
+  }
+
+  fn export_onnx(&self, graph: &mut GraphBuilder, input: &Value<T>) -> Result<Value<T>, String> {
+    let weight = graph.initializer("weight", &self.weight.value().transpose());
+    let mut output = graph.node("MatMul", &[&input.name, &weight], Vec::new());
+    if let Some(bias) = &self.bias {
+      let bias = graph.initializer("bias", &bias.value());
+      output = graph.node("Add", &[&output, &bias], Vec::new());
+    }
+    graph.output(self, output, input)
+  }
+
+  fn named_parameters(&self) -> Vec<(String, Var<T>)> {
+    let mut params = vec![("weight".to_string(), self.weight.clone())];
+    if let Some(bias) = &self.bias {
+      params.push(("bias".to_string(), bias.clone()));
+    }
+    params
+  }
+}
@@ -0,0 +1,125 @@
+use super::activations::{relu, sigmoid};
+use crate::autograd::Var;
+use crate::math::scalar::Float;
+use crate::tensor::Tensor;
+
+fn check_target<T: Float>(op: &str, input: &Var<T>, target: &Tensor<T>) -> Result<(), String> {
+  if input.shape() != target.shape() {
+    return Err(format!("{}: input shape {:?} does not match target shape {:?}", op, input.shape(), target.shape()));
+  }
+  Ok(())
+}
+
+fn reduce<T: Float>(op: &str, losses: Var<T>, weights: Option<&Tensor<T>>) -> Result<Var<T>, String> {
+  let shape = losses.shape();
+  if shape.is_empty() {
+    return Ok(losses);
+  }
+
+  let per_sample = if shape.len() > 1 {
+    losses.mean(&(1..shape.len()).collect::<Vec<_>>(), false)?
+  } else {
+    losses
+  };
+
+  match weights {
+    None => per_sample.mean(&[], false),
+    Some(weights) => {
+      if weights.shape() != [shape[0]] {
+        return Err(format!("{}: expected {} per-sample weights, found shape {:?}", op, shape[0], weights.shape()));
+      }
+      let total = weights.sum_all();
+      if total <= T::zero() {
+        return Err(format!("{}: per-sample weights must have a positive sum", op));
+      }
+      Ok((&per_sample * &Var::constant(weights.clone()))?.sum(&[], false)?.mul_scalar(T::one() / total))
+    }
+  }
+}
+
+pub fn mse_loss<T: Float>(input: &Var<T>, target: &Tensor<T>, weights: Option<&Tensor<T>>) -> Result<Var<T>, String> {
+  check_target("mse_loss", input, target)?;
+  reduce("mse_loss", (input - &Var::constant(target.clone()))?.powi(2), weights)
+}
+
+pub fn mae_loss<T: Float>(input: &Var<T>, target: &Tensor<T>, weights: Option<&Tensor<T>>) -> Result<Var<T>, String> {
+  check_target("mae_loss", input, target)?;
+  reduce("mae_loss", (input - &Var::constant(target.clone()))?.abs(), weights)
+}
+
+pub fn huber_loss<T: Float>(input: &Var<T>, target: &Tensor<T>, delta: T, weights: Option<&Tensor<T>>) -> Result<Var<T>, String> {
+  check_target("huber_loss", input, target)?;
+  if delta <= T::zero() {
+    return Err("huber_loss: delta must be positive".to_string());
+  }
+
+  let error = (input - &Var::constant(target.clone()))?.abs();
+  let linear = relu(&error.add_scalar(-delta));
+  let quadratic = (&error - &linear)?;
+  let losses = (&quadratic.powi(2).mul_scalar(T::from_f64(0.5)) + &linear.mul_scalar(delta))?;
+  reduce("huber_loss", losses, weights)
+}
+
+pub fn binary_cross_entropy<T: Float>(input: &Var<T>, target: &Tensor<T>, weights: Option<&Tensor<T>>) -> Result<Var<T>, String> {
+  check_target("binary_cross_entropy", input, target)?;
+  let eps = T::from_f64(1e-12);
+  let probs = input.clamp(eps, T::one() - eps);
+  let y = Var::constant(target.clone());
+  let not_y = Var::constant(target.map(|&t| T::one() - t));
+  let log_likelihood = (&(&y * &probs.ln())? + &(&not_y * &(-&probs).add_scalar(T::one()).ln())?)?;
+  reduce("binary_cross_entropy", -log_likelihood, weights)
+}
+
+pub fn binary_cross_entropy_with_logits<T: Float>(input: &Var<T>, target: &Tensor<T>, weights: Option<&Tensor<T>>) -> Result<Var<T>, String> {
+  check_target("binary_cross_entropy_with_logits", input, target)?;
+  let value = input.value().zip_map(target, |&x, &y| x.max(T::zero()) - x * y + (T::one() + (-x.abs()).exp()).ln())?;
+  let (logits, labels) = (input.clone(), Var::constant(target.clone()));
+  let losses = Var::from_op(value, "bce_with_logits", vec![input.clone()], move |g| {
+    Ok(vec![(g * &(&sigmoid(&logits) - &labels)?)?])
+  });
+  reduce("binary_cross_entropy_with_logits", losses, weights)
+}
+
+pub fn cross_entropy<T: Float>(logits: &Var<T>, targets: &[usize], weights: Option<&Tensor<T>>) -> Result<Var<T>, String> {
+  let (n, classes) = match logits.shape()[..] {
+    [n, c] => (n, c),
+    ref shape => return Err(format!("cross_entropy: expected [N, C] logits, found shape {:?}", shape))
+  };
+  if targets.len() != n {
+    return Err(format!("cross_entropy: expected {} targets, found {}", n, targets.len()));
+  }
+  if let Some(&bad) = targets.iter().find(|&&t| t >= classes) {
+    return Err(format!("cross_entropy: target class {} out of range for {} classes", bad, classes));
+  }
+
+  let index = Tensor::from_vec(&[n, 1], targets.to_vec())?;
+  let picked = logits.log_softmax(1)?.gather(1, &index)?.reshape(&[n])?;
+  reduce("cross_entropy", -picked, weights)
+}
+
+pub fn hinge_loss<T: Float>(input: &Var<T>, target: &Tensor<T>, weights: Option<&Tensor<T>>) -> Result<Var<T>, String> {
+  check_target("hinge_loss", input, target)?;
+  if target.iter().any(|&t| t != T::one() && t != -T::one()) {
+    return Err("hinge_loss: targets must be -1 or 1".to_string());
+  }
+
+  let margin = (&Var::constant(target.clone()) * input)?;
+  reduce("hinge_loss", relu(&(-margin).add_scalar(T::one())), weights)
+}
+
+pub fn penalty<T: Float>(params: &[Var<T>], l1: f64, l2: f64) -> Result<Var<T>, String> {
+  if l1 < 0.0 || l2 < 0.0 {
+    return Err(format!("penalty: coefficients must be non-negative, found l1 = {} and l2 = {}", l1, l2));
+  }
+
+  let mut total = Var::constant(Tensor::scalar(T::zero()));
+  for param in params {
+    if l1 > 0.0 {
+      total = (&total + &param.abs().sum(&[], false)?.mul_scalar(T::from_f64(l1)))?;
+    }
+    if l2 > 0.0 {
+      total = (&total + &param.powi(2).sum(&[], false)?.mul_scalar(T::from_f64(l2)))?;
+    }
+  }
+  Ok(total)
+}
@@ -0,0 +1,194 @@
+use crate::autograd::{no_grad, Var};
+use crate::math::scalar::Float;
+use crate::onnx::{GraphBuilder, Value};
+use crate::tensor::Tensor;
+
+pub mod activations;
+pub mod attention;
+pub mod conv;
+pub mod dropout;
+pub mod embedding;
+pub mod linear;
+pub mod loss;
+pub mod norm;
+pub mod sequence;
+pub mod sequential;
+pub mod summary;
+pub mod transformer;
+
+pub use activations::{LeakyReLU, ReLU, SiLU, Sigmoid, Softmax, Tanh, GELU};
+pub use attention::MultiheadAttention;
+pub use conv::Conv2d;
+pub use dropout::Dropout;
+pub use embedding::Embedding;
+pub use linear::Linear;
+pub use norm::{BatchNorm1d, BatchNorm2d, LayerNorm};
+pub use sequence::{key_padding_mask, pad_sequences, sequence_mask, PackedSequence, PadOptions, PadSide};
+pub use sequential::Sequential;
+pub use summary::{LayerSummary, ModelSummary};
+pub use transformer::{FeedForwardActivation, TransformerDecoderLayer, TransformerEncoder, TransformerEncoderLayer};
+
+pub trait Module<T: Float = f64> {
+  fn forward(&self, input: &Var<T>) -> Result<Var<T>, String>;
+
+  fn named_parameters(&self) -> Vec<(String, Var<T>)> {
+    Vec::new()
+  }
+
+  fn parameters(&self) -> Vec<Var<T>> {
+    self.named_parameters().into_iter().map(|(_, param)| param).collect()
+  }
+
+  fn trainable_parameters(&self) -> Vec<Var<T>> {
+    self.parameters().into_iter().filter(|param| param.requires_grad()).collect()
+  }
+
+  fn named_buffers(&self) -> Vec<(String, Var<T>)> {
+    Vec::new()
+  }
+
+  fn state_dict(&self) -> Vec<(String, Tensor<T>)> {
+    let mut entries = self.named_parameters();
+    entries.extend(self.named_buffers());
+    entries.into_iter().map(|(name, var)| (name, var.value())).collect()
+  }
+
+  fn load_state_dict(&self, state: &[(String, Tensor<T>)], strict: bool) -> Result<(), String> {
+    let mut entries = self.named_parameters();
+    entries.extend(self.named_buffers());
+
+    let mut updates = Vec::new();
+    let mut missing = Vec::new();
+    for (name, var) in &entries {
+      match state.iter().find(|(key, _)| key == name) {
+        Some((_, tensor)) if tensor.shape() != var.shape().as_slice() => {
+          return Err(format!(
+            "load_state_dict: '{}' has shape {:?} in the state dict but {:?} in the model",
+            name,
+            tensor.shape(),
+            var.shape()
+          ));
+        }
+        Some((_, tensor)) => updates.push((var, tensor)),
+        None => missing.push(name.as_str())
+      }
+    }
+
+    if strict {
+      let unexpected: Vec<&str> = state
+        .iter()
+        .filter(|(key, _)| !entries.iter().any(|(name, _)| name == key))
+        .map(|(key, _)| key.as_str())
+        .collect();
+      if !missing.is_empty() || !unexpected.is_empty() {
+        return Err(format!(
+          "load_state_dict: missing keys [{}], unexpected keys [{}]",
+          missing.join(", "),
+          unexpected.join(", ")
+        ));
+      }
+    }
+
+    for (var, tensor) in updates {
+      var.set_value(tensor.clone())?;
+    }
+    Ok(())
+  }
+
+  fn freeze(&self) -> Result<(), String> {
+    self.parameters().iter().try_for_each(|param| param.set_requires_grad(false))
+  }
+
+  fn unfreeze(&self) -> Result<(), String> {
+    self.parameters().iter().try_for_each(|param| param.set_requires_grad(true))
+  }
+
+  fn name(&self) -> &'static str {
+    let path = std::any::type_name::<Self>();
+    let base = path.split('<').next().unwrap_or(path);
+    base.rsplit("::").next().unwrap_or(base)
+  }
+
+  fn children(&self) -> Vec<&dyn Module<T>> {
+    Vec::new()
+  }
+
+  fn reset_parameters(&self) -> Result<(), String> {
+    Ok(())
+  }
+
+  fn apply<F>(&self, mut f: F) -> Result<(), String>
+  where
+    F: FnMut(&dyn Module<T>) -> Result<(), String>,
+    Self: Sized
+  {
+    visit(self, &mut f)
+  }
+
+  fn set_training(&self, training: bool) {
+    self.children().into_iter().for_each(|child| child.set_training(training));
+  }
+
+  fn train(&self) {
+    self.set_training(true);
+  }
+
+  fn eval(&self) {
+    self.set_training(false);
+  }
+
+  fn predict(&self, input: &Tensor<T>) -> Result<Tensor<T>, String> {
+    no_grad(|| self.forward(&Var::constant(input.clone())).map(|output| output.value()))
+  }
+
+  fn summarize(&self, name: &str, input: &Tensor<T>, layers: &mut Vec<LayerSummary>) -> Result<Tensor<T>, String> {
+    let output = self.predict(input)?;
+    layers.push(LayerSummary::new(if name.is_empty() { self.name() } else { name }, self, &output));
+    Ok(output)
+  }
+
+  fn summary(&self, input_shape: &[usize]) -> Result<ModelSummary, String> {
+    let state = self.state_dict();
+    let mut layers = Vec::new();
+    let result = self.summarize("", &Tensor::zeros(input_shape), &mut layers);
+    self.load_state_dict(&state, true)?;
+    result?;
+
+    let params = self.parameters();
+    let summary = ModelSummary {
+      layers,
+      input_shape: input_shape.to_vec(),
+      total_params: params.iter().map(|param| param.value().len()).sum(),
+      trainable_params: params.iter().filter(|param| param.requires_grad()).map(|param| param.value().len()).sum()
+    };
+    println!("{}", summary);
+    Ok(summary)
+  }
+
+  fn export_onnx(&self, _graph: &mut GraphBuilder, _input: &Value<T>) -> Result<Value<T>, String> {
+    Err(format!("ONNX export is not supported for {}", self.name()))
+  }
+}
+
+fn visit<T: Float>(module: &dyn Module<T>, f: &mut dyn FnMut(&dyn Module<T>) -> Result<(), String>) -> Result<(), String> {
+  for child in module.children() {
+    visit(child, f)?;
+  }
+  f(module)
+}
+
+pub(crate) fn prefixed_buffers<T, M>(prefix: &str, module: &M) -> Vec<(String, Var<T>)>
+where
+  T: Float,
+  M: Module<T> + ?Sized
+{
+  module.named_buffers().into_iter().map(|(name, buffer)| (format!("{}.{}", prefix, name), buffer)).collect()
+}
+
+pub(crate) fn prefixed<T, M>(prefix: &str, module: &M) -> Vec<(String, Var<T>)>
+where
+  T: Float,
+  M: Module<T> + ?Sized
+{
+  module.named_parameters().into_iter().map(|(name, param)| (format!("{}.{}", prefix, name), param)).collect()
+}
@@ -0,0 +1,229 @@
+use std::cell::Cell;
+
+use super::Module;
+use crate::autograd::Var;
+use crate::math::scalar::Float;
+use crate::onnx::{float_attr, int_attr, GraphBuilder, Value};
+use crate::tensor::Tensor;
+
+fn export_affine<T: Float>(graph: &mut GraphBuilder, weight: Option<&Var<T>>, bias: Option<&Var<T>>, shape: &[usize]) -> (String, String) {
+  let weight = weight.map(|w| w.value()).unwrap_or_else(|| Tensor::full(shape, T::one()));
+  let bias = bias.map(|b| b.value()).unwrap_or_else(|| Tensor::zeros(shape));
+  (graph.initializer("weight", &weight), graph.initializer("bias", &bias))
+}
+
+fn reset_affine<T: Float>(weight: Option<&Var<T>>, bias: Option<&Var<T>>) -> Result<(), String> {
+  if let Some(weight) = weight {
+    weight.set_value(Tensor::full(&weight.shape(), T::one()))?;
+  }
+  if let Some(bias) = bias {
+    bias.set_value(Tensor::zeros(&bias.shape()))?;
+  }
+  Ok(())
+}
+
+pub struct BatchNorm<T: Float, const SPATIAL: usize> {
+  pub weight: Option<Var<T>>,
+  pub bias: Option<Var<T>>,
+  pub eps: f64,
+  pub momentum: f64,
+  running_mean: Var<T>,
+  running_var: Var<T>,
+  num_features: usize,
+  training: Cell<bool>
+}
+
+pub type BatchNorm1d<T = f64> = BatchNorm<T, 1>;
+pub type BatchNorm2d<T = f64> = BatchNorm<T, 2>;
+
+impl<T: Float, const SPATIAL: usize> BatchNorm<T, SPATIAL> {
+  pub fn new(num_features: usize, affine: bool) -> Self {
+    BatchNorm {
+      weight: affine.then(|| Var::param(Tensor::full(&[num_features], T::one()))),
+      bias: affine.then(|| Var::param(Tensor::zeros(&[num_features]))),
+      eps: 1e-5,
+      momentum: 0.1,
+      running_mean: Var::constant(Tensor::zeros(&[num_features])),
+      running_var: Var::constant(Tensor::full(&[num_features], T::one())),
+      num_features,
+      training: Cell::new(true)
+    }
+  }
+
+  pub fn num_features(&self) -> usize {
+    self.num_features
+  }
+
+  pub fn running_mean(&self) -> Tensor<T> {
+    self.running_mean.value()
+  }
+
+  pub fn running_var(&self) -> Tensor<T> {
+    self.running_var.value()
+  }
+
+  pub fn reset_running_stats(&self) -> Result<(), String> {
+    self.running_mean.set_value(Tensor::zeros(&[self.num_features]))?;
+    self.running_var.set_value(Tensor::full(&[self.num_features], T::one()))
+  }
+
+  pub fn is_training(&self) -> bool {
+    self.training.get()
+  }
+
+  fn update_running_stats(&self, mean: &Tensor<T>, var: &Tensor<T>, count: usize) -> Result<(), String> {
+    let momentum = T::from_f64(self.momentum);
+    let correction = T::from_f64(count as f64 / (count - 1) as f64);
+    let blend = |running: &Var<T>, batch: &Tensor<T>, scale: T| -> Result<(), String> {
+      let batch = batch.reshape(&[self.num_features])?;
+      running.set_value(running.value().zip_map(&batch, |&r, &b| (T::one() - momentum) * r + momentum * b * scale)?)
+    };
+    blend(&self.running_mean, mean, T::one())?;
+    blend(&self.running_var, var, correction)
+  }
+}
+
+impl<T: Float, const SPATIAL: usize> Module<T> for BatchNorm<T, SPATIAL> {
+  fn forward(&self, input: &Var<T>) -> Result<Var<T>, String> {
+    let shape = input.shape();
+    let ranks: &[usize] = if SPATIAL == 1 { &[2, 3] } else { &[SPATIAL + 2] };
+    if !ranks.contains(&shape.len()) || shape[1] != self.num_features {
+      return Err(format!(
+        "BatchNorm{}d: expected a {}-D input with {} channels, found shape {:?}",
+        SPATIAL,
+        ranks.iter().map(|r| r.to_string()).collect::<Vec<_>>().join(" or "),
+        self.num_features,
+        shape
+      ));
+    }
+
+    let axes: Vec<usize> = (0..shape.len()).filter(|&axis| axis != 1).collect();
+    let mut channel_shape = vec![1; shape.len()];
+    channel_shape[1] = self.num_features;
+
+    let normalized = if self.is_training() {
+      let count: usize = axes.iter().map(|&axis| shape[axis]).product();
+      if count < 2 {
+        return Err(format!("BatchNorm{}d: expected more than one value per channel in training mode, found shape {:?}", SPATIAL, shape));
+      }
+      let mean = input.mean(&axes, true)?;
+      let centered = (input - &mean)?;
+      let var = centered.powi(2).mean(&axes, true)?;
+      self.update_running_stats(&mean.value(), &var.value(), count)?;
+      (&centered / &var.add_scalar(T::from_f64(self.eps)).sqrt())?
+    } else {
+      let mean = Var::constant(self.running_mean.value().reshape(&channel_shape)?);
+      let std = self.running_var.value().reshape(&channel_shape)?.map(|&v| (v + T::from_f64(self.eps)).sqrt());
+      (&(input - &mean)? / &Var::constant(std))?
+    };
+
+    let mut output = normalized;
+    if let Some(weight) = &self.weight {
+      output = (&output * &weight.reshape(&channel_shape)?)?;
+    }
+    if let Some(bias) = &self.bias {
+      output = (&output + &bias.reshape(&channel_shape)?)?;
+    }
+    Ok(output)
+  }
+
+  fn set_training(&self, training: bool) {
+    self.training.set(training);
+  }
+
+  fn reset_parameters(&self) -> Result<(), String> {
+    reset_affine(self.weight.as_ref(), self.bias.as_ref())?;
+    self.reset_running_stats()
+  }
+
+  fn export_onnx(&self, graph: &mut GraphBuilder, input: &Value<T>) -> Result<Value<T>, String> {
+    let (weight, bias) = export_affine(graph, self.weight.as_ref(), self.bias.as_ref(), &[self.num_features]);
+    let mean = graph.initializer("running_mean", &self.running_mean.value());
+    let var = graph.initializer("running_var", &self.running_var.value());
+    let output = graph.node("BatchNormalization", &[&input.name, &weight, &bias, &mean, &var], vec![float_attr("epsilon", self.eps)]);
+    graph.output(self, output, input)
+  }
+
+  fn named_buffers(&self) -> Vec<(String, Var<T>)> {
+    vec![
+      ("running_mean".to_string(), self.running_mean.clone()),
+      ("running_var".to_string(), self.running_var.clone())
+    ]
+  }
+
+  fn named_parameters(&self) -> Vec<(String, Var<T>)> {
+    let mut params = Vec::new();
+    if let Some(weight) = &self.weight {
+      params.push(("weight".to_string(), weight.clone()));
+    }
+    if let Some(bias) = &self.bias {
+      params.push(("bias".to_string(), bias.clone()));
+    }
+    params
+  }
+}
+
+pub struct LayerNorm<T: Float = f64> {
+  pub weight: Option<Var<T>>,
+  pub bias: Option<Var<T>>,
+  pub eps: f64,
+  normalized_shape: Vec<usize>
+}
+
+impl<T: Float> LayerNorm<T> {
+  pub fn new(normalized_shape: &[usize], elementwise_affine: bool) -> Self {
+    LayerNorm {
+      weight: elementwise_affine.then(|| Var::param(Tensor::full(normalized_shape, T::one()))),
+      bias: elementwise_affine.then(|| Var::param(Tensor::zeros(normalized_shape))),
+      eps: 1e-5,
+      normalized_shape: normalized_shape.to_vec()
+    }
+  }
+
+  pub fn normalized_shape(&self) -> &[usize] {
+    &self.normalized_shape
+  }
+}
+
+impl<T: Float> Module<T> for LayerNorm<T> {
+  fn forward(&self, input: &Var<T>) -> Result<Var<T>, String> {
+    let shape = input.shape();
+    if !shape.ends_with(&self.normalized_shape) {
+      return Err(format!("LayerNorm: expected input ending in {:?}, found shape {:?}", self.normalized_shape, shape));
+    }
+
+    let axes: Vec<usize> = (shape.len() - self.normalized_shape.len()..shape.len()).collect();
+    let centered = (input - &input.mean(&axes, true)?)?;
+    let var = centered.powi(2).mean(&axes, true)?;
+    let mut output = (&centered / &var.add_scalar(T::from_f64(self.eps)).sqrt())?;
+    if let Some(weight) = &self.weight {
+      output = (&output * weight)?;
+    }
+    if let Some(bias) = &self.bias {
+      output = (&output + bias)?;
+    }
+    Ok(output)
+  }
+
+  fn export_onnx(&self, graph: &mut GraphBuilder, input: &Value<T>) -> Result<Value<T>, String> {
+    let (weight, bias) = export_affine(graph, self.weight.as_ref(), self.bias.as_ref(), &self.normalized_shape);
+    let attributes = vec![int_attr("axis", -(self.normalized_shape.len() as i64)), float_attr("epsilon", self.eps)];
+    let output = graph.node("LayerNormalization", &[&input.name, &weight, &bias], attributes);
+    graph.output(self, output, input)
+  }
+
+  fn reset_parameters(&self) -> Result<(), String> {
+    reset_affine(self.weight.as_ref(), self.bias.as_ref())
+  }
+
+  fn named_parameters(&self) -> Vec<(String, Var<T>)> {
+    let mut params = Vec::new();
+    if let Some(weight) = &self.weight {
+      params.push(("weight".to_string(), weight.clone()));
+    }
+    if let Some(bias) = &self.bias {
+      params.push(("bias".to_string(), bias.clone()));
+    }
+    params
+  }
+}
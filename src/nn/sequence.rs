@@ -0,0 +1,176 @@
+use crate::math::scalar::Float;
+use crate::tensor::Tensor;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PadSide {
+  Pre,
+  Post
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PadOptions {
+  pub max_length: Option<usize>,
+  pub padding: PadSide,
+  pub truncating: PadSide,
+  pub value: f64
+}
+
+impl PadOptions {
+  pub fn new() -> Self {
+    PadOptions { max_length: None, padding: PadSide::Post, truncating: PadSide::Post, value: 0.0 }
+  }
+}
+
+impl Default for PadOptions {
+  fn default() -> Self {
+    PadOptions::new()
+  }
+}
+
+fn step_shape<T: Float>(sequences: &[Tensor<T>], op: &str) -> Result<Vec<usize>, String> {
+  let first = sequences.first().ok_or_else(|| format!("{}: no sequences", op))?;
+  if first.ndim() == 0 {
+    return Err(format!("{}: sequences must have at least one dimension", op));
+  }
+  let step = first.shape()[1..].to_vec();
+  if let Some(other) = sequences.iter().find(|sequence| sequence.ndim() == 0 || sequence.shape()[1..] != step[..]) {
+    return Err(format!("{}: sequence of shape {:?} does not match step shape {:?}", op, other.shape(), step));
+  }
+  Ok(step)
+}
+
+pub fn pad_sequences<T: Float>(sequences: &[Tensor<T>], options: &PadOptions) -> Result<(Tensor<T>, Vec<usize>), String> {
+  let step = step_shape(sequences, "pad_sequences")?;
+  let step_len: usize = step.iter().product();
+  let longest = sequences.iter().map(|sequence| sequence.shape()[0]).max().unwrap_or(0);
+  let width = options.max_length.unwrap_or(longest);
+
+  let mut data = vec![T::from_f64(options.value); sequences.len() * width * step_len];
+  let mut lengths = Vec::with_capacity(sequences.len());
+  for (row, sequence) in sequences.iter().enumerate() {
+    let values = sequence.to_vec();
+    let len = sequence.shape()[0].min(width);
+    let skip = match options.truncating {
+      PadSide::Pre => sequence.shape()[0] - len,
+      PadSide::Post => 0
+    };
+    let offset = match options.padding {
+      PadSide::Pre => width - len,
+      PadSide::Post => 0
+    };
+    let start = (row * width + offset) * step_len;
+    data[start..start + len * step_len].copy_from_slice(&values[skip * step_len..(skip + len) * step_len]);
+    lengths.push(len);
+  }
+
+  let mut shape = vec![sequences.len(), width];
+  shape.extend(step);
+  Ok((Tensor::from_vec(&shape, data)?, lengths))
+}
+
+pub fn sequence_mask<T: Float>(lengths: &[usize], max_length: usize, padding: PadSide) -> Tensor<T> {
+  Tensor::from_fn(&[lengths.len(), max_length], |i| {
+    let valid = match padding {
+      PadSide::Post => i[1] < lengths[i[0]],
+      PadSide::Pre => i[1] + lengths[i[0]] >= max_length
+    };
+    if valid { T::one() } else { T::zero() }
+  })
+}
+
+pub fn key_padding_mask<T: Float>(mask: &Tensor<T>) -> Result<Tensor<T>, String> {
+  let &[batch, len] = mask.shape() else {
+    return Err(format!("key_padding_mask: expected a (batch, seq) mask, found shape {:?}", mask.shape()));
+  };
+  let additive = mask.map(|&x| if x == T::zero() { T::neg_infinity() } else { T::zero() });
+  additive.reshape(&[batch, 1, 1, len])
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PackedSequence<T: Float = f64> {
+  pub data: Tensor<T>,
+  pub batch_sizes: Vec<usize>,
+  pub sorted_indices: Vec<usize>
+}
+
+impl<T: Float> PackedSequence<T> {
+  pub fn pack(sequences: &[Tensor<T>]) -> Result<Self, String> {
+    let step = step_shape(sequences, "PackedSequence")?;
+    let step_len: usize = step.iter().product();
+    let mut sorted_indices: Vec<usize> = (0..sequences.len()).collect();
+    sorted_indices.sort_by_key(|&index| std::cmp::Reverse(sequences[index].shape()[0]));
+    if sequences[sorted_indices[sorted_indices.len() - 1]].shape()[0] == 0 {
+      return Err("PackedSequence: sequences must not be empty".to_string());
+    }
+
+    let values: Vec<Vec<T>> = sequences.iter().map(Tensor::to_vec).collect();
+    let longest = sequences[sorted_indices[0]].shape()[0];
+    let mut data = Vec::new();
+    let mut batch_sizes = Vec::with_capacity(longest);
+    for t in 0..longest {
+      let active: Vec<usize> = sorted_indices.iter().copied().take_while(|&index| sequences[index].shape()[0] > t).collect();
+      for &index in &active {
+        data.extend_from_slice(&values[index][t * step_len..(t + 1) * step_len]);
+      }
+      batch_sizes.push(active.len());
+    }
+
+    let mut shape = vec![batch_sizes.iter().sum()];
+    shape.extend(step);
+    Ok(PackedSequence { data: Tensor::from_vec(&shape, data)?, batch_sizes, sorted_indices })
+  }
+
+  pub fn pack_padded(padded: &Tensor<T>, lengths: &[usize]) -> Result<Self, String> {
+    if padded.ndim() < 2 || padded.shape()[0] != lengths.len() {
+      return Err(format!("PackedSequence: expected a (batch, seq, ...) tensor for {} lengths, found shape {:?}", lengths.len(), padded.shape()));
+    }
+    let sequences = lengths
+      .iter()
+      .enumerate()
+      .map(|(row, &len)| {
+        let sequence = padded.narrow(0, row, 1)?.narrow(1, 0, len)?;
+        sequence.reshape(&sequence.shape()[1..])
+      })
+      .collect::<Result<Vec<_>, String>>()?;
+    Self::pack(&sequences)
+  }
+
+  pub fn batch_size(&self) -> usize {
+    self.sorted_indices.len()
+  }
+
+  pub fn lengths(&self) -> Vec<usize> {
+    let mut lengths = vec![0; self.batch_size()];
+    for &size in &self.batch_sizes {
+      for &index in &self.sorted_indices[..size] {
+        lengths[index] += 1;
+      }
+    }
+    lengths
+  }
+
+  pub fn step(&self, t: usize) -> Result<Tensor<T>, String> {
+    let size = *self.batch_sizes.get(t).ok_or_else(|| format!("PackedSequence: step {} out of range for {} steps", t, self.batch_sizes.len()))?;
+    let start = self.batch_sizes[..t].iter().sum();
+    self.data.narrow(0, start, size)
+  }
+
+  pub fn unpack(&self, padding_value: f64) -> Result<(Tensor<T>, Vec<usize>), String> {
+    let step: Vec<usize> = self.data.shape()[1..].to_vec();
+    let step_len: usize = step.iter().product();
+    let width = self.batch_sizes.len();
+    let values = self.data.to_vec();
+    let mut data = vec![T::from_f64(padding_value); self.batch_size() * width * step_len];
+    let mut offset = 0;
+    for (t, &size) in self.batch_sizes.iter().enumerate() {
+      for &index in &self.sorted_indices[..size] {
+        let start = (index * width + t) * step_len;
+        data[start..start + step_len].copy_from_slice(&values[offset..offset + step_len]);
+        offset += step_len;
+      }
+    }
+    let mut shape = vec![self.batch_size(), width];
+    shape.extend(step);
+    Ok((Tensor::from_vec(&shape, data)?, self.lengths()))
+  }
+}
@@ -0,0 +1,83 @@
+use super::{prefixed, prefixed_buffers, LayerSummary, Module};
+use crate::autograd::Var;
+use crate::math::scalar::Float;
+use crate::onnx::{GraphBuilder, Value};
+use crate::tensor::Tensor;
+
+pub struct Sequential<T: Float = f64> {
+  layers: Vec<Box<dyn Module<T>>>
+}
+
+impl<T: Float> Sequential<T> {
+  pub fn new() -> Self {
+    Sequential { layers: Vec::new() }
+  }
+
+  pub fn push<M>(&mut self, layer: M)
+  where
+    M: Module<T> + 'static
+  {
+    self.layers.push(Box::new(layer));
+  }
+
+  pub fn layers(&self) -> &[Box<dyn Module<T>>] {
+    &self.layers
+  }
+
+  pub fn len(&self) -> usize {
+    self.layers.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.layers.is_empty()
+  }
+}
+
+impl<T: Float> Default for Sequential<T> {
+  fn default() -> Self {
+    Sequential::new()
+  }
+}
+
+impl<T: Float> Module<T> for Sequential<T> {
+  fn forward(&self, input: &Var<T>) -> Result<Var<T>, String> {
+    self.layers.iter().try_fold(input.clone(), |x, layer| layer.forward(&x))
+  }
+
+  fn named_parameters(&self) -> Vec<(String, Var<T>)> {
+    self
+      .layers
+      .iter()
+      .enumerate()
+      .flat_map(|(i, layer)| prefixed(&i.to_string(), layer.as_ref()))
+      .collect()
+  }
+
+  fn named_buffers(&self) -> Vec<(String, Var<T>)> {
+    self
+      .layers
+      .iter()
+      .enumerate()
+      .flat_map(|(i, layer)| prefixed_buffers(&i.to_string(), layer.as_ref()))
+      .collect()
+  }
+
+  fn summarize(&self, name: &str, input: &Tensor<T>, layers: &mut Vec<LayerSummary>) -> Result<Tensor<T>, String> {
+    self.layers.iter().enumerate().try_fold(input.clone(), |x, (i, layer)| {
+      let path = if name.is_empty() { i.to_string() } else { format!("{}.{}", name, i) };
+      layer.summarize(&path, &x, layers)
+    })
+  }
+
+  fn export_onnx(&self, graph: &mut GraphBuilder, input: &Value<T>) -> Result<Value<T>, String> {
+    self
+      .layers
+      .iter()
+      .enumerate()
+      .try_fold(input.clone(), |x, (i, layer)| graph.scoped(&i.to_string(), |graph| layer.export_onnx(graph, &x)))
+  }
+
+  fn children(&self) -> Vec<&dyn Module<T>> {
+    self.layers.iter().map(|layer| layer.as_ref()).collect()
+  }
+}
@@ -0,0 +1,104 @@
+use std::fmt;
+
+use super::Module;
+use crate::math::scalar::Float;
+use crate::tensor::Tensor;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LayerSummary {
+  pub name: String,
+  pub kind: String,
+  pub output_shape: Vec<usize>,
+  pub params: usize,
+  pub trainable_params: usize
+}
+
+impl LayerSummary {
+  pub fn new<T, M>(name: &str, module: &M, output: &Tensor<T>) -> Self
+  where
+    T: Float,
+    M: Module<T> + ?Sized
+  {
+    let params = module.parameters();
+    LayerSummary {
+      name: name.to_string(),
+      kind: module.name().to_string(),
+      output_shape: output.shape().to_vec(),
+      params: params.iter().map(|param| param.value().len()).sum(),
+      trainable_params: params.iter().filter(|param| param.requires_grad()).map(|param| param.value().len()).sum()
+    }
+  }
+
+  pub fn is_trainable(&self) -> bool {
+    self.trainable_params > 0
+  }
+
+  fn trainable_label(&self) -> &'static str {
+    match (self.params, self.trainable_params) {
+      (0, _) => "-",
+      (_, 0) => "no",
+      (all, trainable) if all == trainable => "yes",
+      _ => "partial"
+    }
+  }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ModelSummary {
+  pub layers: Vec<LayerSummary>,
+  pub input_shape: Vec<usize>,
+  pub total_params: usize,
+  pub trainable_params: usize
+}
+
+impl ModelSummary {
+  pub fn non_trainable_params(&self) -> usize {
+    self.total_params - self.trainable_params
+  }
+
+  pub fn output_shape(&self) -> &[usize] {
+    self.layers.last().map_or(&self.input_shape, |layer| &layer.output_shape)
+  }
+}
+
+impl fmt::Display for ModelSummary {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let header = ["Layer", "Type", "Output shape", "Params", "Trainable"];
+    let rows: Vec<[String; 5]> = self
+      .layers
+      .iter()
+      .map(|layer| {
+        [
+          layer.name.clone(),
+          layer.kind.clone(),
+          format!("{:?}", layer.output_shape),
+          layer.params.to_string(),
+          layer.trainable_label().to_string()
+        ]
+      })
+      .collect();
+
+    let mut widths = header.map(str::len);
+    for row in &rows {
+      for (width, cell) in widths.iter_mut().zip(row) {
+        *width = (*width).max(cell.len());
+      }
+    }
+    let rule = "-".repeat(widths.iter().sum::<usize>() + 2 * (widths.len() - 1));
+    let line = |f: &mut fmt::Formatter<'_>, cells: &[&str]| {
+      let padded: Vec<String> = cells.iter().zip(&widths).map(|(cell, &width)| format!("{:<width$}", cell)).collect();
+      writeln!(f, "{}", padded.join("  ").trim_end())
+    };
+
+    line(f, &header)?;
+    writeln!(f, "{}", rule)?;
+    for row in &rows {
+      line(f, &row.iter().map(String::as_str).collect::<Vec<_>>())?;
+    }
+    writeln!(f, "{}", rule)?;
+    writeln!(f, "Input shape: {:?}", self.input_shape)?;
+    writeln!(f, "Total params: {}", self.total_params)?;
+    writeln!(f, "Trainable params: {}", self.trainable_params)?;
+    write!(f, "Non-trainable params: {}", self.non_trainable_params())
+  }
+}
@@ -0,0 +1,280 @@
+use super::activations::{export_gelu, gelu, relu};
+use super::{prefixed, Dropout, LayerNorm, Linear, Module, MultiheadAttention};
+use crate::autograd::{no_grad, Var};
+use crate::math::scalar::Float;
+use crate::onnx::{GraphBuilder, Value};
+use crate::tensor::Tensor;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FeedForwardActivation {
+  #[default]
+  Relu,
+  Gelu
+}
+
+struct FeedForward<T: Float> {
+  linear1: Linear<T>,
+  linear2: Linear<T>,
+  dropout: Dropout
+}
+
+impl<T: Float> FeedForward<T> {
+  fn new(d_model: usize, dim_feedforward: usize, dropout: f64) -> Result<Self, String> {
+    Ok(FeedForward {
+      linear1: Linear::new(d_model, dim_feedforward, true),
+      linear2: Linear::new(dim_feedforward, d_model, true),
+      dropout: Dropout::new(dropout)?
+    })
+  }
+
+  fn forward(&self, x: &Var<T>, activation: FeedForwardActivation) -> Result<Var<T>, String> {
+    let hidden = self.linear1.forward(x)?;
+    let hidden = match activation {
+      FeedForwardActivation::Relu => relu(&hidden),
+      FeedForwardActivation::Gelu => gelu(&hidden)?
+    };
+    self.linear2.forward(&self.dropout.forward(&hidden)?)
+  }
+
+  fn export_onnx(&self, graph: &mut GraphBuilder, x: &Value<T>, activation: FeedForwardActivation) -> Result<Value<T>, String> {
+    let hidden = graph.scoped("linear1", |graph| self.linear1.export_onnx(graph, x))?;
+    let name = match activation {
+      FeedForwardActivation::Relu => graph.node("Relu", &[&hidden.name], Vec::new()),
+      FeedForwardActivation::Gelu => export_gelu(graph, &hidden.name)
+    };
+    let hidden = Var::constant(hidden.sample);
+    let sample = no_grad(|| match activation {
+      FeedForwardActivation::Relu => Ok(relu(&hidden)),
+      FeedForwardActivation::Gelu => gelu(&hidden)
+    })?;
+    graph.scoped("linear2", |graph| self.linear2.export_onnx(graph, &Value { name, sample: sample.value() }))
+  }
+
+  fn named_parameters(&self) -> Vec<(String, Var<T>)> {
+    let mut params = prefixed("linear1", &self.linear1);
+    params.extend(prefixed("linear2", &self.linear2));
+    params
+  }
+
+  fn children(&self) -> Vec<&dyn Module<T>> {
+    vec![&self.linear1, &self.linear2, &self.dropout]
+  }
+}
+
+fn residual<T, F>(x: &Var<T>, norm: &LayerNorm<T>, dropout: &Dropout, norm_first: bool, sublayer: F) -> Result<Var<T>, String>
+where
+  T: Float,
+  F: FnOnce(&Var<T>) -> Result<Var<T>, String>
+{
+  if norm_first {
+    x + &dropout.forward(&sublayer(&norm.forward(x)?)?)?
+  } else {
+    norm.forward(&(x + &dropout.forward(&sublayer(x)?)?)?)
+  }
+}
+
+fn export_residual<T, F>(graph: &mut GraphBuilder, x: &Value<T>, scope: &str, norm: &LayerNorm<T>, norm_first: bool, sublayer: F) -> Result<Value<T>, String>
+where
+  T: Float,
+  F: FnOnce(&mut GraphBuilder, &Value<T>) -> Result<Value<T>, String>
+{
+  let add = |graph: &mut GraphBuilder, out: Value<T>| -> Result<Value<T>, String> {
+    Ok(Value { name: graph.node("Add", &[&x.name, &out.name], Vec::new()), sample: (&x.sample + &out.sample)? })
+  };
+  if norm_first {
+    let normed = graph.scoped(scope, |graph| norm.export_onnx(graph, x))?;
+    let out = sublayer(graph, &normed)?;
+    add(graph, out)
+  } else {
+    let out = sublayer(graph, x)?;
+    let sum = add(graph, out)?;
+    graph.scoped(scope, |graph| norm.export_onnx(graph, &sum))
+  }
+}
+
+pub struct TransformerEncoderLayer<T: Float = f64> {
+  pub self_attn: MultiheadAttention<T>,
+  pub norm1: LayerNorm<T>,
+  pub norm2: LayerNorm<T>,
+  pub dropout: Dropout,
+  pub activation: FeedForwardActivation,
+  pub norm_first: bool,
+  feed_forward: FeedForward<T>
+}
+
+impl<T: Float> TransformerEncoderLayer<T> {
+  pub fn new(d_model: usize, num_heads: usize, dim_feedforward: usize, dropout: f64) -> Result<Self, String> {
+    Ok(TransformerEncoderLayer {
+      self_attn: MultiheadAttention::new(d_model, num_heads)?,
+      norm1: LayerNorm::new(&[d_model], true),
+      norm2: LayerNorm::new(&[d_model], true),
+      dropout: Dropout::new(dropout)?,
+      activation: FeedForwardActivation::Relu,
+      norm_first: false,
+      feed_forward: FeedForward::new(d_model, dim_feedforward, dropout)?
+    })
+  }
+
+  pub fn forward_with_mask(&self, src: &Var<T>, mask: Option<&Tensor<T>>) -> Result<Var<T>, String> {
+    let x = residual(src, &self.norm1, &self.dropout, self.norm_first, |x| self.self_attn.attend(x, x, x, mask))?;
+    residual(&x, &self.norm2, &self.dropout, self.norm_first, |x| self.feed_forward.forward(x, self.activation))
+  }
+}
+
+impl<T: Float> Module<T> for TransformerEncoderLayer<T> {
+  fn forward(&self, input: &Var<T>) -> Result<Var<T>, String> {
+    self.forward_with_mask(input, None)
+  }
+
+  fn export_onnx(&self, graph: &mut GraphBuilder, input: &Value<T>) -> Result<Value<T>, String> {
+    let x = export_residual(graph, input, "norm1", &self.norm1, self.norm_first, |graph, x| {
+      graph.scoped("self_attn", |graph| self.self_attn.export_onnx(graph, x))
+    })?;
+    export_residual(graph, &x, "norm2", &self.norm2, self.norm_first, |graph, x| {
+      self.feed_forward.export_onnx(graph, x, self.activation)
+    })
+  }
+
+  fn named_parameters(&self) -> Vec<(String, Var<T>)> {
+    let mut params = prefixed("self_attn", &self.self_attn);
+    params.extend(self.feed_forward.named_parameters());
+    params.extend(prefixed("norm1", &self.norm1));
+    params.extend(prefixed("norm2", &self.norm2));
+    params
+  }
+
+  fn children(&self) -> Vec<&dyn Module<T>> {
+    let mut children: Vec<&dyn Module<T>> = vec![&self.self_attn];
+    children.extend(self.feed_forward.children());
+    children.extend([&self.norm1 as &dyn Module<T>, &self.norm2, &self.dropout]);
+    children
+  }
+}
+
+pub struct TransformerDecoderLayer<T: Float = f64> {
+  pub self_attn: MultiheadAttention<T>,
+  pub cross_attn: MultiheadAttention<T>,
+  pub norm1: LayerNorm<T>,
+  pub norm2: LayerNorm<T>,
+  pub norm3: LayerNorm<T>,
+  pub dropout: Dropout,
+  pub activation: FeedForwardActivation,
+  pub norm_first: bool,
+  feed_forward: FeedForward<T>
+}
+
+impl<T: Float> TransformerDecoderLayer<T> {
+  pub fn new(d_model: usize, num_heads: usize, dim_feedforward: usize, dropout: f64) -> Result<Self, String> {
+    let mut self_attn = MultiheadAttention::new(d_model, num_heads)?;
+    self_attn.causal = true;
+    Ok(TransformerDecoderLayer {
+      self_attn,
+      cross_attn: MultiheadAttention::new(d_model, num_heads)?,
+      norm1: LayerNorm::new(&[d_model], true),
+      norm2: LayerNorm::new(&[d_model], true),
+      norm3: LayerNorm::new(&[d_model], true),
+      dropout: Dropout::new(dropout)?,
+      activation: FeedForwardActivation::Relu,
+      norm_first: false,
+      feed_forward: FeedForward::new(d_model, dim_feedforward, dropout)?
+    })
+  }
+
+  pub fn forward_with_memory(
+    &self,
+    target: &Var<T>,
+    memory: &Var<T>,
+    target_mask: Option<&Tensor<T>>,
+    memory_mask: Option<&Tensor<T>>
+  ) -> Result<Var<T>, String> {
+    let x = residual(target, &self.norm1, &self.dropout, self.norm_first, |x| self.self_attn.attend(x, x, x, target_mask))?;
+    let x = residual(&x, &self.norm2, &self.dropout, self.norm_first, |x| self.cross_attn.attend(x, memory, memory, memory_mask))?;
+    residual(&x, &self.norm3, &self.dropout, self.norm_first, |x| self.feed_forward.forward(x, self.activation))
+  }
+}
+
+impl<T: Float> Module<T> for TransformerDecoderLayer<T> {
+  fn forward(&self, _input: &Var<T>) -> Result<Var<T>, String> {
+    Err("TransformerDecoderLayer: a decoder needs encoder memory, use forward_with_memory".to_string())
+  }
+
+  fn named_parameters(&self) -> Vec<(String, Var<T>)> {
+    let mut params = prefixed("self_attn", &self.self_attn);
+    params.extend(prefixed("cross_attn", &self.cross_attn));
+    params.extend(self.feed_forward.named_parameters());
+    params.extend(prefixed("norm1", &self.norm1));
+    params.extend(prefixed("norm2", &self.norm2));
+    params.extend(prefixed("norm3", &self.norm3));
+    params
+  }
+
+  fn children(&self) -> Vec<&dyn Module<T>> {
+    let mut children: Vec<&dyn Module<T>> = vec![&self.self_attn, &self.cross_attn];
+    children.extend(self.feed_forward.children());
+    children.extend([&self.norm1 as &dyn Module<T>, &self.norm2, &self.norm3, &self.dropout]);
+    children
+  }
+}
+
+pub struct TransformerEncoder<T: Float = f64> {
+  pub layers: Vec<TransformerEncoderLayer<T>>,
+  pub norm: Option<LayerNorm<T>>
+}
+
+impl<T: Float> TransformerEncoder<T> {
+  pub fn new(num_layers: usize, d_model: usize, num_heads: usize, dim_feedforward: usize, dropout: f64) -> Result<Self, String> {
+    let layers = (0..num_layers)
+      .map(|_| TransformerEncoderLayer::new(d_model, num_heads, dim_feedforward, dropout))
+      .collect::<Result<Vec<_>, String>>()?;
+    Ok(TransformerEncoder { layers, norm: None })
+  }
+
+  pub fn from_layers(layers: Vec<TransformerEncoderLayer<T>>, norm: Option<LayerNorm<T>>) -> Self {
+    TransformerEncoder { layers, norm }
+  }
+
+  pub fn forward_with_mask(&self, src: &Var<T>, mask: Option<&Tensor<T>>) -> Result<Var<T>, String> {
+    let output = self.layers.iter().try_fold(src.clone(), |x, layer| layer.forward_with_mask(&x, mask))?;
+    match &self.norm {
+      Some(norm) => norm.forward(&output),
+      None => Ok(output)
+    }
+  }
+}
+
+impl<T: Float> Module<T> for TransformerEncoder<T> {
+  fn forward(&self, input: &Var<T>) -> Result<Var<T>, String> {
+    self.forward_with_mask(input, None)
+  }
+
+  fn export_onnx(&self, graph: &mut GraphBuilder, input: &Value<T>) -> Result<Value<T>, String> {
+    let output = self.layers.iter().enumerate().try_fold(input.clone(), |x, (i, layer)| {
+      graph.scoped(&format!("layers.{}", i), |graph| layer.export_onnx(graph, &x))
+    })?;
+    match &self.norm {
+      Some(norm) => graph.scoped("norm", |graph| norm.export_onnx(graph, &output)),
+      None => Ok(output)
+    }
+  }
+
+  fn named_parameters(&self) -> Vec<(String, Var<T>)> {
+    let mut params: Vec<_> = self
+      .layers
+      .iter()
+      .enumerate()
+      .flat_map(|(i, layer)| prefixed(&format!("layers.{}", i), layer))
+      .collect();
+    if let Some(norm) = &self.norm {
+      params.extend(prefixed("norm", norm));
+    }
+    params
+  }
+
+  fn children(&self) -> Vec<&dyn Module<T>> {
+    let mut children: Vec<&dyn Module<T>> = self.layers.iter().map(|layer| layer as &dyn Module<T>).collect();
+    if let Some(norm) = &self.norm {
+      children.push(norm);
+    }
+    children
+  }
+}
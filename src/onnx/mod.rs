@@ -0,0 +1,183 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use crate::math::scalar::Float;
+use crate::nn::Module;
+use crate::tensor::Tensor;
+
+pub mod proto;
+pub mod runtime;
+
+pub use runtime::{load, OnnxModel};
+
+use proto::{AttributeProto, AttributeValue, DataType, Dimension, GraphProto, ModelProto, NodeProto, TensorProto, ValueInfoProto};
+
+pub const OPSET_VERSION: i64 = 17;
+pub const IR_VERSION: i64 = 8;
+
+#[derive(Clone, Debug)]
+pub struct Value<T: Float = f64> {
+  pub name: String,
+  pub sample: Tensor<T>
+}
+
+impl<T: Float> Value<T> {
+  pub fn new(name: &str, sample: Tensor<T>) -> Self {
+    Value { name: name.to_string(), sample }
+  }
+}
+
+#[derive(Default)]
+pub struct GraphBuilder {
+  nodes: Vec<NodeProto>,
+  initializers: Vec<TensorProto>,
+  scopes: Vec<String>,
+  names: HashSet<String>
+}
+
+impl GraphBuilder {
+  pub fn new() -> Self {
+    GraphBuilder::default()
+  }
+
+  pub fn nodes(&self) -> &[NodeProto] {
+    &self.nodes
+  }
+
+  pub fn initializers(&self) -> &[TensorProto] {
+    &self.initializers
+  }
+
+  pub fn scoped<R, F>(&mut self, scope: &str, f: F) -> R
+  where
+    F: FnOnce(&mut GraphBuilder) -> R
+  {
+    self.scopes.push(scope.to_string());
+    let result = f(self);
+    self.scopes.pop();
+    result
+  }
+
+  pub fn fresh_name(&mut self, hint: &str) -> String {
+    let base = self.scopes.iter().map(String::as_str).chain([hint]).collect::<Vec<_>>().join(".");
+    let mut name = base.clone();
+    let mut suffix = 1;
+    while !self.names.insert(name.clone()) {
+      name = format!("{}_{}", base, suffix);
+      suffix += 1;
+    }
+    name
+  }
+
+  pub fn initializer<T: Float>(&mut self, hint: &str, tensor: &Tensor<T>) -> String {
+    let name = self.fresh_name(hint);
+    self.initializers.push(TensorProto {
+      name: name.clone(),
+      dims: tensor.shape().iter().map(|&d| d as i64).collect(),
+      data_type: DataType::Float,
+      raw_data: tensor.iter().flat_map(|x| (x.to_f64() as f32).to_le_bytes()).collect(),
+      ..TensorProto::default()
+    });
+    name
+  }
+
+  pub fn scalar(&mut self, hint: &str, value: f64) -> String {
+    self.initializer(hint, &Tensor::scalar(value))
+  }
+
+  pub fn ints(&mut self, hint: &str, values: &[i64]) -> String {
+    let name = self.fresh_name(hint);
+    self.initializers.push(TensorProto {
+      name: name.clone(),
+      dims: vec![values.len() as i64],
+      data_type: DataType::Int64,
+      int64_data: values.to_vec(),
+      ..TensorProto::default()
+    });
+    name
+  }
+
+  pub fn node(&mut self, op_type: &str, inputs: &[&str], attributes: Vec<AttributeProto>) -> String {
+    let output = self.fresh_name(op_type);
+    self.nodes.push(NodeProto {
+      name: output.clone(),
+      op_type: op_type.to_string(),
+      inputs: inputs.iter().map(|input| input.to_string()).collect(),
+      outputs: vec![output.clone()],
+      attributes
+    });
+    output
+  }
+
+  pub fn output<T, M>(&self, module: &M, name: String, input: &Value<T>) -> Result<Value<T>, String>
+  where
+    T: Float,
+    M: Module<T> + ?Sized
+  {
+    Ok(Value { name, sample: module.predict(&input.sample)? })
+  }
+}
+
+pub fn int_attr(name: &str, value: i64) -> AttributeProto {
+  AttributeProto::new(name, AttributeValue::Int(value))
+}
+
+pub fn ints_attr(name: &str, values: &[i64]) -> AttributeProto {
+  AttributeProto::new(name, AttributeValue::Ints(values.to_vec()))
+}
+
+pub fn float_attr(name: &str, value: f64) -> AttributeProto {
+  AttributeProto::new(name, AttributeValue::Float(value as f32))
+}
+
+fn value_info(name: &str, shape: &[usize]) -> ValueInfoProto {
+  let shape = shape
+    .iter()
+    .enumerate()
+    .map(|(axis, &dim)| if axis == 0 && shape.len() > 1 { Dimension::Symbolic("batch".to_string()) } else { Dimension::Fixed(dim as i64) })
+    .collect();
+  ValueInfoProto { name: name.to_string(), elem_type: DataType::Float, shape }
+}
+
+pub fn to_model<T, M>(model: &M, sample_input: &Tensor<T>) -> Result<ModelProto, String>
+where
+  T: Float,
+  M: Module<T> + ?Sized
+{
+  model.eval();
+  let mut graph = GraphBuilder::new();
+  graph.names.extend(["input".to_string(), "output".to_string()]);
+  let output = model.export_onnx(&mut graph, &Value::new("input", sample_input.clone()))?;
+  graph.nodes.push(NodeProto {
+    name: "output".to_string(),
+    op_type: "Identity".to_string(),
+    inputs: vec![output.name],
+    outputs: vec!["output".to_string()],
+    attributes: Vec::new()
+  });
+
+  Ok(ModelProto {
+    ir_version: IR_VERSION,
+    opset_version: OPSET_VERSION,
+    producer_name: env!("CARGO_PKG_NAME").to_string(),
+    producer_version: env!("CARGO_PKG_VERSION").to_string(),
+    graph: GraphProto {
+      name: model.name().to_string(),
+      nodes: graph.nodes,
+      initializers: graph.initializers,
+      inputs: vec![value_info("input", sample_input.shape())],
+      outputs: vec![value_info("output", output.sample.shape())]
+    }
+  })
+}
+
+pub fn export<T, M, P>(model: &M, sample_input: &Tensor<T>, path: P) -> Result<(), String>
+where
+  T: Float,
+  M: Module<T> + ?Sized,
+  P: AsRef<Path>
+{
+  let model = to_model(model, sample_input)?;
+  fs::write(path.as_ref(), model.encode()).map_err(|e| format!("Cannot write {}: {}", path.as_ref().display(), e))
+}
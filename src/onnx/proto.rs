@@ -0,0 +1,619 @@
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DataType {
+  #[default]
+  Float,
+  Int32,
+  Int64,
+  Double,
+  Other(i32)
+}
+
+impl DataType {
+  pub fn code(self) -> i32 {
+    match self {
+      DataType::Float => 1,
+      DataType::Int32 => 6,
+      DataType::Int64 => 7,
+      DataType::Double => 11,
+      DataType::Other(code) => code
+    }
+  }
+
+  pub fn from_code(code: i32) -> Self {
+    match code {
+      1 => DataType::Float,
+      6 => DataType::Int32,
+      7 => DataType::Int64,
+      11 => DataType::Double,
+      code => DataType::Other(code)
+    }
+  }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TensorProto {
+  pub name: String,
+  pub dims: Vec<i64>,
+  pub data_type: DataType,
+  pub float_data: Vec<f32>,
+  pub int32_data: Vec<i32>,
+  pub int64_data: Vec<i64>,
+  pub double_data: Vec<f64>,
+  pub raw_data: Vec<u8>
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum AttributeValue {
+  Float(f32),
+  Int(i64),
+  String(String),
+  Tensor(TensorProto),
+  Floats(Vec<f32>),
+  Ints(Vec<i64>)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AttributeProto {
+  pub name: String,
+  pub value: AttributeValue
+}
+
+impl AttributeProto {
+  pub fn new(name: &str, value: AttributeValue) -> Self {
+    AttributeProto { name: name.to_string(), value }
+  }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NodeProto {
+  pub name: String,
+  pub op_type: String,
+  pub inputs: Vec<String>,
+  pub outputs: Vec<String>,
+  pub attributes: Vec<AttributeProto>
+}
+
+impl NodeProto {
+  pub fn attribute(&self, name: &str) -> Option<&AttributeValue> {
+    self.attributes.iter().find(|attr| attr.name == name).map(|attr| &attr.value)
+  }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Dimension {
+  Fixed(i64),
+  Symbolic(String)
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ValueInfoProto {
+  pub name: String,
+  pub elem_type: DataType,
+  pub shape: Vec<Dimension>
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GraphProto {
+  pub name: String,
+  pub nodes: Vec<NodeProto>,
+  pub initializers: Vec<TensorProto>,
+  pub inputs: Vec<ValueInfoProto>,
+  pub outputs: Vec<ValueInfoProto>
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ModelProto {
+  pub ir_version: i64,
+  pub opset_version: i64,
+  pub producer_name: String,
+  pub producer_version: String,
+  pub graph: GraphProto
+}
+
+impl ModelProto {
+  pub fn encode(&self) -> Vec<u8> {
+    let mut writer = Writer::default();
+    self.encode_to(&mut writer);
+    writer.buf
+  }
+}
+
+#[derive(Default)]
+struct Writer {
+  buf: Vec<u8>
+}
+
+impl Writer {
+  fn varint(&mut self, mut value: u64) {
+    while value >= 0x80 {
+      self.buf.push((value as u8) | 0x80);
+      value >>= 7;
+    }
+    self.buf.push(value as u8);
+  }
+
+  fn key(&mut self, field: u32, wire: u8) {
+    self.varint(((field as u64) << 3) | wire as u64);
+  }
+
+  fn int(&mut self, field: u32, value: i64) {
+    self.key(field, 0);
+    self.varint(value as u64);
+  }
+
+  fn float(&mut self, field: u32, value: f32) {
+    self.key(field, 5);
+    self.buf.extend_from_slice(&value.to_le_bytes());
+  }
+
+  fn bytes(&mut self, field: u32, data: &[u8]) {
+    self.key(field, 2);
+    self.varint(data.len() as u64);
+    self.buf.extend_from_slice(data);
+  }
+
+  fn string(&mut self, field: u32, value: &str) {
+    if !value.is_empty() {
+      self.bytes(field, value.as_bytes());
+    }
+  }
+
+  fn message<M: Encode>(&mut self, field: u32, message: &M) {
+    let mut inner = Writer::default();
+    message.encode_to(&mut inner);
+    self.bytes(field, &inner.buf);
+  }
+
+  fn packed<V, F>(&mut self, field: u32, values: &[V], mut write: F)
+  where
+    F: FnMut(&mut Writer, &V)
+  {
+    if values.is_empty() {
+      return;
+    }
+    let mut inner = Writer::default();
+    values.iter().for_each(|value| write(&mut inner, value));
+    self.bytes(field, &inner.buf);
+  }
+}
+
+trait Encode {
+  fn encode_to(&self, writer: &mut Writer);
+}
+
+impl Encode for TensorProto {
+  fn encode_to(&self, writer: &mut Writer) {
+    for &dim in &self.dims {
+      writer.int(1, dim);
+    }
+    writer.int(2, self.data_type.code() as i64);
+    writer.packed(4, &self.float_data, |w, v| w.buf.extend_from_slice(&v.to_le_bytes()));
+    writer.packed(5, &self.int32_data, |w, &v| w.varint(v as i64 as u64));
+    writer.packed(7, &self.int64_data, |w, &v| w.varint(v as u64));
+    writer.string(8, &self.name);
+    if !self.raw_data.is_empty() {
+      writer.bytes(9, &self.raw_data);
+    }
+    writer.packed(10, &self.double_data, |w, v| w.buf.extend_from_slice(&v.to_le_bytes()));
+  }
+}
+
+impl Encode for AttributeProto {
+  fn encode_to(&self, writer: &mut Writer) {
+    writer.string(1, &self.name);
+    let kind = match &self.value {
+      AttributeValue::Float(value) => {
+        writer.float(2, *value);
+        1
+      }
+      AttributeValue::Int(value) => {
+        writer.int(3, *value);
+        2
+      }
+      AttributeValue::String(value) => {
+        writer.bytes(4, value.as_bytes());
+        3
+      }
+      AttributeValue::Tensor(tensor) => {
+        writer.message(5, tensor);
+        4
+      }
+      AttributeValue::Floats(values) => {
+        writer.packed(7, values, |w, v| w.buf.extend_from_slice(&v.to_le_bytes()));
+        6
+      }
+      AttributeValue::Ints(values) => {
+        writer.packed(8, values, |w, &v| w.varint(v as u64));
+        7
+      }
+    };
+    writer.int(20, kind);
+  }
+}
+
+impl Encode for NodeProto {
+  fn encode_to(&self, writer: &mut Writer) {
+    for input in &self.inputs {
+      writer.bytes(1, input.as_bytes());
+    }
+    for output in &self.outputs {
+      writer.bytes(2, output.as_bytes());
+    }
+    writer.string(3, &self.name);
+    writer.string(4, &self.op_type);
+    for attribute in &self.attributes {
+      writer.message(5, attribute);
+    }
+  }
+}
+
+struct TensorShape<'a>(&'a [Dimension]);
+
+impl Encode for Dimension {
+  fn encode_to(&self, writer: &mut Writer) {
+    match self {
+      Dimension::Fixed(value) => writer.int(1, *value),
+      Dimension::Symbolic(name) => writer.bytes(2, name.as_bytes())
+    }
+  }
+}
+
+impl Encode for TensorShape<'_> {
+  fn encode_to(&self, writer: &mut Writer) {
+    for dim in self.0 {
+      writer.message(1, dim);
+    }
+  }
+}
+
+struct TensorType<'a>(&'a ValueInfoProto);
+
+impl Encode for TensorType<'_> {
+  fn encode_to(&self, writer: &mut Writer) {
+    writer.int(1, self.0.elem_type.code() as i64);
+    writer.message(2, &TensorShape(&self.0.shape));
+  }
+}
+
+struct TypeProto<'a>(&'a ValueInfoProto);
+
+impl Encode for TypeProto<'_> {
+  fn encode_to(&self, writer: &mut Writer) {
+    writer.message(1, &TensorType(self.0));
+  }
+}
+
+impl Encode for ValueInfoProto {
+  fn encode_to(&self, writer: &mut Writer) {
+    writer.string(1, &self.name);
+    writer.message(2, &TypeProto(self));
+  }
+}
+
+impl Encode for GraphProto {
+  fn encode_to(&self, writer: &mut Writer) {
+    for node in &self.nodes {
+      writer.message(1, node);
+    }
+    writer.string(2, &self.name);
+    for initializer in &self.initializers {
+      writer.message(5, initializer);
+    }
+    for input in &self.inputs {
+      writer.message(11, input);
+    }
+    for output in &self.outputs {
+      writer.message(12, output);
+    }
+  }
+}
+
+struct OperatorSet(i64);
+
+impl Encode for OperatorSet {
+  fn encode_to(&self, writer: &mut Writer) {
+    writer.int(2, self.0);
+  }
+}
+
+impl Encode for ModelProto {
+  fn encode_to(&self, writer: &mut Writer) {
+    writer.int(1, self.ir_version);
+    writer.string(2, &self.producer_name);
+    writer.string(3, &self.producer_version);
+    writer.message(7, &self.graph);
+    writer.message(8, &OperatorSet(self.opset_version));
+  }
+}
+
+enum Field<'a> {
+  Varint(u64),
+  Fixed64([u8; 8]),
+  Bytes(&'a [u8]),
+  Fixed32([u8; 4])
+}
+
+struct Reader<'a> {
+  data: &'a [u8],
+  pos: usize
+}
+
+impl<'a> Reader<'a> {
+  fn new(data: &'a [u8]) -> Self {
+    Reader { data, pos: 0 }
+  }
+
+  fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+    let end = self.pos.checked_add(len).filter(|&end| end <= self.data.len()).ok_or("ONNX: truncated protobuf message")?;
+    let bytes = &self.data[self.pos..end];
+    self.pos = end;
+    Ok(bytes)
+  }
+
+  fn varint(&mut self) -> Result<u64, String> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+      let byte = self.take(1)?[0];
+      value |= ((byte & 0x7F) as u64) << shift;
+      if byte & 0x80 == 0 {
+        return Ok(value);
+      }
+    }
+    Err("ONNX: malformed varint".to_string())
+  }
+
+  fn field(&mut self) -> Result<Option<(u32, Field<'a>)>, String> {
+    if self.pos == self.data.len() {
+      return Ok(None);
+    }
+    let key = self.varint()?;
+    let field = match key & 7 {
+      0 => Field::Varint(self.varint()?),
+      1 => Field::Fixed64(self.take(8)?.try_into().expect("8 bytes")),
+      2 => {
+        let len = self.varint()? as usize;
+        Field::Bytes(self.take(len)?)
+      }
+      5 => Field::Fixed32(self.take(4)?.try_into().expect("4 bytes")),
+      wire => return Err(format!("ONNX: unsupported protobuf wire type {}", wire))
+    };
+    Ok(Some(((key >> 3) as u32, field)))
+  }
+}
+
+fn each_field<'a, F>(data: &'a [u8], mut f: F) -> Result<(), String>
+where
+  F: FnMut(u32, Field<'a>) -> Result<(), String>
+{
+  let mut reader = Reader::new(data);
+  while let Some((number, field)) = reader.field()? {
+    f(number, field)?;
+  }
+  Ok(())
+}
+
+fn int(field: Field<'_>) -> Result<i64, String> {
+  match field {
+    Field::Varint(value) => Ok(value as i64),
+    _ => Err("ONNX: expected a varint field".to_string())
+  }
+}
+
+fn string(field: Field<'_>) -> Result<String, String> {
+  match field {
+    Field::Bytes(bytes) => String::from_utf8(bytes.to_vec()).map_err(|_| "ONNX: invalid UTF-8 string".to_string()),
+    _ => Err("ONNX: expected a length-delimited field".to_string())
+  }
+}
+
+fn message<M: Decode>(field: Field<'_>) -> Result<M, String> {
+  match field {
+    Field::Bytes(bytes) => M::decode(bytes),
+    _ => Err("ONNX: expected an embedded message".to_string())
+  }
+}
+
+fn varints(field: Field<'_>, out: &mut Vec<i64>) -> Result<(), String> {
+  match field {
+    Field::Bytes(bytes) => {
+      let mut reader = Reader::new(bytes);
+      while reader.pos < bytes.len() {
+        out.push(reader.varint()? as i64);
+      }
+      Ok(())
+    }
+    field => int(field).map(|value| out.push(value))
+  }
+}
+
+fn floats(field: Field<'_>, out: &mut Vec<f32>) -> Result<(), String> {
+  match field {
+    Field::Bytes(bytes) if bytes.len() % 4 == 0 => {
+      out.extend(bytes.chunks_exact(4).map(|chunk| f32::from_le_bytes(chunk.try_into().expect("4 bytes"))));
+      Ok(())
+    }
+    Field::Fixed32(bytes) => {
+      out.push(f32::from_le_bytes(bytes));
+      Ok(())
+    }
+    _ => Err("ONNX: malformed float field".to_string())
+  }
+}
+
+fn doubles(field: Field<'_>, out: &mut Vec<f64>) -> Result<(), String> {
+  match field {
+    Field::Bytes(bytes) if bytes.len() % 8 == 0 => {
+      out.extend(bytes.chunks_exact(8).map(|chunk| f64::from_le_bytes(chunk.try_into().expect("8 bytes"))));
+      Ok(())
+    }
+    Field::Fixed64(bytes) => {
+      out.push(f64::from_le_bytes(bytes));
+      Ok(())
+    }
+    _ => Err("ONNX: malformed double field".to_string())
+  }
+}
+
+trait Decode: Sized {
+  fn decode(data: &[u8]) -> Result<Self, String>;
+}
+
+impl Decode for TensorProto {
+  fn decode(data: &[u8]) -> Result<Self, String> {
+    let mut tensor = TensorProto::default();
+    let mut int32_data = Vec::new();
+    each_field(data, |number, field| match number {
+      1 => varints(field, &mut tensor.dims),
+      2 => int(field).map(|code| tensor.data_type = DataType::from_code(code as i32)),
+      4 => floats(field, &mut tensor.float_data),
+      5 => varints(field, &mut int32_data),
+      7 => varints(field, &mut tensor.int64_data),
+      8 => string(field).map(|name| tensor.name = name),
+      9 => match field {
+        Field::Bytes(bytes) => {
+          tensor.raw_data = bytes.to_vec();
+          Ok(())
+        }
+        _ => Err("ONNX: malformed raw_data field".to_string())
+      },
+      10 => doubles(field, &mut tensor.double_data),
+      _ => Ok(())
+    })?;
+    tensor.int32_data = int32_data.into_iter().map(|v| v as i32).collect();
+    Ok(tensor)
+  }
+}
+
+struct RawAttribute(Option<AttributeProto>);
+
+impl Decode for RawAttribute {
+  fn decode(data: &[u8]) -> Result<Self, String> {
+    let (mut name, mut kind) = (String::new(), 0);
+    let (mut f, mut i, mut s, mut t) = (None, None, None, None);
+    let (mut fs, mut is) = (Vec::new(), Vec::new());
+    each_field(data, |number, field| match number {
+      1 => string(field).map(|value| name = value),
+      2 => {
+        let mut values = Vec::new();
+        floats(field, &mut values).map(|_| f = values.pop())
+      }
+      3 => int(field).map(|value| i = Some(value)),
+      4 => string(field).map(|value| s = Some(value)),
+      5 => message(field).map(|value| t = Some(value)),
+      7 => floats(field, &mut fs),
+      8 => varints(field, &mut is),
+      20 => int(field).map(|value| kind = value),
+      _ => Ok(())
+    })?;
+
+    let value = match kind {
+      1 => f.map(AttributeValue::Float),
+      2 => i.map(AttributeValue::Int),
+      3 => s.map(AttributeValue::String),
+      4 => t.map(AttributeValue::Tensor),
+      6 => Some(AttributeValue::Floats(fs)),
+      7 => Some(AttributeValue::Ints(is)),
+      _ => None
+    };
+    Ok(RawAttribute(value.map(|value| AttributeProto { name, value })))
+  }
+}
+
+impl Decode for NodeProto {
+  fn decode(data: &[u8]) -> Result<Self, String> {
+    let mut node = NodeProto::default();
+    each_field(data, |number, field| match number {
+      1 => string(field).map(|input| node.inputs.push(input)),
+      2 => string(field).map(|output| node.outputs.push(output)),
+      3 => string(field).map(|name| node.name = name),
+      4 => string(field).map(|op_type| node.op_type = op_type),
+      5 => message::<RawAttribute>(field).map(|attribute| node.attributes.extend(attribute.0)),
+      _ => Ok(())
+    })?;
+    Ok(node)
+  }
+}
+
+impl Decode for Dimension {
+  fn decode(data: &[u8]) -> Result<Self, String> {
+    let mut dim = Dimension::Symbolic(String::new());
+    each_field(data, |number, field| match number {
+      1 => int(field).map(|value| dim = Dimension::Fixed(value)),
+      2 => string(field).map(|name| dim = Dimension::Symbolic(name)),
+      _ => Ok(())
+    })?;
+    Ok(dim)
+  }
+}
+
+impl Decode for ValueInfoProto {
+  fn decode(data: &[u8]) -> Result<Self, String> {
+    let mut info = ValueInfoProto::default();
+    each_field(data, |number, field| match number {
+      1 => string(field).map(|name| info.name = name),
+      2 => match field {
+        Field::Bytes(type_proto) => each_field(type_proto, |number, field| match (number, field) {
+          (1, Field::Bytes(tensor_type)) => each_field(tensor_type, |number, field| match number {
+            1 => int(field).map(|code| info.elem_type = DataType::from_code(code as i32)),
+            2 => match field {
+              Field::Bytes(shape) => each_field(shape, |number, field| match number {
+                1 => message(field).map(|dim| info.shape.push(dim)),
+                _ => Ok(())
+              }),
+              _ => Err("ONNX: malformed tensor shape".to_string())
+            },
+            _ => Ok(())
+          }),
+          _ => Ok(())
+        }),
+        _ => Err("ONNX: malformed value type".to_string())
+      },
+      _ => Ok(())
+    })?;
+    Ok(info)
+  }
+}
+
+impl Decode for GraphProto {
+  fn decode(data: &[u8]) -> Result<Self, String> {
+    let mut graph = GraphProto::default();
+    each_field(data, |number, field| match number {
+      1 => message(field).map(|node| graph.nodes.push(node)),
+      2 => string(field).map(|name| graph.name = name),
+      5 => message(field).map(|tensor| graph.initializers.push(tensor)),
+      11 => message(field).map(|input| graph.inputs.push(input)),
+      12 => message(field).map(|output| graph.outputs.push(output)),
+      _ => Ok(())
+    })?;
+    Ok(graph)
+  }
+}
+
+impl ModelProto {
+  pub fn decode(data: &[u8]) -> Result<Self, String> {
+    let mut model = ModelProto::default();
+    each_field(data, |number, field| match number {
+      1 => int(field).map(|version| model.ir_version = version),
+      2 => string(field).map(|name| model.producer_name = name),
+      3 => string(field).map(|version| model.producer_version = version),
+      7 => message(field).map(|graph| model.graph = graph),
+      8 => match field {
+        Field::Bytes(opset) => {
+          let (mut domain, mut version) = (String::new(), 0);
+          each_field(opset, |number, field| match number {
+            1 => string(field).map(|value| domain = value),
+            2 => int(field).map(|value| version = value),
+            _ => Ok(())
+          })?;
+          if domain.is_empty() || domain == "ai.onnx" {
+            model.opset_version = version;
+          }
+          Ok(())
+        }
+        _ => Err("ONNX: malformed opset import".to_string())
+      },
+      _ => Ok(())
+    })?;
+    Ok(model)
+  }
+}
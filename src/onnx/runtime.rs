@@ -0,0 +1,616 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use super::proto::{AttributeValue, DataType, GraphProto, ModelProto, NodeProto, TensorProto};
+use crate::autograd::Var;
+use crate::math::scalar::Float;
+use crate::nn::Module;
+use crate::tensor::conv::Conv2dOptions;
+use crate::tensor::pad::PadMode;
+use crate::tensor::Tensor;
+
+pub const SUPPORTED_OPS: &[&str] = &[
+  "Abs",
+  "Add",
+  "AveragePool",
+  "BatchNormalization",
+  "Cast",
+  "Concat",
+  "Constant",
+  "Conv",
+  "Div",
+  "Dropout",
+  "Exp",
+  "Flatten",
+  "Gather",
+  "Gemm",
+  "GlobalAveragePool",
+  "Identity",
+  "LayerNormalization",
+  "LeakyRelu",
+  "LogSoftmax",
+  "MatMul",
+  "MaxPool",
+  "Mul",
+  "Neg",
+  "Pow",
+  "ReduceMean",
+  "Relu",
+  "Reshape",
+  "Shape",
+  "Sigmoid",
+  "Softmax",
+  "Sqrt",
+  "Squeeze",
+  "Sub",
+  "Tanh",
+  "Transpose",
+  "Unsqueeze"
+];
+
+#[derive(Clone, Debug)]
+enum Data<T> {
+  Float(Tensor<T>),
+  Int(Tensor<i64>)
+}
+
+impl<T: Float> Data<T> {
+  fn shape(&self) -> &[usize] {
+    match self {
+      Data::Float(tensor) => tensor.shape(),
+      Data::Int(tensor) => tensor.shape()
+    }
+  }
+
+  fn float(&self, op: &str) -> Result<&Tensor<T>, String> {
+    match self {
+      Data::Float(tensor) => Ok(tensor),
+      Data::Int(_) => Err(format!("{}: expected a floating point input, found an integer tensor", op))
+    }
+  }
+
+  fn ints(&self, op: &str) -> Result<Vec<i64>, String> {
+    match self {
+      Data::Int(tensor) => Ok(tensor.to_vec()),
+      Data::Float(_) => Err(format!("{}: expected an integer input, found a floating point tensor", op))
+    }
+  }
+
+  fn map_tensor<F, G>(&self, f: F, g: G) -> Result<Data<T>, String>
+  where
+    F: FnOnce(&Tensor<T>) -> Result<Tensor<T>, String>,
+    G: FnOnce(&Tensor<i64>) -> Result<Tensor<i64>, String>
+  {
+    Ok(match self {
+      Data::Float(tensor) => Data::Float(f(tensor)?),
+      Data::Int(tensor) => Data::Int(g(tensor)?)
+    })
+  }
+}
+
+fn usize_dims(dims: &[i64]) -> Result<Vec<usize>, String> {
+  dims.iter().map(|&d| usize::try_from(d).map_err(|_| format!("ONNX: invalid dimension {}", d))).collect()
+}
+
+fn tensor_data<T: Float>(proto: &TensorProto) -> Result<Data<T>, String> {
+  let shape = usize_dims(&proto.dims)?;
+  if shape.iter().try_fold(1usize, |len, &dim| len.checked_mul(dim)).is_none() {
+    return Err(format!("ONNX: invalid dimension {:?} for tensor '{}'", proto.dims, proto.name));
+  }
+  let raw = &proto.raw_data;
+  match proto.data_type {
+    DataType::Float if !raw.is_empty() => {
+      let values = raw.chunks_exact(4).map(|c| T::from_f64(f32::from_le_bytes(c.try_into().expect("4 bytes")) as f64)).collect();
+      Tensor::from_vec(&shape, values).map(Data::Float)
+    }
+    DataType::Float => Tensor::from_vec(&shape, proto.float_data.iter().map(|&x| T::from_f64(x as f64)).collect()).map(Data::Float),
+    DataType::Double if !raw.is_empty() => {
+      let values = raw.chunks_exact(8).map(|c| T::from_f64(f64::from_le_bytes(c.try_into().expect("8 bytes")))).collect();
+      Tensor::from_vec(&shape, values).map(Data::Float)
+    }
+    DataType::Double => Tensor::from_vec(&shape, proto.double_data.iter().map(|&x| T::from_f64(x)).collect()).map(Data::Float),
+    DataType::Int64 if !raw.is_empty() => {
+      let values = raw.chunks_exact(8).map(|c| i64::from_le_bytes(c.try_into().expect("8 bytes"))).collect();
+      Tensor::from_vec(&shape, values).map(Data::Int)
+    }
+    DataType::Int64 => Tensor::from_vec(&shape, proto.int64_data.clone()).map(Data::Int),
+    DataType::Int32 if !raw.is_empty() => {
+      let values = raw.chunks_exact(4).map(|c| i32::from_le_bytes(c.try_into().expect("4 bytes")) as i64).collect();
+      Tensor::from_vec(&shape, values).map(Data::Int)
+    }
+    DataType::Int32 => Tensor::from_vec(&shape, proto.int32_data.iter().map(|&x| x as i64).collect()).map(Data::Int),
+    DataType::Other(code) => Err(format!("ONNX: tensor '{}' has unsupported data type {}", proto.name, code))
+  }
+  .map_err(|e| format!("ONNX: cannot decode tensor '{}': {}", proto.name, e))
+}
+
+fn int_attr(node: &NodeProto, name: &str, default: i64) -> i64 {
+  match node.attribute(name) {
+    Some(AttributeValue::Int(value)) => *value,
+    _ => default
+  }
+}
+
+fn float_attr(node: &NodeProto, name: &str, default: f64) -> f64 {
+  match node.attribute(name) {
+    Some(AttributeValue::Float(value)) => *value as f64,
+    _ => default
+  }
+}
+
+fn ints_attr(node: &NodeProto, name: &str) -> Option<Vec<i64>> {
+  match node.attribute(name) {
+    Some(AttributeValue::Ints(values)) => Some(values.clone()),
+    _ => None
+  }
+}
+
+fn axis(op: &str, axis: i64, rank: usize) -> Result<usize, String> {
+  let normalized = if axis < 0 { axis + rank as i64 } else { axis };
+  usize::try_from(normalized).ok().filter(|&a| a < rank).ok_or_else(|| format!("{}: axis {} is out of range for rank {}", op, axis, rank))
+}
+
+fn pair(op: &str, values: &[i64]) -> Result<(usize, usize), String> {
+  match usize_dims(values)?.as_slice() {
+    &[a, b] => Ok((a, b)),
+    _ => Err(format!("{}: only 2-D spatial attributes are supported, found {:?}", op, values))
+  }
+}
+
+fn matmul<T: Float>(a: &Tensor<T>, b: &Tensor<T>) -> Result<Tensor<T>, String> {
+  let lhs = if a.ndim() == 1 { a.unsqueeze(0)? } else { a.clone() };
+  let rhs = if b.ndim() == 1 { b.unsqueeze(1)? } else { b.clone() };
+  let output = lhs.matmul(&rhs)?;
+  let mut shape = output.shape().to_vec();
+  if b.ndim() == 1 {
+    shape.pop();
+  }
+  if a.ndim() == 1 {
+    shape.remove(shape.len() - 1 - usize::from(b.ndim() != 1));
+  }
+  output.reshape(&shape)
+}
+
+fn spatial_options(node: &NodeProto, kernel: (usize, usize), input: &Tensor<impl Clone>) -> Result<(Conv2dOptions, [usize; 4]), String> {
+  let op = node.op_type.as_str();
+  match node.attribute("auto_pad") {
+    None => {}
+    Some(AttributeValue::String(mode)) if mode == "NOTSET" || mode == "VALID" => {}
+    Some(mode) => return Err(format!("{}: auto_pad {:?} is not supported", op, mode))
+  }
+  if int_attr(node, "ceil_mode", 0) != 0 {
+    return Err(format!("{}: ceil_mode is not supported", op));
+  }
+  if input.ndim() != 4 {
+    return Err(format!("{}: expected an [N, C, H, W] input, found shape {:?}", op, input.shape()));
+  }
+
+  let pads = usize_dims(&ints_attr(node, "pads").unwrap_or_else(|| vec![0; 4]))?;
+  let pads: [usize; 4] = pads.try_into().map_err(|_| format!("{}: expected 4 pads", op))?;
+  let options = Conv2dOptions {
+    kernel,
+    stride: pair(op, &ints_attr(node, "strides").unwrap_or_else(|| vec![1, 1]))?,
+    padding: (0, 0),
+    dilation: pair(op, &ints_attr(node, "dilations").unwrap_or_else(|| vec![1, 1]))?
+  };
+  Ok((options, pads))
+}
+
+fn padded<T: Float>(input: &Tensor<T>, pads: [usize; 4], value: T) -> Result<Tensor<T>, String> {
+  if pads == [0; 4] {
+    return Ok(input.clone());
+  }
+  input.pad(&[(0, 0), (0, 0), (pads[0], pads[2]), (pads[1], pads[3])], PadMode::Constant(value))
+}
+
+fn conv<T: Float>(node: &NodeProto, x: &Tensor<T>, w: &Tensor<T>, b: Option<&Tensor<T>>) -> Result<Tensor<T>, String> {
+  let kernel = match w.shape() {
+    &[_, _, kh, kw] => (kh, kw),
+    shape => return Err(format!("Conv: expected a 4-D weight, found shape {:?}", shape))
+  };
+  let (options, pads) = spatial_options(node, kernel, x)?;
+  let x = padded(x, pads, T::zero())?;
+  let groups = usize::try_from(int_attr(node, "group", 1)).map_err(|_| "Conv: invalid group".to_string())?;
+  if groups <= 1 {
+    return x.conv2d(w, b, &options);
+  }
+
+  let (in_per_group, out_per_group) = (w.shape()[1], w.shape()[0] / groups);
+  let outputs = (0..groups)
+    .map(|g| {
+      let b = b.map(|b| b.narrow(0, g * out_per_group, out_per_group)).transpose()?;
+      x.narrow(1, g * in_per_group, in_per_group)?.conv2d(&w.narrow(0, g * out_per_group, out_per_group)?, b.as_ref(), &options)
+    })
+    .collect::<Result<Vec<_>, String>>()?;
+  Tensor::cat(&outputs.iter().collect::<Vec<_>>(), 1)
+}
+
+fn pool<T: Float>(node: &NodeProto, x: &Tensor<T>) -> Result<Tensor<T>, String> {
+  let kernel = pair(&node.op_type, &ints_attr(node, "kernel_shape").ok_or_else(|| format!("{}: missing kernel_shape", node.op_type))?)?;
+  let (options, pads) = spatial_options(node, kernel, x)?;
+  if node.op_type == "MaxPool" {
+    return Ok(padded(x, pads, T::neg_infinity())?.max_pool2d(&options)?.0);
+  }
+  if pads != [0; 4] && int_attr(node, "count_include_pad", 0) == 0 {
+    return Err("AveragePool: padding without count_include_pad is not supported".to_string());
+  }
+  padded(x, pads, T::zero())?.avg_pool2d(&options)
+}
+
+fn channel_shape(rank: usize, channels: usize) -> Vec<usize> {
+  let mut shape = vec![1; rank];
+  shape[1] = channels;
+  shape
+}
+
+fn batch_norm<T: Float>(node: &NodeProto, inputs: &[&Tensor<T>]) -> Result<Tensor<T>, String> {
+  let (x, scale, bias, mean, var) = (inputs[0], inputs[1], inputs[2], inputs[3], inputs[4]);
+  if x.ndim() < 2 {
+    return Err(format!("BatchNormalization: expected an [N, C, ...] input, found shape {:?}", x.shape()));
+  }
+  let shape = channel_shape(x.ndim(), x.shape()[1]);
+  let eps = T::from_f64(float_attr(node, "epsilon", 1e-5));
+  let std = var.map(|&v| (v + eps).sqrt()).reshape(&shape)?;
+  let normalized = (&(x - &mean.reshape(&shape)?)? / &std)?;
+  &(&normalized * &scale.reshape(&shape)?)? + &bias.reshape(&shape)?
+}
+
+fn layer_norm<T: Float>(node: &NodeProto, x: &Tensor<T>, scale: &Tensor<T>, bias: Option<&Tensor<T>>) -> Result<Tensor<T>, String> {
+  let first = axis("LayerNormalization", int_attr(node, "axis", -1), x.ndim())?;
+  let axes: Vec<usize> = (first..x.ndim()).collect();
+  let eps = T::from_f64(float_attr(node, "epsilon", 1e-5));
+  let centered = (x - &x.mean(&axes, true)?)?;
+  let std = centered.map(|&v| v * v).mean(&axes, true)?.map(|&v| (v + eps).sqrt());
+  let output = (&(&centered / &std)? * scale)?;
+  match bias {
+    Some(bias) => &output + bias,
+    None => Ok(output)
+  }
+}
+
+fn reshape_target(op: &str, shape: &[usize], target: &[i64], allow_zero: bool) -> Result<Vec<usize>, String> {
+  let mut dims = Vec::with_capacity(target.len());
+  let mut inferred = None;
+  for (i, &dim) in target.iter().enumerate() {
+    match dim {
+      -1 if inferred.is_none() => {
+        inferred = Some(i);
+        dims.push(1);
+      }
+      0 if !allow_zero => dims.push(*shape.get(i).ok_or_else(|| format!("{}: cannot copy dimension {} of shape {:?}", op, i, shape))?),
+      dim => dims.push(usize::try_from(dim).map_err(|_| format!("{}: invalid target shape {:?}", op, target))?)
+    }
+  }
+  if let Some(i) = inferred {
+    let known: usize = dims.iter().product();
+    let total: usize = shape.iter().product();
+    if known == 0 || !total.is_multiple_of(known) {
+      return Err(format!("{}: cannot reshape {:?} into {:?}", op, shape, target));
+    }
+    dims[i] = total / known;
+  }
+  Ok(dims)
+}
+
+fn squeeze_shape(op: &str, shape: &[usize], axes: Option<Vec<i64>>) -> Result<Vec<usize>, String> {
+  let axes = match axes {
+    Some(axes) => axes.iter().map(|&a| axis(op, a, shape.len())).collect::<Result<Vec<_>, String>>()?,
+    None => (0..shape.len()).filter(|&a| shape[a] == 1).collect()
+  };
+  if let Some(&bad) = axes.iter().find(|&&a| shape[a] != 1) {
+    return Err(format!("{}: axis {} of shape {:?} is not 1", op, bad, shape));
+  }
+  Ok(shape.iter().enumerate().filter(|(a, _)| !axes.contains(a)).map(|(_, &d)| d).collect())
+}
+
+fn unsqueeze_shape(op: &str, shape: &[usize], axes: &[i64]) -> Result<Vec<usize>, String> {
+  let rank = shape.len() + axes.len();
+  let axes = axes.iter().map(|&a| axis(op, a, rank)).collect::<Result<Vec<_>, String>>()?;
+  let mut dims = shape.iter().copied();
+  (0..rank)
+    .map(|a| if axes.contains(&a) { Ok(1) } else { dims.next().ok_or_else(|| format!("{}: duplicate axes", op)) })
+    .collect()
+}
+
+fn gather<T: Float>(data: &Data<T>, indices: &Tensor<i64>, axis_attr: i64) -> Result<Data<T>, String> {
+  let gather_axis = axis("Gather", axis_attr, data.shape().len())?;
+  let size = data.shape()[gather_axis] as i64;
+  let positions = indices
+    .iter()
+    .map(|&i| {
+      let i = if i < 0 { i + size } else { i };
+      usize::try_from(i).ok().filter(|&i| (i as i64) < size).ok_or_else(|| format!("Gather: index {} is out of range", i))
+    })
+    .collect::<Result<Vec<_>, String>>()?;
+
+  let mut shape = data.shape()[..gather_axis].to_vec();
+  shape.extend(indices.shape());
+  shape.extend(&data.shape()[gather_axis + 1..]);
+  data.map_tensor(|t| t.take(&positions, gather_axis)?.reshape(&shape), |t| t.take(&positions, gather_axis)?.reshape(&shape))
+}
+
+fn constant<T: Float>(node: &NodeProto) -> Result<Data<T>, String> {
+  match node.attribute("value") {
+    Some(AttributeValue::Tensor(tensor)) => return tensor_data(tensor),
+    Some(_) => return Err("Constant: malformed value attribute".to_string()),
+    None => {}
+  }
+  match (node.attribute("value_float"), node.attribute("value_floats"), node.attribute("value_int"), node.attribute("value_ints")) {
+    (Some(AttributeValue::Float(x)), ..) => Ok(Data::Float(Tensor::scalar(T::from_f64(*x as f64)))),
+    (_, Some(AttributeValue::Floats(xs)), ..) => Tensor::from_vec(&[xs.len()], xs.iter().map(|&x| T::from_f64(x as f64)).collect()).map(Data::Float),
+    (_, _, Some(AttributeValue::Int(x)), _) => Ok(Data::Int(Tensor::scalar(*x))),
+    (_, _, _, Some(AttributeValue::Ints(xs))) => Tensor::from_vec(&[xs.len()], xs.clone()).map(Data::Int),
+    _ => Err(format!("Constant: node '{}' has no supported value attribute", node.name))
+  }
+}
+
+fn elementwise<T, F>(x: &Data<T>, op: &str, f: F) -> Result<Data<T>, String>
+where
+  T: Float,
+  F: Fn(T) -> T
+{
+  Ok(Data::Float(x.float(op)?.map(|&v| f(v))))
+}
+
+fn binary<T: Float>(op: &str, a: &Data<T>, b: &Data<T>) -> Result<Data<T>, String> {
+  if let (Data::Int(a), Data::Int(b)) = (a, b) {
+    let value = match op {
+      "Add" => a.zip_map(b, |x, y| x + y),
+      "Sub" => a.zip_map(b, |x, y| x - y),
+      "Mul" => a.zip_map(b, |x, y| x * y),
+      _ => a.zip_map(b, |x, y| x.checked_div(*y).unwrap_or(0))
+    };
+    return value.map(Data::Int);
+  }
+
+  let (a, b) = (a.float(op)?, b.float(op)?);
+  let value = match op {
+    "Add" => a + b,
+    "Sub" => a - b,
+    "Mul" => a * b,
+    "Div" => a / b,
+    _ => a.zip_map(b, |&x, &y| x.powf(y))
+  };
+  value.map(Data::Float)
+}
+
+fn gemm<T: Float>(node: &NodeProto, a: &Tensor<T>, b: &Tensor<T>, c: Option<&Tensor<T>>) -> Result<Tensor<T>, String> {
+  let a = if int_attr(node, "transA", 0) != 0 { a.transpose() } else { a.clone() };
+  let b = if int_attr(node, "transB", 0) != 0 { b.transpose() } else { b.clone() };
+  let (alpha, beta) = (T::from_f64(float_attr(node, "alpha", 1.0)), T::from_f64(float_attr(node, "beta", 1.0)));
+  let product = a.matmul(&b)?.map(|&v| v * alpha);
+  match c {
+    Some(c) => &product + &c.map(|&v| v * beta),
+    None => Ok(product)
+  }
+}
+
+fn run_node<T: Float>(node: &NodeProto, inputs: &[Option<&Data<T>>]) -> Result<Vec<Data<T>>, String> {
+  let op = node.op_type.as_str();
+  let input = |i: usize| inputs.get(i).copied().flatten().ok_or_else(|| format!("{}: node '{}' is missing input {}", op, node.name, i));
+  let float = |i: usize| input(i).and_then(|data| data.float(op));
+  let optional = |i: usize| inputs.get(i).copied().flatten().map(|data| data.float(op)).transpose();
+
+  let output = match op {
+    "Identity" | "Dropout" => input(0)?.clone(),
+    "Cast" => match (int_attr(node, "to", 1), input(0)?) {
+      (1 | 11, Data::Int(tensor)) => Data::Float(tensor.map(|&x| T::from_f64(x as f64))),
+      (6 | 7, Data::Float(tensor)) => Data::Int(tensor.map(|&x| x.to_f64() as i64)),
+      (1 | 11 | 6 | 7, data) => data.clone(),
+      (to, _) => return Err(format!("Cast: unsupported target type {}", to))
+    },
+    "Constant" => constant(node)?,
+    "Add" | "Sub" | "Mul" | "Div" | "Pow" => binary(op, input(0)?, input(1)?)?,
+    "Abs" => elementwise(input(0)?, op, |v| v.abs())?,
+    "Neg" => elementwise(input(0)?, op, |v| -v)?,
+    "Exp" => elementwise(input(0)?, op, |v| v.exp())?,
+    "Sqrt" => elementwise(input(0)?, op, |v| v.sqrt())?,
+    "Tanh" => elementwise(input(0)?, op, |v| v.tanh())?,
+    "Relu" => elementwise(input(0)?, op, |v| v.max(T::zero()))?,
+    "Sigmoid" => elementwise(input(0)?, op, |v| T::one() / (T::one() + (-v).exp()))?,
+    "LeakyRelu" => {
+      let alpha = T::from_f64(float_attr(node, "alpha", 0.01));
+      elementwise(input(0)?, op, |v| if v > T::zero() { v } else { v * alpha })?
+    }
+    "Softmax" | "LogSoftmax" => {
+      let x = float(0)?;
+      let axis = axis(op, int_attr(node, "axis", -1), x.ndim())?;
+      Data::Float(if op == "Softmax" { x.softmax(axis)? } else { x.log_softmax(axis)? })
+    }
+    "MatMul" => Data::Float(matmul(float(0)?, float(1)?)?),
+    "Gemm" => Data::Float(gemm(node, float(0)?, float(1)?, optional(2)?)?),
+    "Conv" => Data::Float(conv(node, float(0)?, float(1)?, optional(2)?)?),
+    "MaxPool" | "AveragePool" => Data::Float(pool(node, float(0)?)?),
+    "GlobalAveragePool" => {
+      let x = float(0)?;
+      let axes: Vec<usize> = (2..x.ndim()).collect();
+      Data::Float(x.mean(&axes, true)?)
+    }
+    "BatchNormalization" => Data::Float(batch_norm(node, &[float(0)?, float(1)?, float(2)?, float(3)?, float(4)?])?),
+    "LayerNormalization" => Data::Float(layer_norm(node, float(0)?, float(1)?, optional(2)?)?),
+    "ReduceMean" => {
+      let x = float(0)?;
+      let axes = match (ints_attr(node, "axes"), inputs.get(1).copied().flatten()) {
+        (Some(axes), _) => axes,
+        (None, Some(axes)) => axes.ints(op)?,
+        (None, None) => (0..x.ndim() as i64).collect()
+      };
+      let axes = axes.iter().map(|&a| axis(op, a, x.ndim())).collect::<Result<Vec<_>, String>>()?;
+      Data::Float(x.mean(&axes, int_attr(node, "keepdims", 1) != 0)?)
+    }
+    "Flatten" => {
+      let data = input(0)?;
+      let shape = data.shape();
+      let split = match int_attr(node, "axis", 1) {
+        a if a == shape.len() as i64 => shape.len(),
+        a => axis(op, a, shape.len())?
+      };
+      let target = [shape[..split].iter().product(), shape[split..].iter().product()];
+      data.map_tensor(|t| t.reshape(&target), |t| t.reshape(&target))?
+    }
+    "Reshape" => {
+      let data = input(0)?;
+      let target = reshape_target(op, data.shape(), &input(1)?.ints(op)?, int_attr(node, "allowzero", 0) != 0)?;
+      data.map_tensor(|t| t.contiguous().reshape(&target), |t| t.contiguous().reshape(&target))?
+    }
+    "Transpose" => {
+      let data = input(0)?;
+      let rank = data.shape().len();
+      let perm = match ints_attr(node, "perm") {
+        Some(perm) => perm.iter().map(|&a| axis(op, a, rank)).collect::<Result<Vec<_>, String>>()?,
+        None => (0..rank).rev().collect()
+      };
+      data.map_tensor(|t| Ok(t.permute(&perm)?.contiguous()), |t| Ok(t.permute(&perm)?.contiguous()))?
+    }
+    "Squeeze" | "Unsqueeze" => {
+      let data = input(0)?;
+      let axes = match (ints_attr(node, "axes"), inputs.get(1).copied().flatten()) {
+        (Some(axes), _) => Some(axes),
+        (None, Some(axes)) => Some(axes.ints(op)?),
+        (None, None) => None
+      };
+      let target = if op == "Squeeze" {
+        squeeze_shape(op, data.shape(), axes)?
+      } else {
+        unsqueeze_shape(op, data.shape(), &axes.ok_or("Unsqueeze: missing axes")?)?
+      };
+      data.map_tensor(|t| t.contiguous().reshape(&target), |t| t.contiguous().reshape(&target))?
+    }
+    "Concat" => {
+      let parts = (0..inputs.len()).map(input).collect::<Result<Vec<_>, String>>()?;
+      let axis = axis(op, int_attr(node, "axis", 0), parts.first().map_or(0, |p| p.shape().len()))?;
+      if parts.iter().all(|p| matches!(p, Data::Int(_))) {
+        let tensors = parts.iter().map(|p| match p {
+          Data::Int(t) => t,
+          Data::Float(_) => unreachable!("checked above")
+        });
+        Data::Int(Tensor::cat(&tensors.collect::<Vec<_>>(), axis)?)
+      } else {
+        let tensors = parts.iter().map(|p| p.float(op)).collect::<Result<Vec<_>, String>>()?;
+        Data::Float(Tensor::cat(&tensors, axis)?)
+      }
+    }
+    "Shape" => {
+      let shape = input(0)?.shape();
+      Data::Int(Tensor::from_vec(&[shape.len()], shape.iter().map(|&d| d as i64).collect())?)
+    }
+    "Gather" => {
+      let indices = match input(1)? {
+        Data::Int(indices) => indices.clone(),
+        Data::Float(indices) => indices.map(|&i| i.to_f64() as i64)
+      };
+      gather(input(0)?, &indices, int_attr(node, "axis", 0))?
+    }
+    _ => return Err(format!("ONNX: operator {} is not supported", op))
+  };
+  Ok(vec![output])
+}
+
+#[derive(Debug)]
+pub struct OnnxModel<T: Float = f64> {
+  model: ModelProto,
+  initializers: HashMap<String, Data<T>>
+}
+
+impl<T: Float> OnnxModel<T> {
+  pub fn from_proto(model: ModelProto) -> Result<Self, String> {
+    let unsupported: Vec<&str> = model
+      .graph
+      .nodes
+      .iter()
+      .map(|node| node.op_type.as_str())
+      .filter(|op| !SUPPORTED_OPS.contains(op))
+      .collect();
+    if !unsupported.is_empty() {
+      return Err(format!("ONNX: unsupported operators [{}]", unsupported.join(", ")));
+    }
+
+    let initializers = model
+      .graph
+      .initializers
+      .iter()
+      .map(|tensor| Ok((tensor.name.clone(), tensor_data(tensor)?)))
+      .collect::<Result<HashMap<_, _>, String>>()?;
+    Ok(OnnxModel { model, initializers })
+  }
+
+  pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+    OnnxModel::from_proto(ModelProto::decode(bytes)?)
+  }
+
+  pub fn proto(&self) -> &ModelProto {
+    &self.model
+  }
+
+  pub fn graph(&self) -> &GraphProto {
+    &self.model.graph
+  }
+
+  pub fn input_names(&self) -> Vec<&str> {
+    self
+      .graph()
+      .inputs
+      .iter()
+      .map(|input| input.name.as_str())
+      .filter(|name| !self.initializers.contains_key(*name))
+      .collect()
+  }
+
+  pub fn output_names(&self) -> Vec<&str> {
+    self.graph().outputs.iter().map(|output| output.name.as_str()).collect()
+  }
+
+  pub fn run(&self, inputs: &[(&str, Tensor<T>)]) -> Result<Vec<Tensor<T>>, String> {
+    let mut values: HashMap<&str, Data<T>> = self.initializers.iter().map(|(name, data)| (name.as_str(), data.clone())).collect();
+    for name in self.input_names() {
+      let (_, tensor) = inputs.iter().find(|(key, _)| *key == name).ok_or_else(|| format!("ONNX: missing graph input '{}'", name))?;
+      values.insert(name, Data::Float(tensor.clone()));
+    }
+
+    for node in &self.graph().nodes {
+      let args: Vec<Option<&Data<T>>> = node.inputs.iter().map(|name| if name.is_empty() { None } else { values.get(name.as_str()) }).collect();
+      if let Some((name, _)) = node.inputs.iter().zip(&args).find(|(name, arg)| !name.is_empty() && arg.is_none()) {
+        return Err(format!("ONNX: node '{}' reads '{}' before it is computed", node.name, name));
+      }
+      let outputs = run_node(node, &args).map_err(|e| format!("ONNX: node '{}' ({}): {}", node.name, node.op_type, e))?;
+      for (name, data) in node.outputs.iter().zip(outputs) {
+        values.insert(name, data);
+      }
+    }
+
+    self
+      .output_names()
+      .into_iter()
+      .map(|name| match values.remove(name) {
+        Some(Data::Float(tensor)) => Ok(tensor),
+        Some(Data::Int(tensor)) => Ok(tensor.map(|&v| T::from_f64(v as f64))),
+        None => Err(format!("ONNX: graph output '{}' was never computed", name))
+      })
+      .collect()
+  }
+}
+
+impl<T: Float> Module<T> for OnnxModel<T> {
+  fn forward(&self, input: &Var<T>) -> Result<Var<T>, String> {
+    let names = self.input_names();
+    if names.len() != 1 || self.output_names().len() != 1 {
+      return Err(format!(
+        "OnnxModel: forward needs a graph with one input and one output, found {} and {}",
+        names.len(),
+        self.output_names().len()
+      ));
+    }
+    let output = self.run(&[(names[0], input.value())])?.remove(0);
+    Ok(Var::constant(output))
+  }
+}
+
+pub fn load<T, P>(path: P) -> Result<OnnxModel<T>, String>
+where
+  T: Float,
+  P: AsRef<Path>
+{
+  let bytes = fs::read(path.as_ref()).map_err(|e| format!("Cannot read {}: {}", path.as_ref().display(), e))?;
+  OnnxModel::from_bytes(&bytes)
+}
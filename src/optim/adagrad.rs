@@ -0,0 +1,103 @@
+use super::{buffer_entries, flatten_groups, grad_of, read_buffers, read_scalar, scalar_entry, Optimizer, ParamGroup};
+use crate::autograd::Var;
+use crate::math::scalar::Float;
+use crate::tensor::Tensor;
+
+pub struct Adagrad<T: Float = f64> {
+  pub lr: f64,
+  pub lr_decay: f64,
+  pub eps: f64,
+  pub weight_decay: f64,
+  pub initial_accumulator_value: f64,
+  params: Vec<Var<T>>,
+  decay_overrides: Vec<Option<f64>>,
+  steps: usize,
+  sum: Vec<Option<Tensor<T>>>
+}
+
+impl<T: Float> Adagrad<T> {
+  pub fn new(params: Vec<Var<T>>, lr: f64) -> Self {
+    Adagrad::from_groups(vec![ParamGroup::new(params)], lr)
+  }
+
+  pub fn from_groups(groups: Vec<ParamGroup<T>>, lr: f64) -> Self {
+    let (params, decay_overrides) = flatten_groups(groups);
+    let sum = params.iter().map(|_| None).collect();
+    Adagrad {
+      lr,
+      lr_decay: 0.0,
+      eps: 1e-10,
+      weight_decay: 0.0,
+      initial_accumulator_value: 0.0,
+      params,
+      decay_overrides,
+      steps: 0,
+      sum
+    }
+  }
+}
+
+impl<T: Float> Optimizer<T> for Adagrad<T> {
+  fn params(&self) -> &[Var<T>] {
+    &self.params
+  }
+
+  fn step(&mut self) -> Result<(), String> {
+    self.steps += 1;
+    let lr = T::from_f64(self.lr / (1.0 + (self.steps - 1) as f64 * self.lr_decay));
+    let eps = T::from_f64(self.eps);
+    let initial = T::from_f64(self.initial_accumulator_value);
+
+    for ((param, sum), decay) in self.params.iter().zip(self.sum.iter_mut()).zip(&self.decay_overrides) {
+      let decay = T::from_f64(decay.unwrap_or(self.weight_decay));
+      let grad = match grad_of(param)? {
+        Some(grad) => grad,
+        None => continue
+      };
+
+      let sum = sum.get_or_insert_with(|| Tensor::full(grad.shape(), initial)).as_mut_slice();
+      param.update_value(|value| {
+        for ((p, s), &g) in value.as_mut_slice().iter_mut().zip(sum.iter_mut()).zip(grad.iter()) {
+          let g = g + decay * *p;
+          *s += g * g;
+          *p -= lr * g / (s.sqrt() + eps);
+        }
+      });
+    }
+
+    Ok(())
+  }
+
+  fn learning_rate(&self) -> f64 {
+    self.lr
+  }
+
+  fn set_learning_rate(&mut self, lr: f64) {
+    self.lr = lr;
+  }
+
+  fn state_dict(&self) -> Vec<(String, Tensor<T>)> {
+    let mut state = vec![
+      scalar_entry("step", self.steps as f64),
+      scalar_entry("lr", self.lr),
+      scalar_entry("lr_decay", self.lr_decay),
+      scalar_entry("eps", self.eps),
+      scalar_entry("weight_decay", self.weight_decay),
+      scalar_entry("initial_accumulator_value", self.initial_accumulator_value)
+    ];
+    state.extend(buffer_entries("sum", &self.sum));
+    state
+  }
+
+  fn load_state_dict(&mut self, state: &[(String, Tensor<T>)]) -> Result<(), String> {
+    let sum = read_buffers(state, "sum", &self.params)?;
+    self.steps = read_scalar(state, "step")?.round() as usize;
+    self.lr = read_scalar(state, "lr")?;
+    self.lr_decay = read_scalar(state, "lr_decay")?;
+    self.eps = read_scalar(state, "eps")?;
+    self.weight_decay = read_scalar(state, "weight_decay")?;
+    self.initial_accumulator_value = read_scalar(state, "initial_accumulator_value")?;
+    self.sum = sum;
+    Ok(())
+  }
+}
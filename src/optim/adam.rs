@@ -0,0 +1,186 @@
+use std::ops::{Deref, DerefMut};
+
+use super::{buffer_entries, flatten_groups, grad_of, read_buffers, read_scalar, scalar_entry, Optimizer, ParamGroup};
+use crate::autograd::Var;
+use crate::math::scalar::Float;
+use crate::tensor::Tensor;
+
+pub struct Adam<T: Float = f64> {
+  pub lr: f64,
+  pub beta1: f64,
+  pub beta2: f64,
+  pub eps: f64,
+  pub weight_decay: f64,
+  decoupled: bool,
+  params: Vec<Var<T>>,
+  decay_overrides: Vec<Option<f64>>,
+  steps: usize,
+  exp_avg: Vec<Option<Tensor<T>>>,
+  exp_avg_sq: Vec<Option<Tensor<T>>>
+}
+
+pub struct AdamW<T: Float = f64> {
+  inner: Adam<T>
+}
+
+impl<T: Float> Adam<T> {
+  pub fn new(params: Vec<Var<T>>, lr: f64) -> Self {
+    Adam::from_groups(vec![ParamGroup::new(params)], lr)
+  }
+
+  pub fn from_groups(groups: Vec<ParamGroup<T>>, lr: f64) -> Self {
+    let (params, decay_overrides) = flatten_groups(groups);
+    let slots = || params.iter().map(|_| None).collect();
+    Adam {
+      lr,
+      beta1: 0.9,
+      beta2: 0.999,
+      eps: 1e-8,
+      weight_decay: 0.0,
+      decoupled: false,
+      exp_avg: slots(),
+      exp_avg_sq: slots(),
+      params,
+      decay_overrides,
+      steps: 0
+    }
+  }
+
+  pub fn steps(&self) -> usize {
+    self.steps
+  }
+}
+
+impl<T: Float> AdamW<T> {
+  pub fn new(params: Vec<Var<T>>, lr: f64) -> Self {
+    AdamW::from_groups(vec![ParamGroup::new(params)], lr)
+  }
+
+  pub fn from_groups(groups: Vec<ParamGroup<T>>, lr: f64) -> Self {
+    let mut inner = Adam::from_groups(groups, lr);
+    inner.weight_decay = 0.01;
+    inner.decoupled = true;
+    AdamW { inner }
+  }
+}
+
+impl<T: Float> Deref for AdamW<T> {
+  type Target = Adam<T>;
+
+  fn deref(&self) -> &Adam<T> {
+    &self.inner
+  }
+}
+
+impl<T: Float> DerefMut for AdamW<T> {
+  fn deref_mut(&mut self) -> &mut Adam<T> {
+    &mut self.inner
+  }
+}
+
+impl<T: Float> Optimizer<T> for Adam<T> {
+  fn params(&self) -> &[Var<T>] {
+    &self.params
+  }
+
+  fn step(&mut self) -> Result<(), String> {
+    if !(0.0..1.0).contains(&self.beta1) || !(0.0..1.0).contains(&self.beta2) {
+      return Err(format!("Adam: betas must lie in [0, 1), found ({}, {})", self.beta1, self.beta2));
+    }
+
+    self.steps += 1;
+    let (b1, b2) = (T::from_f64(self.beta1), T::from_f64(self.beta2));
+    let correction1 = T::from_f64(1.0 - self.beta1.powi(self.steps as i32));
+    let correction2 = T::from_f64(1.0 - self.beta2.powi(self.steps as i32));
+    let (lr, eps) = (T::from_f64(self.lr), T::from_f64(self.eps));
+
+    for (i, param) in self.params.iter().enumerate() {
+      let decay = T::from_f64(self.decay_overrides[i].unwrap_or(self.weight_decay));
+      let shrink = T::one() - lr * decay;
+      let grad = match grad_of(param)? {
+        Some(grad) => grad,
+        None => continue
+      };
+
+      let m = self.exp_avg[i].get_or_insert_with(|| Tensor::zeros(grad.shape())).as_mut_slice();
+      let v = self.exp_avg_sq[i].get_or_insert_with(|| Tensor::zeros(grad.shape())).as_mut_slice();
+      param.update_value(|value| {
+        for (((p, m), v), &g) in value.as_mut_slice().iter_mut().zip(m.iter_mut()).zip(v.iter_mut()).zip(grad.iter()) {
+          let g = if self.decoupled {
+            *p *= shrink;
+            g
+          } else {
+            g + decay * *p
+          };
+          *m = b1 * *m + (T::one() - b1) * g;
+          *v = b2 * *v + (T::one() - b2) * g * g;
+          *p -= lr * (*m / correction1) / ((*v / correction2).sqrt() + eps);
+        }
+      });
+    }
+
+    Ok(())
+  }
+
+  fn learning_rate(&self) -> f64 {
+    self.lr
+  }
+
+  fn set_learning_rate(&mut self, lr: f64) {
+    self.lr = lr;
+  }
+
+  fn state_dict(&self) -> Vec<(String, Tensor<T>)> {
+    let mut state = vec![
+      scalar_entry("step", self.steps as f64),
+      scalar_entry("lr", self.lr),
+      scalar_entry("beta1", self.beta1),
+      scalar_entry("beta2", self.beta2),
+      scalar_entry("eps", self.eps),
+      scalar_entry("weight_decay", self.weight_decay)
+    ];
+    state.extend(buffer_entries("exp_avg", &self.exp_avg));
+    state.extend(buffer_entries("exp_avg_sq", &self.exp_avg_sq));
+    state
+  }
+
+  fn load_state_dict(&mut self, state: &[(String, Tensor<T>)]) -> Result<(), String> {
+    let exp_avg = read_buffers(state, "exp_avg", &self.params)?;
+    let exp_avg_sq = read_buffers(state, "exp_avg_sq", &self.params)?;
+    self.steps = read_scalar(state, "step")?.round() as usize;
+    self.lr = read_scalar(state, "lr")?;
+    self.beta1 = read_scalar(state, "beta1")?;
+    self.beta2 = read_scalar(state, "beta2")?;
+    self.eps = read_scalar(state, "eps")?;
+    self.weight_decay = read_scalar(state, "weight_decay")?;
+    self.exp_avg = exp_avg;
+    self.exp_avg_sq = exp_avg_sq;
+    Ok(())
+  }
+}
+
+impl<T: Float> Optimizer<T> for AdamW<T> {
+  fn params(&self) -> &[Var<T>] {
+    self.inner.params()
+  }
+
+  fn step(&mut self) -> Result<(), String> {
+    self.inner.step()
+  }
+
+  fn learning_rate(&self) -> f64 {
+    self.inner.learning_rate()
+  }
+
+  fn set_learning_rate(&mut self, lr: f64) {
+    self.inner.set_learning_rate(lr)
+  }
+
+  fn state_dict(&self) -> Vec<(String, Tensor<T>)> {
+    self.inner.state_dict()
+  }
+
+  fn load_state_dict(&mut self, state: &[(String, Tensor<T>)]) -> Result<(), String> {
+    self.inner.load_state_dict(state)
+  }
+}
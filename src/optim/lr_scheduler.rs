@@ -0,0 +1,208 @@
+use std::f64::consts::PI;
+
+use super::Optimizer;
+use crate::math::scalar::Float;
+
+pub trait LrScheduler {
+  fn lr(&self) -> f64;
+
+  fn advance(&mut self, metric: Option<f64>) -> Result<(), String>;
+
+  fn step<T: Float>(&mut self, optimizer: &mut dyn Optimizer<T>) -> Result<(), String>
+  where
+    Self: Sized
+  {
+    self.advance(None)?;
+    optimizer.set_learning_rate(self.lr());
+    Ok(())
+  }
+
+  fn step_with_metric<T: Float>(&mut self, optimizer: &mut dyn Optimizer<T>, metric: f64) -> Result<(), String>
+  where
+    Self: Sized
+  {
+    self.advance(Some(metric))?;
+    optimizer.set_learning_rate(self.lr());
+    Ok(())
+  }
+}
+
+pub struct StepLR {
+  pub base_lr: f64,
+  pub step_size: usize,
+  pub gamma: f64,
+  epoch: usize
+}
+
+impl StepLR {
+  pub fn new(base_lr: f64, step_size: usize, gamma: f64) -> Self {
+    StepLR { base_lr, step_size, gamma, epoch: 0 }
+  }
+}
+
+impl LrScheduler for StepLR {
+  fn lr(&self) -> f64 {
+    self.base_lr * self.gamma.powi((self.epoch / self.step_size.max(1)) as i32)
+  }
+
+  fn advance(&mut self, _metric: Option<f64>) -> Result<(), String> {
+    self.epoch += 1;
+    Ok(())
+  }
+}
+
+pub struct ExponentialLR {
+  pub base_lr: f64,
+  pub gamma: f64,
+  epoch: usize
+}
+
+impl ExponentialLR {
+  pub fn new(base_lr: f64, gamma: f64) -> Self {
+    ExponentialLR { base_lr, gamma, epoch: 0 }
+  }
+}
+
+impl LrScheduler for ExponentialLR {
+  fn lr(&self) -> f64 {
+    self.base_lr * self.gamma.powi(self.epoch as i32)
+  }
+
+  fn advance(&mut self, _metric: Option<f64>) -> Result<(), String> {
+    self.epoch += 1;
+    Ok(())
+  }
+}
+
+pub struct CosineAnnealingLR {
+  pub base_lr: f64,
+  pub t_max: usize,
+  pub eta_min: f64,
+  epoch: usize
+}
+
+impl CosineAnnealingLR {
+  pub fn new(base_lr: f64, t_max: usize, eta_min: f64) -> Self {
+    CosineAnnealingLR { base_lr, t_max, eta_min, epoch: 0 }
+  }
+}
+
+impl LrScheduler for CosineAnnealingLR {
+  fn lr(&self) -> f64 {
+    let progress = self.epoch.min(self.t_max) as f64 / self.t_max.max(1) as f64;
+    self.eta_min + (self.base_lr - self.eta_min) * (1.0 + (PI * progress).cos()) / 2.0
+  }
+
+  fn advance(&mut self, _metric: Option<f64>) -> Result<(), String> {
+    self.epoch += 1;
+    Ok(())
+  }
+}
+
+pub struct WarmupLR {
+  pub warmup_steps: usize,
+  pub start_factor: f64,
+  inner: Box<dyn LrScheduler>,
+  epoch: usize
+}
+
+impl WarmupLR {
+  pub fn new(inner: Box<dyn LrScheduler>, warmup_steps: usize, start_factor: f64) -> Self {
+    WarmupLR {
+      warmup_steps,
+      start_factor,
+      inner,
+      epoch: 0
+    }
+  }
+}
+
+impl LrScheduler for WarmupLR {
+  fn lr(&self) -> f64 {
+    if self.epoch >= self.warmup_steps {
+      return self.inner.lr();
+    }
+    let progress = self.epoch as f64 / self.warmup_steps as f64;
+    self.inner.lr() * (self.start_factor + (1.0 - self.start_factor) * progress)
+  }
+
+  fn advance(&mut self, metric: Option<f64>) -> Result<(), String> {
+    if self.epoch >= self.warmup_steps {
+      self.inner.advance(metric)?;
+    }
+    self.epoch += 1;
+    Ok(())
+  }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PlateauMode {
+  #[default]
+  Min,
+  Max
+}
+
+pub struct ReduceLROnPlateau {
+  pub mode: PlateauMode,
+  pub factor: f64,
+  pub patience: usize,
+  pub threshold: f64,
+  pub cooldown: usize,
+  pub min_lr: f64,
+  lr: f64,
+  best: Option<f64>,
+  bad_epochs: usize,
+  cooldown_left: usize
+}
+
+impl ReduceLROnPlateau {
+  pub fn new(base_lr: f64) -> Self {
+    ReduceLROnPlateau {
+      mode: PlateauMode::Min,
+      factor: 0.1,
+      patience: 10,
+      threshold: 1e-4,
+      cooldown: 0,
+      min_lr: 0.0,
+      lr: base_lr,
+      best: None,
+      bad_epochs: 0,
+      cooldown_left: 0
+    }
+  }
+
+  fn improves(&self, metric: f64, best: f64) -> bool {
+    match self.mode {
+      PlateauMode::Min => metric < best * (1.0 - self.threshold.copysign(best)),
+      PlateauMode::Max => metric > best * (1.0 + self.threshold.copysign(best))
+    }
+  }
+}
+
+impl LrScheduler for ReduceLROnPlateau {
+  fn lr(&self) -> f64 {
+    self.lr
+  }
+
+  fn advance(&mut self, metric: Option<f64>) -> Result<(), String> {
+    let metric = metric.ok_or_else(|| "ReduceLROnPlateau: a metric is required to step".to_string())?;
+    match self.best {
+      Some(best) if !self.improves(metric, best) => self.bad_epochs += 1,
+      _ => {
+        self.best = Some(metric);
+        self.bad_epochs = 0;
+      }
+    }
+
+    if self.cooldown_left > 0 {
+      self.cooldown_left -= 1;
+      self.bad_epochs = 0;
+    }
+    if self.bad_epochs > self.patience {
+      self.lr = (self.lr * self.factor).max(self.min_lr);
+      self.cooldown_left = self.cooldown;
+      self.bad_epochs = 0;
+    }
+    Ok(())
+  }
+}
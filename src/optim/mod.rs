@@ -0,0 +1,112 @@
+use crate::autograd::Var;
+use crate::math::scalar::Float;
+use crate::tensor::Tensor;
+
+pub mod adagrad;
+pub mod adam;
+pub mod lr_scheduler;
+pub mod rmsprop;
+pub mod sgd;
+
+pub use adagrad::Adagrad;
+pub use adam::{Adam, AdamW};
+pub use rmsprop::RMSProp;
+pub use sgd::SGD;
+
+pub struct ParamGroup<T: Float = f64> {
+  pub params: Vec<Var<T>>,
+  pub weight_decay: Option<f64>
+}
+
+impl<T: Float> ParamGroup<T> {
+  pub fn new(params: Vec<Var<T>>) -> Self {
+    ParamGroup { params, weight_decay: None }
+  }
+
+  pub fn with_weight_decay(params: Vec<Var<T>>, weight_decay: f64) -> Self {
+    ParamGroup { params, weight_decay: Some(weight_decay) }
+  }
+}
+
+pub(crate) fn flatten_groups<T: Float>(groups: Vec<ParamGroup<T>>) -> (Vec<Var<T>>, Vec<Option<f64>>) {
+  groups
+    .into_iter()
+    .flat_map(|group| {
+      let decay = group.weight_decay;
+      group.params.into_iter().map(move |param| (param, decay))
+    })
+    .unzip()
+}
+
+pub trait Optimizer<T: Float = f64> {
+  fn params(&self) -> &[Var<T>];
+
+  fn step(&mut self) -> Result<(), String>;
+
+  fn learning_rate(&self) -> f64;
+
+  fn set_learning_rate(&mut self, lr: f64);
+
+  fn state_dict(&self) -> Vec<(String, Tensor<T>)>;
+
+  fn load_state_dict(&mut self, state: &[(String, Tensor<T>)]) -> Result<(), String>;
+
+  fn zero_grad(&self) {
+    for param in self.params() {
+      param.zero_grad();
+    }
+  }
+}
+
+pub(crate) fn grad_of<T: Float>(param: &Var<T>) -> Result<Option<Tensor<T>>, String> {
+  if !param.requires_grad() {
+    return Ok(None);
+  }
+  match param.grad() {
+    Some(grad) if grad.shape() != param.shape() => {
+      Err(format!("Gradient shape {:?} does not match parameter shape {:?}", grad.shape(), param.shape()))
+    }
+    grad => Ok(grad)
+  }
+}
+
+pub(crate) fn scalar_entry<T: Float>(name: &str, value: f64) -> (String, Tensor<T>) {
+  (name.to_string(), Tensor::scalar(T::from_f64(value)))
+}
+
+pub(crate) fn buffer_entries<T: Float>(prefix: &str, buffers: &[Option<Tensor<T>>]) -> Vec<(String, Tensor<T>)> {
+  buffers
+    .iter()
+    .enumerate()
+    .filter_map(|(i, buffer)| buffer.as_ref().map(|b| (format!("{}.{}", prefix, i), b.clone())))
+    .collect()
+}
+
+fn find<'a, T>(state: &'a [(String, Tensor<T>)], name: &str) -> Option<&'a Tensor<T>> {
+  state.iter().find(|(key, _)| key == name).map(|(_, tensor)| tensor)
+}
+
+pub(crate) fn read_scalar<T: Float>(state: &[(String, Tensor<T>)], name: &str) -> Result<f64, String> {
+  match find(state, name) {
+    Some(tensor) if tensor.len() == 1 => Ok(tensor.iter().next().expect("one element").to_f64()),
+    Some(tensor) => Err(format!("Optimizer state '{}' should be a scalar, found shape {:?}", name, tensor.shape())),
+    None => Err(format!("Optimizer state is missing '{}'", name))
+  }
+}
+
+pub(crate) fn read_buffers<T: Float>(state: &[(String, Tensor<T>)], prefix: &str, params: &[Var<T>]) -> Result<Vec<Option<Tensor<T>>>, String> {
+  params
+    .iter()
+    .enumerate()
+    .map(|(i, param)| match find(state, &format!("{}.{}", prefix, i)) {
+      Some(buffer) if buffer.shape() != param.shape() => Err(format!(
+        "Optimizer state '{}.{}' has shape {:?} but the parameter has shape {:?}",
+        prefix,
+        i,
+        buffer.shape(),
+        param.shape()
+      )),
+      buffer => Ok(buffer.cloned())
+    })
+    .collect()
+}
@@ -0,0 +1,129 @@
+use super::{buffer_entries, flatten_groups, grad_of, read_buffers, read_scalar, scalar_entry, Optimizer, ParamGroup};
+use crate::autograd::Var;
+use crate::math::scalar::Float;
+use crate::tensor::Tensor;
+
+pub struct RMSProp<T: Float = f64> {
+  pub lr: f64,
+  pub alpha: f64,
+  pub eps: f64,
+  pub momentum: f64,
+  pub centered: bool,
+  pub weight_decay: f64,
+  params: Vec<Var<T>>,
+  decay_overrides: Vec<Option<f64>>,
+  square_avg: Vec<Option<Tensor<T>>>,
+  grad_avg: Vec<Option<Tensor<T>>>,
+  momentum_buffer: Vec<Option<Tensor<T>>>
+}
+
+impl<T: Float> RMSProp<T> {
+  pub fn new(params: Vec<Var<T>>, lr: f64) -> Self {
+    RMSProp::from_groups(vec![ParamGroup::new(params)], lr)
+  }
+
+  pub fn from_groups(groups: Vec<ParamGroup<T>>, lr: f64) -> Self {
+    let (params, decay_overrides) = flatten_groups(groups);
+    let slots = || params.iter().map(|_| None).collect();
+    RMSProp {
+      lr,
+      alpha: 0.99,
+      eps: 1e-8,
+      momentum: 0.0,
+      centered: false,
+      weight_decay: 0.0,
+      square_avg: slots(),
+      grad_avg: slots(),
+      momentum_buffer: slots(),
+      params,
+      decay_overrides
+    }
+  }
+}
+
+impl<T: Float> Optimizer<T> for RMSProp<T> {
+  fn params(&self) -> &[Var<T>] {
+    &self.params
+  }
+
+  fn step(&mut self) -> Result<(), String> {
+    let (lr, alpha, eps) = (T::from_f64(self.lr), T::from_f64(self.alpha), T::from_f64(self.eps));
+    let momentum = T::from_f64(self.momentum);
+
+    for (i, param) in self.params.iter().enumerate() {
+      let decay = T::from_f64(self.decay_overrides[i].unwrap_or(self.weight_decay));
+      let grad = match grad_of(param)? {
+        Some(grad) => grad,
+        None => continue
+      };
+
+      let zeros = || Tensor::zeros(grad.shape());
+      let square_avg = self.square_avg[i].get_or_insert_with(zeros).as_mut_slice();
+      let mut grad_avg = self.centered.then(|| self.grad_avg[i].get_or_insert_with(zeros).as_mut_slice());
+      let mut buffer = (self.momentum > 0.0).then(|| self.momentum_buffer[i].get_or_insert_with(zeros).as_mut_slice());
+
+      param.update_value(|value| {
+        for (j, (p, &g)) in value.as_mut_slice().iter_mut().zip(grad.iter()).enumerate() {
+          let g = g + decay * *p;
+          square_avg[j] = alpha * square_avg[j] + (T::one() - alpha) * g * g;
+          let variance = match grad_avg.as_deref_mut() {
+            Some(grad_avg) => {
+              grad_avg[j] = alpha * grad_avg[j] + (T::one() - alpha) * g;
+              square_avg[j] - grad_avg[j] * grad_avg[j]
+            }
+            None => square_avg[j]
+          };
+          let update = g / (variance.sqrt() + eps);
+          match buffer.as_deref_mut() {
+            Some(buffer) => {
+              buffer[j] = momentum * buffer[j] + update;
+              *p -= lr * buffer[j];
+            }
+            None => *p -= lr * update
+          }
+        }
+      });
+    }
+
+    Ok(())
+  }
+
+  fn learning_rate(&self) -> f64 {
+    self.lr
+  }
+
+  fn set_learning_rate(&mut self, lr: f64) {
+    self.lr = lr;
+  }
+
+  fn state_dict(&self) -> Vec<(String, Tensor<T>)> {
+    let mut state = vec![
+      scalar_entry("lr", self.lr),
+      scalar_entry("alpha", self.alpha),
+      scalar_entry("eps", self.eps),
+      scalar_entry("momentum", self.momentum),
+      scalar_entry("centered", if self.centered { 1.0 } else { 0.0 }),
+      scalar_entry("weight_decay", self.weight_decay)
+    ];
+    state.extend(buffer_entries("square_avg", &self.square_avg));
+    state.extend(buffer_entries("grad_avg", &self.grad_avg));
+    state.extend(buffer_entries("momentum_buffer", &self.momentum_buffer));
+    state
+  }
+
+  fn load_state_dict(&mut self, state: &[(String, Tensor<T>)]) -> Result<(), String> {
+    let square_avg = read_buffers(state, "square_avg", &self.params)?;
+    let grad_avg = read_buffers(state, "grad_avg", &self.params)?;
+    let momentum_buffer = read_buffers(state, "momentum_buffer", &self.params)?;
+    self.lr = read_scalar(state, "lr")?;
+    self.alpha = read_scalar(state, "alpha")?;
+    self.eps = read_scalar(state, "eps")?;
+    self.momentum = read_scalar(state, "momentum")?;
+    self.centered = read_scalar(state, "centered")? != 0.0;
+    self.weight_decay = read_scalar(state, "weight_decay")?;
+    self.square_avg = square_avg;
+    self.grad_avg = grad_avg;
+    self.momentum_buffer = momentum_buffer;
+    Ok(())
+  }
+}
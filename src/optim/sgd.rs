@@ -0,0 +1,104 @@
+use super::{buffer_entries, flatten_groups, grad_of, read_buffers, read_scalar, scalar_entry, Optimizer, ParamGroup};
+use crate::autograd::Var;
+use crate::math::scalar::Float;
+use crate::tensor::Tensor;
+
+pub struct SGD<T: Float = f64> {
+  pub lr: f64,
+  pub momentum: f64,
+  pub nesterov: bool,
+  pub weight_decay: f64,
+  params: Vec<Var<T>>,
+  decay_overrides: Vec<Option<f64>>,
+  velocity: Vec<Option<Tensor<T>>>
+}
+
+impl<T: Float> SGD<T> {
+  pub fn new(params: Vec<Var<T>>, lr: f64) -> Self {
+    SGD::from_groups(vec![ParamGroup::new(params)], lr)
+  }
+
+  pub fn from_groups(groups: Vec<ParamGroup<T>>, lr: f64) -> Self {
+    let (params, decay_overrides) = flatten_groups(groups);
+    let velocity = params.iter().map(|_| None).collect();
+    SGD {
+      lr,
+      momentum: 0.0,
+      nesterov: false,
+      weight_decay: 0.0,
+      params,
+      decay_overrides,
+      velocity
+    }
+  }
+}
+
+impl<T: Float> Optimizer<T> for SGD<T> {
+  fn params(&self) -> &[Var<T>] {
+    &self.params
+  }
+
+  fn step(&mut self) -> Result<(), String> {
+    if self.nesterov && self.momentum <= 0.0 {
+      return Err("SGD: Nesterov momentum requires a positive momentum".to_string());
+    }
+
+    let (lr, momentum) = (T::from_f64(self.lr), T::from_f64(self.momentum));
+    for ((param, velocity), decay) in self.params.iter().zip(self.velocity.iter_mut()).zip(&self.decay_overrides) {
+      let decay = T::from_f64(decay.unwrap_or(self.weight_decay));
+      let grad = match grad_of(param)? {
+        Some(grad) => grad,
+        None => continue
+      };
+
+      param.update_value(|value| {
+        let first = velocity.is_none();
+        let values = value.as_mut_slice();
+        if self.momentum == 0.0 {
+          for (p, &g) in values.iter_mut().zip(grad.iter()) {
+            *p -= lr * (g + decay * *p);
+          }
+          return;
+        }
+
+        let buffer = velocity.get_or_insert_with(|| Tensor::zeros(grad.shape())).as_mut_slice();
+        for ((p, v), &g) in values.iter_mut().zip(buffer.iter_mut()).zip(grad.iter()) {
+          let d = g + decay * *p;
+          *v = if first { d } else { momentum * *v + d };
+          *p -= lr * if self.nesterov { d + momentum * *v } else { *v };
+        }
+      });
+    }
+
+    Ok(())
+  }
+
+  fn learning_rate(&self) -> f64 {
+    self.lr
+  }
+
+  fn set_learning_rate(&mut self, lr: f64) {
+    self.lr = lr;
+  }
+
+  fn state_dict(&self) -> Vec<(String, Tensor<T>)> {
+    let mut state = vec![
+      scalar_entry("lr", self.lr),
+      scalar_entry("momentum", self.momentum),
+      scalar_entry("nesterov", if self.nesterov { 1.0 } else { 0.0 }),
+      scalar_entry("weight_decay", self.weight_decay)
+    ];
+    state.extend(buffer_entries("momentum_buffer", &self.velocity));
+    state
+  }
+
+  fn load_state_dict(&mut self, state: &[(String, Tensor<T>)]) -> Result<(), String> {
+    let velocity = read_buffers(state, "momentum_buffer", &self.params)?;
+    self.lr = read_scalar(state, "lr")?;
+    self.momentum = read_scalar(state, "momentum")?;
+    self.nesterov = read_scalar(state, "nesterov")? != 0.0;
+    self.weight_decay = read_scalar(state, "weight_decay")?;
+    self.velocity = velocity;
+    Ok(())
+  }
+}
@@ -0,0 +1,386 @@
+use std::collections::BTreeMap;
+
+use crate::math::matrix::Matrix;
+use crate::math::scalar::Float;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HandleUnknown {
+  Error,
+  Ignore
+}
+
+pub fn one_hot<T: Float>(labels: &[usize], classes: usize) -> Result<Matrix<T>, String> {
+  if let Some(&label) = labels.iter().find(|&&label| label >= classes) {
+    return Err(format!("one_hot: label {} out of range for {} classes", label, classes));
+  }
+  Ok(Matrix::from_fn(labels.len(), classes, |row, col| if labels[row] == col { T::one() } else { T::zero() }))
+}
+
+fn argmax<T: Float>(values: &[T]) -> Option<usize> {
+  let mut best: Option<usize> = None;
+  for (i, &value) in values.iter().enumerate() {
+    if best.is_none_or(|b| value > values[b]) {
+      best = Some(i);
+    }
+  }
+  best
+}
+
+pub fn from_one_hot<T: Float>(matrix: &Matrix<T>) -> Vec<usize> {
+  matrix.data.chunks(matrix.cols.max(1)).take(matrix.rows).map(|row| argmax(row).unwrap_or(0)).collect()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LabelEncoder<L = String> {
+  pub unknown_value: Option<usize>,
+  classes: Vec<L>
+}
+
+impl<L: Clone + Ord> LabelEncoder<L> {
+  pub fn new() -> Self {
+    LabelEncoder { unknown_value: None, classes: Vec::new() }
+  }
+
+  pub fn classes(&self) -> &[L] {
+    &self.classes
+  }
+
+  pub fn is_fitted(&self) -> bool {
+    !self.classes.is_empty()
+  }
+
+  pub fn fit(&mut self, labels: &[L]) -> Result<(), String> {
+    if labels.is_empty() {
+      return Err("LabelEncoder: cannot fit on an empty label set".to_string());
+    }
+    let mut classes = labels.to_vec();
+    classes.sort();
+    classes.dedup();
+    self.classes = classes;
+    Ok(())
+  }
+
+  pub fn transform(&self, labels: &[L]) -> Result<Vec<usize>, String> {
+    if !self.is_fitted() {
+      return Err("LabelEncoder: transform called before fit".to_string());
+    }
+    labels
+      .iter()
+      .enumerate()
+      .map(|(i, label)| match (self.classes.binary_search(label), self.unknown_value) {
+        (Ok(index), _) => Ok(index),
+        (Err(_), Some(value)) => Ok(value),
+        (Err(_), None) => Err(format!("LabelEncoder: unknown label at position {}", i))
+      })
+      .collect()
+  }
+
+  pub fn fit_transform(&mut self, labels: &[L]) -> Result<Vec<usize>, String> {
+    self.fit(labels)?;
+    self.transform(labels)
+  }
+
+  pub fn inverse_transform(&self, indices: &[usize]) -> Result<Vec<L>, String> {
+    indices
+      .iter()
+      .map(|&index| {
+        self
+          .classes
+          .get(index)
+          .cloned()
+          .ok_or_else(|| format!("LabelEncoder: index {} out of range for {} classes", index, self.classes.len()))
+      })
+      .collect()
+  }
+
+  pub fn transform_one_hot<T: Float>(&self, labels: &[L]) -> Result<Matrix<T>, String> {
+    one_hot(&self.transform(labels)?, self.classes.len())
+  }
+
+  pub fn inverse_one_hot<T: Float>(&self, matrix: &Matrix<T>) -> Result<Vec<L>, String> {
+    if matrix.cols != self.classes.len() {
+      return Err(format!("LabelEncoder: expected {} one-hot columns, got {}", self.classes.len(), matrix.cols));
+    }
+    self.inverse_transform(&from_one_hot(matrix))
+  }
+}
+
+impl<L: Clone + Ord> Default for LabelEncoder<L> {
+  fn default() -> Self {
+    LabelEncoder::new()
+  }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OneHotEncoder {
+  pub handle_unknown: HandleUnknown,
+  categories: Vec<Vec<String>>
+}
+
+impl OneHotEncoder {
+  pub fn new() -> Self {
+    OneHotEncoder { handle_unknown: HandleUnknown::Error, categories: Vec::new() }
+  }
+
+  pub fn categories(&self) -> &[Vec<String>] {
+    &self.categories
+  }
+
+  pub fn is_fitted(&self) -> bool {
+    !self.categories.is_empty()
+  }
+
+  pub fn n_features_out(&self) -> usize {
+    self.categories.iter().map(Vec::len).sum()
+  }
+
+  fn check_width(&self, width: usize, row: usize) -> Result<(), String> {
+    if width != self.categories.len() {
+      return Err(format!("OneHotEncoder: row {} has {} columns, expected {}", row, width, self.categories.len()));
+    }
+    Ok(())
+  }
+
+  pub fn fit<R, S>(&mut self, rows: &[R]) -> Result<(), String>
+  where
+    R: AsRef<[S]>,
+    S: AsRef<str>
+  {
+    let width = rows.first().map(|row| row.as_ref().len()).filter(|&width| width > 0);
+    let Some(width) = width else {
+      return Err("OneHotEncoder: cannot fit on empty data".to_string());
+    };
+
+    let mut categories = vec![Vec::new(); width];
+    for (i, row) in rows.iter().enumerate() {
+      let row = row.as_ref();
+      if row.len() != width {
+        return Err(format!("OneHotEncoder: row {} has {} columns, expected {}", i, row.len(), width));
+      }
+      for (column, value) in categories.iter_mut().zip(row) {
+        column.push(value.as_ref().to_string());
+      }
+    }
+    for column in &mut categories {
+      column.sort();
+      column.dedup();
+    }
+    self.categories = categories;
+    Ok(())
+  }
+
+  pub fn transform<T, R, S>(&self, rows: &[R]) -> Result<Matrix<T>, String>
+  where
+    T: Float,
+    R: AsRef<[S]>,
+    S: AsRef<str>
+  {
+    if !self.is_fitted() {
+      return Err("OneHotEncoder: transform called before fit".to_string());
+    }
+
+    let width = self.n_features_out();
+    let mut data = vec![T::zero(); rows.len() * width];
+    for (i, row) in rows.iter().enumerate() {
+      let row = row.as_ref();
+      self.check_width(row.len(), i)?;
+      let mut offset = i * width;
+      for (feature, (column, value)) in self.categories.iter().zip(row).enumerate() {
+        match column.binary_search_by(|category| category.as_str().cmp(value.as_ref())) {
+          Ok(index) => data[offset + index] = T::one(),
+          Err(_) if self.handle_unknown == HandleUnknown::Ignore => {}
+          Err(_) => return Err(format!("OneHotEncoder: unknown category {:?} in column {} of row {}", value.as_ref(), feature, i))
+        }
+        offset += column.len();
+      }
+    }
+    Matrix::from_vec(rows.len(), width, data)
+  }
+
+  pub fn fit_transform<T, R, S>(&mut self, rows: &[R]) -> Result<Matrix<T>, String>
+  where
+    T: Float,
+    R: AsRef<[S]>,
+    S: AsRef<str>
+  {
+    self.fit(rows)?;
+    self.transform(rows)
+  }
+
+  pub fn inverse_transform<T: Float>(&self, matrix: &Matrix<T>) -> Result<Vec<Vec<Option<String>>>, String> {
+    if matrix.cols != self.n_features_out() {
+      return Err(format!("OneHotEncoder: expected {} columns, got {}", self.n_features_out(), matrix.cols));
+    }
+
+    Ok(
+      matrix
+        .data
+        .chunks(matrix.cols.max(1))
+        .take(matrix.rows)
+        .map(|row| {
+          let mut offset = 0;
+          self
+            .categories
+            .iter()
+            .map(|column| {
+              let block = &row[offset..offset + column.len()];
+              offset += column.len();
+              argmax(block).filter(|&index| block[index] > T::zero()).map(|index| column[index].clone())
+            })
+            .collect()
+        })
+        .collect()
+    )
+  }
+}
+
+impl Default for OneHotEncoder {
+  fn default() -> Self {
+    OneHotEncoder::new()
+  }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TargetEncoder {
+  pub smoothing: f64,
+  pub folds: usize,
+  prior: f64,
+  encodings: Vec<BTreeMap<String, f64>>
+}
+
+fn target_statistics<R, S>(rows: &[(usize, &R)], target: &[f64], width: usize, smoothing: f64) -> (f64, Vec<BTreeMap<String, f64>>)
+where
+  R: AsRef<[S]>,
+  S: AsRef<str>
+{
+  let prior = rows.iter().map(|&(i, _)| target[i]).sum::<f64>() / rows.len().max(1) as f64;
+  let mut sums: Vec<BTreeMap<String, (f64, usize)>> = vec![BTreeMap::new(); width];
+  for &(i, row) in rows {
+    for (column, value) in sums.iter_mut().zip(row.as_ref()) {
+      let entry = column.entry(value.as_ref().to_string()).or_insert((0.0, 0));
+      entry.0 += target[i];
+      entry.1 += 1;
+    }
+  }
+  let encodings = sums
+    .into_iter()
+    .map(|column| {
+      column
+        .into_iter()
+        .map(|(category, (sum, count))| (category, (sum + smoothing * prior) / (count as f64 + smoothing)))
+        .collect()
+    })
+    .collect();
+  (prior, encodings)
+}
+
+fn encode_rows<T, R, S>(rows: &[R], prior: f64, encodings: &[BTreeMap<String, f64>]) -> Result<Matrix<T>, String>
+where
+  T: Float,
+  R: AsRef<[S]>,
+  S: AsRef<str>
+{
+  let width = encodings.len();
+  let mut data = Vec::with_capacity(rows.len() * width);
+  for (i, row) in rows.iter().enumerate() {
+    let row = row.as_ref();
+    if row.len() != width {
+      return Err(format!("TargetEncoder: row {} has {} columns, expected {}", i, row.len(), width));
+    }
+    for (column, value) in encodings.iter().zip(row) {
+      data.push(T::from_f64(column.get(value.as_ref()).copied().unwrap_or(prior)));
+    }
+  }
+  Matrix::from_vec(rows.len(), width, data)
+}
+
+impl TargetEncoder {
+  pub fn new() -> Self {
+    TargetEncoder { smoothing: 1.0, folds: 5, prior: 0.0, encodings: Vec::new() }
+  }
+
+  pub fn prior(&self) -> f64 {
+    self.prior
+  }
+
+  pub fn encoding(&self, column: usize, category: &str) -> Option<f64> {
+    self.encodings.get(column)?.get(category).copied()
+  }
+
+  pub fn is_fitted(&self) -> bool {
+    !self.encodings.is_empty()
+  }
+
+  fn check<R, S>(&self, rows: &[R], target: &[f64]) -> Result<usize, String>
+  where
+    R: AsRef<[S]>,
+    S: AsRef<str>
+  {
+    if rows.is_empty() || rows.len() != target.len() {
+      return Err(format!("TargetEncoder: need matching non-empty rows and targets, found {} and {}", rows.len(), target.len()));
+    }
+    if self.smoothing < 0.0 {
+      return Err(format!("TargetEncoder: smoothing must be non-negative, found {}", self.smoothing));
+    }
+    Ok(rows[0].as_ref().len())
+  }
+
+  pub fn fit<R, S>(&mut self, rows: &[R], target: &[f64]) -> Result<(), String>
+  where
+    R: AsRef<[S]>,
+    S: AsRef<str>
+  {
+    let width = self.check(rows, target)?;
+    let indexed: Vec<(usize, &R)> = rows.iter().enumerate().collect();
+    let (prior, encodings) = target_statistics(&indexed, target, width, self.smoothing);
+    self.prior = prior;
+    self.encodings = encodings;
+    Ok(())
+  }
+
+  pub fn transform<T, R, S>(&self, rows: &[R]) -> Result<Matrix<T>, String>
+  where
+    T: Float,
+    R: AsRef<[S]>,
+    S: AsRef<str>
+  {
+    if !self.is_fitted() {
+      return Err("TargetEncoder: transform called before fit".to_string());
+    }
+    encode_rows(rows, self.prior, &self.encodings)
+  }
+
+  pub fn fit_transform<T, R, S>(&mut self, rows: &[R], target: &[f64]) -> Result<Matrix<T>, String>
+  where
+    T: Float,
+    R: AsRef<[S]>,
+    S: AsRef<str>
+  {
+    self.fit(rows, target)?;
+    if self.folds < 2 || rows.len() < self.folds {
+      return self.transform(rows);
+    }
+
+    let width = self.encodings.len();
+    let mut out = Matrix::from_vec(rows.len(), width, vec![T::zero(); rows.len() * width])?;
+    for fold in 0..self.folds {
+      let train: Vec<(usize, &R)> = rows.iter().enumerate().filter(|(i, _)| i % self.folds != fold).collect();
+      let (prior, encodings) = target_statistics(&train, target, width, self.smoothing);
+      for (i, row) in rows.iter().enumerate().skip(fold).step_by(self.folds) {
+        let encoded: Matrix<T> = encode_rows(std::slice::from_ref(row), prior, &encodings)?;
+        out.data[i * width..(i + 1) * width].copy_from_slice(&encoded.data);
+      }
+    }
+    Ok(out)
+  }
+}
+
+impl Default for TargetEncoder {
+  fn default() -> Self {
+    TargetEncoder::new()
+  }
+}
@@ -0,0 +1,52 @@
+use crate::math::matrix::Matrix;
+use crate::math::scalar::Float;
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+fn fnv1a(column: usize, value: &str) -> u64 {
+  let mut hash = FNV_OFFSET;
+  for byte in (column as u64).to_le_bytes().iter().chain(b"=").chain(value.as_bytes()) {
+    hash ^= *byte as u64;
+    hash = hash.wrapping_mul(FNV_PRIME);
+  }
+  hash
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FeatureHasher {
+  pub n_features: usize,
+  pub alternate_sign: bool
+}
+
+impl FeatureHasher {
+  pub fn new(n_features: usize) -> Result<Self, String> {
+    if n_features == 0 {
+      return Err("FeatureHasher: n_features must be positive".to_string());
+    }
+    Ok(FeatureHasher { n_features, alternate_sign: true })
+  }
+
+  pub fn bucket(&self, column: usize, value: &str) -> (usize, f64) {
+    let hash = fnv1a(column, value);
+    let sign = if self.alternate_sign && hash >> 63 == 1 { -1.0 } else { 1.0 };
+    ((hash % self.n_features as u64) as usize, sign)
+  }
+
+  pub fn transform<T, R, S>(&self, rows: &[R]) -> Result<Matrix<T>, String>
+  where
+    T: Float,
+    R: AsRef<[S]>,
+    S: AsRef<str>
+  {
+    let mut data = vec![0.0; rows.len() * self.n_features];
+    for (i, row) in rows.iter().enumerate() {
+      for (column, value) in row.as_ref().iter().enumerate() {
+        let (bucket, sign) = self.bucket(column, value.as_ref());
+        data[i * self.n_features + bucket] += sign;
+      }
+    }
+    Matrix::from_vec(rows.len(), self.n_features, data.into_iter().map(T::from_f64).collect())
+  }
+}
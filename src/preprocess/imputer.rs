@@ -0,0 +1,107 @@
+use std::collections::BTreeMap;
+
+use crate::math::matrix::Matrix;
+use crate::math::scalar::Float;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ImputeStrategy {
+  Mean,
+  Median,
+  MostFrequent,
+  Constant(f64)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Imputer {
+  pub strategy: ImputeStrategy,
+  pub column_strategies: BTreeMap<usize, ImputeStrategy>,
+  statistics: Option<Vec<f64>>
+}
+
+fn median(mut values: Vec<f64>) -> f64 {
+  values.sort_by(f64::total_cmp);
+  let mid = values.len() / 2;
+  if values.len().is_multiple_of(2) {
+    (values[mid - 1] + values[mid]) / 2.0
+  } else {
+    values[mid]
+  }
+}
+
+fn most_frequent(mut values: Vec<f64>) -> f64 {
+  values.sort_by(f64::total_cmp);
+  let (mut best, mut best_count) = (values[0], 0);
+  for run in values.chunk_by(|a, b| a == b) {
+    if run.len() > best_count {
+      (best, best_count) = (run[0], run.len());
+    }
+  }
+  best
+}
+
+impl Imputer {
+  pub fn new(strategy: ImputeStrategy) -> Self {
+    Imputer { strategy, column_strategies: BTreeMap::new(), statistics: None }
+  }
+
+  pub fn set_column_strategy(&mut self, column: usize, strategy: ImputeStrategy) {
+    self.column_strategies.insert(column, strategy);
+  }
+
+  pub fn strategy_for(&self, column: usize) -> ImputeStrategy {
+    self.column_strategies.get(&column).copied().unwrap_or(self.strategy)
+  }
+
+  pub fn statistics(&self) -> Option<&[f64]> {
+    self.statistics.as_deref()
+  }
+
+  pub fn is_fitted(&self) -> bool {
+    self.statistics.is_some()
+  }
+
+  pub fn fit<T: Float>(&mut self, data: &Matrix<T>) -> Result<(), String> {
+    if let Some(&column) = self.column_strategies.keys().find(|&&column| column >= data.cols) {
+      return Err(format!("Imputer: strategy set for column {} but data has {} columns", column, data.cols));
+    }
+
+    let means = data.nanmean(0)?;
+    let mut statistics = Vec::with_capacity(data.cols);
+    for col in 0..data.cols {
+      let strategy = self.strategy_for(col);
+      let observed: Vec<f64> = (0..data.rows).map(|row| data[(row, col)].to_f64()).filter(|x| !x.is_nan()).collect();
+      if observed.is_empty() && !matches!(strategy, ImputeStrategy::Constant(_)) {
+        return Err(format!("Imputer: column {} has no observed values", col));
+      }
+      statistics.push(match strategy {
+        ImputeStrategy::Mean => means[col].to_f64(),
+        ImputeStrategy::Median => median(observed),
+        ImputeStrategy::MostFrequent => most_frequent(observed),
+        ImputeStrategy::Constant(value) => value
+      });
+    }
+    self.statistics = Some(statistics);
+    Ok(())
+  }
+
+  pub fn transform<T: Float>(&self, data: &Matrix<T>) -> Result<Matrix<T>, String> {
+    let statistics = self.statistics.as_ref().ok_or("Imputer: transform called before fit")?;
+    if data.cols != statistics.len() {
+      return Err(format!("Imputer: fitted on {} features, got {}", statistics.len(), data.cols));
+    }
+    let mut out = data.clone();
+    for (i, x) in out.data.iter_mut().enumerate() {
+      if x.is_nan() {
+        *x = T::from_f64(statistics[i % data.cols]);
+      }
+    }
+    Ok(out)
+  }
+
+  pub fn fit_transform<T: Float>(&mut self, data: &Matrix<T>) -> Result<Matrix<T>, String> {
+    self.fit(data)?;
+    self.transform(data)
+  }
+}
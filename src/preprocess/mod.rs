@@ -0,0 +1,33 @@
+use crate::math::matrix::Matrix;
+use crate::math::scalar::Float;
+
+pub mod encoders;
+pub mod hashing;
+pub mod imputer;
+pub mod scalers;
+
+pub use encoders::{HandleUnknown, LabelEncoder, OneHotEncoder, TargetEncoder};
+pub use hashing::FeatureHasher;
+pub use imputer::{ImputeStrategy, Imputer};
+pub use scalers::{MinMaxScaler, RobustScaler, StandardScaler};
+
+pub trait Scaler {
+  fn fit<T: Float>(&mut self, data: &Matrix<T>) -> Result<(), String>;
+
+  fn transform<T: Float>(&self, data: &Matrix<T>) -> Result<Matrix<T>, String>;
+
+  fn inverse_transform<T: Float>(&self, data: &Matrix<T>) -> Result<Matrix<T>, String>;
+
+  fn is_fitted(&self) -> bool;
+
+  fn fit_transform<T: Float>(&mut self, data: &Matrix<T>) -> Result<Matrix<T>, String> {
+    self.fit(data)?;
+    self.transform(data)
+  }
+}
+
+pub(crate) fn columns<T: Float>(data: &Matrix<T>) -> Vec<Vec<f64>> {
+  (0..data.cols)
+    .map(|col| (0..data.rows).map(|row| data.data[row * data.cols + col].to_f64()).collect())
+    .collect()
+}
@@ -0,0 +1,246 @@
+use super::{columns, Scaler};
+use crate::math::matrix::Matrix;
+use crate::math::scalar::Float;
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Affine {
+  center: Vec<f64>,
+  scale: Vec<f64>
+}
+
+impl Affine {
+  fn new(center: Vec<f64>, scale: Vec<f64>) -> Self {
+    let scale = scale.into_iter().map(|s| if s == 0.0 || !s.is_finite() { 1.0 } else { s }).collect();
+    Affine { center, scale }
+  }
+
+  fn check<T>(&self, name: &str, data: &Matrix<T>) -> Result<(), String> {
+    if data.cols != self.center.len() {
+      return Err(format!("{}: fitted on {} features, got {}", name, self.center.len(), data.cols));
+    }
+    Ok(())
+  }
+
+  fn apply<T: Float>(&self, data: &Matrix<T>) -> Result<Matrix<T>, String> {
+    Matrix::from_vec(
+      data.rows,
+      data.cols,
+      data
+        .data
+        .iter()
+        .enumerate()
+        .map(|(i, x)| {
+          let col = i % data.cols;
+          T::from_f64((x.to_f64() - self.center[col]) / self.scale[col])
+        })
+        .collect()
+    )
+  }
+
+  fn invert<T: Float>(&self, data: &Matrix<T>) -> Result<Matrix<T>, String> {
+    Matrix::from_vec(
+      data.rows,
+      data.cols,
+      data
+        .data
+        .iter()
+        .enumerate()
+        .map(|(i, x)| {
+          let col = i % data.cols;
+          T::from_f64(x.to_f64() * self.scale[col] + self.center[col])
+        })
+        .collect()
+    )
+  }
+}
+
+fn fitted<'a>(name: &str, affine: &'a Option<Affine>) -> Result<&'a Affine, String> {
+  affine.as_ref().ok_or_else(|| format!("{}: transform called before fit", name))
+}
+
+fn check_samples<T>(name: &str, data: &Matrix<T>) -> Result<(), String> {
+  if data.rows == 0 {
+    return Err(format!("{}: cannot fit on an empty matrix", name));
+  }
+  Ok(())
+}
+
+macro_rules! impl_affine_scaler {
+  ($scaler:ident) => {
+    impl Scaler for $scaler {
+      fn fit<T: Float>(&mut self, data: &Matrix<T>) -> Result<(), String> {
+        check_samples(stringify!($scaler), data)?;
+        self.fitted = Some(self.fit_columns(&columns(data))?);
+        Ok(())
+      }
+
+      fn transform<T: Float>(&self, data: &Matrix<T>) -> Result<Matrix<T>, String> {
+        let affine = fitted(stringify!($scaler), &self.fitted)?;
+        affine.check(stringify!($scaler), data)?;
+        affine.apply(data)
+      }
+
+      fn inverse_transform<T: Float>(&self, data: &Matrix<T>) -> Result<Matrix<T>, String> {
+        let affine = fitted(stringify!($scaler), &self.fitted)?;
+        affine.check(stringify!($scaler), data)?;
+        affine.invert(data)
+      }
+
+      fn is_fitted(&self) -> bool {
+        self.fitted.is_some()
+      }
+    }
+  };
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StandardScaler {
+  pub with_mean: bool,
+  pub with_std: bool,
+  fitted: Option<Affine>
+}
+
+impl StandardScaler {
+  pub fn new() -> Self {
+    StandardScaler { with_mean: true, with_std: true, fitted: None }
+  }
+
+  pub fn mean(&self) -> Option<&[f64]> {
+    self.fitted.as_ref().map(|affine| affine.center.as_slice())
+  }
+
+  pub fn std(&self) -> Option<&[f64]> {
+    self.fitted.as_ref().map(|affine| affine.scale.as_slice())
+  }
+
+  fn fit_columns(&mut self, columns: &[Vec<f64>]) -> Result<Affine, String> {
+    let (mut center, mut scale) = (Vec::new(), Vec::new());
+    for column in columns {
+      let n = column.len() as f64;
+      let mean = column.iter().sum::<f64>() / n;
+      let var = column.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n;
+      center.push(if self.with_mean { mean } else { 0.0 });
+      scale.push(if self.with_std { var.sqrt() } else { 1.0 });
+    }
+    Ok(Affine::new(center, scale))
+  }
+}
+
+impl Default for StandardScaler {
+  fn default() -> Self {
+    StandardScaler::new()
+  }
+}
+
+impl_affine_scaler!(StandardScaler);
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MinMaxScaler {
+  pub feature_range: (f64, f64),
+  extrema: Option<(Vec<f64>, Vec<f64>)>,
+  fitted: Option<Affine>
+}
+
+impl MinMaxScaler {
+  pub fn new() -> Self {
+    MinMaxScaler { feature_range: (0.0, 1.0), extrema: None, fitted: None }
+  }
+
+  pub fn data_min(&self) -> Option<&[f64]> {
+    self.extrema.as_ref().map(|(min, _)| min.as_slice())
+  }
+
+  pub fn data_max(&self) -> Option<&[f64]> {
+    self.extrema.as_ref().map(|(_, max)| max.as_slice())
+  }
+
+  fn fit_columns(&mut self, columns: &[Vec<f64>]) -> Result<Affine, String> {
+    let (low, high) = self.feature_range;
+    if low >= high {
+      return Err(format!("MinMaxScaler: invalid feature_range ({}, {})", low, high));
+    }
+
+    let (mut center, mut scale) = (Vec::new(), Vec::new());
+    let (mut mins, mut maxs) = (Vec::new(), Vec::new());
+    for column in columns {
+      let min = column.iter().copied().fold(f64::INFINITY, f64::min);
+      let max = column.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+      let span = if max > min { (max - min) / (high - low) } else { 1.0 };
+      center.push(min - low * span);
+      scale.push(span);
+      mins.push(min);
+      maxs.push(max);
+    }
+    self.extrema = Some((mins, maxs));
+    Ok(Affine::new(center, scale))
+  }
+}
+
+impl Default for MinMaxScaler {
+  fn default() -> Self {
+    MinMaxScaler::new()
+  }
+}
+
+impl_affine_scaler!(MinMaxScaler);
+
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+  let position = q * (sorted.len() - 1) as f64;
+  let (lower, upper) = (position.floor() as usize, position.ceil() as usize);
+  sorted[lower] + (sorted[upper] - sorted[lower]) * (position - lower as f64)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RobustScaler {
+  pub with_centering: bool,
+  pub with_scaling: bool,
+  pub quantile_range: (f64, f64),
+  fitted: Option<Affine>
+}
+
+impl RobustScaler {
+  pub fn new() -> Self {
+    RobustScaler {
+      with_centering: true,
+      with_scaling: true,
+      quantile_range: (25.0, 75.0),
+      fitted: None
+    }
+  }
+
+  pub fn center(&self) -> Option<&[f64]> {
+    self.fitted.as_ref().map(|affine| affine.center.as_slice())
+  }
+
+  pub fn scale(&self) -> Option<&[f64]> {
+    self.fitted.as_ref().map(|affine| affine.scale.as_slice())
+  }
+
+  fn fit_columns(&mut self, columns: &[Vec<f64>]) -> Result<Affine, String> {
+    let (low, high) = self.quantile_range;
+    if !(0.0..high).contains(&low) || high > 100.0 {
+      return Err(format!("RobustScaler: invalid quantile_range ({}, {})", low, high));
+    }
+
+    let (mut center, mut scale) = (Vec::new(), Vec::new());
+    for column in columns {
+      let mut sorted = column.clone();
+      sorted.sort_by(f64::total_cmp);
+      center.push(if self.with_centering { quantile(&sorted, 0.5) } else { 0.0 });
+      scale.push(if self.with_scaling { quantile(&sorted, high / 100.0) - quantile(&sorted, low / 100.0) } else { 1.0 });
+    }
+    Ok(Affine::new(center, scale))
+  }
+}
+
+impl Default for RobustScaler {
+  fn default() -> Self {
+    RobustScaler::new()
+  }
+}
+
+impl_affine_scaler!(RobustScaler);
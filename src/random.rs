@@ -0,0 +1,127 @@
+use std::cell::RefCell;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rng {
+  state: [u64; 4]
+}
+
+fn splitmix64(state: &mut u64) -> u64 {
+  *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+  let mut z = *state;
+  z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+  z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+  z ^ (z >> 31)
+}
+
+impl Rng {
+  pub fn seed_from_u64(seed: u64) -> Self {
+    let mut sm = seed;
+    Rng {
+      state: [splitmix64(&mut sm), splitmix64(&mut sm), splitmix64(&mut sm), splitmix64(&mut sm)]
+    }
+  }
+
+  pub fn from_entropy() -> Self {
+    let mut hasher = RandomState::new().build_hasher();
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_nanos() as u64);
+    hasher.write_u64(nanos);
+    Rng::seed_from_u64(hasher.finish())
+  }
+
+  pub fn next_u64(&mut self) -> u64 {
+    let result = self.state[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+    let t = self.state[1] << 17;
+
+    self.state[2] ^= self.state[0];
+    self.state[3] ^= self.state[1];
+    self.state[1] ^= self.state[2];
+    self.state[0] ^= self.state[3];
+    self.state[2] ^= t;
+    self.state[3] = self.state[3].rotate_left(45);
+
+    result
+  }
+
+  pub fn next_f64(&mut self) -> f64 {
+    (self.next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+  }
+
+  pub fn uniform(&mut self, low: f64, high: f64) -> f64 {
+    low + (high - low) * self.next_f64()
+  }
+
+  pub fn normal(&mut self, mean: f64, std: f64) -> f64 {
+    let u1 = 1.0 - self.next_f64();
+    let u2 = self.next_f64();
+    mean + std * (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+  }
+
+  pub fn bernoulli(&mut self, p: f64) -> bool {
+    self.next_f64() < p
+  }
+
+  pub fn gen_range(&mut self, n: usize) -> usize {
+    assert!(n > 0, "gen_range: empty range");
+    let n = n as u64;
+    let zone = u64::MAX - u64::MAX % n;
+    loop {
+      let x = self.next_u64();
+      if x < zone {
+        return (x % n) as usize;
+      }
+    }
+  }
+
+  pub fn shuffle<T>(&mut self, items: &mut [T]) {
+    for i in (1..items.len()).rev() {
+      items.swap(i, self.gen_range(i + 1));
+    }
+  }
+}
+
+thread_local! {
+  static THREAD_RNG: RefCell<Rng> = RefCell::new(Rng::from_entropy());
+}
+
+static DETERMINISTIC: AtomicBool = AtomicBool::new(false);
+
+pub fn with_rng<R, F>(f: F) -> R
+where
+  F: FnOnce(&mut Rng) -> R
+{
+  THREAD_RNG.with(|rng| f(&mut rng.borrow_mut()))
+}
+
+pub fn seed(seed: u64) {
+  with_rng(|rng| *rng = Rng::seed_from_u64(seed));
+}
+
+pub fn with_seed<R, F>(seed: u64, f: F) -> R
+where
+  F: FnOnce() -> R
+{
+  struct Restore(Option<Rng>);
+
+  impl Drop for Restore {
+    fn drop(&mut self) {
+      if let Some(rng) = self.0.take() {
+        with_rng(|current| *current = rng);
+      }
+    }
+  }
+
+  let _restore = Restore(Some(with_rng(|rng| std::mem::replace(rng, Rng::seed_from_u64(seed)))));
+  f()
+}
+
+pub fn set_deterministic(enabled: bool) {
+  DETERMINISTIC.store(enabled, Ordering::SeqCst);
+}
+
+pub fn is_deterministic() -> bool {
+  DETERMINISTIC.load(Ordering::SeqCst)
+}
@@ -0,0 +1,103 @@
+use std::ops::{Add, Div, Mul, Sub};
+
+use super::Tensor;
+use crate::math::scalar::Scalar;
+use crate::math::shape::ShapeError;
+
+pub fn broadcast_shape(left: &[usize], right: &[usize]) -> Result<Vec<usize>, ShapeError> {
+  let ndim = left.len().max(right.len());
+  let mut shape = vec![0; ndim];
+
+  for i in 0..ndim {
+    let l = if i < ndim - left.len() { 1 } else { left[i - (ndim - left.len())] };
+    let r = if i < ndim - right.len() { 1 } else { right[i - (ndim - right.len())] };
+    shape[i] = match (l, r) {
+      (l, r) if l == r => l,
+      (1, r) => r,
+      (l, 1) => l,
+      _ => return Err(ShapeError::Broadcast { left: left.to_vec(), right: right.to_vec() })
+    };
+  }
+
+  Ok(shape)
+}
+
+impl<T> Tensor<T> {
+  pub fn broadcast_to(&self, shape: &[usize]) -> Result<Self, ShapeError> {
+    let error = || ShapeError::Broadcast { left: self.shape().to_vec(), right: shape.to_vec() };
+    if shape.len() < self.ndim() {
+      return Err(error());
+    }
+
+    let lead = shape.len() - self.ndim();
+    let mut strides = vec![0; shape.len()];
+    for (i, &dim) in shape.iter().enumerate().skip(lead) {
+      let own = self.shape()[i - lead];
+      strides[i] = if own == dim {
+        self.strides()[i - lead]
+      } else if own == 1 {
+        0
+      } else {
+        return Err(error());
+      };
+    }
+
+    Ok(self.view(shape.to_vec(), strides, self.offset()))
+  }
+}
+
+impl<T: Scalar> Tensor<T> {
+  pub fn sum_to(&self, shape: &[usize]) -> Result<Self, String> {
+    let error = || ShapeError::Broadcast { left: shape.to_vec(), right: self.shape().to_vec() };
+    if shape.len() > self.ndim() {
+      return Err(error().into());
+    }
+
+    let lead = self.ndim() - shape.len();
+    let mut axes: Vec<usize> = (0..lead).collect();
+    for (i, &dim) in shape.iter().enumerate() {
+      let own = self.shape()[i + lead];
+      if dim == 1 && own != 1 {
+        axes.push(i + lead);
+      } else if dim != own {
+        return Err(error().into());
+      }
+    }
+
+    if axes.is_empty() {
+      return Ok(self.clone());
+    }
+    self.sum(&axes, true)?.reshape(shape)
+  }
+}
+
+macro_rules! impl_broadcast_op {
+  ($trait:ident, $method:ident, $op:tt) => {
+    impl<T> $trait for &Tensor<T>
+    where
+      T: $trait<Output = T> + Copy
+    {
+      type Output = Result<Tensor<T>, String>;
+
+      fn $method(self, rhs: Self) -> Self::Output {
+        self.zip_map(rhs, |a, b| *a $op *b)
+      }
+    }
+
+    impl<T> $trait for Tensor<T>
+    where
+      T: $trait<Output = T> + Copy
+    {
+      type Output = Result<Tensor<T>, String>;
+
+      fn $method(self, rhs: Self) -> Self::Output {
+        (&self).$method(&rhs)
+      }
+    }
+  };
+}
+
+impl_broadcast_op!(Add, add, +);
+impl_broadcast_op!(Sub, sub, -);
+impl_broadcast_op!(Mul, mul, *);
+impl_broadcast_op!(Div, div, /);
@@ -0,0 +1,61 @@
+use super::slice::SliceArg;
+use super::Tensor;
+
+impl<T: Clone> Tensor<T> {
+  pub fn cat(tensors: &[&Tensor<T>], axis: usize) -> Result<Tensor<T>, String> {
+    let first = tensors.first().ok_or_else(|| "cat: expected at least one tensor".to_string())?;
+    if axis >= first.ndim() {
+      return Err(format!("cat: axis {} out of range for a tensor with {} dimensions", axis, first.ndim()));
+    }
+
+    let mut shape = first.shape().to_vec();
+    shape[axis] = 0;
+    for tensor in tensors {
+      let compatible = tensor.ndim() == first.ndim()
+        && tensor.shape().iter().zip(first.shape()).enumerate().all(|(i, (a, b))| i == axis || a == b);
+      if !compatible {
+        return Err(format!("cat: shape {:?} does not match {:?} outside axis {}", tensor.shape(), first.shape(), axis));
+      }
+      shape[axis] += tensor.shape()[axis];
+    }
+
+    let outer: usize = shape[..axis].iter().product();
+    let inner: usize = shape[axis + 1..].iter().product();
+    let parts: Vec<Tensor<T>> = tensors.iter().map(|t| t.contiguous()).collect();
+
+    let mut data = Vec::with_capacity(shape.iter().product());
+    for o in 0..outer {
+      for part in &parts {
+        let block = part.shape()[axis] * inner;
+        data.extend_from_slice(&part.storage()[o * block..(o + 1) * block]);
+      }
+    }
+
+    Tensor::from_vec(&shape, data)
+  }
+
+  pub fn stack(tensors: &[&Tensor<T>], axis: usize) -> Result<Tensor<T>, String> {
+    let expanded = tensors.iter().map(|t| t.unsqueeze(axis)).collect::<Result<Vec<_>, _>>()?;
+    Tensor::cat(&expanded.iter().collect::<Vec<_>>(), axis)
+  }
+
+  pub fn unsqueeze(&self, axis: usize) -> Result<Tensor<T>, String> {
+    if axis > self.ndim() {
+      return Err(format!("unsqueeze: axis {} out of range for a tensor with {} dimensions", axis, self.ndim()));
+    }
+
+    let mut shape = self.shape().to_vec();
+    shape.insert(axis, 1);
+    self.reshape(&shape)
+  }
+
+  pub fn narrow(&self, axis: usize, start: usize, len: usize) -> Result<Tensor<T>, String> {
+    if axis >= self.ndim() {
+      return Err(format!("narrow: axis {} out of range for a tensor with {} dimensions", axis, self.ndim()));
+    }
+
+    let mut args = vec![SliceArg::from(..); axis];
+    args.push(SliceArg::from(start..start + len));
+    self.slice(&args)
+  }
+}
@@ -0,0 +1,145 @@
+use super::device::dispatch;
+use super::Tensor;
+use crate::math::scalar::Scalar;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Conv2dOptions {
+  pub kernel: (usize, usize),
+  pub stride: (usize, usize),
+  pub padding: (usize, usize),
+  pub dilation: (usize, usize)
+}
+
+impl Conv2dOptions {
+  pub fn new(kernel: (usize, usize)) -> Self {
+    Conv2dOptions {
+      kernel,
+      stride: (1, 1),
+      padding: (0, 0),
+      dilation: (1, 1)
+    }
+  }
+
+  pub fn output_size(&self, height: usize, width: usize) -> Result<(usize, usize), String> {
+    let extent = |size: usize, k: usize, s: usize, p: usize, d: usize| {
+      let span = d * (k.max(1) - 1) + 1;
+      if k == 0 || s == 0 || size + 2 * p < span {
+        return Err(format!(
+          "conv2d: kernel {:?} with stride {:?}, padding {:?}, dilation {:?} does not fit a {}x{} input",
+          self.kernel, self.stride, self.padding, self.dilation, height, width
+        ));
+      }
+      Ok((size + 2 * p - span) / s + 1)
+    };
+
+    Ok((
+      extent(height, self.kernel.0, self.stride.0, self.padding.0, self.dilation.0)?,
+      extent(width, self.kernel.1, self.stride.1, self.padding.1, self.dilation.1)?
+    ))
+  }
+
+  pub(crate) fn source(&self, out: (usize, usize), k: (usize, usize), size: (usize, usize)) -> Option<usize> {
+    let y = (out.0 * self.stride.0 + k.0 * self.dilation.0).checked_sub(self.padding.0)?;
+    let x = (out.1 * self.stride.1 + k.1 * self.dilation.1).checked_sub(self.padding.1)?;
+    (y < size.0 && x < size.1).then_some(y * size.1 + x)
+  }
+}
+
+fn image_dims(op: &str, shape: &[usize]) -> Result<(usize, usize, usize, usize), String> {
+  match shape {
+    &[n, c, h, w] => Ok((n, c, h, w)),
+    _ => Err(format!("{}: expected an [N, C, H, W] tensor, found shape {:?}", op, shape))
+  }
+}
+
+impl<T: Scalar> Tensor<T> {
+  pub fn im2col(&self, options: &Conv2dOptions) -> Result<Tensor<T>, String> {
+    let (n, c, h, w) = image_dims("im2col", self.shape())?;
+    let (oh, ow) = options.output_size(h, w)?;
+    let (kh, kw) = options.kernel;
+    let (rows, cols) = (c * kh * kw, oh * ow);
+
+    dispatch("im2col", &[self.device()], || {
+      let input = self.contiguous();
+      let src = input.storage();
+      let mut data = vec![T::zero(); n * rows * cols];
+      for b in 0..n {
+        for ch in 0..c {
+          let image = &src[(b * c + ch) * h * w..(b * c + ch + 1) * h * w];
+          for ki in 0..kh {
+            for kj in 0..kw {
+              let row = (ch * kh + ki) * kw + kj;
+              let out = &mut data[(b * rows + row) * cols..(b * rows + row + 1) * cols];
+              for oy in 0..oh {
+                for ox in 0..ow {
+                  if let Some(offset) = options.source((oy, ox), (ki, kj), (h, w)) {
+                    out[oy * ow + ox] = image[offset];
+                  }
+                }
+              }
+            }
+          }
+        }
+      }
+
+      Tensor::from_vec(&[n, rows, cols], data)
+    })
+  }
+
+  pub fn col2im(&self, channels: usize, size: (usize, usize), options: &Conv2dOptions) -> Result<Tensor<T>, String> {
+    let (h, w) = size;
+    let (oh, ow) = options.output_size(h, w)?;
+    let (kh, kw) = options.kernel;
+    let (rows, cols) = (channels * kh * kw, oh * ow);
+    let n = match self.shape() {
+      &[n, r, l] if r == rows && l == cols => n,
+      shape => return Err(format!("col2im: expected shape [N, {}, {}], found {:?}", rows, cols, shape))
+    };
+
+    dispatch("col2im", &[self.device()], || {
+      let input = self.contiguous();
+      let src = input.storage();
+      let mut data = vec![T::zero(); n * channels * h * w];
+      for b in 0..n {
+        for ch in 0..channels {
+          let image = &mut data[(b * channels + ch) * h * w..(b * channels + ch + 1) * h * w];
+          for ki in 0..kh {
+            for kj in 0..kw {
+              let row = (ch * kh + ki) * kw + kj;
+              let column = &src[(b * rows + row) * cols..(b * rows + row + 1) * cols];
+              for oy in 0..oh {
+                for ox in 0..ow {
+                  if let Some(offset) = options.source((oy, ox), (ki, kj), (h, w)) {
+                    image[offset] += column[oy * ow + ox];
+                  }
+                }
+              }
+            }
+          }
+        }
+      }
+
+      Tensor::from_vec(&[n, channels, h, w], data)
+    })
+  }
+
+  pub fn conv2d(&self, weight: &Tensor<T>, bias: Option<&Tensor<T>>, options: &Conv2dOptions) -> Result<Tensor<T>, String> {
+    let (n, _, h, w) = image_dims("conv2d", self.shape())?;
+    let (out_channels, kernel_rows) = conv_weight_dims(weight.shape(), options)?;
+    let (oh, ow) = options.output_size(h, w)?;
+
+    let cols = self.im2col(options)?;
+    let mut out = weight.reshape(&[out_channels, kernel_rows])?.matmul(&cols)?;
+    if let Some(bias) = bias {
+      out = (&out + &bias.reshape(&[out_channels, 1])?)?;
+    }
+    out.reshape(&[n, out_channels, oh, ow])
+  }
+}
+
+pub(crate) fn conv_weight_dims(shape: &[usize], options: &Conv2dOptions) -> Result<(usize, usize), String> {
+  match shape {
+    &[o, c, kh, kw] if (kh, kw) == options.kernel => Ok((o, c * kh * kw)),
+    _ => Err(format!("conv2d: weight shape {:?} does not match kernel {:?}", shape, options.kernel))
+  }
+}
@@ -0,0 +1,77 @@
+use std::fmt;
+use std::str::FromStr;
+
+use super::Tensor;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Device {
+  #[default]
+  Cpu,
+  Gpu(usize)
+}
+
+impl Device {
+  pub fn is_available(&self) -> bool {
+    match self {
+      Device::Cpu => true,
+      Device::Gpu(_) => false
+    }
+  }
+
+  pub fn is_cpu(&self) -> bool {
+    matches!(self, Device::Cpu)
+  }
+}
+
+impl fmt::Display for Device {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Device::Cpu => write!(f, "cpu"),
+      Device::Gpu(id) => write!(f, "gpu:{}", id)
+    }
+  }
+}
+
+impl FromStr for Device {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let lower = s.trim().to_ascii_lowercase();
+    match lower.split_once(':') {
+      None if lower == "cpu" => Ok(Device::Cpu),
+      None if lower == "gpu" || lower == "cuda" => Ok(Device::Gpu(0)),
+      Some(("gpu" | "cuda", id)) => id.parse().map(Device::Gpu).map_err(|_| format!("Invalid device id in '{}'", s)),
+      _ => Err(format!("Unknown device '{}' (expected cpu, gpu or gpu:<id>)", s))
+    }
+  }
+}
+
+pub(crate) fn dispatch<R, F>(op: &str, devices: &[Device], cpu: F) -> Result<R, String>
+where
+  F: FnOnce() -> Result<R, String>
+{
+  let device = devices.first().copied().unwrap_or_default();
+  if let Some(other) = devices.iter().find(|&&d| d != device) {
+    return Err(format!("{}: expected all tensors on the same device, found {} and {}", op, device, other));
+  }
+
+  match device {
+    Device::Cpu => cpu(),
+    Device::Gpu(_) => Err(format!("{}: no {} kernel is available in this build", op, device))
+  }
+}
+
+impl<T: Clone> Tensor<T> {
+  pub fn to(&self, device: Device) -> Result<Tensor<T>, String> {
+    if device == self.device() {
+      return Ok(self.clone());
+    }
+    if !device.is_available() {
+      return Err(format!("Cannot move tensor to {}: device is not available in this build", device));
+    }
+
+    let mut tensor = self.contiguous();
+    tensor.device = device;
+    Ok(tensor)
+  }
+}
@@ -0,0 +1,80 @@
+use super::{IndexIter, Tensor};
+use crate::math::matrix::Matrix;
+use crate::math::scalar::Scalar;
+
+fn check_index_shape<T>(op: &str, target: &Tensor<T>, axis: usize, index: &Tensor<usize>) -> Result<(), String> {
+  if axis >= target.ndim() {
+    return Err(format!("{}: axis {} out of range for a tensor with {} dimensions", op, axis, target.ndim()));
+  }
+  let fits = index.ndim() == target.ndim()
+    && index.shape().iter().zip(target.shape()).enumerate().all(|(d, (i, t))| d == axis || i <= t);
+  if !fits {
+    return Err(format!("{}: index shape {:?} does not fit tensor shape {:?} along axis {}", op, index.shape(), target.shape(), axis));
+  }
+
+  Ok(())
+}
+
+impl<T: Clone> Tensor<T> {
+  pub fn index_select(&self, axis: usize, indices: &[usize]) -> Result<Tensor<T>, String> {
+    self.take(indices, axis)
+  }
+
+  pub fn gather(&self, axis: usize, index: &Tensor<usize>) -> Result<Tensor<T>, String> {
+    check_index_shape("gather", self, axis, index)?;
+
+    let data = IndexIter::new(index.shape())
+      .zip(index.iter())
+      .map(|(mut position, &i)| {
+        position[axis] = i;
+        self
+          .get(&position)
+          .cloned()
+          .ok_or_else(|| format!("gather: index {} out of bounds for axis {} with size {}", i, axis, self.shape()[axis]))
+      })
+      .collect::<Result<Vec<_>, _>>()?;
+
+    Tensor::from_vec(index.shape(), data)
+  }
+}
+
+impl<T: Scalar> Tensor<T> {
+  pub fn scatter_add(&self, axis: usize, index: &Tensor<usize>, src: &Tensor<T>) -> Result<Tensor<T>, String> {
+    check_index_shape("scatter_add", self, axis, index)?;
+    if src.ndim() != index.ndim() || index.shape().iter().zip(src.shape()).any(|(i, s)| i > s) {
+      return Err(format!("scatter_add: index shape {:?} does not fit source shape {:?}", index.shape(), src.shape()));
+    }
+
+    let mut out = self.contiguous();
+    for (position, &i) in IndexIter::new(index.shape()).zip(index.iter()) {
+      let value = *src.get(&position).expect("index shape fits source shape");
+      let mut target = position;
+      target[axis] = i;
+      let size = self.shape()[axis];
+      let slot = out
+        .get_mut(&target)
+        .ok_or_else(|| format!("scatter_add: index {} out of bounds for axis {} with size {}", i, axis, size))?;
+      *slot += value;
+    }
+
+    Ok(out)
+  }
+}
+
+impl<T: Clone> Matrix<T> {
+  pub fn index_select(&self, axis: usize, indices: &[usize]) -> Result<Matrix<T>, String> {
+    Tensor::from(self.clone()).index_select(axis, indices)?.try_into()
+  }
+
+  pub fn gather(&self, axis: usize, index: &Matrix<usize>) -> Result<Matrix<T>, String> {
+    Tensor::from(self.clone()).gather(axis, &Tensor::from(index.clone()))?.try_into()
+  }
+}
+
+impl<T: Scalar> Matrix<T> {
+  pub fn scatter_add(&self, axis: usize, index: &Matrix<usize>, src: &Matrix<T>) -> Result<Matrix<T>, String> {
+    Tensor::from(self.clone())
+      .scatter_add(axis, &Tensor::from(index.clone()), &Tensor::from(src.clone()))?
+      .try_into()
+  }
+}
@@ -0,0 +1,480 @@
+use std::collections::{HashMap, HashSet};
+use std::ops::{Add, Div, Mul, Neg, Sub};
+use std::rc::Rc;
+use std::sync::Arc;
+
+use super::broadcast::broadcast_shape;
+use super::linalg::matmul_shape;
+use super::reduce::{normalize_axes, reduced_shape};
+use super::Tensor;
+use crate::math::scalar::Float;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UnaryOp<T = f64> {
+  Neg,
+  Exp,
+  Ln,
+  Sqrt,
+  Tanh,
+  Relu,
+  Sigmoid,
+  Powi(i32),
+  AddScalar(T),
+  MulScalar(T)
+}
+
+impl<T: Float> UnaryOp<T> {
+  fn apply(&self, x: T) -> T {
+    match *self {
+      UnaryOp::Neg => -x,
+      UnaryOp::Exp => x.exp(),
+      UnaryOp::Ln => x.ln(),
+      UnaryOp::Sqrt => x.sqrt(),
+      UnaryOp::Tanh => x.tanh(),
+      UnaryOp::Relu => if x > T::zero() { x } else { T::zero() },
+      UnaryOp::Sigmoid => T::one() / (T::one() + (-x).exp()),
+      UnaryOp::Powi(n) => x.powi(n),
+      UnaryOp::AddScalar(s) => x + s,
+      UnaryOp::MulScalar(s) => x * s
+    }
+  }
+
+  fn name(&self) -> &'static str {
+    match self {
+      UnaryOp::Neg => "neg",
+      UnaryOp::Exp => "exp",
+      UnaryOp::Ln => "ln",
+      UnaryOp::Sqrt => "sqrt",
+      UnaryOp::Tanh => "tanh",
+      UnaryOp::Relu => "relu",
+      UnaryOp::Sigmoid => "sigmoid",
+      UnaryOp::Powi(_) => "powi",
+      UnaryOp::AddScalar(_) => "add_scalar",
+      UnaryOp::MulScalar(_) => "mul_scalar"
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BinaryOp {
+  Add,
+  Sub,
+  Mul,
+  Div,
+  Maximum,
+  Minimum
+}
+
+impl BinaryOp {
+  fn apply<T: Float>(&self, a: T, b: T) -> T {
+    match self {
+      BinaryOp::Add => a + b,
+      BinaryOp::Sub => a - b,
+      BinaryOp::Mul => a * b,
+      BinaryOp::Div => a / b,
+      BinaryOp::Maximum => a.max(b),
+      BinaryOp::Minimum => a.min(b)
+    }
+  }
+
+  fn name(&self) -> &'static str {
+    match self {
+      BinaryOp::Add => "add",
+      BinaryOp::Sub => "sub",
+      BinaryOp::Mul => "mul",
+      BinaryOp::Div => "div",
+      BinaryOp::Maximum => "maximum",
+      BinaryOp::Minimum => "minimum"
+    }
+  }
+}
+
+enum Expr<T> {
+  Input(Tensor<T>),
+  Unary(UnaryOp<T>, LazyTensor<T>),
+  Binary(BinaryOp, LazyTensor<T>, LazyTensor<T>),
+  MatMul(LazyTensor<T>, LazyTensor<T>),
+  Sum(LazyTensor<T>, Vec<usize>, bool)
+}
+
+struct Node<T> {
+  expr: Expr<T>,
+  shape: Vec<usize>
+}
+
+#[derive(Clone)]
+pub struct LazyTensor<T = f64> {
+  node: Rc<Node<T>>
+}
+
+impl<T> LazyTensor<T> {
+  fn key(&self) -> usize {
+    Rc::as_ptr(&self.node) as usize
+  }
+
+  pub fn shape(&self) -> &[usize] {
+    &self.node.shape
+  }
+
+  fn children(&self) -> Vec<&LazyTensor<T>> {
+    match &self.node.expr {
+      Expr::Input(_) => Vec::new(),
+      Expr::Unary(_, a) | Expr::Sum(a, _, _) => vec![a],
+      Expr::Binary(_, a, b) | Expr::MatMul(a, b) => vec![a, b]
+    }
+  }
+
+  fn is_elementwise(&self) -> bool {
+    matches!(self.node.expr, Expr::Unary(..) | Expr::Binary(..))
+  }
+}
+
+impl<T: Float> Tensor<T> {
+  pub fn lazy(&self) -> LazyTensor<T> {
+    LazyTensor::new(Expr::Input(self.clone()), self.shape().to_vec())
+  }
+}
+
+impl<T: Float> From<Tensor<T>> for LazyTensor<T> {
+  fn from(tensor: Tensor<T>) -> Self {
+    let shape = tensor.shape().to_vec();
+    LazyTensor::new(Expr::Input(tensor), shape)
+  }
+}
+
+impl<T: Float> LazyTensor<T> {
+  fn new(expr: Expr<T>, shape: Vec<usize>) -> Self {
+    LazyTensor { node: Rc::new(Node { expr, shape }) }
+  }
+
+  pub fn unary(&self, op: UnaryOp<T>) -> LazyTensor<T> {
+    LazyTensor::new(Expr::Unary(op, self.clone()), self.shape().to_vec())
+  }
+
+  pub fn binary(&self, op: BinaryOp, other: &LazyTensor<T>) -> Result<LazyTensor<T>, String> {
+    let shape = broadcast_shape(self.shape(), other.shape())?;
+    Ok(LazyTensor::new(Expr::Binary(op, self.clone(), other.clone()), shape))
+  }
+
+  pub fn exp(&self) -> LazyTensor<T> {
+    self.unary(UnaryOp::Exp)
+  }
+
+  pub fn ln(&self) -> LazyTensor<T> {
+    self.unary(UnaryOp::Ln)
+  }
+
+  pub fn sqrt(&self) -> LazyTensor<T> {
+    self.unary(UnaryOp::Sqrt)
+  }
+
+  pub fn tanh(&self) -> LazyTensor<T> {
+    self.unary(UnaryOp::Tanh)
+  }
+
+  pub fn relu(&self) -> LazyTensor<T> {
+    self.unary(UnaryOp::Relu)
+  }
+
+  pub fn sigmoid(&self) -> LazyTensor<T> {
+    self.unary(UnaryOp::Sigmoid)
+  }
+
+  pub fn powi(&self, n: i32) -> LazyTensor<T> {
+    self.unary(UnaryOp::Powi(n))
+  }
+
+  pub fn add_scalar(&self, scalar: T) -> LazyTensor<T> {
+    self.unary(UnaryOp::AddScalar(scalar))
+  }
+
+  pub fn mul_scalar(&self, scalar: T) -> LazyTensor<T> {
+    self.unary(UnaryOp::MulScalar(scalar))
+  }
+
+  pub fn maximum(&self, other: &LazyTensor<T>) -> Result<LazyTensor<T>, String> {
+    self.binary(BinaryOp::Maximum, other)
+  }
+
+  pub fn minimum(&self, other: &LazyTensor<T>) -> Result<LazyTensor<T>, String> {
+    self.binary(BinaryOp::Minimum, other)
+  }
+
+  pub fn matmul(&self, other: &LazyTensor<T>) -> Result<LazyTensor<T>, String> {
+    let shape = matmul_shape(self.shape(), other.shape())?;
+    Ok(LazyTensor::new(Expr::MatMul(self.clone(), other.clone()), shape))
+  }
+
+  pub fn sum(&self, axes: &[usize], keepdims: bool) -> Result<LazyTensor<T>, String> {
+    let shape = reduced_shape(self.shape(), &normalize_axes(self.shape(), axes)?, keepdims);
+    Ok(LazyTensor::new(Expr::Sum(self.clone(), axes.to_vec(), keepdims), shape))
+  }
+
+  pub fn explain(&self) -> Vec<String> {
+    let plan = Plan::compile(self);
+    plan.steps.iter().zip(&plan.shapes).filter_map(|(step, shape)| step.describe(&plan.shapes, shape)).collect()
+  }
+
+  pub fn eval(&self) -> Result<Tensor<T>, String> {
+    Plan::compile(self).run()
+  }
+}
+
+enum Instr<T> {
+  Load(usize),
+  Unary(UnaryOp<T>),
+  Binary(BinaryOp)
+}
+
+enum Step<T> {
+  Input(Tensor<T>),
+  MatMul(usize, usize),
+  Sum(usize, Vec<usize>, bool),
+  Fused { inputs: Vec<usize>, program: Vec<Instr<T>> }
+}
+
+impl<T: Float> Step<T> {
+  fn inputs(&self) -> Vec<usize> {
+    match self {
+      Step::Input(_) => Vec::new(),
+      Step::MatMul(a, b) => vec![*a, *b],
+      Step::Sum(a, _, _) => vec![*a],
+      Step::Fused { inputs, .. } => inputs.clone()
+    }
+  }
+
+  fn describe(&self, shapes: &[Vec<usize>], shape: &[usize]) -> Option<String> {
+    match self {
+      Step::Input(_) => None,
+      Step::MatMul(a, b) => Some(format!("matmul {:?} x {:?} -> {:?}", shapes[*a], shapes[*b], shape)),
+      Step::Sum(a, axes, _) => Some(format!("sum {:?} over {:?} -> {:?}", shapes[*a], axes, shape)),
+      Step::Fused { inputs, program } => {
+        let ops: Vec<&str> = program
+          .iter()
+          .filter_map(|instr| match instr {
+            Instr::Load(_) => None,
+            Instr::Unary(op) => Some(op.name()),
+            Instr::Binary(op) => Some(op.name())
+          })
+          .collect();
+        Some(format!("fused [{}] over {} inputs -> {:?}", ops.join(", "), inputs.len(), shape))
+      }
+    }
+  }
+}
+
+struct Plan<T> {
+  steps: Vec<Step<T>>,
+  shapes: Vec<Vec<usize>>
+}
+
+struct Planner<T> {
+  uses: HashMap<usize, usize>,
+  slots: HashMap<usize, usize>,
+  plan: Plan<T>
+}
+
+impl<T: Float> Plan<T> {
+  fn compile(root: &LazyTensor<T>) -> Plan<T> {
+    let mut uses = HashMap::new();
+    let mut seen = HashSet::new();
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+      if !seen.insert(node.key()) {
+        continue;
+      }
+      for child in node.children() {
+        *uses.entry(child.key()).or_insert(0) += 1;
+        stack.push(child);
+      }
+    }
+
+    let mut planner = Planner {
+      uses,
+      slots: HashMap::new(),
+      plan: Plan { steps: Vec::new(), shapes: Vec::new() }
+    };
+    planner.schedule(root);
+    planner.plan
+  }
+
+  fn run(self) -> Result<Tensor<T>, String> {
+    let mut last_use: Vec<usize> = (0..self.steps.len()).collect();
+    for (i, step) in self.steps.iter().enumerate() {
+      for slot in step.inputs() {
+        last_use[slot] = i;
+      }
+    }
+
+    let mut values: Vec<Option<Tensor<T>>> = (0..self.steps.len()).map(|_| None).collect();
+    for (i, (step, shape)) in self.steps.into_iter().zip(&self.shapes).enumerate() {
+      let get = |values: &[Option<Tensor<T>>], slot: usize| values[slot].clone().expect("inputs are scheduled before use");
+      let value = match step {
+        Step::Input(tensor) => tensor,
+        Step::MatMul(a, b) => get(&values, a).matmul(&get(&values, b))?,
+        Step::Sum(a, axes, keepdims) => get(&values, a).sum(&axes, keepdims)?,
+        Step::Fused { inputs, program } => {
+          let tensors = inputs
+            .iter()
+            .map(|&slot| if last_use[slot] == i { values[slot].take() } else { values[slot].clone() })
+            .map(|value| value.expect("inputs are scheduled before use"))
+            .collect();
+          run_fused(tensors, &program, shape)?
+        }
+      };
+
+      for (slot, &last) in last_use.iter().enumerate().take(i) {
+        if last == i {
+          values[slot] = None;
+        }
+      }
+      values[i] = Some(value);
+    }
+
+    Ok(values.pop().flatten().expect("the root is scheduled last"))
+  }
+}
+
+impl<T: Float> Planner<T> {
+  fn schedule(&mut self, node: &LazyTensor<T>) -> usize {
+    if let Some(&slot) = self.slots.get(&node.key()) {
+      return slot;
+    }
+
+    let step = match &node.node.expr {
+      Expr::Input(tensor) => Step::Input(tensor.clone()),
+      Expr::MatMul(a, b) => Step::MatMul(self.schedule(a), self.schedule(b)),
+      Expr::Sum(a, axes, keepdims) => Step::Sum(self.schedule(a), axes.clone(), *keepdims),
+      Expr::Unary(..) | Expr::Binary(..) => {
+        let (mut inputs, mut program) = (Vec::new(), Vec::new());
+        self.emit(node, true, &mut inputs, &mut program);
+        Step::Fused { inputs, program }
+      }
+    };
+
+    let slot = self.plan.steps.len();
+    self.plan.steps.push(step);
+    self.plan.shapes.push(node.shape().to_vec());
+    self.slots.insert(node.key(), slot);
+    slot
+  }
+
+  fn emit(&mut self, node: &LazyTensor<T>, root: bool, inputs: &mut Vec<usize>, program: &mut Vec<Instr<T>>) {
+    let fusable = node.is_elementwise() && (root || self.uses.get(&node.key()) == Some(&1));
+    if !fusable {
+      let slot = self.schedule(node);
+      let k = inputs.iter().position(|&s| s == slot).unwrap_or_else(|| {
+        inputs.push(slot);
+        inputs.len() - 1
+      });
+      program.push(Instr::Load(k));
+      return;
+    }
+
+    match &node.node.expr {
+      Expr::Unary(op, a) => {
+        self.emit(a, false, inputs, program);
+        program.push(Instr::Unary(*op));
+      }
+      Expr::Binary(op, a, b) => {
+        self.emit(a, false, inputs, program);
+        self.emit(b, false, inputs, program);
+        program.push(Instr::Binary(*op));
+      }
+      _ => unreachable!("only elementwise nodes are fused")
+    }
+  }
+}
+
+fn run_fused<T: Float>(mut tensors: Vec<Tensor<T>>, program: &[Instr<T>], shape: &[usize]) -> Result<Tensor<T>, String> {
+  let len: usize = shape.iter().product();
+  let target = tensors.iter().position(|t| {
+    t.shape() == shape && t.is_contiguous() && t.offset == 0 && t.data.len() == len && Arc::strong_count(&t.data) == 1
+  });
+
+  let mut out = match target {
+    Some(k) => {
+      let data = std::mem::replace(&mut tensors[k].data, Arc::new(Vec::new()));
+      Arc::try_unwrap(data).unwrap_or_else(|data| (*data).clone())
+    }
+    None => vec![T::zero(); len]
+  };
+
+  let views = tensors
+    .iter()
+    .enumerate()
+    .map(|(k, t)| if Some(k) == target { Ok(None) } else { t.broadcast_to(shape).map(Some) })
+    .collect::<Result<Vec<_>, _>>()?;
+  let mut offsets: Vec<_> = views.iter().map(|view| view.as_ref().map(|v| v.offsets())).collect();
+
+  let mut registers = vec![T::zero(); tensors.len()];
+  let mut stack = Vec::with_capacity(program.len());
+  for slot in out.iter_mut() {
+    for (k, register) in registers.iter_mut().enumerate() {
+      *register = match (&views[k], &mut offsets[k]) {
+        (Some(view), Some(iter)) => view.data[iter.next().expect("broadcast view covers the output")],
+        _ => *slot
+      };
+    }
+
+    stack.clear();
+    for instr in program {
+      match instr {
+        Instr::Load(k) => stack.push(registers[*k]),
+        Instr::Unary(op) => {
+          let x = stack.pop().expect("unary operand");
+          stack.push(op.apply(x));
+        }
+        Instr::Binary(op) => {
+          let b = stack.pop().expect("binary rhs");
+          let a = stack.pop().expect("binary lhs");
+          stack.push(op.apply(a, b));
+        }
+      }
+    }
+    *slot = stack.pop().expect("program leaves one value");
+  }
+
+  Tensor::from_vec(shape, out)
+}
+
+macro_rules! impl_lazy_op {
+  ($trait:ident, $method:ident, $op:ident) => {
+    impl<T: Float> $trait for &LazyTensor<T> {
+      type Output = Result<LazyTensor<T>, String>;
+
+      fn $method(self, rhs: Self) -> Self::Output {
+        self.binary(BinaryOp::$op, rhs)
+      }
+    }
+
+    impl<T: Float> $trait for LazyTensor<T> {
+      type Output = Result<LazyTensor<T>, String>;
+
+      fn $method(self, rhs: Self) -> Self::Output {
+        (&self).$method(&rhs)
+      }
+    }
+  };
+}
+
+impl_lazy_op!(Add, add, Add);
+impl_lazy_op!(Sub, sub, Sub);
+impl_lazy_op!(Mul, mul, Mul);
+impl_lazy_op!(Div, div, Div);
+
+impl<T: Float> Neg for &LazyTensor<T> {
+  type Output = LazyTensor<T>;
+
+  fn neg(self) -> LazyTensor<T> {
+    self.unary(UnaryOp::Neg)
+  }
+}
+
+impl<T: Float> Neg for LazyTensor<T> {
+  type Output = LazyTensor<T>;
+
+  fn neg(self) -> LazyTensor<T> {
+    -&self
+  }
+}
@@ -0,0 +1,52 @@
+use super::broadcast::broadcast_shape;
+use super::device::dispatch;
+use super::Tensor;
+use crate::math::scalar::Scalar;
+use crate::math::shape::Shape;
+
+pub(crate) fn matmul_shape(lhs: &[usize], rhs: &[usize]) -> Result<Vec<usize>, String> {
+  if lhs.len() < 2 || rhs.len() < 2 {
+    return Err(format!("matmul: expected at least 2 dimensions, found shapes {:?} and {:?}", lhs, rhs));
+  }
+
+  let (lhs_batch, lhs_mat) = lhs.split_at(lhs.len() - 2);
+  let (rhs_batch, rhs_mat) = rhs.split_at(rhs.len() - 2);
+  Shape::new(lhs_mat[0], lhs_mat[1]).check_matmul(&Shape::new(rhs_mat[0], rhs_mat[1]))?;
+
+  let mut shape = broadcast_shape(lhs_batch, rhs_batch)?;
+  shape.extend([lhs_mat[0], rhs_mat[1]]);
+  Ok(shape)
+}
+
+impl<T: Scalar> Tensor<T> {
+  pub fn matmul(&self, other: &Tensor<T>) -> Result<Tensor<T>, String> {
+    let shape = matmul_shape(self.shape(), other.shape())?;
+    dispatch("matmul", &[self.device(), other.device()], || self.matmul_cpu(other, &shape))
+  }
+
+  fn matmul_cpu(&self, other: &Tensor<T>, shape: &[usize]) -> Result<Tensor<T>, String> {
+    let (batch, mat) = shape.split_at(shape.len() - 2);
+    let (m, k, n) = (mat[0], self.shape()[self.ndim() - 1], mat[1]);
+
+    let lhs = self.broadcast_to(&[batch, &[m, k]].concat())?.contiguous();
+    let rhs = other.broadcast_to(&[batch, &[k, n]].concat())?.contiguous();
+    let (a, b) = (lhs.storage(), rhs.storage());
+
+    let batches: usize = batch.iter().product();
+    let mut data = vec![T::zero(); batches * m * n];
+    for p in 0..batches {
+      let (a, b) = (&a[p * m * k..], &b[p * k * n..]);
+      let out = &mut data[p * m * n..(p + 1) * m * n];
+      for i in 0..m {
+        for l in 0..k {
+          let x = a[i * k + l];
+          for j in 0..n {
+            out[i * n + j] += x * b[l * n + j];
+          }
+        }
+      }
+    }
+
+    Tensor::from_vec(shape, data)
+  }
+}
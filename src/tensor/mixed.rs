@@ -0,0 +1,44 @@
+use super::Tensor;
+use crate::math::half::{bf16, f16, HalfFloat};
+
+impl Tensor<f32> {
+  pub fn to_half<H: HalfFloat>(&self) -> Tensor<H> {
+    self.map(|&x| H::from_f32(x))
+  }
+
+  pub fn to_f16(&self) -> Tensor<f16> {
+    self.to_half()
+  }
+
+  pub fn to_bf16(&self) -> Tensor<bf16> {
+    self.to_half()
+  }
+}
+
+macro_rules! impl_mixed_precision {
+  ($($t:ty),*) => {
+    $(impl Tensor<$t> {
+      pub fn to_f32(&self) -> Tensor<f32> {
+        self.map(|&x| x.to_f32())
+      }
+
+      pub fn matmul(&self, other: &Tensor<$t>) -> Result<Tensor<$t>, String> {
+        Ok(self.to_f32().matmul(&other.to_f32())?.to_half())
+      }
+
+      pub fn sum(&self, axes: &[usize], keepdims: bool) -> Result<Tensor<$t>, String> {
+        self.reduce(axes, keepdims, 0f32, |acc, &x| acc + x.to_f32(), |acc, _| <$t>::from_f32(acc))
+      }
+
+      pub fn mean(&self, axes: &[usize], keepdims: bool) -> Result<Tensor<$t>, String> {
+        self.reduce(axes, keepdims, 0f32, |acc, &x| acc + x.to_f32(), |acc, n| <$t>::from_f32(acc / n as f32))
+      }
+
+      pub fn sum_all(&self) -> f32 {
+        self.iter().map(|x| x.to_f32()).sum()
+      }
+    })*
+  };
+}
+
+impl_mixed_precision!(f16, bf16);
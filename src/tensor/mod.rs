@@ -0,0 +1,444 @@
+use std::fmt;
+use std::sync::Arc;
+
+use self::device::Device;
+use crate::math::matrix::Matrix;
+use crate::math::shape::ShapeError;
+use crate::math::vector::Vector;
+
+pub mod broadcast;
+pub mod concat;
+pub mod conv;
+pub mod device;
+pub mod gather;
+pub mod lazy;
+pub mod linalg;
+pub mod mixed;
+pub mod pad;
+pub mod pool;
+pub mod quant;
+pub mod reduce;
+pub mod slice;
+pub mod softmax;
+pub mod topk;
+
+#[derive(Clone, Debug)]
+pub struct Tensor<T = f64> {
+  data: Arc<Vec<T>>,
+  shape: Vec<usize>,
+  strides: Vec<usize>,
+  offset: usize,
+  device: Device
+}
+
+pub(crate) fn contiguous_strides(shape: &[usize]) -> Vec<usize> {
+  let mut strides = vec![1; shape.len()];
+  for i in (0..shape.len().saturating_sub(1)).rev() {
+    strides[i] = strides[i + 1] * shape[i + 1];
+  }
+
+  strides
+}
+
+impl<T> Tensor<T> {
+  pub fn from_vec(shape: &[usize], data: Vec<T>) -> Result<Self, String> {
+    let len = shape.iter().try_fold(1usize, |len, &dim| len.checked_mul(dim));
+    if len != Some(data.len()) {
+      return Err(ShapeError::TensorSize { shape: shape.to_vec(), len: data.len() }.into());
+    }
+
+    Ok(Tensor {
+      data: Arc::new(data),
+      shape: shape.to_vec(),
+      strides: contiguous_strides(shape),
+      offset: 0,
+      device: Device::Cpu
+    })
+  }
+
+  pub fn scalar(value: T) -> Self {
+    Tensor {
+      data: Arc::new(vec![value]),
+      shape: Vec::new(),
+      strides: Vec::new(),
+      offset: 0,
+      device: Device::Cpu
+    }
+  }
+
+  pub fn from_fn<F>(shape: &[usize], mut f: F) -> Self
+  where
+    F: FnMut(&[usize]) -> T
+  {
+    let data = IndexIter::new(shape).map(|index| f(&index)).collect();
+
+    Tensor {
+      data: Arc::new(data),
+      shape: shape.to_vec(),
+      strides: contiguous_strides(shape),
+      offset: 0,
+      device: Device::Cpu
+    }
+  }
+
+  pub(crate) fn view(&self, shape: Vec<usize>, strides: Vec<usize>, offset: usize) -> Self {
+    Tensor {
+      data: self.data.clone(),
+      shape,
+      strides,
+      offset,
+      device: self.device
+    }
+  }
+
+  pub fn full(shape: &[usize], value: T) -> Self
+  where
+    T: Clone
+  {
+    Self::from_fn(shape, |_| value.clone())
+  }
+
+  pub fn zeros(shape: &[usize]) -> Self
+  where
+    T: Clone + Default
+  {
+    Self::full(shape, T::default())
+  }
+
+  pub fn device(&self) -> Device {
+    self.device
+  }
+
+  pub fn shape(&self) -> &[usize] {
+    &self.shape
+  }
+
+  pub fn strides(&self) -> &[usize] {
+    &self.strides
+  }
+
+  pub fn ndim(&self) -> usize {
+    self.shape.len()
+  }
+
+  pub fn len(&self) -> usize {
+    self.shape.iter().product()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  pub fn is_contiguous(&self) -> bool {
+    self.strides == contiguous_strides(&self.shape)
+  }
+
+  pub(crate) fn storage(&self) -> &Arc<Vec<T>> {
+    &self.data
+  }
+
+  pub(crate) fn offset(&self) -> usize {
+    self.offset
+  }
+
+  pub fn shares_storage(&self, other: &Self) -> bool {
+    Arc::ptr_eq(&self.data, &other.data)
+  }
+
+  fn flat_offset(&self, index: &[usize]) -> Option<usize> {
+    if index.len() != self.shape.len() || index.iter().zip(self.shape.iter()).any(|(i, d)| i >= d) {
+      return None;
+    }
+
+    Some(self.offset + index.iter().zip(self.strides.iter()).map(|(i, s)| i * s).sum::<usize>())
+  }
+
+  pub fn get(&self, index: &[usize]) -> Option<&T> {
+    self.flat_offset(index).map(|offset| &self.data[offset])
+  }
+
+  pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+    self.offsets().map(move |offset| &self.data[offset])
+  }
+
+  pub(crate) fn offsets(&self) -> OffsetIter {
+    OffsetIter::new(&self.shape, &self.strides, self.offset)
+  }
+
+  pub fn map<U, F>(&self, f: F) -> Tensor<U>
+  where
+    F: Fn(&T) -> U
+  {
+    Tensor {
+      data: Arc::new(self.iter().map(f).collect()),
+      shape: self.shape.clone(),
+      strides: contiguous_strides(&self.shape),
+      offset: 0,
+      device: self.device
+    }
+  }
+}
+
+impl<T: Clone> Tensor<T> {
+  pub fn to_vec(&self) -> Vec<T> {
+    self.iter().cloned().collect()
+  }
+
+  pub fn contiguous(&self) -> Self {
+    if self.is_contiguous() && self.offset == 0 && self.data.len() == self.len() {
+      return self.clone();
+    }
+
+    Tensor {
+      data: Arc::new(self.to_vec()),
+      shape: self.shape.clone(),
+      strides: contiguous_strides(&self.shape),
+      offset: 0,
+      device: self.device
+    }
+  }
+
+  pub fn into_vec(self) -> Vec<T> {
+    if self.is_contiguous() && self.offset == 0 && self.data.len() == self.len() {
+      Arc::try_unwrap(self.data).unwrap_or_else(|data| (*data).clone())
+    } else {
+      self.to_vec()
+    }
+  }
+
+  pub fn as_mut_slice(&mut self) -> &mut [T] {
+    if !(self.is_contiguous() && self.offset == 0 && self.data.len() == self.len()) {
+      *self = self.contiguous();
+    }
+    Arc::make_mut(&mut self.data).as_mut_slice()
+  }
+
+  pub fn get_mut(&mut self, index: &[usize]) -> Option<&mut T> {
+    let offset = self.flat_offset(index)?;
+    Some(&mut Arc::make_mut(&mut self.data)[offset])
+  }
+
+  pub fn reshape(&self, shape: &[usize]) -> Result<Self, String> {
+    let len: usize = shape.iter().product();
+    if len != self.len() {
+      return Err(ShapeError::TensorSize { shape: shape.to_vec(), len: self.len() }.into());
+    }
+
+    let base = self.contiguous();
+    Ok(Tensor {
+      data: base.data,
+      shape: shape.to_vec(),
+      strides: contiguous_strides(shape),
+      offset: base.offset,
+      device: base.device
+    })
+  }
+
+  pub fn permute(&self, axes: &[usize]) -> Result<Self, String> {
+    let mut seen = vec![false; self.ndim()];
+    if axes.len() != self.ndim() || axes.iter().any(|&a| a >= self.ndim() || std::mem::replace(&mut seen[a], true)) {
+      return Err(format!("Invalid permutation {:?} for a tensor with {} dimensions", axes, self.ndim()));
+    }
+
+    Ok(Tensor {
+      data: self.data.clone(),
+      shape: axes.iter().map(|&a| self.shape[a]).collect(),
+      strides: axes.iter().map(|&a| self.strides[a]).collect(),
+      offset: self.offset,
+      device: self.device
+    })
+  }
+
+  pub fn swap_axes(&self, a: usize, b: usize) -> Result<Self, String> {
+    let mut axes: Vec<usize> = (0..self.ndim()).collect();
+    if a >= self.ndim() || b >= self.ndim() {
+      return Err(format!("Cannot swap axes {} and {} of a tensor with {} dimensions", a, b, self.ndim()));
+    }
+    axes.swap(a, b);
+    self.permute(&axes)
+  }
+
+  pub fn transpose(&self) -> Self {
+    let axes: Vec<usize> = (0..self.ndim()).rev().collect();
+    self.permute(&axes).expect("reversed axes are always a valid permutation")
+  }
+
+  pub fn zip_map<U, V, F>(&self, other: &Tensor<U>, f: F) -> Result<Tensor<V>, String>
+  where
+    F: Fn(&T, &U) -> V
+  {
+    let shape = broadcast::broadcast_shape(&self.shape, &other.shape)?;
+    let lhs = self.broadcast_to(&shape)?;
+    let rhs = other.broadcast_to(&shape)?;
+
+    device::dispatch("zip_map", &[self.device, other.device], || {
+      let data = lhs.iter().zip(rhs.iter()).map(|(a, b)| f(a, b)).collect();
+      Ok(Tensor {
+        data: Arc::new(data),
+        strides: contiguous_strides(&shape),
+        shape,
+        offset: 0,
+        device: self.device
+      })
+    })
+  }
+}
+
+impl<T: PartialEq> PartialEq for Tensor<T> {
+  fn eq(&self, other: &Self) -> bool {
+    self.shape == other.shape && self.iter().zip(other.iter()).all(|(a, b)| a == b)
+  }
+}
+
+impl<T> From<Matrix<T>> for Tensor<T> {
+  fn from(matrix: Matrix<T>) -> Self {
+    let shape = vec![matrix.rows, matrix.cols];
+    Tensor {
+      data: Arc::new(matrix.data),
+      strides: contiguous_strides(&shape),
+      shape,
+      offset: 0,
+      device: Device::Cpu
+    }
+  }
+}
+
+impl<T> From<Vector<T>> for Tensor<T> {
+  fn from(vector: Vector<T>) -> Self {
+    let shape = vec![vector.len()];
+    Tensor {
+      data: Arc::new(vector.data),
+      strides: vec![1],
+      shape,
+      offset: 0,
+      device: Device::Cpu
+    }
+  }
+}
+
+impl<T: Clone> TryFrom<Tensor<T>> for Matrix<T> {
+  type Error = String;
+
+  fn try_from(tensor: Tensor<T>) -> Result<Self, Self::Error> {
+    let (rows, cols) = match tensor.shape() {
+      [] => (1, 1),
+      [n] => (1, *n),
+      [r, c] => (*r, *c),
+      shape => return Err(format!("Cannot convert a tensor of shape {:?} into a Matrix", shape))
+    };
+
+    Matrix::from_vec(rows, cols, tensor.into_vec())
+  }
+}
+
+pub(crate) struct IndexIter {
+  shape: Vec<usize>,
+  index: Vec<usize>,
+  remaining: usize
+}
+
+impl IndexIter {
+  pub(crate) fn new(shape: &[usize]) -> Self {
+    IndexIter {
+      shape: shape.to_vec(),
+      index: vec![0; shape.len()],
+      remaining: shape.iter().product()
+    }
+  }
+}
+
+impl Iterator for IndexIter {
+  type Item = Vec<usize>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    if self.remaining == 0 {
+      return None;
+    }
+
+    let current = self.index.clone();
+    self.remaining -= 1;
+    for axis in (0..self.shape.len()).rev() {
+      self.index[axis] += 1;
+      if self.index[axis] < self.shape[axis] {
+        break;
+      }
+      self.index[axis] = 0;
+    }
+
+    Some(current)
+  }
+}
+
+pub(crate) struct OffsetIter {
+  shape: Vec<usize>,
+  strides: Vec<usize>,
+  index: Vec<usize>,
+  offset: usize,
+  remaining: usize
+}
+
+impl OffsetIter {
+  fn new(shape: &[usize], strides: &[usize], offset: usize) -> Self {
+    OffsetIter {
+      shape: shape.to_vec(),
+      strides: strides.to_vec(),
+      index: vec![0; shape.len()],
+      offset,
+      remaining: shape.iter().product()
+    }
+  }
+}
+
+impl Iterator for OffsetIter {
+  type Item = usize;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    if self.remaining == 0 {
+      return None;
+    }
+
+    let current = self.offset;
+    self.remaining -= 1;
+    for axis in (0..self.shape.len()).rev() {
+      self.index[axis] += 1;
+      self.offset += self.strides[axis];
+      if self.index[axis] < self.shape[axis] {
+        break;
+      }
+      self.offset -= self.strides[axis] * self.shape[axis];
+      self.index[axis] = 0;
+    }
+
+    Some(current)
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    (self.remaining, Some(self.remaining))
+  }
+}
+
+impl<T: fmt::Display> Tensor<T> {
+  fn write_axis(&self, f: &mut fmt::Formatter<'_>, axis: usize, offset: usize) -> fmt::Result {
+    if axis == self.ndim() {
+      return match f.precision() {
+        Some(p) => write!(f, "{:.*}", p, self.data[offset]),
+        None => write!(f, "{}", self.data[offset])
+      };
+    }
+
+    write!(f, "[")?;
+    for i in 0..self.shape[axis] {
+      if i > 0 {
+        write!(f, ", ")?;
+      }
+      self.write_axis(f, axis + 1, offset + i * self.strides[axis])?;
+    }
+    write!(f, "]")
+  }
+}
+
+impl<T: fmt::Display> fmt::Display for Tensor<T> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    self.write_axis(f, 0, self.offset)
+  }
+}
@@ -0,0 +1,66 @@
+use super::Tensor;
+use crate::math::matrix::Matrix;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PadMode<T = f64> {
+  Constant(T),
+  Reflect,
+  Replicate
+}
+
+fn source_coord<T>(i: usize, before: usize, n: usize, mode: &PadMode<T>) -> Option<usize> {
+  let j = i as isize - before as isize;
+  if (0..n as isize).contains(&j) {
+    return Some(j as usize);
+  }
+
+  match mode {
+    PadMode::Constant(_) => None,
+    PadMode::Replicate => Some(j.clamp(0, n as isize - 1) as usize),
+    PadMode::Reflect if n == 1 => Some(0),
+    PadMode::Reflect => {
+      let period = 2 * (n as isize - 1);
+      let r = j.rem_euclid(period);
+      Some(if r < n as isize { r } else { period - r } as usize)
+    }
+  }
+}
+
+pub(crate) fn padded_shape<T>(shape: &[usize], pads: &[(usize, usize)], mode: &PadMode<T>) -> Result<Vec<usize>, String> {
+  if pads.len() != shape.len() {
+    return Err(format!("pad: expected {} (before, after) pairs, found {}", shape.len(), pads.len()));
+  }
+  if !matches!(mode, PadMode::Constant(_)) && shape.iter().zip(pads).any(|(&n, &(b, a))| n == 0 && b + a > 0) {
+    return Err(format!("pad: cannot reflect or replicate an empty axis of shape {:?}", shape));
+  }
+
+  Ok(shape.iter().zip(pads).map(|(n, (b, a))| n + b + a).collect())
+}
+
+pub(crate) fn pad_source<T>(index: &[usize], shape: &[usize], pads: &[(usize, usize)], mode: &PadMode<T>) -> Option<Vec<usize>> {
+  index
+    .iter()
+    .zip(shape)
+    .zip(pads)
+    .map(|((&i, &n), &(before, _))| source_coord(i, before, n, mode))
+    .collect()
+}
+
+impl<T: Clone> Tensor<T> {
+  pub fn pad(&self, pads: &[(usize, usize)], mode: PadMode<T>) -> Result<Tensor<T>, String> {
+    let shape = padded_shape(self.shape(), pads, &mode)?;
+    Ok(Tensor::from_fn(&shape, |index| match pad_source(index, self.shape(), pads, &mode) {
+      Some(source) => self.get(&source).expect("source index within shape").clone(),
+      None => match &mode {
+        PadMode::Constant(value) => value.clone(),
+        _ => unreachable!("only constant padding leaves positions without a source")
+      }
+    }))
+  }
+}
+
+impl<T: Clone> Matrix<T> {
+  pub fn pad(&self, rows: (usize, usize), cols: (usize, usize), mode: PadMode<T>) -> Result<Matrix<T>, String> {
+    Tensor::from(self.clone()).pad(&[rows, cols], mode)?.try_into()
+  }
+}
@@ -0,0 +1,62 @@
+use super::conv::Conv2dOptions;
+use super::Tensor;
+use crate::math::scalar::{Float, Scalar};
+
+impl Conv2dOptions {
+  pub fn pooling(kernel: (usize, usize)) -> Self {
+    Conv2dOptions {
+      stride: kernel,
+      ..Conv2dOptions::new(kernel)
+    }
+  }
+}
+
+impl<T: Scalar + PartialOrd> Tensor<T> {
+  pub fn max_pool2d(&self, options: &Conv2dOptions) -> Result<(Tensor<T>, Tensor<usize>), String> {
+    let (n, c, h, w) = match self.shape() {
+      &[n, c, h, w] => (n, c, h, w),
+      shape => return Err(format!("max_pool2d: expected an [N, C, H, W] tensor, found shape {:?}", shape))
+    };
+    if h == 0 || w == 0 {
+      return Err(format!("max_pool2d: input has zero spatial size {}x{}", h, w));
+    }
+    let (oh, ow) = options.output_size(h, w)?;
+    let (kh, kw) = options.kernel;
+
+    let input = self.contiguous();
+    let src = input.storage();
+    let mut values = Vec::with_capacity(n * c * oh * ow);
+    let mut indices = Vec::with_capacity(values.capacity());
+    for plane in src.chunks(h * w).take(n * c) {
+      for oy in 0..oh {
+        for ox in 0..ow {
+          let best = (0..kh)
+            .flat_map(|ki| (0..kw).map(move |kj| (ki, kj)))
+            .filter_map(|k| options.source((oy, ox), k, (h, w)))
+            .fold(None, |best: Option<usize>, i| match best {
+              Some(b) if plane[b] >= plane[i] => Some(b),
+              _ => Some(i)
+            })
+            .ok_or_else(|| format!("max_pool2d: window at ({}, {}) lies entirely in the padding", oy, ox))?;
+          values.push(plane[best]);
+          indices.push(best);
+        }
+      }
+    }
+
+    Ok((Tensor::from_vec(&[n, c, oh, ow], values)?, Tensor::from_vec(&[n, c, oh, ow], indices)?))
+  }
+}
+
+impl<T: Float> Tensor<T> {
+  pub fn avg_pool2d(&self, options: &Conv2dOptions) -> Result<Tensor<T>, String> {
+    let (n, c, h, w) = match self.shape() {
+      &[n, c, h, w] => (n, c, h, w),
+      shape => return Err(format!("avg_pool2d: expected an [N, C, H, W] tensor, found shape {:?}", shape))
+    };
+    let (oh, ow) = options.output_size(h, w)?;
+
+    let cols = self.reshape(&[n * c, 1, h, w])?.im2col(options)?;
+    cols.mean(&[1], false)?.reshape(&[n, c, oh, ow])
+  }
+}
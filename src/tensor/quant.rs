@@ -0,0 +1,138 @@
+use super::device::dispatch;
+use super::linalg::matmul_shape;
+use super::Tensor;
+use crate::math::scalar::Float;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QuantParams {
+  pub scale: f64,
+  pub zero_point: i32
+}
+
+impl QuantParams {
+  pub fn new(scale: f64, zero_point: i32) -> Result<Self, String> {
+    if !(scale.is_finite() && scale > 0.0) {
+      return Err(format!("Quantization scale must be positive and finite, found {}", scale));
+    }
+    if !(i8::MIN as i32..=i8::MAX as i32).contains(&zero_point) {
+      return Err(format!("Quantization zero point {} is outside the int8 range", zero_point));
+    }
+
+    Ok(QuantParams { scale, zero_point })
+  }
+
+  pub fn symmetric(max_abs: f64) -> Self {
+    let scale = if max_abs > 0.0 { max_abs / i8::MAX as f64 } else { 1.0 };
+    QuantParams { scale, zero_point: 0 }
+  }
+
+  pub fn asymmetric(min: f64, max: f64) -> Self {
+    let (min, max) = (min.min(0.0), max.max(0.0));
+    if max == min {
+      return QuantParams { scale: 1.0, zero_point: 0 };
+    }
+
+    let scale = (max - min) / 255.0;
+    let zero_point = (i8::MIN as f64 - min / scale).round().clamp(i8::MIN as f64, i8::MAX as f64) as i32;
+    QuantParams { scale, zero_point }
+  }
+
+  pub fn quantize(&self, x: f64) -> i8 {
+    ((x / self.scale).round() + self.zero_point as f64).clamp(i8::MIN as f64, i8::MAX as f64) as i8
+  }
+
+  pub fn dequantize(&self, q: i8) -> f64 {
+    (q as i32 - self.zero_point) as f64 * self.scale
+  }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct QuantizedTensor {
+  values: Tensor<i8>,
+  params: QuantParams
+}
+
+impl QuantizedTensor {
+  pub fn from_parts(values: Tensor<i8>, params: QuantParams) -> Self {
+    QuantizedTensor { values, params }
+  }
+
+  pub fn values(&self) -> &Tensor<i8> {
+    &self.values
+  }
+
+  pub fn params(&self) -> QuantParams {
+    self.params
+  }
+
+  pub fn shape(&self) -> &[usize] {
+    self.values.shape()
+  }
+
+  pub fn dequantize<T: Float>(&self) -> Tensor<T> {
+    self.values.map(|&q| T::from_f64(self.params.dequantize(q)))
+  }
+
+  pub fn matmul_i32(&self, other: &QuantizedTensor) -> Result<Tensor<i32>, String> {
+    self.values.matmul_i32(&other.values, self.params.zero_point, other.params.zero_point)
+  }
+
+  pub fn matmul<T: Float>(&self, other: &QuantizedTensor) -> Result<Tensor<T>, String> {
+    let scale = self.params.scale * other.params.scale;
+    Ok(self.matmul_i32(other)?.map(|&acc| T::from_f64(acc as f64 * scale)))
+  }
+}
+
+impl<T: Float> Tensor<T> {
+  pub fn quantize(&self, scale: f64, zero_point: i32) -> Result<QuantizedTensor, String> {
+    let params = QuantParams::new(scale, zero_point)?;
+    Ok(self.quantize_with(params))
+  }
+
+  pub fn quantize_with(&self, params: QuantParams) -> QuantizedTensor {
+    QuantizedTensor {
+      values: self.map(|&x| params.quantize(x.to_f64())),
+      params
+    }
+  }
+
+  pub fn quantize_symmetric(&self) -> QuantizedTensor {
+    let max_abs = self.iter().fold(0.0f64, |m, &x| m.max(x.to_f64().abs()));
+    self.quantize_with(QuantParams::symmetric(max_abs))
+  }
+
+  pub fn quantize_asymmetric(&self) -> QuantizedTensor {
+    let (min, max) = self.iter().fold((0.0f64, 0.0f64), |(lo, hi), &x| (lo.min(x.to_f64()), hi.max(x.to_f64())));
+    self.quantize_with(QuantParams::asymmetric(min, max))
+  }
+}
+
+impl Tensor<i8> {
+  pub fn matmul_i32(&self, other: &Tensor<i8>, lhs_zero: i32, rhs_zero: i32) -> Result<Tensor<i32>, String> {
+    let shape = matmul_shape(self.shape(), other.shape())?;
+    dispatch("matmul_i32", &[self.device(), other.device()], || {
+      let (batch, mat) = shape.split_at(shape.len() - 2);
+      let (m, k, n) = (mat[0], self.shape()[self.ndim() - 1], mat[1]);
+      let lhs = self.broadcast_to(&[batch, &[m, k]].concat())?.contiguous();
+      let rhs = other.broadcast_to(&[batch, &[k, n]].concat())?.contiguous();
+      let (a, b) = (lhs.storage(), rhs.storage());
+
+      let batches: usize = batch.iter().product();
+      let mut data = vec![0i32; batches * m * n];
+      for p in 0..batches {
+        let (a, b) = (&a[p * m * k..], &b[p * k * n..]);
+        let out = &mut data[p * m * n..(p + 1) * m * n];
+        for i in 0..m {
+          for l in 0..k {
+            let x = a[i * k + l] as i32 - lhs_zero;
+            for j in 0..n {
+              out[i * n + j] += x * (b[l * n + j] as i32 - rhs_zero);
+            }
+          }
+        }
+      }
+
+      Tensor::from_vec(&shape, data)
+    })
+  }
+}
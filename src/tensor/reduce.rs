@@ -0,0 +1,212 @@
+use super::device::dispatch;
+use super::{contiguous_strides, IndexIter, Tensor};
+use crate::math::matrix::Matrix;
+use crate::math::scalar::{Float, Scalar};
+use crate::math::vector::Vector;
+
+pub(crate) fn normalize_axes(shape: &[usize], axes: &[usize]) -> Result<Vec<bool>, String> {
+  let mut reduced = vec![axes.is_empty(); shape.len()];
+  for &axis in axes {
+    if axis >= shape.len() {
+      return Err(format!("Axis {} out of range for a tensor with {} dimensions", axis, shape.len()));
+    }
+    if reduced[axis] {
+      return Err(format!("Axis {} repeated in reduction", axis));
+    }
+    reduced[axis] = true;
+  }
+
+  Ok(reduced)
+}
+
+pub(crate) fn reduced_shape(shape: &[usize], reduced: &[bool], keepdims: bool) -> Vec<usize> {
+  shape
+    .iter()
+    .zip(reduced.iter())
+    .filter_map(|(&dim, &r)| match (r, keepdims) {
+      (false, _) => Some(dim),
+      (true, true) => Some(1),
+      (true, false) => None
+    })
+    .collect()
+}
+
+impl<T> Tensor<T> {
+  pub(crate) fn normalize_axes(&self, axes: &[usize]) -> Result<Vec<bool>, String> {
+    normalize_axes(self.shape(), axes)
+  }
+
+  pub(crate) fn reduced_shape(&self, reduced: &[bool], keepdims: bool) -> Vec<usize> {
+    reduced_shape(self.shape(), reduced, keepdims)
+  }
+
+  pub fn reduce<A, F, G>(&self, axes: &[usize], keepdims: bool, init: A, fold: F, finish: G) -> Result<Tensor<T>, String>
+  where
+    A: Clone,
+    F: Fn(A, &T) -> A,
+    G: Fn(A, usize) -> T
+  {
+    let reduced = self.normalize_axes(axes)?;
+    dispatch("reduce", &[self.device()], || {
+      let kept_shape = self.reduced_shape(&reduced, true);
+      let kept_strides = contiguous_strides(&kept_shape);
+      let count: usize = self.shape().iter().zip(reduced.iter()).filter(|(_, &r)| r).map(|(&d, _)| d).product();
+
+      let mut accumulators = vec![init; kept_shape.iter().product()];
+      for (index, value) in IndexIter::new(self.shape()).zip(self.iter()) {
+        let out: usize = index
+          .iter()
+          .zip(reduced.iter())
+          .zip(kept_strides.iter())
+          .map(|((&i, &r), &s)| if r { 0 } else { i * s })
+          .sum();
+        accumulators[out] = fold(accumulators[out].clone(), value);
+      }
+
+      let data = accumulators.into_iter().map(|acc| finish(acc, count)).collect();
+      Tensor::from_vec(&self.reduced_shape(&reduced, keepdims), data)
+    })
+  }
+}
+
+impl<T: Scalar> Tensor<T> {
+  pub fn sum(&self, axes: &[usize], keepdims: bool) -> Result<Tensor<T>, String> {
+    self.reduce(axes, keepdims, T::zero(), |acc, &x| acc + x, |acc, _| acc)
+  }
+
+  pub fn prod(&self, axes: &[usize], keepdims: bool) -> Result<Tensor<T>, String> {
+    self.reduce(axes, keepdims, T::one(), |acc, &x| acc * x, |acc, _| acc)
+  }
+
+  pub fn sum_all(&self) -> T {
+    self.iter().fold(T::zero(), |acc, &x| acc + x)
+  }
+}
+
+impl<T: Copy + PartialOrd> Tensor<T> {
+  pub fn max(&self, axes: &[usize], keepdims: bool) -> Result<Tensor<T>, String> {
+    if self.is_empty() {
+      return Err("max: cannot reduce an empty tensor".to_string());
+    }
+
+    self.reduce(
+      axes,
+      keepdims,
+      None,
+      |acc: Option<T>, &x| match acc {
+        Some(m) if m >= x => Some(m),
+        _ => Some(x)
+      },
+      |acc, _| acc.expect("cannot reduce over an empty axis")
+    )
+  }
+
+  pub fn min(&self, axes: &[usize], keepdims: bool) -> Result<Tensor<T>, String> {
+    if self.is_empty() {
+      return Err("min: cannot reduce an empty tensor".to_string());
+    }
+
+    self.reduce(
+      axes,
+      keepdims,
+      None,
+      |acc: Option<T>, &x| match acc {
+        Some(m) if m <= x => Some(m),
+        _ => Some(x)
+      },
+      |acc, _| acc.expect("cannot reduce over an empty axis")
+    )
+  }
+}
+
+impl<T: Float> Tensor<T> {
+  pub fn mean(&self, axes: &[usize], keepdims: bool) -> Result<Tensor<T>, String> {
+    self.reduce(axes, keepdims, T::zero(), |acc, &x| acc + x, |acc, n| acc / T::from_f64(n as f64))
+  }
+
+  pub fn var(&self, axes: &[usize], keepdims: bool) -> Result<Tensor<T>, String> {
+    let mean = self.mean(axes, true)?;
+    let centered = self.zip_map(&mean, |&x, &m| (x - m) * (x - m))?;
+    centered.mean(axes, keepdims)
+  }
+}
+
+impl<T: Float> Tensor<T> {
+  pub fn nansum(&self, axes: &[usize], keepdims: bool) -> Result<Tensor<T>, String> {
+    self.reduce(axes, keepdims, T::zero(), |acc, &x| if x.is_nan() { acc } else { acc + x }, |acc, _| acc)
+  }
+
+  pub fn nanmean(&self, axes: &[usize], keepdims: bool) -> Result<Tensor<T>, String> {
+    self.reduce(
+      axes,
+      keepdims,
+      (T::zero(), 0usize),
+      |(sum, n), &x| if x.is_nan() { (sum, n) } else { (sum + x, n + 1) },
+      |(sum, n), _| if n == 0 { T::from_f64(f64::NAN) } else { sum / T::from_f64(n as f64) }
+    )
+  }
+
+  pub fn nanmax(&self, axes: &[usize], keepdims: bool) -> Result<Tensor<T>, String> {
+    self.reduce(
+      axes,
+      keepdims,
+      None,
+      |acc: Option<T>, &x| match acc {
+        _ if x.is_nan() => acc,
+        Some(m) if m >= x => Some(m),
+        _ => Some(x)
+      },
+      |acc, _| acc.unwrap_or(T::from_f64(f64::NAN))
+    )
+  }
+
+  pub fn nanmin(&self, axes: &[usize], keepdims: bool) -> Result<Tensor<T>, String> {
+    self.reduce(
+      axes,
+      keepdims,
+      None,
+      |acc: Option<T>, &x| match acc {
+        _ if x.is_nan() => acc,
+        Some(m) if m <= x => Some(m),
+        _ => Some(x)
+      },
+      |acc, _| acc.unwrap_or(T::from_f64(f64::NAN))
+    )
+  }
+
+  pub fn count_nan(&self, axes: &[usize], keepdims: bool) -> Result<Tensor<T>, String> {
+    self.reduce(axes, keepdims, T::zero(), |acc, &x| if x.is_nan() { acc + T::one() } else { acc }, |acc, _| acc)
+  }
+}
+
+impl<T: Float> Matrix<T> {
+  fn reduce_axis<F>(&self, axis: usize, f: F) -> Result<Vector<T>, String>
+  where
+    F: Fn(&Tensor<T>, &[usize]) -> Result<Tensor<T>, String>
+  {
+    if axis > 1 {
+      return Err(format!("Axis {} out of range for a matrix", axis));
+    }
+    Ok(Vector::from(f(&Tensor::from(self.clone()), &[axis])?.into_vec()))
+  }
+
+  pub fn nansum(&self, axis: usize) -> Result<Vector<T>, String> {
+    self.reduce_axis(axis, |t, axes| t.nansum(axes, false))
+  }
+
+  pub fn nanmean(&self, axis: usize) -> Result<Vector<T>, String> {
+    self.reduce_axis(axis, |t, axes| t.nanmean(axes, false))
+  }
+
+  pub fn nanmax(&self, axis: usize) -> Result<Vector<T>, String> {
+    self.reduce_axis(axis, |t, axes| t.nanmax(axes, false))
+  }
+
+  pub fn nanmin(&self, axis: usize) -> Result<Vector<T>, String> {
+    self.reduce_axis(axis, |t, axes| t.nanmin(axes, false))
+  }
+
+  pub fn count_nan(&self, axis: usize) -> Result<Vector<usize>, String> {
+    Ok(self.reduce_axis(axis, |t, axes| t.count_nan(axes, false))?.iter().map(|n| n.to_f64() as usize).collect())
+  }
+}
@@ -0,0 +1,149 @@
+use std::ops::{Range, RangeFrom, RangeFull, RangeInclusive, RangeTo, RangeToInclusive};
+
+use super::{IndexIter, Tensor};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SliceArg {
+  Index(usize),
+  Range { start: usize, end: Option<usize>, step: usize }
+}
+
+impl SliceArg {
+  pub fn step_by(self, step: usize) -> Self {
+    match self {
+      SliceArg::Range { start, end, .. } => SliceArg::Range { start, end, step },
+      index => index
+    }
+  }
+}
+
+impl From<usize> for SliceArg {
+  fn from(index: usize) -> Self {
+    SliceArg::Index(index)
+  }
+}
+
+impl From<Range<usize>> for SliceArg {
+  fn from(range: Range<usize>) -> Self {
+    SliceArg::Range { start: range.start, end: Some(range.end), step: 1 }
+  }
+}
+
+impl From<RangeInclusive<usize>> for SliceArg {
+  fn from(range: RangeInclusive<usize>) -> Self {
+    SliceArg::Range { start: *range.start(), end: Some(*range.end() + 1), step: 1 }
+  }
+}
+
+impl From<RangeFrom<usize>> for SliceArg {
+  fn from(range: RangeFrom<usize>) -> Self {
+    SliceArg::Range { start: range.start, end: None, step: 1 }
+  }
+}
+
+impl From<RangeTo<usize>> for SliceArg {
+  fn from(range: RangeTo<usize>) -> Self {
+    SliceArg::Range { start: 0, end: Some(range.end), step: 1 }
+  }
+}
+
+impl From<RangeToInclusive<usize>> for SliceArg {
+  fn from(range: RangeToInclusive<usize>) -> Self {
+    SliceArg::Range { start: 0, end: Some(range.end + 1), step: 1 }
+  }
+}
+
+impl From<RangeFull> for SliceArg {
+  fn from(_: RangeFull) -> Self {
+    SliceArg::Range { start: 0, end: None, step: 1 }
+  }
+}
+
+#[macro_export]
+macro_rules! s {
+  ($($arg:expr),* $(,)?) => {
+    [$($crate::tensor::slice::SliceArg::from($arg)),*]
+  };
+}
+
+impl<T> Tensor<T> {
+  pub fn slice(&self, args: &[SliceArg]) -> Result<Tensor<T>, String> {
+    if args.len() > self.ndim() {
+      return Err(format!("Too many slice arguments ({}) for a tensor with {} dimensions", args.len(), self.ndim()));
+    }
+
+    let mut shape = Vec::with_capacity(self.ndim());
+    let mut strides = Vec::with_capacity(self.ndim());
+    let mut offset = self.offset();
+
+    for axis in 0..self.ndim() {
+      let dim = self.shape()[axis];
+      let stride = self.strides()[axis];
+      match args.get(axis) {
+        Some(SliceArg::Index(i)) => {
+          if *i >= dim {
+            return Err(format!("Index {} out of bounds for axis {} with size {}", i, axis, dim));
+          }
+          offset += i * stride;
+        }
+        Some(SliceArg::Range { start, end, step }) => {
+          let end = end.unwrap_or(dim);
+          if *start > end || end > dim || *step == 0 {
+            return Err(format!("Invalid range {}..{} (step {}) for axis {} with size {}", start, end, step, axis, dim));
+          }
+          if start < &end {
+            offset += start * stride;
+          }
+          shape.push((end - start).div_ceil(*step));
+          strides.push(stride * step);
+        }
+        None => {
+          shape.push(dim);
+          strides.push(stride);
+        }
+      }
+    }
+
+    Ok(self.view(shape, strides, offset))
+  }
+}
+
+impl<T: Clone> Tensor<T> {
+  pub fn take(&self, indices: &[usize], axis: usize) -> Result<Tensor<T>, String> {
+    if axis >= self.ndim() {
+      return Err(format!("Axis {} out of range for a tensor with {} dimensions", axis, self.ndim()));
+    }
+    if let Some(bad) = indices.iter().find(|&&i| i >= self.shape()[axis]) {
+      return Err(format!("Index {} out of bounds for axis {} with size {}", bad, axis, self.shape()[axis]));
+    }
+
+    let mut shape = self.shape().to_vec();
+    shape[axis] = indices.len();
+    let data = IndexIter::new(&shape)
+      .map(|mut index| {
+        index[axis] = indices[index[axis]];
+        self.get(&index).expect("index checked against shape").clone()
+      })
+      .collect();
+
+    Tensor::from_vec(&shape, data)
+  }
+
+  pub fn compress(&self, mask: &[bool], axis: usize) -> Result<Tensor<T>, String> {
+    if axis >= self.ndim() || mask.len() != self.shape()[axis] {
+      return Err(format!(
+        "Mask of length {} does not match axis {} of a tensor with shape {:?}",
+        mask.len(), axis, self.shape()
+      ));
+    }
+
+    let indices: Vec<usize> = mask.iter().enumerate().filter(|(_, &m)| m).map(|(i, _)| i).collect();
+    self.take(&indices, axis)
+  }
+
+  pub fn masked_select(&self, mask: &Tensor<bool>) -> Result<Tensor<T>, String> {
+    let mask = mask.broadcast_to(self.shape())?;
+    let data: Vec<T> = self.iter().zip(mask.iter()).filter(|(_, &m)| m).map(|(x, _)| x.clone()).collect();
+    Tensor::from_vec(&[data.len()], data)
+  }
+}
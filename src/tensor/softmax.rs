@@ -0,0 +1,38 @@
+use super::Tensor;
+use crate::math::matrix::Matrix;
+use crate::math::scalar::Float;
+
+impl<T: Float> Tensor<T> {
+  pub fn logsumexp(&self, axis: usize, keepdims: bool) -> Result<Tensor<T>, String> {
+    let max = self.max(&[axis], true)?.map(|&m| if m == T::infinity() || m == T::neg_infinity() { T::zero() } else { m });
+    let shifted = self.zip_map(&max, |&x, &m| (x - m).exp())?;
+    let lse = shifted.sum(&[axis], true)?.zip_map(&max, |&s, &m| s.ln() + m)?;
+    if keepdims {
+      return Ok(lse);
+    }
+
+    let mut shape = self.shape().to_vec();
+    shape.remove(axis);
+    lse.reshape(&shape)
+  }
+
+  pub fn softmax(&self, axis: usize) -> Result<Tensor<T>, String> {
+    let lse = self.logsumexp(axis, true)?;
+    self.zip_map(&lse, |&x, &l| (x - l).exp())
+  }
+
+  pub fn log_softmax(&self, axis: usize) -> Result<Tensor<T>, String> {
+    let lse = self.logsumexp(axis, true)?;
+    self.zip_map(&lse, |&x, &l| x - l)
+  }
+}
+
+impl<T: Float> Matrix<T> {
+  pub fn softmax(&self, axis: usize) -> Result<Matrix<T>, String> {
+    Tensor::from(self.clone()).softmax(axis)?.try_into()
+  }
+
+  pub fn log_softmax(&self, axis: usize) -> Result<Matrix<T>, String> {
+    Tensor::from(self.clone()).log_softmax(axis)?.try_into()
+  }
+}
@@ -0,0 +1,90 @@
+use std::cmp::Ordering;
+
+use super::Tensor;
+use crate::math::matrix::Matrix;
+use crate::math::vector::Vector;
+
+impl<T: Copy + PartialOrd> Tensor<T> {
+  fn select_along(&self, axis: usize, k: usize, largest: bool) -> Result<(Tensor<T>, Tensor<usize>), String> {
+    if axis >= self.ndim() {
+      return Err(format!("Axis {} out of range for a tensor with {} dimensions", axis, self.ndim()));
+    }
+    let n = self.shape()[axis];
+    if k > n {
+      return Err(format!("topk: k = {} exceeds the size {} of axis {}", k, n, axis));
+    }
+
+    let mut perm: Vec<usize> = (0..self.ndim()).filter(|&a| a != axis).collect();
+    perm.push(axis);
+    let mut inverse = vec![0; perm.len()];
+    for (i, &a) in perm.iter().enumerate() {
+      inverse[a] = i;
+    }
+
+    let moved = self.permute(&perm)?.contiguous();
+    let mut moved_shape = moved.shape().to_vec();
+    *moved_shape.last_mut().expect("axis is in range") = k;
+
+    let mut values = Vec::with_capacity(moved_shape.iter().product());
+    let mut indices = Vec::with_capacity(values.capacity());
+    if k > 0 {
+      for lane in moved.storage().chunks(n) {
+        let mut order: Vec<usize> = (0..n).collect();
+        order.sort_by(|&a, &b| {
+          let ordering = lane[a].partial_cmp(&lane[b]).unwrap_or(Ordering::Equal);
+          if largest { ordering.reverse() } else { ordering }
+        });
+        for &i in &order[..k] {
+          values.push(lane[i]);
+          indices.push(i);
+        }
+      }
+    }
+
+    let values = Tensor::from_vec(&moved_shape, values)?.permute(&inverse)?.contiguous();
+    let indices = Tensor::from_vec(&moved_shape, indices)?.permute(&inverse)?.contiguous();
+    Ok((values, indices))
+  }
+
+  pub fn topk(&self, k: usize, axis: usize) -> Result<(Tensor<T>, Tensor<usize>), String> {
+    self.select_along(axis, k, true)
+  }
+
+  pub fn bottomk(&self, k: usize, axis: usize) -> Result<(Tensor<T>, Tensor<usize>), String> {
+    self.select_along(axis, k, false)
+  }
+
+  pub fn argmax(&self, axis: usize) -> Result<Tensor<usize>, String> {
+    self.arg_extreme(axis, true)
+  }
+
+  pub fn argmin(&self, axis: usize) -> Result<Tensor<usize>, String> {
+    self.arg_extreme(axis, false)
+  }
+
+  fn arg_extreme(&self, axis: usize, largest: bool) -> Result<Tensor<usize>, String> {
+    if self.shape().get(axis) == Some(&0) {
+      return Err(format!("Cannot take the arg{} of an empty axis {}", if largest { "max" } else { "min" }, axis));
+    }
+
+    let (_, indices) = self.select_along(axis, 1, largest)?;
+    let mut shape = self.shape().to_vec();
+    shape.remove(axis);
+    indices.reshape(&shape)
+  }
+}
+
+impl<T: Copy + PartialOrd> Matrix<T> {
+  pub fn argmax(&self, axis: usize) -> Result<Vector<usize>, String> {
+    Ok(Vector::from(Tensor::from(self.clone()).argmax(axis)?.into_vec()))
+  }
+
+  pub fn argmin(&self, axis: usize) -> Result<Vector<usize>, String> {
+    Ok(Vector::from(Tensor::from(self.clone()).argmin(axis)?.into_vec()))
+  }
+
+  pub fn topk(&self, k: usize, axis: usize) -> Result<(Matrix<T>, Matrix<usize>), String> {
+    let (values, indices) = Tensor::from(self.clone()).topk(k, axis)?;
+    Ok((values.try_into()?, indices.try_into()?))
+  }
+}
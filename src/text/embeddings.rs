@@ -0,0 +1,226 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+
+use crate::math::scalar::Float;
+use crate::nn::Embedding;
+use crate::random::with_rng;
+use crate::tensor::Tensor;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EmbeddingFormat {
+  Text,
+  Binary
+}
+
+impl EmbeddingFormat {
+  pub fn from_path(path: &Path) -> Self {
+    match path.extension().and_then(|ext| ext.to_str()) {
+      Some("bin") => EmbeddingFormat::Binary,
+      _ => EmbeddingFormat::Text
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OovInit {
+  Zeros,
+  Mean,
+  Uniform(f64)
+}
+
+#[derive(Clone, Debug)]
+pub struct EmbeddingOptions {
+  pub format: Option<EmbeddingFormat>,
+  pub vocab: Option<Vec<String>>,
+  pub unk_token: Option<String>,
+  pub oov: OovInit,
+  pub normalize: bool,
+  pub lowercase: bool,
+  pub limit: Option<usize>,
+  pub freeze: bool
+}
+
+impl EmbeddingOptions {
+  pub fn new() -> Self {
+    EmbeddingOptions {
+      format: None,
+      vocab: None,
+      unk_token: Some("<unk>".to_string()),
+      oov: OovInit::Zeros,
+      normalize: false,
+      lowercase: false,
+      limit: None,
+      freeze: true
+    }
+  }
+}
+
+impl Default for EmbeddingOptions {
+  fn default() -> Self {
+    EmbeddingOptions::new()
+  }
+}
+
+pub struct PretrainedEmbeddings<T: Float = f64> {
+  pub embedding: Embedding<T>,
+  pub index: HashMap<String, usize>,
+  pub oov: Vec<String>,
+  unk_id: Option<usize>
+}
+
+impl<T: Float> PretrainedEmbeddings<T> {
+  pub fn row(&self, token: &str) -> Option<usize> {
+    self.index.get(token).copied().or(self.unk_id)
+  }
+
+  pub fn unk_id(&self) -> Option<usize> {
+    self.unk_id
+  }
+
+  pub fn vector(&self, token: &str) -> Option<Tensor<T>> {
+    let row = self.row(token)?;
+    let weight = self.embedding.weight.value();
+    weight.narrow(0, row, 1).ok()?.reshape(&[self.embedding.embedding_dim()]).ok()
+  }
+
+  pub fn encode<S: AsRef<str>>(&self, tokens: &[S]) -> Result<Vec<usize>, String> {
+    tokens.iter().map(|token| self.row(token.as_ref()).ok_or_else(|| format!("PretrainedEmbeddings: '{}' is out of vocabulary", token.as_ref()))).collect()
+  }
+}
+
+type Vectors = Vec<(String, Vec<f64>)>;
+
+fn parse_vector(fields: &[&str], dim: Option<usize>, line: usize) -> Result<Vec<f64>, String> {
+  if let Some(dim) = dim {
+    if fields.len() != dim {
+      return Err(format!("load_embeddings: line {} has {} values, expected {}", line, fields.len(), dim));
+    }
+  }
+  fields.iter().map(|field| field.parse::<f64>().map_err(|_| format!("load_embeddings: line {} has invalid value '{}'", line, field))).collect()
+}
+
+fn read_text<R: Read>(reader: R, limit: Option<usize>) -> Result<Vectors, String> {
+  let mut vectors = Vec::new();
+  let mut dim = None;
+  for (number, line) in BufReader::new(reader).lines().enumerate() {
+    if limit.is_some_and(|limit| vectors.len() >= limit) {
+      break;
+    }
+    let line = line.map_err(|e| e.to_string())?;
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    if fields.is_empty() {
+      continue;
+    }
+    if number == 0 && fields.len() == 2 && fields.iter().all(|field| field.parse::<usize>().is_ok()) {
+      dim = fields[1].parse().ok();
+      continue;
+    }
+    let vector = parse_vector(&fields[1..], dim, number + 1)?;
+    dim = Some(vector.len());
+    vectors.push((fields[0].to_string(), vector));
+  }
+  Ok(vectors)
+}
+
+fn read_binary<R: Read>(reader: R, limit: Option<usize>) -> Result<Vectors, String> {
+  let mut reader = BufReader::new(reader);
+  let mut header = String::new();
+  reader.read_line(&mut header).map_err(|e| e.to_string())?;
+  let sizes: Vec<usize> = header.split_whitespace().filter_map(|field| field.parse().ok()).collect();
+  let &[count, dim] = sizes.as_slice() else {
+    return Err(format!("load_embeddings: invalid word2vec header '{}'", header.trim()));
+  };
+
+  let len = dim.checked_mul(4).ok_or_else(|| format!("load_embeddings: dimension {} is too large", dim))?;
+  let wanted = count.min(limit.unwrap_or(count));
+  let mut vectors = Vec::with_capacity(wanted.min(1024));
+  let mut bytes = Vec::new();
+  for _ in 0..wanted {
+    let mut word = Vec::new();
+    reader.read_until(b' ', &mut word).map_err(|e| e.to_string())?;
+    if word.last() != Some(&b' ') {
+      return Err(format!("load_embeddings: truncated file after {} of {} vectors", vectors.len(), count));
+    }
+    let word = String::from_utf8_lossy(&word[..word.len() - 1]).trim_start_matches('\n').to_string();
+    bytes.clear();
+    (&mut reader).take(len as u64).read_to_end(&mut bytes).map_err(|e| e.to_string())?;
+    if bytes.len() != len {
+      return Err(format!("load_embeddings: truncated vector for '{}'", word));
+    }
+    let vector = bytes.chunks_exact(4).map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]) as f64).collect();
+    vectors.push((word, vector));
+  }
+  Ok(vectors)
+}
+
+pub fn read_embeddings<T: Float, R: Read>(reader: R, format: EmbeddingFormat, options: &EmbeddingOptions) -> Result<PretrainedEmbeddings<T>, String> {
+  let mut vectors = match format {
+    EmbeddingFormat::Text => read_text(reader, options.limit)?,
+    EmbeddingFormat::Binary => read_binary(reader, options.limit)?
+  };
+  let Some(dim) = vectors.first().map(|(_, vector)| vector.len()) else {
+    return Err("load_embeddings: no vectors found".to_string());
+  };
+  for (word, vector) in vectors.iter_mut() {
+    if options.lowercase {
+      *word = word.to_lowercase();
+    }
+    let norm = vector.iter().map(|x| x * x).sum::<f64>().sqrt();
+    if options.normalize && norm > 0.0 {
+      vector.iter_mut().for_each(|x| *x /= norm);
+    }
+  }
+
+  let mut loaded: HashMap<String, Vec<f64>> = HashMap::with_capacity(vectors.len());
+  let mut order = Vec::with_capacity(vectors.len());
+  for (word, vector) in vectors {
+    if let Entry::Vacant(entry) = loaded.entry(word) {
+      order.push(entry.key().clone());
+      entry.insert(vector);
+    }
+  }
+  let mean: Vec<f64> = (0..dim).map(|i| loaded.values().map(|vector| vector[i]).sum::<f64>() / loaded.len() as f64).collect();
+  let oov_vector = || -> Vec<f64> {
+    match options.oov {
+      OovInit::Zeros => vec![0.0; dim],
+      OovInit::Mean => mean.clone(),
+      OovInit::Uniform(scale) => with_rng(|rng| (0..dim).map(|_| rng.uniform(-scale, scale)).collect())
+    }
+  };
+
+  let mut tokens = options.vocab.clone().unwrap_or(order);
+  if let Some(unk) = &options.unk_token {
+    if !tokens.contains(unk) {
+      tokens.push(unk.clone());
+    }
+  }
+  let mut index = HashMap::with_capacity(tokens.len());
+  let mut oov = Vec::new();
+  let mut data = Vec::with_capacity(tokens.len() * dim);
+  for token in tokens {
+    if index.contains_key(&token) {
+      continue;
+    }
+    match loaded.get(&token) {
+      Some(vector) => data.extend(vector.iter().copied()),
+      None => {
+        data.extend(oov_vector());
+        oov.push(token.clone());
+      }
+    }
+    index.insert(token, index.len());
+  }
+
+  let weight = Tensor::from_vec(&[index.len(), dim], data.into_iter().map(T::from_f64).collect())?;
+  let unk_id = options.unk_token.as_ref().and_then(|unk| index.get(unk).copied());
+  Ok(PretrainedEmbeddings { embedding: Embedding::from_pretrained(weight, options.freeze)?, index, oov, unk_id })
+}
+
+pub fn load_embeddings<T: Float, P: AsRef<Path>>(path: P, options: &EmbeddingOptions) -> Result<PretrainedEmbeddings<T>, String> {
+  let path = path.as_ref();
+  let file = File::open(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+  read_embeddings(file, options.format.unwrap_or_else(|| EmbeddingFormat::from_path(path)), options)
+}
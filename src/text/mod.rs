@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+
+pub mod embeddings;
+pub mod unigram;
+pub mod vectorize;
+pub mod wordpiece;
+
+pub use embeddings::{load_embeddings, read_embeddings, EmbeddingFormat, EmbeddingOptions, OovInit, PretrainedEmbeddings};
+pub use unigram::UnigramTokenizer;
+pub use vectorize::{CountVectorizer, TfidfVectorizer};
+pub use wordpiece::WordPieceTokenizer;
+
+pub const PAD_TOKEN: &str = "[PAD]";
+pub const UNK_TOKEN: &str = "[UNK]";
+
+pub trait Tokenizer {
+  fn encode(&self, text: &str) -> Result<Vec<usize>, String>;
+}
+
+impl<F> Tokenizer for F
+where
+  F: Fn(&str) -> Result<Vec<usize>, String>
+{
+  fn encode(&self, text: &str) -> Result<Vec<usize>, String> {
+    self(text)
+  }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WhitespaceTokenizer {
+  pub lowercase: bool,
+  pub min_frequency: usize,
+  tokens: Vec<String>,
+  ids: HashMap<String, usize>
+}
+
+impl WhitespaceTokenizer {
+  pub fn new() -> Self {
+    let mut tokenizer = WhitespaceTokenizer { lowercase: true, min_frequency: 1, tokens: Vec::new(), ids: HashMap::new() };
+    tokenizer.reset();
+    tokenizer
+  }
+
+  fn reset(&mut self) {
+    self.tokens = vec![PAD_TOKEN.to_string(), UNK_TOKEN.to_string()];
+    self.ids = self.tokens.iter().enumerate().map(|(id, token)| (token.clone(), id)).collect();
+  }
+
+  fn words<'t>(&self, text: &'t str) -> impl Iterator<Item = String> + 't {
+    let lowercase = self.lowercase;
+    text.split_whitespace().map(move |word| if lowercase { word.to_lowercase() } else { word.to_string() })
+  }
+
+  pub fn fit<S: AsRef<str>>(&mut self, texts: &[S]) -> &mut Self {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    let mut first_seen = Vec::new();
+    for text in texts {
+      for word in self.words(text.as_ref()) {
+        let count = counts.entry(word.clone()).or_insert(0);
+        if *count == 0 {
+          first_seen.push(word);
+        }
+        *count += 1;
+      }
+    }
+    self.reset();
+    for word in first_seen {
+      if counts[&word] >= self.min_frequency && !self.ids.contains_key(&word) {
+        self.ids.insert(word.clone(), self.tokens.len());
+        self.tokens.push(word);
+      }
+    }
+    self
+  }
+
+  pub fn vocab_size(&self) -> usize {
+    self.tokens.len()
+  }
+
+  pub fn token_to_id(&self, token: &str) -> Option<usize> {
+    self.ids.get(token).copied()
+  }
+
+  pub fn id_to_token(&self, id: usize) -> Option<&str> {
+    self.tokens.get(id).map(String::as_str)
+  }
+
+  pub fn pad_id(&self) -> usize {
+    self.ids[PAD_TOKEN]
+  }
+
+  pub fn unk_id(&self) -> usize {
+    self.ids[UNK_TOKEN]
+  }
+
+  pub fn decode(&self, ids: &[usize]) -> Result<String, String> {
+    let words = ids
+      .iter()
+      .filter(|&&id| id != self.pad_id())
+      .map(|&id| self.id_to_token(id).ok_or_else(|| format!("WhitespaceTokenizer: unknown token id {}", id)))
+      .collect::<Result<Vec<_>, String>>()?;
+    Ok(words.join(" "))
+  }
+}
+
+impl Default for WhitespaceTokenizer {
+  fn default() -> Self {
+    WhitespaceTokenizer::new()
+  }
+}
+
+impl Tokenizer for WhitespaceTokenizer {
+  fn encode(&self, text: &str) -> Result<Vec<usize>, String> {
+    Ok(self.words(text).map(|word| self.ids.get(&word).copied().unwrap_or(self.unk_id())).collect())
+  }
+}
@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use super::wordpiece::read_lines;
+use super::Tokenizer;
+
+pub const SPACE_SYMBOL: char = '\u{2581}';
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UnigramTokenizer {
+  pub add_dummy_prefix: bool,
+  pub unk_penalty: f64,
+  pieces: Vec<(String, f64)>,
+  ids: HashMap<String, usize>,
+  unk_id: usize,
+  max_piece_chars: usize
+}
+
+impl UnigramTokenizer {
+  pub fn from_pieces(pieces: Vec<(String, f64)>, unk_token: &str) -> Result<Self, String> {
+    let mut ids = HashMap::with_capacity(pieces.len());
+    for (id, (piece, _)) in pieces.iter().enumerate() {
+      if ids.insert(piece.clone(), id).is_some() {
+        return Err(format!("UnigramTokenizer: duplicate piece '{}'", piece));
+      }
+    }
+    let unk_id = *ids.get(unk_token).ok_or_else(|| format!("UnigramTokenizer: vocab has no {} piece", unk_token))?;
+    let max_piece_chars = pieces.iter().map(|(piece, _)| piece.chars().count()).max().unwrap_or(1);
+    Ok(UnigramTokenizer { add_dummy_prefix: true, unk_penalty: 10.0, pieces, ids, unk_id, max_piece_chars })
+  }
+
+  pub fn read_vocab<R: Read>(reader: R) -> Result<Self, String> {
+    let pieces = read_lines(reader)?
+      .iter()
+      .enumerate()
+      .filter(|(_, line)| !line.trim().is_empty())
+      .map(|(number, line)| {
+        let (piece, score) = line.trim_end_matches('\r').rsplit_once('\t').ok_or_else(|| format!("UnigramTokenizer: line {} is not 'piece<TAB>score'", number + 1))?;
+        let score = score.trim().parse::<f64>().map_err(|_| format!("UnigramTokenizer: line {} has invalid score '{}'", number + 1, score))?;
+        Ok((piece.to_string(), score))
+      })
+      .collect::<Result<Vec<_>, String>>()?;
+    Self::from_pieces(pieces, "<unk>")
+  }
+
+  pub fn from_vocab_file<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+    let file = File::open(path.as_ref()).map_err(|e| format!("Cannot read {}: {}", path.as_ref().display(), e))?;
+    Self::read_vocab(file)
+  }
+
+  pub fn vocab_size(&self) -> usize {
+    self.pieces.len()
+  }
+
+  pub fn unk_id(&self) -> usize {
+    self.unk_id
+  }
+
+  pub fn piece_to_id(&self, piece: &str) -> Option<usize> {
+    self.ids.get(piece).copied()
+  }
+
+  pub fn id_to_piece(&self, id: usize) -> Option<&str> {
+    self.pieces.get(id).map(|(piece, _)| piece.as_str())
+  }
+
+  fn normalize(&self, text: &str) -> Vec<char> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let mut chars = Vec::new();
+    for (i, word) in words.iter().enumerate() {
+      if i > 0 || self.add_dummy_prefix {
+        chars.push(SPACE_SYMBOL);
+      }
+      chars.extend(word.chars());
+    }
+    chars
+  }
+
+  fn segment(&self, chars: &[char]) -> Vec<(usize, usize)> {
+    let min_score = self.pieces.iter().map(|&(_, score)| score).fold(0.0, f64::min);
+    let mut best = vec![(f64::NEG_INFINITY, 0, self.unk_id); chars.len() + 1];
+    best[0].0 = 0.0;
+    for end in 1..=chars.len() {
+      for start in end.saturating_sub(self.max_piece_chars)..end {
+        if best[start].0 == f64::NEG_INFINITY {
+          continue;
+        }
+        let piece: String = chars[start..end].iter().collect();
+        let candidate = match self.ids.get(&piece) {
+          Some(&id) if id != self.unk_id => Some((best[start].0 + self.pieces[id].1, id)),
+          _ if end == start + 1 => Some((best[start].0 + min_score - self.unk_penalty, self.unk_id)),
+          _ => None
+        };
+        if let Some((score, id)) = candidate {
+          if score > best[end].0 {
+            best[end] = (score, start, id);
+          }
+        }
+      }
+    }
+
+    let mut spans = Vec::new();
+    let mut end = chars.len();
+    while end > 0 {
+      let (_, start, id) = best[end];
+      spans.push((start, id));
+      end = start;
+    }
+    spans.reverse();
+    spans
+  }
+
+  pub fn tokenize(&self, text: &str) -> Vec<String> {
+    let chars = self.normalize(text);
+    let spans = self.segment(&chars);
+    let mut pieces: Vec<String> = Vec::new();
+    let mut previous_unk = false;
+    for (i, &(start, id)) in spans.iter().enumerate() {
+      let end = spans.get(i + 1).map_or(chars.len(), |&(next, _)| next);
+      let piece: String = chars[start..end].iter().collect();
+      let unk = id == self.unk_id;
+      match pieces.last_mut() {
+        Some(last) if unk && previous_unk => last.push_str(&piece),
+        _ => pieces.push(piece)
+      }
+      previous_unk = unk;
+    }
+    pieces
+  }
+
+  pub fn decode(&self, ids: &[usize]) -> Result<String, String> {
+    let mut text = String::new();
+    for &id in ids {
+      let piece = self.id_to_piece(id).ok_or_else(|| format!("UnigramTokenizer: unknown piece id {}", id))?;
+      if piece.starts_with('<') && piece.ends_with('>') {
+        continue;
+      }
+      text.push_str(piece);
+    }
+    let text = text.replace(SPACE_SYMBOL, " ");
+    Ok(if self.add_dummy_prefix { text.strip_prefix(' ').unwrap_or(&text).to_string() } else { text })
+  }
+}
+
+impl Tokenizer for UnigramTokenizer {
+  fn encode(&self, text: &str) -> Result<Vec<usize>, String> {
+    Ok(self.tokenize(text).iter().map(|piece| self.ids.get(piece).copied().unwrap_or(self.unk_id)).collect())
+  }
+}
@@ -0,0 +1,230 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::math::matrix::Matrix;
+use crate::math::scalar::Float;
+use crate::math::sparse::CsrMatrix;
+
+pub const ENGLISH_STOP_WORDS: &[&str] = &[
+  "a", "about", "above", "after", "again", "against", "all", "am", "an", "and", "any", "are", "as", "at", "be", "because", "been", "before", "being", "below",
+  "between", "both", "but", "by", "can", "could", "did", "do", "does", "doing", "down", "during", "each", "few", "for", "from", "further", "had", "has", "have",
+  "having", "he", "her", "here", "hers", "herself", "him", "himself", "his", "how", "i", "if", "in", "into", "is", "it", "its", "itself", "just", "me", "more",
+  "most", "my", "myself", "no", "nor", "not", "now", "of", "off", "on", "once", "only", "or", "other", "our", "ours", "ourselves", "out", "over", "own", "same",
+  "she", "should", "so", "some", "such", "than", "that", "the", "their", "theirs", "them", "themselves", "then", "there", "these", "they", "this", "those",
+  "through", "to", "too", "under", "until", "up", "very", "was", "we", "were", "what", "when", "where", "which", "while", "who", "whom", "why", "will", "with",
+  "would", "you", "your", "yours", "yourself", "yourselves"
+];
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CountVectorizer {
+  pub lowercase: bool,
+  pub ngram_range: (usize, usize),
+  pub stop_words: Vec<String>,
+  pub min_df: usize,
+  pub max_df: f64,
+  pub max_features: Option<usize>,
+  pub binary: bool,
+  vocabulary: BTreeMap<String, usize>
+}
+
+impl CountVectorizer {
+  pub fn new() -> Self {
+    CountVectorizer {
+      lowercase: true,
+      ngram_range: (1, 1),
+      stop_words: Vec::new(),
+      min_df: 1,
+      max_df: 1.0,
+      max_features: None,
+      binary: false,
+      vocabulary: BTreeMap::new()
+    }
+  }
+
+  pub fn english_stop_words(&mut self) -> &mut Self {
+    self.stop_words = ENGLISH_STOP_WORDS.iter().map(|word| word.to_string()).collect();
+    self
+  }
+
+  pub fn is_fitted(&self) -> bool {
+    !self.vocabulary.is_empty()
+  }
+
+  pub fn vocabulary(&self) -> &BTreeMap<String, usize> {
+    &self.vocabulary
+  }
+
+  pub fn feature_names(&self) -> Vec<&str> {
+    self.vocabulary.keys().map(String::as_str).collect()
+  }
+
+  pub fn n_features(&self) -> usize {
+    self.vocabulary.len()
+  }
+
+  pub fn analyze(&self, document: &str) -> Vec<String> {
+    let document = if self.lowercase { document.to_lowercase() } else { document.to_string() };
+    let stop_words: HashSet<&str> = self.stop_words.iter().map(String::as_str).collect();
+    let words: Vec<&str> = document
+      .split(|c: char| !c.is_alphanumeric() && c != '_')
+      .filter(|word| word.chars().count() >= 2 && !stop_words.contains(word))
+      .collect();
+    let (low, high) = self.ngram_range;
+    let mut terms = Vec::new();
+    for n in low.max(1)..=high {
+      terms.extend(words.windows(n).map(|window| window.join(" ")));
+    }
+    terms
+  }
+
+  fn validate(&self) -> Result<(), String> {
+    let (low, high) = self.ngram_range;
+    if low == 0 || low > high {
+      return Err(format!("CountVectorizer: invalid ngram_range ({}, {})", low, high));
+    }
+    if !(self.max_df > 0.0 && self.max_df <= 1.0) {
+      return Err(format!("CountVectorizer: max_df must be in (0, 1], got {}", self.max_df));
+    }
+    Ok(())
+  }
+
+  fn not_fitted() -> String {
+    "CountVectorizer: not fitted".to_string()
+  }
+
+  pub fn fit<S: AsRef<str>>(&mut self, documents: &[S]) -> Result<&mut Self, String> {
+    self.validate()?;
+    let mut document_frequency: HashMap<String, usize> = HashMap::new();
+    let mut total: HashMap<String, usize> = HashMap::new();
+    for document in documents {
+      let terms = self.analyze(document.as_ref());
+      for term in &terms {
+        *total.entry(term.clone()).or_insert(0) += 1;
+      }
+      for term in terms.into_iter().collect::<HashSet<_>>() {
+        *document_frequency.entry(term).or_insert(0) += 1;
+      }
+    }
+
+    let max_df = self.max_df * documents.len() as f64;
+    let mut kept: Vec<(String, usize)> = total
+      .into_iter()
+      .filter(|(term, _)| document_frequency[term] >= self.min_df && document_frequency[term] as f64 <= max_df)
+      .collect();
+    if let Some(max_features) = self.max_features {
+      kept.sort_by(|(a, count_a), (b, count_b)| count_b.cmp(count_a).then_with(|| a.cmp(b)));
+      kept.truncate(max_features);
+    }
+    if kept.is_empty() {
+      return Err("CountVectorizer: empty vocabulary after filtering".to_string());
+    }
+    let mut terms: Vec<String> = kept.into_iter().map(|(term, _)| term).collect();
+    terms.sort();
+    self.vocabulary = terms.into_iter().enumerate().map(|(index, term)| (term, index)).collect();
+    Ok(self)
+  }
+
+  pub fn transform_sparse<T: Float, S: AsRef<str>>(&self, documents: &[S]) -> Result<CsrMatrix<T>, String> {
+    if !self.is_fitted() {
+      return Err(Self::not_fitted());
+    }
+    let rows = documents
+      .iter()
+      .map(|document| {
+        let mut counts: BTreeMap<usize, f64> = BTreeMap::new();
+        for term in self.analyze(document.as_ref()) {
+          if let Some(&index) = self.vocabulary.get(&term) {
+            *counts.entry(index).or_insert(0.0) += 1.0;
+          }
+        }
+        counts.into_iter().map(|(index, count)| (index, T::from_f64(if self.binary { 1.0 } else { count }))).collect()
+      })
+      .collect();
+    CsrMatrix::from_rows(self.n_features(), rows)
+  }
+
+  pub fn transform<T: Float, S: AsRef<str>>(&self, documents: &[S]) -> Result<Matrix<T>, String> {
+    Ok(self.transform_sparse(documents)?.to_dense())
+  }
+
+  pub fn fit_transform<T: Float, S: AsRef<str>>(&mut self, documents: &[S]) -> Result<CsrMatrix<T>, String> {
+    self.fit(documents)?.transform_sparse(documents)
+  }
+}
+
+impl Default for CountVectorizer {
+  fn default() -> Self {
+    CountVectorizer::new()
+  }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TfidfVectorizer {
+  pub counts: CountVectorizer,
+  pub smooth_idf: bool,
+  pub sublinear_tf: bool,
+  pub l2_normalize: bool,
+  idf: Vec<f64>
+}
+
+impl TfidfVectorizer {
+  pub fn new() -> Self {
+    TfidfVectorizer { counts: CountVectorizer::new(), smooth_idf: true, sublinear_tf: false, l2_normalize: true, idf: Vec::new() }
+  }
+
+  pub fn idf(&self) -> &[f64] {
+    &self.idf
+  }
+
+  pub fn vocabulary(&self) -> &BTreeMap<String, usize> {
+    self.counts.vocabulary()
+  }
+
+  pub fn fit<S: AsRef<str>>(&mut self, documents: &[S]) -> Result<&mut Self, String> {
+    let counts: CsrMatrix<f64> = self.counts.fit(documents)?.transform_sparse(documents)?;
+    let mut document_frequency = vec![0.0; counts.cols];
+    for &col in counts.indices() {
+      document_frequency[col] += 1.0;
+    }
+    let smooth = if self.smooth_idf { 1.0 } else { 0.0 };
+    let n = documents.len() as f64 + smooth;
+    self.idf = document_frequency.iter().map(|df| (n / (df + smooth)).ln() + 1.0).collect();
+    Ok(self)
+  }
+
+  pub fn transform_sparse<T: Float, S: AsRef<str>>(&self, documents: &[S]) -> Result<CsrMatrix<T>, String> {
+    if self.idf.is_empty() {
+      return Err("TfidfVectorizer: not fitted".to_string());
+    }
+    let counts: CsrMatrix<f64> = self.counts.transform_sparse(documents)?;
+    let rows = (0..counts.rows)
+      .map(|row| {
+        let weights: Vec<(usize, f64)> = counts
+          .row(row)
+          .map(|(col, count)| {
+            let tf = if self.sublinear_tf { 1.0 + count.ln() } else { count };
+            (col, tf * self.idf[col])
+          })
+          .collect();
+        let norm = if self.l2_normalize { weights.iter().map(|(_, w)| w * w).sum::<f64>().sqrt() } else { 1.0 };
+        weights.into_iter().map(|(col, w)| (col, T::from_f64(if norm > 0.0 { w / norm } else { w }))).collect()
+      })
+      .collect();
+    CsrMatrix::from_rows(counts.cols, rows)
+  }
+
+  pub fn transform<T: Float, S: AsRef<str>>(&self, documents: &[S]) -> Result<Matrix<T>, String> {
+    Ok(self.transform_sparse(documents)?.to_dense())
+  }
+
+  pub fn fit_transform<T: Float, S: AsRef<str>>(&mut self, documents: &[S]) -> Result<CsrMatrix<T>, String> {
+    self.fit(documents)?.transform_sparse(documents)
+  }
+}
+
+impl Default for TfidfVectorizer {
+  fn default() -> Self {
+    TfidfVectorizer::new()
+  }
+}
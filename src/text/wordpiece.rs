@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+
+use super::{Tokenizer, UNK_TOKEN};
+
+pub const CLS_TOKEN: &str = "[CLS]";
+pub const SEP_TOKEN: &str = "[SEP]";
+
+fn is_cjk(c: char) -> bool {
+  matches!(c as u32, 0x4E00..=0x9FFF | 0x3400..=0x4DBF | 0x20000..=0x2A6DF | 0x2A700..=0x2CEAF | 0xF900..=0xFAFF | 0x2F800..=0x2FA1F)
+}
+
+fn is_punctuation(c: char) -> bool {
+  c.is_ascii_punctuation() || (!c.is_ascii() && !c.is_alphanumeric() && !c.is_whitespace() && !c.is_control())
+}
+
+pub(crate) fn read_lines<R: Read>(reader: R) -> Result<Vec<String>, String> {
+  BufReader::new(reader).lines().map(|line| line.map_err(|e| e.to_string())).collect()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WordPieceTokenizer {
+  pub lowercase: bool,
+  pub add_special_tokens: bool,
+  pub continuation_prefix: String,
+  pub max_word_chars: usize,
+  tokens: Vec<String>,
+  ids: HashMap<String, usize>
+}
+
+impl WordPieceTokenizer {
+  pub fn from_vocab(tokens: Vec<String>) -> Result<Self, String> {
+    let mut ids = HashMap::with_capacity(tokens.len());
+    for (id, token) in tokens.iter().enumerate() {
+      if ids.insert(token.clone(), id).is_some() {
+        return Err(format!("WordPieceTokenizer: duplicate token '{}'", token));
+      }
+    }
+    if !ids.contains_key(UNK_TOKEN) {
+      return Err(format!("WordPieceTokenizer: vocab has no {} token", UNK_TOKEN));
+    }
+    Ok(WordPieceTokenizer { lowercase: true, add_special_tokens: true, continuation_prefix: "##".to_string(), max_word_chars: 100, tokens, ids })
+  }
+
+  pub fn read_vocab<R: Read>(reader: R) -> Result<Self, String> {
+    Self::from_vocab(read_lines(reader)?.into_iter().map(|line| line.trim_end_matches('\r').to_string()).collect())
+  }
+
+  pub fn from_vocab_file<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+    let file = File::open(path.as_ref()).map_err(|e| format!("Cannot read {}: {}", path.as_ref().display(), e))?;
+    Self::read_vocab(file)
+  }
+
+  pub fn vocab_size(&self) -> usize {
+    self.tokens.len()
+  }
+
+  pub fn token_to_id(&self, token: &str) -> Option<usize> {
+    self.ids.get(token).copied()
+  }
+
+  pub fn id_to_token(&self, id: usize) -> Option<&str> {
+    self.tokens.get(id).map(String::as_str)
+  }
+
+  fn pre_tokenize(&self, text: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let text = if self.lowercase { text.to_lowercase() } else { text.to_string() };
+    for c in text.chars().filter(|&c| c != '\u{0}' && c != '\u{FFFD}' && (!c.is_control() || c.is_whitespace())) {
+      if c.is_whitespace() || is_punctuation(c) || is_cjk(c) {
+        if !current.is_empty() {
+          words.push(std::mem::take(&mut current));
+        }
+        if !c.is_whitespace() {
+          words.push(c.to_string());
+        }
+      } else {
+        current.push(c);
+      }
+    }
+    if !current.is_empty() {
+      words.push(current);
+    }
+    words
+  }
+
+  fn split_word(&self, word: &str) -> Option<Vec<String>> {
+    let chars: Vec<char> = word.chars().collect();
+    if chars.len() > self.max_word_chars {
+      return None;
+    }
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    while start < chars.len() {
+      let piece = (start + 1..=chars.len()).rev().find_map(|end| {
+        let mut piece: String = chars[start..end].iter().collect();
+        if start > 0 {
+          piece.insert_str(0, &self.continuation_prefix);
+        }
+        self.ids.contains_key(&piece).then_some((piece, end))
+      });
+      let (piece, end) = piece?;
+      pieces.push(piece);
+      start = end;
+    }
+    Some(pieces)
+  }
+
+  pub fn tokenize(&self, text: &str) -> Vec<String> {
+    let mut pieces = Vec::new();
+    for word in self.pre_tokenize(text) {
+      match self.split_word(&word) {
+        Some(split) => pieces.extend(split),
+        None => pieces.push(UNK_TOKEN.to_string())
+      }
+    }
+    if self.add_special_tokens && self.ids.contains_key(CLS_TOKEN) && self.ids.contains_key(SEP_TOKEN) {
+      pieces.insert(0, CLS_TOKEN.to_string());
+      pieces.push(SEP_TOKEN.to_string());
+    }
+    pieces
+  }
+
+  pub fn decode(&self, ids: &[usize]) -> Result<String, String> {
+    let mut text = String::new();
+    for &id in ids {
+      let token = self.id_to_token(id).ok_or_else(|| format!("WordPieceTokenizer: unknown token id {}", id))?;
+      if token.starts_with('[') && token.ends_with(']') && token != UNK_TOKEN {
+        continue;
+      }
+      match token.strip_prefix(self.continuation_prefix.as_str()) {
+        Some(rest) => text.push_str(rest),
+        None => {
+          if !text.is_empty() {
+            text.push(' ');
+          }
+          text.push_str(token);
+        }
+      }
+    }
+    Ok(text)
+  }
+}
+
+impl Tokenizer for WordPieceTokenizer {
+  fn encode(&self, text: &str) -> Result<Vec<usize>, String> {
+    Ok(self.tokenize(text).iter().map(|piece| self.ids[piece]).collect())
+  }
+}
@@ -0,0 +1,255 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+
+use super::EpochLogs;
+use crate::io::checkpoint::save_state_dict;
+use crate::io::npy::NpyElement;
+use crate::math::scalar::Float;
+use crate::nn::Module;
+use crate::optim::lr_scheduler::PlateauMode;
+use crate::tensor::Tensor;
+
+pub struct CallbackContext<'a, T: Float> {
+  pub model: &'a dyn Module<T>,
+  pub epoch: usize,
+  pub stop_training: bool
+}
+
+pub trait Callback<T: Float = f64> {
+  fn on_train_begin(&mut self, _ctx: &mut CallbackContext<'_, T>) -> Result<(), String> {
+    Ok(())
+  }
+
+  fn on_epoch_begin(&mut self, _ctx: &mut CallbackContext<'_, T>) -> Result<(), String> {
+    Ok(())
+  }
+
+  fn on_batch_end(&mut self, _ctx: &mut CallbackContext<'_, T>, _batch: usize, _loss: f64) -> Result<(), String> {
+    Ok(())
+  }
+
+  fn on_epoch_end(&mut self, _ctx: &mut CallbackContext<'_, T>, _logs: &EpochLogs) -> Result<(), String> {
+    Ok(())
+  }
+
+  fn on_train_end(&mut self, _ctx: &mut CallbackContext<'_, T>) -> Result<(), String> {
+    Ok(())
+  }
+}
+
+impl<T: Float, C: Callback<T> + ?Sized> Callback<T> for &mut C {
+  fn on_train_begin(&mut self, ctx: &mut CallbackContext<'_, T>) -> Result<(), String> {
+    (**self).on_train_begin(ctx)
+  }
+
+  fn on_epoch_begin(&mut self, ctx: &mut CallbackContext<'_, T>) -> Result<(), String> {
+    (**self).on_epoch_begin(ctx)
+  }
+
+  fn on_batch_end(&mut self, ctx: &mut CallbackContext<'_, T>, batch: usize, loss: f64) -> Result<(), String> {
+    (**self).on_batch_end(ctx, batch, loss)
+  }
+
+  fn on_epoch_end(&mut self, ctx: &mut CallbackContext<'_, T>, logs: &EpochLogs) -> Result<(), String> {
+    (**self).on_epoch_end(ctx, logs)
+  }
+
+  fn on_train_end(&mut self, ctx: &mut CallbackContext<'_, T>) -> Result<(), String> {
+    (**self).on_train_end(ctx)
+  }
+}
+
+fn monitored(logs: &EpochLogs, monitor: &str) -> Result<f64, String> {
+  logs.get(monitor).ok_or_else(|| {
+    let available: Vec<&str> = logs.values().iter().map(|(name, _)| name.as_str()).collect();
+    format!("Monitored metric '{}' is not in the epoch logs (available: {})", monitor, available.join(", "))
+  })
+}
+
+fn improved(mode: PlateauMode, value: f64, best: Option<f64>, min_delta: f64) -> bool {
+  match (mode, best) {
+    (_, None) => !value.is_nan(),
+    (PlateauMode::Min, Some(best)) => value < best - min_delta,
+    (PlateauMode::Max, Some(best)) => value > best + min_delta
+  }
+}
+
+pub struct EarlyStopping<T: Float = f64> {
+  pub monitor: String,
+  pub mode: PlateauMode,
+  pub patience: usize,
+  pub min_delta: f64,
+  pub restore_best_weights: bool,
+  best: Option<f64>,
+  wait: usize,
+  stopped_epoch: Option<usize>,
+  best_weights: Option<Vec<Tensor<T>>>
+}
+
+impl<T: Float> EarlyStopping<T> {
+  pub fn new(monitor: &str, patience: usize) -> Self {
+    EarlyStopping {
+      monitor: monitor.to_string(),
+      mode: PlateauMode::Min,
+      patience,
+      min_delta: 0.0,
+      restore_best_weights: false,
+      best: None,
+      wait: 0,
+      stopped_epoch: None,
+      best_weights: None
+    }
+  }
+
+  pub fn best(&self) -> Option<f64> {
+    self.best
+  }
+
+  pub fn stopped_epoch(&self) -> Option<usize> {
+    self.stopped_epoch
+  }
+}
+
+impl<T: Float> Callback<T> for EarlyStopping<T> {
+  fn on_train_begin(&mut self, _ctx: &mut CallbackContext<'_, T>) -> Result<(), String> {
+    self.best = None;
+    self.wait = 0;
+    self.stopped_epoch = None;
+    self.best_weights = None;
+    Ok(())
+  }
+
+  fn on_epoch_end(&mut self, ctx: &mut CallbackContext<'_, T>, logs: &EpochLogs) -> Result<(), String> {
+    let value = monitored(logs, &self.monitor)?;
+    if improved(self.mode, value, self.best, self.min_delta) {
+      self.best = Some(value);
+      self.wait = 0;
+      if self.restore_best_weights {
+        self.best_weights = Some(ctx.model.parameters().iter().map(|param| param.value()).collect());
+      }
+      return Ok(());
+    }
+
+    self.wait += 1;
+    if self.wait >= self.patience {
+      self.stopped_epoch = Some(ctx.epoch);
+      ctx.stop_training = true;
+      if let Some(weights) = &self.best_weights {
+        for (param, weight) in ctx.model.parameters().iter().zip(weights) {
+          param.set_value(weight.clone())?;
+        }
+      }
+    }
+    Ok(())
+  }
+}
+
+pub struct ModelCheckpoint {
+  pub path: PathBuf,
+  pub monitor: String,
+  pub mode: PlateauMode,
+  pub save_best_only: bool,
+  best: Option<f64>
+}
+
+impl ModelCheckpoint {
+  pub fn new<P: Into<PathBuf>>(path: P, monitor: &str) -> Self {
+    ModelCheckpoint {
+      path: path.into(),
+      monitor: monitor.to_string(),
+      mode: PlateauMode::Min,
+      save_best_only: true,
+      best: None
+    }
+  }
+
+  pub fn best(&self) -> Option<f64> {
+    self.best
+  }
+}
+
+impl<T: Float + NpyElement> Callback<T> for ModelCheckpoint {
+  fn on_epoch_end(&mut self, ctx: &mut CallbackContext<'_, T>, logs: &EpochLogs) -> Result<(), String> {
+    let value = monitored(logs, &self.monitor)?;
+    let is_best = improved(self.mode, value, self.best, 0.0);
+    if is_best {
+      self.best = Some(value);
+    }
+    if !is_best && self.save_best_only {
+      return Ok(());
+    }
+
+    save_state_dict(ctx.model, &self.path)
+  }
+}
+
+pub struct CsvLogger {
+  pub path: PathBuf,
+  writer: Option<BufWriter<File>>,
+  columns: Vec<String>
+}
+
+impl CsvLogger {
+  pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+    CsvLogger { path: path.into(), writer: None, columns: Vec::new() }
+  }
+}
+
+impl<T: Float> Callback<T> for CsvLogger {
+  fn on_train_begin(&mut self, _ctx: &mut CallbackContext<'_, T>) -> Result<(), String> {
+    let file = File::create(&self.path).map_err(|e| format!("Cannot create {}: {}", self.path.display(), e))?;
+    self.writer = Some(BufWriter::new(file));
+    self.columns.clear();
+    Ok(())
+  }
+
+  fn on_epoch_end(&mut self, _ctx: &mut CallbackContext<'_, T>, logs: &EpochLogs) -> Result<(), String> {
+    let writer = self.writer.as_mut().ok_or("CsvLogger: on_train_begin was not called")?;
+    if self.columns.is_empty() {
+      self.columns = logs.values().iter().map(|(name, _)| name.clone()).collect();
+      writeln!(writer, "epoch,{}", self.columns.join(",")).map_err(|e| e.to_string())?;
+    }
+
+    let row: Vec<String> = self.columns.iter().map(|name| logs.get(name).map(|v| v.to_string()).unwrap_or_default()).collect();
+    writeln!(writer, "{},{}", logs.epoch, row.join(",")).map_err(|e| e.to_string())?;
+    writer.flush().map_err(|e| e.to_string())
+  }
+
+  fn on_train_end(&mut self, _ctx: &mut CallbackContext<'_, T>) -> Result<(), String> {
+    if let Some(mut writer) = self.writer.take() {
+      writer.flush().map_err(|e| e.to_string())?;
+    }
+    Ok(())
+  }
+}
+
+pub struct ConsoleLogger {
+  pub every: usize
+}
+
+impl ConsoleLogger {
+  pub fn new() -> Self {
+    ConsoleLogger { every: 1 }
+  }
+
+  pub fn format(logs: &EpochLogs) -> String {
+    let values: Vec<String> = logs.values().iter().map(|(name, value)| format!("{}={:.6}", name, value)).collect();
+    format!("epoch {}: {}", logs.epoch, values.join(" "))
+  }
+}
+
+impl Default for ConsoleLogger {
+  fn default() -> Self {
+    ConsoleLogger::new()
+  }
+}
+
+impl<T: Float> Callback<T> for ConsoleLogger {
+  fn on_epoch_end(&mut self, _ctx: &mut CallbackContext<'_, T>, logs: &EpochLogs) -> Result<(), String> {
+    if self.every > 0 && logs.epoch.is_multiple_of(self.every) {
+      println!("{}", ConsoleLogger::format(logs));
+    }
+    Ok(())
+  }
+}
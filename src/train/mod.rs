@@ -0,0 +1,367 @@
+use std::sync::Arc;
+
+use crate::autograd::scaler::all_finite;
+use crate::autograd::{autocast, no_grad, HalfPrecision, LossScaler, Var};
+use crate::math::scalar::Float;
+use crate::metrics::{Average, MeanMetric, Meter};
+use crate::nn::Module;
+use crate::optim::Optimizer;
+use crate::tensor::device::Device;
+use crate::tensor::Tensor;
+
+pub mod callbacks;
+mod parallel;
+
+pub use callbacks::{Callback, CallbackContext, ConsoleLogger, CsvLogger, EarlyStopping, ModelCheckpoint};
+
+use parallel::{parallel_step, ParallelStep};
+
+pub type Batch<T> = (Tensor<T>, Tensor<T>);
+pub type LossFn<T> = Arc<dyn Fn(&Var<T>, &Tensor<T>) -> Result<Var<T>, String> + Send + Sync>;
+
+pub trait BatchSource<T: Float> {
+  fn batches(&self) -> Box<dyn Iterator<Item = Result<Batch<T>, String>> + '_>;
+}
+
+impl<T: Float> BatchSource<T> for [Batch<T>] {
+  fn batches(&self) -> Box<dyn Iterator<Item = Result<Batch<T>, String>> + '_> {
+    Box::new(self.iter().cloned().map(Ok))
+  }
+}
+
+impl<T: Float> BatchSource<T> for Vec<Batch<T>> {
+  fn batches(&self) -> Box<dyn Iterator<Item = Result<Batch<T>, String>> + '_> {
+    self.as_slice().batches()
+  }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EpochLogs {
+  pub epoch: usize,
+  values: Vec<(String, f64)>
+}
+
+impl EpochLogs {
+  pub fn new(epoch: usize) -> Self {
+    EpochLogs { epoch, values: Vec::new() }
+  }
+
+  pub fn get(&self, name: &str) -> Option<f64> {
+    self.values.iter().find(|(key, _)| key == name).map(|&(_, value)| value)
+  }
+
+  pub fn insert(&mut self, name: &str, value: f64) {
+    match self.values.iter_mut().find(|(key, _)| key == name) {
+      Some(entry) => entry.1 = value,
+      None => self.values.push((name.to_string(), value))
+    }
+  }
+
+  pub fn values(&self) -> &[(String, f64)] {
+    &self.values
+  }
+}
+
+struct MixedPrecision {
+  precision: HalfPrecision,
+  scaler: LossScaler,
+  skipped_steps: usize
+}
+
+pub struct Trainer<'a, T: Float = f64> {
+  model: &'a dyn Module<T>,
+  optimizer: &'a mut dyn Optimizer<T>,
+  loss: LossFn<T>,
+  meters: Vec<Box<dyn Meter<T> + 'a>>,
+  callbacks: Vec<Box<dyn Callback<T> + 'a>>,
+  parallel: Option<ParallelStep<'a, T>>,
+  accumulation_steps: usize,
+  mixed_precision: Option<MixedPrecision>,
+  device: Device,
+  history: Vec<EpochLogs>
+}
+
+impl<'a, T: Float> Trainer<'a, T> {
+  pub fn new<L>(model: &'a dyn Module<T>, optimizer: &'a mut dyn Optimizer<T>, loss: L) -> Self
+  where
+    L: Fn(&Var<T>, &Tensor<T>) -> Result<Var<T>, String> + Send + Sync + 'static
+  {
+    Trainer {
+      model,
+      optimizer,
+      loss: Arc::new(loss),
+      meters: Vec::new(),
+      callbacks: Vec::new(),
+      parallel: None,
+      accumulation_steps: 1,
+      mixed_precision: None,
+      device: Device::Cpu,
+      history: Vec::new()
+    }
+  }
+
+  pub fn add_metric<M>(&mut self, name: &str, metric: M)
+  where
+    M: Fn(&Tensor<T>, &Tensor<T>) -> Result<f64, String> + 'static
+  {
+    self.add_meter(MeanMetric::new(name, metric));
+  }
+
+  pub fn add_meter<M>(&mut self, meter: M)
+  where
+    M: Meter<T> + 'a
+  {
+    self.meters.push(Box::new(meter));
+  }
+
+  pub fn meters(&self) -> &[Box<dyn Meter<T> + 'a>] {
+    &self.meters
+  }
+
+  pub fn add_callback<C>(&mut self, callback: C)
+  where
+    C: Callback<T> + 'a
+  {
+    self.callbacks.push(Box::new(callback));
+  }
+
+  pub fn data_parallel<F, M>(&mut self, workers: usize, factory: F) -> Result<(), String>
+  where
+    T: Send + Sync,
+    F: Fn() -> M + Send + Sync + 'a,
+    M: Module<T>
+  {
+    if workers == 0 {
+      return Err("Trainer: data-parallel training needs at least one worker".to_string());
+    }
+
+    let loss = Arc::clone(&self.loss);
+    self.parallel = Some(Box::new(move |state, features, targets| {
+      parallel_step(workers, &factory, &*loss, state, features, targets)
+    }));
+    Ok(())
+  }
+
+  pub fn single_device(&mut self) {
+    self.parallel = None;
+  }
+
+  pub fn is_data_parallel(&self) -> bool {
+    self.parallel.is_some()
+  }
+
+  pub fn accumulate_gradients(&mut self, steps: usize) -> Result<(), String> {
+    if steps == 0 {
+      return Err("Trainer: gradient accumulation needs at least one micro-batch per step".to_string());
+    }
+    self.accumulation_steps = steps;
+    Ok(())
+  }
+
+  pub fn accumulation_steps(&self) -> usize {
+    self.accumulation_steps
+  }
+
+  pub fn mixed_precision(&mut self, precision: HalfPrecision, scaler: LossScaler) {
+    self.mixed_precision = Some(MixedPrecision { precision, scaler, skipped_steps: 0 });
+  }
+
+  pub fn full_precision(&mut self) {
+    self.mixed_precision = None;
+  }
+
+  pub fn precision(&self) -> Option<HalfPrecision> {
+    self.mixed_precision.as_ref().map(|amp| amp.precision)
+  }
+
+  pub fn loss_scaler(&self) -> Option<&LossScaler> {
+    self.mixed_precision.as_ref().map(|amp| &amp.scaler)
+  }
+
+  pub fn skipped_steps(&self) -> usize {
+    self.mixed_precision.as_ref().map_or(0, |amp| amp.skipped_steps)
+  }
+
+  pub fn device(&self) -> Device {
+    self.device
+  }
+
+  pub fn to(&mut self, device: Device) -> Result<(), String> {
+    for param in self.model.parameters() {
+      param.set_value(param.value().to(device)?)?;
+    }
+    self.device = device;
+    Ok(())
+  }
+
+  pub fn history(&self) -> &[EpochLogs] {
+    &self.history
+  }
+
+  pub fn metric_history(&self, name: &str) -> Vec<f64> {
+    self.history.iter().filter_map(|logs| logs.get(name)).collect()
+  }
+
+  pub fn optimizer(&mut self) -> &mut dyn Optimizer<T> {
+    &mut *self.optimizer
+  }
+
+  fn context(&self) -> CallbackContext<'a, T> {
+    CallbackContext { model: self.model, epoch: self.history.len(), stop_training: false }
+  }
+
+  fn step_accumulated(&mut self, samples: usize) -> Result<(), String> {
+    let params = self.model.parameters();
+    let mut scale = if self.accumulation_steps > 1 { samples as f64 } else { 1.0 };
+    if let Some(amp) = &mut self.mixed_precision {
+      let overflow = params.iter().filter_map(|param| param.grad()).any(|grad| !all_finite(&grad));
+      scale *= amp.scaler.scale();
+      amp.scaler.update(overflow);
+      if overflow {
+        amp.skipped_steps += 1;
+        return Ok(());
+      }
+    }
+
+    if scale != 1.0 {
+      let inv = T::from_f64(1.0 / scale);
+      for param in &params {
+        if let Some(grad) = param.grad() {
+          param.set_grad(Some(grad.map(|&g| g * inv)))?;
+        }
+      }
+    }
+    self.optimizer.step()
+  }
+
+  fn run_epoch(&mut self, data: &dyn BatchSource<T>, train: bool, ctx: &mut CallbackContext<'a, T>) -> Result<EpochLogs, String> {
+    let mut loss_average = Average::new();
+    let (mut pending, mut pending_samples) = (0, 0);
+    let precision = self.precision();
+    if train && precision.is_some() && self.parallel.is_some() {
+      return Err("Trainer: mixed precision is not supported with data-parallel training".to_string());
+    }
+    self.meters.iter_mut().for_each(|meter| meter.reset());
+    for (index, batch) in data.batches().enumerate() {
+      let (features, targets) = batch?;
+      let (features, targets) = (features.to(self.device)?, targets.to(self.device)?);
+      let count = features.shape().first().copied().unwrap_or(1);
+      let weight = T::from_f64(if self.accumulation_steps > 1 { count as f64 } else { 1.0 });
+      if train && pending == 0 {
+        self.optimizer.zero_grad();
+      }
+
+      let (output, loss) = if let (true, Some(step)) = (train, &self.parallel) {
+        let result = step(&self.model.state_dict(), &features, &targets)?;
+        for (param, grad) in self.model.parameters().iter().zip(result.grads) {
+          let grad = match (grad, param.grad()) {
+            (Some(grad), Some(acc)) => acc.zip_map(&grad, |&a, &g| a + g * weight)?,
+            (Some(grad), None) => grad.map(|&g| g * weight),
+            (None, _) => continue
+          };
+          param.set_grad(Some(grad))?;
+        }
+        for ((_, buffer), value) in self.model.named_buffers().iter().zip(result.buffers) {
+          buffer.set_value(value)?;
+        }
+        (result.output, Tensor::scalar(T::from_f64(result.loss)))
+      } else if train {
+        let output = autocast(precision, || self.model.forward(&Var::constant(features)))?;
+        let loss = (self.loss)(&output, &targets)?;
+        let scale = self.loss_scaler().map_or(1.0, |scaler| scaler.scale());
+        loss.mul_scalar(weight * T::from_f64(scale)).backward()?;
+        (output.value(), loss.value())
+      } else {
+        no_grad(|| {
+          let output = autocast(precision, || self.model.forward(&Var::constant(features)))?;
+          let loss = (self.loss)(&output, &targets)?;
+          Ok::<_, String>((output.value(), loss.value()))
+        })?
+      };
+
+      if train {
+        pending += 1;
+        pending_samples += count;
+        if pending == self.accumulation_steps {
+          self.step_accumulated(pending_samples)?;
+          (pending, pending_samples) = (0, 0);
+        }
+      }
+
+      let loss = loss.iter().map(|x| x.to_f64()).sum();
+      loss_average.add(loss, count as f64);
+      for meter in &mut self.meters {
+        meter.update(&output, &targets)?;
+      }
+
+      if train {
+        for callback in &mut self.callbacks {
+          callback.on_batch_end(ctx, index, loss)?;
+        }
+      }
+    }
+
+    if pending > 0 {
+      self.step_accumulated(pending_samples)?;
+    }
+    if loss_average.weight() == 0.0 {
+      return Err("Trainer: the data source produced no samples".to_string());
+    }
+
+    let mut logs = EpochLogs::new(self.history.len());
+    logs.insert("loss", loss_average.value());
+    for meter in &self.meters {
+      logs.insert(meter.name(), meter.value());
+    }
+    Ok(logs)
+  }
+
+  pub fn train_epoch(&mut self, data: &dyn BatchSource<T>) -> Result<EpochLogs, String> {
+    let mut ctx = self.context();
+    self.model.train();
+    self.run_epoch(data, true, &mut ctx)
+  }
+
+  pub fn evaluate(&mut self, data: &dyn BatchSource<T>) -> Result<EpochLogs, String> {
+    let mut ctx = self.context();
+    self.model.eval();
+    self.run_epoch(data, false, &mut ctx)
+  }
+
+  pub fn fit(&mut self, train: &dyn BatchSource<T>, validation: Option<&dyn BatchSource<T>>, epochs: usize) -> Result<&[EpochLogs], String> {
+    let mut ctx = self.context();
+    for callback in &mut self.callbacks {
+      callback.on_train_begin(&mut ctx)?;
+    }
+
+    for _ in 0..epochs {
+      ctx.epoch = self.history.len();
+      for callback in &mut self.callbacks {
+        callback.on_epoch_begin(&mut ctx)?;
+      }
+
+      self.model.train();
+      let mut logs = self.run_epoch(train, true, &mut ctx)?;
+      if let Some(validation) = validation {
+        self.model.eval();
+        for (name, value) in self.run_epoch(validation, false, &mut ctx)?.values() {
+          logs.insert(&format!("val_{}", name), *value);
+        }
+      }
+
+      for callback in &mut self.callbacks {
+        callback.on_epoch_end(&mut ctx, &logs)?;
+      }
+      self.history.push(logs);
+      if ctx.stop_training {
+        break;
+      }
+    }
+
+    for callback in &mut self.callbacks {
+      callback.on_train_end(&mut ctx)?;
+    }
+    self.model.train();
+    Ok(&self.history)
+  }
+}
@@ -0,0 +1,146 @@
+use std::thread;
+
+use crate::autograd::Var;
+use crate::math::scalar::Float;
+use crate::nn::Module;
+use crate::random::{is_deterministic, with_rng, with_seed};
+use crate::tensor::Tensor;
+
+pub(crate) type ParallelStep<'a, T> = Box<dyn Fn(&[(String, Tensor<T>)], &Tensor<T>, &Tensor<T>) -> Result<StepResult<T>, String> + 'a>;
+
+pub(crate) struct StepResult<T> {
+  pub loss: f64,
+  pub output: Tensor<T>,
+  pub grads: Vec<Option<Tensor<T>>>,
+  pub buffers: Vec<Tensor<T>>
+}
+
+struct ShardResult<T> {
+  size: usize,
+  loss: f64,
+  output: Tensor<T>,
+  grads: Vec<Option<Tensor<T>>>,
+  buffers: Vec<Tensor<T>>
+}
+
+fn shard_sizes(batch: usize, workers: usize) -> Vec<usize> {
+  let shards = workers.min(batch).max(1);
+  (0..shards).map(|i| batch / shards + usize::from(i < batch % shards)).collect()
+}
+
+fn run_shard<T, M, F, L>(
+  factory: &F,
+  loss_fn: &L,
+  state: &[(String, Tensor<T>)],
+  features: Tensor<T>,
+  targets: Tensor<T>,
+  seed: u64
+) -> Result<ShardResult<T>, String>
+where
+  T: Float,
+  M: Module<T>,
+  F: Fn() -> M,
+  L: Fn(&Var<T>, &Tensor<T>) -> Result<Var<T>, String> + ?Sized
+{
+  with_seed(seed, || {
+    let size = features.shape()[0];
+    let replica = factory();
+    replica.load_state_dict(state, true)?;
+    replica.train();
+
+    let output = replica.forward(&Var::constant(features))?;
+    let loss = loss_fn(&output, &targets)?;
+    loss.backward()?;
+
+    Ok(ShardResult {
+      size,
+      loss: loss.value().iter().map(|x| x.to_f64()).sum(),
+      output: output.value(),
+      grads: replica.parameters().iter().map(|param| param.grad()).collect(),
+      buffers: replica.named_buffers().iter().map(|(_, buffer)| buffer.value()).collect()
+    })
+  })
+}
+
+fn weighted_sum<T: Float>(acc: Option<Tensor<T>>, value: &Tensor<T>, weight: T) -> Result<Tensor<T>, String> {
+  match acc {
+    Some(acc) => acc.zip_map(value, |&a, &v| a + v * weight),
+    None => Ok(value.map(|&v| v * weight))
+  }
+}
+
+pub(crate) fn parallel_step<T, M, F, L>(
+  workers: usize,
+  factory: &F,
+  loss_fn: &L,
+  state: &[(String, Tensor<T>)],
+  features: &Tensor<T>,
+  targets: &Tensor<T>
+) -> Result<StepResult<T>, String>
+where
+  T: Float + Send + Sync,
+  M: Module<T>,
+  F: Fn() -> M + Sync,
+  L: Fn(&Var<T>, &Tensor<T>) -> Result<Var<T>, String> + Sync + ?Sized
+{
+  let batch = features.shape().first().copied().unwrap_or(0);
+  if batch == 0 || targets.shape().first() != Some(&batch) {
+    return Err(format!(
+      "Data-parallel training needs features and targets with a shared batch axis, found {:?} and {:?}",
+      features.shape(),
+      targets.shape()
+    ));
+  }
+
+  let mut shards = Vec::new();
+  let mut start = 0;
+  for size in shard_sizes(batch, workers) {
+    shards.push((features.narrow(0, start, size)?, targets.narrow(0, start, size)?));
+    start += size;
+  }
+
+  let seeds: Vec<u64> = with_rng(|rng| shards.iter().map(|_| rng.next_u64()).collect());
+  let results = if is_deterministic() {
+    shards
+      .into_iter()
+      .zip(seeds)
+      .map(|((x, y), seed)| run_shard(factory, loss_fn, state, x, y, seed))
+      .collect::<Result<Vec<_>, String>>()?
+  } else {
+    thread::scope(|scope| {
+      let handles: Vec<_> = shards
+        .into_iter()
+        .zip(seeds)
+        .map(|((x, y), seed)| scope.spawn(move || run_shard(factory, loss_fn, state, x, y, seed)))
+        .collect();
+      handles
+        .into_iter()
+        .map(|handle| handle.join().map_err(|_| "Data-parallel worker panicked".to_string())?)
+        .collect::<Result<Vec<_>, String>>()
+    })?
+  };
+
+  let mut loss = 0.0;
+  let mut grads: Vec<Option<Tensor<T>>> = vec![None; results[0].grads.len()];
+  let mut buffers: Vec<Option<Tensor<T>>> = vec![None; results[0].buffers.len()];
+  for result in &results {
+    let share = result.size as f64 / batch as f64;
+    loss += result.loss * share;
+    for (acc, grad) in grads.iter_mut().zip(&result.grads) {
+      if let Some(grad) = grad {
+        *acc = Some(weighted_sum(acc.take(), grad, T::from_f64(share))?);
+      }
+    }
+    for (acc, buffer) in buffers.iter_mut().zip(&result.buffers) {
+      *acc = Some(weighted_sum(acc.take(), buffer, T::from_f64(share))?);
+    }
+  }
+
+  let outputs: Vec<&Tensor<T>> = results.iter().map(|result| &result.output).collect();
+  Ok(StepResult {
+    loss,
+    output: Tensor::cat(&outputs, 0)?,
+    grads,
+    buffers: buffers.into_iter().flatten().collect()
+  })
+}